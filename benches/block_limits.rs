@@ -6,15 +6,16 @@ use blockstack_lib::{
     burnchains::BurnchainHeaderHash,
     chainstate::{
         self,
-        burn::BlockHeaderHash,
+        burn::{BlockHeaderHash, ConsensusHash},
         stacks::{index::MarfTrieId, StacksBlockId},
     },
     vm::clarity::ClarityInstance,
-    vm::costs::ExecutionCost,
+    vm::costs::profiler::ProfileMetric,
+    vm::costs::{ExecutionCost, LimitedCostTracker},
     vm::database::MarfedKV,
     vm::{
         database::{HeadersDB, NULL_BURN_STATE_DB},
-        types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
+        types::{BuffData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
         Value,
     },
 };
@@ -68,6 +69,34 @@ impl HeadersDB for TestHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+
+    fn get_consensus_hash_for_block(&self, _id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        None
+    }
+}
+
+/// Where to report the cost profile of a test's last block
+struct Profiling {
+    folded_path: Option<String>,
+}
+
+fn report_profile(cost_tracker: &LimitedCostTracker, profiling: Option<&Profiling>) {
+    let (profile, profiling) = match (cost_tracker.get_profile(), profiling) {
+        (Some(profile), Some(profiling)) => (profile, profiling),
+        _ => return,
+    };
+
+    let mut table = vec![];
+    profile.write_table(&mut table).unwrap();
+    print!("{}", String::from_utf8_lossy(&table));
+
+    if let Some(ref folded_path) = profiling.folded_path {
+        let mut folded = vec![];
+        profile
+            .write_folded(&mut folded, ProfileMetric::Runtime)
+            .unwrap();
+        fs::write(folded_path, &folded).unwrap();
+    }
 }
 
 fn as_hash160(inp: u32) -> [u8; 20] {
@@ -82,7 +111,12 @@ fn as_hash(inp: u32) -> [u8; 32] {
     out
 }
 
-fn transfer_test(buildup_count: u32, scaling: u32, genesis_size: u32) -> ExecutionCost {
+fn transfer_test(
+    buildup_count: u32,
+    scaling: u32,
+    genesis_size: u32,
+    profiling: Option<&Profiling>,
+) -> ExecutionCost {
     let start = Instant::now();
 
     let marf = setup_chain_state(genesis_size);
@@ -135,6 +169,10 @@ fn transfer_test(buildup_count: u32, scaling: u32, genesis_size: u32) -> Executi
         &NULL_BURN_STATE_DB,
     );
 
+    if profiling.is_some() {
+        conn.enable_profiling();
+    }
+
     let begin = Instant::now();
 
     let mut rng = rand::thread_rng();
@@ -143,12 +181,14 @@ fn transfer_test(buildup_count: u32, scaling: u32, genesis_size: u32) -> Executi
         let to = (from + rng.gen_range(1, principals.len())) % principals.len();
 
         conn.as_transaction(|tx| {
-            tx.run_stx_transfer(&principals[from], &principals[to], 10)
+            tx.run_stx_transfer(&principals[from], &principals[to], 10, &BuffData::empty())
                 .unwrap()
         });
     }
 
-    let this_cost = conn.commit_to_block(&blocks[last_block]).get_total();
+    let cost_tracker = conn.commit_to_block(&blocks[last_block]);
+    report_profile(&cost_tracker, profiling);
+    let this_cost = cost_tracker.get_total();
     let elapsed = begin.elapsed();
 
     println!(
@@ -206,6 +246,7 @@ fn test_via_raw_contract(
     scaling: u32,
     buildup_count: u32,
     genesis_size: u32,
+    profiling: Option<&Profiling>,
 ) -> ExecutionCost {
     let start = Instant::now();
 
@@ -266,6 +307,10 @@ fn test_via_raw_contract(
         &NULL_BURN_STATE_DB,
     );
 
+    if profiling.is_some() {
+        conn.enable_profiling();
+    }
+
     let begin = Instant::now();
 
     let exec_cost = conn.as_transaction(|tx| {
@@ -285,7 +330,8 @@ fn test_via_raw_contract(
         initialize_cost
     });
 
-    let _this_cost = conn.commit_to_block(&blocks[last_block]).get_total();
+    let cost_tracker = conn.commit_to_block(&blocks[last_block]);
+    report_profile(&cost_tracker, profiling);
     let elapsed = begin.elapsed();
 
     println!(
@@ -299,7 +345,12 @@ fn test_via_raw_contract(
     exec_cost
 }
 
-fn smart_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32) -> ExecutionCost {
+fn smart_contract_test(
+    scaling: u32,
+    buildup_count: u32,
+    genesis_size: u32,
+    profiling: Option<&Profiling>,
+) -> ExecutionCost {
     let start = Instant::now();
 
     let marf = setup_chain_state(genesis_size);
@@ -359,6 +410,10 @@ fn smart_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32) -> E
         &NULL_BURN_STATE_DB,
     );
 
+    if profiling.is_some() {
+        conn.enable_profiling();
+    }
+
     let begin = Instant::now();
 
     conn.as_transaction(|tx| {
@@ -372,7 +427,9 @@ fn smart_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32) -> E
             .expect("FATAL: failed to store contract analysis");
     });
 
-    let this_cost = conn.commit_to_block(&blocks[last_block]).get_total();
+    let cost_tracker = conn.commit_to_block(&blocks[last_block]);
+    report_profile(&cost_tracker, profiling);
+    let this_cost = cost_tracker.get_total();
     let elapsed = begin.elapsed();
 
     println!(
@@ -386,7 +443,12 @@ fn smart_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32) -> E
     this_cost
 }
 
-fn expensive_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32) -> ExecutionCost {
+fn expensive_contract_test(
+    scaling: u32,
+    buildup_count: u32,
+    genesis_size: u32,
+    profiling: Option<&Profiling>,
+) -> ExecutionCost {
     let start = Instant::now();
 
     let marf = setup_chain_state(genesis_size);
@@ -454,6 +516,10 @@ fn expensive_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32)
         &NULL_BURN_STATE_DB,
     );
 
+    if profiling.is_some() {
+        conn.enable_profiling();
+    }
+
     let begin = Instant::now();
 
     conn.as_transaction(|tx| {
@@ -467,7 +533,9 @@ fn expensive_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32)
             .expect("FATAL: failed to store contract analysis");
     });
 
-    let this_cost = conn.commit_to_block(&blocks[last_block]).get_total();
+    let cost_tracker = conn.commit_to_block(&blocks[last_block]);
+    report_profile(&cost_tracker, profiling);
+    let this_cost = cost_tracker.get_total();
     let elapsed = begin.elapsed();
 
     println!(
@@ -481,7 +549,12 @@ fn expensive_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32)
     this_cost
 }
 
-fn stack_stx_test(buildup_count: u32, genesis_size: u32, scaling: u32) -> ExecutionCost {
+fn stack_stx_test(
+    buildup_count: u32,
+    genesis_size: u32,
+    scaling: u32,
+    profiling: Option<&Profiling>,
+) -> ExecutionCost {
     let start = Instant::now();
     let marf = setup_chain_state(genesis_size);
 
@@ -550,6 +623,10 @@ fn stack_stx_test(buildup_count: u32, genesis_size: u32, scaling: u32) -> Execut
         &NULL_BURN_STATE_DB,
     );
 
+    if profiling.is_some() {
+        conn.enable_profiling();
+    }
+
     let begin = Instant::now();
 
     conn.as_transaction(|tx| {
@@ -575,7 +652,9 @@ fn stack_stx_test(buildup_count: u32, genesis_size: u32, scaling: u32) -> Execut
         }
     });
 
-    let this_cost = conn.commit_to_block(&blocks[last_block]).get_total();
+    let cost_tracker = conn.commit_to_block(&blocks[last_block]);
+    report_profile(&cost_tracker, profiling);
+    let this_cost = cost_tracker.get_total();
     let elapsed = begin.elapsed();
 
     println!(
@@ -592,9 +671,26 @@ fn stack_stx_test(buildup_count: u32, genesis_size: u32, scaling: u32) -> Execut
 fn main() {
     let argv: Vec<_> = env::args().collect();
 
+    // profile the last block's costs with `--profile`, and write its folded stacks to <path>
+    // with `--profile=<path>`
+    let profiling = argv
+        .iter()
+        .find(|arg| *arg == "--profile" || arg.starts_with("--profile="))
+        .map(|arg| Profiling {
+            folded_path: if arg.starts_with("--profile=") {
+                Some(arg["--profile=".len()..].to_string())
+            } else {
+                None
+            },
+        });
+    let argv: Vec<_> = argv
+        .into_iter()
+        .filter(|arg| *arg != "--profile" && !arg.starts_with("--profile="))
+        .collect();
+
     if argv.len() < 3 {
         eprintln!(
-            "Usage: {} [test-name] [scalar-0] ... [scalar-n]
+            "Usage: {} [test-name] [scalar-0] ... [scalar-n] [--profile[=<folded-stacks-path>]]
 
 transfer <block_build_up> <genesis_size> <number_of_ops>
 smart-contract <block_build_up> <genesis_size> <number_of_ops>
@@ -613,16 +709,16 @@ clarity-raw  <block_build_up> <genesis_size> <number_of_ops> <eval-block>
     let scaling = argv[4].parse().expect("Invalid scalar");
 
     let result = match argv[1].as_str() {
-        "transfer" => transfer_test(block_build_up, scaling, genesis_size),
-        "smart-contract" => smart_contract_test(scaling, block_build_up, genesis_size),
-        "expensive-contract" => expensive_contract_test(scaling, block_build_up, genesis_size),
+        "transfer" => transfer_test(block_build_up, scaling, genesis_size, profiling.as_ref()),
+        "smart-contract" => smart_contract_test(scaling, block_build_up, genesis_size, profiling.as_ref()),
+        "expensive-contract" => expensive_contract_test(scaling, block_build_up, genesis_size, profiling.as_ref()),
         "clarity-transfer" => test_via_raw_contract("(stx-transfer? u1 tx-sender 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
-                                                    scaling, block_build_up, genesis_size),
+                                                    scaling, block_build_up, genesis_size, profiling.as_ref()),
         "clarity-verify" => test_via_raw_contract("(secp256k1-verify 0xde5b9eb9e7c5592930eb2e30a01369c36586d872082ed8181ee83d2a0ec20f04
  0x8738487ebe69b93d8e51583be8eee50bb4213fc49c767d329632730cc193b873554428fc936ca3569afc15f1c9365f6591d6251a89fee9c9ac661116824d3a1301
  0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110)",
-                                                  scaling, block_build_up, genesis_size),
-        "stack-stx" => stack_stx_test(block_build_up, genesis_size, scaling),
+                                                  scaling, block_build_up, genesis_size, profiling.as_ref()),
+        "stack-stx" => stack_stx_test(block_build_up, genesis_size, scaling, profiling.as_ref()),
         _ => {
             eprintln!("bad test name");
             process::exit(1);
@@ -20,17 +20,24 @@
 #![allow(non_upper_case_globals)]
 
 extern crate blockstack_lib;
+#[macro_use]
+extern crate serde_json;
 
 use blockstack_lib::address::AddressHashMode;
 use blockstack_lib::burnchains::Address;
+use blockstack_lib::burnchains::Txid;
 use blockstack_lib::chainstate::stacks::{
-    StacksAddress, StacksBlock, StacksMicroblock, StacksPrivateKey, StacksPublicKey,
-    StacksTransaction, StacksTransactionSigner, TokenTransferMemo, TransactionAnchorMode,
-    TransactionAuth, TransactionContractCall, TransactionPayload, TransactionSmartContract,
+    AssetInfo, FungibleConditionCode, NonfungibleConditionCode, PostConditionPrincipal,
+    SinglesigHashMode, StacksAddress, StacksBlock, StacksMicroblock, StacksPrivateKey,
+    StacksPublicKey, StacksTransaction, StacksTransactionSigner, TokenTransferMemo,
+    TransactionAnchorMode, TransactionAuth, TransactionAuthField, TransactionAuthFlags,
+    TransactionContractCall, TransactionPayload, TransactionPostCondition,
+    TransactionPostConditionMode, TransactionPublicKeyEncoding, TransactionSmartContract,
     TransactionSpendingCondition, TransactionVersion, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
-    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+    C32_ADDRESS_VERSION_TESTNET_MULTISIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use blockstack_lib::core::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
+use blockstack_lib::net::codec::{read_next, write_next};
 use blockstack_lib::net::{Error as NetError, StacksMessageCodec};
 use blockstack_lib::util::{
     hash::hex_bytes, hash::to_hex, log, retry::LogReader, strings::StacksString,
@@ -44,7 +51,7 @@ use blockstack_lib::vm::{
 use std::convert::TryFrom;
 use std::io::prelude::*;
 use std::io::Read;
-use std::{env, fs, io};
+use std::{env, fs, io, mem};
 
 use blockstack_lib::address::b58;
 use blockstack_lib::burnchains::bitcoin::address::{
@@ -66,6 +73,9 @@ This CLI has these methods:
   decode-tx          used to decode a hex-encoded transaction into a human-readable representation
   decode-block       used to decode a hex-encoded Stacks block into a human-readable representation
   decode-microblock  used to decode a hex-encoded Stacks microblock into a human-readable representation
+  sign               used to add the next signature to a multisig or sponsored transaction
+  sponsor            used to set and sign the sponsor of a sponsored transaction
+  finalize           used to check that a multisig or sponsored transaction is fully signed
 
 For usage information on those methods, call `blockstack-cli [method] -h`
 
@@ -86,6 +96,31 @@ is that the miner chooses, but you can decide which with the following options:
 
   --microblock-only  indicates to mine this transaction only in a microblock
   --block-only       indicates to mine this transaction only in a block
+
+A transaction can also be made for a multisig account, or to be paid for by a sponsor:
+
+  --multisig [num-required] [public-key-hex,...]
+                     makes the origin the multisig account of the given public keys, in place of
+                     the secret key argument. The transaction is output unsigned, and its signers
+                     add their signatures with `sign`.
+  --sponsored        makes the transaction sponsored. Its fee is paid by the account that signs
+                     it with `sponsor`, so the fee-rate must be 0.
+
+Post-conditions, which abort the transaction unless its asset transfers meet them, are added
+with the first three of these options, which can be given more than once:
+
+  --stx-post-condition [principal] [condition-code] [amount]
+  --ft-post-condition [principal] [asset] [condition-code] [amount]
+  --nft-post-condition [principal] [asset] [condition-code] [asset-value]
+  --post-condition-mode [allow|deny]
+                     whether the transaction may transfer assets that its post-conditions do
+                     not cover (default: deny)
+
+The [principal] whose transfers are checked is `origin` (the origin account), an address, or a
+contract principal. An [asset] is named as address.contract-name::asset-name, and an
+[asset-value] is a Clarity expression. STX and fungible token conditions use one of the
+condition codes sent-eq, sent-gt, sent-ge, sent-lt, and sent-le, and non-fungible token
+conditions use sent or not-sent.
 ";
 
 const CALL_USAGE: &str = "blockstack-cli (options) contract-call [origin-secret-key-hex] [fee-rate] [nonce] [contract-publisher-address] [contract-name] [function-name] [args...]
@@ -100,6 +135,31 @@ is that the miner chooses, but you can decide which with the following options:
   --microblock-only  indicates to mine this transaction only in a microblock
   --block-only       indicates to mine this transaction only in a block
 
+A transaction can also be made for a multisig account, or to be paid for by a sponsor:
+
+  --multisig [num-required] [public-key-hex,...]
+                     makes the origin the multisig account of the given public keys, in place of
+                     the secret key argument. The transaction is output unsigned, and its signers
+                     add their signatures with `sign`.
+  --sponsored        makes the transaction sponsored. Its fee is paid by the account that signs
+                     it with `sponsor`, so the fee-rate must be 0.
+
+Post-conditions, which abort the transaction unless its asset transfers meet them, are added
+with the first three of these options, which can be given more than once:
+
+  --stx-post-condition [principal] [condition-code] [amount]
+  --ft-post-condition [principal] [asset] [condition-code] [amount]
+  --nft-post-condition [principal] [asset] [condition-code] [asset-value]
+  --post-condition-mode [allow|deny]
+                     whether the transaction may transfer assets that its post-conditions do
+                     not cover (default: deny)
+
+The [principal] whose transfers are checked is `origin` (the origin account), an address, or a
+contract principal. An [asset] is named as address.contract-name::asset-name, and an
+[asset-value] is a Clarity expression. STX and fungible token conditions use one of the
+condition codes sent-eq, sent-gt, sent-ge, sent-lt, and sent-le, and non-fungible token
+conditions use sent or not-sent.
+
 Arguments are supplied in one of two ways: through script evaluation or via hex encoding
 of the value serialization format. The method for supplying arguments is chosen by
 prefacing each argument with a flag:
@@ -127,6 +187,31 @@ is that the miner chooses, but you can decide which with the following options:
 
   --microblock-only  indicates to mine this transaction only in a microblock
   --block-only       indicates to mine this transaction only in a block
+
+A transaction can also be made for a multisig account, or to be paid for by a sponsor:
+
+  --multisig [num-required] [public-key-hex,...]
+                     makes the origin the multisig account of the given public keys, in place of
+                     the secret key argument. The transaction is output unsigned, and its signers
+                     add their signatures with `sign`.
+  --sponsored        makes the transaction sponsored. Its fee is paid by the account that signs
+                     it with `sponsor`, so the fee-rate must be 0.
+
+Post-conditions, which abort the transaction unless its asset transfers meet them, are added
+with the first three of these options, which can be given more than once:
+
+  --stx-post-condition [principal] [condition-code] [amount]
+  --ft-post-condition [principal] [asset] [condition-code] [amount]
+  --nft-post-condition [principal] [asset] [condition-code] [asset-value]
+  --post-condition-mode [allow|deny]
+                     whether the transaction may transfer assets that its post-conditions do
+                     not cover (default: deny)
+
+The [principal] whose transfers are checked is `origin` (the origin account), an address, or a
+contract principal. An [asset] is named as address.contract-name::asset-name, and an
+[asset-value] is a Clarity expression. STX and fungible token conditions use one of the
+condition codes sent-eq, sent-gt, sent-ge, sent-lt, and sent-le, and non-fungible token
+conditions use sent or not-sent.
 ";
 
 const GENERATE_USAGE: &str = "blockstack-cli (options) generate-sk
@@ -162,6 +247,35 @@ raw binary microblock will be read from stdin.
 N.B. Stacks microblocks are not stored as files in the Stacks chainstate -- they are stored in 
 block's sqlite database.";
 
+const SIGN_USAGE: &str = "blockstack-cli (options) sign [secret-key-hex] [transaction-hex]
+
+The sign command adds the next signature that a partially signed transaction needs: its
+origin's signatures come first, then its sponsor's. If successful, this command outputs the
+hex string encoding of the transaction to stdout, and exits with code 0. Once the transaction
+has all its signatures, this is the signed transaction.
+
+A multisig account's signers must sign in the order of the account's public keys, but the
+signers that are not needed can be skipped.";
+
+const SPONSOR_USAGE: &str =
+    "blockstack-cli (options) sponsor [sponsor-secret-key-hex] [fee-rate] [nonce] [transaction-hex]
+
+The sponsor command sets the account that pays for a sponsored transaction, once its origin has
+signed it, and signs it with the sponsor's secret key. If successful, this command outputs the
+hex string encoding of the transaction to stdout, and exits with code 0.
+
+The sponsor can also be a multisig account:
+
+  --multisig [num-required] [public-key-hex,...]
+                     makes the sponsor the multisig account of the given public keys, in place of
+                     the secret key argument. Its signers add their signatures with `sign`.";
+
+const FINALIZE_USAGE: &str = "blockstack-cli (options) finalize [transaction-hex]
+
+The finalize command checks that a multisig or sponsored transaction has all the signatures it
+needs, and that they are valid. If so, this command outputs the hex string encoding of the
+signed transaction to stdout, and exits with code 0.";
+
 #[derive(Debug)]
 enum CliError {
     ClarityRuntimeError(RuntimeErrorType),
@@ -268,23 +382,260 @@ fn make_contract_call(
     })
 }
 
-fn make_standard_single_sig_tx(
+/// The account that authorizes a transaction, as its origin or its sponsor
+enum AccountSigner {
+    /// A single-signature account, whose secret key signs the transaction right away
+    SecretKey(StacksPrivateKey),
+    /// A multisig account needing the given number of signatures from its public keys' owners,
+    /// who sign the transaction later with `sign`
+    Multisig(u16, Vec<StacksPublicKey>),
+}
+
+fn make_spending_condition(
+    signer: &AccountSigner,
+) -> Result<TransactionSpendingCondition, CliError> {
+    match signer {
+        AccountSigner::SecretKey(secret_key) => Ok(
+            TransactionSpendingCondition::new_singlesig_p2pkh(StacksPublicKey::from_private(
+                secret_key,
+            ))
+            .expect("Failed to create p2pkh spending condition from public key."),
+        ),
+        AccountSigner::Multisig(num_required, public_keys) => {
+            let mut spending_condition =
+                TransactionSpendingCondition::new_multisig_p2sh(*num_required, public_keys.clone())
+                    .ok_or("Failed to create p2sh spending condition from public keys")?;
+            // the public keys are listed in order, and each signer replaces theirs with a
+            // signature when they sign
+            if let TransactionSpendingCondition::Multisig(ref mut multisig) = spending_condition {
+                for public_key in public_keys.iter() {
+                    multisig.push_public_key(public_key.clone());
+                }
+            }
+            Ok(spending_condition)
+        }
+    }
+}
+
+fn make_unsigned_tx(
     version: TransactionVersion,
     chain_id: u32,
     payload: TransactionPayload,
-    publicKey: &StacksPublicKey,
+    origin: &AccountSigner,
+    sponsored: bool,
     nonce: u64,
     tx_fee: u64,
-) -> StacksTransaction {
-    let mut spending_condition =
-        TransactionSpendingCondition::new_singlesig_p2pkh(publicKey.clone())
-            .expect("Failed to create p2pkh spending condition from public key.");
+) -> Result<StacksTransaction, CliError> {
+    let mut spending_condition = make_spending_condition(origin)?;
     spending_condition.set_nonce(nonce);
     spending_condition.set_tx_fee(tx_fee);
-    let auth = TransactionAuth::Standard(spending_condition);
+    let auth = if sponsored {
+        if tx_fee != 0 {
+            return Err(
+                "A sponsored transaction's fee is paid by its sponsor, so its fee-rate must be 0"
+                    .into(),
+            );
+        }
+        // the sponsor is set once the origin has signed
+        TransactionAuth::Sponsored(
+            spending_condition,
+            TransactionSpendingCondition::new_initial_sighash(),
+        )
+    } else {
+        TransactionAuth::Standard(spending_condition)
+    };
     let mut tx = StacksTransaction::new(version, auth, payload);
     tx.chain_id = chain_id;
-    tx
+    Ok(tx)
+}
+
+/// Sign a spending condition with `secret_key`, given the sighash that its first signer signs.
+/// A multisig condition lists its signers' public keys, and each signature replaces its
+/// signer's public key. Since each signature signs over the ones before it, signers must sign
+/// in the order of their public keys.
+fn sign_spending_condition(
+    spending_condition: &mut TransactionSpendingCondition,
+    initial_sighash: &Txid,
+    cond_code: &TransactionAuthFlags,
+    secret_key: &StacksPrivateKey,
+) -> Result<(), CliError> {
+    let public_key = StacksPublicKey::from_private(secret_key);
+    let key_encoding = if public_key.compressed() {
+        TransactionPublicKeyEncoding::Compressed
+    } else {
+        TransactionPublicKeyEncoding::Uncompressed
+    };
+    let tx_fee = spending_condition.tx_fee();
+    let nonce = spending_condition.nonce();
+
+    match spending_condition {
+        TransactionSpendingCondition::Singlesig(ref mut singlesig) => {
+            let expected_condition = match singlesig.hash_mode {
+                SinglesigHashMode::P2PKH => {
+                    TransactionSpendingCondition::new_singlesig_p2pkh(public_key)
+                }
+                SinglesigHashMode::P2WPKH => {
+                    TransactionSpendingCondition::new_singlesig_p2wpkh(public_key)
+                }
+            };
+            if expected_condition.map(|condition| condition.address_mainnet().bytes)
+                != Some(singlesig.signer.clone())
+            {
+                return Err("The secret key does not belong to the signing account".into());
+            }
+            let (signature, _) = TransactionSpendingCondition::next_signature(
+                initial_sighash,
+                cond_code,
+                tx_fee,
+                nonce,
+                secret_key,
+            )?;
+            singlesig.key_encoding = key_encoding;
+            singlesig.signature = signature;
+        }
+        TransactionSpendingCondition::Multisig(ref mut multisig) => {
+            let mut cur_sighash = initial_sighash.clone();
+            let mut position = None;
+            for (i, field) in multisig.fields.iter().enumerate() {
+                match field {
+                    TransactionAuthField::Signature(ref encoding, ref signature) => {
+                        if position.is_some() {
+                            return Err(
+                                "A signer whose public key comes after this key's has already signed"
+                                    .into(),
+                            );
+                        }
+                        let (_, next_sighash) = TransactionSpendingCondition::next_verification(
+                            &cur_sighash,
+                            cond_code,
+                            tx_fee,
+                            nonce,
+                            encoding,
+                            signature,
+                        )?;
+                        cur_sighash = next_sighash;
+                    }
+                    TransactionAuthField::PublicKey(ref signer_key) => {
+                        if position.is_none() && *signer_key == public_key {
+                            position = Some(i);
+                        }
+                    }
+                }
+            }
+            let position = position
+                .ok_or("The secret key does not belong to a signer that has yet to sign")?;
+            let (signature, _) = TransactionSpendingCondition::next_signature(
+                &cur_sighash,
+                cond_code,
+                tx_fee,
+                nonce,
+                secret_key,
+            )?;
+            multisig.fields[position] = TransactionAuthField::Signature(key_encoding, signature);
+        }
+    }
+    Ok(())
+}
+
+/// Add the next signature that a transaction needs: its origin's signatures come first, then
+/// its sponsor's.
+fn sign_next(tx: &mut StacksTransaction, secret_key: &StacksPrivateKey) -> Result<(), CliError> {
+    let origin = tx.get_origin();
+    if origin.num_signatures() < origin.signatures_required() {
+        // the origin signs the transaction without its signatures (or its sponsor)
+        let mut initial_tx = tx.clone();
+        initial_tx.auth = initial_tx.auth.into_initial_sighash_auth();
+        let initial_sighash = initial_tx.txid();
+        return match tx.auth {
+            TransactionAuth::Standard(ref mut origin)
+            | TransactionAuth::Sponsored(ref mut origin, _) => sign_spending_condition(
+                origin,
+                &initial_sighash,
+                &TransactionAuthFlags::AuthStandard,
+                secret_key,
+            ),
+        };
+    }
+
+    let sponsor_sighash = tx.verify_origin()?;
+    match tx.auth {
+        TransactionAuth::Standard(_) => {
+            Err("The transaction already has all its signatures".into())
+        }
+        TransactionAuth::Sponsored(_, ref mut sponsor) => {
+            if *sponsor == TransactionSpendingCondition::new_initial_sighash() {
+                return Err(
+                    "The transaction's origin has signed it, but it has no sponsor yet".into(),
+                );
+            }
+            if sponsor.num_signatures() >= sponsor.signatures_required() {
+                return Err("The transaction already has all its signatures".into());
+            }
+            sign_spending_condition(
+                sponsor,
+                &sponsor_sighash,
+                &TransactionAuthFlags::AuthSponsored,
+                secret_key,
+            )
+        }
+    }
+}
+
+fn spending_conditions_mut(auth: &mut TransactionAuth) -> Vec<&mut TransactionSpendingCondition> {
+    match auth {
+        TransactionAuth::Standard(ref mut origin) => vec![origin],
+        TransactionAuth::Sponsored(ref mut origin, ref mut sponsor) => vec![origin, sponsor],
+    }
+}
+
+/// Encode a transaction that may not be fully signed. A multisig spending condition can only be
+/// decoded once it has all its signatures, so the auth fields of one that is still being signed
+/// are moved out of the transaction, and encoded after it along with the number of signatures
+/// it needs. A fully signed transaction is encoded as usual.
+fn encode_partial_tx(tx: &StacksTransaction) -> String {
+    let mut tx = tx.clone();
+    let mut pending_fields = vec![];
+    for spending_condition in spending_conditions_mut(&mut tx.auth) {
+        let is_pending =
+            spending_condition.num_signatures() < spending_condition.signatures_required();
+        if let TransactionSpendingCondition::Multisig(ref mut multisig) = spending_condition {
+            if is_pending {
+                pending_fields.push((
+                    multisig.signatures_required,
+                    mem::replace(&mut multisig.fields, vec![]),
+                ));
+                multisig.signatures_required = 0;
+            }
+        }
+    }
+
+    let mut tx_bytes = vec![];
+    tx.consensus_serialize(&mut tx_bytes)
+        .expect("FATAL: invalid transaction");
+    for (signatures_required, fields) in pending_fields.iter() {
+        write_next(&mut tx_bytes, signatures_required).expect("FATAL: invalid auth fields");
+        write_next(&mut tx_bytes, fields).expect("FATAL: invalid auth fields");
+    }
+    to_hex(&tx_bytes)
+}
+
+/// Decode a transaction encoded with `encode_partial_tx`
+fn decode_partial_tx(tx_hex: &str) -> Result<StacksTransaction, CliError> {
+    let tx_bytes = hex_bytes(tx_hex)?;
+    let mut cursor = io::Cursor::new(&tx_bytes);
+    let mut tx = StacksTransaction::consensus_deserialize(&mut cursor)?;
+    for spending_condition in spending_conditions_mut(&mut tx.auth) {
+        if let TransactionSpendingCondition::Multisig(ref mut multisig) = spending_condition {
+            if multisig.signatures_required == 0 && multisig.fields.len() == 0 {
+                multisig.signatures_required = read_next(&mut cursor)?;
+                multisig.fields = read_next(&mut cursor)?;
+            }
+        }
+    }
+    if cursor.position() != tx_bytes.len() as u64 {
+        return Err("Unexpected bytes after the transaction".into());
+    }
+    Ok(tx)
 }
 
 fn sign_transaction_single_sig_standard(
@@ -340,6 +691,264 @@ fn parse_anchor_mode(
     }
 }
 
+fn parse_sponsored(args: &mut Vec<String>) -> bool {
+    if let Some(ix) = args.iter().position(|x| x == "--sponsored") {
+        args.remove(ix);
+        true
+    } else {
+        false
+    }
+}
+
+const FUNGIBLE_CONDITION_CODES: &[(&str, FungibleConditionCode)] = &[
+    ("sent-eq", FungibleConditionCode::SentEq),
+    ("sent-gt", FungibleConditionCode::SentGt),
+    ("sent-ge", FungibleConditionCode::SentGe),
+    ("sent-lt", FungibleConditionCode::SentLt),
+    ("sent-le", FungibleConditionCode::SentLe),
+];
+
+const NONFUNGIBLE_CONDITION_CODES: &[(&str, NonfungibleConditionCode)] = &[
+    ("sent", NonfungibleConditionCode::Sent),
+    ("not-sent", NonfungibleConditionCode::NotSent),
+];
+
+fn parse_fungible_condition_code(code: &str) -> Result<FungibleConditionCode, CliError> {
+    FUNGIBLE_CONDITION_CODES
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| CliError::Message(format!("Unknown fungible condition code: {}", code)))
+}
+
+fn parse_nonfungible_condition_code(code: &str) -> Result<NonfungibleConditionCode, CliError> {
+    NONFUNGIBLE_CONDITION_CODES
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| CliError::Message(format!("Unknown non-fungible condition code: {}", code)))
+}
+
+fn fungible_condition_code_name(code: &FungibleConditionCode) -> &'static str {
+    FUNGIBLE_CONDITION_CODES
+        .iter()
+        .find(|(_, named_code)| named_code == code)
+        .map(|(name, _)| *name)
+        .expect("FATAL: unnamed fungible condition code")
+}
+
+fn nonfungible_condition_code_name(code: &NonfungibleConditionCode) -> &'static str {
+    NONFUNGIBLE_CONDITION_CODES
+        .iter()
+        .find(|(_, named_code)| named_code == code)
+        .map(|(name, _)| *name)
+        .expect("FATAL: unnamed non-fungible condition code")
+}
+
+/// Parse a post-condition principal: `origin`, a standard address, or a contract principal
+fn parse_post_condition_principal(principal: &str) -> Result<PostConditionPrincipal, CliError> {
+    if principal == "origin" {
+        return Ok(PostConditionPrincipal::Origin);
+    }
+    let mut parts = principal.splitn(2, '.');
+    let address = StacksAddress::from_string(parts.next().unwrap_or(""))
+        .ok_or("Failed to parse post-condition principal")?;
+    match parts.next() {
+        Some(contract_name) => Ok(PostConditionPrincipal::Contract(
+            address,
+            ContractName::try_from(contract_name.to_string())?,
+        )),
+        None => Ok(PostConditionPrincipal::Standard(address)),
+    }
+}
+
+fn post_condition_principal_name(principal: &PostConditionPrincipal) -> String {
+    match principal {
+        PostConditionPrincipal::Origin => "origin".to_string(),
+        PostConditionPrincipal::Standard(address) => address.to_string(),
+        PostConditionPrincipal::Contract(address, contract_name) => {
+            format!("{}.{}", address, contract_name)
+        }
+    }
+}
+
+/// Parse an asset, named as `address.contract-name::asset-name`
+fn parse_asset_info(asset: &str) -> Result<AssetInfo, CliError> {
+    let mut parts = asset.splitn(2, "::");
+    let contract = parts.next().unwrap_or("");
+    let asset_name = parts
+        .next()
+        .ok_or("Assets must be named as address.contract-name::asset-name")?;
+    let mut contract_parts = contract.splitn(2, '.');
+    let contract_address = StacksAddress::from_string(contract_parts.next().unwrap_or(""))
+        .ok_or("Failed to parse asset contract address")?;
+    let contract_name = contract_parts
+        .next()
+        .ok_or("Assets must be named as address.contract-name::asset-name")?;
+    Ok(AssetInfo {
+        contract_address,
+        contract_name: ContractName::try_from(contract_name.to_string())?,
+        asset_name: ClarityName::try_from(asset_name.to_string())?,
+    })
+}
+
+fn asset_info_name(asset_info: &AssetInfo) -> String {
+    format!(
+        "{}.{}::{}",
+        asset_info.contract_address, asset_info.contract_name, asset_info.asset_name
+    )
+}
+
+/// Parse one post-condition option, given as the option's name followed by its arguments
+fn parse_post_condition(option: &[String]) -> Result<TransactionPostCondition, CliError> {
+    let principal = parse_post_condition_principal(&option[1])?;
+    let post_condition = match option[0].as_str() {
+        "--stx-post-condition" => TransactionPostCondition::STX(
+            principal,
+            parse_fungible_condition_code(&option[2])?,
+            option[3].parse()?,
+        ),
+        "--ft-post-condition" => TransactionPostCondition::Fungible(
+            principal,
+            parse_asset_info(&option[2])?,
+            parse_fungible_condition_code(&option[3])?,
+            option[4].parse()?,
+        ),
+        _ => {
+            let asset_value = vm::execute(&option[4])?
+                .ok_or("Supplied asset value did not evaluate to a Value")?;
+            TransactionPostCondition::Nonfungible(
+                principal,
+                parse_asset_info(&option[2])?,
+                asset_value,
+                parse_nonfungible_condition_code(&option[3])?,
+            )
+        }
+    };
+
+    // make sure it decodes as it was given, i.e. that its asset value can be deserialized
+    let mut post_condition_bytes = vec![];
+    post_condition.consensus_serialize(&mut post_condition_bytes)?;
+    let decoded = TransactionPostCondition::consensus_deserialize(&mut io::Cursor::new(
+        &post_condition_bytes,
+    ))?;
+    if decoded != post_condition {
+        return Err("Post-condition does not encode correctly".into());
+    }
+    Ok(post_condition)
+}
+
+/// Remove the post-condition options from the arguments, and parse them in the order given
+fn parse_post_conditions(
+    args: &mut Vec<String>,
+    usage: &str,
+) -> Result<Vec<TransactionPostCondition>, CliError> {
+    let mut post_conditions = vec![];
+    let mut ix = 0;
+    while ix < args.len() {
+        let num_args = match args[ix].as_str() {
+            "--stx-post-condition" => 3,
+            "--ft-post-condition" | "--nft-post-condition" => 4,
+            _ => {
+                ix += 1;
+                continue;
+            }
+        };
+        if ix + num_args >= args.len() {
+            return Err(CliError::Message(format!("USAGE:\n {}", usage)));
+        }
+        let option: Vec<String> = args.drain(ix..ix + num_args + 1).collect();
+        post_conditions.push(parse_post_condition(&option)?);
+    }
+    Ok(post_conditions)
+}
+
+fn parse_post_condition_mode(
+    args: &mut Vec<String>,
+    usage: &str,
+) -> Result<TransactionPostConditionMode, CliError> {
+    let ix = match args.iter().position(|x| x == "--post-condition-mode") {
+        Some(ix) => ix,
+        None => return Ok(TransactionPostConditionMode::Deny),
+    };
+    if ix + 1 >= args.len() {
+        return Err(CliError::Message(format!("USAGE:\n {}", usage)));
+    }
+    let mode = match args[ix + 1].as_str() {
+        "allow" => TransactionPostConditionMode::Allow,
+        "deny" => TransactionPostConditionMode::Deny,
+        _ => return Err("The post-condition mode must be `allow` or `deny`".into()),
+    };
+    args.drain(ix..ix + 2);
+    Ok(mode)
+}
+
+/// Render a post-condition for `decode-tx`, naming its principal, asset, and condition code
+/// as they are given to the post-condition options
+fn post_condition_to_json(post_condition: &TransactionPostCondition) -> serde_json::Value {
+    match post_condition {
+        TransactionPostCondition::STX(principal, code, amount) => json!({
+            "principal": post_condition_principal_name(principal),
+            "asset": "STX",
+            "condition_code": fungible_condition_code_name(code),
+            "amount": amount,
+        }),
+        TransactionPostCondition::Fungible(principal, asset_info, code, amount) => json!({
+            "principal": post_condition_principal_name(principal),
+            "asset": asset_info_name(asset_info),
+            "condition_code": fungible_condition_code_name(code),
+            "amount": amount,
+        }),
+        TransactionPostCondition::Nonfungible(principal, asset_info, asset_value, code) => json!({
+            "principal": post_condition_principal_name(principal),
+            "asset": asset_info_name(asset_info),
+            "condition_code": nonfungible_condition_code_name(code),
+            "asset_value": asset_value.to_string(),
+        }),
+    }
+}
+
+/// Parse the account that signs a transaction: either the multisig account given with
+/// `--multisig [num-required] [public-key-hex,...]`, or the secret key in the first argument.
+/// Either way, its arguments are removed.
+fn parse_account_signer(args: &mut Vec<String>, usage: &str) -> Result<AccountSigner, CliError> {
+    if let Some(ix) = args.iter().position(|x| x == "--multisig") {
+        if ix + 2 >= args.len() {
+            return Err(CliError::Message(format!("USAGE:\n {}", usage)));
+        }
+        let num_required: u16 = args[ix + 1].parse()?;
+        let public_keys = args[ix + 2]
+            .split(',')
+            .map(StacksPublicKey::from_hex)
+            .collect::<Result<Vec<_>, _>>()?;
+        if num_required == 0 || num_required as usize > public_keys.len() {
+            return Err(
+                "A multisig account must require between 1 and its number of public keys signatures"
+                    .into(),
+            );
+        }
+        args.drain(ix..ix + 3);
+        Ok(AccountSigner::Multisig(num_required, public_keys))
+    } else if args.len() > 0 {
+        let secret_key = StacksPrivateKey::from_hex(&args.remove(0))?;
+        Ok(AccountSigner::SecretKey(secret_key))
+    } else {
+        Err(CliError::Message(format!(
+            "Incorrect argument count supplied \n\nUSAGE:\n {}",
+            usage
+        )))
+    }
+}
+
+/// Sign a new transaction with its origin's secret key, if it has one, and encode it
+fn sign_new_tx(unsigned_tx: StacksTransaction, origin: &AccountSigner) -> Result<String, CliError> {
+    let mut tx = unsigned_tx;
+    if let AccountSigner::SecretKey(ref secret_key) = origin {
+        sign_next(&mut tx, secret_key)?;
+    }
+    Ok(encode_partial_tx(&tx))
+}
+
 fn handle_contract_publish(
     args_slice: &[String],
     version: TransactionVersion,
@@ -350,18 +959,21 @@ fn handle_contract_publish(
     if args.len() >= 1 && args[0] == "-h" {
         return Err(CliError::Message(format!("USAGE:\n {}", PUBLISH_USAGE)));
     }
-    if args.len() != 5 {
+    let anchor_mode = parse_anchor_mode(&mut args, PUBLISH_USAGE)?;
+    let sponsored = parse_sponsored(&mut args);
+    let post_condition_mode = parse_post_condition_mode(&mut args, PUBLISH_USAGE)?;
+    let post_conditions = parse_post_conditions(&mut args, PUBLISH_USAGE)?;
+    let origin = parse_account_signer(&mut args, PUBLISH_USAGE)?;
+    if args.len() != 4 {
         return Err(CliError::Message(format!(
             "Incorrect argument count supplied \n\nUSAGE:\n {}",
             PUBLISH_USAGE
         )));
     }
-    let anchor_mode = parse_anchor_mode(&mut args, PUBLISH_USAGE)?;
-    let sk_publisher = &args[0];
-    let tx_fee = args[1].parse()?;
-    let nonce = args[2].parse()?;
-    let contract_name = &args[3];
-    let contract_file = &args[4];
+    let tx_fee = args[0].parse()?;
+    let nonce = args[1].parse()?;
+    let contract_name = &args[2];
+    let contract_file = &args[3];
 
     let contract_contents = if contract_file == "-" {
         let mut buffer = String::new();
@@ -371,31 +983,21 @@ fn handle_contract_publish(
         fs::read_to_string(contract_file)?
     };
 
-    let sk_publisher = StacksPrivateKey::from_hex(sk_publisher)?;
-
     let payload = make_contract_publish(contract_name.clone(), contract_contents)?;
-    let mut unsigned_tx = make_standard_single_sig_tx(
+    let mut unsigned_tx = make_unsigned_tx(
         version,
         chain_id,
         payload.into(),
-        &StacksPublicKey::from_private(&sk_publisher),
+        &origin,
+        sponsored,
         nonce,
         tx_fee,
-    );
+    )?;
     unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    unsigned_tx.post_conditions = post_conditions;
 
-    let mut unsigned_tx_bytes = vec![];
-    unsigned_tx
-        .consensus_serialize(&mut unsigned_tx_bytes)
-        .expect("FATAL: invalid transaction");
-    let signed_tx =
-        sign_transaction_single_sig_standard(&to_hex(&unsigned_tx_bytes), &sk_publisher)?;
-
-    let mut signed_tx_bytes = vec![];
-    signed_tx
-        .consensus_serialize(&mut signed_tx_bytes)
-        .expect("FATAL: invalid signed transaction");
-    Ok(to_hex(&signed_tx_bytes))
+    sign_new_tx(unsigned_tx, &origin)
 }
 
 fn handle_contract_call(
@@ -407,21 +1009,24 @@ fn handle_contract_call(
     if args.len() >= 1 && args[0] == "-h" {
         return Err(CliError::Message(format!("USAGE:\n {}", CALL_USAGE)));
     }
-    if args.len() < 6 {
+    let anchor_mode = parse_anchor_mode(&mut args, CALL_USAGE)?;
+    let sponsored = parse_sponsored(&mut args);
+    let post_condition_mode = parse_post_condition_mode(&mut args, CALL_USAGE)?;
+    let post_conditions = parse_post_conditions(&mut args, CALL_USAGE)?;
+    let origin = parse_account_signer(&mut args, CALL_USAGE)?;
+    if args.len() < 5 {
         return Err(CliError::Message(format!(
             "Incorrect argument count supplied \n\nUSAGE:\n {}",
             CALL_USAGE
         )));
     }
-    let anchor_mode = parse_anchor_mode(&mut args, CALL_USAGE)?;
-    let sk_origin = &args[0];
-    let tx_fee = args[1].parse()?;
-    let nonce = args[2].parse()?;
-    let contract_address = &args[3];
-    let contract_name = &args[4];
-    let function_name = &args[5];
+    let tx_fee = args[0].parse()?;
+    let nonce = args[1].parse()?;
+    let contract_address = &args[2];
+    let contract_name = &args[3];
+    let function_name = &args[4];
 
-    let val_args = &args[6..];
+    let val_args = &args[5..];
 
     if val_args.len() % 2 != 0 {
         return Err(
@@ -452,35 +1057,26 @@ fn handle_contract_call(
         arg_iterator += 2;
     }
 
-    let sk_origin = StacksPrivateKey::from_hex(sk_origin)?;
-
     let payload = make_contract_call(
         contract_address.clone(),
         contract_name.clone(),
         function_name.clone(),
         values,
     )?;
-    let mut unsigned_tx = make_standard_single_sig_tx(
+    let mut unsigned_tx = make_unsigned_tx(
         version,
         chain_id,
         payload.into(),
-        &StacksPublicKey::from_private(&sk_origin),
+        &origin,
+        sponsored,
         nonce,
         tx_fee,
-    );
+    )?;
     unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    unsigned_tx.post_conditions = post_conditions;
 
-    let mut unsigned_tx_bytes = vec![];
-    unsigned_tx
-        .consensus_serialize(&mut unsigned_tx_bytes)
-        .expect("FATAL: invalid transaction");
-    let signed_tx = sign_transaction_single_sig_standard(&to_hex(&unsigned_tx_bytes), &sk_origin)?;
-
-    let mut signed_tx_bytes = vec![];
-    signed_tx
-        .consensus_serialize(&mut signed_tx_bytes)
-        .expect("FATAL: invalid signed transaction");
-    Ok(to_hex(&signed_tx_bytes))
+    sign_new_tx(unsigned_tx, &origin)
 }
 
 fn handle_token_transfer(
@@ -495,24 +1091,27 @@ fn handle_token_transfer(
             TOKEN_TRANSFER_USAGE
         )));
     }
-    if args.len() < 5 {
+    let anchor_mode = parse_anchor_mode(&mut args, TOKEN_TRANSFER_USAGE)?;
+    let sponsored = parse_sponsored(&mut args);
+    let post_condition_mode = parse_post_condition_mode(&mut args, TOKEN_TRANSFER_USAGE)?;
+    let post_conditions = parse_post_conditions(&mut args, TOKEN_TRANSFER_USAGE)?;
+    let origin = parse_account_signer(&mut args, TOKEN_TRANSFER_USAGE)?;
+    if args.len() < 4 {
         return Err(CliError::Message(format!(
             "Incorrect argument count supplied \n\nUSAGE:\n {}",
             TOKEN_TRANSFER_USAGE
         )));
     }
 
-    let anchor_mode = parse_anchor_mode(&mut args, TOKEN_TRANSFER_USAGE)?;
-    let sk_origin = StacksPrivateKey::from_hex(&args[0])?;
-    let tx_fee = args[1].parse()?;
-    let nonce = args[2].parse()?;
+    let tx_fee = args[0].parse()?;
+    let nonce = args[1].parse()?;
     let recipient_address =
-        PrincipalData::parse(&args[3]).map_err(|_e| "Failed to parse recipient")?;
-    let amount = &args[4].parse()?;
+        PrincipalData::parse(&args[2]).map_err(|_e| "Failed to parse recipient")?;
+    let amount = &args[3].parse()?;
     let memo = {
         let mut memo = [0; 34];
-        let mut bytes = if args.len() == 6 {
-            args[5].as_bytes().to_vec()
+        let mut bytes = if args.len() == 5 {
+            args[4].as_bytes().to_vec()
         } else {
             vec![]
         };
@@ -522,27 +1121,14 @@ fn handle_token_transfer(
     };
 
     let payload = TransactionPayload::TokenTransfer(recipient_address, *amount, memo);
-    let mut unsigned_tx = make_standard_single_sig_tx(
-        version,
-        chain_id,
-        payload,
-        &StacksPublicKey::from_private(&sk_origin),
-        nonce,
-        tx_fee,
-    );
+    let mut unsigned_tx = make_unsigned_tx(
+        version, chain_id, payload, &origin, sponsored, nonce, tx_fee,
+    )?;
     unsigned_tx.anchor_mode = anchor_mode;
+    unsigned_tx.post_condition_mode = post_condition_mode;
+    unsigned_tx.post_conditions = post_conditions;
 
-    let mut unsigned_tx_bytes = vec![];
-    unsigned_tx
-        .consensus_serialize(&mut unsigned_tx_bytes)
-        .expect("FATAL: invalid transaction");
-    let signed_tx = sign_transaction_single_sig_standard(&to_hex(&unsigned_tx_bytes), &sk_origin)?;
-
-    let mut signed_tx_bytes = vec![];
-    signed_tx
-        .consensus_serialize(&mut signed_tx_bytes)
-        .expect("FATAL: invalid signed transaction");
-    Ok(to_hex(&signed_tx_bytes))
+    sign_new_tx(unsigned_tx, &origin)
 }
 
 fn generate_secret_key(args: &[String], version: TransactionVersion) -> Result<String, CliError> {
@@ -639,7 +1225,16 @@ fn decode_transaction(args: &[String], _version: TransactionVersion) -> Result<S
     let mut debug_cursor = LogReader::from_reader(&mut cursor);
 
     match StacksTransaction::consensus_deserialize(&mut debug_cursor) {
-        Ok(tx) => Ok(serde_json::to_string(&tx).expect("Failed to serialize transaction to JSON")),
+        Ok(tx) => {
+            let mut tx_json =
+                serde_json::to_value(&tx).expect("Failed to serialize transaction to JSON");
+            tx_json["post_conditions"] = tx
+                .post_conditions
+                .iter()
+                .map(post_condition_to_json)
+                .collect();
+            Ok(tx_json.to_string())
+        }
         Err(e) => {
             let mut ret = String::new();
             ret.push_str(&format!("Failed to decode transaction: {:?}\n", &e));
@@ -729,6 +1324,73 @@ fn decode_microblock(args: &[String], _version: TransactionVersion) -> Result<St
     }
 }
 
+fn handle_sign(args: &[String], _version: TransactionVersion) -> Result<String, CliError> {
+    if args.len() >= 1 && args[0] == "-h" {
+        return Err(CliError::Message(format!("USAGE:\n {}", SIGN_USAGE)));
+    }
+    if args.len() != 2 {
+        return Err(CliError::Message(format!(
+            "Incorrect argument count supplied \n\nUSAGE:\n {}",
+            SIGN_USAGE
+        )));
+    }
+    let secret_key = StacksPrivateKey::from_hex(&args[0])?;
+    let mut tx = decode_partial_tx(&args[1])?;
+
+    sign_next(&mut tx, &secret_key)?;
+    Ok(encode_partial_tx(&tx))
+}
+
+fn handle_sponsor(args_slice: &[String], _version: TransactionVersion) -> Result<String, CliError> {
+    let mut args = args_slice.to_vec();
+    if args.len() >= 1 && args[0] == "-h" {
+        return Err(CliError::Message(format!("USAGE:\n {}", SPONSOR_USAGE)));
+    }
+    let sponsor = parse_account_signer(&mut args, SPONSOR_USAGE)?;
+    if args.len() != 3 {
+        return Err(CliError::Message(format!(
+            "Incorrect argument count supplied \n\nUSAGE:\n {}",
+            SPONSOR_USAGE
+        )));
+    }
+    let tx_fee = args[0].parse()?;
+    let nonce = args[1].parse()?;
+    let mut tx = decode_partial_tx(&args[2])?;
+
+    tx.verify_origin().map_err(|e| {
+        CliError::Message(format!(
+            "The transaction's origin has not finished signing it: {}",
+            e
+        ))
+    })?;
+
+    let mut spending_condition = make_spending_condition(&sponsor)?;
+    spending_condition.set_nonce(nonce);
+    spending_condition.set_tx_fee(tx_fee);
+    tx.auth
+        .set_sponsor(spending_condition)
+        .map_err(|_e| "The transaction is not sponsored")?;
+
+    if let AccountSigner::SecretKey(ref secret_key) = sponsor {
+        sign_next(&mut tx, secret_key)?;
+    }
+    Ok(encode_partial_tx(&tx))
+}
+
+fn handle_finalize(args: &[String], _version: TransactionVersion) -> Result<String, CliError> {
+    if (args.len() >= 1 && args[0] == "-h") || args.len() != 1 {
+        return Err(CliError::Message(format!("USAGE:\n {}", FINALIZE_USAGE)));
+    }
+    let tx = decode_partial_tx(&args[0])?;
+    tx.verify()
+        .map_err(|e| CliError::Message(format!("The transaction is not fully signed: {}", e)))?;
+
+    let mut signed_tx_bytes = vec![];
+    tx.consensus_serialize(&mut signed_tx_bytes)
+        .expect("FATAL: invalid signed transaction");
+    Ok(to_hex(&signed_tx_bytes))
+}
+
 fn main() {
     let mut argv: Vec<String> = env::args().collect();
 
@@ -769,6 +1431,9 @@ fn main_handler(mut argv: Vec<String>) -> Result<String, CliError> {
             "decode-tx" => decode_transaction(args, tx_version),
             "decode-block" => decode_block(args, tx_version),
             "decode-microblock" => decode_microblock(args, tx_version),
+            "sign" => handle_sign(args, tx_version),
+            "sponsor" => handle_sponsor(args, tx_version),
+            "finalize" => handle_finalize(args, tx_version),
             _ => Err(CliError::Usage),
         }
     } else {
@@ -1059,6 +1724,277 @@ mod test {
         );
     }
 
+    #[test]
+    fn multisig_and_sponsored() {
+        let secret_keys: Vec<_> = (0..3).map(|_| StacksPrivateKey::new()).collect();
+        let public_keys: Vec<_> = secret_keys
+            .iter()
+            .map(|sk| StacksPublicKey::from_private(sk))
+            .collect();
+        let public_keys_arg = public_keys
+            .iter()
+            .map(|pk| pk.to_hex())
+            .collect::<Vec<_>>()
+            .join(",");
+        let multisig_address = StacksAddress::from_public_keys(
+            C32_ADDRESS_VERSION_TESTNET_MULTISIG,
+            &AddressHashMode::SerializeP2SH,
+            2,
+            &public_keys,
+        )
+        .unwrap();
+        let sign = |sk: &StacksPrivateKey, tx: &str| {
+            main_handler(to_string_vec(&["sign", &sk.to_hex(), tx]))
+        };
+
+        let unsigned_tx = main_handler(to_string_vec(&[
+            "--testnet",
+            "token-transfer",
+            "--multisig",
+            "2",
+            &public_keys_arg,
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+        ]))
+        .unwrap();
+        assert!(format!(
+            "{}",
+            main_handler(to_string_vec(&["finalize", &unsigned_tx])).unwrap_err()
+        )
+        .contains("not fully signed"));
+
+        // signers sign in the order of their public keys, skipping the second
+        let partial_tx = sign(&secret_keys[2], &unsigned_tx).unwrap();
+        assert!(
+            format!("{}", sign(&secret_keys[0], &partial_tx).unwrap_err())
+                .contains("has already signed")
+        );
+        let partial_tx = sign(&secret_keys[0], &unsigned_tx).unwrap();
+        assert!(
+            format!("{}", sign(&secret_keys[0], &partial_tx).unwrap_err()).contains("yet to sign")
+        );
+        assert!(format!(
+            "{}",
+            sign(&StacksPrivateKey::new(), &partial_tx).unwrap_err()
+        )
+        .contains("yet to sign"));
+        let signed_tx = sign(&secret_keys[2], &partial_tx).unwrap();
+        assert!(
+            format!("{}", sign(&secret_keys[1], &signed_tx).unwrap_err())
+                .contains("all its signatures")
+        );
+
+        let multisig_tx = main_handler(to_string_vec(&["finalize", &signed_tx])).unwrap();
+        assert_eq!(multisig_tx, signed_tx);
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            hex_bytes(&multisig_tx).unwrap(),
+        ))
+        .unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.origin_address(), multisig_address);
+
+        // a sponsored transaction, whose origin signs before a multisig sponsor
+        let tt_args = [
+            "--testnet",
+            "token-transfer",
+            "--sponsored",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "ST1A14RBKJ289E3DP89QAZE2RRHDPWP5RHMYFRCHV",
+            "10",
+        ];
+        assert!(
+            format!("{}", main_handler(to_string_vec(&tt_args)).unwrap_err())
+                .contains("fee-rate must be 0")
+        );
+        let mut tt_args = tt_args.to_vec();
+        tt_args[4] = "0";
+        let origin_signed_tx = main_handler(to_string_vec(&tt_args)).unwrap();
+        assert!(
+            format!("{}", sign(&secret_keys[0], &origin_signed_tx).unwrap_err())
+                .contains("no sponsor yet")
+        );
+
+        let partial_tx = main_handler(to_string_vec(&[
+            "sponsor",
+            "--multisig",
+            "2",
+            &public_keys_arg,
+            "300",
+            "7",
+            &origin_signed_tx,
+        ]))
+        .unwrap();
+        let partial_tx = sign(&secret_keys[0], &partial_tx).unwrap();
+        assert!(main_handler(to_string_vec(&["finalize", &partial_tx])).is_err());
+        let partial_tx = sign(&secret_keys[1], &partial_tx).unwrap();
+        let final_tx = main_handler(to_string_vec(&["finalize", &partial_tx])).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            hex_bytes(&final_tx).unwrap(),
+        ))
+        .unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.sponsor_address(), Some(multisig_address));
+        assert_eq!(tx.get_tx_fee(), 300);
+        assert_eq!(tx.get_sponsor_nonce(), Some(7));
+
+        // a standard transaction has no sponsor to set
+        assert!(format!(
+            "{}",
+            main_handler(to_string_vec(&[
+                "sponsor",
+                "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+                "300",
+                "7",
+                &multisig_tx,
+            ]))
+            .unwrap_err()
+        )
+        .contains("not sponsored"));
+    }
+
+    #[test]
+    fn post_conditions() {
+        let cc_args = [
+            "contract-call",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "--nft-post-condition",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::names",
+            "not-sent",
+            "\"alice\"",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+            "foo-contract",
+            "transfer-fookens",
+            "--stx-post-condition",
+            "origin",
+            "sent-le",
+            "100",
+            "--post-condition-mode",
+            "allow",
+            "--ft-post-condition",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::fookens",
+            "sent-eq",
+            "2",
+            "-e",
+            "(+ 1 1)",
+        ];
+        let tx_hex = main_handler(to_string_vec(&cc_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            hex_bytes(&tx_hex).unwrap(),
+        ))
+        .unwrap();
+        tx.verify().unwrap();
+        assert_eq!(tx.post_condition_mode, TransactionPostConditionMode::Allow);
+        match tx.payload {
+            TransactionPayload::ContractCall(ref call) => {
+                assert_eq!(call.function_args, vec![Value::Int(2)])
+            }
+            _ => panic!("expected a contract-call"),
+        }
+
+        let decoded: serde_json::Value =
+            serde_json::from_str(&main_handler(to_string_vec(&["decode-tx", &tx_hex])).unwrap())
+                .unwrap();
+        assert_eq!(
+            decoded["post_conditions"],
+            json!([
+                {
+                    "principal": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract",
+                    "asset": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::names",
+                    "condition_code": "not-sent",
+                    "asset_value": "\"alice\"",
+                },
+                {
+                    "principal": "origin",
+                    "asset": "STX",
+                    "condition_code": "sent-le",
+                    "amount": 100,
+                },
+                {
+                    "principal": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+                    "asset": "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.foo-contract::fookens",
+                    "condition_code": "sent-eq",
+                    "amount": 2,
+                },
+            ])
+        );
+        assert_eq!(decoded["post_condition_mode"], json!("Allow"));
+
+        // transactions deny unmentioned transfers by default
+        let tt_args = [
+            "token-transfer",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4",
+            "10",
+            "--stx-post-condition",
+            "origin",
+            "sent-eq",
+            "10",
+        ];
+        let tx_hex = main_handler(to_string_vec(&tt_args)).unwrap();
+        let tx = StacksTransaction::consensus_deserialize(&mut io::Cursor::new(
+            hex_bytes(&tx_hex).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(tx.post_condition_mode, TransactionPostConditionMode::Deny);
+        assert_eq!(tx.post_conditions.len(), 1);
+
+        let mut bad_args = tt_args.to_vec();
+        bad_args[8] = "sent-most";
+        assert!(
+            format!("{}", main_handler(to_string_vec(&bad_args)).unwrap_err())
+                .contains("Unknown fungible condition code")
+        );
+
+        let mut bad_args = tt_args.to_vec();
+        bad_args[7] = "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.not a name";
+        assert!(main_handler(to_string_vec(&bad_args)).is_err());
+
+        let publish_args = [
+            "publish",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "foo-contract",
+            "./sample-contracts/tokens.clar",
+            "--ft-post-condition",
+            "origin",
+            "SPJT598WY1RJN792HRKRHRQYFB7RJ5ZCG6J6GEZ4.tokens",
+            "sent-eq",
+            "1",
+        ];
+        assert!(format!(
+            "{}",
+            main_handler(to_string_vec(&publish_args)).unwrap_err()
+        )
+        .contains("address.contract-name::asset-name"));
+
+        let publish_args = [
+            "publish",
+            "043ff5004e3d695060fa48ac94c96049b8c14ef441c50a184a6a3875d2a000f3",
+            "1",
+            "0",
+            "foo-contract",
+            "./sample-contracts/tokens.clar",
+            "--post-condition-mode",
+            "maybe",
+        ];
+        assert!(format!(
+            "{}",
+            main_handler(to_string_vec(&publish_args)).unwrap_err()
+        )
+        .contains("`allow` or `deny`"));
+    }
+
     #[test]
     fn simple_addresses() {
         let addr_args = [
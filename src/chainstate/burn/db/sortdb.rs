@@ -2961,6 +2961,38 @@ impl SortitionDB {
         Ok(burn_total)
     }
 
+    /// Find out which PoX reward addresses the block commits in a given burnchain block paid,
+    /// and how much each address was paid in total.
+    /// Returns the addresses in the order the first such block commit paid them, or an empty
+    /// list and 0 if every block commit burned its commitment instead.  Only the block commits
+    /// that paid exactly those addresses count towards the total.
+    pub fn get_reward_set_payouts_at(
+        conn: &Connection,
+        sortition_id: &SortitionId,
+    ) -> Result<(Vec<StacksAddress>, u128), db_error> {
+        let block_commits = SortitionDB::get_block_commits_by_block(conn, sortition_id)?;
+        let mut addrs = vec![];
+        let mut payout: u128 = 0;
+
+        for block_commit in block_commits.iter() {
+            if block_commit.all_outputs_burn() || block_commit.commit_outs.len() == 0 {
+                continue;
+            }
+            if addrs.len() == 0 {
+                addrs = block_commit.commit_outs.clone();
+            } else if block_commit.commit_outs != addrs {
+                continue;
+            }
+            // every PoX output of a block commit is paid the same amount
+            payout = payout
+                .checked_add(
+                    (block_commit.burn_fee as u128) / (block_commit.commit_outs.len() as u128),
+                )
+                .expect("Way too many tokens transferred");
+        }
+        Ok((addrs, payout))
+    }
+
     /// Get all user burns registered in a block on is fork.
     /// Returns list of user burns in order by vtxindex.
     pub fn get_user_burns_by_block(
@@ -4350,6 +4382,97 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_reward_set_payouts_at() {
+        let block_height = 123;
+        let first_burn_hash = BurnchainHeaderHash([0x00; 32]);
+        let reward_addrs = vec![
+            StacksAddress::new(1, Hash160([0x01; 20])),
+            StacksAddress::new(1, Hash160([0x02; 20])),
+        ];
+        let other_addrs = vec![
+            StacksAddress::new(1, Hash160([0x03; 20])),
+            StacksAddress::new(1, Hash160([0x04; 20])),
+        ];
+
+        let make_commit =
+            |vtxindex: u32, commit_outs: Vec<StacksAddress>, burn_fee: u64| LeaderBlockCommitOp {
+                sunset_burn: 0,
+                block_header_hash: BlockHeaderHash([vtxindex as u8; 32]),
+                new_seed: VRFSeed([vtxindex as u8; 32]),
+                parent_block_ptr: 0,
+                parent_vtxindex: 0,
+                key_block_ptr: 0,
+                key_vtxindex: 0,
+                memo: vec![0x80],
+                commit_outs,
+                burn_fee,
+                input: (Txid([0; 32]), 0),
+                apparent_sender: BurnchainSigner {
+                    public_keys: vec![StacksPublicKey::from_hex(
+                        "02d8015134d9db8178ac93acbc43170a2f20febba5087a5b0437058765ad5133d0",
+                    )
+                    .unwrap()],
+                    num_sigs: 1,
+                    hash_mode: AddressHashMode::SerializeP2PKH,
+                },
+                txid: Txid([vtxindex as u8; 32]),
+                vtxindex,
+                block_height: block_height + 1,
+                burn_parent_modulus: (block_height % BURN_BLOCK_MINED_AT_MODULUS) as u8,
+                burn_header_hash: BurnchainHeaderHash([0x01; 32]),
+            };
+
+        let mut db = SortitionDB::connect_test(block_height, &first_burn_hash).unwrap();
+
+        // only burns
+        let snapshot = test_append_snapshot(
+            &mut db,
+            BurnchainHeaderHash([0x01; 32]),
+            &vec![BlockstackOperationType::LeaderBlockCommit(make_commit(
+                1,
+                vec![StacksAddress::burn_address(false)],
+                1000,
+            ))],
+        );
+        assert_eq!(
+            SortitionDB::get_reward_set_payouts_at(db.conn(), &snapshot.sortition_id).unwrap(),
+            (vec![], 0)
+        );
+
+        // commits that paid other addresses than the first one aren't counted
+        let snapshot = test_append_snapshot(
+            &mut db,
+            BurnchainHeaderHash([0x02; 32]),
+            &vec![
+                BlockstackOperationType::LeaderBlockCommit(make_commit(
+                    1,
+                    vec![StacksAddress::burn_address(false)],
+                    1000,
+                )),
+                BlockstackOperationType::LeaderBlockCommit(make_commit(
+                    2,
+                    reward_addrs.clone(),
+                    100,
+                )),
+                BlockstackOperationType::LeaderBlockCommit(make_commit(
+                    3,
+                    other_addrs.clone(),
+                    10000,
+                )),
+                BlockstackOperationType::LeaderBlockCommit(make_commit(
+                    4,
+                    reward_addrs.clone(),
+                    300,
+                )),
+            ],
+        );
+        assert_eq!(
+            SortitionDB::get_reward_set_payouts_at(db.conn(), &snapshot.sortition_id).unwrap(),
+            (reward_addrs, 200)
+        );
+    }
+
     #[test]
     fn test_insert_user_burn() {
         let block_height = 123;
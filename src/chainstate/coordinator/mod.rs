@@ -292,12 +292,14 @@ impl<'a, T: BlockEventDispatcher>
                 CoordinatorEvents::NEW_STACKS_BLOCK => {
                     debug!("Received new stacks block notice");
                     if let Err(e) = inst.handle_new_stacks_block() {
+                        check_pruned_state_error(&e);
                         warn!("Error processing new stacks block: {:?}", e);
                     }
                 }
                 CoordinatorEvents::NEW_BURN_BLOCK => {
                     debug!("Received new burn block notice");
                     if let Err(e) = inst.handle_new_burnchain_block() {
+                        check_pruned_state_error(&e);
                         warn!("Error processing new burn block: {:?}", e);
                     }
                 }
@@ -352,6 +354,12 @@ impl<'a, T: BlockEventDispatcher, U: RewardSetProvider> ChainsCoordinator<'a, T,
             atlas_config: AtlasConfig::default(false),
         }
     }
+
+    /// Get the chainstate that this coordinator processes blocks into
+    #[cfg(test)]
+    pub fn chainstate_mut(&mut self) -> &mut StacksChainState {
+        &mut self.chain_state_db
+    }
 }
 
 pub fn get_next_recipients<U: RewardSetProvider>(
@@ -437,6 +445,18 @@ pub fn get_reward_cycle_info<U: RewardSetProvider>(
     }
 }
 
+/// A node that pruned the Clarity state a valid Stacks block needs can never process that block,
+/// nor advance its canonical chain tip past it.  Stop the node rather than leave it stalled.
+fn check_pruned_state_error(error: &Error) {
+    if let Error::ChainstateError(ChainstateError::PrunedStateError(msg)) = error {
+        panic!(
+            "FATAL: this node pruned Clarity state that a Stacks block needs ({}). Re-sync its \
+             chain state with a larger `marf_prune_depth`, or with pruning disabled.",
+            msg
+        );
+    }
+}
+
 fn dispatcher_announce_burn_ops<T: BlockEventDispatcher>(
     dispatcher: &T,
     burn_header: &BurnchainBlockHeader,
@@ -3599,3 +3599,13 @@ fn preprocess_block(
         )
         .unwrap();
 }
+
+#[test]
+#[should_panic(expected = "FATAL: this node pruned Clarity state that a Stacks block needs")]
+fn test_pruned_state_stops_coordinator() {
+    // other errors are only logged
+    check_pruned_state_error(&CoordError::NoSortitions);
+    check_pruned_state_error(&CoordError::ChainstateError(
+        chainstate::stacks::Error::PrunedStateError("block".into()),
+    ));
+}
@@ -58,6 +58,8 @@ const BOOT_CODE_POX_TESTNET_CONSTS: &'static str = std::include_str!("pox-testne
 const BOOT_CODE_POX_MAINNET_CONSTS: &'static str = std::include_str!("pox-mainnet.clar");
 const BOOT_CODE_LOCKUP: &'static str = std::include_str!("lockup.clar");
 pub const BOOT_CODE_COSTS: &'static str = std::include_str!("costs.clar");
+/// Published when Clarity 2 activates, rather than at genesis
+pub const BOOT_CODE_COSTS_2: &'static str = std::include_str!("costs-2.clar");
 const BOOT_CODE_COST_VOTING_MAINNET: &'static str = std::include_str!("cost-voting.clar");
 const BOOT_CODE_BNS: &'static str = std::include_str!("bns.clar");
 const BOOT_CODE_GENESIS: &'static str = std::include_str!("genesis.clar");
@@ -675,6 +677,46 @@ pub mod test {
         }
     }
 
+    /// Get the (locked, unlock-height, unlocked) tuple that `stx-account` returns.  This is a
+    /// Clarity 2 function, so it is evaluated in the `costs-2` contract, which only exists once
+    /// Clarity 2 is active.
+    fn get_stx_account(peer: &mut TestPeer, addr: &PrincipalData) -> (u128, u128, u128) {
+        let value = eval_at_tip(
+            peer,
+            "costs-2",
+            &format!("(stx-account '{})", addr.to_string()),
+        );
+        let data = value.expect_tuple();
+        (
+            data.get("locked").unwrap().to_owned().expect_u128(),
+            data.get("unlock-height").unwrap().to_owned().expect_u128(),
+            data.get("unlocked").unwrap().to_owned().expect_u128(),
+        )
+    }
+
+    /// Look up a burnchain block with `get-burn-block-info?`.  Like `stx-account`, this is a
+    /// Clarity 2 function, so it is evaluated in the `costs-2` contract.
+    fn get_burn_block_info(peer: &mut TestPeer, prop: &str, burn_height: u64) -> Option<Value> {
+        eval_at_tip(
+            peer,
+            "costs-2",
+            &format!("(get-burn-block-info? {} u{})", prop, burn_height),
+        )
+        .expect_optional()
+    }
+
+    /// Get the snapshot of the last burnchain block that code evaluated at the Stacks tip can
+    /// see, i.e. the one at `burn-block-height`
+    fn get_burn_block_height_snapshot(peer: &mut TestPeer) -> BlockSnapshot {
+        let burn_height = eval_at_tip(peer, "pox", "burn-block-height").expect_u128() as u64;
+        with_sortdb(peer, |_, sortdb| {
+            let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+            SortitionDB::get_ancestor_snapshot(&sortdb.index_conn(), burn_height, &tip.sortition_id)
+        })
+        .unwrap()
+        .unwrap()
+    }
+
     fn get_stacker_info(
         peer: &mut TestPeer,
         addr: &PrincipalData,
@@ -1449,6 +1491,7 @@ pub mod test {
 
         let (mut peer, mut keys) =
             instantiate_pox_peer(&burnchain, "test-pox-lockup-single-tx-sender", 6002);
+        peer.set_clarity_2_activation_height(0);
 
         let num_blocks = 10;
 
@@ -1457,6 +1500,7 @@ pub mod test {
         let charlie = keys.pop().unwrap();
 
         let mut alice_reward_cycle = 0;
+        let mut alice_paid = false;
 
         for tenure_id in 0..num_blocks {
             let microblock_privkey = StacksPrivateKey::new();
@@ -1578,6 +1622,18 @@ pub mod test {
                 let alice_balance = get_balance(&mut peer, &key_to_stacks_addr(&alice).into());
                 assert_eq!(alice_balance, 0);
 
+                // ...and stx-account reports it as locked until its unlock height
+                let alice_account = get_account(&mut peer, &key_to_stacks_addr(&alice).into());
+                let (locked, unlock_height, unlocked) =
+                    get_stx_account(&mut peer, &key_to_stacks_addr(&alice).into());
+                assert_eq!(locked, alice_account.stx_balance.amount_locked);
+                assert_eq!(locked, 1024 * POX_THRESHOLD_STEPS_USTX);
+                assert_eq!(
+                    unlock_height,
+                    alice_account.stx_balance.unlock_height as u128
+                );
+                assert_eq!(unlocked, 0);
+
                 let min_ustx = with_sortdb(&mut peer, |ref mut chainstate, ref sortdb| {
                     chainstate.get_stacking_minimum(sortdb, &tip_index_block)
                 })
@@ -1637,12 +1693,55 @@ pub mod test {
                             * (burnchain.pox_constants.reward_cycle_length as u128)
                             + (burnchain.first_block_height as u128)
                     );
+
+                    // the last visible burnchain block's commits paid Alice, unless they had to
+                    // burn
+                    let tip = get_burn_block_height_snapshot(&mut peer);
+                    let block_commits = with_sortdb(&mut peer, |_, sortdb| {
+                        SortitionDB::get_block_commits_by_block(sortdb.conn(), &tip.sortition_id)
+                    })
+                    .unwrap();
+                    let pox_addrs = get_burn_block_info(&mut peer, "pox-addrs", tip.block_height)
+                        .unwrap()
+                        .expect_tuple();
+                    let addrs = pox_addrs.get("addrs").unwrap().to_owned().expect_list();
+                    let payout = pox_addrs.get("payout").unwrap().to_owned().expect_u128();
+                    if block_commits[0].all_outputs_burn()
+                        || block_commits[0].commit_outs.len() == 0
+                    {
+                        assert_eq!(addrs.len(), 0);
+                        assert_eq!(payout, 0);
+                    } else {
+                        let expected_addrs: Vec<_> = block_commits[0]
+                            .commit_outs
+                            .iter()
+                            .map(|addr| Value::Tuple(addr.as_clarity_tuple()))
+                            .collect();
+                        assert_eq!(addrs, expected_addrs);
+                        assert!(addrs.contains(&Value::Tuple(
+                            key_to_stacks_addr(&alice).as_clarity_tuple()
+                        )));
+                        assert_eq!(payout, (block_commits[0].burn_fee as u128) / 2);
+                        alice_paid = true;
+                    }
                 } else {
                     // no reward addresses
                     assert_eq!(reward_addrs.len(), 0);
                 }
             }
+
+            // get-burn-block-info? sees the burnchain up to burn-block-height
+            let tip = get_burn_block_height_snapshot(&mut peer);
+            assert_eq!(
+                get_burn_block_info(&mut peer, "header-hash", tip.block_height),
+                Some(Value::buff_from(tip.burn_header_hash.as_bytes().to_vec()).unwrap())
+            );
+            assert_eq!(
+                get_burn_block_info(&mut peer, "header-hash", tip.block_height + 1),
+                None
+            );
         }
+        assert!(alice_paid);
     }
 
     #[test]
@@ -34,6 +34,7 @@ use chainstate::burn::operations::*;
 use chainstate::stacks::db::accounts::MinerReward;
 use chainstate::stacks::db::transactions::TransactionNonceMismatch;
 use chainstate::stacks::db::*;
+use chainstate::stacks::index::marf::MarfConnection;
 use chainstate::stacks::index::MarfTrieId;
 use chainstate::stacks::Error;
 use chainstate::stacks::*;
@@ -63,7 +64,7 @@ use net::Error as net_error;
 use net::MAX_MESSAGE_LEN;
 
 use vm::types::{
-    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, SequenceData,
+    AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, SequenceData,
     StandardPrincipalData, TupleData, TypeSignature, Value,
 };
 
@@ -76,7 +77,9 @@ use vm::clarity::{ClarityBlockConnection, ClarityConnection, ClarityInstance};
 
 pub use vm::analysis::errors::{CheckError, CheckErrors};
 
-use vm::database::{BurnStateDB, ClarityDatabase, NULL_BURN_STATE_DB, NULL_HEADER_DB};
+use vm::database::{
+    BurnStateDB, ClarityDatabase, ClaritySerializable, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+};
 
 use vm::contracts::Contract;
 use vm::costs::LimitedCostTracker;
@@ -125,6 +128,23 @@ pub struct StagingUserBurnSupport {
     pub vtxindex: u32,
 }
 
+/// Where a confirmed transaction was mined, and what it did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedTransactionInfo {
+    pub txid: Txid,
+    /// the anchored block that mined or confirmed the transaction
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    /// position of the transaction in its anchored block or microblock
+    pub tx_index: u32,
+    /// set if the transaction was mined in a microblock
+    pub microblock_hash: Option<BlockHeaderHash>,
+    pub microblock_sequence: Option<u16>,
+    pub result: Value,
+    pub post_condition_aborted: bool,
+    pub execution_cost: ExecutionCost,
+}
+
 #[derive(Debug)]
 pub enum MemPoolRejection {
     SerializationFailure(net_error),
@@ -153,6 +173,15 @@ pub enum MemPoolRejection {
     BadTransactionVersion,
     TransferRecipientIsSender(PrincipalData),
     TransferAmountMustBePositive,
+    TooManyPendingTxs {
+        principal: PrincipalData,
+        max_pending: u64,
+    },
+    MemPoolFull {
+        fee_rate: f64,
+        /// the fee rate that must be exceeded to evict enough transactions, if any would do
+        min_fee_rate: Option<f64>,
+    },
     DBError(db_error),
     Other(String),
 }
@@ -198,6 +227,32 @@ impl MemPoolRejection {
                 Some(json!({"recipient": recipient.to_string()})),
             ),
             TransferAmountMustBePositive => ("TransferAmountMustBePositive", None),
+            TooManyPendingTxs {
+                principal,
+                max_pending,
+            } => (
+                "TooManyPendingTransactions",
+                Some(
+                    json!({"message": "Origin has too many transactions in the mempool",
+                            "principal": principal.to_string(),
+                            "max_pending": max_pending}),
+                ),
+            ),
+            MemPoolFull {
+                fee_rate,
+                min_fee_rate,
+            } => (
+                "MempoolFull",
+                Some(match min_fee_rate {
+                    Some(min_fee_rate) => {
+                        json!({"message": "Mempool is full of transactions with higher fee rates",
+                               "expected": min_fee_rate,
+                               "actual": fee_rate})
+                    }
+                    None => json!({"message": "Mempool is too small for the transaction",
+                                   "actual": fee_rate}),
+                }),
+            ),
             BadNonces(TransactionNonceMismatch {
                 expected,
                 actual,
@@ -369,6 +424,35 @@ impl FromRow<StagingUserBurnSupport> for StagingUserBurnSupport {
     }
 }
 
+impl FromRow<ConfirmedTransactionInfo> for ConfirmedTransactionInfo {
+    fn from_row<'a>(row: &'a Row) -> Result<ConfirmedTransactionInfo, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let microblock_hash: Option<BlockHeaderHash> = row.get_unwrap("microblock_hash");
+        let microblock_sequence: Option<u16> = row.get_unwrap("microblock_sequence");
+        let result_hex: String = row.get_unwrap("result");
+        let post_condition_aborted: bool = row.get_unwrap("post_condition_aborted");
+        let execution_cost: ExecutionCost = row.get_unwrap("cost");
+
+        let result =
+            Value::try_deserialize_hex_untyped(&result_hex).map_err(|_e| db_error::ParseError)?;
+
+        Ok(ConfirmedTransactionInfo {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            microblock_hash,
+            microblock_sequence,
+            result,
+            post_condition_aborted,
+            execution_cost,
+        })
+    }
+}
+
 impl StagingMicroblock {
     #[cfg(test)]
     pub fn try_into_microblock(self) -> Result<StacksMicroblock, StagingMicroblock> {
@@ -3256,6 +3340,19 @@ impl StacksChainState {
                 &index_block_hash
             );
             return Ok(false);
+        } else if StacksChainState::is_stacks_block_processed(
+            &self.db(),
+            consensus_hash,
+            &block.block_hash(),
+        )? {
+            // e.g. an ancestor of the block a chain state snapshot was imported at
+            debug!(
+                "Block already has a header: {}/{} ({})",
+                consensus_hash,
+                &block.block_hash(),
+                &index_block_hash
+            );
+            return Ok(false);
         }
 
         // find all user burns that supported this block
@@ -3974,12 +4071,18 @@ impl StacksChainState {
                             sender,
                             recipient,
                             transfered_ustx,
+                            memo,
                             txid,
                             burn_header_hash,
                             ..
                         } = transfer_stx_op;
                         let result = clarity_tx.connection().as_transaction(|tx| {
-                            tx.run_stx_transfer(&sender.into(), &recipient.into(), transfered_ustx)
+                            tx.run_stx_transfer(
+                                &sender.into(),
+                                &recipient.into(),
+                                transfered_ustx,
+                                &BuffData { data: memo },
+                            )
                         });
                         match result {
                             Ok((value, _, events)) => Some(StacksTransactionReceipt {
@@ -4236,6 +4339,22 @@ impl StacksChainState {
                 )
             };
 
+            // a block can't be processed on state this node no longer has, but it may still be
+            // valid
+            let parent_index_hash = StacksBlockHeader::make_index_block_hash(
+                &parent_consensus_hash,
+                &parent_block_hash,
+            );
+            if clarity_instance.with_marf(|marf| marf.is_block_pruned(&parent_index_hash))? {
+                let msg = format!(
+                    "Cannot process Stacks block {}: the state of its parent {} was pruned",
+                    block.block_hash(),
+                    &parent_index_hash
+                );
+                warn!("{}", &msg);
+                return Err(Error::PrunedStateError(msg));
+            }
+
             let (last_microblock_hash, last_microblock_seq) = if microblocks.len() > 0 {
                 let _first_mblock_hash = microblocks[0].block_hash();
                 let num_mblocks = microblocks.len();
@@ -4362,6 +4481,10 @@ impl StacksChainState {
                     &mut clarity_tx,
                     &microblocks,
                 ) {
+                    Err((Error::PrunedStateError(msg), _)) => {
+                        clarity_tx.rollback_block();
+                        return Err(Error::PrunedStateError(msg));
+                    }
                     Err((e, offending_mblock_header_hash)) => {
                         let msg = format!(
                             "Invalid Stacks microblocks {},{} (offender {}): {:?}",
@@ -4412,6 +4535,10 @@ impl StacksChainState {
             // process anchored block
             let (block_fees, block_burns, txs_receipts) =
                 match StacksChainState::process_block_transactions(&mut clarity_tx, &block) {
+                    Err(Error::PrunedStateError(msg)) => {
+                        clarity_tx.rollback_block();
+                        return Err(Error::PrunedStateError(msg));
+                    }
                     Err(e) => {
                         let msg = format!("Invalid Stacks block {}: {:?}", block.block_hash(), &e);
                         warn!("{}", &msg);
@@ -4751,6 +4878,9 @@ impl StacksChainState {
         sort_tx: &mut SortitionHandleTx,
     ) -> Result<(Option<StacksEpochReceipt>, Option<TransactionPayload>), Error> {
         let blocks_path = self.blocks_path.clone();
+        let block_limit = self.block_limit.clone();
+        let event_index = self.event_index;
+        let marf_prune_depth = self.marf_prune_depth;
         let (mut chainstate_tx, clarity_instance) = self.chainstate_tx_begin()?;
 
         // this is a transaction against both the headers and staging blocks databases!
@@ -4916,6 +5046,17 @@ impl StacksChainState {
             &user_supports,
         ) {
             Ok(next_chain_tip_info) => next_chain_tip_info,
+            Err(Error::PrunedStateError(msg)) => {
+                // this node can't process the block, but that doesn't make it invalid -- so leave
+                // it (and the microblocks it confirms) staged, and don't orphan its descendants
+                warn!(
+                    "Leaving {}/{} unprocessed: {}",
+                    &next_staging_block.consensus_hash,
+                    &block.block_hash(),
+                    &msg
+                );
+                return Err(Error::PrunedStateError(msg));
+            }
             Err(e) => {
                 // something's wrong with this epoch -- either a microblock was invalid, or the
                 // anchored block was invalid.  Either way, the anchored block will _never be_
@@ -5000,6 +5141,33 @@ impl StacksChainState {
             next_staging_block.parent_anchored_block_hash
         );
 
+        // remember what the mined transactions paid, so we can suggest fees later
+        let num_microblock_txs = next_microblocks.iter().map(|mb| mb.txs.len()).sum();
+        StacksChainState::store_fee_rates(
+            chainstate_tx.deref_mut(),
+            &epoch_receipt.header,
+            &block_limit,
+            &epoch_receipt.tx_receipts,
+            num_microblock_txs,
+        )?;
+
+        // remember where the mined transactions landed, so clients can look them up by txid
+        StacksChainState::store_txids(
+            chainstate_tx.deref_mut(),
+            &epoch_receipt.header,
+            &block,
+            &next_microblocks,
+            &epoch_receipt.tx_receipts,
+        )?;
+
+        if event_index {
+            StacksChainState::store_events(
+                chainstate_tx.deref_mut(),
+                &epoch_receipt.header,
+                &epoch_receipt.tx_receipts,
+            )?;
+        }
+
         if next_staging_block.parent_microblock_hash != EMPTY_MICROBLOCK_PARENT_HASH
             || next_staging_block.parent_microblock_seq != 0
         {
@@ -5023,9 +5191,48 @@ impl StacksChainState {
 
         chainstate_tx.commit().map_err(Error::DBError)?;
 
+        if let Some(depth) = marf_prune_depth {
+            StacksChainState::prune_clarity_state(
+                clarity_instance,
+                &epoch_receipt.header.index_block_hash(),
+                depth,
+            )?;
+        }
+
         Ok((Some(epoch_receipt), None))
     }
 
+    /// Prune the tries of the Clarity state MARF that are more than `depth` blocks below `tip`,
+    /// once at least `depth` more blocks have been processed since it was last pruned (so between
+    /// `depth` and `2 * depth` blocks of state are kept).
+    fn prune_clarity_state(
+        clarity_instance: &mut ClarityInstance,
+        tip: &StacksBlockId,
+        depth: u32,
+    ) -> Result<(), Error> {
+        clarity_instance.with_marf(|marf| {
+            let tip_height = match marf.get_block_height_of(tip, tip)? {
+                Some(height) => height,
+                None => {
+                    warn!("Could not obtain block height of {} to prune state", tip);
+                    return Ok(());
+                }
+            };
+            let horizon = tip_height.saturating_sub(depth);
+            let last_horizon = marf.get_prune_horizon()?.unwrap_or(0);
+            if horizon < last_horizon.saturating_add(depth) {
+                return Ok(());
+            }
+
+            let stats = marf.prune(tip, depth)?;
+            info!(
+                "Pruned Clarity state below height {}: {} tries, {} bytes to {} bytes",
+                stats.horizon, stats.pruned_blocks, stats.bytes_before, stats.bytes_after
+            );
+            Ok(())
+        })
+    }
+
     /// Process staging blocks at the canonical chain tip,
     ///  this only needs to be used in contexts that aren't
     ///  PoX aware (i.e., unit tests, and old stacks-node loops),
@@ -5089,6 +5296,17 @@ impl StacksChainState {
                     warn!("Encountered invalid block: {}", &msg);
                     continue;
                 }
+                Err(Error::PrunedStateError(msg)) => {
+                    // the block is valid, but this node can never process it, nor any of its
+                    // descendants -- so don't go on as if it could follow the chain
+                    error!(
+                        "Cannot process a block that needs pruned Clarity state: {}. This node \
+                         must re-sync its chain state with a larger marf_prune_depth, or with \
+                         pruning disabled",
+                        &msg
+                    );
+                    return Err(Error::PrunedStateError(msg));
+                }
                 Err(e) => {
                     error!("Unrecoverable error when processing blocks: {:?}", &e);
                     return Err(e);
@@ -5149,6 +5367,155 @@ impl StacksChainState {
         query_row(&self.db(), sql, args).map_err(Error::DBError)
     }
 
+    /// Get the header of the block at the given height in the fork ending at `tip`.
+    pub fn get_block_header_at_height(
+        conn: &StacksDBConn,
+        tip: &StacksBlockId,
+        height: u64,
+    ) -> Result<Option<StacksHeaderInfo>, Error> {
+        if height >= u32::max_value() as u64 {
+            return Ok(None);
+        }
+        StacksChainState::get_index_tip_ancestor_conn(conn, tip, height)
+    }
+
+    /// Record where each transaction in a newly-processed block and in the parent microblock
+    /// stream it confirms was mined, along with its receipt, so it can be looked up by txid.
+    pub fn store_txids(
+        tx: &mut StacksDBTx,
+        header: &StacksHeaderInfo,
+        block: &StacksBlock,
+        microblocks: &[StacksMicroblock],
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        let index_block_hash = header.index_block_hash();
+
+        let mut locations = HashMap::new();
+        for (i, block_tx) in block.txs.iter().enumerate() {
+            locations.insert(block_tx.txid(), (i, None));
+        }
+        for microblock in microblocks.iter() {
+            for (i, microblock_tx) in microblock.txs.iter().enumerate() {
+                locations.insert(microblock_tx.txid(), (i, Some(&microblock.header)));
+            }
+        }
+
+        for receipt in receipts.iter() {
+            let txid = match receipt.transaction {
+                TransactionOrigin::Stacks(ref stacks_tx) => stacks_tx.txid(),
+                TransactionOrigin::Burn(_) => {
+                    continue;
+                }
+            };
+            let (tx_index, microblock_header_opt) = match locations.get(&txid) {
+                Some(location) => location.clone(),
+                None => {
+                    warn!(
+                        "Transaction {} is not in block {} or its parent microblocks",
+                        &txid, &index_block_hash
+                    );
+                    continue;
+                }
+            };
+            let microblock_hash = microblock_header_opt.map(|header| header.block_hash());
+            let microblock_sequence = microblock_header_opt.map(|header| header.sequence);
+
+            let args: &[&dyn ToSql] = &[
+                &txid,
+                &index_block_hash,
+                &u64_to_sql(header.block_height)?,
+                &(tx_index as i64),
+                &microblock_hash,
+                &microblock_sequence,
+                &receipt.result.serialize(),
+                &receipt.post_condition_aborted,
+                &receipt.execution_cost,
+            ];
+            tx.execute(
+                "INSERT OR REPLACE INTO txids (txid, index_block_hash, block_height, tx_index, microblock_hash, microblock_sequence, result, post_condition_aborted, cost) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                args,
+            )
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        }
+        Ok(())
+    }
+
+    /// Find where a transaction was mined in the fork ending at `tip`, and what it did.
+    /// Returns None if it was not mined in that fork.
+    pub fn get_confirmed_transaction_info(
+        conn: &StacksDBConn,
+        tip: &StacksBlockId,
+        txid: &Txid,
+    ) -> Result<Option<ConfirmedTransactionInfo>, Error> {
+        let sql = "SELECT * FROM txids WHERE txid = ?1";
+        let args: &[&dyn ToSql] = &[txid];
+        let candidates: Vec<ConfirmedTransactionInfo> =
+            query_rows(conn, sql, args).map_err(Error::DBError)?;
+
+        for candidate in candidates.into_iter() {
+            let ancestor_opt = conn
+                .get_ancestor_block_hash(candidate.block_height, tip)
+                .map_err(Error::DBError)?;
+            if ancestor_opt.as_ref() == Some(&candidate.index_block_hash) {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Load a confirmed transaction from the anchored block or microblock it was mined in.
+    /// Returns None if the block or microblock is no longer available.
+    pub fn load_confirmed_transaction(
+        &self,
+        info: &ConfirmedTransactionInfo,
+    ) -> Result<Option<StacksTransaction>, Error> {
+        let mut txs = match info.microblock_hash {
+            Some(ref microblock_hash) => {
+                match StacksChainState::load_staging_microblock_bytes(&self.db(), microblock_hash)?
+                {
+                    Some(microblock_data) => {
+                        let microblock =
+                            StacksMicroblock::consensus_deserialize(&mut &microblock_data[..])
+                                .map_err(Error::NetError)?;
+                        microblock.txs
+                    }
+                    None => {
+                        return Ok(None);
+                    }
+                }
+            }
+            None => {
+                let header =
+                    match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                        &self.db(),
+                        &info.index_block_hash,
+                    )? {
+                        Some(header) => header,
+                        None => {
+                            return Ok(None);
+                        }
+                    };
+                match StacksChainState::load_block(
+                    &self.blocks_path,
+                    &header.consensus_hash,
+                    &header.anchored_header.block_hash(),
+                )? {
+                    Some(block) => block.txs,
+                    None => {
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+
+        let tx_index = info.tx_index as usize;
+        if tx_index >= txs.len() {
+            return Ok(None);
+        }
+        Ok(Some(txs.swap_remove(tx_index)))
+    }
+
     /// This runs checks for the validity of a transaction that
     ///   can be performed just by inspecting the transaction itself (i.e., without
     ///   consulting chain state).
@@ -5473,6 +5840,7 @@ pub mod test {
     use burnchains::*;
     use chainstate::burn::db::sortdb::*;
     use chainstate::burn::*;
+    use chainstate::coordinator::Error as coordinator_error;
     use std::fs;
     use util::db::Error as db_error;
     use util::db::*;
@@ -9172,6 +9540,171 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_process_block_reading_pruned_state() {
+        let privk = StacksPrivateKey::from_hex(
+            "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+        )
+        .unwrap();
+        let addr = StacksAddress::from_public_keys(
+            C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            &AddressHashMode::SerializeP2PKH,
+            1,
+            &vec![StacksPublicKey::from_private(&privk)],
+        )
+        .unwrap();
+
+        let mut peer_config =
+            TestPeerConfig::new("test_process_block_reading_pruned_state", 21315, 21316);
+        peer_config.initial_balances = vec![(addr.to_account_principal(), 1_000_000_000)];
+        let mut peer = TestPeer::new(peer_config);
+
+        let num_blocks = 6;
+        let mut first_block_id = None;
+        let mut last_block_id = None;
+        for tenure_id in 0..num_blocks {
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = match parent_opt {
+                        None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                        Some(block) => {
+                            let ic = sortdb.index_conn();
+                            let snapshot =
+                                SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                                    &ic,
+                                    &tip.sortition_id,
+                                    &block.block_hash(),
+                                )
+                                .unwrap()
+                                .unwrap(); // succeeds because we don't fork
+                            StacksChainState::get_anchored_block_header_info(
+                                chainstate.db(),
+                                &snapshot.consensus_hash,
+                                &snapshot.winning_stacks_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap()
+                        }
+                    };
+
+                    let mut block_txs = vec![make_coinbase(miner, tenure_id)];
+                    if tenure_id == num_blocks - 1 {
+                        // the last block reads the state of the first one
+                        let code = format!(
+                            "(define-constant old-balance (at-block 0x{} (stx-get-balance tx-sender)))",
+                            first_block_id.as_ref().unwrap()
+                        );
+                        block_txs.push(make_user_contract_publish(
+                            &privk,
+                            0,
+                            0,
+                            "read-old-state",
+                            &code,
+                        ));
+                    }
+
+                    let block_builder = StacksBlockBuilder::make_regtest_block_builder(
+                        &parent_tip,
+                        vrf_proof,
+                        tip.total_burn,
+                        Hash160([tenure_id as u8; 20]),
+                    )
+                    .unwrap();
+                    let (anchored_block, _size, _cost) =
+                        StacksBlockBuilder::make_anchored_block_from_txs(
+                            block_builder,
+                            chainstate,
+                            &sortdb.index_conn(),
+                            block_txs,
+                        )
+                        .unwrap();
+                    (anchored_block, vec![])
+                },
+            );
+
+            let (_, _, consensus_hash) = peer.next_burnchain_block(burn_ops.clone());
+            let block_id = StacksBlockHeader::make_index_block_hash(
+                &consensus_hash,
+                &stacks_block.block_hash(),
+            );
+
+            if tenure_id < num_blocks - 1 {
+                peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+                if first_block_id.is_none() {
+                    first_block_id = Some(block_id.clone());
+                }
+                last_block_id = Some(block_id);
+                continue;
+            }
+
+            // the node prunes the first block's state before it gets to process the last block
+            StacksChainState::prune_clarity_state(
+                &mut peer.coord.chainstate_mut().clarity_state,
+                last_block_id.as_ref().unwrap(),
+                2,
+            )
+            .unwrap();
+            assert!(peer
+                .chainstate()
+                .clarity_state
+                .with_marf(|marf| marf.is_block_pruned(first_block_id.as_ref().unwrap()))
+                .unwrap());
+
+            let sortdb = peer.sortdb.take().unwrap();
+            {
+                let ic = sortdb.index_conn();
+                let tip = SortitionDB::get_canonical_burn_chain_tip(&ic).unwrap();
+                peer.chainstate()
+                    .preprocess_stacks_epoch(&ic, &tip, &stacks_block, &microblocks)
+                    .unwrap();
+            }
+            peer.sortdb = Some(sortdb);
+
+            // processing the block fails, rather than skipping over it...
+            match peer.coord.handle_new_stacks_block() {
+                Err(coordinator_error::ChainstateError(chainstate_error::PrunedStateError(_))) => {}
+                x => panic!("Expected a pruned state error, got {:?}", x),
+            }
+
+            // ...and the block is neither processed nor orphaned, since it is valid
+            let blocks_db = peer.chainstate().db();
+            assert_eq!(
+                StacksChainState::get_staging_block_status(
+                    blocks_db,
+                    &consensus_hash,
+                    &stacks_block.block_hash()
+                )
+                .unwrap(),
+                Some(false)
+            );
+            assert!(!StacksChainState::is_block_orphaned(
+                blocks_db,
+                &consensus_hash,
+                &stacks_block.block_hash()
+            )
+            .unwrap());
+
+            let (tip_consensus_hash, tip_block_hash) =
+                SortitionDB::get_canonical_stacks_chain_tip_hash(
+                    peer.sortdb.as_ref().unwrap().conn(),
+                )
+                .unwrap();
+            assert_eq!(
+                &StacksBlockHeader::make_index_block_hash(&tip_consensus_hash, &tip_block_hash),
+                last_block_id.as_ref().unwrap()
+            );
+        }
+    }
+
     #[test]
     fn stacks_db_staging_microblocks_fork() {
         // multiple anchored blocks build off of a forked microblock stream
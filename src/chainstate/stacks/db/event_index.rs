@@ -0,0 +1,438 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
+use burnchains::Txid;
+
+use chainstate::stacks::db::*;
+use chainstate::stacks::events::{
+    FTEventType, NFTEventType, STXEventType, StacksTransactionEvent, StacksTransactionReceipt,
+};
+use chainstate::stacks::Error;
+use chainstate::stacks::*;
+
+use util::db::Error as db_error;
+use util::db::{query_rows, u64_to_sql, FromColumn, FromRow};
+
+use vm::types::{PrincipalData, QualifiedContractIdentifier};
+
+/// How many events to return per page if the caller doesn't say.
+pub const EVENT_INDEX_DEFAULT_PAGE_SIZE: u64 = 50;
+
+/// The most events that can be returned per page.
+pub const EVENT_INDEX_MAX_PAGE_SIZE: u64 = 200;
+
+/// An event emitted by a transaction in a processed block (or in the parent microblock stream it
+/// confirmed), as stored in the event index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedEvent {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub event_index: u32,
+    pub txid: Txid,
+    pub event_type: String,
+    pub contract_identifier: Option<String>,
+    /// The event as it is sent to event observers
+    pub event: serde_json::Value,
+}
+
+impl FromRow<IndexedEvent> for IndexedEvent {
+    fn from_row<'a>(row: &'a Row) -> Result<IndexedEvent, db_error> {
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let event_index: u32 = row.get_unwrap("event_index");
+        let txid = Txid::from_column(row, "txid")?;
+        let event_type: String = row.get_unwrap("event_type");
+        let contract_identifier: Option<String> = row.get_unwrap("contract_identifier");
+        let event_json: String = row.get_unwrap("event");
+
+        let event = serde_json::from_str(&event_json).map_err(|_e| db_error::ParseError)?;
+
+        Ok(IndexedEvent {
+            index_block_hash,
+            block_height,
+            event_index,
+            txid,
+            event_type,
+            contract_identifier,
+            event,
+        })
+    }
+}
+
+/// Where a page of indexed events ends: the height and event index of its last event.  Written
+/// as `{block_height}-{event_index}` in RPC requests and responses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventCursor {
+    pub block_height: u64,
+    pub event_index: u32,
+}
+
+impl EventCursor {
+    pub fn parse(cursor: &str) -> Option<EventCursor> {
+        let mut parts = cursor.splitn(2, '-');
+        let block_height = parts.next()?.parse::<u64>().ok()?;
+        let event_index = parts.next()?.parse::<u32>().ok()?;
+        Some(EventCursor {
+            block_height,
+            event_index,
+        })
+    }
+}
+
+impl fmt::Display for EventCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.block_height, self.event_index)
+    }
+}
+
+impl From<&IndexedEvent> for EventCursor {
+    fn from(event: &IndexedEvent) -> EventCursor {
+        EventCursor {
+            block_height: event.block_height,
+            event_index: event.event_index,
+        }
+    }
+}
+
+/// Which indexed events to find.  Unset fields match any event.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EventFilter {
+    /// The contract that emitted the event, or that defines the asset it's about
+    pub contract_identifier: Option<QualifiedContractIdentifier>,
+    /// The event type, as named in event observer payloads (e.g. `ft_transfer_event`)
+    pub event_type: Option<String>,
+    /// A principal that sent, received, burnt, or locked the event's asset
+    pub principal: Option<PrincipalData>,
+}
+
+/// Get the keys an event is indexed under: its type (as named in event observer payloads), the
+/// contract that emitted it or defines its asset (if any), and the principals it involves.
+pub fn get_event_keys(
+    event: &StacksTransactionEvent,
+) -> (
+    &'static str,
+    Option<&QualifiedContractIdentifier>,
+    Vec<&PrincipalData>,
+) {
+    match event {
+        StacksTransactionEvent::SmartContractEvent(event_data) => {
+            ("contract_event", Some(&event_data.key.0), vec![])
+        }
+        StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(event_data)) => (
+            "stx_transfer_event",
+            None,
+            vec![&event_data.sender, &event_data.recipient],
+        ),
+        StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(event_data)) => {
+            ("stx_mint_event", None, vec![&event_data.recipient])
+        }
+        StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(event_data)) => {
+            ("stx_burn_event", None, vec![&event_data.sender])
+        }
+        StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(event_data)) => {
+            ("stx_lock_event", None, vec![&event_data.locked_address])
+        }
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => (
+            "nft_transfer_event",
+            Some(&event_data.asset_identifier.contract_identifier),
+            vec![&event_data.sender, &event_data.recipient],
+        ),
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => (
+            "nft_mint_event",
+            Some(&event_data.asset_identifier.contract_identifier),
+            vec![&event_data.recipient],
+        ),
+        StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => (
+            "nft_burn_event",
+            Some(&event_data.asset_identifier.contract_identifier),
+            vec![&event_data.sender],
+        ),
+        StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => (
+            "ft_transfer_event",
+            Some(&event_data.asset_identifier.contract_identifier),
+            vec![&event_data.sender, &event_data.recipient],
+        ),
+        StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => (
+            "ft_mint_event",
+            Some(&event_data.asset_identifier.contract_identifier),
+            vec![&event_data.recipient],
+        ),
+        StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => (
+            "ft_burn_event",
+            Some(&event_data.asset_identifier.contract_identifier),
+            vec![&event_data.sender],
+        ),
+    }
+}
+
+impl StacksChainState {
+    /// Index the events emitted by the transactions in a newly-processed block and in the parent
+    /// microblock stream it confirms.  Events are numbered in the order they were emitted, the
+    /// same way they are numbered in the block's event observer payload.
+    pub fn store_events(
+        tx: &mut StacksDBTx,
+        header: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), Error> {
+        let index_block_hash = header.index_block_hash();
+        let block_height = u64_to_sql(header.block_height)?;
+
+        let mut event_index: u32 = 0;
+        for receipt in receipts.iter() {
+            let txid = receipt.transaction.txid();
+            let committed = !receipt.post_condition_aborted;
+            for event in receipt.events.iter() {
+                let (event_type, contract_identifier, principals) = get_event_keys(event);
+                let event_json = event.json_serialize(event_index as usize, &txid, committed);
+
+                let args: &[&dyn ToSql] = &[
+                    &index_block_hash,
+                    &block_height,
+                    &event_index,
+                    &txid,
+                    &event_type,
+                    &contract_identifier.map(|contract_id| contract_id.to_string()),
+                    &event_json.to_string(),
+                ];
+                tx.execute(
+                    "INSERT OR REPLACE INTO events (index_block_hash, block_height, event_index, txid, event_type, contract_identifier, event) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    args,
+                )
+                .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+
+                for principal in principals.into_iter() {
+                    let args: &[&dyn ToSql] = &[
+                        &principal.to_string(),
+                        &index_block_hash,
+                        &block_height,
+                        &event_index,
+                    ];
+                    tx.execute(
+                        "INSERT OR REPLACE INTO event_principals (principal, index_block_hash, block_height, event_index) VALUES (?1, ?2, ?3, ?4)",
+                        args,
+                    )
+                    .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+                }
+
+                event_index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Page through the indexed events that match `filter` and were emitted in the fork ending
+    /// at `tip`, oldest first.  Returns at most `limit` events, starting after `after` if given.
+    /// Pass the cursor of the last event returned to get the next page.
+    pub fn get_indexed_events(
+        conn: &StacksDBConn,
+        tip: &StacksBlockId,
+        filter: &EventFilter,
+        after: Option<&EventCursor>,
+        limit: u64,
+    ) -> Result<Vec<IndexedEvent>, Error> {
+        let tip_height =
+            match StacksChainState::get_stacks_block_header_info_by_index_block_hash(conn, tip)? {
+                Some(tip_header) => tip_header.block_height,
+                None => {
+                    return Ok(vec![]);
+                }
+            };
+
+        // events from every fork are indexed, so rows are read in batches of `limit` and the
+        // ones from other forks are dropped.  Rows from the same height and event index are
+        // ordered by block, so the next batch can pick up where this one left off.
+        let mut in_fork: HashMap<StacksBlockId, bool> = HashMap::new();
+        let mut position = after.map(|cursor| (cursor.block_height, cursor.event_index, None));
+        let mut events = vec![];
+
+        while (events.len() as u64) < limit {
+            let batch = StacksChainState::get_indexed_events_batch(
+                conn,
+                filter,
+                tip_height,
+                position.as_ref(),
+                limit,
+            )?;
+            let batch_len = batch.len() as u64;
+
+            for event in batch.into_iter() {
+                position = Some((
+                    event.block_height,
+                    event.event_index,
+                    Some(event.index_block_hash.clone()),
+                ));
+
+                let is_in_fork = match in_fork.get(&event.index_block_hash) {
+                    Some(is_in_fork) => *is_in_fork,
+                    None => {
+                        let ancestor_opt = conn
+                            .get_ancestor_block_hash(event.block_height, tip)
+                            .map_err(Error::DBError)?;
+                        let is_in_fork = ancestor_opt.as_ref() == Some(&event.index_block_hash);
+                        in_fork.insert(event.index_block_hash.clone(), is_in_fork);
+                        is_in_fork
+                    }
+                };
+                if is_in_fork && (events.len() as u64) < limit {
+                    events.push(event);
+                }
+            }
+
+            if batch_len < limit {
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    /// Get up to `limit` indexed events that match `filter`, at or below `max_height`, from every
+    /// fork, ordered by height, event index, and block.  If `after` is given, only events after
+    /// that position are returned; a position without a block skips every event at that height
+    /// and event index.
+    fn get_indexed_events_batch(
+        conn: &StacksDBConn,
+        filter: &EventFilter,
+        max_height: u64,
+        after: Option<&(u64, u32, Option<StacksBlockId>)>,
+        limit: u64,
+    ) -> Result<Vec<IndexedEvent>, Error> {
+        // with a principal, walk that principal's events instead of every event
+        let (mut sql, table) = match filter.principal {
+            Some(_) => ("SELECT events.* FROM event_principals JOIN events ON events.index_block_hash = event_principals.index_block_hash AND events.event_index = event_principals.event_index".to_string(), "event_principals"),
+            None => ("SELECT events.* FROM events".to_string(), "events"),
+        };
+        let mut clauses = vec![];
+        let mut args: Vec<Box<dyn ToSql>> = vec![];
+
+        if let Some(ref principal) = filter.principal {
+            args.push(Box::new(principal.to_string()));
+            clauses.push(format!("event_principals.principal = ?{}", args.len()));
+        }
+        if let Some(ref contract_identifier) = filter.contract_identifier {
+            args.push(Box::new(contract_identifier.to_string()));
+            clauses.push(format!("events.contract_identifier = ?{}", args.len()));
+        }
+        if let Some(ref event_type) = filter.event_type {
+            args.push(Box::new(event_type.clone()));
+            clauses.push(format!("events.event_type = ?{}", args.len()));
+        }
+
+        args.push(Box::new(u64_to_sql(max_height)?));
+        clauses.push(format!("{}.block_height <= ?{}", table, args.len()));
+
+        if let Some((block_height, event_index, block_opt)) = after {
+            args.push(Box::new(u64_to_sql(*block_height)?));
+            let height_arg = args.len();
+            args.push(Box::new(*event_index));
+            let index_arg = args.len();
+            let same_index_clause = match block_opt {
+                Some(block) => {
+                    args.push(Box::new(block.clone()));
+                    format!(
+                        " OR ({}.event_index = ?{} AND {}.index_block_hash > ?{})",
+                        table,
+                        index_arg,
+                        table,
+                        args.len()
+                    )
+                }
+                None => "".to_string(),
+            };
+            clauses.push(format!(
+                "({t}.block_height > ?{h} OR ({t}.block_height = ?{h} AND ({t}.event_index > ?{i}{same})))",
+                t = table,
+                h = height_arg,
+                i = index_arg,
+                same = same_index_clause
+            ));
+        }
+
+        args.push(Box::new(u64_to_sql(limit)?));
+        sql.push_str(&format!(
+            " WHERE {} ORDER BY {t}.block_height ASC, {t}.event_index ASC, {t}.index_block_hash ASC LIMIT ?{}",
+            clauses.join(" AND "),
+            args.len(),
+            t = table
+        ));
+
+        query_rows(conn, &sql, args.iter().map(|arg| arg.as_ref())).map_err(Error::DBError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chainstate::stacks::events::{FTTransferEventData, SmartContractEventData};
+    use vm::types::{AssetIdentifier, StandardPrincipalData, Value};
+
+    #[test]
+    fn event_index_keys() {
+        let contract_id =
+            QualifiedContractIdentifier::new(StandardPrincipalData(1, [0x01; 20]), "token".into());
+        let sender = PrincipalData::from(StandardPrincipalData(1, [0x02; 20]));
+        let recipient = PrincipalData::from(contract_id.clone());
+
+        let print_event = StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+            key: (contract_id.clone(), "print".to_string()),
+            value: Value::Int(1),
+        });
+        assert_eq!(
+            get_event_keys(&print_event),
+            ("contract_event", Some(&contract_id), vec![])
+        );
+
+        let transfer_event =
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(FTTransferEventData {
+                asset_identifier: AssetIdentifier {
+                    contract_identifier: contract_id.clone(),
+                    asset_name: "stackaroo".into(),
+                },
+                sender: sender.clone(),
+                recipient: recipient.clone(),
+                amount: 123,
+            }));
+        assert_eq!(
+            get_event_keys(&transfer_event),
+            (
+                "ft_transfer_event",
+                Some(&contract_id),
+                vec![&sender, &recipient]
+            )
+        );
+    }
+
+    #[test]
+    fn event_cursor_parse() {
+        let cursor = EventCursor {
+            block_height: 123,
+            event_index: 4,
+        };
+        assert_eq!(cursor.to_string(), "123-4");
+        assert_eq!(EventCursor::parse("123-4"), Some(cursor));
+
+        assert_eq!(EventCursor::parse("123"), None);
+        assert_eq!(EventCursor::parse("123-"), None);
+        assert_eq!(EventCursor::parse("-4"), None);
+        assert_eq!(EventCursor::parse("123-4-5"), None);
+    }
+}
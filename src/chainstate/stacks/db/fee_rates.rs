@@ -0,0 +1,174 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Ordering;
+
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+
+use chainstate::stacks::db::*;
+use chainstate::stacks::events::{StacksTransactionReceipt, TransactionOrigin};
+use chainstate::stacks::Error;
+use chainstate::stacks::*;
+
+use core::mempool::compute_fee_rate;
+
+use net::StacksMessageCodec;
+
+use util::db::Error as db_error;
+use util::db::{query_row_columns, u64_to_sql};
+
+use vm::costs::ExecutionCost;
+
+/// How many of the most recent anchored blocks (along with the microblock streams they confirm)
+/// to consult when suggesting fee rates.
+pub const FEE_RATE_HISTORY_WINDOW: u64 = 10;
+
+/// Fee rates (as computed by `core::mempool::compute_fee_rate`) that a transaction should pay to
+/// be mined about as readily as the cheapest quarter, the median, and the most expensive quarter
+/// of recently-mined transactions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeRateEstimate {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+impl FeeRateEstimate {
+    /// Make an estimate from a list of observed fee rates.  Returns None if the list is empty.
+    pub fn from_fee_rates(mut fee_rates: Vec<f64>) -> Option<FeeRateEstimate> {
+        if fee_rates.len() == 0 {
+            return None;
+        }
+        fee_rates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let percentile = |p: usize| fee_rates[(fee_rates.len() - 1) * p / 100];
+        Some(FeeRateEstimate {
+            low: percentile(25),
+            medium: percentile(50),
+            high: percentile(75),
+        })
+    }
+}
+
+impl StacksChainState {
+    /// Record the fee rate of every fee-paying transaction mined in a newly-processed block and
+    /// in the parent microblock stream it confirms.  `receipts` are the block's transaction
+    /// receipts, the last `num_microblock_txs` of which are for microblock transactions.
+    pub fn store_fee_rates(
+        tx: &mut StacksDBTx,
+        header: &StacksHeaderInfo,
+        block_limit: &ExecutionCost,
+        receipts: &[StacksTransactionReceipt],
+        num_microblock_txs: usize,
+    ) -> Result<(), Error> {
+        let index_block_hash = header.index_block_hash();
+        let first_microblock_tx = receipts.len().saturating_sub(num_microblock_txs);
+
+        for (i, receipt) in receipts.iter().enumerate() {
+            let stacks_tx = match receipt.transaction {
+                TransactionOrigin::Stacks(ref stacks_tx) => stacks_tx,
+                TransactionOrigin::Burn(_) => {
+                    continue;
+                }
+            };
+            let tx_fee = stacks_tx.get_tx_fee();
+            if tx_fee == 0 {
+                continue;
+            }
+            if let TransactionPayload::Coinbase(..) = stacks_tx.payload {
+                continue;
+            }
+
+            let tx_len = stacks_tx.serialize_to_vec().len() as u64;
+            let fee_rate = compute_fee_rate(tx_fee, tx_len, &receipt.execution_cost, block_limit);
+
+            let args: &[&dyn ToSql] = &[
+                &index_block_hash,
+                &u64_to_sql(header.block_height)?,
+                &stacks_tx.txid(),
+                &(i >= first_microblock_tx),
+                &fee_rate,
+            ];
+            tx.execute(
+                "INSERT OR REPLACE INTO fee_rates (index_block_hash, block_height, txid, microblock, fee_rate) VALUES (?1, ?2, ?3, ?4, ?5)",
+                args,
+            )
+            .map_err(|e| Error::DBError(db_error::SqliteError(e)))?;
+        }
+        Ok(())
+    }
+
+    /// Get the fee rates of the transactions mined in the given block and the parent microblock
+    /// stream it confirmed.
+    pub fn get_fee_rates_at_block(
+        conn: &Connection,
+        block_id: &StacksBlockId,
+    ) -> Result<Vec<f64>, Error> {
+        let sql = "SELECT fee_rate FROM fee_rates WHERE index_block_hash = ?1".to_string();
+        let args: &[&dyn ToSql] = &[block_id];
+        query_row_columns(conn, &sql, args, "fee_rate").map_err(Error::DBError)
+    }
+
+    /// Suggest fee rates from the transactions mined in the last `window` blocks of the fork
+    /// ending at `tip`.  Returns None if no fee-paying transactions were mined in that span.
+    pub fn get_fee_rate_estimate(
+        conn: &Connection,
+        tip: &StacksBlockId,
+        window: u64,
+    ) -> Result<Option<FeeRateEstimate>, Error> {
+        let mut fee_rates = vec![];
+        let mut block_id = tip.clone();
+        for _ in 0..window {
+            fee_rates.append(&mut StacksChainState::get_fee_rates_at_block(
+                conn, &block_id,
+            )?);
+            block_id = match StacksChainState::get_parent_block_id(conn, &block_id)? {
+                Some(parent_id) => parent_id,
+                None => {
+                    break;
+                }
+            };
+        }
+        Ok(FeeRateEstimate::from_fee_rates(fee_rates))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fee_rate_estimate_percentiles() {
+        assert_eq!(FeeRateEstimate::from_fee_rates(vec![]), None);
+        assert_eq!(
+            FeeRateEstimate::from_fee_rates(vec![3.0]),
+            Some(FeeRateEstimate {
+                low: 3.0,
+                medium: 3.0,
+                high: 3.0
+            })
+        );
+        assert_eq!(
+            FeeRateEstimate::from_fee_rates(vec![9.0, 1.0, 5.0, 3.0, 7.0]),
+            Some(FeeRateEstimate {
+                low: 3.0,
+                medium: 5.0,
+                high: 7.0
+            })
+        );
+    }
+}
@@ -17,7 +17,10 @@
 pub mod accounts;
 pub mod blocks;
 pub mod contracts;
+pub mod event_index;
+pub mod fee_rates;
 pub mod headers;
+pub mod snapshot;
 pub mod transactions;
 pub mod unconfirmed;
 
@@ -106,6 +109,10 @@ pub struct StacksChainState {
     pub root_path: String,
     pub block_limit: ExecutionCost,
     pub unconfirmed_state: Option<UnconfirmedState>,
+    /// whether or not to index the events emitted by processed blocks (see `event_index`)
+    pub event_index: bool,
+    /// if set, prune the Clarity state of blocks more than this many blocks below the chain tip
+    pub marf_prune_depth: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -453,7 +460,7 @@ pub struct BlockStreamData {
     num_mblocks_ptr: usize,
 }
 
-pub const CHAINSTATE_VERSION: &'static str = "1";
+pub const CHAINSTATE_VERSION: &'static str = "4";
 
 const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     "PRAGMA foreign_keys = ON;",
@@ -602,6 +609,69 @@ const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     );"#,
 ];
 
+// version 2: fee rates of mined transactions
+const CHAINSTATE_SCHEMA_2: &'static [&'static str] = &[r#"
+    -- fee rates paid by the transactions mined in each processed block and in the parent
+    -- microblock stream it confirmed.  Used only for fee estimation.
+    CREATE TABLE fee_rates(
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        txid TEXT NOT NULL,
+        microblock INTEGER NOT NULL,        -- 1 if the transaction was mined in a microblock
+        fee_rate REAL NOT NULL,             -- see core::mempool::compute_fee_rate
+        PRIMARY KEY(index_block_hash,txid)
+    );"#];
+
+// version 3: where each mined transaction is, and its receipt
+const CHAINSTATE_SCHEMA_3: &'static [&'static str] = &[
+    r#"
+    -- where each transaction in each processed block (and in the parent microblock stream it
+    -- confirmed) was mined, and what it did.  A transaction can be mined in more than one fork.
+    CREATE TABLE txids(
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,     -- the anchored block that was processed
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,          -- position in the anchored block or microblock
+        microblock_hash TEXT,               -- set if the transaction was mined in a microblock
+        microblock_sequence INTEGER,
+        result TEXT NOT NULL,               -- hex-encoded, consensus-serialized Clarity value
+        post_condition_aborted INTEGER NOT NULL,
+        cost TEXT NOT NULL,
+        PRIMARY KEY(txid,index_block_hash)
+    );"#,
+    "CREATE INDEX txids_by_block ON txids(index_block_hash);",
+];
+
+// version 4: the event index.  Only populated if the event index is enabled.
+const CHAINSTATE_SCHEMA_4: &'static [&'static str] = &[
+    r#"
+    -- events emitted by the transactions in each processed block (and in the parent microblock
+    -- stream it confirmed).
+    CREATE TABLE events(
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        event_index INTEGER NOT NULL,       -- position among all of the block's events
+        txid TEXT NOT NULL,
+        event_type TEXT NOT NULL,           -- as named in event observer payloads
+        contract_identifier TEXT,           -- contract that emitted the event or defines its asset
+        event TEXT NOT NULL,                -- JSON, as sent to event observers
+        PRIMARY KEY(index_block_hash,event_index)
+    );"#,
+    r#"
+    -- principals that sent, received, burnt, or locked the asset in each indexed event
+    CREATE TABLE event_principals(
+        principal TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        event_index INTEGER NOT NULL,
+        PRIMARY KEY(principal,index_block_hash,event_index)
+    );"#,
+    "CREATE INDEX events_by_height ON events(block_height,event_index);",
+    "CREATE INDEX events_by_contract ON events(contract_identifier,block_height,event_index);",
+    "CREATE INDEX events_by_type ON events(event_type,block_height,event_index);",
+    "CREATE INDEX event_principals_by_principal ON event_principals(principal,block_height,event_index);",
+];
+
 #[cfg(test)]
 pub const MINER_REWARD_MATURITY: u64 = 2; // small for testing purposes
 
@@ -709,6 +779,18 @@ impl StacksChainState {
                 tx.execute_batch(cmd)?;
             }
 
+            for cmd in CHAINSTATE_SCHEMA_2 {
+                tx.execute_batch(cmd)?;
+            }
+
+            for cmd in CHAINSTATE_SCHEMA_3 {
+                tx.execute_batch(cmd)?;
+            }
+
+            for cmd in CHAINSTATE_SCHEMA_4 {
+                tx.execute_batch(cmd)?;
+            }
+
             tx.execute(
                 "INSERT INTO db_config (version,mainnet,chain_id) VALUES (?1,?2,?3)",
                 &[
@@ -735,7 +817,7 @@ impl StacksChainState {
             // instantiate!
             StacksChainState::instantiate_db(mainnet, chain_id, index_path)
         } else {
-            let marf = StacksChainState::open_index(index_path)?;
+            let mut marf = StacksChainState::open_index(index_path)?;
             // sanity check
             let db_config = query_row::<DBConfig, _>(
                 marf.sqlite_conn(),
@@ -752,14 +834,6 @@ impl StacksChainState {
                 return Err(Error::InvalidChainstateDB);
             }
 
-            if db_config.version != CHAINSTATE_VERSION {
-                error!(
-                    "Invalid chain state database: expected version = {}, got {}",
-                    CHAINSTATE_VERSION, db_config.version
-                );
-                return Err(Error::InvalidChainstateDB);
-            }
-
             if db_config.chain_id != chain_id {
                 error!(
                     "Invalid chain ID: expected {}, got {}",
@@ -768,10 +842,49 @@ impl StacksChainState {
                 return Err(Error::InvalidChainstateDB);
             }
 
+            if db_config.version != CHAINSTATE_VERSION {
+                StacksChainState::migrate_db(&mut marf, &db_config.version)?;
+            }
+
             Ok(marf)
         }
     }
 
+    /// Upgrade a chain state database from `version` to `CHAINSTATE_VERSION`, by applying the
+    /// schema changes of each version in between.
+    fn migrate_db(marf: &mut MARF<StacksBlockId>, version: &str) -> Result<(), Error> {
+        let dbtx = StacksDBTx::new(marf, ());
+        {
+            let tx = dbtx.tx();
+            let mut version = version.to_string();
+            while version != CHAINSTATE_VERSION {
+                let (schema, next_version) = match version.as_str() {
+                    "1" => (CHAINSTATE_SCHEMA_2, "2"),
+                    "2" => (CHAINSTATE_SCHEMA_3, "3"),
+                    "3" => (CHAINSTATE_SCHEMA_4, "4"),
+                    _ => {
+                        error!(
+                            "Invalid chain state database: expected version = {}, got {}",
+                            CHAINSTATE_VERSION, version
+                        );
+                        return Err(Error::InvalidChainstateDB);
+                    }
+                };
+                info!(
+                    "Migrating chain state database from version {} to {}",
+                    version, next_version
+                );
+                for cmd in schema {
+                    tx.execute_batch(cmd)?;
+                }
+                version = next_version.to_string();
+            }
+            tx.execute("UPDATE db_config SET version = ?1", &[&CHAINSTATE_VERSION])?;
+        }
+        dbtx.commit()?;
+        Ok(())
+    }
+
     pub fn open_index(marf_path: &str) -> Result<MARF<StacksBlockId>, Error> {
         test_debug!("Open MARF index at {}", marf_path);
         let marf =
@@ -1274,7 +1387,10 @@ impl StacksChainState {
     /// Re-open the chainstate -- i.e. to get a new handle to it using an existing chain state's
     /// parameters
     pub fn reopen(&self) -> Result<(StacksChainState, Vec<StacksTransactionReceipt>), Error> {
-        StacksChainState::open(self.mainnet, self.chain_id, &self.root_path)
+        let (mut chainstate, receipts) =
+            StacksChainState::open(self.mainnet, self.chain_id, &self.root_path)?;
+        chainstate.inherit_clarity_params(self);
+        Ok((chainstate, receipts))
     }
 
     /// Re-open the chainstate -- i.e. to get a new handle to it using an existing chain state's
@@ -1283,7 +1399,23 @@ impl StacksChainState {
         &self,
         budget: ExecutionCost,
     ) -> Result<(StacksChainState, Vec<StacksTransactionReceipt>), Error> {
-        StacksChainState::open_and_exec(self.mainnet, self.chain_id, &self.root_path, None, budget)
+        let (mut chainstate, receipts) = StacksChainState::open_and_exec(
+            self.mainnet,
+            self.chain_id,
+            &self.root_path,
+            None,
+            budget,
+        )?;
+        chainstate.inherit_clarity_params(self);
+        Ok((chainstate, receipts))
+    }
+
+    /// Carry over the Clarity parameters of the handle this one was re-opened from.  The miner
+    /// builds blocks on a re-opened handle, so it must activate Clarity 2 at the same height as
+    /// the handle that validates them.
+    fn inherit_clarity_params(&mut self, other: &StacksChainState) {
+        self.clarity_state
+            .set_clarity_2_activation_height(other.clarity_state.get_clarity_2_activation_height());
     }
 
     pub fn open_testnet<F>(
@@ -1304,13 +1436,8 @@ impl StacksChainState {
         StacksChainState::open_and_exec(mainnet, chain_id, path_str, None, block_limit)
     }
 
-    pub fn open_and_exec(
-        mainnet: bool,
-        chain_id: u32,
-        path_str: &str,
-        boot_data: Option<&mut ChainStateBootData>,
-        block_limit: ExecutionCost,
-    ) -> Result<(StacksChainState, Vec<StacksTransactionReceipt>), Error> {
+    /// The directory under `path_str` that holds the chain state of the given network
+    fn chain_path(mainnet: bool, chain_id: u32, path_str: &str) -> PathBuf {
         let mut path = PathBuf::from(path_str);
 
         let chain_id_str = if mainnet {
@@ -1320,6 +1447,17 @@ impl StacksChainState {
         };
 
         path.push(chain_id_str);
+        path
+    }
+
+    pub fn open_and_exec(
+        mainnet: bool,
+        chain_id: u32,
+        path_str: &str,
+        boot_data: Option<&mut ChainStateBootData>,
+        block_limit: ExecutionCost,
+    ) -> Result<(StacksChainState, Vec<StacksTransactionReceipt>), Error> {
+        let path = StacksChainState::chain_path(mainnet, chain_id, path_str);
         StacksChainState::mkdirs(&path)?;
 
         let mut blocks_path = path.clone();
@@ -1387,6 +1525,8 @@ impl StacksChainState {
             root_path: path_str.to_string(),
             block_limit: block_limit,
             unconfirmed_state: None,
+            event_index: false,
+            marf_prune_depth: None,
         };
 
         let mut receipts = vec![];
@@ -2023,6 +2163,51 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_migrate_chainstate_db() {
+        let chainstate = instantiate_chainstate(false, 0x80000000, "migrate-chainstate-db");
+
+        // roll the database back to version 1
+        chainstate
+            .db()
+            .execute_batch(
+                "DROP TABLE fee_rates;
+                DROP TABLE txids;
+                DROP TABLE events;
+                DROP TABLE event_principals;
+                UPDATE db_config SET version = '1';",
+            )
+            .unwrap();
+        drop(chainstate);
+
+        let chainstate = open_chainstate(false, 0x80000000, "migrate-chainstate-db");
+        let version: String = chainstate
+            .db()
+            .query_row("SELECT version FROM db_config", NO_PARAMS, |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CHAINSTATE_VERSION);
+        for table in ["fee_rates", "txids", "events", "event_principals"].iter() {
+            let count: i64 = chainstate
+                .db()
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM {}", table),
+                    NO_PARAMS,
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(count, 0);
+        }
+
+        // versions this code doesn't know about can't be opened
+        chainstate
+            .db()
+            .execute("UPDATE db_config SET version = '0'", NO_PARAMS)
+            .unwrap();
+        drop(chainstate);
+        let path = chainstate_path("migrate-chainstate-db");
+        assert!(StacksChainState::open(false, 0x80000000, &path).is_err());
+    }
+
     #[test]
     fn test_chainstate_sampled_genesis_consistency() {
         // Test root hash for the test chainstate data set
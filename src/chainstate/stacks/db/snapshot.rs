@@ -0,0 +1,714 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshots of the chain state at a block.
+//!
+//! A snapshot holds what a new node needs to process the block's descendants without having
+//! processed the block and its ancestors itself. It is written as:
+//!
+//! * the block's consensus hash and header,
+//! * the network of the chain state, as its mainnet flag and chain ID,
+//! * the Clarity state at the block (see `MarfedKV::export_snapshot()`),
+//! * the number of headers, and the headers of the block and its ancestors from the boot block
+//!   up, with their parents and costs,
+//! * the number of miner payments, and the miner payments of those blocks, and
+//! * the tries of the headers index at the block (see `index::snapshot::export_snapshot()`).
+//!
+//! The Clarity state must hash to the header's state root and the headers index to the header's
+//! index root, and each ancestor's header must be the one that the headers index names at its
+//! height. Contract metadata is rebuilt from the contracts' sources, which must hash to the
+//! contract hashes in the Clarity state (see `MarfedKV::import_snapshot()`); only the values of
+//! contract constants and token supplies, which publishing a contract evaluates, are taken from
+//! the snapshot after checking their types. The burnchain data of the ancestors' headers and the
+//! miner payments are taken as they are in the snapshot, so snapshots should come from a trusted
+//! node. The ancestors' blocks and their Clarity state are not part of a snapshot.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use chainstate::burn::ConsensusHash;
+use chainstate::stacks::db::blocks::StagingUserBurnSupport;
+use chainstate::stacks::db::*;
+use chainstate::stacks::index::marf::MarfConnection;
+use chainstate::stacks::Error;
+use chainstate::stacks::*;
+
+use net::codec::{read_next, write_next};
+use net::Error as net_error;
+use net::StacksMessageCodec;
+
+use util::db::query_rows;
+use util::db::Error as db_error;
+
+use vm::costs::ExecutionCost;
+use vm::database::marf::MarfedKV;
+
+/// A header of a block in a snapshot, with what the headers database keeps about it
+struct SnapshotHeader {
+    header_info: StacksHeaderInfo,
+    parent_block_id: StacksBlockId,
+    anchored_block_cost: ExecutionCost,
+}
+
+/// A miner payment of a block in a snapshot
+struct SnapshotPayment(MinerPaymentSchedule);
+
+fn write_u128<W: Write>(fd: &mut W, value: u128) -> Result<(), net_error> {
+    write_next(fd, &((value >> 64) as u64))?;
+    write_next(fd, &(value as u64))
+}
+
+fn read_u128<R: Read>(fd: &mut R) -> Result<u128, net_error> {
+    let high: u64 = read_next(fd)?;
+    let low: u64 = read_next(fd)?;
+    Ok(((high as u128) << 64) | (low as u128))
+}
+
+impl StacksMessageCodec for SnapshotHeader {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        let header_info = &self.header_info;
+        write_next(fd, &header_info.consensus_hash)?;
+        write_next(fd, &header_info.anchored_header)?;
+        write_next(fd, &header_info.index_root)?;
+        write_next(fd, &header_info.burn_header_hash)?;
+        write_next(fd, &header_info.burn_header_height)?;
+        write_next(fd, &header_info.burn_header_timestamp)?;
+        write_next(fd, &header_info.anchored_block_size)?;
+        write_next(fd, &self.parent_block_id)?;
+
+        let cost = &self.anchored_block_cost;
+        write_next(fd, &cost.write_length)?;
+        write_next(fd, &cost.write_count)?;
+        write_next(fd, &cost.read_length)?;
+        write_next(fd, &cost.read_count)?;
+        write_next(fd, &cost.runtime)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<SnapshotHeader, net_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let anchored_header: StacksBlockHeader = read_next(fd)?;
+        let index_root: TrieHash = read_next(fd)?;
+        let burn_header_hash: BurnchainHeaderHash = read_next(fd)?;
+        let burn_header_height: u32 = read_next(fd)?;
+        let burn_header_timestamp: u64 = read_next(fd)?;
+        let anchored_block_size: u64 = read_next(fd)?;
+        let parent_block_id: StacksBlockId = read_next(fd)?;
+
+        let anchored_block_cost = ExecutionCost {
+            write_length: read_next(fd)?,
+            write_count: read_next(fd)?,
+            read_length: read_next(fd)?,
+            read_count: read_next(fd)?,
+            runtime: read_next(fd)?,
+        };
+
+        let block_height = anchored_header.total_work.work;
+        Ok(SnapshotHeader {
+            header_info: StacksHeaderInfo {
+                anchored_header,
+                microblock_tail: None,
+                block_height,
+                index_root,
+                consensus_hash,
+                burn_header_hash,
+                burn_header_height,
+                burn_header_timestamp,
+                anchored_block_size,
+            },
+            parent_block_id,
+            anchored_block_cost,
+        })
+    }
+}
+
+impl StacksMessageCodec for SnapshotPayment {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), net_error> {
+        let payment = &self.0;
+        write_next(fd, &payment.address)?;
+        write_next(fd, &payment.block_hash)?;
+        write_next(fd, &payment.consensus_hash)?;
+        write_next(fd, &payment.parent_block_hash)?;
+        write_next(fd, &payment.parent_consensus_hash)?;
+        write_u128(fd, payment.coinbase)?;
+        write_u128(fd, payment.tx_fees_anchored)?;
+        write_u128(fd, payment.tx_fees_streamed)?;
+        write_u128(fd, payment.stx_burns)?;
+        write_next(fd, &payment.burnchain_commit_burn)?;
+        write_next(fd, &payment.burnchain_sortition_burn)?;
+        write_next(fd, &(if payment.miner { 1u8 } else { 0u8 }))?;
+        write_next(fd, &payment.stacks_block_height)?;
+        write_next(fd, &payment.vtxindex)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<SnapshotPayment, net_error> {
+        Ok(SnapshotPayment(MinerPaymentSchedule {
+            address: read_next(fd)?,
+            block_hash: read_next(fd)?,
+            consensus_hash: read_next(fd)?,
+            parent_block_hash: read_next(fd)?,
+            parent_consensus_hash: read_next(fd)?,
+            coinbase: read_u128(fd)?,
+            tx_fees_anchored: read_u128(fd)?,
+            tx_fees_streamed: read_u128(fd)?,
+            stx_burns: read_u128(fd)?,
+            burnchain_commit_burn: read_next(fd)?,
+            burnchain_sortition_burn: read_next(fd)?,
+            miner: read_next::<u8, _>(fd)? != 0,
+            stacks_block_height: read_next(fd)?,
+            vtxindex: read_next(fd)?,
+        }))
+    }
+}
+
+fn corrupt_snapshot(msg: String) -> Error {
+    error!("Invalid chain state snapshot: {}", msg);
+    Error::DBError(db_error::Corruption)
+}
+
+impl StacksChainState {
+    /// Get the headers of a block and all of its ancestors, from the boot block up
+    fn get_snapshot_headers(
+        conn: &DBConn,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<Vec<SnapshotHeader>, Error> {
+        let mut headers = vec![];
+        let mut block_id = index_block_hash.clone();
+        loop {
+            let header_info = StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                conn, &block_id,
+            )?
+            .ok_or(Error::NoSuchBlockError)?;
+            let parent_block_id = StacksChainState::get_parent_block_id(conn, &block_id)?
+                .ok_or(Error::NoSuchBlockError)?;
+            let anchored_block_cost =
+                StacksChainState::get_stacks_block_anchored_cost(conn, &block_id)?
+                    .ok_or(Error::NoSuchBlockError)?;
+
+            let block_height = header_info.block_height;
+            headers.push(SnapshotHeader {
+                header_info,
+                parent_block_id: parent_block_id.clone(),
+                anchored_block_cost,
+            });
+            if block_height == 0 {
+                break;
+            }
+            block_id = parent_block_id;
+        }
+        headers.reverse();
+        Ok(headers)
+    }
+
+    /// Write a snapshot of the chain state at a processed block, for a new node to start its
+    /// chain state from with `import_chainstate_snapshot()`.
+    pub fn export_chainstate_snapshot<W: Write>(
+        &mut self,
+        index_block_hash: &StacksBlockId,
+        fd: &mut W,
+    ) -> Result<(), Error> {
+        let headers = StacksChainState::get_snapshot_headers(self.db(), index_block_hash)?;
+        let mut payments = vec![];
+        for header in headers.iter() {
+            let qry = "SELECT * FROM payments WHERE index_block_hash = ?1 ORDER BY vtxindex ASC";
+            let args: &[&dyn ToSql] = &[&header.header_info.index_block_hash()];
+            let rows = query_rows::<MinerPaymentSchedule, _>(self.db(), qry, args)?;
+            payments.extend(rows.into_iter().map(SnapshotPayment));
+        }
+
+        let tip = &headers[headers.len() - 1].header_info;
+        write_next(fd, &tip.consensus_hash)?;
+        write_next(fd, &tip.anchored_header)?;
+        write_next(fd, &(if self.mainnet { 1u8 } else { 0u8 }))?;
+        write_next(fd, &self.chain_id)?;
+
+        self.clarity_state
+            .with_marf(|marf| MarfedKV::export_snapshot(marf, index_block_hash, fd))?;
+
+        write_next(fd, &(headers.len() as u32))?;
+        for header in headers.iter() {
+            write_next(fd, header)?;
+        }
+        write_next(fd, &(payments.len() as u32))?;
+        for payment in payments.iter() {
+            write_next(fd, payment)?;
+        }
+
+        self.state_index.export_snapshot(index_block_hash, fd)?;
+        Ok(())
+    }
+
+    /// Create the chain state of the given network under `path_str`, where `open()` will find
+    /// it, from a snapshot of the chain state at the block `index_block_hash`. There must not be
+    /// any chain state there yet. The snapshot's header must be the block's header, and its
+    /// state and headers index must hash to the header's state and index roots. Returns the
+    /// block's header.
+    pub fn import_chainstate_snapshot<R: Read>(
+        mainnet: bool,
+        chain_id: u32,
+        path_str: &str,
+        index_block_hash: &StacksBlockId,
+        fd: &mut R,
+    ) -> Result<StacksHeaderInfo, Error> {
+        let mut vm_path = StacksChainState::chain_path(mainnet, chain_id, path_str);
+        vm_path.push("vm");
+        if fs::metadata(&vm_path).is_ok() {
+            error!(
+                "Cannot import a chain state snapshot into {:?}, which already has chain state",
+                &vm_path
+            );
+            return Err(Error::DBError(db_error::ExistsError));
+        }
+        StacksChainState::mkdirs(&vm_path)?;
+
+        let result = StacksChainState::inner_import_chainstate_snapshot(
+            mainnet,
+            chain_id,
+            vm_path.clone(),
+            index_block_hash,
+            fd,
+        );
+        if result.is_err() {
+            // leave nothing behind for open() to mistake for chain state
+            if let Err(e) = fs::remove_dir_all(&vm_path) {
+                warn!("Failed to remove {:?}: {:?}", &vm_path, &e);
+            }
+        }
+        result
+    }
+
+    fn inner_import_chainstate_snapshot<R: Read>(
+        mainnet: bool,
+        chain_id: u32,
+        vm_path: PathBuf,
+        index_block_hash: &StacksBlockId,
+        fd: &mut R,
+    ) -> Result<StacksHeaderInfo, Error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let header: StacksBlockHeader = read_next(fd)?;
+        let header_index_block_hash = header.index_block_hash(&consensus_hash);
+        if header_index_block_hash != *index_block_hash {
+            return Err(Error::InvalidStacksBlock(format!(
+                "Snapshot is of block {}, not {}",
+                header_index_block_hash, index_block_hash
+            )));
+        }
+
+        let snapshot_mainnet = read_next::<u8, _>(fd)? != 0;
+        let snapshot_chain_id: u32 = read_next(fd)?;
+        if snapshot_mainnet != mainnet || snapshot_chain_id != chain_id {
+            error!(
+                "Invalid chain state snapshot: expected mainnet = {} and chain ID {}, got {} and {}",
+                mainnet, chain_id, snapshot_mainnet, snapshot_chain_id
+            );
+            return Err(Error::InvalidChainstateDB);
+        }
+
+        let mut clarity_path = vm_path.clone();
+        clarity_path.push("clarity");
+        let clarity_path_str = clarity_path
+            .to_str()
+            .ok_or_else(|| Error::DBError(db_error::ParseError))?;
+        MarfedKV::import_snapshot(
+            clarity_path_str,
+            index_block_hash,
+            &header.state_index_root,
+            mainnet,
+            fd,
+        )?;
+
+        let num_headers: u32 = read_next(fd)?;
+        let mut headers = vec![];
+        for _ in 0..num_headers {
+            headers.push(read_next::<SnapshotHeader, _>(fd)?);
+        }
+        let num_payments: u32 = read_next(fd)?;
+        let mut payments = vec![];
+        for _ in 0..num_payments {
+            payments.push(read_next::<SnapshotPayment, _>(fd)?.0);
+        }
+
+        let tip = match headers.last() {
+            Some(tip) if tip.header_info.index_block_hash() == *index_block_hash => {
+                tip.header_info.clone()
+            }
+            _ => {
+                return Err(corrupt_snapshot(format!(
+                    "headers do not end at {}",
+                    index_block_hash
+                )));
+            }
+        };
+
+        let mut index_path = vm_path;
+        index_path.push("index");
+        let index_path_str = index_path
+            .to_str()
+            .ok_or_else(|| Error::DBError(db_error::ParseError))?;
+        let mut state_index = StacksChainState::instantiate_db(mainnet, chain_id, index_path_str)?;
+        state_index.import_snapshot(fd, index_block_hash, &tip.index_root)?;
+
+        // the headers index vouches for each ancestor at its height
+        let tip_marf_height = state_index
+            .get_block_height_of(index_block_hash, index_block_hash)?
+            .ok_or_else(|| corrupt_snapshot(format!("no height for {}", index_block_hash)))?;
+        if (tip_marf_height as u64) < tip.block_height {
+            return Err(corrupt_snapshot(format!(
+                "{} is at height {} in the headers index, below its block height {}",
+                index_block_hash, tip_marf_height, tip.block_height
+            )));
+        }
+        let marf_height_offset = tip_marf_height as u64 - tip.block_height;
+
+        let mut block_ids = vec![];
+        for (height, header) in headers.iter().enumerate() {
+            let header_info = &header.header_info;
+            let block_id = header_info.index_block_hash();
+            if header_info.block_height != height as u64
+                || header_info.burn_header_timestamp >= i64::max_value() as u64
+            {
+                return Err(corrupt_snapshot(format!(
+                    "header of {} is not valid at height {}",
+                    block_id, height
+                )));
+            }
+            if height > 0 && header.parent_block_id != block_ids[height - 1] {
+                return Err(corrupt_snapshot(format!(
+                    "parent of {} is {}, not {}",
+                    block_id,
+                    header.parent_block_id,
+                    block_ids[height - 1]
+                )));
+            }
+
+            let marf_height = (height as u64 + marf_height_offset) as u32;
+            if state_index.get_bhh_at_height(index_block_hash, marf_height)?
+                != Some(block_id.clone())
+                || state_index.get_root_hash_at(&block_id)? != header_info.index_root
+            {
+                return Err(corrupt_snapshot(format!(
+                    "header of {} is not the one in the headers index at height {}",
+                    block_id, height
+                )));
+            }
+            block_ids.push(block_id);
+        }
+
+        // each block's miner payment, and the user burns that supported it
+        let mut schedules: HashMap<StacksBlockId, (Option<MinerPaymentSchedule>, Vec<_>)> =
+            HashMap::new();
+        for payment in payments.into_iter() {
+            let block_id = StacksBlockHeader::make_index_block_hash(
+                &payment.consensus_hash,
+                &payment.block_hash,
+            );
+            if block_ids.get(payment.stacks_block_height as usize) != Some(&block_id)
+                || payment.burnchain_commit_burn >= i64::max_value() as u64
+                || payment.burnchain_sortition_burn >= i64::max_value() as u64
+            {
+                return Err(corrupt_snapshot(format!(
+                    "payment for {} is not valid at height {}",
+                    block_id, payment.stacks_block_height
+                )));
+            }
+
+            let schedule = schedules.entry(block_id.clone()).or_insert((None, vec![]));
+            if payment.miner {
+                if schedule.0.is_some() {
+                    return Err(corrupt_snapshot(format!(
+                        "{} has more than one miner payment",
+                        block_id
+                    )));
+                }
+                schedule.0 = Some(payment);
+            } else {
+                schedule.1.push(StagingUserBurnSupport {
+                    consensus_hash: payment.consensus_hash,
+                    anchored_block_hash: payment.block_hash,
+                    address: payment.address,
+                    burn_amount: payment.burnchain_commit_burn,
+                    vtxindex: payment.vtxindex,
+                });
+            }
+        }
+
+        let mut tx = StacksDBTx::new(&mut state_index, ());
+        for header in headers.iter() {
+            StacksChainState::insert_stacks_block_header(
+                &mut tx,
+                &header.parent_block_id,
+                &header.header_info,
+                &header.anchored_block_cost,
+            )?;
+        }
+        for block_id in block_ids.iter() {
+            match schedules.remove(block_id) {
+                Some((Some(miner_payment), user_burns)) => {
+                    StacksChainState::insert_miner_payment_schedule(
+                        &mut tx,
+                        &miner_payment,
+                        &user_burns,
+                    )?;
+                }
+                Some((None, _)) => {
+                    return Err(corrupt_snapshot(format!(
+                        "{} has user burns but no miner payment",
+                        block_id
+                    )));
+                }
+                None => {}
+            }
+        }
+        tx.commit()?;
+
+        info!(
+            "Imported chain state at {} (height {}) with state root {}",
+            index_block_hash, tip.block_height, tip.anchored_header.state_index_root
+        );
+        Ok(tip)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    use chainstate::burn::db::sortdb::SortitionDB;
+    use chainstate::stacks::miner::test::*;
+    use core::mempool::MemPoolDB;
+    use core::CHAIN_ID_MAINNET;
+    use net::test::*;
+    use util::hash::Hash160;
+
+    /// Mine a block on the peer's chain tip, and process it. Returns the block and the
+    /// consensus hash of the burnchain block that selected it.
+    fn mine_tenure(peer: &mut TestPeer, tenure_id: usize) -> (StacksBlock, ConsensusHash) {
+        let chainstate_path = peer.chainstate_path.clone();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+            .unwrap();
+
+        let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+            |ref mut miner, ref mut sortdb, ref mut chainstate, vrf_proof, ref parent_opt, _| {
+                let parent_tip = match parent_opt {
+                    None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                    Some(block) => {
+                        let ic = sortdb.index_conn();
+                        let snapshot = SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                            &ic,
+                            &tip.sortition_id,
+                            &block.block_hash(),
+                        )
+                        .unwrap()
+                        .unwrap();
+                        StacksChainState::get_anchored_block_header_info(
+                            chainstate.db(),
+                            &snapshot.consensus_hash,
+                            &snapshot.winning_stacks_block_hash,
+                        )
+                        .unwrap()
+                        .unwrap()
+                    }
+                };
+
+                let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+                let coinbase_tx = make_coinbase(miner, tenure_id);
+
+                let anchored_block = StacksBlockBuilder::build_anchored_block(
+                    chainstate,
+                    &sortdb.index_conn(),
+                    &mut mempool,
+                    &parent_tip,
+                    tip.total_burn,
+                    vrf_proof,
+                    Hash160([tenure_id as u8; 20]),
+                    &coinbase_tx,
+                    ExecutionCost::max_value(),
+                )
+                .unwrap();
+                (anchored_block.0, vec![])
+            },
+        );
+
+        let (_, _, consensus_hash) = peer.next_burnchain_block(burn_ops);
+        peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+        (stacks_block, consensus_hash)
+    }
+
+    fn get_payments(conn: &DBConn, block_id: &StacksBlockId) -> Vec<MinerPaymentSchedule> {
+        let qry = "SELECT * FROM payments WHERE index_block_hash = ?1 ORDER BY vtxindex ASC";
+        let args: &[&dyn ToSql] = &[block_id];
+        query_rows::<MinerPaymentSchedule, _>(conn, qry, args).unwrap()
+    }
+
+    #[test]
+    fn test_chainstate_snapshot_export_import() {
+        let peer_config =
+            TestPeerConfig::new("test_chainstate_snapshot_export_import", 52000, 52001);
+        let mut peer = TestPeer::new(peer_config);
+
+        let mut blocks = vec![];
+        for tenure_id in 0..5 {
+            blocks.push(mine_tenure(&mut peer, tenure_id));
+        }
+        let block_ids: Vec<_> = blocks
+            .iter()
+            .map(|(block, consensus_hash)| {
+                StacksBlockHeader::make_index_block_hash(consensus_hash, &block.block_hash())
+            })
+            .collect();
+        let tip = block_ids[block_ids.len() - 1].clone();
+
+        let mut snapshot = vec![];
+        peer.chainstate()
+            .export_chainstate_snapshot(&tip, &mut snapshot)
+            .unwrap();
+
+        let path = "/tmp/test_chainstate_snapshot_export_import";
+        if fs::metadata(path).is_ok() {
+            fs::remove_dir_all(path).unwrap();
+        }
+
+        // a snapshot of another network is refused, and leaves no chain state behind
+        match StacksChainState::import_chainstate_snapshot(
+            true,
+            CHAIN_ID_MAINNET,
+            path,
+            &tip,
+            &mut Cursor::new(&snapshot),
+        ) {
+            Err(Error::InvalidChainstateDB) => {}
+            res => panic!("Imported a testnet snapshot as mainnet: {:?}", res),
+        }
+        let mut vm_path = StacksChainState::chain_path(true, CHAIN_ID_MAINNET, path);
+        vm_path.push("vm");
+        assert!(fs::metadata(&vm_path).is_err());
+
+        // a snapshot that changes the code of a boot contract is refused
+        let original = b"(define-constant MAX_POX_REWARD_CYCLES u12)";
+        let position = snapshot
+            .windows(original.len())
+            .position(|window| window == &original[..])
+            .unwrap();
+        let mut tampered = snapshot.clone();
+        tampered[position..position + original.len()]
+            .copy_from_slice(b"(define-constant MAX_POX_REWARD_CYCLES u99)");
+        assert!(StacksChainState::import_chainstate_snapshot(
+            false,
+            0x80000000,
+            path,
+            &tip,
+            &mut Cursor::new(&tampered),
+        )
+        .is_err());
+        let mut vm_path = StacksChainState::chain_path(false, 0x80000000, path);
+        vm_path.push("vm");
+        assert!(fs::metadata(&vm_path).is_err());
+
+        let header_info = StacksChainState::import_chainstate_snapshot(
+            false,
+            0x80000000,
+            path,
+            &tip,
+            &mut Cursor::new(&snapshot),
+        )
+        .unwrap();
+        assert_eq!(header_info.index_block_hash(), tip);
+
+        // an existing chain state is left alone
+        match StacksChainState::import_chainstate_snapshot(
+            false,
+            0x80000000,
+            path,
+            &tip,
+            &mut Cursor::new(&snapshot),
+        ) {
+            Err(Error::DBError(db_error::ExistsError)) => {}
+            res => panic!("Imported a snapshot over a chain state: {:?}", res),
+        }
+
+        let (mut imported, _) = StacksChainState::open(false, 0x80000000, path).unwrap();
+        for block_id in block_ids.iter() {
+            assert_eq!(
+                StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    imported.db(),
+                    block_id
+                )
+                .unwrap(),
+                StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    peer.chainstate().db(),
+                    block_id
+                )
+                .unwrap()
+            );
+            assert_eq!(
+                get_payments(imported.db(), block_id),
+                get_payments(peer.chainstate().db(), block_id)
+            );
+        }
+
+        // an ancestor's block is not staged
+        let sortdb = peer.sortdb.take().unwrap();
+        assert!(!imported
+            .preprocess_anchored_block(
+                &sortdb.index_conn(),
+                &blocks[1].1,
+                &blocks[1].0,
+                &blocks[0].1,
+                0
+            )
+            .unwrap());
+        peer.sortdb = Some(sortdb);
+
+        // the next block is processed on top of the snapshot
+        let (next_block, next_consensus_hash) = mine_tenure(&mut peer, 5);
+        let next_block_id = StacksBlockHeader::make_index_block_hash(
+            &next_consensus_hash,
+            &next_block.block_hash(),
+        );
+
+        let mut sortdb = peer.sortdb.take().unwrap();
+        {
+            let ic = sortdb.index_conn();
+            let sn = SortitionDB::get_canonical_burn_chain_tip(&ic).unwrap();
+            imported
+                .preprocess_stacks_epoch(&ic, &sn, &next_block, &vec![])
+                .unwrap();
+        }
+        let receipts = imported.process_blocks_at_tip(&mut sortdb, 1).unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert!(receipts[0].0.is_some());
+        peer.sortdb = Some(sortdb);
+
+        assert_eq!(
+            StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                imported.db(),
+                &next_block_id
+            )
+            .unwrap(),
+            StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                peer.chainstate().db(),
+                &next_block_id
+            )
+            .unwrap()
+        );
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
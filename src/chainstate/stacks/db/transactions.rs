@@ -28,6 +28,7 @@ use chainstate::stacks::*;
 
 use std::path::{Path, PathBuf};
 
+use chainstate::stacks::index::Error as marf_error;
 use util::db::Error as db_error;
 use util::db::{query_count, query_rows, DBConn};
 
@@ -60,11 +61,13 @@ use vm::clarity::{
 };
 
 use vm::errors::Error as InterpreterError;
+use vm::errors::{IncomparableError, InterpreterError as VmInterpreterError};
 
 pub use vm::analysis::errors::CheckErrors;
 use vm::analysis::types::ContractAnalysis;
 use vm::clarity::Error as clarity_error;
 
+use vm::database::BurnStateDB;
 use vm::database::ClarityDatabase;
 
 use vm::contracts::Contract;
@@ -269,6 +272,7 @@ enum ClarityRuntimeTxError {
     },
     AbortedByCallback(Option<Value>, AssetMap, Vec<StacksTransactionEvent>),
     CostError(ExecutionCost, ExecutionCost),
+    PrunedState(clarity_error),
     Rejectable(clarity_error),
 }
 
@@ -291,6 +295,13 @@ fn handle_clarity_runtime_error(error: clarity_error) -> ClarityRuntimeTxError {
             ClarityRuntimeTxError::AbortedByCallback(val, assets, events)
         }
         clarity_error::CostError(cost, budget) => ClarityRuntimeTxError::CostError(cost, budget),
+        // the transaction read state that this node pruned, which says nothing about whether it
+        // is valid
+        clarity_error::Interpreter(InterpreterError::Interpreter(
+            VmInterpreterError::MarfFailure(IncomparableError {
+                err: marf_error::PrunedBlockError(_),
+            }),
+        )) => ClarityRuntimeTxError::PrunedState(error),
         unhandled_error => ClarityRuntimeTxError::Rejectable(unhandled_error),
     }
 }
@@ -403,6 +414,14 @@ impl StacksChainState {
         // valid auth?
         tx.verify().map_err(Error::NetError)?;
 
+        StacksChainState::check_transaction_destination(config, tx)
+    }
+
+    /// Make sure a transaction is destined for this chain
+    fn check_transaction_destination(
+        config: &DBConfig,
+        tx: &StacksTransaction,
+    ) -> Result<(), Error> {
         // destined for us?
         if config.chain_id != tx.chain_id {
             let msg = format!(
@@ -832,7 +851,7 @@ impl StacksChainState {
         origin_account: &StacksAccount,
     ) -> Result<StacksTransactionReceipt, Error> {
         match tx.payload {
-            TransactionPayload::TokenTransfer(ref addr, ref amount, ref _memo) => {
+            TransactionPayload::TokenTransfer(ref addr, ref amount, ref memo) => {
                 // post-conditions are not allowed for this variant, since they're non-sensical.
                 // Their presence in this variant makes the transaction invalid.
                 if tx.post_conditions.len() > 0 {
@@ -850,7 +869,14 @@ impl StacksChainState {
 
                 let cost_before = clarity_tx.cost_so_far();
                 let (value, _asset_map, events) = clarity_tx
-                    .run_stx_transfer(&origin_account.principal, addr, *amount as u128)
+                    .run_stx_transfer(
+                        &origin_account.principal,
+                        addr,
+                        *amount as u128,
+                        &BuffData {
+                            data: memo.0.to_vec(),
+                        },
+                    )
                     .map_err(Error::ClarityError)?;
 
                 let mut total_cost = clarity_tx.cost_so_far();
@@ -927,6 +953,12 @@ impl StacksChainState {
                             warn!("Block compute budget exceeded: if included, this will invalidate a block"; "txid" => %tx.txid(), "cost" => %cost_after, "budget" => %budget);
                             return Err(Error::CostOverflowError(cost_before, cost_after, budget));
                         }
+                        ClarityRuntimeTxError::PrunedState(e) => {
+                            warn!("Transaction needs pruned state";
+                                      "txid" => %tx.txid(),
+                                      "error" => ?e);
+                            return Err(Error::PrunedStateError(e.to_string()));
+                        }
                         ClarityRuntimeTxError::Rejectable(e) => {
                             error!("Unexpected error invalidating transaction: if included, this will invalidate a block";
                                        "contract_name" => %contract_id,
@@ -1066,6 +1098,12 @@ impl StacksChainState {
                                       "budget" => %budget);
                             return Err(Error::CostOverflowError(cost_before, cost_after, budget));
                         }
+                        ClarityRuntimeTxError::PrunedState(e) => {
+                            warn!("Transaction needs pruned state";
+                                      "txid" => %tx.txid(),
+                                      "error" => ?e);
+                            return Err(Error::PrunedStateError(e.to_string()));
+                        }
                         ClarityRuntimeTxError::Rejectable(e) => {
                             error!("Unexpected error invalidating transaction: if included, this will invalidate a block";
                                        "contract_name" => %contract_id,
@@ -1134,36 +1172,79 @@ impl StacksChainState {
         debug!("Process transaction {} ({})", tx.txid(), tx.payload.name());
 
         StacksChainState::process_transaction_precheck(&clarity_block.config, tx)?;
+        StacksChainState::process_checked_transaction(clarity_block, tx, quiet)
+    }
 
+    /// Process a transaction that has passed its pre-checks.  Return the fee and the transaction
+    /// receipt.
+    fn process_checked_transaction(
+        clarity_block: &mut ClarityTx,
+        tx: &StacksTransaction,
+        quiet: bool,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
         let mut transaction = clarity_block.connection().start_transaction_processing();
+        let (fee, tx_receipt) =
+            StacksChainState::apply_checked_transaction(&mut transaction, tx, quiet)?;
+
+        transaction.commit();
+
+        Ok((fee, tx_receipt))
+    }
+
+    /// Apply a transaction that has passed its pre-checks to `transaction`, without committing
+    /// it.  Return the fee and the transaction receipt.
+    fn apply_checked_transaction(
+        transaction: &mut ClarityTransactionConnection,
+        tx: &StacksTransaction,
+        quiet: bool,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
         let (origin_account, payer_account) =
-            StacksChainState::check_transaction_nonces(&mut transaction, tx, quiet)?;
+            StacksChainState::check_transaction_nonces(transaction, tx, quiet)?;
 
         let tx_receipt =
-            StacksChainState::process_transaction_payload(&mut transaction, tx, &origin_account)?;
+            StacksChainState::process_transaction_payload(transaction, tx, &origin_account)?;
 
-        let new_payer_account = StacksChainState::get_payer_account(&mut transaction, tx);
+        let new_payer_account = StacksChainState::get_payer_account(transaction, tx);
         let fee = tx.get_tx_fee();
-        StacksChainState::pay_transaction_fee(&mut transaction, fee, new_payer_account)?;
+        StacksChainState::pay_transaction_fee(transaction, fee, new_payer_account)?;
 
         // update the account nonces
         StacksChainState::update_account_nonce(
-            &mut transaction,
+            transaction,
             &origin_account.principal,
             origin_account.nonce,
         );
         if origin_account != payer_account {
             StacksChainState::update_account_nonce(
-                &mut transaction,
+                transaction,
                 &payer_account.principal,
                 payer_account.nonce,
             );
         }
 
-        transaction.commit();
-
         Ok((fee, tx_receipt))
     }
+
+    /// Process a transaction against the Clarity state at the given chain tip, and return the fee
+    /// and the transaction receipt.  Signatures are not checked, so the transaction may be
+    /// unsigned.  The transaction runs on a read-only connection and its writes are discarded,
+    /// so nothing is committed.  The transaction may consume at most cost_limit.  Returns
+    /// Ok(None) if the chain tip does not exist.
+    pub fn simulate_transaction(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        tip: &StacksBlockId,
+        tx: &StacksTransaction,
+        cost_limit: ExecutionCost,
+    ) -> Result<Option<(u64, StacksTransactionReceipt)>, Error> {
+        StacksChainState::check_transaction_destination(&self.config(), tx)?;
+        self.maybe_read_only_clarity_tx(burn_dbconn, tip, |conn| {
+            conn.as_simulated_transaction(cost_limit, |transaction| {
+                StacksChainState::apply_checked_transaction(transaction, tx, true)
+            })
+        })?
+        .transpose()
+    }
 }
 
 #[cfg(test)]
@@ -7528,4 +7609,27 @@ pub mod test {
 
         conn.commit_block();
     }
+
+    #[test]
+    fn pruned_state_does_not_reject_transactions() {
+        let pruned = clarity_error::Interpreter(InterpreterError::Interpreter(
+            VmInterpreterError::MarfFailure(IncomparableError {
+                err: marf_error::PrunedBlockError("block".into()),
+            }),
+        ));
+        match handle_clarity_runtime_error(pruned) {
+            ClarityRuntimeTxError::PrunedState(_) => {}
+            _ => panic!("pruned state should not reject a transaction"),
+        }
+
+        let missing = clarity_error::Interpreter(InterpreterError::Interpreter(
+            VmInterpreterError::MarfFailure(IncomparableError {
+                err: marf_error::NotFoundError,
+            }),
+        ));
+        match handle_clarity_runtime_error(missing) {
+            ClarityRuntimeTxError::Rejectable(_) => {}
+            _ => panic!("other MARF failures should reject a transaction"),
+        }
+    }
 }
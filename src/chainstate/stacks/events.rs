@@ -21,7 +21,8 @@ use net::StacksMessageCodec;
 use vm::analysis::ContractAnalysis;
 use vm::costs::ExecutionCost;
 use vm::types::{
-    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+    AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+    Value,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -186,6 +187,8 @@ pub struct STXTransferEventData {
     pub sender: PrincipalData,
     pub recipient: PrincipalData,
     pub amount: u128,
+    /// The memo given to `stx-transfer-memo?` or a token-transfer transaction (empty if none)
+    pub memo: BuffData,
 }
 
 impl STXTransferEventData {
@@ -194,6 +197,7 @@ impl STXTransferEventData {
             "sender": format!("{}",self.sender),
             "recipient": format!("{}",self.recipient),
             "amount": format!("{}", self.amount),
+            "memo": format!("0x{}", self.memo),
         })
     }
 }
@@ -43,6 +43,10 @@ use chainstate::stacks::index::{
     proofs::TrieMerkleProof, MARFValue, MarfTrieId, TrieHash, TRIEHASH_ENCODED_SIZE,
 };
 
+use chainstate::stacks::index::prune::{prune_tries, PruneStats};
+use chainstate::stacks::index::snapshot::{export_snapshot, import_snapshot};
+use chainstate::stacks::index::trie_sql;
+
 use chainstate::stacks::index::trie::Trie;
 
 use chainstate::stacks::index::Error;
@@ -85,7 +89,10 @@ pub trait MarfConnection<T: MarfTrieId> {
 
     /// Resolve a key from the MARF to a MARFValue with respect to the given block height.
     fn get(&mut self, block_hash: &T, key: &str) -> Result<Option<MARFValue>, Error> {
-        self.with_conn(|c| MARF::get_by_key(c, block_hash, key))
+        self.with_conn(|c| {
+            MARF::check_not_pruned(c, block_hash)?;
+            MARF::get_by_key(c, block_hash, key)
+        })
     }
 
     fn get_with_proof(
@@ -94,6 +101,7 @@ pub trait MarfConnection<T: MarfTrieId> {
         key: &str,
     ) -> Result<Option<(MARFValue, TrieMerkleProof<T>)>, Error> {
         self.with_conn(|conn| {
+            MARF::check_not_pruned(conn, block_hash)?;
             let marf_value = match MARF::get_by_key(conn, block_hash, key)? {
                 None => return Ok(None),
                 Some(x) => x,
@@ -121,12 +129,25 @@ pub trait MarfConnection<T: MarfTrieId> {
         self.with_conn(|c| c.get_root_hash_at(block_hash))
     }
 
+    /// Has this block's trie been pruned?
+    fn is_block_pruned(&mut self, bhh: &T) -> Result<bool, Error> {
+        self.with_conn(|c| c.is_block_pruned(bhh))
+    }
+
+    /// Get the lowest block height whose trie is kept whole, if the MARF was ever pruned
+    fn get_prune_horizon(&self) -> Result<Option<u32>, Error> {
+        trie_sql::get_prune_horizon(self.sqlite_conn())
+    }
+
     /// Check if a block can open successfully, i.e.,
     ///   it's a known block, the storage system isn't issueing IOErrors, _and_ it's in the same fork
     ///   as the current block
     /// The MARF _must_ be open to a valid block for this check to be evaluated.
+    /// Fails with a PrunedBlockError if the block's trie was pruned.
     fn check_ancestor_block_hash(&mut self, bhh: &T) -> Result<(), Error> {
         self.with_conn(|conn| {
+            MARF::check_not_pruned(conn, bhh)?;
+
             let cur_block_hash = conn.get_cur_block();
             if cur_block_hash == *bhh {
                 // a block is in its own fork
@@ -306,6 +327,7 @@ impl<'a, T: MarfTrieId> MarfTransaction<'a, T> {
             error!("Block data already exists: {}", next_chain_tip);
             return Err(Error::ExistsError);
         }
+        MARF::check_not_pruned(&mut self.storage, chain_tip)?;
 
         let block_height = self.inner_get_extension_height(chain_tip, next_chain_tip)?;
         MARF::extend_trie(&mut self.storage, next_chain_tip)?;
@@ -479,6 +501,7 @@ impl<'a, T: MarfTrieId> MarfTransaction<'a, T> {
             error!("No such confirmed block {}", chain_tip);
             return Err(Error::NotFoundError);
         }
+        MARF::check_not_pruned(&mut self.storage, chain_tip)?;
 
         let unconfirmed_tip = MARF::make_unconfirmed_chain_tip(chain_tip);
 
@@ -493,6 +516,35 @@ impl<'a, T: MarfTrieId> MarfTransaction<'a, T> {
         Ok(unconfirmed_tip)
     }
 
+    /// Prune the tries of the blocks more than `depth` blocks below `tip`.
+    /// See `prune::prune_tries()`.
+    pub fn prune(&mut self, tip: &T, depth: u32) -> Result<PruneStats, Error> {
+        if self.storage.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.open_chain_tip.is_some() {
+            return Err(Error::InProgressError);
+        }
+        prune_tries(&mut self.storage, tip, depth)
+    }
+
+    /// Import a snapshot of the tries at `block_hash` into this empty MARF, and check that its
+    /// root hash is `root_hash`. See `snapshot::import_snapshot()`.
+    pub fn import_snapshot<R: Read>(
+        &mut self,
+        fd: &mut R,
+        block_hash: &T,
+        root_hash: &TrieHash,
+    ) -> Result<Vec<MARFValue>, Error> {
+        if self.storage.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+        if self.open_chain_tip.is_some() {
+            return Err(Error::InProgressError);
+        }
+        import_snapshot(&mut self.storage, fd, block_hash, root_hash)
+    }
+
     /// Drop the current trie from the MARF. This rolls back all
     ///   changes in the block, and closes the current chain tip.
     pub fn drop_current(mut self) {
@@ -1043,6 +1095,17 @@ impl<T: MarfTrieId> MARF<T> {
         Ok(MARF::from_storage(file_storage))
     }
 
+    /// Fail with a PrunedBlockError if the block's trie was pruned, so its keys cannot be read
+    pub fn check_not_pruned(
+        storage: &mut TrieStorageConnection<T>,
+        block_hash: &T,
+    ) -> Result<(), Error> {
+        if storage.is_block_pruned(block_hash)? {
+            return Err(Error::PrunedBlockError(block_hash.to_string()));
+        }
+        Ok(())
+    }
+
     pub fn get_by_key(
         storage: &mut TrieStorageConnection<T>,
         block_hash: &T,
@@ -1232,6 +1295,7 @@ impl<T: MarfTrieId> MARF<T> {
         key: &str,
     ) -> Result<Option<(MARFValue, TrieMerkleProof<T>)>, Error> {
         let mut conn = self.storage.connection();
+        MARF::check_not_pruned(&mut conn, block_hash)?;
         let marf_value = match MARF::get_by_key(&mut conn, block_hash, key)? {
             None => return Ok(None),
             Some(x) => x,
@@ -1459,6 +1523,44 @@ impl<T: MarfTrieId> MARF<T> {
     pub fn get_root_hash_at(&mut self, block_hash: &T) -> Result<TrieHash, Error> {
         self.storage.connection().get_root_hash_at(block_hash)
     }
+
+    /// Prune the tries of the blocks more than `depth` blocks below `tip`.
+    /// See `prune::prune_tries()`.
+    pub fn prune(&mut self, tip: &T, depth: u32) -> Result<PruneStats, Error> {
+        let mut tx = self.begin_tx()?;
+        let stats = tx.prune(tip, depth)?;
+        tx.commit()?;
+        Ok(stats)
+    }
+
+    /// Return the space freed by pruning to the filesystem
+    pub fn vacuum(&mut self) -> Result<(), Error> {
+        self.storage.vacuum()
+    }
+
+    /// Write a snapshot of the tries at `block_hash`, for another MARF to import.
+    /// See `snapshot::export_snapshot()`.
+    pub fn export_snapshot<W: Write>(
+        &mut self,
+        block_hash: &T,
+        fd: &mut W,
+    ) -> Result<Vec<MARFValue>, Error> {
+        export_snapshot(&mut self.storage.connection(), block_hash, fd)
+    }
+
+    /// Import a snapshot of the tries at `block_hash` into this empty MARF.
+    /// See `snapshot::import_snapshot()`.
+    pub fn import_snapshot<R: Read>(
+        &mut self,
+        fd: &mut R,
+        block_hash: &T,
+        root_hash: &TrieHash,
+    ) -> Result<Vec<MARFValue>, Error> {
+        let mut tx = self.begin_tx()?;
+        let values = tx.import_snapshot(fd, block_hash, root_hash)?;
+        tx.commit()?;
+        Ok(values)
+    }
 }
 
 #[cfg(test)]
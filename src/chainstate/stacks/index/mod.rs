@@ -18,6 +18,8 @@ pub mod bits;
 pub mod marf;
 pub mod node;
 pub mod proofs;
+pub mod prune;
+pub mod snapshot;
 pub mod storage;
 pub mod trie;
 pub mod trie_sql;
@@ -283,6 +285,7 @@ pub enum Error {
     CursorError(node::CursorError),
     RestoreMarfBlockError(Box<Error>),
     NonMatchingForks([u8; 32], [u8; 32]),
+    PrunedBlockError(String),
 }
 
 impl From<io::Error> for Error {
@@ -345,6 +348,7 @@ impl fmt::Display for Error {
             Error::NonMatchingForks(_, _) => {
                 write!(f, "The supplied blocks are not in the same fork")
             }
+            Error::PrunedBlockError(ref bhh) => write!(f, "The state at block {} was pruned", bhh),
             Error::RequestedIdentifierForExtensionTrie => {
                 write!(f, "BUG: MARF requested the identifier for a RAM trie")
             }
@@ -1596,6 +1596,418 @@ impl<T: MarfTrieId> TrieMerkleProof<T> {
     }
 }
 
+/// An entry in a multi-proof, shared by every key whose proof uses it.  Unlike a node in a
+/// TrieMerkleProof, a node entry carries the hashes of _all_ of its children, so the same entry
+/// can serve every proven path that passes through the node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrieMultiProofEntry<T: MarfTrieId> {
+    Node((ProofTrieNode<T>, Vec<TrieHash>)),
+    Leaf(TrieLeaf),
+    Shunt((i64, Vec<TrieHash>)),
+}
+
+define_u8_enum!( TrieMultiProofEntryIndicator {
+    Node = 0, Leaf = 1, Shunt = 2
+});
+
+/// One item of a key's proof within a multi-proof: the index of the shared entry, and the chr
+/// of the child the proven path follows out of it (0 for shunt entries).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieMultiProofStep {
+    pub entry: u32,
+    pub chr: u8,
+}
+
+/// A Merkle proof of inclusion for many keys at once, all against the same MARF root.  Each key's
+/// TrieMerkleProof is encoded as a list of steps into one table of distinct entries, so the nodes
+/// and shunt proofs that many keys' paths share (like the root node of the tip's trie) are only
+/// sent once.  Verification expands each key's proof and checks it just like a single proof.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieMultiProof<T: MarfTrieId> {
+    pub entries: Vec<TrieMultiProofEntry<T>>,
+    pub proofs: Vec<Vec<TrieMultiProofStep>>,
+}
+
+impl<T: MarfTrieId> StacksMessageCodec for TrieMultiProofEntry<T> {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), ::net::Error> {
+        match self {
+            TrieMultiProofEntry::Node((node, hashes)) => {
+                (TrieMultiProofEntryIndicator::Node as u8).consensus_serialize(fd)?;
+                node.consensus_serialize(fd)?;
+                hashes.consensus_serialize(fd)
+            }
+            TrieMultiProofEntry::Leaf(leaf) => {
+                (TrieMultiProofEntryIndicator::Leaf as u8).consensus_serialize(fd)?;
+                leaf.consensus_serialize(fd)
+            }
+            TrieMultiProofEntry::Shunt((idx, hashes)) => {
+                (TrieMultiProofEntryIndicator::Shunt as u8).consensus_serialize(fd)?;
+                idx.consensus_serialize(fd)?;
+                hashes.consensus_serialize(fd)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TrieMultiProofEntry<T>, ::net::Error> {
+        let type_byte = TrieMultiProofEntryIndicator::from_u8(read_next(fd)?).ok_or_else(|| {
+            ::net::Error::DeserializeError("Bad type byte in Trie multi-proof entry".into())
+        })?;
+
+        let entry = match type_byte {
+            TrieMultiProofEntryIndicator::Node => {
+                let node = read_next(fd)?;
+                let hashes = read_next(fd)?;
+                TrieMultiProofEntry::Node((node, hashes))
+            }
+            TrieMultiProofEntryIndicator::Leaf => TrieMultiProofEntry::Leaf(read_next(fd)?),
+            TrieMultiProofEntryIndicator::Shunt => {
+                let idx = read_next(fd)?;
+                let hashes = read_next(fd)?;
+                TrieMultiProofEntry::Shunt((idx, hashes))
+            }
+        };
+
+        Ok(entry)
+    }
+}
+
+impl StacksMessageCodec for TrieMultiProofStep {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), ::net::Error> {
+        self.entry.consensus_serialize(fd)?;
+        self.chr.consensus_serialize(fd)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TrieMultiProofStep, ::net::Error> {
+        let entry = read_next(fd)?;
+        let chr = read_next(fd)?;
+        Ok(TrieMultiProofStep { entry, chr })
+    }
+}
+
+impl<T: MarfTrieId> StacksMessageCodec for TrieMultiProof<T> {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), ::net::Error> {
+        self.entries.consensus_serialize(fd)?;
+        self.proofs.consensus_serialize(fd)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TrieMultiProof<T>, ::net::Error> {
+        let entries = read_next(fd)?;
+        let proofs = read_next(fd)?;
+        Ok(TrieMultiProof { entries, proofs })
+    }
+}
+
+impl<T: MarfTrieId> TrieMultiProof<T> {
+    pub fn to_hex(&self) -> String {
+        let mut marf_proof = vec![];
+        self.consensus_serialize(&mut marf_proof)
+            .expect("Write error on memory buffer");
+        to_hex(&marf_proof)
+    }
+
+    /// Combine single-key proofs, all made against the same root, into one multi-proof.
+    pub fn from_proofs(proofs: &[TrieMerkleProof<T>]) -> Result<TrieMultiProof<T>, Error> {
+        let mut multi_proof = TrieMultiProof {
+            entries: vec![],
+            proofs: Vec::with_capacity(proofs.len()),
+        };
+        let mut entry_index = HashMap::new();
+        for proof in proofs.iter() {
+            multi_proof.add_proof(&mut entry_index, proof)?;
+        }
+        Ok(multi_proof)
+    }
+
+    /// Make a multi-proof of inclusion for a list of paths and the values they must resolve to.
+    /// If any path doesn't resolve, return an error (NotFoundError)
+    pub fn from_paths(
+        storage: &mut TrieStorageConnection<T>,
+        paths: &[(TriePath, MARFValue)],
+        root_block_header: &T,
+    ) -> Result<TrieMultiProof<T>, Error> {
+        let mut proofs = Vec::with_capacity(paths.len());
+        for (path, value) in paths.iter() {
+            proofs.push(TrieMerkleProof::from_path(
+                storage,
+                path,
+                value,
+                root_block_header,
+            )?);
+        }
+        TrieMultiProof::from_proofs(&proofs)
+    }
+
+    /// Add an entry to the table, or find the identical entry already in it.
+    fn intern_entry(
+        &mut self,
+        entry_index: &mut HashMap<Vec<u8>, u32>,
+        entry: TrieMultiProofEntry<T>,
+    ) -> u32 {
+        let mut entry_bytes = vec![];
+        entry
+            .consensus_serialize(&mut entry_bytes)
+            .expect("Write error on memory buffer");
+
+        if let Some(idx) = entry_index.get(&entry_bytes) {
+            return *idx;
+        }
+
+        let idx = self.entries.len() as u32;
+        self.entries.push(entry);
+        entry_index.insert(entry_bytes, idx);
+        idx
+    }
+
+    /// Turn a segment proof node into a shared entry by filling in the hash of the child on the
+    /// proven path.  `child_hash` is the hash calculated for the proof node below this one, or
+    /// None if this node is at the bottom of a segment proof that ends in a backptr (whose child
+    /// hash is the hash of the block it points to).  Returns the entry and the node's own hash.
+    fn make_node_entry(
+        node: &ProofTrieNode<T>,
+        chr: u8,
+        child_hash: Option<TrieHash>,
+        hashes: &[TrieHash],
+    ) -> Result<(TrieMultiProofEntry<T>, TrieHash), Error> {
+        let child_hash = match child_hash {
+            Some(h) => h,
+            None => {
+                let backptr = node
+                    .ptrs()
+                    .iter()
+                    .find(|ptr| ptr.chr == chr && is_backptr(ptr.id))
+                    .ok_or_else(|| {
+                        Error::CorruptionError(format!(
+                            "No backptr for 0x{:02x} at the bottom of a segment proof",
+                            chr
+                        ))
+                    })?;
+                TrieHash(backptr.back_block.clone().to_bytes())
+            }
+        };
+
+        let mut all_hashes = Vec::with_capacity(node.ptrs().len());
+        let mut ih = 0;
+        for ptr in node.ptrs() {
+            if ptr.id != TrieNodeID::Empty as u8 && ptr.chr == chr {
+                all_hashes.push(child_hash.clone());
+            } else if ih < hashes.len() {
+                all_hashes.push(hashes[ih].clone());
+                ih += 1;
+            } else {
+                return Err(Error::CorruptionError(
+                    "Segment proof node has too few child hashes".to_string(),
+                ));
+            }
+        }
+
+        let node_hash = get_node_hash(node, &all_hashes, &mut ());
+        Ok((
+            TrieMultiProofEntry::Node((node.clone(), all_hashes)),
+            node_hash,
+        ))
+    }
+
+    fn add_proof(
+        &mut self,
+        entry_index: &mut HashMap<Vec<u8>, u32>,
+        proof: &TrieMerkleProof<T>,
+    ) -> Result<(), Error> {
+        let mut steps = Vec::with_capacity(proof.len());
+
+        // hash of the previous proof node, if it was part of the same segment proof
+        let mut child_hash = None;
+        for proof_node in proof.iter() {
+            let (entry, chr, node_hash) = match proof_node {
+                TrieMerkleProofType::Leaf((ref chr, ref leaf)) => (
+                    TrieMultiProofEntry::Leaf(leaf.clone()),
+                    *chr,
+                    Some(get_leaf_hash(leaf)),
+                ),
+                TrieMerkleProofType::Shunt((ref idx, ref hashes)) => {
+                    (TrieMultiProofEntry::Shunt((*idx, hashes.clone())), 0, None)
+                }
+                TrieMerkleProofType::Node4((ref chr, ref node, ref hashes)) => {
+                    let (entry, hash) =
+                        TrieMultiProof::make_node_entry(node, *chr, child_hash, hashes)?;
+                    (entry, *chr, Some(hash))
+                }
+                TrieMerkleProofType::Node16((ref chr, ref node, ref hashes)) => {
+                    let (entry, hash) =
+                        TrieMultiProof::make_node_entry(node, *chr, child_hash, hashes)?;
+                    (entry, *chr, Some(hash))
+                }
+                TrieMerkleProofType::Node48((ref chr, ref node, ref hashes)) => {
+                    let (entry, hash) =
+                        TrieMultiProof::make_node_entry(node, *chr, child_hash, hashes)?;
+                    (entry, *chr, Some(hash))
+                }
+                TrieMerkleProofType::Node256((ref chr, ref node, ref hashes)) => {
+                    let (entry, hash) =
+                        TrieMultiProof::make_node_entry(node, *chr, child_hash, hashes)?;
+                    (entry, *chr, Some(hash))
+                }
+            };
+
+            child_hash = node_hash;
+            let entry = self.intern_entry(entry_index, entry);
+            steps.push(TrieMultiProofStep { entry, chr });
+        }
+
+        self.proofs.push(steps);
+        Ok(())
+    }
+
+    /// Remove the hash of the child on the proven path from a node entry's hashes, so the
+    /// verifier has to calculate it.
+    fn segment_proof_hashes(
+        node: &ProofTrieNode<T>,
+        chr: u8,
+        all_hashes: &[TrieHash],
+        count: usize,
+    ) -> Option<Vec<TrieHash>> {
+        if node.ptrs().len() != count || all_hashes.len() != count {
+            trace!(
+                "Multi-proof node has {} ptrs and {} hashes, expected {}",
+                node.ptrs().len(),
+                all_hashes.len(),
+                count
+            );
+            return None;
+        }
+
+        let mut hashes = Vec::with_capacity(count - 1);
+        let mut found = false;
+        for (ptr, hash) in node.ptrs().iter().zip(all_hashes.iter()) {
+            if !found && ptr.id != TrieNodeID::Empty as u8 && ptr.chr == chr {
+                found = true;
+            } else {
+                hashes.push(hash.clone());
+            }
+        }
+
+        if !found {
+            trace!("Char 0x{:02x} does not appear in multi-proof node", chr);
+            return None;
+        }
+        Some(hashes)
+    }
+
+    /// Rebuild the single-key proof for the `i`th proven key.
+    /// Returns None if the multi-proof is malformed.
+    pub fn expand_proof(&self, i: usize) -> Option<TrieMerkleProof<T>> {
+        let steps = self.proofs.get(i)?;
+        let mut proof = Vec::with_capacity(steps.len());
+        for step in steps.iter() {
+            let entry = match self.entries.get(step.entry as usize) {
+                Some(entry) => entry,
+                None => {
+                    trace!("Multi-proof has no entry {}", step.entry);
+                    return None;
+                }
+            };
+
+            let proof_node = match entry {
+                TrieMultiProofEntry::Leaf(ref leaf) => {
+                    TrieMerkleProofType::Leaf((step.chr, leaf.clone()))
+                }
+                TrieMultiProofEntry::Shunt((ref idx, ref hashes)) => {
+                    TrieMerkleProofType::Shunt((*idx, hashes.clone()))
+                }
+                TrieMultiProofEntry::Node((ref node, ref all_hashes)) => {
+                    match TrieNodeID::from_u8(node.id) {
+                        Some(TrieNodeID::Node4) => {
+                            let hashes = TrieMultiProof::segment_proof_hashes(
+                                node, step.chr, all_hashes, 4,
+                            )?;
+                            let mut hash_slice = [TrieHash::from_data(&[]); 3];
+                            hash_slice.copy_from_slice(&hashes[0..3]);
+                            TrieMerkleProofType::Node4((step.chr, node.clone(), hash_slice))
+                        }
+                        Some(TrieNodeID::Node16) => {
+                            let hashes = TrieMultiProof::segment_proof_hashes(
+                                node, step.chr, all_hashes, 16,
+                            )?;
+                            let mut hash_slice = [TrieHash::from_data(&[]); 15];
+                            hash_slice.copy_from_slice(&hashes[0..15]);
+                            TrieMerkleProofType::Node16((step.chr, node.clone(), hash_slice))
+                        }
+                        Some(TrieNodeID::Node48) => {
+                            let hashes = TrieMultiProof::segment_proof_hashes(
+                                node, step.chr, all_hashes, 48,
+                            )?;
+                            let mut hash_slice = [TrieHash::from_data(&[]); 47];
+                            hash_slice.copy_from_slice(&hashes[0..47]);
+                            TrieMerkleProofType::Node48((step.chr, node.clone(), hash_slice))
+                        }
+                        Some(TrieNodeID::Node256) => {
+                            let hashes = TrieMultiProof::segment_proof_hashes(
+                                node, step.chr, all_hashes, 256,
+                            )?;
+                            let mut hash_slice = [TrieHash::from_data(&[]); 255];
+                            hash_slice.copy_from_slice(&hashes[0..255]);
+                            TrieMerkleProofType::Node256((step.chr, node.clone(), hash_slice))
+                        }
+                        _ => {
+                            trace!("Multi-proof node entry has invalid ID {}", node.id);
+                            return None;
+                        }
+                    }
+                }
+            };
+            proof.push(proof_node);
+        }
+        Some(TrieMerkleProof(proof))
+    }
+
+    /// Verify this multi-proof against the root hash it was (supposedly) generated from.
+    /// `paths` are the proven paths and their values, in the same order as the multi-proof's
+    /// proofs, and every one of them must verify.  As with a single proof, the verifier needs to
+    /// know which trie roots correspond to which block headers.
+    pub fn verify(
+        &self,
+        paths: &[(TriePath, MARFValue)],
+        root_hash: &TrieHash,
+        root_to_block: &HashMap<TrieHash, T>,
+    ) -> bool {
+        if paths.len() != self.proofs.len() {
+            trace!(
+                "Multi-proof has {} proofs, but {} paths were given",
+                self.proofs.len(),
+                paths.len()
+            );
+            return false;
+        }
+
+        for (i, (path, marf_value)) in paths.iter().enumerate() {
+            let proof = match self.expand_proof(i) {
+                Some(proof) => proof,
+                None => {
+                    return false;
+                }
+            };
+            if !proof.verify(path, marf_value, root_hash, root_to_block) {
+                trace!("Multi-proof does not prove {:?}", path);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Verify this multi-proof for a list of key/value pairs.
+    pub fn verify_entries(
+        &self,
+        entries: &[(String, String)],
+        root_hash: &TrieHash,
+        root_to_block: &HashMap<TrieHash, T>,
+    ) -> bool {
+        let paths: Vec<_> = entries
+            .iter()
+            .map(|(key, value)| (TriePath::from_key(key), MARFValue::from_value(value)))
+            .collect();
+        self.verify(&paths, root_hash, root_to_block)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1774,4 +2186,100 @@ mod test {
         println!("DEBUG: verify(old_v)");
         assert!(!proof_5.verify(&triepath_4, &marf_value_4, &root_hash_5, &root_to_block));
     }
+
+    #[test]
+    fn multi_proof_verify() {
+        let mut m = MARF::from_path(":memory:").unwrap();
+
+        let sentinel_block = BlockHeaderHash::sentinel();
+        let mut blocks = vec![];
+        let mut entries = vec![];
+
+        // 10 blocks, each writing 10 keys, so that proofs for keys from earlier blocks have to
+        // follow backptrs
+        let mut parent = sentinel_block.clone();
+        for i in 0..10u8 {
+            let block = BlockHeaderHash([i + 1; 32]);
+            m.begin(&parent, &block).unwrap();
+            for j in 0..10 {
+                let key = format!("key-{}-{}", i, j);
+                let value = format!("value-{}-{}", i, j);
+                m.insert(&key, MARFValue::from_value(&value)).unwrap();
+                entries.push((key, value));
+            }
+            m.commit().unwrap();
+            blocks.push(block.clone());
+            parent = block;
+        }
+
+        let tip = blocks.last().unwrap().clone();
+        let root_hash = m.get_root_hash_at(&tip).unwrap();
+        let root_to_block = m
+            .borrow_storage_backend()
+            .read_root_to_block_table()
+            .unwrap();
+
+        // prove every other key
+        let proven: Vec<_> = entries.iter().step_by(2).cloned().collect();
+        let paths: Vec<_> = proven
+            .iter()
+            .map(|(k, v)| (TriePath::from_key(k), MARFValue::from_value(v)))
+            .collect();
+
+        let multi_proof =
+            TrieMultiProof::from_paths(&mut m.borrow_storage_backend(), &paths, &tip).unwrap();
+        assert!(multi_proof.verify(&paths, &root_hash, &root_to_block));
+        assert!(multi_proof.verify_entries(&proven, &root_hash, &root_to_block));
+
+        // expands back into the single-key proofs
+        let mut single_proofs_len = 0;
+        for (i, (path, value)) in paths.iter().enumerate() {
+            let proof =
+                TrieMerkleProof::from_path(&mut m.borrow_storage_backend(), path, value, &tip)
+                    .unwrap();
+            assert_eq!(multi_proof.expand_proof(i).unwrap().0, proof.0);
+            single_proofs_len += proof.to_hex().len();
+        }
+        assert!(multi_proof.to_hex().len() < single_proofs_len / 2);
+
+        // survives a round-trip through its encoding
+        let mut bytes = vec![];
+        multi_proof.consensus_serialize(&mut bytes).unwrap();
+        let decoded =
+            TrieMultiProof::<BlockHeaderHash>::consensus_deserialize(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded, multi_proof);
+        assert!(decoded.verify(&paths, &root_hash, &root_to_block));
+
+        // wrong value
+        let mut bad_paths = paths.clone();
+        bad_paths[3].1 = MARFValue::from_value(&"nope".to_string());
+        assert!(!multi_proof.verify(&bad_paths, &root_hash, &root_to_block));
+
+        // keys out of order
+        let mut bad_paths = paths.clone();
+        bad_paths.swap(0, 1);
+        assert!(!multi_proof.verify(&bad_paths, &root_hash, &root_to_block));
+
+        // missing key
+        assert!(!multi_proof.verify(&paths[1..], &root_hash, &root_to_block));
+
+        // wrong root
+        let old_root_hash = m.get_root_hash_at(&blocks[8]).unwrap();
+        assert!(!multi_proof.verify(&paths, &old_root_hash, &root_to_block));
+
+        // tampered child hash in a shared node
+        let mut bad_proof = multi_proof.clone();
+        for entry in bad_proof.entries.iter_mut() {
+            if let TrieMultiProofEntry::Node((_, ref mut hashes)) = entry {
+                hashes[0] = TrieHash([0xff; 32]);
+            }
+        }
+        assert!(!bad_proof.verify(&paths, &root_hash, &root_to_block));
+
+        // dangling entry reference
+        let mut bad_proof = multi_proof.clone();
+        bad_proof.proofs[0][0].entry = bad_proof.entries.len() as u32;
+        assert!(bad_proof.expand_proof(0).is_none());
+        assert!(!bad_proof.verify(&paths, &root_hash, &root_to_block));
+    }
 }
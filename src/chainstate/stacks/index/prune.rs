@@ -0,0 +1,692 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pruning of historical tries.
+//!
+//! Every block's trie is kept in its own blob, and shares the nodes of its ancestors' tries
+//! through back-pointers. Pruning discards the nodes of old tries that the retained tries can no
+//! longer reach. A pruned trie keeps:
+//!
+//! * the nodes that retained tries reach through back-pointers, along with their subtrees,
+//! * the nodes on the paths from its root to those nodes, and the hashes of their children, so
+//!   that Merkle proofs can still be built through it,
+//! * its root node and the hashes of the root's children, so its root hash can be read and
+//!   proven, and
+//! * the paths to the block height keys that building a proof through it looks up, and the
+//!   hashes of their nodes' children.
+//!
+//! The kept nodes are packed together, and the back-pointers into them are rewritten to their
+//! new offsets. A back-pointer's offset is not part of a node's hash, so no hashes change.
+//! Pointers to discarded nodes are set to 0, which is never the offset of a node.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::{Cursor, Seek, SeekFrom, Write};
+
+use rusqlite::Connection;
+
+use chainstate::stacks::index::bits::{get_node_byte_len, read_nodetype, write_nodetype_bytes};
+use chainstate::stacks::index::marf::{
+    BLOCK_HASH_TO_HEIGHT_MAPPING_KEY, BLOCK_HEIGHT_TO_HASH_MAPPING_KEY, MARF, OWN_BLOCK_HEIGHT_KEY,
+};
+use chainstate::stacks::index::node::{
+    clear_backptr, is_backptr, TrieNodeID, TrieNodeType, TriePath, TriePtr,
+};
+use chainstate::stacks::index::storage::{TrieStorageConnection, TrieStorageTransaction};
+use chainstate::stacks::index::trie_sql;
+use chainstate::stacks::index::{Error, MarfTrieId};
+
+use util::log;
+
+/// What a pruning pass did
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PruneStats {
+    /// The lowest block height whose trie is kept whole
+    pub horizon: u32,
+    /// How many tries are pruned, including those pruned by earlier passes
+    pub pruned_blocks: u64,
+    /// The total length of the pruned tries before this pass
+    pub bytes_before: u64,
+    /// The total length of the pruned tries after this pass
+    pub bytes_after: u64,
+}
+
+/// Finds the nodes of the pruned tries that must be kept
+pub struct PruneMarker<'a> {
+    conn: &'a Connection,
+    root: TriePtr,
+    pruned: &'a HashSet<u32>,
+    /// kept nodes of each pruned trie, as their offsets and node IDs
+    kept: HashMap<u32, BTreeMap<u32, u8>>,
+    /// nodes of pruned tries whose subtrees were kept
+    visited: HashSet<(u32, u32)>,
+    /// the pruned tries that each pruned trie's kept nodes point back into
+    backptr_targets: HashMap<u32, HashSet<u32>>,
+}
+
+impl<'a> PruneMarker<'a> {
+    pub fn new(conn: &'a Connection, root: TriePtr, pruned: &'a HashSet<u32>) -> PruneMarker<'a> {
+        PruneMarker {
+            conn,
+            root,
+            pruned,
+            kept: HashMap::new(),
+            visited: HashSet::new(),
+            backptr_targets: HashMap::new(),
+        }
+    }
+
+    fn keep(&mut self, block_id: u32, ptr: &TriePtr) {
+        if self.pruned.contains(&block_id) {
+            self.kept
+                .entry(block_id)
+                .or_insert_with(BTreeMap::new)
+                .insert(ptr.ptr(), clear_backptr(ptr.id()));
+        }
+    }
+
+    fn read_node(&self, block_id: u32, ptr: &TriePtr) -> Result<TrieNodeType, Error> {
+        let (node, _) = trie_sql::read_node_type(self.conn, block_id, &ptr.from_backptr())?;
+        Ok(node)
+    }
+
+    /// Keep the subtree at `ptr`, and whatever it points back to. `prefix` is the path to the
+    /// node.
+    pub fn mark_reachable(
+        &mut self,
+        block_id: u32,
+        ptr: &TriePtr,
+        prefix: &[u8],
+    ) -> Result<(), Error> {
+        self.keep(block_id, ptr);
+        let node = self.read_node(block_id, ptr)?;
+        if node.is_leaf() {
+            return Ok(());
+        }
+
+        let mut node_prefix = prefix.to_vec();
+        node_prefix.extend_from_slice(node.path_bytes());
+
+        for child in node.ptrs().iter() {
+            if child.id() == TrieNodeID::Empty as u8 || child.ptr() == 0 {
+                continue;
+            }
+            let mut child_prefix = node_prefix.clone();
+            child_prefix.push(child.chr());
+
+            if !is_backptr(child.id()) {
+                self.mark_reachable(block_id, child, &child_prefix)?;
+                continue;
+            }
+
+            let back_block = child.back_block();
+            if !self.pruned.contains(&back_block) {
+                continue;
+            }
+            if self.pruned.contains(&block_id) {
+                // proving through this back-pointer looks up its target's height
+                self.backptr_targets
+                    .entry(block_id)
+                    .or_insert_with(HashSet::new)
+                    .insert(back_block);
+            }
+            if !self.visited.insert((back_block, child.ptr())) {
+                continue;
+            }
+            self.mark_segment(back_block, &child_prefix)?;
+            self.mark_reachable(back_block, child, &child_prefix)?;
+        }
+        Ok(())
+    }
+
+    /// Keep the nodes on the path from a trie's root to the node at `prefix`, and their
+    /// children's hashes. If the path leaves the trie through a back-pointer, returns the trie
+    /// it continues in.
+    fn mark_segment(&mut self, block_id: u32, prefix: &[u8]) -> Result<Option<u32>, Error> {
+        let mut ptr = self.root.clone();
+        let mut depth = 0;
+        loop {
+            self.keep(block_id, &ptr);
+            let node = self.read_node(block_id, &ptr)?;
+            if node.is_leaf() {
+                break;
+            }
+            for child in node.ptrs().iter() {
+                if child.id() != TrieNodeID::Empty as u8
+                    && !is_backptr(child.id())
+                    && child.ptr() != 0
+                {
+                    self.keep(block_id, child);
+                }
+            }
+
+            if depth >= prefix.len() {
+                break;
+            }
+            let node_path = node.path_bytes();
+            if depth + node_path.len() >= prefix.len()
+                || prefix[depth..depth + node_path.len()] != node_path[..]
+            {
+                break;
+            }
+            depth += node_path.len();
+
+            match node.walk(prefix[depth]) {
+                Some(child) if child.ptr() != 0 => {
+                    if is_backptr(child.id()) {
+                        return Ok(Some(child.back_block()));
+                    }
+                    ptr = child;
+                    depth += 1;
+                }
+                _ => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Keep the nodes that looking up `path` in a trie visits, and their children's hashes.
+    /// Where the lookup follows a back-pointer, the path to its node from the root of the trie
+    /// it points into is kept too.
+    fn mark_key(&mut self, block_id: u32, path: &TriePath) -> Result<(), Error> {
+        let mut block_id = block_id;
+        while let Some(back_block) = self.mark_segment(block_id, path.as_bytes())? {
+            block_id = back_block;
+        }
+        Ok(())
+    }
+
+    /// Keep the nodes of a pruned trie at `height` that building a proof through it visits.
+    /// `blocks` are the local IDs and hashes of the tries its back-pointers may point into.
+    pub fn mark_pruned_trie<T: MarfTrieId>(
+        &mut self,
+        block_id: u32,
+        height: u32,
+        blocks: &[(u32, T)],
+    ) -> Result<(), Error> {
+        // a shunt proof through this trie recomputes its root hash from its root's children
+        self.mark_segment(block_id, &[])?;
+
+        // building a proof through this trie looks up its height and its ancestors' hashes
+        let mut keys = vec![OWN_BLOCK_HEIGHT_KEY.to_string()];
+        let mut log_depth = 0;
+        while log_depth < 32 && (1u32 << log_depth) <= height {
+            keys.push(format!(
+                "{}::{}",
+                BLOCK_HEIGHT_TO_HASH_MAPPING_KEY,
+                height - (1u32 << log_depth)
+            ));
+            log_depth += 1;
+        }
+        if let Some(targets) = self.backptr_targets.get(&block_id) {
+            for (target_id, target_hash) in blocks.iter() {
+                if targets.contains(target_id) {
+                    keys.push(format!(
+                        "{}::{}",
+                        BLOCK_HASH_TO_HEIGHT_MAPPING_KEY, target_hash
+                    ));
+                }
+            }
+        }
+        for key in keys.iter() {
+            self.mark_key(block_id, &TriePath::from_key(key))?;
+        }
+        Ok(())
+    }
+
+    /// The kept nodes of each pruned trie, as their offsets and node IDs
+    pub fn into_kept(self) -> HashMap<u32, BTreeMap<u32, u8>> {
+        self.kept
+    }
+}
+
+/// Point `ptr` at the new offset of its node, if the node's trie was packed. Returns whether
+/// the pointer changed.
+fn remap_ptr(
+    ptr: &mut TriePtr,
+    block_id: u32,
+    new_offsets: &HashMap<u32, HashMap<u32, u32>>,
+) -> bool {
+    if ptr.id() == TrieNodeID::Empty as u8 {
+        return false;
+    }
+    let target_block = if is_backptr(ptr.id()) {
+        ptr.back_block()
+    } else {
+        block_id
+    };
+    match new_offsets.get(&target_block) {
+        Some(offsets) => {
+            let new_ptr = offsets.get(&ptr.ptr()).cloned().unwrap_or(0);
+            let changed = new_ptr != ptr.ptr();
+            ptr.ptr = new_ptr;
+            changed
+        }
+        None => false,
+    }
+}
+
+/// Find where the kept nodes of each trie go once it is packed. Each trie's kept nodes stay in
+/// their current order.
+pub fn layout_tries<T: MarfTrieId>(
+    conn: &Connection,
+    kept: &HashMap<u32, BTreeMap<u32, u8>>,
+) -> Result<HashMap<u32, HashMap<u32, u32>>, Error> {
+    let root_ptr = TrieStorageConnection::<T>::root_ptr_disk();
+    let mut new_offsets = HashMap::new();
+    for (block_id, nodes) in kept.iter() {
+        let blob = trie_sql::read_trie_blob(conn, *block_id)?;
+        let mut offsets = HashMap::new();
+        let mut offset = root_ptr;
+        for (ptr, id) in nodes.iter() {
+            let (node, _) =
+                read_nodetype(&mut Cursor::new(&blob[..]), &TriePtr::new(*id, 0, *ptr))?;
+            offsets.insert(*ptr, offset);
+            offset += get_node_byte_len(&node) as u32;
+        }
+        new_offsets.insert(*block_id, offsets);
+    }
+    Ok(new_offsets)
+}
+
+/// Pack the kept `nodes` of the trie `blob` into a new blob, as laid out by `layout_tries()`.
+/// Back-pointers into the blocks in `block_ids` are given the blocks' new local IDs.
+pub fn pack_trie<T: MarfTrieId>(
+    blob: &[u8],
+    block_id: u32,
+    nodes: &BTreeMap<u32, u8>,
+    new_offsets: &HashMap<u32, HashMap<u32, u32>>,
+    block_ids: &HashMap<u32, u32>,
+) -> Result<Vec<u8>, Error> {
+    let root_ptr = TrieStorageConnection::<T>::root_ptr_disk();
+    let mut packed = Cursor::new(Vec::with_capacity(blob.len()));
+    packed.write_all(&blob[0..root_ptr as usize])?;
+    for (ptr, id) in nodes.iter() {
+        let (mut node, hash) = read_nodetype(&mut Cursor::new(blob), &TriePtr::new(*id, 0, *ptr))?;
+        if !node.is_leaf() {
+            for child in node.ptrs_mut().iter_mut() {
+                remap_ptr(child, block_id, new_offsets);
+                if is_backptr(child.id()) {
+                    if let Some(new_block_id) = block_ids.get(&child.back_block()) {
+                        child.back_block = *new_block_id;
+                    }
+                }
+            }
+        }
+        write_nodetype_bytes(&mut packed, &node, hash)?;
+    }
+    Ok(packed.into_inner())
+}
+
+/// Prune the tries of the blocks more than `depth` blocks below `tip`, in every fork.
+/// The tries of the blocks from that height up, and of unconfirmed blocks, are kept whole, and
+/// the tries that earlier passes pruned are packed again. Keys can no longer be read from the
+/// pruned tries, and blocks can no longer be built on them.
+pub fn prune_tries<T: MarfTrieId>(
+    storage: &mut TrieStorageTransaction<T>,
+    tip: &T,
+    depth: u32,
+) -> Result<PruneStats, Error> {
+    if storage.is_block_pruned(tip)? {
+        return Err(Error::PrunedBlockError(tip.to_string()));
+    }
+    storage.open_block(tip)?;
+    let tip_height = MARF::get_block_height_miner_tip(storage, tip, tip)?.ok_or_else(|| {
+        Error::CorruptionError(format!("Could not obtain block height for block {}", tip))
+    })?;
+    let horizon = tip_height.saturating_sub(depth);
+
+    // find the blocks to prune, and their heights (which the pruned tries still record)
+    let mut heights = trie_sql::get_pruned_block_heights(storage.sqlite_tx())?;
+    let mut pruned = heights.keys().cloned().collect::<HashSet<u32>>();
+    let blocks = trie_sql::get_confirmed_blocks::<T>(storage.sqlite_tx())?;
+    for (block_id, block_hash) in blocks.iter() {
+        if heights.contains_key(block_id) {
+            continue;
+        }
+        match MARF::get_block_height_miner_tip(storage, block_hash, block_hash)? {
+            Some(height) => {
+                if height < horizon {
+                    pruned.insert(*block_id);
+                }
+                heights.insert(*block_id, height);
+            }
+            None => warn!("Could not obtain block height for block {}", block_hash),
+        }
+    }
+
+    let mut stats = PruneStats {
+        horizon,
+        pruned_blocks: pruned.len() as u64,
+        ..PruneStats::default()
+    };
+    if pruned.len() == 0 {
+        return Ok(stats);
+    }
+
+    debug!(
+        "Prune {} tries below height {} (tip {} is at height {})",
+        pruned.len(),
+        horizon,
+        tip,
+        tip_height
+    );
+
+    let root_ptr = TrieStorageConnection::<T>::root_ptr_disk();
+    let root = TriePtr::new(TrieNodeID::Node256 as u8, 0, root_ptr);
+    let conn: &Connection = storage.sqlite_tx();
+
+    let mut retained = vec![];
+    for (block_id, _) in blocks.iter() {
+        if !pruned.contains(block_id) {
+            retained.push(*block_id);
+        }
+    }
+    retained.extend(trie_sql::get_unconfirmed_block_identifiers(conn)?);
+
+    // step 1: find the nodes to keep
+    let mut marker = PruneMarker::new(conn, root.clone(), &pruned);
+    for block_id in retained.iter() {
+        marker.mark_reachable(*block_id, &root, &[])?;
+    }
+    for (block_id, block_hash) in blocks.iter() {
+        if !pruned.contains(block_id) {
+            continue;
+        }
+        marker.mark_pruned_trie(*block_id, heights[block_id], &blocks)?;
+        trace!(
+            "Keep {} nodes of pruned trie {}",
+            marker
+                .kept
+                .get(block_id)
+                .map(|kept| kept.len())
+                .unwrap_or(0),
+            block_hash
+        );
+    }
+    let kept = marker.into_kept();
+
+    // step 2: lay out the kept nodes of each pruned trie in their current order
+    let new_offsets = layout_tries::<T>(conn, &kept)?;
+
+    // step 3: pack the pruned tries
+    for (block_id, block_hash) in blocks.iter() {
+        let nodes = match kept.get(block_id) {
+            Some(nodes) => nodes,
+            None => continue,
+        };
+        let blob = trie_sql::read_trie_blob(conn, *block_id)?;
+        let packed = pack_trie::<T>(&blob, *block_id, nodes, &new_offsets, &HashMap::new())?;
+
+        stats.bytes_before += blob.len() as u64;
+        stats.bytes_after += packed.len() as u64;
+        trie_sql::update_trie_blob(conn, *block_id, &packed)?;
+        trie_sql::set_block_pruned(conn, *block_id, block_hash, heights[block_id])?;
+    }
+
+    // step 4: rewrite the retained tries' back-pointers into the pruned tries
+    for block_id in retained.iter() {
+        let mut blob = trie_sql::read_trie_blob(conn, *block_id)?;
+        let mut changed = false;
+        let mut frontier = VecDeque::new();
+        frontier.push_back(root.clone());
+        while let Some(ptr) = frontier.pop_front() {
+            let (mut node, hash) = read_nodetype(&mut Cursor::new(&blob[..]), &ptr)?;
+            if node.is_leaf() {
+                continue;
+            }
+            let mut node_changed = false;
+            for child in node.ptrs_mut().iter_mut() {
+                if child.id() == TrieNodeID::Empty as u8 {
+                    continue;
+                }
+                if is_backptr(child.id()) {
+                    node_changed |= remap_ptr(child, *block_id, &new_offsets);
+                } else {
+                    frontier.push_back(child.clone());
+                }
+            }
+            if node_changed {
+                let mut cursor = Cursor::new(&mut blob);
+                cursor.seek(SeekFrom::Start(ptr.ptr() as u64))?;
+                write_nodetype_bytes(&mut cursor, &node, hash)?;
+                changed = true;
+            }
+        }
+        if changed {
+            trie_sql::update_trie_blob(conn, *block_id, &blob)?;
+        }
+    }
+
+    storage.open_block(tip)?;
+
+    debug!(
+        "Pruned {} tries below height {}: {} bytes to {} bytes",
+        stats.pruned_blocks, horizon, stats.bytes_before, stats.bytes_after
+    );
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::fs;
+
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::stacks::index::marf::MarfConnection;
+    use chainstate::stacks::index::test::merkle_test_marf_key_value;
+    use chainstate::stacks::index::MARFValue;
+
+    fn block(i: u8) -> BlockHeaderHash {
+        BlockHeaderHash([i; 32])
+    }
+
+    fn open_marf(path: &str) -> MARF<BlockHeaderHash> {
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+        MARF::from_path(path).unwrap()
+    }
+
+    /// Build the next block, and return the keys and values it can see
+    fn append_block(
+        marf: &mut MARF<BlockHeaderHash>,
+        parent: &BlockHeaderHash,
+        next: &BlockHeaderHash,
+        state: &HashMap<String, String>,
+        writes: &[(String, String)],
+    ) -> HashMap<String, String> {
+        marf.begin(parent, next).unwrap();
+        let mut state = state.clone();
+        for (key, value) in writes.iter() {
+            marf.insert(key, MARFValue::from_value(value)).unwrap();
+            state.insert(key.clone(), value.clone());
+        }
+        marf.commit().unwrap();
+        state
+    }
+
+    /// The first block fills out the top of the trie, so that later blocks' keys sit deeper in
+    /// it. Every block overwrites the shared keys.
+    fn block_writes(i: u8) -> Vec<(String, String)> {
+        let mut writes = vec![(format!("key-{}", i), format!("value-{}", i))];
+        for j in 0..10 {
+            writes.push((format!("shared-{}", j), format!("shared-value-{}-{}", j, i)));
+        }
+        if i == 1 {
+            for j in 0..300 {
+                writes.push((format!("filler-{}", j), format!("filler-value-{}", j)));
+            }
+        }
+        writes
+    }
+
+    fn check_state(
+        marf: &mut MARF<BlockHeaderHash>,
+        block_hash: &BlockHeaderHash,
+        state: &HashMap<String, String>,
+        check_proofs: bool,
+    ) {
+        for (key, value) in state.iter() {
+            assert_eq!(
+                marf.get(block_hash, key).unwrap(),
+                Some(MARFValue::from_value(value))
+            );
+            if check_proofs && (!key.starts_with("filler-") || key.ends_with("0")) {
+                merkle_test_marf_key_value(
+                    &mut marf.borrow_storage_backend(),
+                    block_hash,
+                    key,
+                    value,
+                    None,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_prune_tries() {
+        let path = "/tmp/test_prune_tries.marf";
+        let reference_path = "/tmp/test_prune_tries_reference.marf";
+        let mut marf = open_marf(path);
+        let mut reference = open_marf(reference_path);
+
+        // a chain of 40 blocks, and a fork off of the 20th
+        let mut states = vec![HashMap::new()];
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..=40 {
+            let writes = block_writes(i);
+            append_block(&mut reference, &parent, &block(i), &states[0], &writes);
+            let state = append_block(
+                &mut marf,
+                &parent,
+                &block(i),
+                &states[i as usize - 1],
+                &writes,
+            );
+            states.push(state);
+            parent = block(i);
+        }
+        let fork_writes = vec![("fork-key".to_string(), "fork-value".to_string())];
+        append_block(
+            &mut reference,
+            &block(20),
+            &block(200),
+            &states[0],
+            &fork_writes,
+        );
+        append_block(
+            &mut marf,
+            &block(20),
+            &block(200),
+            &states[20],
+            &fork_writes,
+        );
+
+        // block i is at height i - 1, so this keeps blocks 30 to 40
+        let stats = marf.prune(&block(40), 10).unwrap();
+        assert_eq!(stats.horizon, 29);
+        assert_eq!(stats.pruned_blocks, 30);
+        assert!(stats.bytes_after < stats.bytes_before);
+        assert_eq!(marf.get_prune_horizon().unwrap(), Some(29));
+
+        // root hashes are unchanged
+        for i in (1..=40).chain(vec![200].into_iter()) {
+            assert_eq!(
+                marf.get_root_hash_at(&block(i)).unwrap(),
+                reference.get_root_hash_at(&block(i)).unwrap()
+            );
+        }
+
+        // retained blocks can be read and proven
+        for i in 30..=40 {
+            check_state(
+                &mut marf,
+                &block(i),
+                &states[i as usize],
+                i == 30 || i == 40,
+            );
+        }
+
+        // pruned blocks cannot be read, or built on
+        for i in (1..30).chain(vec![200].into_iter()) {
+            assert!(marf.is_block_pruned(&block(i)).unwrap());
+            match marf.get(&block(i), "key-1") {
+                Err(Error::PrunedBlockError(_)) => {}
+                x => panic!("Expected a pruned block error, got {:?}", x),
+            }
+        }
+        assert!(!marf.is_block_pruned(&block(30)).unwrap());
+        match marf.begin(&block(29), &block(41)) {
+            Err(Error::PrunedBlockError(_)) => {}
+            x => panic!("Expected a pruned block error, got {:?}", x),
+        }
+        match marf.prune(&block(10), 1) {
+            Err(Error::PrunedBlockError(_)) => {}
+            x => panic!("Expected a pruned block error, got {:?}", x),
+        }
+
+        // ancestor checks and height lookups still work from retained blocks
+        marf.open_block(&block(40)).unwrap();
+        match marf.check_ancestor_block_hash(&block(5)) {
+            Err(Error::PrunedBlockError(_)) => {}
+            x => panic!("Expected a pruned block error, got {:?}", x),
+        }
+        marf.check_ancestor_block_hash(&block(35)).unwrap();
+        assert_eq!(
+            marf.get_bhh_at_height(&block(40), 3).unwrap(),
+            Some(block(4))
+        );
+        assert_eq!(
+            marf.get_block_height_of(&block(2), &block(40)).unwrap(),
+            Some(1)
+        );
+
+        // the pruned MARF builds the same tries as an unpruned one
+        let writes = block_writes(41);
+        append_block(&mut reference, &block(40), &block(41), &states[0], &writes);
+        let state = append_block(&mut marf, &block(40), &block(41), &states[40], &writes);
+        assert_eq!(
+            marf.get_root_hash_at(&block(41)).unwrap(),
+            reference.get_root_hash_at(&block(41)).unwrap()
+        );
+        states.push(state);
+
+        // pruning again packs the pruned tries further
+        let stats = marf.prune(&block(41), 5).unwrap();
+        assert_eq!(stats.horizon, 35);
+        assert_eq!(stats.pruned_blocks, 36);
+        for i in 36..=41 {
+            check_state(
+                &mut marf,
+                &block(i),
+                &states[i as usize],
+                i == 36 || i == 41,
+            );
+        }
+        for i in 30..36 {
+            assert!(marf.is_block_pruned(&block(i)).unwrap());
+        }
+
+        // nothing new to prune
+        let stats = marf.prune(&block(41), 5).unwrap();
+        assert_eq!(stats.bytes_before, stats.bytes_after);
+
+        marf.vacuum().unwrap();
+        check_state(&mut marf, &block(41), &states[41], false);
+    }
+}
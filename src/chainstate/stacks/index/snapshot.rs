@@ -0,0 +1,811 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshots of the tries at a block.
+//!
+//! A snapshot holds what a MARF needs to read a block's keys and build new blocks on it: the
+//! block's trie, and the tries of its ancestors pruned the way `prune::prune_tries()` prunes
+//! them. It is written as:
+//!
+//! * the magic bytes and format version,
+//! * the number of tries, and
+//! * each trie from the lowest block height up, as its block hash and its packed data blob.
+//!   Back-pointers name the blocks they point into by their position in the snapshot, counting
+//!   from 1.
+//!
+//! Importing a snapshot recomputes the hash of every node that it can reach, and the root hash
+//! of every trie, so a snapshot needs no more trust than the root hash it is checked against.
+//! The ancestors' tries are marked as pruned once imported.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+
+use rusqlite::Connection;
+
+use chainstate::stacks::index::bits::{get_leaf_hash, get_nodetype_hash_bytes};
+use chainstate::stacks::index::marf::MARF;
+use chainstate::stacks::index::node::{
+    clear_backptr, is_backptr, TrieNodeID, TrieNodeType, TriePtr,
+};
+use chainstate::stacks::index::prune::{layout_tries, pack_trie, PruneMarker};
+use chainstate::stacks::index::storage::{TrieStorageConnection, TrieStorageTransaction};
+use chainstate::stacks::index::trie_sql;
+use chainstate::stacks::index::{BlockMap, Error, MARFValue, MarfTrieId, TrieHash};
+
+use util::log;
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"MARF";
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+pub fn write_u32<W: Write>(fd: &mut W, value: u32) -> Result<(), Error> {
+    fd.write_all(&value.to_be_bytes())?;
+    Ok(())
+}
+
+pub fn read_u32<R: Read>(fd: &mut R) -> Result<u32, Error> {
+    let mut bytes = [0u8; 4];
+    fd.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Write a length-prefixed byte string
+pub fn write_data<W: Write>(fd: &mut W, data: &[u8]) -> Result<(), Error> {
+    if data.len() > u32::max_value() as usize {
+        return Err(Error::CorruptionError(format!(
+            "Cannot write {} bytes to a snapshot",
+            data.len()
+        )));
+    }
+    write_u32(fd, data.len() as u32)?;
+    fd.write_all(data)?;
+    Ok(())
+}
+
+/// Read a length-prefixed byte string
+pub fn read_data<R: Read>(fd: &mut R) -> Result<Vec<u8>, Error> {
+    let len = read_u32(fd)?;
+    let mut data = vec![];
+    fd.take(len as u64).read_to_end(&mut data)?;
+    if data.len() != len as usize {
+        return Err(Error::CorruptionError(format!(
+            "Snapshot ends {} bytes into a {}-byte string",
+            data.len(),
+            len
+        )));
+    }
+    Ok(data)
+}
+
+fn root_ptr<T: MarfTrieId>() -> TriePtr {
+    TriePtr::new(
+        TrieNodeID::Node256 as u8,
+        0,
+        TrieStorageConnection::<T>::root_ptr_disk(),
+    )
+}
+
+/// Get the values of the leaves that a trie can reach
+fn get_leaf_values(
+    conn: &Connection,
+    block_id: u32,
+    root: &TriePtr,
+) -> Result<Vec<MARFValue>, Error> {
+    let mut values = vec![];
+    let mut frontier = vec![(block_id, root.clone())];
+    while let Some((block_id, ptr)) = frontier.pop() {
+        let (node, _) = trie_sql::read_node_type(conn, block_id, &ptr.from_backptr())?;
+        if let TrieNodeType::Leaf(leaf) = node {
+            values.push(leaf.data);
+            continue;
+        }
+        for child in node.ptrs().iter() {
+            if child.id() == TrieNodeID::Empty as u8 || child.ptr() == 0 {
+                continue;
+            }
+            if is_backptr(child.id()) {
+                frontier.push((child.back_block(), child.clone()));
+            } else {
+                frontier.push((block_id, child.clone()));
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Write a snapshot of the tries at `block_hash`. Returns the values of the leaves that the
+/// block's trie can reach.
+pub fn export_snapshot<T: MarfTrieId, W: Write>(
+    storage: &mut TrieStorageConnection<T>,
+    block_hash: &T,
+    fd: &mut W,
+) -> Result<Vec<MARFValue>, Error> {
+    let (cur_block_hash, cur_block_id) = storage.get_cur_block_and_id();
+    let result = inner_export_snapshot(storage, block_hash, fd);
+
+    // restore
+    storage.open_block_maybe_id(&cur_block_hash, cur_block_id)?;
+    result
+}
+
+fn inner_export_snapshot<T: MarfTrieId, W: Write>(
+    storage: &mut TrieStorageConnection<T>,
+    block_hash: &T,
+    fd: &mut W,
+) -> Result<Vec<MARFValue>, Error> {
+    MARF::check_not_pruned(storage, block_hash)?;
+    storage.open_block(block_hash)?;
+    let height =
+        MARF::get_block_height_miner_tip(storage, block_hash, block_hash)?.ok_or_else(|| {
+            Error::CorruptionError(format!(
+                "Could not obtain block height for block {}",
+                block_hash
+            ))
+        })?;
+
+    // the tries in the snapshot, by height
+    let mut blocks = vec![];
+    for ancestor_height in 0..height {
+        let ancestor = MARF::get_block_at_height(storage, ancestor_height, block_hash)?
+            .ok_or_else(|| {
+                Error::CorruptionError(format!(
+                    "Could not obtain block hash at block height {}",
+                    ancestor_height
+                ))
+            })?;
+        let ancestor_id = trie_sql::get_block_identifier(storage.sqlite_conn(), &ancestor)?;
+        blocks.push((ancestor_id, ancestor));
+    }
+    let block_id = trie_sql::get_confirmed_block_identifier(storage.sqlite_conn(), block_hash)?
+        .ok_or(Error::NotFoundError)?;
+    blocks.push((block_id, block_hash.clone()));
+
+    debug!(
+        "Export snapshot of {} tries at {} (height {})",
+        blocks.len(),
+        block_hash,
+        height
+    );
+
+    // keep the block's whole trie, and the ancestors' tries as if they were pruned
+    let conn = storage.sqlite_conn();
+    let root = root_ptr::<T>();
+    let tries = blocks.iter().map(|(id, _)| *id).collect::<HashSet<u32>>();
+    let mut marker = PruneMarker::new(conn, root.clone(), &tries);
+    marker.mark_reachable(block_id, &root, &[])?;
+    for (ancestor_height, (ancestor_id, _)) in blocks[0..height as usize].iter().enumerate() {
+        marker.mark_pruned_trie(*ancestor_id, ancestor_height as u32, &blocks)?;
+    }
+    let kept = marker.into_kept();
+
+    let new_offsets = layout_tries::<T>(conn, &kept)?;
+    let snapshot_ids = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, (id, _))| (*id, i as u32 + 1))
+        .collect::<HashMap<u32, u32>>();
+
+    fd.write_all(SNAPSHOT_MAGIC)?;
+    write_u32(fd, SNAPSHOT_VERSION)?;
+    write_u32(fd, blocks.len() as u32)?;
+    for (id, hash) in blocks.iter() {
+        let nodes = kept.get(id).ok_or_else(|| {
+            Error::CorruptionError(format!("Kept no nodes of the trie of {}", hash))
+        })?;
+        let blob = trie_sql::read_trie_blob(conn, *id)?;
+        let packed = pack_trie::<T>(&blob, *id, nodes, &new_offsets, &snapshot_ids)?;
+        fd.write_all(hash.as_bytes())?;
+        write_data(fd, &packed)?;
+    }
+
+    get_leaf_values(conn, block_id, &root)
+}
+
+/// The block hashes of the tries in a snapshot, by their position in it
+struct SnapshotBlockMap<'a, T: MarfTrieId> {
+    hashes: &'a [T],
+}
+
+impl<'a, T: MarfTrieId> BlockMap for SnapshotBlockMap<'a, T> {
+    type TrieId = T;
+    fn get_block_hash(&self, id: u32) -> Result<T, Error> {
+        self.get_block_hash_ref(id).map(|hash| hash.clone())
+    }
+    fn get_block_hash_caching(&mut self, id: u32) -> Result<&T, Error> {
+        self.get_block_hash_ref(id)
+    }
+}
+
+impl<'a, T: MarfTrieId> SnapshotBlockMap<'a, T> {
+    fn get_block_hash_ref(&self, id: u32) -> Result<&'a T, Error> {
+        if id == 0 {
+            return Err(Error::NotFoundError);
+        }
+        self.hashes.get(id as usize - 1).ok_or(Error::NotFoundError)
+    }
+}
+
+/// Check that `backptr`, found at `prefix`, points to the node at `prefix` in its trie
+fn check_backptr<T: MarfTrieId>(
+    conn: &Connection,
+    block_id: u32,
+    prefix: &[u8],
+    backptr: &TriePtr,
+) -> Result<(), Error> {
+    let mut ptr = root_ptr::<T>();
+    let mut depth = 0;
+    while depth < prefix.len() {
+        let (node, _) = trie_sql::read_node_type(conn, block_id, &ptr)?;
+        let node_path = node.path_bytes();
+        if node.is_leaf()
+            || depth + node_path.len() >= prefix.len()
+            || prefix[depth..depth + node_path.len()] != node_path[..]
+        {
+            break;
+        }
+        depth += node_path.len();
+
+        match node.walk(prefix[depth]) {
+            Some(child) if !is_backptr(child.id()) && child.ptr() != 0 => {
+                ptr = child;
+                depth += 1;
+            }
+            _ => break,
+        }
+    }
+
+    if depth != prefix.len()
+        || ptr.ptr() != backptr.ptr()
+        || ptr.id() != clear_backptr(backptr.id())
+    {
+        return Err(Error::CorruptionError(format!(
+            "Back-pointer to offset {} of trie {} does not point to its node",
+            backptr.ptr(),
+            block_id
+        )));
+    }
+    Ok(())
+}
+
+/// Recompute the hash of the node at `ptr` from its children's hashes, and check it against
+/// its stored hash. A node whose children were not kept is taken at its stored hash, which
+/// its parent's hash covers. The root node's hash is returned without its ancestors' hashes.
+fn verify_node<T: MarfTrieId>(
+    conn: &Connection,
+    map: &mut SnapshotBlockMap<T>,
+    block_id: u32,
+    ptr: &TriePtr,
+    prefix: &[u8],
+) -> Result<TrieHash, Error> {
+    let (node, hash) = trie_sql::read_node_type(conn, block_id, ptr)?;
+    let node_hash = match node {
+        TrieNodeType::Leaf(ref leaf) => get_leaf_hash(leaf),
+        _ => {
+            let mut node_prefix = prefix.to_vec();
+            node_prefix.extend_from_slice(node.path_bytes());
+
+            let mut child_hashes = Vec::with_capacity(node.ptrs().len());
+            let mut kept = 0;
+            let mut discarded = 0;
+            for child in node.ptrs().iter() {
+                if child.id() == TrieNodeID::Empty as u8 {
+                    child_hashes.push(TrieHash::from_data(&[]));
+                    continue;
+                }
+                let mut child_prefix = node_prefix.clone();
+                child_prefix.push(child.chr());
+
+                if is_backptr(child.id()) {
+                    let back_block = child.back_block();
+                    if back_block == 0 || back_block >= block_id {
+                        return Err(Error::CorruptionError(format!(
+                            "Trie {} has a back-pointer into trie {}, which is not an ancestor",
+                            block_id, back_block
+                        )));
+                    }
+                    if child.ptr() != 0 {
+                        check_backptr::<T>(conn, back_block, &child_prefix, child)?;
+                    }
+                    // a back-pointer's hash is the hash of the block it points into
+                    let back_block_hash = map.get_block_hash(back_block)?;
+                    child_hashes.push(TrieHash(back_block_hash.to_bytes()));
+                } else if child.ptr() == 0 {
+                    discarded += 1;
+                    child_hashes.push(TrieHash([0u8; 32]));
+                } else {
+                    kept += 1;
+                    child_hashes.push(verify_node(conn, map, block_id, child, &child_prefix)?);
+                }
+            }
+
+            if discarded > 0 {
+                if kept > 0 {
+                    return Err(Error::CorruptionError(format!(
+                        "Node at offset {} of trie {} keeps only some of its children",
+                        ptr.ptr(),
+                        block_id
+                    )));
+                }
+                return Ok(hash);
+            }
+            get_nodetype_hash_bytes::<T, _>(&node, &child_hashes, map)
+        }
+    };
+
+    if !prefix.is_empty() && node_hash != hash {
+        return Err(Error::CorruptionError(format!(
+            "Node at offset {} of trie {} has hash {}, but hashes to {}",
+            ptr.ptr(),
+            block_id,
+            hash,
+            node_hash
+        )));
+    }
+    Ok(node_hash)
+}
+
+/// Import a snapshot of the tries at `block_hash` into empty storage, and check that its
+/// root hash is `root_hash`. Returns the values of the leaves that the block's trie can reach.
+pub fn import_snapshot<T: MarfTrieId, R: Read>(
+    storage: &mut TrieStorageTransaction<T>,
+    fd: &mut R,
+    block_hash: &T,
+    root_hash: &TrieHash,
+) -> Result<Vec<MARFValue>, Error> {
+    if trie_sql::count_blocks(storage.sqlite_tx())? > 0
+        || trie_sql::get_unconfirmed_block_identifiers(storage.sqlite_tx())?.len() > 0
+    {
+        error!("Cannot import a snapshot into a MARF that already has tries");
+        return Err(Error::ExistsError);
+    }
+
+    let mut magic = [0u8; 4];
+    fd.read_exact(&mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err(Error::CorruptionError("Not a MARF snapshot".to_string()));
+    }
+    let version = read_u32(fd)?;
+    if version != SNAPSHOT_VERSION {
+        return Err(Error::CorruptionError(format!(
+            "Unsupported MARF snapshot version {}",
+            version
+        )));
+    }
+
+    // step 1: store the tries, so each one's local ID is its position in the snapshot
+    let num_blocks = read_u32(fd)?;
+    let mut hashes = vec![];
+    for i in 0..num_blocks {
+        let mut hash_bytes = [0u8; 32];
+        fd.read_exact(&mut hash_bytes)?;
+        let hash = T::from_bytes(hash_bytes);
+        let blob = read_data(fd)?;
+        if blob.len() <= TrieStorageConnection::<T>::root_ptr_disk() as usize {
+            return Err(Error::CorruptionError(format!(
+                "Trie of {} has no root node",
+                hash
+            )));
+        }
+        let block_id = trie_sql::write_trie_blob(storage.sqlite_tx(), &hash, &blob)?;
+        if block_id != i + 1 {
+            return Err(Error::CorruptionError(format!(
+                "Trie of {} was stored at {}, not {}",
+                hash,
+                block_id,
+                i + 1
+            )));
+        }
+        hashes.push(hash);
+    }
+    if hashes.last() != Some(block_hash) {
+        return Err(Error::CorruptionError(format!(
+            "Snapshot is not of block {}",
+            block_hash
+        )));
+    }
+    let height = num_blocks - 1;
+
+    debug!(
+        "Verify snapshot of {} tries at {} (height {})",
+        num_blocks, block_hash, height
+    );
+
+    // step 2: recompute the hashes of every trie, from the lowest block height up, since each
+    // root hash covers the root hashes below it
+    let root = root_ptr::<T>();
+    let mut roots = vec![];
+    {
+        let conn: &Connection = storage.sqlite_tx();
+        let mut map = SnapshotBlockMap { hashes: &hashes };
+        for (i, hash) in hashes.iter().enumerate() {
+            let node_hash = verify_node(conn, &mut map, i as u32 + 1, &root, &[])?;
+            let mut hashes_bytes = vec![node_hash];
+            let mut log_depth = 0;
+            while log_depth < 32 && (1usize << log_depth) <= i {
+                hashes_bytes.push(roots[i - (1usize << log_depth)]);
+                log_depth += 1;
+            }
+            let trie_root_hash = if hashes_bytes.len() == 1 {
+                node_hash
+            } else {
+                TrieHash::from_data_array(hashes_bytes.as_slice())
+            };
+
+            let (_, stored_root_hash) = trie_sql::read_node_type(conn, i as u32 + 1, &root)?;
+            if stored_root_hash != trie_root_hash {
+                return Err(Error::CorruptionError(format!(
+                    "Trie of {} has root hash {}, but hashes to {}",
+                    hash, stored_root_hash, trie_root_hash
+                )));
+            }
+            roots.push(trie_root_hash);
+        }
+    }
+    if roots[height as usize] != *root_hash {
+        return Err(Error::CorruptionError(format!(
+            "Snapshot of {} has root hash {}, not {}",
+            block_hash, roots[height as usize], root_hash
+        )));
+    }
+
+    // step 3: the root hashes are chained by height, so check that each trie is the one the
+    // block's trie records at its height
+    storage.open_block(block_hash)?;
+    if MARF::get_block_height_miner_tip(storage, block_hash, block_hash)? != Some(height) {
+        return Err(Error::CorruptionError(format!(
+            "Trie of {} is not at height {}",
+            block_hash, height
+        )));
+    }
+    for (ancestor_height, hash) in hashes[0..height as usize].iter().enumerate() {
+        let ancestor = MARF::get_block_at_height(storage, ancestor_height as u32, block_hash)?;
+        if ancestor.as_ref() != Some(hash) {
+            return Err(Error::CorruptionError(format!(
+                "Trie of {} is not at height {} below {}",
+                hash, ancestor_height, block_hash
+            )));
+        }
+    }
+
+    for (ancestor_height, hash) in hashes[0..height as usize].iter().enumerate() {
+        trie_sql::set_block_pruned(
+            storage.sqlite_tx(),
+            ancestor_height as u32 + 1,
+            hash,
+            ancestor_height as u32,
+        )?;
+    }
+
+    debug!(
+        "Imported snapshot of {} tries at {} with root hash {}",
+        num_blocks, block_hash, root_hash
+    );
+    get_leaf_values(storage.sqlite_tx(), height + 1, &root)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs;
+
+    use chainstate::burn::BlockHeaderHash;
+    use chainstate::stacks::index::marf::MarfConnection;
+    use chainstate::stacks::index::test::merkle_test_marf_key_value;
+    use chainstate::stacks::StacksBlockId;
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::{MarfedKV, NULL_BURN_STATE_DB, NULL_HEADER_DB};
+    use vm::types::{QualifiedContractIdentifier, StandardPrincipalData, Value};
+
+    fn block(i: u8) -> BlockHeaderHash {
+        BlockHeaderHash([i; 32])
+    }
+
+    fn open_marf(path: &str) -> MARF<BlockHeaderHash> {
+        if fs::metadata(path).is_ok() {
+            fs::remove_file(path).unwrap();
+        }
+        MARF::from_path(path).unwrap()
+    }
+
+    fn block_writes(i: u8) -> Vec<(String, String)> {
+        let mut writes = vec![(format!("key-{}", i), format!("value-{}", i))];
+        for j in 0..5 {
+            writes.push((format!("shared-{}", j), format!("shared-value-{}-{}", j, i)));
+        }
+        if i == 1 {
+            for j in 0..200 {
+                writes.push((format!("filler-{}", j), format!("filler-value-{}", j)));
+            }
+        }
+        writes
+    }
+
+    fn append_block(
+        marf: &mut MARF<BlockHeaderHash>,
+        parent: &BlockHeaderHash,
+        next: &BlockHeaderHash,
+        writes: &[(String, String)],
+    ) {
+        marf.begin(parent, next).unwrap();
+        for (key, value) in writes.iter() {
+            marf.insert(key, MARFValue::from_value(value)).unwrap();
+        }
+        marf.commit().unwrap();
+    }
+
+    fn import(
+        path: &str,
+        snapshot: &[u8],
+        block_hash: &BlockHeaderHash,
+        root_hash: &TrieHash,
+    ) -> Result<(MARF<BlockHeaderHash>, Vec<MARFValue>), Error> {
+        let mut marf = open_marf(path);
+        let values = marf.import_snapshot(&mut &snapshot[..], block_hash, root_hash)?;
+        Ok((marf, values))
+    }
+
+    fn sorted(values: &[MARFValue]) -> Vec<String> {
+        let mut values: Vec<_> = values.iter().map(|value| value.to_hex()).collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_snapshot_export_import() {
+        let path = "/tmp/test_snapshot_export_import.marf";
+        let import_path = "/tmp/test_snapshot_export_import_imported.marf";
+        let mut marf = open_marf(path);
+
+        // a chain of 30 blocks, and a fork off of the 10th
+        let mut state = HashMap::new();
+        let mut parent = BlockHeaderHash::sentinel();
+        for i in 1..=30 {
+            let writes = block_writes(i);
+            append_block(&mut marf, &parent, &block(i), &writes);
+            if i <= 20 {
+                state.extend(writes.into_iter());
+            }
+            parent = block(i);
+        }
+        append_block(
+            &mut marf,
+            &block(10),
+            &block(100),
+            &[("fork-key".to_string(), "fork-value".to_string())],
+        );
+
+        let root_hash = marf.get_root_hash_at(&block(20)).unwrap();
+        let mut snapshot = vec![];
+        let exported = marf.export_snapshot(&block(20), &mut snapshot).unwrap();
+
+        let (mut imported, values) =
+            import(import_path, &snapshot, &block(20), &root_hash).unwrap();
+        assert_eq!(sorted(&exported), sorted(&values));
+        for i in 1..=20 {
+            assert_eq!(
+                imported.get_root_hash_at(&block(i)).unwrap(),
+                marf.get_root_hash_at(&block(i)).unwrap()
+            );
+        }
+        assert!(!imported.is_block_pruned(&block(20)).unwrap());
+        for i in 1..20 {
+            assert!(imported.is_block_pruned(&block(i)).unwrap());
+        }
+        assert!(imported.get_root_hash_at(&block(100)).is_err());
+
+        // the block's state can be read and proven
+        for (key, value) in state.iter() {
+            assert_eq!(
+                imported.get(&block(20), key).unwrap(),
+                Some(MARFValue::from_value(value))
+            );
+            if !key.starts_with("filler-") || key.ends_with("0") {
+                merkle_test_marf_key_value(
+                    &mut imported.borrow_storage_backend(),
+                    &block(20),
+                    key,
+                    value,
+                    None,
+                );
+            }
+        }
+        assert_eq!(imported.get(&block(20), "key-21").unwrap(), None);
+
+        // the imported MARF builds the same tries as the original
+        append_block(&mut imported, &block(20), &block(200), &block_writes(21));
+        append_block(&mut marf, &block(20), &block(200), &block_writes(21));
+        assert_eq!(
+            imported.get_root_hash_at(&block(200)).unwrap(),
+            marf.get_root_hash_at(&block(200)).unwrap()
+        );
+
+        // a snapshot can only be imported into an empty MARF
+        match imported.import_snapshot(&mut &snapshot[..], &block(20), &root_hash) {
+            Err(Error::ExistsError) => {}
+            x => panic!("Expected an exists error, got {:?}", x),
+        }
+
+        // the snapshot must have the expected root hash and block
+        match import(import_path, &snapshot, &block(20), &TrieHash([0u8; 32])) {
+            Err(Error::CorruptionError(_)) => {}
+            x => panic!("Expected a corruption error, got {:?}", x.map(|_| ())),
+        }
+        match import(import_path, &snapshot, &block(19), &root_hash) {
+            Err(Error::CorruptionError(_)) => {}
+            x => panic!("Expected a corruption error, got {:?}", x.map(|_| ())),
+        }
+
+        // a snapshot whose values were changed does not hash to the root hash
+        let value = MARFValue::from_value("value-20");
+        let offset = snapshot
+            .windows(value.as_bytes().len())
+            .position(|window| window == value.as_bytes())
+            .unwrap();
+        let mut tampered = snapshot.clone();
+        tampered[offset] ^= 0x01;
+        match import(import_path, &tampered, &block(20), &root_hash) {
+            Err(Error::CorruptionError(_)) => {}
+            x => panic!("Expected a corruption error, got {:?}", x.map(|_| ())),
+        }
+
+        // nothing is kept from a failed import
+        let mut empty = MARF::<BlockHeaderHash>::from_path(import_path).unwrap();
+        assert!(!empty.get_root_hash_at(&block(20)).is_ok());
+
+        // a pruned MARF can export the blocks it kept
+        marf.prune(&block(30), 5).unwrap();
+        let root_hash = marf.get_root_hash_at(&block(28)).unwrap();
+        let mut snapshot = vec![];
+        marf.export_snapshot(&block(28), &mut snapshot).unwrap();
+        let (mut imported, _) = import(import_path, &snapshot, &block(28), &root_hash).unwrap();
+        merkle_test_marf_key_value(
+            &mut imported.borrow_storage_backend(),
+            &block(28),
+            &"key-3".to_string(),
+            &"value-3".to_string(),
+            None,
+        );
+        match marf.export_snapshot(&block(20), &mut vec![]) {
+            Err(Error::PrunedBlockError(_)) => {}
+            x => panic!("Expected a pruned block error, got {:?}", x.map(|_| ())),
+        }
+    }
+
+    /// Replace the only occurrence of `original` in `snapshot` with `replacement`, of the same
+    /// length
+    fn replace_bytes(snapshot: &[u8], original: &str, replacement: &str) -> Vec<u8> {
+        assert_eq!(original.len(), replacement.len());
+        let positions: Vec<_> = snapshot
+            .windows(original.len())
+            .enumerate()
+            .filter(|(_, window)| *window == original.as_bytes())
+            .map(|(position, _)| position)
+            .collect();
+        assert_eq!(
+            positions.len(),
+            1,
+            "{} is not in the snapshot once",
+            original
+        );
+
+        let mut replaced = snapshot.to_vec();
+        replaced[positions[0]..positions[0] + original.len()]
+            .copy_from_slice(replacement.as_bytes());
+        replaced
+    }
+
+    #[test]
+    fn test_clarity_snapshot_export_import() {
+        let path = "/tmp/test_clarity_snapshot_export_import";
+        if fs::metadata(path).is_ok() {
+            fs::remove_dir_all(path).unwrap();
+        }
+
+        let contract_id = QualifiedContractIdentifier::local("counter").unwrap();
+        let contract = "(define-constant step 1)
+             (define-data-var counter int 1)
+             (define-map bumped int principal)
+             (define-public (bump)
+               (begin
+                 (var-set counter (+ (var-get counter) step))
+                 (map-set bumped (var-get counter) tx-sender)
+                 (ok (var-get counter))))";
+
+        let mut marf_kv = MarfedKV::temporary();
+        {
+            let mut store = marf_kv.begin(&StacksBlockId::sentinel(), &StacksBlockId([0u8; 32]));
+            store
+                .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB)
+                .initialize();
+            store.test_commit();
+        }
+        {
+            let mut store = marf_kv.begin(&StacksBlockId([0u8; 32]), &StacksBlockId([1u8; 32]));
+            let mut owned_env =
+                OwnedEnvironment::new(store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+            owned_env
+                .initialize_contract(contract_id.clone(), contract)
+                .unwrap();
+            store.test_commit();
+        }
+
+        let tip = StacksBlockId([1u8; 32]);
+        let root_hash = marf_kv.get_marf().get_root_hash_at(&tip).unwrap();
+        let mut snapshot = vec![];
+        MarfedKV::export_snapshot(marf_kv.get_marf(), &tip, &mut snapshot).unwrap();
+
+        // the state must hash to the expected root hash
+        assert!(MarfedKV::import_snapshot(
+            path,
+            &tip,
+            &TrieHash([0u8; 32]),
+            false,
+            &mut &snapshot[..]
+        )
+        .is_err());
+
+        // the contract's source must hash to its contract hash, and its constants must be the
+        // ones of its source
+        let tampered_path = "/tmp/test_clarity_snapshot_export_import_tampered";
+        for (original, tampered) in [
+            ("(define-constant step 1)", "(define-constant step 7)"),
+            ("\"step\":{\"Int\":1}", "\"step\":{\"Int\":7}"),
+        ]
+        .iter()
+        {
+            let tampered_snapshot = replace_bytes(&snapshot, original, tampered);
+            assert!(MarfedKV::import_snapshot(
+                tampered_path,
+                &tip,
+                &root_hash,
+                false,
+                &mut &tampered_snapshot[..]
+            )
+            .is_err());
+            fs::remove_dir_all(tampered_path).unwrap();
+        }
+
+        // the contract's code is rebuilt from its source, whatever the snapshot's contract says
+        let tampered_snapshot = replace_bytes(&snapshot, "\"step\"}", "\"stop\"}");
+        let mut imported =
+            MarfedKV::import_snapshot(path, &tip, &root_hash, false, &mut &tampered_snapshot[..])
+                .unwrap();
+
+        // the imported state can run the contract, and builds the same state as the original
+        let next = StacksBlockId([2u8; 32]);
+        for marf_kv in [&mut marf_kv, &mut imported].iter_mut() {
+            let mut store = marf_kv.begin(&tip, &next);
+            let mut owned_env =
+                OwnedEnvironment::new(store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB));
+            let (result, _, _) = owned_env
+                .execute_transaction(
+                    Value::from(StandardPrincipalData::transient()),
+                    contract_id.clone(),
+                    "bump",
+                    &[],
+                )
+                .unwrap();
+            assert_eq!(result, Value::okay(Value::Int(2)).unwrap());
+            store.test_commit();
+        }
+        assert_eq!(
+            imported.get_marf().get_root_hash_at(&next).unwrap(),
+            marf_kv.get_marf().get_root_hash_at(&next).unwrap()
+        );
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
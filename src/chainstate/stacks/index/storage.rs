@@ -810,6 +810,8 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
 
         if create_flag {
             trie_sql::create_tables_if_needed(&mut db)?;
+        } else if !readonly {
+            trie_sql::migrate_tables_if_needed(&mut db)?;
         }
 
         debug!("Opened TrieFileStorage {};", db_path);
@@ -873,6 +875,16 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
         self.data.unconfirmed
     }
 
+    /// Rebuild the database file, so the space freed by pruning is returned to the filesystem.
+    /// This rewrites the whole file, and must not run while other connections are open.
+    pub fn vacuum(&mut self) -> Result<(), Error> {
+        if self.readonly() {
+            return Err(Error::ReadOnlyError);
+        }
+        self.db.execute_batch("VACUUM;")?;
+        Ok(())
+    }
+
     pub fn reopen_readonly(&self) -> Result<TrieFileStorage<T>, Error> {
         let db = Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
         db.busy_handler(Some(tx_busy_handler))?;
@@ -1362,6 +1374,16 @@ impl<'a, T: MarfTrieId> TrieStorageConnection<'a, T> {
         }
     }
 
+    pub fn sqlite_conn(&self) -> &Connection {
+        &self.db
+    }
+
+    /// Has the trie of this block been pruned?  A pruned trie can still be opened, since its
+    /// root hash and block height records are kept, but its other keys cannot be read.
+    pub fn is_block_pruned(&self, bhh: &T) -> Result<bool, Error> {
+        trie_sql::is_block_pruned(&self.db, bhh)
+    }
+
     pub fn has_block(&self, bhh: &T) -> Result<bool, Error> {
         Ok(self.has_confirmed_block(bhh)? || self.has_unconfirmed_block(bhh)?)
     }
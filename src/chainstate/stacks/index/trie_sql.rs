@@ -91,6 +91,14 @@ static SQL_EXTENSION_LOCKS_TABLE: &str = "
 CREATE TABLE IF NOT EXISTS block_extension_locks (block_hash TEXT PRIMARY KEY);
 ";
 
+static SQL_MARF_PRUNED_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS pruned_blocks (
+   block_id INTEGER PRIMARY KEY,
+   block_hash TEXT UNIQUE NOT NULL,
+   block_height INTEGER NOT NULL
+);
+";
+
 pub fn create_tables_if_needed(conn: &mut Connection) -> Result<(), Error> {
     sql_pragma(conn, "PRAGMA journal_mode = WAL;")?;
 
@@ -99,10 +107,18 @@ pub fn create_tables_if_needed(conn: &mut Connection) -> Result<(), Error> {
     tx.execute_batch(SQL_MARF_DATA_TABLE)?;
     tx.execute_batch(SQL_MARF_MINED_TABLE)?;
     tx.execute_batch(SQL_EXTENSION_LOCKS_TABLE)?;
+    tx.execute_batch(SQL_MARF_PRUNED_TABLE)?;
 
     tx.commit().map_err(|e| e.into())
 }
 
+/// Add the tables that databases created by earlier versions lack
+pub fn migrate_tables_if_needed(conn: &mut Connection) -> Result<(), Error> {
+    let tx = tx_begin_immediate(conn)?;
+    tx.execute_batch(SQL_MARF_PRUNED_TABLE)?;
+    tx.commit().map_err(|e| e.into())
+}
+
 pub fn get_block_identifier<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Result<u32, Error> {
     conn.query_row(
         "SELECT block_id FROM marf_data WHERE block_hash = ?",
@@ -418,6 +434,89 @@ pub fn count_blocks(conn: &Connection) -> Result<u32, Error> {
     Ok(result)
 }
 
+/// Get the local IDs and hashes of all confirmed tries, in the order they were stored
+pub fn get_confirmed_blocks<T: MarfTrieId>(conn: &Connection) -> Result<Vec<(u32, T)>, Error> {
+    let mut s = conn.prepare(
+        "SELECT block_id, block_hash FROM marf_data WHERE unconfirmed = 0 ORDER BY block_id",
+    )?;
+    let rows = s.query_and_then(NO_PARAMS, |row| {
+        let block_id: u32 = row.get("block_id")?;
+        let block_hash: T = row.get("block_hash")?;
+        Ok((block_id, block_hash))
+    })?;
+    rows.collect()
+}
+
+pub fn get_unconfirmed_block_identifiers(conn: &Connection) -> Result<Vec<u32>, Error> {
+    let mut s = conn.prepare("SELECT block_id FROM marf_data WHERE unconfirmed = 1")?;
+    let rows = s.query_and_then(NO_PARAMS, |row| row.get("block_id").map_err(Error::from))?;
+    rows.collect()
+}
+
+/// Read a trie's whole data blob
+pub fn read_trie_blob(conn: &Connection, block_id: u32) -> Result<Vec<u8>, Error> {
+    conn.query_row(
+        "SELECT data FROM marf_data WHERE block_id = ?",
+        &[block_id],
+        |row| row.get("data"),
+    )
+    .map_err(|e| e.into())
+}
+
+/// Replace a trie's data blob. The trie's local ID stays the same.
+pub fn update_trie_blob(conn: &Connection, block_id: u32, data: &[u8]) -> Result<(), Error> {
+    let args: &[&dyn ToSql] = &[&data, &block_id];
+    conn.execute("UPDATE marf_data SET data = ? WHERE block_id = ?", args)?;
+    Ok(())
+}
+
+pub fn is_block_pruned<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Result<bool, Error> {
+    let pruned = conn
+        .query_row(
+            "SELECT 1 FROM pruned_blocks WHERE block_hash = ?",
+            &[bhh],
+            |_row| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    Ok(pruned)
+}
+
+/// Get the heights of the pruned tries, by local ID
+pub fn get_pruned_block_heights(conn: &Connection) -> Result<HashMap<u32, u32>, Error> {
+    let mut s = conn.prepare("SELECT block_id, block_height FROM pruned_blocks")?;
+    let rows = s.query_and_then(NO_PARAMS, |row| {
+        let block_id: u32 = row.get("block_id")?;
+        let block_height: u32 = row.get("block_height")?;
+        Ok((block_id, block_height))
+    })?;
+    rows.collect()
+}
+
+pub fn set_block_pruned<T: MarfTrieId>(
+    conn: &Connection,
+    block_id: u32,
+    bhh: &T,
+    block_height: u32,
+) -> Result<(), Error> {
+    let args: &[&dyn ToSql] = &[&block_id, bhh, &block_height];
+    conn.execute(
+        "INSERT OR REPLACE INTO pruned_blocks (block_id, block_hash, block_height) VALUES (?, ?, ?)",
+        args,
+    )?;
+    Ok(())
+}
+
+/// Get the lowest block height that the last pruning kept, if the tries were ever pruned
+pub fn get_prune_horizon(conn: &Connection) -> Result<Option<u32>, Error> {
+    let max_height: Option<u32> = conn.query_row(
+        "SELECT MAX(block_height) FROM pruned_blocks",
+        NO_PARAMS,
+        |row| row.get(0),
+    )?;
+    Ok(max_height.map(|height| height + 1))
+}
+
 pub fn drop_lock<T: MarfTrieId>(conn: &Connection, bhh: &T) -> Result<(), Error> {
     conn.execute(
         "DELETE FROM block_extension_locks WHERE block_hash = ?",
@@ -445,5 +544,6 @@ pub fn clear_tables(tx: &Transaction) -> Result<(), Error> {
     tx.execute("DELETE FROM block_extension_locks", NO_PARAMS)?;
     tx.execute("DELETE FROM marf_data", NO_PARAMS)?;
     tx.execute("DELETE FROM mined_blocks", NO_PARAMS)?;
+    tx.execute("DELETE FROM pruned_blocks", NO_PARAMS)?;
     Ok(())
 }
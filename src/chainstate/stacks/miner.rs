@@ -1288,6 +1288,7 @@ impl StacksBlockBuilder {
     }
 
     /// Given access to the mempool, mine an anchored block with no more than the given execution cost.
+    ///   Mempool transactions are considered in the order they arrived.
     ///   returns the assembled block, and the consumed execution budget.
     pub fn build_anchored_block(
         chainstate_handle: &StacksChainState, // not directly used; used as a handle to open other chainstates
@@ -1299,6 +1300,35 @@ impl StacksBlockBuilder {
         pubkey_hash: Hash160,
         coinbase_tx: &StacksTransaction,
         execution_budget: ExecutionCost,
+    ) -> Result<(StacksBlock, ExecutionCost, u64), Error> {
+        StacksBlockBuilder::build_anchored_block_with_order(
+            chainstate_handle,
+            burn_dbconn,
+            mempool,
+            parent_stacks_header,
+            total_burn,
+            proof,
+            pubkey_hash,
+            coinbase_tx,
+            execution_budget,
+            MemPoolWalkOrder::Arrival,
+        )
+    }
+
+    /// Given access to the mempool, mine an anchored block with no more than the given execution cost,
+    ///   considering mempool transactions in the given order.
+    ///   returns the assembled block, and the consumed execution budget.
+    pub fn build_anchored_block_with_order(
+        chainstate_handle: &StacksChainState, // not directly used; used as a handle to open other chainstates
+        burn_dbconn: &SortitionDBConn,
+        mempool: &mut MemPoolDB,
+        parent_stacks_header: &StacksHeaderInfo, // Stacks header we're building off of
+        total_burn: u64, // the burn so far on the burnchain (i.e. from the last burnchain block)
+        proof: VRFProof, // proof over the burnchain's last seed
+        pubkey_hash: Hash160,
+        coinbase_tx: &StacksTransaction,
+        execution_budget: ExecutionCost,
+        walk_order: MemPoolWalkOrder,
     ) -> Result<(StacksBlock, ExecutionCost, u64), Error> {
         if let TransactionPayload::Coinbase(..) = coinbase_tx.payload {
         } else {
@@ -1318,6 +1348,8 @@ impl StacksBlockBuilder {
             &tip_consensus_hash, &tip_block_hash, tip_height
         );
 
+        let block_limit = execution_budget.clone();
+
         let (mut header_reader_chainstate, _) = chainstate_handle.reopen()?; // used for reading block headers during an epoch
         let (mut chainstate, _) = chainstate_handle.reopen_limited(execution_budget)?; // used for processing a block up to the given limit
 
@@ -1340,86 +1372,97 @@ impl StacksBlockBuilder {
 
         let mut block_limit_hit = BlockLimitFunction::NO_LIMIT_HIT;
 
-        let result = mempool.iterate_candidates(
-            &tip_consensus_hash,
-            &tip_block_hash,
-            tip_height,
-            &mut header_reader_chainstate,
-            |available_txs| {
-                if block_limit_hit == BlockLimitFunction::LIMIT_REACHED {
-                    return Ok(());
-                }
+        let mut mine_candidates = |available_txs: Vec<MemPoolTxInfo>| -> Result<(), Error> {
+            if block_limit_hit == BlockLimitFunction::LIMIT_REACHED {
+                return Ok(());
+            }
 
-                for txinfo in available_txs.into_iter() {
-                    // skip transactions early if we can
-                    if considered.contains(&txinfo.tx.txid()) {
+            for txinfo in available_txs.into_iter() {
+                // skip transactions early if we can
+                if considered.contains(&txinfo.tx.txid()) {
+                    continue;
+                }
+                if let Some(nonce) = mined_origin_nonces.get(&txinfo.tx.origin_address()) {
+                    if *nonce >= txinfo.tx.get_origin_nonce() {
                         continue;
                     }
-                    if let Some(nonce) = mined_origin_nonces.get(&txinfo.tx.origin_address()) {
-                        if *nonce >= txinfo.tx.get_origin_nonce() {
-                            continue;
-                        }
-                    }
-                    if let Some(sponsor_addr) = txinfo.tx.sponsor_address() {
-                        if let Some(nonce) = mined_sponsor_nonces.get(&sponsor_addr) {
-                            if let Some(sponsor_nonce) = txinfo.tx.get_sponsor_nonce() {
-                                if *nonce >= sponsor_nonce {
-                                    continue;
-                                }
+                }
+                if let Some(sponsor_addr) = txinfo.tx.sponsor_address() {
+                    if let Some(nonce) = mined_sponsor_nonces.get(&sponsor_addr) {
+                        if let Some(sponsor_nonce) = txinfo.tx.get_sponsor_nonce() {
+                            if *nonce >= sponsor_nonce {
+                                continue;
                             }
                         }
                     }
+                }
 
-                    considered.insert(txinfo.tx.txid());
-
-                    match builder.try_mine_tx_with_len(
-                        &mut epoch_tx,
-                        &txinfo.tx,
-                        txinfo.metadata.len,
-                        &block_limit_hit,
-                    ) {
-                        Ok(_) => {}
-                        Err(Error::BlockTooBigError) => {
-                            // done mining -- our execution budget is exceeded.
-                            // Make the block from the transactions we did manage to get
-                            debug!("Block budget exceeded on tx {}", &txinfo.tx.txid());
-                            if block_limit_hit == BlockLimitFunction::NO_LIMIT_HIT {
-                                block_limit_hit = BlockLimitFunction::CONTRACT_LIMIT_HIT;
-                                continue;
-                            } else if block_limit_hit == BlockLimitFunction::CONTRACT_LIMIT_HIT {
-                                block_limit_hit = BlockLimitFunction::LIMIT_REACHED;
-                            }
-                        }
-                        Err(Error::TransactionTooBigError) => {
-                            invalidated_txs.push(txinfo.metadata.txid);
-                            if block_limit_hit == BlockLimitFunction::NO_LIMIT_HIT {
-                                block_limit_hit = BlockLimitFunction::CONTRACT_LIMIT_HIT;
-                                continue;
-                            } else if block_limit_hit == BlockLimitFunction::CONTRACT_LIMIT_HIT {
-                                block_limit_hit = BlockLimitFunction::LIMIT_REACHED;
-                            }
-                        }
-                        Err(Error::InvalidStacksTransaction(_, true)) => {
-                            // if we have an invalid transaction that was quietly ignored, don't warn here either
+                considered.insert(txinfo.tx.txid());
+
+                match builder.try_mine_tx_with_len(
+                    &mut epoch_tx,
+                    &txinfo.tx,
+                    txinfo.metadata.len,
+                    &block_limit_hit,
+                ) {
+                    Ok(_) => {}
+                    Err(Error::BlockTooBigError) => {
+                        // done mining -- our execution budget is exceeded.
+                        // Make the block from the transactions we did manage to get
+                        debug!("Block budget exceeded on tx {}", &txinfo.tx.txid());
+                        if block_limit_hit == BlockLimitFunction::NO_LIMIT_HIT {
+                            block_limit_hit = BlockLimitFunction::CONTRACT_LIMIT_HIT;
                             continue;
+                        } else if block_limit_hit == BlockLimitFunction::CONTRACT_LIMIT_HIT {
+                            block_limit_hit = BlockLimitFunction::LIMIT_REACHED;
                         }
-                        Err(e) => {
-                            warn!("Failed to apply tx {}: {:?}", &txinfo.tx.txid(), &e);
+                    }
+                    Err(Error::TransactionTooBigError) => {
+                        invalidated_txs.push(txinfo.metadata.txid);
+                        if block_limit_hit == BlockLimitFunction::NO_LIMIT_HIT {
+                            block_limit_hit = BlockLimitFunction::CONTRACT_LIMIT_HIT;
                             continue;
+                        } else if block_limit_hit == BlockLimitFunction::CONTRACT_LIMIT_HIT {
+                            block_limit_hit = BlockLimitFunction::LIMIT_REACHED;
                         }
                     }
-
-                    mined_origin_nonces
-                        .insert(txinfo.tx.origin_address(), txinfo.tx.get_origin_nonce());
-                    if let (Some(sponsor_addr), Some(sponsor_nonce)) =
-                        (txinfo.tx.sponsor_address(), txinfo.tx.get_sponsor_nonce())
-                    {
-                        mined_sponsor_nonces.insert(sponsor_addr, sponsor_nonce);
+                    Err(Error::InvalidStacksTransaction(_, true)) => {
+                        // if we have an invalid transaction that was quietly ignored, don't warn here either
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("Failed to apply tx {}: {:?}", &txinfo.tx.txid(), &e);
+                        continue;
                     }
                 }
-                Ok(())
-            },
-        );
+
+                mined_origin_nonces
+                    .insert(txinfo.tx.origin_address(), txinfo.tx.get_origin_nonce());
+                if let (Some(sponsor_addr), Some(sponsor_nonce)) =
+                    (txinfo.tx.sponsor_address(), txinfo.tx.get_sponsor_nonce())
+                {
+                    mined_sponsor_nonces.insert(sponsor_addr, sponsor_nonce);
+                }
+            }
+            Ok(())
+        };
+
+        let result = match walk_order {
+            MemPoolWalkOrder::Arrival => mempool.iterate_candidates(
+                &tip_consensus_hash,
+                &tip_block_hash,
+                tip_height,
+                &mut header_reader_chainstate,
+                &mut mine_candidates,
+            ),
+            MemPoolWalkOrder::FeeRate => mempool.iterate_candidates_by_fee_rate(
+                &tip_consensus_hash,
+                &tip_block_hash,
+                tip_height,
+                &mut header_reader_chainstate,
+                &mut mine_candidates,
+            ),
+        };
 
         mempool.drop_txs(&invalidated_txs)?;
 
@@ -6552,6 +6595,142 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_build_anchored_blocks_by_fee_rate() {
+        let mut privks = vec![];
+        let mut balances = vec![];
+        let num_blocks = 3;
+        let num_senders = 6;
+
+        for _ in 0..num_senders {
+            let privk = StacksPrivateKey::new();
+            let addr = StacksAddress::from_public_keys(
+                C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                &AddressHashMode::SerializeP2PKH,
+                1,
+                &vec![StacksPublicKey::from_private(&privk)],
+            )
+            .unwrap();
+
+            privks.push(privk);
+            balances.push((addr.to_account_principal(), 100000000));
+        }
+
+        let mut peer_config =
+            TestPeerConfig::new("test_build_anchored_blocks_by_fee_rate", 2016, 2017);
+        peer_config.initial_balances = balances;
+
+        let mut peer = TestPeer::new(peer_config);
+
+        let chainstate_path = peer.chainstate_path.clone();
+
+        let recipient_addr_str = "ST1RFD5Q2QPK3E0F08HG9XDX7SSC7CNRS0QR0SGEV";
+        let recipient = StacksAddress::from_string(recipient_addr_str).unwrap();
+        let mut sender_nonces = vec![0; num_senders];
+
+        for tenure_id in 0..num_blocks {
+            // send transactions to the mempool
+            let tip =
+                SortitionDB::get_canonical_burn_chain_tip(&peer.sortdb.as_ref().unwrap().conn())
+                    .unwrap();
+
+            let (burn_ops, stacks_block, microblocks) = peer.make_tenure(
+                |ref mut miner,
+                 ref mut sortdb,
+                 ref mut chainstate,
+                 vrf_proof,
+                 ref parent_opt,
+                 ref parent_microblock_header_opt| {
+                    let parent_tip = match parent_opt {
+                        None => StacksChainState::get_genesis_header_info(chainstate.db()).unwrap(),
+                        Some(block) => {
+                            let ic = sortdb.index_conn();
+                            let snapshot =
+                                SortitionDB::get_block_snapshot_for_winning_stacks_block(
+                                    &ic,
+                                    &tip.sortition_id,
+                                    &block.block_hash(),
+                                )
+                                .unwrap()
+                                .unwrap(); // succeeds because we don't fork
+                            StacksChainState::get_anchored_block_header_info(
+                                chainstate.db(),
+                                &snapshot.consensus_hash,
+                                &snapshot.winning_stacks_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap()
+                        }
+                    };
+
+                    let parent_header_hash = parent_tip.anchored_header.block_hash();
+                    let parent_consensus_hash = parent_tip.consensus_hash.clone();
+
+                    let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+                    let coinbase_tx = make_coinbase(miner, tenure_id);
+
+                    if tenure_id > 0 {
+                        // sender 0 sends a low-fee transaction, and then a high-fee transaction
+                        // that can only be mined after it.  Everyone else pays more the later
+                        // they submit.
+                        let mut fees = vec![200, 5000];
+                        for i in 1..num_senders {
+                            fees.push(200 + 100 * (i as u64));
+                        }
+
+                        for (i, fee) in fees.into_iter().enumerate() {
+                            let sender = if i < 2 { 0 } else { i - 1 };
+                            let stx_transfer = make_user_stacks_transfer(
+                                &privks[sender],
+                                sender_nonces[sender],
+                                fee,
+                                &recipient.to_account_principal(),
+                                1,
+                            );
+                            sender_nonces[sender] += 1;
+                            mempool
+                                .submit(
+                                    chainstate,
+                                    &parent_consensus_hash,
+                                    &parent_header_hash,
+                                    &stx_transfer,
+                                )
+                                .unwrap();
+                        }
+                    }
+
+                    let anchored_block = StacksBlockBuilder::build_anchored_block_with_order(
+                        chainstate,
+                        &sortdb.index_conn(),
+                        &mut mempool,
+                        &parent_tip,
+                        tip.total_burn,
+                        vrf_proof,
+                        Hash160([tenure_id as u8; 20]),
+                        &coinbase_tx,
+                        ExecutionCost::max_value(),
+                        MemPoolWalkOrder::FeeRate,
+                    )
+                    .unwrap();
+                    (anchored_block.0, vec![])
+                },
+            );
+
+            peer.next_burnchain_block(burn_ops.clone());
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+            if tenure_id > 0 {
+                // highest fee rate first, but sender 0's transactions stay in nonce order
+                let fees: Vec<u64> = stacks_block.txs[1..]
+                    .iter()
+                    .map(|tx| tx.get_tx_fee())
+                    .collect();
+                assert_eq!(fees, vec![700, 600, 500, 400, 300, 200, 5000]);
+            }
+        }
+    }
+
     #[test]
     fn test_build_anchored_blocks_skip_too_expensive() {
         let privk = StacksPrivateKey::from_hex(
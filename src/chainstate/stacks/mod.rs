@@ -174,6 +174,8 @@ pub enum Error {
     PoxAlreadyLocked,
     PoxInsufficientBalance,
     PoxNoRewardCycle,
+    /// The block needs Clarity state that this node has pruned. The block itself may be valid.
+    PrunedStateError(String),
 }
 
 impl From<marf_error> for Error {
@@ -230,6 +232,7 @@ impl fmt::Display for Error {
             Error::PoxAlreadyLocked => write!(f, "Account has already locked STX for PoX"),
             Error::PoxInsufficientBalance => write!(f, "Not enough STX to lock"),
             Error::PoxNoRewardCycle => write!(f, "No such reward cycle"),
+            Error::PrunedStateError(ref s) => fmt::Display::fmt(s, f),
         }
     }
 }
@@ -261,6 +264,7 @@ impl error::Error for Error {
             Error::PoxAlreadyLocked => None,
             Error::PoxInsufficientBalance => None,
             Error::PoxNoRewardCycle => None,
+            Error::PrunedStateError(ref _s) => None,
         }
     }
 }
@@ -292,6 +296,7 @@ impl Error {
             Error::PoxAlreadyLocked => "PoxAlreadyLocked",
             Error::PoxInsufficientBalance => "PoxInsufficientBalance",
             Error::PoxNoRewardCycle => "PoxNoRewardCycle",
+            Error::PrunedStateError(ref _s) => "PrunedStateError",
         }
     }
 
@@ -15,17 +15,18 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use rand::Rng;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::env;
 use std::fs;
 use std::io;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use util::log;
 
-use chainstate::burn::BlockHeaderHash;
+use chainstate::burn::{BlockHeaderHash, ConsensusHash};
 use chainstate::stacks::index::{storage::TrieFileStorage, MarfTrieId};
 use chainstate::stacks::StacksBlockId;
 
@@ -43,19 +44,26 @@ use vm::analysis::contract_interface_builder::build_contract_interface;
 use vm::analysis::{errors::CheckResult, AnalysisDatabase, ContractAnalysis};
 use vm::ast::build_ast;
 use vm::contexts::OwnedEnvironment;
-use vm::costs::LimitedCostTracker;
+use vm::costs::profiler::{CostProfile, ProfileMetric};
+use vm::costs::{ExecutionCost, LimitedCostTracker};
+use vm::coverage::CoverageReporter;
 use vm::database::{
     ClarityDatabase, HeadersDB, MarfedKV, MemoryBackingStore, STXBalance, SqliteConnection,
     NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
+use vm::debug::Debugger;
 use vm::errors::{Error, InterpreterResult, RuntimeErrorType};
 use vm::types::{PrincipalData, QualifiedContractIdentifier};
-use vm::{execute as vm_execute, SymbolicExpression, SymbolicExpressionType, Value};
+use vm::{
+    execute as vm_execute, ClarityVersion, DefineFunctionsParsed, SymbolicExpression,
+    SymbolicExpressionType, Value,
+};
 
 use address::c32::c32_address;
 
 use burnchains::BurnchainHeaderHash;
 use chainstate::burn::VRFSeed;
+use chainstate::stacks::boot::{boot_code_id, BOOT_CODE_COSTS, BOOT_CODE_COST_VOTING_TESTNET};
 use chainstate::stacks::StacksAddress;
 
 use serde::Serialize;
@@ -91,7 +99,24 @@ where command is one of:
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
   repl               to typecheck and evaluate expressions in a stdin/stdout loop.
   execute            to execute a public function of a defined contract.
+  debug              like `execute`, but steps through the function in an interactive debugger,
+                     and does not commit its effects.
   generate_address   to generate a random Stacks public address for testing purposes.
+  coverage_report    to print an LCOV report of the contract code that has run with `--coverage`.
+  test               to deploy contracts to a fresh VM state database and run the tests in them.
+
+`launch`, `execute`, `eval`, `eval_at_chaintip`, and `eval_at_block` accept a `--coverage` flag,
+which records the contract code they run in the VM state database.
+
+`execute` accepts a `--profile` flag, which prints what each function called cost, using the
+default cost functions.  With `--profile=<path>`, it also writes the runtime cost of each call
+stack to `<path>` in the folded-stack format that flame graph tools read.
+
+`test` runs the public functions named `test-*` in the contracts whose files are named
+`*_test.clar`.  A test passes if it returns `(ok ...)`, and fails if it returns `(err ...)`.
+Each test runs in its own block, and its effects are rolled back after it runs.  Writing
+`;; @mine-blocks <n>` in the comment above a test mines `n` empty blocks before it runs.
+With `--junit=<path>`, `test` also writes its results to `<path>` as a JUnit XML report.
 ",
         invoked_by
     );
@@ -141,6 +166,7 @@ fn run_analysis(
         analysis_db,
         save_contract,
         LimitedCostTracker::new_free(),
+        ClarityVersion::latest(),
     )
     .map_err(|(e, _)| e)
 }
@@ -249,15 +275,7 @@ where
     F: FnOnce(WritableMarfStore) -> (WritableMarfStore, R),
 {
     // store CLI data alongside the MARF database state
-    let mut cli_db_path_buf = PathBuf::from(db_path);
-    cli_db_path_buf.push("cli.sqlite");
-    let cli_db_path = cli_db_path_buf
-        .to_str()
-        .expect(&format!(
-            "FATAL: failed to convert '{}' to a string",
-            db_path
-        ))
-        .to_string();
+    let cli_db_path = get_cli_db_path(db_path);
 
     // need to load the last block
     let (from, to) = advance_cli_chain_tip(&cli_db_path);
@@ -274,15 +292,7 @@ where
     F: FnOnce(WritableMarfStore) -> (WritableMarfStore, R),
 {
     // store CLI data alongside the MARF database state
-    let mut cli_db_path_buf = PathBuf::from(db_path);
-    cli_db_path_buf.push("cli.sqlite");
-    let cli_db_path = cli_db_path_buf
-        .to_str()
-        .expect(&format!(
-            "FATAL: failed to convert '{}' to a string",
-            db_path
-        ))
-        .to_string();
+    let cli_db_path = get_cli_db_path(db_path);
 
     let cli_db_conn = create_or_open_db(&cli_db_path);
     let from = get_cli_chain_tip(&cli_db_conn);
@@ -321,21 +331,36 @@ impl CLIHeadersDB {
     }
 
     pub fn open(&self) -> Connection {
-        let mut cli_db_path_buf = PathBuf::from(&self.db_path);
-        cli_db_path_buf.push("cli.sqlite");
-        let cli_db_path = cli_db_path_buf
-            .to_str()
-            .expect(&format!(
-                "FATAL: failed to convert '{}' to a string",
-                &self.db_path
-            ))
-            .to_string();
-
+        let cli_db_path = get_cli_db_path(&self.db_path);
         let cli_db_conn = create_or_open_db(&cli_db_path);
         cli_db_conn
     }
 }
 
+// Mocked header data for the blocks the CLI simulates.  It's derived from the block's ID, so it's
+// unique to each block.
+fn mock_burn_header_hash(id_bhh: &StacksBlockId) -> BurnchainHeaderHash {
+    let hash_bytes = Sha512Trunc256Sum::from_data(&id_bhh.0);
+    BurnchainHeaderHash(hash_bytes.0)
+}
+
+fn mock_vrf_seed(id_bhh: &StacksBlockId) -> VRFSeed {
+    let hash_bytes = Sha512Trunc256Sum::from_data(&id_bhh.0);
+    let hash_bytes_2 = Sha512Trunc256Sum::from_data(&hash_bytes.0);
+    VRFSeed(hash_bytes_2.0)
+}
+
+fn mock_stacks_block_header_hash(id_bhh: &StacksBlockId) -> BlockHeaderHash {
+    let hash_bytes = Sha512Trunc256Sum::from_data(&id_bhh.0);
+    let hash_bytes_2 = Sha512Trunc256Sum::from_data(&hash_bytes.0);
+    let hash_bytes_3 = Sha512Trunc256Sum::from_data(&hash_bytes_2.0);
+    BlockHeaderHash(hash_bytes_3.0)
+}
+
+fn mock_burn_block_time(height: u64) -> u64 {
+    height * 600 + 1231006505
+}
+
 impl HeadersDB for CLIHeadersDB {
     fn get_burn_header_hash_for_block(
         &self,
@@ -344,8 +369,7 @@ impl HeadersDB for CLIHeadersDB {
         // mock it
         let conn = self.open();
         if let Some(_) = get_cli_block_height(&conn, id_bhh) {
-            let hash_bytes = Sha512Trunc256Sum::from_data(&id_bhh.0);
-            Some(BurnchainHeaderHash(hash_bytes.0))
+            Some(mock_burn_header_hash(id_bhh))
         } else {
             None
         }
@@ -355,9 +379,7 @@ impl HeadersDB for CLIHeadersDB {
         let conn = self.open();
         if let Some(_) = get_cli_block_height(&conn, id_bhh) {
             // mock it, but make it unique
-            let hash_bytes = Sha512Trunc256Sum::from_data(&id_bhh.0);
-            let hash_bytes_2 = Sha512Trunc256Sum::from_data(&hash_bytes.0);
-            Some(VRFSeed(hash_bytes_2.0))
+            Some(mock_vrf_seed(id_bhh))
         } else {
             None
         }
@@ -370,10 +392,7 @@ impl HeadersDB for CLIHeadersDB {
         let conn = self.open();
         if let Some(_) = get_cli_block_height(&conn, id_bhh) {
             // mock it, but make it unique
-            let hash_bytes = Sha512Trunc256Sum::from_data(&id_bhh.0);
-            let hash_bytes_2 = Sha512Trunc256Sum::from_data(&hash_bytes.0);
-            let hash_bytes_3 = Sha512Trunc256Sum::from_data(&hash_bytes_2.0);
-            Some(BlockHeaderHash(hash_bytes_3.0))
+            Some(mock_stacks_block_header_hash(id_bhh))
         } else {
             None
         }
@@ -381,7 +400,7 @@ impl HeadersDB for CLIHeadersDB {
     fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64> {
         let conn = self.open();
         if let Some(height) = get_cli_block_height(&conn, id_bhh) {
-            Some((height * 600 + 1231006505) as u64)
+            Some(mock_burn_block_time(height))
         } else {
             None
         }
@@ -397,6 +416,703 @@ impl HeadersDB for CLIHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, _id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        None
+    }
+}
+
+/// The headers of the blocks simulated by `test`, which builds forks off of the block its
+/// contracts are deployed in, so unlike `CLIHeadersDB`, it can't assume one chain of blocks.
+struct TestHeadersDB {
+    heights: HashMap<StacksBlockId, u64>,
+}
+
+impl TestHeadersDB {
+    pub fn new() -> TestHeadersDB {
+        TestHeadersDB {
+            heights: HashMap::new(),
+        }
+    }
+
+    /// Make up the ID of a new block whose parent is `parent` (or that has none), and remember
+    /// its height
+    pub fn new_block(&mut self, parent: Option<&StacksBlockId>) -> StacksBlockId {
+        let height = parent
+            .and_then(|parent| self.heights.get(parent))
+            .map(|height| height + 1)
+            .unwrap_or(1);
+        let block_id = StacksBlockId(rand::thread_rng().gen::<[u8; 32]>());
+        self.heights.insert(block_id.clone(), height);
+        block_id
+    }
+}
+
+impl HeadersDB for TestHeadersDB {
+    fn get_burn_header_hash_for_block(
+        &self,
+        id_bhh: &StacksBlockId,
+    ) -> Option<BurnchainHeaderHash> {
+        self.heights
+            .get(id_bhh)
+            .map(|_| mock_burn_header_hash(id_bhh))
+    }
+    fn get_vrf_seed_for_block(&self, id_bhh: &StacksBlockId) -> Option<VRFSeed> {
+        self.heights.get(id_bhh).map(|_| mock_vrf_seed(id_bhh))
+    }
+    fn get_stacks_block_header_hash_for_block(
+        &self,
+        id_bhh: &StacksBlockId,
+    ) -> Option<BlockHeaderHash> {
+        self.heights
+            .get(id_bhh)
+            .map(|_| mock_stacks_block_header_hash(id_bhh))
+    }
+    fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64> {
+        self.heights
+            .get(id_bhh)
+            .map(|height| mock_burn_block_time(*height))
+    }
+    fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32> {
+        self.heights.get(id_bhh).map(|height| *height as u32)
+    }
+    fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
+        None
+    }
+    fn get_consensus_hash_for_block(&self, _id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        None
+    }
+}
+
+fn get_cli_db_path(db_path: &str) -> String {
+    let mut cli_db_path_buf = PathBuf::from(db_path);
+    cli_db_path_buf.push("cli.sqlite");
+    cli_db_path_buf
+        .to_str()
+        .expect(&format!(
+            "FATAL: failed to convert '{}' to a string",
+            db_path
+        ))
+        .to_string()
+}
+
+fn create_coverage_tables(tx: &Transaction) {
+    friendly_expect(tx.execute("CREATE TABLE IF NOT EXISTS cli_contract_sources(contract_identifier TEXT PRIMARY KEY, source_path TEXT NOT NULL, source TEXT NOT NULL);", NO_PARAMS),
+                    "FATAL: failed to create 'cli_contract_sources' table");
+    friendly_expect(tx.execute("CREATE TABLE IF NOT EXISTS cli_coverage(contract_identifier TEXT NOT NULL, expr_id INTEGER NOT NULL, hits INTEGER NOT NULL, PRIMARY KEY(contract_identifier, expr_id));", NO_PARAMS),
+                    "FATAL: failed to create 'cli_coverage' table");
+}
+
+/// Remember where a launched contract's source came from, so coverage reports can refer to it
+fn save_contract_source(
+    db_path: &str,
+    contract_identifier: &QualifiedContractIdentifier,
+    source_path: &str,
+    source: &str,
+) {
+    let mut conn = create_or_open_db(&get_cli_db_path(db_path));
+    let tx = friendly_expect(
+        conn.transaction(),
+        &format!("FATAL: failed to begin transaction on '{}'", db_path),
+    );
+    create_coverage_tables(&tx);
+
+    let args: &[&dyn ToSql] = &[&contract_identifier.to_string(), &source_path, &source];
+    friendly_expect(
+        tx.execute(
+            "INSERT OR REPLACE INTO cli_contract_sources (contract_identifier, source_path, source) VALUES (?1, ?2, ?3)",
+            args,
+        ),
+        "FATAL: failed to store contract source",
+    );
+    friendly_expect(
+        tx.commit(),
+        &format!("FATAL: failed to commit contract source to '{}'", db_path),
+    );
+}
+
+/// Get the identifier, source path, and source code of every launched contract
+fn get_contract_sources(db_path: &str) -> Vec<(QualifiedContractIdentifier, String, String)> {
+    let mut conn = create_or_open_db(&get_cli_db_path(db_path));
+    let tx = friendly_expect(
+        conn.transaction(),
+        &format!("FATAL: failed to begin transaction on '{}'", db_path),
+    );
+    create_coverage_tables(&tx);
+
+    let mut sources = vec![];
+    let mut stmt = friendly_expect(
+        tx.prepare("SELECT contract_identifier, source_path, source FROM cli_contract_sources ORDER BY contract_identifier"),
+        "FATAL: could not prepare query",
+    );
+    let mut rows = friendly_expect(stmt.query(NO_PARAMS), "FATAL: could not fetch rows");
+    while let Some(row) = rows.next().expect("FATAL: could not read contract source") {
+        let contract_identifier = friendly_expect(
+            QualifiedContractIdentifier::from_column(&row, "contract_identifier"),
+            "FATAL: could not parse contract identifier",
+        );
+        let source_path: String = row.get_unwrap("source_path");
+        let source: String = row.get_unwrap("source");
+        sources.push((contract_identifier, source_path, source));
+    }
+    sources
+}
+
+/// Add the expression hits recorded by a coverage reporter to the ones recorded so far
+fn save_coverage(db_path: &str, reporter: &CoverageReporter) {
+    let mut conn = create_or_open_db(&get_cli_db_path(db_path));
+    let tx = friendly_expect(
+        conn.transaction(),
+        &format!("FATAL: failed to begin transaction on '{}'", db_path),
+    );
+    create_coverage_tables(&tx);
+
+    for (contract_identifier, contract_hits) in reporter.get_hits().iter() {
+        for (expr_id, hits) in contract_hits.iter() {
+            let args: &[&dyn ToSql] = &[
+                &contract_identifier.to_string(),
+                &(*expr_id as i64),
+                &(*hits as i64),
+            ];
+            friendly_expect(
+                tx.execute(
+                    "INSERT INTO cli_coverage (contract_identifier, expr_id, hits) VALUES (?1, ?2, ?3) \
+                     ON CONFLICT(contract_identifier, expr_id) DO UPDATE SET hits = hits + excluded.hits",
+                    args,
+                ),
+                "FATAL: failed to store coverage",
+            );
+        }
+    }
+    friendly_expect(
+        tx.commit(),
+        &format!("FATAL: failed to commit coverage to '{}'", db_path),
+    );
+}
+
+/// Make an LCOV report of the recorded coverage of every launched contract
+fn make_coverage_report(db_path: &str) -> String {
+    let mut conn = create_or_open_db(&get_cli_db_path(db_path));
+    let tx = friendly_expect(
+        conn.transaction(),
+        &format!("FATAL: failed to begin transaction on '{}'", db_path),
+    );
+    create_coverage_tables(&tx);
+
+    let mut reporter = CoverageReporter::new();
+    {
+        let mut stmt = friendly_expect(
+            tx.prepare("SELECT contract_identifier, expr_id, hits FROM cli_coverage"),
+            "FATAL: could not prepare query",
+        );
+        let mut rows = friendly_expect(stmt.query(NO_PARAMS), "FATAL: could not fetch rows");
+        while let Some(row) = rows.next().expect("FATAL: could not read coverage") {
+            let contract_identifier = friendly_expect(
+                QualifiedContractIdentifier::from_column(&row, "contract_identifier"),
+                "FATAL: could not parse contract identifier",
+            );
+            let expr_id = friendly_expect(
+                u64::from_column(&row, "expr_id"),
+                "FATAL: could not parse expression ID",
+            );
+            let hits = friendly_expect(
+                u64::from_column(&row, "hits"),
+                "FATAL: could not parse hit count",
+            );
+            reporter.add_hits(&contract_identifier, expr_id, hits);
+        }
+    }
+
+    let mut report = vec![];
+    for (contract_identifier, source_path, source) in get_contract_sources(db_path).iter() {
+        let ast = friendly_expect(
+            parse(&contract_identifier, &source),
+            &format!("Failed to parse contract {}", &contract_identifier),
+        );
+        friendly_expect(
+            reporter.write_lcov(&mut report, contract_identifier, source_path, &ast),
+            "Failed to write coverage report",
+        );
+    }
+
+    friendly_expect(
+        String::from_utf8(report),
+        "FATAL: coverage report is not valid UTF-8",
+    )
+}
+
+/// Make a cost tracker that charges the default costs.  The VM state database doesn't have the
+/// boot cost contracts, so the tracker loads them from a scratch database.
+fn make_cost_tracker() -> LimitedCostTracker {
+    let mut cost_store = MemoryBackingStore::new();
+    {
+        let mut vm_env = OwnedEnvironment::new_free(false, cost_store.as_clarity_db());
+        friendly_expect(
+            vm_env.initialize_contract(boot_code_id("costs", false), BOOT_CODE_COSTS),
+            "FATAL: failed to initialize the cost contract",
+        );
+        friendly_expect(
+            vm_env.initialize_contract(
+                boot_code_id("cost-voting", false),
+                &BOOT_CODE_COST_VOTING_TESTNET,
+            ),
+            "FATAL: failed to initialize the cost voting contract",
+        );
+    }
+
+    friendly_expect(
+        LimitedCostTracker::new(
+            false,
+            ExecutionCost::max_value(),
+            &mut cost_store.as_clarity_db(),
+        )
+        .map_err(|e| format!("{:?}", e)),
+        "FATAL: failed to load the cost functions",
+    )
+}
+
+/// Make a cost tracker that charges the default costs, and attributes them to the functions that
+/// incur them
+fn make_profiling_cost_tracker() -> LimitedCostTracker {
+    let mut cost_tracker = make_cost_tracker();
+    cost_tracker.enable_profiling();
+    cost_tracker
+}
+
+/// Print a cost profile's table of function costs, and write its folded stacks to
+/// `folded_path` if given
+fn report_cost_profile(cost_profile: &CostProfile, folded_path: &Option<String>) {
+    let mut table = vec![];
+    friendly_expect(
+        cost_profile.write_table(&mut table),
+        "Failed to write cost profile",
+    );
+    print!("{}", String::from_utf8_lossy(&table));
+
+    if let Some(folded_path) = folded_path {
+        let mut folded = vec![];
+        friendly_expect(
+            cost_profile.write_folded(&mut folded, ProfileMetric::Runtime),
+            "Failed to write cost profile",
+        );
+        friendly_expect(
+            fs::write(folded_path, &folded),
+            &format!("Failed to write {}", folded_path),
+        );
+    }
+}
+
+/// The address that deploys the contracts run by `test`, and calls their test functions
+const TEST_DEPLOYER: &str = "S1G2081040G2081040G2081040G208105NK8PE5";
+
+/// Contracts whose files are named `*_test.clar` are test contracts
+const TEST_CONTRACT_SUFFIX: &str = "_test";
+
+/// A test contract's public functions whose names start with `test-` are its test functions
+const TEST_FUNCTION_PREFIX: &str = "test-";
+
+#[derive(Debug, Clone, PartialEq)]
+struct TestFunction {
+    name: String,
+    num_args: usize,
+    /// How many empty blocks to mine before running the test
+    mine_blocks: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TestOutcome {
+    /// The test function returned `(ok ...)` with this value
+    Passed(Value),
+    /// The test function returned `(err ...)` with this value
+    Failed(Value),
+    /// The test function couldn't be called, or aborted with an error
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TestResult {
+    contract_identifier: QualifiedContractIdentifier,
+    name: String,
+    outcome: TestOutcome,
+    cost: ExecutionCost,
+}
+
+/// Find the `;; @mine-blocks <n>` annotations in a test contract's source, which ask for `n`
+/// empty blocks to be mined before running the test function defined right after them.
+fn find_mine_blocks_annotations(source: &str) -> Result<HashMap<String, u64>, String> {
+    let mut annotations = HashMap::new();
+    let mut mine_blocks = None;
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with(";;") {
+            let mut words = line.trim_start_matches(';').split_whitespace();
+            if words.next() == Some("@mine-blocks") {
+                match words.next().map(|count| count.parse::<u64>()) {
+                    Some(Ok(count)) => mine_blocks = Some(count),
+                    _ => return Err(format!("Line {}: expected `@mine-blocks <count>`", i + 1)),
+                }
+            }
+        } else if line.starts_with("(define-public") {
+            let name = line["(define-public".len()..]
+                .trim_start()
+                .trim_start_matches('(')
+                .split(|c: char| c.is_whitespace() || c == ')')
+                .next();
+            if let (Some(name), Some(count)) = (name, mine_blocks.take()) {
+                annotations.insert(name.to_string(), count);
+            }
+        } else {
+            mine_blocks = None;
+        }
+    }
+    Ok(annotations)
+}
+
+/// Find a test contract's test functions, in the order they're defined
+fn find_test_functions(
+    contract_identifier: &QualifiedContractIdentifier,
+    source: &str,
+) -> Result<Vec<TestFunction>, String> {
+    let annotations = find_mine_blocks_annotations(source)?;
+    let expressions = parse(contract_identifier, source).map_err(|e| e.to_string())?;
+
+    let mut test_functions = vec![];
+    for expr in expressions.iter() {
+        if let Ok(Some(DefineFunctionsParsed::PublicFunction { signature, .. })) =
+            DefineFunctionsParsed::try_parse(expr)
+        {
+            if let Some(name) = signature.get(0).and_then(|name| name.match_atom()) {
+                if name.starts_with(TEST_FUNCTION_PREFIX) {
+                    test_functions.push(TestFunction {
+                        name: name.to_string(),
+                        num_args: signature.len() - 1,
+                        mine_blocks: annotations.get(name.as_str()).cloned().unwrap_or(0),
+                    });
+                }
+            }
+        }
+    }
+    Ok(test_functions)
+}
+
+fn deploy_contract(
+    marf: &mut WritableMarfStore,
+    header_db: &dyn HeadersDB,
+    contract_identifier: &QualifiedContractIdentifier,
+    source: &str,
+) -> Result<(), String> {
+    let mut ast = parse(contract_identifier, source).map_err(|e| e.to_string())?;
+    {
+        let mut db = AnalysisDatabase::new(marf);
+        run_analysis(contract_identifier, &mut ast, &mut db, true).map_err(|e| e.to_string())?;
+    }
+
+    let db = marf.as_clarity_db(header_db, &NULL_BURN_STATE_DB);
+    let mut vm_env = OwnedEnvironment::new_cost_limited(false, db, LimitedCostTracker::new_free());
+    vm_env
+        .initialize_contract(contract_identifier.clone(), source)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn run_test(
+    marf: &mut WritableMarfStore,
+    header_db: &dyn HeadersDB,
+    contract_identifier: &QualifiedContractIdentifier,
+    test_function: &TestFunction,
+    cost_tracker: LimitedCostTracker,
+) -> (TestOutcome, ExecutionCost) {
+    if test_function.num_args > 0 {
+        return (
+            TestOutcome::Error("Test functions cannot take arguments".to_string()),
+            ExecutionCost::zero(),
+        );
+    }
+
+    let db = marf.as_clarity_db(header_db, &NULL_BURN_STATE_DB);
+    let mut vm_env = OwnedEnvironment::new_cost_limited(false, db, cost_tracker);
+    let sender = Value::Principal(PrincipalData::Standard(contract_identifier.issuer.clone()));
+    let result = vm_env.execute_transaction(
+        sender,
+        contract_identifier.clone(),
+        &test_function.name,
+        &[],
+    );
+    let cost = vm_env
+        .destruct()
+        .map(|(_, cost_tracker)| cost_tracker.get_total())
+        .unwrap_or_else(ExecutionCost::zero);
+
+    let outcome = match result {
+        Ok((Value::Response(data), _, _)) => {
+            if data.committed {
+                TestOutcome::Passed(*data.data)
+            } else {
+                TestOutcome::Failed(*data.data)
+            }
+        }
+        Ok((value, _, _)) => TestOutcome::Error(format!(
+            "Expected a response from the test function. Found: {}",
+            value
+        )),
+        Err(error) => TestOutcome::Error(error.to_string()),
+    };
+    (outcome, cost)
+}
+
+/// Deploy the contracts in `contract_paths` to a fresh VM state database, in the order given,
+/// and run the test functions of the test contracts among them.  Each test function runs in its
+/// own block, built on the block the contracts were deployed in (and the empty blocks it asked
+/// for), and that block is rolled back once it returns, so tests can't affect each other.
+fn run_tests(contract_paths: &[String]) -> Vec<TestResult> {
+    let deployer = friendly_expect(
+        PrincipalData::parse_standard_principal(TEST_DEPLOYER),
+        "Failed to parse the test deployer address",
+    );
+    let contracts: Vec<_> = contract_paths
+        .iter()
+        .map(|path| {
+            let name = friendly_expect_opt(
+                Path::new(path).file_stem().and_then(|name| name.to_str()),
+                &format!("Failed to get a contract name from {}", path),
+            );
+            let contract_identifier = QualifiedContractIdentifier::new(
+                deployer.clone(),
+                friendly_expect(
+                    name.to_string().try_into(),
+                    &format!("Invalid contract name '{}'", name),
+                ),
+            );
+            let source = friendly_expect(
+                fs::read_to_string(path),
+                &format!("Error reading file: {}", path),
+            );
+            (contract_identifier, path, source)
+        })
+        .collect();
+
+    let db_path = env::temp_dir().join(format!(
+        "clarity-cli-test-{}",
+        rand::thread_rng().gen::<u64>()
+    ));
+    let db_path = friendly_expect_opt(
+        db_path.to_str().map(|path| path.to_string()),
+        "FATAL: failed to make a temporary VM state database path",
+    );
+    let mut marf_kv = friendly_expect(
+        MarfedKV::open(&db_path, None),
+        "Failed to open VM database.",
+    );
+    let mut header_db = TestHeadersDB::new();
+
+    let deploy_block = header_db.new_block(None);
+    let mut marf = marf_kv.begin(&StacksBlockId::sentinel(), &deploy_block);
+    marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB)
+        .initialize();
+    for (contract_identifier, path, source) in contracts.iter() {
+        friendly_expect(
+            deploy_contract(&mut marf, &header_db, contract_identifier, source),
+            &format!("Failed to deploy {}", path),
+        );
+    }
+    marf.commit_to(&deploy_block);
+
+    let cost_tracker = make_cost_tracker();
+    let mut results = vec![];
+    for (contract_identifier, path, source) in contracts.iter() {
+        if !contract_identifier
+            .name
+            .as_str()
+            .ends_with(TEST_CONTRACT_SUFFIX)
+        {
+            continue;
+        }
+        let test_functions = friendly_expect(
+            find_test_functions(contract_identifier, source),
+            &format!("Failed to find the tests in {}", path),
+        );
+        for test_function in test_functions.iter() {
+            let mut parent = deploy_block.clone();
+            for _ in 0..test_function.mine_blocks {
+                let block = header_db.new_block(Some(&parent));
+                marf_kv.begin(&parent, &block).commit_to(&block);
+                parent = block;
+            }
+
+            let block = header_db.new_block(Some(&parent));
+            let mut marf = marf_kv.begin(&parent, &block);
+            let (outcome, cost) = run_test(
+                &mut marf,
+                &header_db,
+                contract_identifier,
+                test_function,
+                cost_tracker.clone(),
+            );
+            marf.rollback_block();
+
+            results.push(TestResult {
+                contract_identifier: contract_identifier.clone(),
+                name: test_function.name.clone(),
+                outcome,
+                cost,
+            });
+        }
+    }
+
+    if let Err(e) = fs::remove_dir_all(&db_path) {
+        eprintln!("Failed to remove {}: {}", &db_path, e);
+    }
+    results
+}
+
+/// Count the tests that passed, failed, and errored
+fn count_test_outcomes(results: &[TestResult]) -> (usize, usize, usize) {
+    results
+        .iter()
+        .fold((0, 0, 0), |(passed, failed, errors), result| {
+            match result.outcome {
+                TestOutcome::Passed(_) => (passed + 1, failed, errors),
+                TestOutcome::Failed(_) => (passed, failed + 1, errors),
+                TestOutcome::Error(_) => (passed, failed, errors + 1),
+            }
+        })
+}
+
+fn write_test_report<W: Write>(fd: &mut W, results: &[TestResult]) -> io::Result<()> {
+    let mut contract_identifier = None;
+    for result in results.iter() {
+        if contract_identifier != Some(&result.contract_identifier) {
+            writeln!(fd, "{}", &result.contract_identifier)?;
+            contract_identifier = Some(&result.contract_identifier);
+        }
+        match result.outcome {
+            TestOutcome::Passed(ref value) => writeln!(
+                fd,
+                "  {} ... ok, returned {}, cost {}",
+                &result.name, value, &result.cost
+            )?,
+            TestOutcome::Failed(ref value) => writeln!(
+                fd,
+                "  {} ... FAILED, returned {}, cost {}",
+                &result.name, value, &result.cost
+            )?,
+            TestOutcome::Error(ref message) => {
+                writeln!(fd, "  {} ... ERROR: {}", &result.name, message)?
+            }
+        }
+    }
+
+    let (passed, failed, errors) = count_test_outcomes(results);
+    writeln!(
+        fd,
+        "\ntest result: {}. {} passed; {} failed; {} errors",
+        if failed + errors == 0 { "ok" } else { "FAILED" },
+        passed,
+        failed,
+        errors
+    )
+}
+
+fn xml_escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Write the results of `test` as a JUnit XML report, with one test suite per test contract
+fn write_junit_report<W: Write>(fd: &mut W, results: &[TestResult]) -> io::Result<()> {
+    let (_, failed, errors) = count_test_outcomes(results);
+    writeln!(fd, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        fd,
+        "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\">",
+        results.len(),
+        failed,
+        errors
+    )?;
+
+    let mut suites: Vec<(&QualifiedContractIdentifier, Vec<TestResult>)> = vec![];
+    for result in results.iter() {
+        match suites.last_mut() {
+            Some((contract_identifier, suite))
+                if *contract_identifier == &result.contract_identifier =>
+            {
+                suite.push(result.clone())
+            }
+            _ => suites.push((&result.contract_identifier, vec![result.clone()])),
+        }
+    }
+
+    for (contract_identifier, suite) in suites.iter() {
+        let contract_name = xml_escape(&contract_identifier.to_string());
+        let (_, failed, errors) = count_test_outcomes(suite);
+        writeln!(
+            fd,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">",
+            &contract_name,
+            suite.len(),
+            failed,
+            errors
+        )?;
+        for result in suite.iter() {
+            writeln!(
+                fd,
+                "    <testcase classname=\"{}\" name=\"{}\">",
+                &contract_name,
+                xml_escape(&result.name)
+            )?;
+            writeln!(
+                fd,
+                "      <properties><property name=\"cost\" value=\"{}\"/></properties>",
+                xml_escape(&result.cost.to_string())
+            )?;
+            match result.outcome {
+                TestOutcome::Passed(ref value) => writeln!(
+                    fd,
+                    "      <system-out>returned {}</system-out>",
+                    xml_escape(&value.to_string())
+                )?,
+                TestOutcome::Failed(ref value) => writeln!(
+                    fd,
+                    "      <failure message=\"returned {}\"/>",
+                    xml_escape(&value.to_string())
+                )?,
+                TestOutcome::Error(ref message) => {
+                    writeln!(fd, "      <error message=\"{}\"/>", xml_escape(message))?
+                }
+            }
+            writeln!(fd, "    </testcase>")?;
+        }
+        writeln!(fd, "  </testsuite>")?;
+    }
+    writeln!(fd, "</testsuites>")
+}
+
+fn get_sender(sender_in: &str) -> PrincipalData {
+    if let Ok(sender) = PrincipalData::parse_standard_principal(sender_in) {
+        PrincipalData::Standard(sender)
+    } else {
+        eprintln!("Unexpected result parsing sender: {}", sender_in);
+        panic_test!();
+    }
+}
+
+fn get_call_arguments(args: &[String]) -> Vec<SymbolicExpression> {
+    args.iter()
+        .map(|argument| {
+            let argument_parsed = friendly_expect(
+                vm_execute(argument),
+                &format!("Error parsing argument \"{}\"", argument),
+            );
+            let argument_value = friendly_expect_opt(
+                argument_parsed,
+                &format!("Failed to parse a value from the argument: {}", argument),
+            );
+            SymbolicExpression::atom_value(argument_value)
+        })
+        .collect()
 }
 
 fn get_eval_input(invoked_by: &str, args: &[String]) -> EvalInput {
@@ -450,6 +1166,32 @@ struct InitialAllocation {
 }
 
 pub fn invoke_command(invoked_by: &str, args: &[String]) {
+    let coverage = args.iter().any(|arg| arg == "--coverage");
+    let profile = args
+        .iter()
+        .any(|arg| arg == "--profile" || arg.starts_with("--profile="));
+    let profile_path = args
+        .iter()
+        .filter(|arg| arg.starts_with("--profile="))
+        .map(|arg| arg["--profile=".len()..].to_string())
+        .last();
+    let junit_path = args
+        .iter()
+        .filter(|arg| arg.starts_with("--junit="))
+        .map(|arg| arg["--junit=".len()..].to_string())
+        .last();
+    let args: Vec<String> = args
+        .iter()
+        .filter(|arg| {
+            *arg != "--coverage"
+                && *arg != "--profile"
+                && !arg.starts_with("--profile=")
+                && !arg.starts_with("--junit=")
+        })
+        .cloned()
+        .collect();
+    let args = &args[..];
+
     if args.len() < 1 {
         print_usage(invoked_by)
     }
@@ -709,7 +1451,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 "Failed to open VM database.",
             );
             let header_db = CLIHeadersDB::new(&vm_filename);
-            let result = in_block(vm_filename, marf_kv, |mut marf| {
+            let (result, coverage_reporter) = in_block(vm_filename, marf_kv, |mut marf| {
                 let result = {
                     let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
                     let mut vm_env = OwnedEnvironment::new_cost_limited(
@@ -717,13 +1459,21 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                         db,
                         LimitedCostTracker::new_free(),
                     );
-                    vm_env
+                    if coverage {
+                        vm_env.set_coverage_reporter(CoverageReporter::new());
+                    }
+                    let result = vm_env
                         .get_exec_environment(None)
-                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content)
+                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content);
+                    (result, vm_env.take_coverage_reporter())
                 };
                 (marf, result)
             });
 
+            if let Some(reporter) = coverage_reporter {
+                save_coverage(vm_filename, &reporter);
+            }
+
             match result {
                 Ok(x) => {
                     println!("Program executed successfully! Output: \n{}", x);
@@ -742,7 +1492,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 "Failed to open VM database.",
             );
             let header_db = CLIHeadersDB::new(&vm_filename);
-            let result = at_chaintip(vm_filename, marf_kv, |mut marf| {
+            let (result, coverage_reporter) = at_chaintip(vm_filename, marf_kv, |mut marf| {
                 let result = {
                     let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
                     let mut vm_env = OwnedEnvironment::new_cost_limited(
@@ -750,13 +1500,21 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                         db,
                         LimitedCostTracker::new_free(),
                     );
-                    vm_env
+                    if coverage {
+                        vm_env.set_coverage_reporter(CoverageReporter::new());
+                    }
+                    let result = vm_env
                         .get_exec_environment(None)
-                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content)
+                        .eval_read_only(&evalInput.contract_identifier, &evalInput.content);
+                    (result, vm_env.take_coverage_reporter())
                 };
                 (marf, result)
             });
 
+            if let Some(reporter) = coverage_reporter {
+                save_coverage(vm_filename, &reporter);
+            }
+
             match result {
                 Ok(x) => {
                     println!("Program executed successfully! Output: \n{}", x);
@@ -795,7 +1553,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 "Failed to open VM database.",
             );
             let header_db = CLIHeadersDB::new(&vm_filename);
-            let result = at_block(chain_tip, marf_kv, |mut marf| {
+            let (result, coverage_reporter) = at_block(chain_tip, marf_kv, |mut marf| {
                 let result = {
                     let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
                     let mut vm_env = OwnedEnvironment::new_cost_limited(
@@ -803,13 +1561,21 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                         db,
                         LimitedCostTracker::new_free(),
                     );
-                    vm_env
+                    if coverage {
+                        vm_env.set_coverage_reporter(CoverageReporter::new());
+                    }
+                    let result = vm_env
                         .get_exec_environment(None)
-                        .eval_read_only(&contract_identifier, &content)
+                        .eval_read_only(&contract_identifier, &content);
+                    (result, vm_env.take_coverage_reporter())
                 };
                 (marf, result)
             });
 
+            if let Some(reporter) = coverage_reporter {
+                save_coverage(vm_filename, &reporter);
+            }
+
             match result {
                 Ok(x) => {
                     println!("Program executed successfully! Output: \n{}", x);
@@ -866,13 +1632,32 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                                 db,
                                 LimitedCostTracker::new_free(),
                             );
-                            vm_env.initialize_contract(contract_identifier, &contract_content)
+                            if coverage {
+                                vm_env.set_coverage_reporter(CoverageReporter::new());
+                            }
+                            let result = vm_env.initialize_contract(
+                                contract_identifier.clone(),
+                                &contract_content,
+                            );
+                            if let Some(reporter) = vm_env.take_coverage_reporter() {
+                                save_coverage(vm_filename, &reporter);
+                            }
+                            result
                         };
                         (marf, Ok((analysis, result)))
                     }
                 }
             });
 
+            if let Ok((_, Ok(_))) = result {
+                save_contract_source(
+                    vm_filename,
+                    &contract_identifier,
+                    &args[2],
+                    &contract_content,
+                );
+            }
+
             match result {
                 Ok((contract_analysis, Ok(_x))) => match args.last() {
                     Some(s) if s == "--output_analysis" => {
@@ -913,33 +1698,100 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
             );
 
             let tx_name = &args[3];
-            let sender_in = &args[4];
+            let sender = get_sender(&args[4]);
+            let arguments = get_call_arguments(&args[5..]);
 
-            let sender = {
-                if let Ok(sender) = PrincipalData::parse_standard_principal(sender_in) {
-                    PrincipalData::Standard(sender)
-                } else {
-                    eprintln!("Unexpected result parsing sender: {}", sender_in);
+            let cost_tracker = if profile {
+                make_profiling_cost_tracker()
+            } else {
+                LimitedCostTracker::new_free()
+            };
+
+            let (result, coverage_reporter, cost_profile) =
+                in_block(vm_filename, marf_kv, |mut marf| {
+                    let result = {
+                        let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
+                        let mut vm_env =
+                            OwnedEnvironment::new_cost_limited(false, db, cost_tracker);
+                        if coverage {
+                            vm_env.set_coverage_reporter(CoverageReporter::new());
+                        }
+                        let result = vm_env.execute_transaction(
+                            Value::Principal(sender),
+                            contract_identifier,
+                            &tx_name,
+                            &arguments,
+                        );
+                        (
+                            result,
+                            vm_env.take_coverage_reporter(),
+                            vm_env.take_cost_profile(),
+                        )
+                    };
+                    (marf, result)
+                });
+
+            if let Some(reporter) = coverage_reporter {
+                save_coverage(vm_filename, &reporter);
+            }
+            if let Some(cost_profile) = cost_profile {
+                report_cost_profile(&cost_profile, &profile_path);
+            }
+
+            match result {
+                Ok((x, _, events)) => {
+                    if let Value::Response(data) = x {
+                        if data.committed {
+                            println!(
+                                "Transaction executed and committed. Returned: {}\n{:?}",
+                                data.data, events
+                            );
+                        } else {
+                            println!("Aborted: {}", data.data);
+                        }
+                    } else {
+                        panic!(format!(
+                            "Expected a ResponseType result from transaction. Found: {}",
+                            x
+                        ));
+                    }
+                }
+                Err(error) => {
+                    eprintln!("Transaction execution error: \n{}", error);
                     panic_test!();
                 }
-            };
+            }
+        }
+        "debug" => {
+            if args.len() < 5 {
+                eprintln!("Usage: {} {} [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, args[0]);
+                panic_test!();
+            }
+            let vm_filename = &args[1];
+            let marf_kv = friendly_expect(
+                MarfedKV::open(vm_filename, None),
+                "Failed to open VM database.",
+            );
+            let header_db = CLIHeadersDB::new(&vm_filename);
 
-            let arguments: Vec<_> = args[5..]
-                .iter()
-                .map(|argument| {
-                    let argument_parsed = friendly_expect(
-                        vm_execute(argument),
-                        &format!("Error parsing argument \"{}\"", argument),
-                    );
-                    let argument_value = friendly_expect_opt(
-                        argument_parsed,
-                        &format!("Failed to parse a value from the argument: {}", argument),
-                    );
-                    SymbolicExpression::atom_value(argument_value)
-                })
-                .collect();
+            let contract_identifier = friendly_expect(
+                QualifiedContractIdentifier::parse(&args[2]),
+                "Failed to parse contract identifier.",
+            );
 
-            let result = in_block(vm_filename, marf_kv, |mut marf| {
+            let tx_name = &args[3];
+            let sender = get_sender(&args[4]);
+            let arguments = get_call_arguments(&args[5..]);
+
+            let mut debugger = Debugger::new(
+                Box::new(io::BufReader::new(io::stdin())),
+                Box::new(io::stdout()),
+            );
+            for (contract_identifier, _, source) in get_contract_sources(vm_filename).iter() {
+                debugger.add_source(contract_identifier, source);
+            }
+
+            let result = at_chaintip(vm_filename, marf_kv, |mut marf| {
                 let result = {
                     let db = marf.as_clarity_db(&header_db, &NULL_BURN_STATE_DB);
                     let mut vm_env = OwnedEnvironment::new_cost_limited(
@@ -947,6 +1799,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                         db,
                         LimitedCostTracker::new_free(),
                     );
+                    vm_env.set_debugger(debugger);
                     vm_env.execute_transaction(
                         Value::Principal(sender),
                         contract_identifier,
@@ -959,21 +1812,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
 
             match result {
                 Ok((x, _, events)) => {
-                    if let Value::Response(data) = x {
-                        if data.committed {
-                            println!(
-                                "Transaction executed and committed. Returned: {}\n{:?}",
-                                data.data, events
-                            );
-                        } else {
-                            println!("Aborted: {}", data.data);
-                        }
-                    } else {
-                        panic!(format!(
-                            "Expected a ResponseType result from transaction. Found: {}",
-                            x
-                        ));
-                    }
+                    println!("Transaction returned: {}\n{:?}", x, events);
                 }
                 Err(error) => {
                     eprintln!("Transaction execution error: \n{}", error);
@@ -981,6 +1820,58 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) {
                 }
             }
         }
+        "coverage_report" => {
+            if args.len() != 2 {
+                eprintln!("Usage: {} {} [vm-state.db]", invoked_by, args[0]);
+                panic_test!();
+            }
+            print!("{}", make_coverage_report(&args[1]));
+        }
+        "test" => {
+            if args.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [contract.clar...] [contract_test.clar...] (--junit=<path>)",
+                    invoked_by, args[0]
+                );
+                panic_test!();
+            }
+            if !args[1..].iter().any(|path| {
+                Path::new(path)
+                    .file_stem()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.ends_with(TEST_CONTRACT_SUFFIX))
+                    .unwrap_or(false)
+            }) {
+                eprintln!("No test contracts given: test contract files are named *_test.clar");
+                panic_test!();
+            }
+
+            let results = run_tests(&args[1..]);
+
+            let mut report = vec![];
+            friendly_expect(
+                write_test_report(&mut report, &results),
+                "Failed to write test report",
+            );
+            print!("{}", String::from_utf8_lossy(&report));
+
+            if let Some(junit_path) = junit_path {
+                let mut junit_report = vec![];
+                friendly_expect(
+                    write_junit_report(&mut junit_report, &results),
+                    "Failed to write JUnit report",
+                );
+                friendly_expect(
+                    fs::write(&junit_path, &junit_report),
+                    &format!("Failed to write {}", junit_path),
+                );
+            }
+
+            let (_, failed, errors) = count_test_outcomes(&results);
+            if failed + errors > 0 {
+                panic_test!();
+            }
+        }
         _ => print_usage(invoked_by),
     }
 }
@@ -1119,4 +2010,173 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn test_coverage_report() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+        invoke_command(
+            "test",
+            &[
+                "launch".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                db_name.clone(),
+                "--coverage".to_string(),
+            ],
+        );
+
+        for _ in 0..2 {
+            invoke_command(
+                "test",
+                &[
+                    "execute".to_string(),
+                    "--coverage".to_string(),
+                    db_name.clone(),
+                    "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                    "mint!".to_string(),
+                    "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                    "u1000".to_string(),
+                ],
+            );
+        }
+
+        // runs without --coverage aren't recorded
+        invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u1000".to_string(),
+            ],
+        );
+
+        let report = make_coverage_report(&db_name);
+        assert!(report.starts_with("TN:\nSF:sample-contracts/tokens.clar\n"));
+        assert!(report.contains("FNDA:2,mint!\n"));
+        assert!(report.contains("FNDA:0,token-transfer\n"));
+        assert!(report.ends_with("end_of_record\n"));
+    }
+
+    #[test]
+    fn test_profile() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        let folded_name = format!("/tmp/profile_{}.folded", rand::thread_rng().gen::<i32>());
+
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+        invoke_command(
+            "test",
+            &[
+                "launch".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                db_name.clone(),
+            ],
+        );
+        invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u1000".to_string(),
+                format!("--profile={}", &folded_name),
+            ],
+        );
+
+        let folded = fs::read_to_string(&folded_name).unwrap();
+        assert!(folded.lines().any(|line| line.starts_with(
+            "S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;let;\
+             S1G2081040G2081040G2081040G208105NK8PE5.tokens:token-credit!;if;let;begin;map-set "
+        )));
+        for line in folded.lines() {
+            let runtime = line.rsplit(' ').next().unwrap();
+            assert!(runtime.parse::<u64>().unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn test_run_tests() {
+        let junit_name = format!("/tmp/junit_{}.xml", rand::thread_rng().gen::<i32>());
+        invoke_command(
+            "test",
+            &[
+                "test".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                "sample-contracts/tokens_test.clar".to_string(),
+                format!("--junit={}", &junit_name),
+            ],
+        );
+        let junit_report = fs::read_to_string(&junit_name).unwrap();
+        assert!(junit_report.contains("<testsuites tests=\"4\" failures=\"0\" errors=\"0\">"));
+
+        let test_dir = format!("/tmp/tests_{}", rand::thread_rng().gen::<i32>());
+        fs::create_dir_all(&test_dir).unwrap();
+        let test_contract_name = format!("{}/failing_test.clar", &test_dir);
+        fs::write(
+            &test_contract_name,
+            "(define-data-var counter uint u0)
+(define-public (test-fail) (err \"expected <1>\"))
+(define-public (test-panic) (ok (unwrap-panic (element-at (list u1) u1))))
+(define-public (test-args (n uint)) (ok n))
+(define-public (helper) (ok (var-set counter u1)))
+;; @mine-blocks 2
+(define-public (test-pass) (ok (var-get counter)))
+",
+        )
+        .unwrap();
+
+        let results = run_tests(&[test_contract_name]);
+        let outcomes: Vec<_> = results
+            .iter()
+            .map(|result| (result.name.as_str(), result.outcome.clone()))
+            .collect();
+        assert_eq!(outcomes.len(), 4);
+        assert_eq!(
+            outcomes[0],
+            (
+                "test-fail",
+                TestOutcome::Failed(
+                    Value::string_ascii_from_bytes(b"expected <1>".to_vec()).unwrap()
+                )
+            )
+        );
+        assert_eq!(outcomes[1].0, "test-panic");
+        match outcomes[1].1 {
+            TestOutcome::Error(_) => {}
+            _ => panic!("Expected test-panic to error"),
+        }
+        assert_eq!(
+            outcomes[2],
+            (
+                "test-args",
+                TestOutcome::Error("Test functions cannot take arguments".to_string())
+            )
+        );
+        assert_eq!(
+            outcomes[3],
+            ("test-pass", TestOutcome::Passed(Value::UInt(0)))
+        );
+        assert!(results[3].cost.runtime > 0);
+
+        let mut report = vec![];
+        write_test_report(&mut report, &results).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("  test-fail ... FAILED, returned \"expected <1>\", cost "));
+        assert!(report.ends_with("test result: FAILED. 1 passed; 1 failed; 2 errors\n"));
+
+        let mut junit_report = vec![];
+        write_junit_report(&mut junit_report, &results).unwrap();
+        let junit_report = String::from_utf8(junit_report).unwrap();
+        assert!(
+            junit_report.contains("<failure message=\"returned &quot;expected &lt;1&gt;&quot;\"/>")
+        );
+        assert!(junit_report.contains("<testsuite name=\"S1G2081040G2081040G2081040G208105NK8PE5.failing_test\" tests=\"4\" failures=\"1\" errors=\"2\">"));
+    }
 }
@@ -0,0 +1,36 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+extern crate blockstack_lib;
+
+use blockstack_lib::lsp;
+use std::io;
+use std::process;
+
+fn main() {
+    // the server always speaks over stdin and stdout, so arguments like `--stdio` are ignored
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match lsp::serve(&mut stdin.lock(), &mut stdout.lock()) {
+        Ok(true) => process::exit(0),
+        // the client exited without shutting the server down first
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("clarity-lsp: {}", e);
+            process::exit(1);
+        }
+    }
+}
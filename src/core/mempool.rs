@@ -23,6 +23,10 @@ use rusqlite::Transaction;
 use rusqlite::NO_PARAMS;
 
 use std::cmp;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+use std::mem;
 use std::ops::Deref;
 use std::ops::DerefMut;
 
@@ -34,13 +38,15 @@ use net::StacksMessageCodec;
 use chainstate::burn::BlockHeaderHash;
 use chainstate::stacks::{
     db::blocks::MemPoolRejection, db::StacksChainState, index::Error as MarfError,
-    Error as ChainstateError, StacksAddress, StacksBlockHeader, StacksTransaction,
+    Error as ChainstateError, StacksAddress, StacksBlockHeader, StacksBlockId, StacksTransaction,
 };
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
+use util::db::query_count;
 use util::db::query_row;
+use util::db::query_row_columns;
 use util::db::query_rows;
 use util::db::tx_begin_immediate;
 use util::db::tx_busy_handler;
@@ -56,12 +62,93 @@ use core::FIRST_STACKS_BLOCK_HASH;
 use rusqlite::Error as SqliteError;
 
 use chainstate::stacks::TransactionPayload;
+use chainstate::stacks::MAX_BLOCK_LEN;
+use vm::costs::ExecutionCost;
 use vm::types::PrincipalData;
 
 // maximum number of confirmations a transaction can have before it's garbage-collected
 pub const MEMPOOL_MAX_TRANSACTION_AGE: u64 = 256;
 pub const MAXIMUM_MEMPOOL_TX_CHAINING: u64 = 25;
 
+// static execution cost estimates, used to rank transactions by fee rate before they are run.
+// a contract-call is assumed to do a modest amount of I/O; a contract-publish is charged in
+// proportion to the size of its code (which gets analyzed and stored along with its analysis).
+pub const MEMPOOL_ESTIMATED_CONTRACT_CALL_COST: ExecutionCost = ExecutionCost {
+    write_length: 1_000,
+    write_count: 5,
+    read_length: 10_000,
+    read_count: 20,
+    runtime: 1_000_000,
+};
+pub const MEMPOOL_ESTIMATED_PUBLISH_RUNTIME_PER_BYTE: u64 = 1_000;
+
+/// Order in which the mempool hands candidate transactions to a block miner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemPoolWalkOrder {
+    /// Walk back from the chain tip through its ancestors, visiting transactions in the order
+    /// they were accepted (see `MemPoolDB::iterate_candidates`).
+    Arrival,
+    /// Visit transactions from the highest fee rate to the lowest, without ever visiting a
+    /// transaction before its origin's lower-nonce transactions
+    /// (see `MemPoolDB::iterate_candidates_by_fee_rate`).
+    FeeRate,
+}
+
+impl Default for MemPoolWalkOrder {
+    fn default() -> MemPoolWalkOrder {
+        MemPoolWalkOrder::Arrival
+    }
+}
+
+/// Admission policy knobs for the mempool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolSettings {
+    /// How much higher (in percent) a replacement transaction's fee must be than the combined
+    /// fees of the transactions it conflicts with on origin or sponsor nonce.  A margin of 0
+    /// means the replacement's fee need only be strictly higher.
+    pub replace_by_fee_margin: u64,
+    /// Upper bound on the total length of all pending transactions.  Once it is reached, a new
+    /// transaction is only admitted if evicting transactions with lower fee rates makes room for
+    /// it.  None means unbounded.
+    pub max_bytes: Option<u64>,
+    /// Upper bound on the number of pending transactions with the same origin address.  None
+    /// means unbounded.
+    pub max_txs_per_origin: Option<u64>,
+}
+
+impl Default for MemPoolSettings {
+    fn default() -> MemPoolSettings {
+        MemPoolSettings {
+            replace_by_fee_margin: 0,
+            max_bytes: None,
+            max_txs_per_origin: None,
+        }
+    }
+}
+
+/// Why a transaction was evicted from the mempool
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemPoolDropReason {
+    /// A transaction with the same origin or sponsor nonce and a high enough fee replaced it.
+    ReplaceByFee,
+    /// A transaction with the same origin or sponsor nonce replaced it, because this
+    /// transaction was accepted on a different fork.
+    ReplaceAcrossFork,
+    /// The mempool was full, and this transaction (or a lower-nonce transaction from the same
+    /// origin) had the lowest fee rate.
+    LowFeeRate,
+}
+
+impl fmt::Display for MemPoolDropReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemPoolDropReason::ReplaceByFee => write!(f, "ReplaceByFee"),
+            MemPoolDropReason::ReplaceAcrossFork => write!(f, "ReplaceAcrossFork"),
+            MemPoolDropReason::LowFeeRate => write!(f, "LowFeeRate"),
+        }
+    }
+}
+
 pub struct MemPoolAdmitter {
     cur_block: BlockHeaderHash,
     cur_consensus_hash: ConsensusHash,
@@ -196,15 +283,28 @@ const MEMPOOL_INITIAL_SCHEMA: &'static [&'static str] = &[
     "CREATE INDEX by_estimated_fee ON mempool(estimated_fee);",
 ];
 
+// fee rates (see `compute_fee_rate`) are stored so the cheapest transactions can be found when
+// the mempool is full.  Transactions admitted before this column existed get theirs backfilled.
+const MEMPOOL_SCHEMA_2: &'static [&'static str] = &[
+    "ALTER TABLE mempool ADD COLUMN fee_rate REAL NOT NULL DEFAULT 0;",
+    "CREATE INDEX by_fee_rate ON mempool(fee_rate);",
+];
+
 pub struct MemPoolDB {
     db: DBConn,
     path: String,
     admitter: MemPoolAdmitter,
+    settings: MemPoolSettings,
+    dropped_txs: Vec<(Txid, MemPoolDropReason)>,
 }
 
 pub struct MemPoolTx<'a> {
     tx: DBTx<'a>,
     admitter: &'a mut MemPoolAdmitter,
+    settings: &'a MemPoolSettings,
+    dropped_txs: &'a mut Vec<(Txid, MemPoolDropReason)>,
+    // transactions evicted in this DB transaction; only reported once it commits
+    pending_dropped_txs: Vec<(Txid, MemPoolDropReason)>,
 }
 
 impl<'a> Deref for MemPoolTx<'a> {
@@ -221,12 +321,31 @@ impl<'a> DerefMut for MemPoolTx<'a> {
 }
 
 impl<'a> MemPoolTx<'a> {
-    pub fn new(tx: DBTx<'a>, admitter: &'a mut MemPoolAdmitter) -> MemPoolTx<'a> {
-        MemPoolTx { tx, admitter }
+    pub fn new(
+        tx: DBTx<'a>,
+        admitter: &'a mut MemPoolAdmitter,
+        settings: &'a MemPoolSettings,
+        dropped_txs: &'a mut Vec<(Txid, MemPoolDropReason)>,
+    ) -> MemPoolTx<'a> {
+        MemPoolTx {
+            tx,
+            admitter,
+            settings,
+            dropped_txs,
+            pending_dropped_txs: vec![],
+        }
     }
 
     pub fn commit(self) -> Result<(), db_error> {
-        self.tx.commit().map_err(db_error::SqliteError)
+        let MemPoolTx {
+            tx,
+            dropped_txs,
+            pending_dropped_txs,
+            ..
+        } = self;
+        tx.commit().map_err(db_error::SqliteError)?;
+        dropped_txs.extend(pending_dropped_txs);
+        Ok(())
     }
 
     fn is_block_in_fork(
@@ -299,6 +418,207 @@ impl MemPoolTxInfo {
             metadata: metadata,
         }
     }
+
+    /// Fee rate of this transaction, using a static estimate of its execution cost.
+    pub fn fee_rate(&self, block_limit: &ExecutionCost) -> f64 {
+        compute_fee_rate(
+            self.metadata.tx_fee,
+            self.metadata.len,
+            &estimate_tx_cost(&self.tx),
+            block_limit,
+        )
+    }
+}
+
+/// Statically estimate a transaction's execution cost, without running it.
+pub fn estimate_tx_cost(tx: &StacksTransaction) -> ExecutionCost {
+    estimate_payload_cost(&tx.payload)
+}
+
+/// Statically estimate the execution cost of a transaction payload, without running it.
+pub fn estimate_payload_cost(payload: &TransactionPayload) -> ExecutionCost {
+    match *payload {
+        TransactionPayload::ContractCall(_) => MEMPOOL_ESTIMATED_CONTRACT_CALL_COST,
+        TransactionPayload::SmartContract(ref smart_contract) => {
+            let code_len = smart_contract.code_body.len() as u64;
+            ExecutionCost {
+                write_length: code_len.saturating_mul(2),
+                write_count: 2,
+                read_length: 0,
+                read_count: 1,
+                runtime: code_len.saturating_mul(MEMPOOL_ESTIMATED_PUBLISH_RUNTIME_PER_BYTE),
+            }
+        }
+        TransactionPayload::TokenTransfer(..)
+        | TransactionPayload::PoisonMicroblock(..)
+        | TransactionPayload::Coinbase(..) => ExecutionCost::zero(),
+    }
+}
+
+/// Compute a transaction's fee rate: its fee divided by the amount of block capacity it
+/// consumes (see `compute_block_capacity`).
+pub fn compute_fee_rate(
+    tx_fee: u64,
+    tx_len: u64,
+    cost: &ExecutionCost,
+    block_limit: &ExecutionCost,
+) -> f64 {
+    (tx_fee as f64) / compute_block_capacity(tx_len, cost, block_limit)
+}
+
+/// Compute the amount of block capacity a transaction consumes: its length in bytes, plus its
+/// largest execution cost dimension expressed as a fraction of `block_limit` and scaled to bytes
+/// by `MAX_BLOCK_LEN`.  Always at least 1.
+pub fn compute_block_capacity(
+    tx_len: u64,
+    cost: &ExecutionCost,
+    block_limit: &ExecutionCost,
+) -> f64 {
+    let largest_proportion = [
+        (cost.runtime, block_limit.runtime),
+        (cost.write_length, block_limit.write_length),
+        (cost.write_count, block_limit.write_count),
+        (cost.read_length, block_limit.read_length),
+        (cost.read_count, block_limit.read_count),
+    ]
+    .iter()
+    .map(|(used, limit)| (*used as f64) / (cmp::max(1, *limit) as f64))
+    .fold(0.0, f64::max);
+
+    let capacity = largest_proportion * (MAX_BLOCK_LEN as f64) + (tx_len as f64);
+    capacity.max(1.0)
+}
+
+/// How many transactions `MemPoolDB::iterate_candidates_by_fee_rate` reads from the mempool DB
+/// at a time.
+pub const MEMPOOL_FEE_RATE_PAGE_SIZE: u64 = 1024;
+
+/// A transaction as ranked by `MemPoolDB::iterate_candidates_by_fee_rate`.  Higher fee rates
+/// come first; ties go to the transaction that arrived first, and then to the lower txid.  This
+/// is the same order as `MemPoolDB::get_txs_by_fee_rate`.
+#[derive(Clone)]
+struct FeeRateCandidate {
+    fee_rate: f64,
+    accept_time: u64,
+    txid: Txid,
+}
+
+impl FromRow<FeeRateCandidate> for FeeRateCandidate {
+    fn from_row<'a>(row: &'a Row) -> Result<FeeRateCandidate, db_error> {
+        let fee_rate = f64::from_column(row, "fee_rate")?;
+        let accept_time = u64::from_column(row, "accept_time")?;
+        let txid = Txid::from_column(row, "txid")?;
+        Ok(FeeRateCandidate {
+            fee_rate,
+            accept_time,
+            txid,
+        })
+    }
+}
+
+impl Ord for FeeRateCandidate {
+    fn cmp(&self, other: &FeeRateCandidate) -> Ordering {
+        self.fee_rate
+            .partial_cmp(&other.fee_rate)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.accept_time.cmp(&self.accept_time))
+            .then_with(|| other.txid.as_bytes().cmp(self.txid.as_bytes()))
+    }
+}
+
+impl PartialOrd for FeeRateCandidate {
+    fn partial_cmp(&self, other: &FeeRateCandidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for FeeRateCandidate {
+    fn eq(&self, other: &FeeRateCandidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FeeRateCandidate {}
+
+/// State of one `MemPoolDB::iterate_candidates_by_fee_rate` pass.
+struct FeeRateWalk {
+    tip_consensus_hash: ConsensusHash,
+    tip_block_hash: BlockHeaderHash,
+    tip_height: u64,
+    /// whether or not the chain tips that mempool transactions were accepted against are in the
+    /// fork of the walk's tip
+    in_fork: HashMap<StacksBlockId, bool>,
+    /// transactions that have been passed to `todo`
+    visited: HashSet<Txid>,
+    /// transactions that have been read, but that must wait for a lower-nonce transaction of
+    /// their origin or sponsor to be visited first
+    deferred: HashMap<Txid, FeeRateCandidate>,
+    /// deferred transactions whose lower-nonce transactions have all been visited
+    ready: BinaryHeap<FeeRateCandidate>,
+}
+
+impl FeeRateWalk {
+    fn new(
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        tip_height: u64,
+    ) -> FeeRateWalk {
+        FeeRateWalk {
+            tip_consensus_hash: tip_consensus_hash.clone(),
+            tip_block_hash: tip_block_hash.clone(),
+            tip_height,
+            in_fork: HashMap::new(),
+            visited: HashSet::new(),
+            deferred: HashMap::new(),
+            ready: BinaryHeap::new(),
+        }
+    }
+
+    /// Would `MemPoolDB::iterate_candidates` visit this transaction from the walk's tip?  That
+    /// is, was it accepted against the tip or one of its ancestors?
+    fn is_in_fork(
+        &mut self,
+        chainstate: &mut StacksChainState,
+        metadata: &MemPoolTxMetadata,
+    ) -> Result<bool, ChainstateError> {
+        if metadata.consensus_hash == self.tip_consensus_hash
+            && metadata.block_header_hash == self.tip_block_hash
+        {
+            return Ok(true);
+        }
+        if metadata.block_height >= self.tip_height {
+            return Ok(false);
+        }
+
+        let index_block = StacksBlockHeader::make_index_block_hash(
+            &metadata.consensus_hash,
+            &metadata.block_header_hash,
+        );
+        if let Some(in_fork) = self.in_fork.get(&index_block) {
+            return Ok(*in_fork);
+        }
+
+        let in_fork = {
+            let headers_conn = chainstate.index_conn()?;
+            let tip_index_block = StacksBlockHeader::make_index_block_hash(
+                &self.tip_consensus_hash,
+                &self.tip_block_hash,
+            );
+            match StacksChainState::get_index_tip_ancestor_conn(
+                &headers_conn,
+                &tip_index_block,
+                metadata.block_height,
+            )? {
+                Some(ancestor) => {
+                    ancestor.consensus_hash == metadata.consensus_hash
+                        && ancestor.anchored_header.block_hash() == metadata.block_header_hash
+                }
+                None => false,
+            }
+        };
+        self.in_fork.insert(index_block, in_fork);
+        Ok(in_fork)
+    }
 }
 
 impl MemPoolDB {
@@ -307,10 +627,40 @@ impl MemPoolDB {
 
         let tx = tx_begin_immediate(conn)?;
 
-        for cmd in MEMPOOL_INITIAL_SCHEMA {
+        for cmd in MEMPOOL_INITIAL_SCHEMA.iter().chain(MEMPOOL_SCHEMA_2.iter()) {
+            tx.execute_batch(cmd).map_err(db_error::SqliteError)?;
+        }
+
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Add the fee_rate column to a mempool DB created before it existed, and compute the fee
+    /// rates of the transactions already in it.
+    fn migrate_mempool_db(conn: &mut DBConn, block_limit: &ExecutionCost) -> Result<(), db_error> {
+        let has_fee_rate: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('mempool') WHERE name = 'fee_rate'",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .map_err(db_error::SqliteError)?;
+        if has_fee_rate > 0 {
+            return Ok(());
+        }
+
+        let tx = tx_begin_immediate(conn)?;
+        for cmd in MEMPOOL_SCHEMA_2 {
             tx.execute_batch(cmd).map_err(db_error::SqliteError)?;
         }
 
+        let txs = query_rows::<MemPoolTxInfo, _>(&tx, "SELECT * FROM mempool", NO_PARAMS)?;
+        for txinfo in txs.iter() {
+            let args: &[&dyn ToSql] = &[&txinfo.fee_rate(block_limit), &txinfo.metadata.txid];
+            tx.execute("UPDATE mempool SET fee_rate = ?1 WHERE txid = ?2", args)
+                .map_err(db_error::SqliteError)?;
+        }
+
         tx.commit().map_err(db_error::SqliteError)?;
         Ok(())
     }
@@ -364,12 +714,16 @@ impl MemPoolDB {
         if create_flag {
             // instantiate!
             MemPoolDB::instantiate_mempool_db(&mut conn)?;
+        } else {
+            MemPoolDB::migrate_mempool_db(&mut conn, &chainstate.block_limit)?;
         }
 
         Ok(MemPoolDB {
             db: conn,
             path: db_path,
             admitter: admitter,
+            settings: MemPoolSettings::default(),
+            dropped_txs: vec![],
         })
     }
 
@@ -615,13 +969,183 @@ impl MemPoolDB {
         }
     }
 
+    ///
+    /// Iterate over candidates in the mempool, highest fee rate first.
+    ///  Every transaction that iterate_candidates would visit from the given
+    ///  chain tip is read from the mempool DB in pages, in descending order
+    ///  of the fee rate it was admitted with (see compute_fee_rate()).  todo
+    ///  is called once per transaction, but a transaction is never passed to
+    ///  todo before the lower-nonce transactions of its origin, or of its
+    ///  sponsor, that are in the chain tip's fork.  Such a transaction is held
+    ///  back until they have all been visited, and then goes in its fee rate
+    ///  order among the transactions that have not been visited yet.
+    ///  Transactions whose nonces can never be put in order are not visited.
+    pub fn iterate_candidates_by_fee_rate<F, E>(
+        &self,
+        tip_consensus_hash: &ConsensusHash,
+        tip_block_hash: &BlockHeaderHash,
+        tip_height: u64,
+        chainstate: &mut StacksChainState,
+        mut todo: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Vec<MemPoolTxInfo>) -> Result<(), E>,
+        E: From<db_error> + From<ChainstateError>,
+    {
+        debug!(
+            "Begin scanning transaction mempool by fee rate at {}/{} height={}",
+            tip_consensus_hash, tip_block_hash, tip_height
+        );
+
+        let mut walk = FeeRateWalk::new(tip_consensus_hash, tip_block_hash, tip_height);
+        let mut last_candidate: Option<FeeRateCandidate> = None;
+
+        loop {
+            let candidates = MemPoolDB::get_txs_by_fee_rate(
+                &self.db,
+                last_candidate.as_ref(),
+                MEMPOOL_FEE_RATE_PAGE_SIZE,
+            )?;
+            if candidates.len() == 0 {
+                break;
+            }
+
+            for candidate in candidates.into_iter() {
+                // held-back transactions that now outrank this one go first
+                while walk
+                    .ready
+                    .peek()
+                    .map(|ready| *ready > candidate)
+                    .unwrap_or(false)
+                {
+                    let ready = walk.ready.pop().expect("BUG: no ready candidate");
+                    self.visit_by_fee_rate(&mut walk, chainstate, &ready.txid, &mut todo)?;
+                }
+
+                let metadata = match MemPoolDB::get_tx_metadata(&self.db, &candidate.txid)? {
+                    Some(metadata) => metadata,
+                    None => continue,
+                };
+                last_candidate = Some(candidate.clone());
+
+                if !walk.is_in_fork(chainstate, &metadata)? {
+                    continue;
+                }
+                if !self.is_nonce_ready(&mut walk, chainstate, &metadata)? {
+                    walk.deferred.insert(candidate.txid.clone(), candidate);
+                    continue;
+                }
+                self.visit_by_fee_rate(&mut walk, chainstate, &candidate.txid, &mut todo)?;
+            }
+        }
+
+        while let Some(ready) = walk.ready.pop() {
+            self.visit_by_fee_rate(&mut walk, chainstate, &ready.txid, &mut todo)?;
+        }
+
+        debug!(
+            "Done scanning mempool by fee rate: visited {} transactions, {} could not be put in nonce order",
+            walk.visited.len(),
+            walk.deferred.len()
+        );
+        Ok(())
+    }
+
+    /// The (address, nonce) pairs that a transaction must be mined in order with: its origin's,
+    /// and its sponsor's if it has one.
+    fn get_tx_nonces(metadata: &MemPoolTxMetadata) -> Vec<(StacksAddress, u64)> {
+        let mut nonces = vec![(metadata.origin_address.clone(), metadata.origin_nonce)];
+        if metadata.sponsor_address != metadata.origin_address
+            || metadata.sponsor_nonce != metadata.origin_nonce
+        {
+            nonces.push((metadata.sponsor_address.clone(), metadata.sponsor_nonce));
+        }
+        nonces
+    }
+
+    /// Have all of the lower-nonce transactions of this transaction's origin and sponsor in the
+    /// walk's fork been visited?
+    fn is_nonce_ready(
+        &self,
+        walk: &mut FeeRateWalk,
+        chainstate: &mut StacksChainState,
+        metadata: &MemPoolTxMetadata,
+    ) -> Result<bool, ChainstateError> {
+        for (address, nonce) in MemPoolDB::get_tx_nonces(metadata).iter() {
+            for prior in MemPoolDB::get_txs_before_nonce(&self.db, address, *nonce)?.iter() {
+                if !walk.visited.contains(&prior.txid) && walk.is_in_fork(chainstate, prior)? {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Pass a transaction to todo, and then find the held-back transactions that were waiting
+    /// for it.
+    fn visit_by_fee_rate<F, E>(
+        &self,
+        walk: &mut FeeRateWalk,
+        chainstate: &mut StacksChainState,
+        txid: &Txid,
+        todo: &mut F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Vec<MemPoolTxInfo>) -> Result<(), E>,
+        E: From<db_error> + From<ChainstateError>,
+    {
+        let txinfo = match MemPoolDB::get_tx(&self.db, txid)? {
+            Some(txinfo) => txinfo,
+            None => return Ok(()),
+        };
+        let nonces = MemPoolDB::get_tx_nonces(&txinfo.metadata);
+
+        walk.visited.insert(txid.clone());
+        todo(vec![txinfo])?;
+
+        for (address, _) in nonces.iter() {
+            for waiting in MemPoolDB::get_txs_by_address(&self.db, address)?.iter() {
+                if !walk.deferred.contains_key(&waiting.txid) {
+                    continue;
+                }
+                if self.is_nonce_ready(walk, chainstate, waiting)? {
+                    let candidate = walk
+                        .deferred
+                        .remove(&waiting.txid)
+                        .expect("BUG: deferred candidate disappeared");
+                    walk.ready.push(candidate);
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn conn(&self) -> &DBConn {
         &self.db
     }
 
     pub fn tx_begin<'a>(&'a mut self) -> Result<MemPoolTx<'a>, db_error> {
         let tx = tx_begin_immediate(&mut self.db)?;
-        Ok(MemPoolTx::new(tx, &mut self.admitter))
+        Ok(MemPoolTx::new(
+            tx,
+            &mut self.admitter,
+            &self.settings,
+            &mut self.dropped_txs,
+        ))
+    }
+
+    pub fn get_settings(&self) -> &MemPoolSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: MemPoolSettings) {
+        self.settings = settings;
+    }
+
+    /// Take the list of transactions that have been evicted from the mempool (and why) since
+    /// the last call, so they can be reported to event observers.
+    pub fn take_dropped_txs(&mut self) -> Vec<(Txid, MemPoolDropReason)> {
+        mem::replace(&mut self.dropped_txs, vec![])
     }
 
     fn db_has_tx(conn: &DBConn, txid: &Txid) -> Result<bool, db_error> {
@@ -641,6 +1165,14 @@ impl MemPoolDB {
         )
     }
 
+    fn get_tx_metadata(conn: &DBConn, txid: &Txid) -> Result<Option<MemPoolTxMetadata>, db_error> {
+        query_row(
+            conn,
+            "SELECT * FROM mempool WHERE txid = ?1",
+            &[txid as &dyn ToSql],
+        )
+    }
+
     fn get_tx_estimated_fee(conn: &DBConn, txid: &Txid) -> Result<Option<u64>, db_error> {
         query_row(
             conn,
@@ -683,6 +1215,57 @@ impl MemPoolDB {
         Ok(rows)
     }
 
+    /// Get the next page of transactions in descending fee rate order, starting after the given
+    /// one (see `FeeRateCandidate`).
+    fn get_txs_by_fee_rate(
+        conn: &DBConn,
+        after: Option<&FeeRateCandidate>,
+        count: u64,
+    ) -> Result<Vec<FeeRateCandidate>, db_error> {
+        match after {
+            Some(after) => {
+                let sql = "SELECT fee_rate, accept_time, txid FROM mempool
+                           WHERE fee_rate < ?1 OR (fee_rate = ?1 AND (accept_time > ?2 OR (accept_time = ?2 AND txid > ?3)))
+                           ORDER BY fee_rate DESC, accept_time ASC, txid ASC LIMIT ?4";
+                let args: &[&dyn ToSql] = &[
+                    &after.fee_rate,
+                    &u64_to_sql(after.accept_time)?,
+                    &after.txid,
+                    &u64_to_sql(count)?,
+                ];
+                query_rows(conn, sql, args)
+            }
+            None => {
+                let sql = "SELECT fee_rate, accept_time, txid FROM mempool
+                           ORDER BY fee_rate DESC, accept_time ASC, txid ASC LIMIT ?1";
+                let args: &[&dyn ToSql] = &[&u64_to_sql(count)?];
+                query_rows(conn, sql, args)
+            }
+        }
+    }
+
+    /// Get the metadata of every transaction that uses this address as its origin or its sponsor.
+    fn get_txs_by_address(
+        conn: &DBConn,
+        address: &StacksAddress,
+    ) -> Result<Vec<MemPoolTxMetadata>, db_error> {
+        let sql = "SELECT * FROM mempool WHERE origin_address = ?1 OR sponsor_address = ?1";
+        let args: &[&dyn ToSql] = &[&address.to_string()];
+        query_rows(conn, sql, args)
+    }
+
+    /// Get the metadata of every transaction that uses this address as its origin or its sponsor,
+    /// with a lower nonce than the given one.
+    fn get_txs_before_nonce(
+        conn: &DBConn,
+        address: &StacksAddress,
+        nonce: u64,
+    ) -> Result<Vec<MemPoolTxMetadata>, db_error> {
+        let sql = "SELECT * FROM mempool WHERE (origin_address = ?1 AND origin_nonce < ?2) OR (sponsor_address = ?1 AND sponsor_nonce < ?2)";
+        let args: &[&dyn ToSql] = &[&address.to_string(), &u64_to_sql(nonce)?];
+        query_rows(conn, sql, args)
+    }
+
     /// Given a chain tip, find the highest block-height from _before_ this tip
     pub fn get_previous_block_height(conn: &DBConn, height: u64) -> Result<Option<u64>, db_error> {
         let sql = "SELECT height FROM mempool WHERE height < ?1 ORDER BY height DESC LIMIT 1";
@@ -787,9 +1370,12 @@ impl MemPoolDB {
         Ok(cmp::max(as_origin, as_sponsor))
     }
 
-    /// Add a transaction to the mempool.  If it already exists, then replace it if the given fee
-    /// is higher than the one that's already there.
-    /// Carry out the mempool admission test before adding.
+    /// Add a transaction to the mempool.  If there are already transactions with the same origin
+    /// nonce or sponsor nonce, then replace them if the given fee exceeds their combined fee by
+    /// at least the configured replace-by-fee margin, or if they were accepted on a different
+    /// fork.  Replaced transactions are recorded as dropped once the mempool transaction commits.
+    /// Carry out the mempool admission test before adding.  Replaced transactions may already be
+    /// deleted when this fails, so the caller must roll back the mempool transaction on error.
     /// Don't call directly; use submit()
     fn try_add_tx<'a>(
         tx: &mut MemPoolTx<'a>,
@@ -800,6 +1386,7 @@ impl MemPoolDB {
         tx_bytes: Vec<u8>,
         estimated_fee: u64,
         tx_fee: u64,
+        fee_rate: f64,
         height: u64,
         origin_address: &StacksAddress,
         origin_nonce: u64,
@@ -809,54 +1396,80 @@ impl MemPoolDB {
         let length = tx_bytes.len() as u64;
 
         // do we already have txs with either the same origin nonce or sponsor nonce ?
-        let prior_tx = {
-            match MemPoolDB::get_tx_metadata_by_address(tx, true, origin_address, origin_nonce)? {
-                Some(prior_tx) => Some(prior_tx),
-                None => MemPoolDB::get_tx_metadata_by_address(
-                    tx,
-                    false,
-                    sponsor_address,
-                    sponsor_nonce,
-                )?,
+        let mut prior_txs: Vec<MemPoolTxMetadata> = vec![];
+        if let Some(prior_tx) =
+            MemPoolDB::get_tx_metadata_by_address(tx, true, origin_address, origin_nonce)?
+        {
+            prior_txs.push(prior_tx);
+        }
+        if let Some(prior_tx) =
+            MemPoolDB::get_tx_metadata_by_address(tx, false, sponsor_address, sponsor_nonce)?
+        {
+            if prior_txs.iter().all(|t| t.txid != prior_tx.txid) {
+                prior_txs.push(prior_tx);
             }
-        };
+        }
 
         // if so, is this a replace-by-fee? or a replace-in-chain-tip?
-        let add_tx = if let Some(prior_tx) = prior_tx {
-            if estimated_fee > prior_tx.estimated_fee {
+        let mut evicted = vec![];
+        if prior_txs.len() > 0 {
+            let prior_fees: u128 = prior_txs.iter().map(|t| t.tx_fee as u128).sum();
+            let margin = tx.settings.replace_by_fee_margin as u128;
+
+            if (tx_fee as u128) * 100 > prior_fees * (100 + margin) {
                 // is this a replace-by-fee ?
-                true
-            } else if !tx.is_block_in_fork(
-                chainstate,
-                &prior_tx.consensus_hash,
-                &prior_tx.block_header_hash,
-                consensus_hash,
-                block_header_hash,
-            )? {
-                // is this a replace-across-fork ?
-                true
+                for prior_tx in prior_txs.iter() {
+                    evicted.push((prior_tx.txid.clone(), MemPoolDropReason::ReplaceByFee));
+                }
             } else {
-                // there's a >= fee tx in this fork, cannot add
-                info!("TX conflicts with sponsor/origin nonce in same fork with >= fee";
-                      "new_txid" => %txid, 
-                      "old_txid" => %prior_tx.txid,
-                      "origin_addr" => %origin_address,
-                      "origin_nonce" => origin_nonce,
-                      "sponsor_addr" => %sponsor_address,
-                      "sponsor_nonce" => sponsor_nonce,
-                      "new_fee" => estimated_fee,
-                      "old_fee" => prior_tx.estimated_fee);
-                false
+                for prior_tx in prior_txs.iter() {
+                    if tx.is_block_in_fork(
+                        chainstate,
+                        &prior_tx.consensus_hash,
+                        &prior_tx.block_header_hash,
+                        consensus_hash,
+                        block_header_hash,
+                    )? {
+                        // there's a conflicting tx in this fork and the fee isn't high enough, cannot add
+                        info!("TX conflicts with sponsor/origin nonce in same fork without a high enough fee";
+                              "new_txid" => %txid,
+                              "old_txid" => %prior_tx.txid,
+                              "origin_addr" => %origin_address,
+                              "origin_nonce" => origin_nonce,
+                              "sponsor_addr" => %sponsor_address,
+                              "sponsor_nonce" => sponsor_nonce,
+                              "new_fee" => tx_fee,
+                              "old_fees" => %prior_fees,
+                              "margin" => tx.settings.replace_by_fee_margin);
+                        return Err(MemPoolRejection::ConflictingNonceInMempool);
+                    }
+                    // is this a replace-across-fork ?
+                    evicted.push((prior_tx.txid.clone(), MemPoolDropReason::ReplaceAcrossFork));
+                }
             }
-        } else {
-            // no conflicting TX with this origin/sponsor, go ahead and add
-            true
-        };
+        }
 
-        if !add_tx {
-            return Err(MemPoolRejection::ConflictingNonceInMempool);
+        // a replacement can conflict with two different transactions (one on the origin nonce,
+        // one on the sponsor nonce), so remove them explicitly rather than relying on
+        // INSERT OR REPLACE.
+        for (evicted_txid, reason) in evicted.into_iter() {
+            debug!("Evict mempool tx";
+                   "txid" => %evicted_txid,
+                   "replacement_txid" => %txid,
+                   "reason" => %reason);
+            tx.execute(
+                "DELETE FROM mempool WHERE txid = ?1",
+                &[&evicted_txid as &dyn ToSql],
+            )
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+            if evicted_txid != txid {
+                tx.pending_dropped_txs.push((evicted_txid, reason));
+            }
         }
 
+        MemPoolDB::check_origin_limit(tx, origin_address)?;
+        MemPoolDB::make_room(tx, &txid, length, fee_rate, origin_address, origin_nonce)?;
+
         let sql = "INSERT OR REPLACE INTO mempool (
             txid,
             origin_address,
@@ -870,8 +1483,9 @@ impl MemPoolDB {
             block_header_hash,
             height,
             accept_time,
-            tx)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)";
+            tx,
+            fee_rate)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)";
 
         let args: &[&dyn ToSql] = &[
             &txid,
@@ -887,6 +1501,7 @@ impl MemPoolDB {
             &u64_to_sql(height)?,
             &u64_to_sql(get_epoch_time_secs())?,
             &tx_bytes,
+            &fee_rate,
         ];
 
         tx.execute(sql, args)
@@ -894,6 +1509,164 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Refuse a transaction if its origin already has the maximum number of pending transactions.
+    fn check_origin_limit<'a>(
+        tx: &mut MemPoolTx<'a>,
+        origin_address: &StacksAddress,
+    ) -> Result<(), MemPoolRejection> {
+        let max_pending = match tx.settings.max_txs_per_origin {
+            Some(max_pending) => max_pending,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let sql = "SELECT COUNT(*) FROM mempool WHERE origin_address = ?1";
+        let args: &[&dyn ToSql] = &[&origin_address.to_string()];
+        let num_pending = query_count(tx, sql, args)? as u64;
+        if num_pending >= max_pending {
+            info!("TX rejected: origin has too many pending transactions";
+                  "origin_addr" => %origin_address,
+                  "num_pending" => num_pending,
+                  "max_pending" => max_pending);
+            return Err(MemPoolRejection::TooManyPendingTxs {
+                principal: origin_address.clone().into(),
+                max_pending,
+            });
+        }
+        Ok(())
+    }
+
+    /// Make room for a transaction of `length` bytes if the mempool has a size limit, by evicting
+    /// the transactions with the lowest fee rates.  Evicting a transaction also evicts its origin's
+    /// higher-nonce transactions, since they can no longer be mined.  Only transactions with a
+    /// lower fee rate than the new one can be evicted, and never the new transaction's
+    /// lower-nonce predecessors from the same origin.  Nothing is evicted unless the new
+    /// transaction then fits.  Evictions are recorded as dropped once the mempool transaction
+    /// commits.
+    fn make_room<'a>(
+        tx: &mut MemPoolTx<'a>,
+        txid: &Txid,
+        length: u64,
+        fee_rate: f64,
+        origin_address: &StacksAddress,
+        origin_nonce: u64,
+    ) -> Result<(), MemPoolRejection> {
+        let max_bytes = match tx.settings.max_bytes {
+            Some(max_bytes) => max_bytes,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let total_bytes: i64 = tx
+            .query_row(
+                "SELECT IFNULL(SUM(length), 0) FROM mempool",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+        let mut excess_bytes = (total_bytes as u64)
+            .saturating_add(length)
+            .saturating_sub(max_bytes);
+        if excess_bytes == 0 {
+            return Ok(());
+        }
+
+        // find the cheapest transactions whose eviction makes enough room, before evicting any
+        let mut evicted_txids: Vec<Txid> = vec![];
+        let mut min_fee_rate = None;
+        {
+            let sql = "SELECT txid, origin_address, origin_nonce, fee_rate FROM mempool
+                       WHERE NOT (origin_address = ?1 AND origin_nonce < ?2)
+                       ORDER BY fee_rate ASC, accept_time DESC";
+            let args: &[&dyn ToSql] = &[&origin_address.to_string(), &u64_to_sql(origin_nonce)?];
+            let mut stmt = tx
+                .prepare(sql)
+                .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+            let mut rows = stmt
+                .query(args)
+                .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+            while let Some(row) = rows
+                .next()
+                .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?
+            {
+                let victim_txid: Txid = row.get_unwrap(0);
+                let victim_origin: String = row.get_unwrap(1);
+                let victim_nonce: i64 = row.get_unwrap(2);
+                let victim_fee_rate: f64 = row.get_unwrap(3);
+                if evicted_txids.contains(&victim_txid) {
+                    continue;
+                }
+
+                let sql = "SELECT txid, length FROM mempool WHERE origin_address = ?1 AND origin_nonce >= ?2 ORDER BY origin_nonce";
+                let args: &[&dyn ToSql] = &[&victim_origin, &victim_nonce];
+                let mut group_stmt = tx
+                    .prepare(sql)
+                    .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+                let group = group_stmt
+                    .query_map(args, |row| {
+                        let evicted_txid: Txid = row.get_unwrap(0);
+                        let evicted_length: i64 = row.get_unwrap(1);
+                        Ok((evicted_txid, evicted_length as u64))
+                    })
+                    .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+                for evicted in group {
+                    let (evicted_txid, evicted_length) =
+                        evicted.map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+                    if !evicted_txids.contains(&evicted_txid) {
+                        excess_bytes = excess_bytes.saturating_sub(evicted_length);
+                        evicted_txids.push(evicted_txid);
+                    }
+                }
+
+                if excess_bytes == 0 {
+                    min_fee_rate = Some(victim_fee_rate);
+                    break;
+                }
+            }
+        }
+
+        let min_fee_rate = match min_fee_rate {
+            Some(min_fee_rate) => min_fee_rate,
+            None => {
+                info!("TX rejected: mempool is full and evicting cannot make room";
+                      "txid" => %txid,
+                      "length" => length,
+                      "max_bytes" => max_bytes);
+                return Err(MemPoolRejection::MemPoolFull {
+                    fee_rate,
+                    min_fee_rate: None,
+                });
+            }
+        };
+        if min_fee_rate >= fee_rate {
+            info!("TX rejected: mempool is full of transactions with higher fee rates";
+                  "txid" => %txid,
+                  "fee_rate" => fee_rate,
+                  "min_fee_rate" => min_fee_rate);
+            return Err(MemPoolRejection::MemPoolFull {
+                fee_rate,
+                min_fee_rate: Some(min_fee_rate),
+            });
+        }
+
+        for evicted_txid in evicted_txids.into_iter() {
+            debug!("Evict mempool tx";
+                   "txid" => %evicted_txid,
+                   "new_txid" => %txid,
+                   "reason" => %MemPoolDropReason::LowFeeRate);
+            tx.execute(
+                "DELETE FROM mempool WHERE txid = ?1",
+                &[&evicted_txid as &dyn ToSql],
+            )
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+            tx.pending_dropped_txs
+                .push((evicted_txid, MemPoolDropReason::LowFeeRate));
+        }
+        Ok(())
+    }
+
     /// Garbage-collect the mempool.  Remove transactions that have a given number of
     /// confirmations.
     pub fn garbage_collect<'a>(tx: &mut MemPoolTx<'a>, min_height: u64) -> Result<(), db_error> {
@@ -995,6 +1768,8 @@ impl MemPoolDB {
         let estimated_fee = tx_fee
             .checked_mul(len)
             .ok_or(MemPoolRejection::Other("Fee numeric overflow".to_string()))?;
+        let fee_rate =
+            compute_fee_rate(tx_fee, len, &estimate_tx_cost(tx), &chainstate.block_limit);
 
         if do_admission_checks {
             mempool_tx
@@ -1012,6 +1787,7 @@ impl MemPoolDB {
             tx_data,
             estimated_fee,
             tx_fee,
+            fee_rate,
             height,
             &origin_address,
             origin_nonce,
@@ -1139,7 +1915,10 @@ mod tests {
         vm::costs::ExecutionCost,
     };
 
-    use super::MemPoolDB;
+    use super::{
+        compute_fee_rate, estimate_tx_cost, MemPoolDB, MemPoolDropReason, MemPoolSettings,
+        MemPoolTx,
+    };
     use util::db::{DBConn, FromRow};
 
     use chainstate::burn::ConsensusHash;
@@ -1147,6 +1926,7 @@ mod tests {
     use chainstate::stacks::db::test::instantiate_chainstate;
     use chainstate::stacks::db::test::instantiate_chainstate_with_balances;
     use chainstate::stacks::test::codec_all_transactions;
+    use core::BLOCK_LIMIT_MAINNET;
     use core::FIRST_BURNCHAIN_CONSENSUS_HASH;
     use core::FIRST_STACKS_BLOCK_HASH;
 
@@ -1316,6 +2096,7 @@ mod tests {
                 tx_bytes,
                 estimated_fee,
                 tx.get_tx_fee(),
+                0.0,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -1443,6 +2224,7 @@ mod tests {
             tx_bytes,
             estimated_fee,
             tx.get_tx_fee(),
+            0.0,
             height,
             &origin_address,
             origin_nonce,
@@ -1633,6 +2415,7 @@ mod tests {
             tx_bytes,
             estimated_fee,
             tx.get_tx_fee(),
+            0.0,
             height,
             &origin_address,
             origin_nonce,
@@ -1662,6 +2445,7 @@ mod tests {
             tx_bytes,
             estimated_fee,
             tx.get_tx_fee(),
+            0.0,
             height,
             &origin_address,
             origin_nonce,
@@ -1736,6 +2520,7 @@ mod tests {
                 tx_bytes,
                 estimated_fee,
                 tx.get_tx_fee(),
+                0.0,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -1798,6 +2583,7 @@ mod tests {
                 tx_bytes,
                 estimated_fee,
                 tx.get_tx_fee(),
+                0.0,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -1863,6 +2649,7 @@ mod tests {
                 tx_bytes,
                 estimated_fee,
                 tx.get_tx_fee(),
+                0.0,
                 height,
                 &origin_address,
                 origin_nonce,
@@ -1929,4 +2716,542 @@ mod tests {
         .unwrap();
         assert_eq!(txs.len(), 0);
     }
+
+    #[test]
+    fn mempool_iterate_candidates_by_fee_rate() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_iterate_candidates_by_fee_rate");
+        let chainstate_path = chainstate_path("mempool_iterate_candidates_by_fee_rate");
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        let b_1 = (ConsensusHash([0x1; 20]), BlockHeaderHash([0x4; 32]));
+        let recipient = PrincipalData::from(StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0xff; 20]),
+        });
+
+        let make_tx = |sk: &str, nonce: u64, fee: u64, payload: TransactionPayload| {
+            let privk = StacksPrivateKey::from_hex(sk).unwrap();
+            let mut tx = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                TransactionAuth::from_p2pkh(&privk).unwrap(),
+                payload,
+            );
+            tx.chain_id = 0x80000000;
+            tx.set_origin_nonce(nonce);
+            tx.set_tx_fee(fee);
+            tx
+        };
+        let transfer = || {
+            TransactionPayload::TokenTransfer(recipient.clone(), 123, TokenTransferMemo([0u8; 34]))
+        };
+        let contract_call = || {
+            TransactionPayload::new_contract_call(
+                StacksAddress {
+                    version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                    bytes: Hash160([0xfe; 20]),
+                },
+                "hello-world",
+                "foo",
+                vec![],
+            )
+            .unwrap()
+        };
+
+        // the second origin's transfer pays the most per byte, but the first origin's
+        // high-fee transaction must wait for its low-fee predecessor.  The contract-call
+        // pays a large fee, but consumes far more of the block's read budget.
+        let txs = vec![
+            make_tx(SK_1, 0, 100, transfer()),
+            make_tx(SK_1, 1, 1000, transfer()),
+            make_tx(SK_2, 0, 500, transfer()),
+            make_tx(SK_3, 0, 600, contract_call()),
+        ];
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for tx in txs.iter() {
+            let tx_bytes = tx.serialize_to_vec();
+            let len = tx_bytes.len() as u64;
+            let origin_address = tx.origin_address();
+            let fee_rate = compute_fee_rate(
+                tx.get_tx_fee(),
+                len,
+                &estimate_tx_cost(tx),
+                &BLOCK_LIMIT_MAINNET,
+            );
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &b_1.0,
+                &b_1.1,
+                tx.txid(),
+                tx_bytes,
+                tx.get_tx_fee() * len,
+                tx.get_tx_fee(),
+                fee_rate,
+                1,
+                &origin_address,
+                tx.get_origin_nonce(),
+                &origin_address,
+                tx.get_origin_nonce(),
+            )
+            .unwrap();
+        }
+        mempool_tx.commit().unwrap();
+
+        let mut visited = vec![];
+        mempool
+            .iterate_candidates_by_fee_rate::<_, ChainstateError>(
+                &b_1.0,
+                &b_1.1,
+                1,
+                &mut chainstate,
+                |available_txs| {
+                    assert_eq!(available_txs.len(), 1);
+                    visited.push(available_txs[0].tx.txid());
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            visited,
+            vec![txs[2].txid(), txs[0].txid(), txs[1].txid(), txs[3].txid()]
+        );
+    }
+
+    #[test]
+    fn mempool_iterate_candidates_by_fee_rate_sponsor_nonces() {
+        let mut chainstate = instantiate_chainstate(
+            false,
+            0x80000000,
+            "mempool_iterate_candidates_by_fee_rate_sponsor_nonces",
+        );
+        let chainstate_path =
+            chainstate_path("mempool_iterate_candidates_by_fee_rate_sponsor_nonces");
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        let b_1 = (ConsensusHash([0x1; 20]), BlockHeaderHash([0x4; 32]));
+        let recipient = PrincipalData::from(StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0xff; 20]),
+        });
+
+        let make_tx = |sk: &str, nonce: u64, fee: u64, sponsor: Option<(&str, u64)>| {
+            let privk = StacksPrivateKey::from_hex(sk).unwrap();
+            let mut auth = TransactionAuth::from_p2pkh(&privk).unwrap();
+            if let Some((sponsor_sk, _)) = sponsor {
+                let sponsor_privk = StacksPrivateKey::from_hex(sponsor_sk).unwrap();
+                auth = auth
+                    .into_sponsored(TransactionAuth::from_p2pkh(&sponsor_privk).unwrap())
+                    .unwrap();
+            }
+            let mut tx = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                auth,
+                TransactionPayload::TokenTransfer(
+                    recipient.clone(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            );
+            tx.chain_id = 0x80000000;
+            tx.set_origin_nonce(nonce);
+            if let Some((_, sponsor_nonce)) = sponsor {
+                tx.set_sponsor_nonce(sponsor_nonce).unwrap();
+            }
+            tx.set_tx_fee(fee);
+            tx
+        };
+
+        // the first sponsored transaction pays the most, but its sponsor's nonce comes after the
+        // sponsor's own low-fee transaction's, and the second sponsored transaction uses the
+        // sponsor's nonce after that.
+        let txs = vec![
+            make_tx(SK_1, 0, 100, None),
+            make_tx(SK_2, 0, 1000, Some((SK_1, 1))),
+            make_tx(SK_3, 0, 500, Some((SK_1, 2))),
+            make_tx(SK_3, 1, 200, None),
+        ];
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        for tx in txs.iter() {
+            let tx_bytes = tx.serialize_to_vec();
+            let len = tx_bytes.len() as u64;
+            let origin_address = tx.origin_address();
+            let sponsor_address = tx.sponsor_address().unwrap_or(origin_address.clone());
+            let sponsor_nonce = tx.get_sponsor_nonce().unwrap_or(tx.get_origin_nonce());
+            let fee_rate = compute_fee_rate(
+                tx.get_tx_fee(),
+                len,
+                &estimate_tx_cost(tx),
+                &BLOCK_LIMIT_MAINNET,
+            );
+            MemPoolDB::try_add_tx(
+                &mut mempool_tx,
+                &mut chainstate,
+                &b_1.0,
+                &b_1.1,
+                tx.txid(),
+                tx_bytes,
+                tx.get_tx_fee() * len,
+                tx.get_tx_fee(),
+                fee_rate,
+                1,
+                &origin_address,
+                tx.get_origin_nonce(),
+                &sponsor_address,
+                sponsor_nonce,
+            )
+            .unwrap();
+        }
+        mempool_tx.commit().unwrap();
+
+        let mut visited = vec![];
+        mempool
+            .iterate_candidates_by_fee_rate::<_, ChainstateError>(
+                &b_1.0,
+                &b_1.1,
+                1,
+                &mut chainstate,
+                |available_txs| {
+                    assert_eq!(available_txs.len(), 1);
+                    visited.push(available_txs[0].tx.txid());
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            visited,
+            vec![txs[0].txid(), txs[1].txid(), txs[2].txid(), txs[3].txid()]
+        );
+    }
+
+    #[test]
+    fn mempool_replace_by_fee_margin() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_replace_by_fee_margin");
+        let chainstate_path = chainstate_path("mempool_replace_by_fee_margin");
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+        mempool.set_settings(MemPoolSettings {
+            replace_by_fee_margin: 10,
+            ..MemPoolSettings::default()
+        });
+
+        let b_1 = (ConsensusHash([0x1; 20]), BlockHeaderHash([0x4; 32]));
+        let recipient = PrincipalData::from(StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0xff; 20]),
+        });
+        let sponsor_address = StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0xfe; 20]),
+        };
+
+        let make_tx = |sk: &str, fee: u64| {
+            let privk = StacksPrivateKey::from_hex(sk).unwrap();
+            let mut tx = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                TransactionAuth::from_p2pkh(&privk).unwrap(),
+                TransactionPayload::TokenTransfer(
+                    recipient.clone(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            );
+            tx.chain_id = 0x80000000;
+            tx.set_origin_nonce(0);
+            tx.set_tx_fee(fee);
+            tx
+        };
+
+        // (tx, sponsor address, sponsor nonce)
+        let add_tx = |mempool_tx: &mut MemPoolTx,
+                      chainstate: &mut StacksChainState,
+                      tx: &StacksTransaction,
+                      sponsor: Option<(&StacksAddress, u64)>| {
+            let tx_bytes = tx.serialize_to_vec();
+            let len = tx_bytes.len() as u64;
+            let origin_address = tx.origin_address();
+            let (sponsor_address, sponsor_nonce) =
+                sponsor.unwrap_or((&origin_address, tx.get_origin_nonce()));
+            MemPoolDB::try_add_tx(
+                mempool_tx,
+                chainstate,
+                &b_1.0,
+                &b_1.1,
+                tx.txid(),
+                tx_bytes,
+                tx.get_tx_fee() * len,
+                tx.get_tx_fee(),
+                0.0,
+                1,
+                &origin_address,
+                tx.get_origin_nonce(),
+                sponsor_address,
+                sponsor_nonce,
+            )
+        };
+
+        // replacing on the origin nonce needs a fee more than 10% higher
+        let tx_1 = make_tx(SK_1, 1000);
+        let tx_1_low = make_tx(SK_1, 1100);
+        let tx_1_high = make_tx(SK_1, 1101);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_1, None).unwrap();
+        match add_tx(&mut mempool_tx, &mut chainstate, &tx_1_low, None).unwrap_err() {
+            MemPoolRejection::ConflictingNonceInMempool => {}
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_1_high, None).unwrap();
+
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &tx_1.txid()).unwrap());
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &tx_1_low.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_1_high.txid()).unwrap());
+        mempool_tx.commit().unwrap();
+
+        assert_eq!(
+            mempool.take_dropped_txs(),
+            vec![(tx_1.txid(), MemPoolDropReason::ReplaceByFee)]
+        );
+        assert_eq!(mempool.take_dropped_txs(), vec![]);
+
+        // replacing on the sponsor nonce works the same way, even across origins
+        let tx_2 = make_tx(SK_2, 1000);
+        let tx_3_low = make_tx(SK_3, 1050);
+        let tx_3_high = make_tx(SK_3, 2000);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &tx_2,
+            Some((&sponsor_address, 5)),
+        )
+        .unwrap();
+        match add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &tx_3_low,
+            Some((&sponsor_address, 5)),
+        )
+        .unwrap_err()
+        {
+            MemPoolRejection::ConflictingNonceInMempool => {}
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        mempool_tx.commit().unwrap();
+
+        // evictions in an aborted mempool transaction are not reported
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &tx_3_high,
+            Some((&sponsor_address, 5)),
+        )
+        .unwrap();
+        drop(mempool_tx);
+
+        assert_eq!(mempool.take_dropped_txs(), vec![]);
+        assert!(mempool.has_tx(&tx_2.txid()));
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &tx_3_high,
+            Some((&sponsor_address, 5)),
+        )
+        .unwrap();
+        mempool_tx.commit().unwrap();
+
+        assert!(!mempool.has_tx(&tx_2.txid()));
+        assert!(mempool.has_tx(&tx_3_high.txid()));
+        assert_eq!(
+            mempool.take_dropped_txs(),
+            vec![(tx_2.txid(), MemPoolDropReason::ReplaceByFee)]
+        );
+    }
+
+    #[test]
+    fn mempool_size_and_origin_limits() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "mempool_size_and_origin_limits");
+        let chainstate_path = chainstate_path("mempool_size_and_origin_limits");
+        let mut mempool = MemPoolDB::open(false, 0x80000000, &chainstate_path).unwrap();
+
+        let b_1 = (ConsensusHash([0x1; 20]), BlockHeaderHash([0x4; 32]));
+        let recipient = PrincipalData::from(StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([0xff; 20]),
+        });
+
+        let make_tx = |sk: &str, nonce: u64, fee: u64| {
+            let privk = StacksPrivateKey::from_hex(sk).unwrap();
+            let mut tx = StacksTransaction::new(
+                TransactionVersion::Testnet,
+                TransactionAuth::from_p2pkh(&privk).unwrap(),
+                TransactionPayload::TokenTransfer(
+                    recipient.clone(),
+                    123,
+                    TokenTransferMemo([0u8; 34]),
+                ),
+            );
+            tx.chain_id = 0x80000000;
+            tx.set_origin_nonce(nonce);
+            tx.set_tx_fee(fee);
+            tx
+        };
+
+        let add_tx = |mempool_tx: &mut MemPoolTx,
+                      chainstate: &mut StacksChainState,
+                      tx: &StacksTransaction,
+                      fee_rate: f64| {
+            let tx_bytes = tx.serialize_to_vec();
+            let len = tx_bytes.len() as u64;
+            let origin_address = tx.origin_address();
+            MemPoolDB::try_add_tx(
+                mempool_tx,
+                chainstate,
+                &b_1.0,
+                &b_1.1,
+                tx.txid(),
+                tx_bytes,
+                tx.get_tx_fee() * len,
+                tx.get_tx_fee(),
+                fee_rate,
+                1,
+                &origin_address,
+                tx.get_origin_nonce(),
+                &origin_address,
+                tx.get_origin_nonce(),
+            )
+        };
+
+        // all of these transfers have the same length, so the mempool fits three of them
+        let tx_len = make_tx(SK_1, 0, 1).serialize_to_vec().len() as u64;
+        mempool.set_settings(MemPoolSettings {
+            max_bytes: Some(3 * tx_len),
+            max_txs_per_origin: Some(2),
+            ..MemPoolSettings::default()
+        });
+
+        let tx_1_0 = make_tx(SK_1, 0, 1);
+        let tx_1_1 = make_tx(SK_1, 1, 2);
+        let tx_1_2 = make_tx(SK_1, 2, 3);
+        let tx_2_0 = make_tx(SK_2, 0, 4);
+        let tx_3_0 = make_tx(SK_3, 0, 5);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        // an origin can only have two pending transactions
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_1_0, 1.0).unwrap();
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_1_1, 5.0).unwrap();
+        match add_tx(&mut mempool_tx, &mut chainstate, &tx_1_2, 5.0).unwrap_err() {
+            MemPoolRejection::TooManyPendingTxs { max_pending, .. } => {
+                assert_eq!(max_pending, 2);
+            }
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+
+        // once the mempool is full, the cheapest transaction is evicted along with its origin's
+        // later transactions, even if they pay more
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_2_0, 3.0).unwrap();
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_3_0, 2.0).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &tx_1_0.txid()).unwrap());
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &tx_1_1.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_2_0.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_3_0.txid()).unwrap());
+
+        mempool_tx.commit().unwrap();
+        assert_eq!(
+            mempool.take_dropped_txs(),
+            vec![
+                (tx_1_0.txid(), MemPoolDropReason::LowFeeRate),
+                (tx_1_1.txid(), MemPoolDropReason::LowFeeRate)
+            ]
+        );
+
+        let tx_1_0_again = make_tx(SK_1, 0, 6);
+        let tx_2_1 = make_tx(SK_2, 1, 7);
+        let tx_3_1 = make_tx(SK_3, 1, 8);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_1_0_again, 2.5).unwrap();
+
+        // a transaction that pays no more than the cheapest one is refused
+        match add_tx(&mut mempool_tx, &mut chainstate, &tx_2_1, 1.0).unwrap_err() {
+            MemPoolRejection::MemPoolFull {
+                fee_rate,
+                min_fee_rate,
+            } => {
+                assert_eq!(fee_rate, 1.0);
+                assert_eq!(min_fee_rate, Some(2.0));
+            }
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+
+        // a transaction never evicts its own origin's predecessors
+        add_tx(&mut mempool_tx, &mut chainstate, &tx_3_1, 10.0).unwrap();
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, &tx_1_0_again.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_2_0.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_3_0.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_3_1.txid()).unwrap());
+
+        mempool_tx.commit().unwrap();
+        assert_eq!(
+            mempool.take_dropped_txs(),
+            vec![(tx_1_0_again.txid(), MemPoolDropReason::LowFeeRate)]
+        );
+
+        // a refused transaction evicts nothing, even if it pays more than some of the
+        // transactions it would have had to evict
+        mempool.set_settings(MemPoolSettings {
+            max_bytes: Some(tx_len),
+            max_txs_per_origin: Some(2),
+            ..MemPoolSettings::default()
+        });
+        let tx_1_0_cheap = make_tx(SK_1, 0, 9);
+        let tx_1_0_rich = make_tx(SK_1, 0, 10);
+
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        match add_tx(&mut mempool_tx, &mut chainstate, &tx_1_0_cheap, 2.5).unwrap_err() {
+            MemPoolRejection::MemPoolFull {
+                fee_rate,
+                min_fee_rate,
+            } => {
+                assert_eq!(fee_rate, 2.5);
+                assert_eq!(min_fee_rate, Some(3.0));
+            }
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_2_0.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_3_0.txid()).unwrap());
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_3_1.txid()).unwrap());
+        mempool_tx.commit().unwrap();
+
+        // no fee rate makes room for a transaction larger than the mempool
+        mempool.set_settings(MemPoolSettings {
+            max_bytes: Some(tx_len - 1),
+            max_txs_per_origin: Some(2),
+            ..MemPoolSettings::default()
+        });
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+        match add_tx(&mut mempool_tx, &mut chainstate, &tx_1_0_rich, 100.0).unwrap_err() {
+            MemPoolRejection::MemPoolFull {
+                fee_rate,
+                min_fee_rate,
+            } => {
+                assert_eq!(fee_rate, 100.0);
+                assert_eq!(min_fee_rate, None);
+            }
+            e => panic!("Unexpected rejection: {:?}", &e),
+        }
+        assert!(MemPoolDB::db_has_tx(&mempool_tx, &tx_2_0.txid()).unwrap());
+        mempool_tx.commit().unwrap();
+        assert_eq!(mempool.take_dropped_txs(), vec![]);
+    }
 }
@@ -98,6 +98,12 @@ pub const MICROSTACKS_PER_STACKS: u32 = 1_000_000;
 pub const POX_SUNSET_START: u64 = 100_000;
 pub const POX_SUNSET_END: u64 = POX_SUNSET_START + 400_000;
 
+/// Burnchain heights at which Clarity 2 activates: the first Stacks block whose parent was mined
+///  at or after this height publishes the `costs-2` boot contract, and contracts published from
+///  then on can use the Clarity 2 native functions.  Not yet scheduled on either network.
+pub const CLARITY_2_ACTIVATION_HEIGHT_MAINNET: u64 = u64::MAX;
+pub const CLARITY_2_ACTIVATION_HEIGHT_TESTNET: u64 = u64::MAX;
+
 pub const POX_PREPARE_WINDOW_LENGTH: u32 = 100;
 pub const POX_REWARD_CYCLE_LENGTH: u32 = 2100;
 /// The maximum amount that PoX rewards can be scaled by.
@@ -85,6 +85,7 @@ pub mod core;
 pub mod deps;
 
 pub mod clarity;
+pub mod lsp;
 
 pub mod monitoring;
 
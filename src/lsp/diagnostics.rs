@@ -0,0 +1,233 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use vm::analysis;
+use vm::ast::build_ast;
+use vm::costs::LimitedCostTracker;
+use vm::database::MemoryBackingStore;
+use vm::diagnostic::Diagnostic;
+use vm::representations::{SymbolicExpression, SymbolicExpressionType, TraitDefinition};
+use vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use vm::ClarityVersion;
+
+/// Parse and analyze a contract as it would be when deployed, returning the first error found.
+/// The contracts it refers to are loaded with `load_source` and analyzed first, so that calls
+/// to them and the traits they define can be checked. Errors in those contracts are not
+/// reported here: a contract that fails to check is left out, and references to it are reported
+/// instead.
+pub fn check_contract<F>(
+    contract_identifier: &QualifiedContractIdentifier,
+    source: &str,
+    load_source: &F,
+) -> Result<(), Diagnostic>
+where
+    F: Fn(&QualifiedContractIdentifier) -> Option<String>,
+{
+    let mut ast = build_ast(contract_identifier, source, &mut ()).map_err(|e| e.diagnostic)?;
+
+    let mut analysis_marf = MemoryBackingStore::new();
+    let mut visited = HashSet::new();
+    visited.insert(contract_identifier.clone());
+    analyze_dependencies(
+        &ast.expressions,
+        &mut analysis_marf,
+        load_source,
+        &mut visited,
+    );
+
+    let mut analysis_db = analysis_marf.as_analysis_db();
+    analysis::run_analysis(
+        contract_identifier,
+        &mut ast.expressions,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        ClarityVersion::latest(),
+    )
+    .map(|_| ())
+    .map_err(|(e, _)| e.diagnostic)
+}
+
+fn analyze_dependencies<F>(
+    expressions: &[SymbolicExpression],
+    analysis_marf: &mut MemoryBackingStore,
+    load_source: &F,
+    visited: &mut HashSet<QualifiedContractIdentifier>,
+) where
+    F: Fn(&QualifiedContractIdentifier) -> Option<String>,
+{
+    let mut dependencies = vec![];
+    for expr in expressions.iter() {
+        collect_contract_references(expr, &mut dependencies);
+    }
+
+    for dependency in dependencies.into_iter() {
+        if !visited.insert(dependency.clone()) {
+            continue;
+        }
+        let source = match load_source(&dependency) {
+            Some(source) => source,
+            None => continue,
+        };
+        let mut ast = match build_ast(&dependency, &source, &mut ()) {
+            Ok(ast) => ast,
+            Err(_) => continue,
+        };
+        analyze_dependencies(&ast.expressions, analysis_marf, load_source, visited);
+
+        // analyses are stored as contract metadata, which needs the contract to be recorded
+        {
+            let mut clarity_db = analysis_marf.as_clarity_db();
+            clarity_db.begin();
+            if clarity_db
+                .insert_contract_hash(&dependency, &source)
+                .is_err()
+            {
+                clarity_db.roll_back();
+                continue;
+            }
+            clarity_db.commit();
+        }
+        let _ = analysis::run_analysis(
+            &dependency,
+            &mut ast.expressions,
+            &mut analysis_marf.as_analysis_db(),
+            true,
+            LimitedCostTracker::new_free(),
+            ClarityVersion::latest(),
+        );
+    }
+}
+
+/// Collect the contracts an expression refers to, in the order they appear
+fn collect_contract_references(
+    expr: &SymbolicExpression,
+    references: &mut Vec<QualifiedContractIdentifier>,
+) {
+    match expr.expr {
+        SymbolicExpressionType::AtomValue(Value::Principal(PrincipalData::Contract(ref id)))
+        | SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(ref id))) => {
+            references.push(id.clone())
+        }
+        SymbolicExpressionType::Field(ref trait_identifier)
+        | SymbolicExpressionType::TraitReference(
+            _,
+            TraitDefinition::Imported(ref trait_identifier),
+        ) => references.push(trait_identifier.contract_identifier.clone()),
+        SymbolicExpressionType::List(ref exprs) => {
+            for expr in exprs.iter() {
+                collect_contract_references(expr, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_check_contract_dependencies() {
+        let mut sources = HashMap::new();
+        sources.insert(
+            "trait-defs",
+            "(define-trait token-trait ((get-balance (principal) (response uint uint))))",
+        );
+        sources.insert(
+            "token",
+            "(impl-trait .trait-defs.token-trait)
+             (define-public (get-balance (owner principal)) (ok u1))",
+        );
+        sources.insert(
+            "broken",
+            "(define-public (get-balance (owner principal)) (ok (+ u1 1)))",
+        );
+        let load_source = |id: &QualifiedContractIdentifier| {
+            sources
+                .get(id.name.as_str())
+                .map(|source| source.to_string())
+        };
+
+        let caller = QualifiedContractIdentifier::local("caller").unwrap();
+        check_contract(
+            &caller,
+            "(use-trait token-trait .trait-defs.token-trait)
+             (define-public (balance-of (token <token-trait>))
+               (contract-call? token get-balance tx-sender))
+             (define-public (token-balance)
+               (contract-call? .token get-balance tx-sender))",
+            &load_source,
+        )
+        .unwrap();
+
+        // a call to a contract that is missing or does not check is reported
+        let err = check_contract(
+            &caller,
+            "(define-public (token-balance)\n  (contract-call? .broken get-balance tx-sender))",
+            &load_source,
+        )
+        .unwrap_err();
+        assert!(err.message.contains("broken"));
+        assert_eq!(err.spans[0].start_line, 2);
+
+        // cycles between contracts are not followed forever
+        sources.insert("ping", "(define-public (ping) (contract-call? .pong pong))");
+        sources.insert("pong", "(define-public (pong) (contract-call? .ping ping))");
+        let load_source = |id: &QualifiedContractIdentifier| {
+            sources
+                .get(id.name.as_str())
+                .map(|source| source.to_string())
+        };
+        assert!(check_contract(
+            &QualifiedContractIdentifier::local("ping").unwrap(),
+            sources["ping"],
+            &load_source
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_check_contract_spans() {
+        let load_source = |_: &QualifiedContractIdentifier| None;
+        let contract_identifier = QualifiedContractIdentifier::transient();
+
+        let err = check_contract(
+            &contract_identifier,
+            "(define-constant a u1)\n\n\n(define-read-only (f)\n  (+ a 1))",
+            &load_source,
+        )
+        .unwrap_err();
+        assert_eq!(err.spans.len(), 1);
+        assert_eq!(err.spans[0].start_line, 5);
+        assert_eq!(err.spans[0].start_column, 3);
+        assert_eq!(err.spans[0].end_line, 5);
+        assert_eq!(err.spans[0].end_column, 9);
+
+        let err = check_contract(
+            &contract_identifier,
+            "(define-constant a u1)\n  (define-constant b 1a)",
+            &load_source,
+        )
+        .unwrap_err();
+        assert_eq!(err.spans.len(), 1);
+        assert_eq!(err.spans[0].start_line, 2);
+        assert_eq!(err.spans[0].start_column, 23);
+    }
+}
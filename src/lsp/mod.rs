@@ -0,0 +1,929 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+pub mod diagnostics;
+pub mod protocol;
+pub mod symbols;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde_json;
+use url::Url;
+
+use vm::ast::parser;
+use vm::diagnostic;
+use vm::docs::make_json_api_reference;
+use vm::representations::PreSymbolicExpressionType;
+use vm::types::QualifiedContractIdentifier;
+use vm::DefineFunctions;
+
+use self::diagnostics::check_contract;
+use self::protocol::{
+    read_message, write_message, CompletionItem, Diagnostic, DiagnosticRelatedInformation,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Location,
+    MarkupContent, Position, Range, TextDocumentPositionParams, COMPLETION_KIND_CONSTANT,
+    COMPLETION_KIND_FUNCTION, COMPLETION_KIND_INTERFACE, COMPLETION_KIND_KEYWORD,
+    COMPLETION_KIND_STRUCT, COMPLETION_KIND_VARIABLE, INVALID_PARAMS, INVALID_REQUEST,
+    MARKUP_KIND_MARKDOWN, METHOD_NOT_FOUND, PARSE_ERROR, SEVERITY_ERROR,
+};
+use self::symbols::{
+    find_contract_reference, find_definitions, find_expressions_at, ContractReference,
+};
+
+type RequestResult = Result<serde_json::Value, (i64, String)>;
+
+/// A native function's documentation, as output by `make_json_api_reference`
+#[derive(Deserialize)]
+struct FunctionReference {
+    name: String,
+    input_type: String,
+    output_type: String,
+    signature: String,
+    description: String,
+    example: String,
+}
+
+/// A keyword's documentation, as output by `make_json_api_reference`
+#[derive(Deserialize)]
+struct KeywordReference {
+    name: String,
+    output_type: String,
+    description: String,
+    example: String,
+}
+
+#[derive(Deserialize)]
+struct ApiReference {
+    functions: Vec<FunctionReference>,
+    keywords: Vec<KeywordReference>,
+}
+
+/// What hovering over, or completing, a native name shows
+struct NativeDocumentation {
+    kind: u32,
+    detail: String,
+    markdown: String,
+}
+
+/// A language server for Clarity contracts. Documents are identified by URI, and a contract
+/// refers to the contract `.foo` (or `'<issuer>.foo`) in the file `foo.clar` in its directory,
+/// which is read from disk unless it is open.
+pub struct LanguageServer {
+    /// The text of the open documents
+    documents: HashMap<String, String>,
+    natives: BTreeMap<String, NativeDocumentation>,
+    shutdown_requested: bool,
+    exit_requested: bool,
+}
+
+/// Get the contract identifier a document is checked under: its file name, without the `.clar`
+/// extension, issued by the transient principal
+fn get_contract_identifier(uri: &str) -> QualifiedContractIdentifier {
+    Url::parse(uri)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()
+                .and_then(|segments| segments.last())
+                .map(|file_name| file_name.trim_end_matches(".clar").to_string())
+        })
+        .and_then(|name| QualifiedContractIdentifier::local(&name).ok())
+        .unwrap_or_else(QualifiedContractIdentifier::transient)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_!?+<>=/*".contains(c)
+}
+
+/// Get the name (or other word) at a position in a document
+fn get_word_at<'a>(text: &'a str, position: &Position) -> Option<&'a str> {
+    let line = text.lines().nth(position.line as usize)?;
+    let mut utf16_offset = 0;
+    let (index, c) = line.char_indices().find(|(_, c)| {
+        utf16_offset += c.len_utf16() as u32;
+        utf16_offset > position.character
+    })?;
+    if !is_name_char(c) {
+        return None;
+    }
+    let start = line[..index]
+        .rfind(|c| !is_name_char(c))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let end = line[index..]
+        .find(|c| !is_name_char(c))
+        .map(|i| index + i)
+        .unwrap_or(line.len());
+    Some(&line[start..end])
+}
+
+fn to_lsp_diagnostic(uri: &str, source: &str, diagnostic: diagnostic::Diagnostic) -> Diagnostic {
+    let mut spans = diagnostic.spans.iter();
+    let range = spans
+        .next()
+        .map(|span| Range::from_span(span, source))
+        .unwrap_or_else(Range::empty);
+    let related_information = spans
+        .map(|span| DiagnosticRelatedInformation {
+            location: Location {
+                uri: uri.to_string(),
+                range: Range::from_span(span, source),
+            },
+            message: diagnostic.message.clone(),
+        })
+        .collect();
+    let message = match diagnostic.suggestion {
+        Some(suggestion) => format!("{}\n{}", diagnostic.message, suggestion),
+        None => diagnostic.message,
+    };
+    Diagnostic {
+        range,
+        severity: SEVERITY_ERROR,
+        source: "clarity".into(),
+        message,
+        related_information,
+    }
+}
+
+fn get_completion_kind(define_type: DefineFunctions) -> u32 {
+    match define_type {
+        DefineFunctions::PublicFunction
+        | DefineFunctions::PrivateFunction
+        | DefineFunctions::ReadOnlyFunction => COMPLETION_KIND_FUNCTION,
+        DefineFunctions::PersistedVariable => COMPLETION_KIND_VARIABLE,
+        DefineFunctions::Map => COMPLETION_KIND_STRUCT,
+        DefineFunctions::Trait | DefineFunctions::UseTrait | DefineFunctions::ImplTrait => {
+            COMPLETION_KIND_INTERFACE
+        }
+        DefineFunctions::Constant
+        | DefineFunctions::FungibleToken
+        | DefineFunctions::NonFungibleToken => COMPLETION_KIND_CONSTANT,
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: &serde_json::Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params.clone())
+        .map_err(|e| (INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+fn make_error_response(id: serde_json::Value, code: i64, message: String) -> serde_json::Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message,
+        }
+    })
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        let api_reference: ApiReference = serde_json::from_str(&make_json_api_reference())
+            .expect("Failed to parse the Clarity API reference");
+
+        let mut natives = BTreeMap::new();
+        for function in api_reference.functions.into_iter() {
+            let markdown = format!(
+                "```clarity\n{}\n```\n\n{}\n\n**Input:** `{}`\n\n**Output:** `{}`\n\n**Example:**\n\n```clarity\n{}\n```",
+                function.signature,
+                function.description,
+                function.input_type,
+                function.output_type,
+                function.example
+            );
+            natives.insert(
+                function.name,
+                NativeDocumentation {
+                    kind: COMPLETION_KIND_FUNCTION,
+                    detail: function.signature,
+                    markdown,
+                },
+            );
+        }
+        for keyword in api_reference.keywords.into_iter() {
+            let markdown = format!(
+                "```clarity\n{}\n```\n\n{}\n\n**Output:** `{}`\n\n**Example:**\n\n```clarity\n{}\n```",
+                keyword.name, keyword.description, keyword.output_type, keyword.example
+            );
+            natives.insert(
+                keyword.name,
+                NativeDocumentation {
+                    kind: COMPLETION_KIND_KEYWORD,
+                    detail: keyword.output_type,
+                    markdown,
+                },
+            );
+        }
+
+        LanguageServer {
+            documents: HashMap::new(),
+            natives,
+            shutdown_requested: false,
+            exit_requested: false,
+        }
+    }
+
+    /// Has the client sent the `exit` notification?
+    pub fn is_exiting(&self) -> bool {
+        self.exit_requested
+    }
+
+    /// Has the client sent the `shutdown` request?
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown_requested
+    }
+
+    /// Handle one message from the client, returning the messages to send back to it
+    pub fn handle_message(&mut self, message: &[u8]) -> Vec<serde_json::Value> {
+        let message: serde_json::Value = match serde_json::from_slice(message) {
+            Ok(message) => message,
+            Err(e) => {
+                return vec![make_error_response(
+                    serde_json::Value::Null,
+                    PARSE_ERROR,
+                    format!("Invalid JSON: {}", e),
+                )]
+            }
+        };
+        let id = message.get("id").cloned();
+        let params = message
+            .get("params")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        let method = match message.get("method").and_then(|method| method.as_str()) {
+            Some(method) => method,
+            // the server sends no requests, so there are no responses to handle
+            None if message.get("result").is_some() || message.get("error").is_some() => {
+                return vec![]
+            }
+            None => {
+                return vec![make_error_response(
+                    id.unwrap_or(serde_json::Value::Null),
+                    INVALID_REQUEST,
+                    "Missing method".into(),
+                )]
+            }
+        };
+
+        match id {
+            Some(id) => {
+                let response = match self.handle_request(method, &params) {
+                    Ok(result) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": result,
+                    }),
+                    Err((code, message)) => make_error_response(id, code, message),
+                };
+                vec![response]
+            }
+            None => self.handle_notification(method, &params),
+        }
+    }
+
+    fn handle_request(&mut self, method: &str, params: &serde_json::Value) -> RequestResult {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        // full-document sync
+                        "change": 1,
+                    },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {
+                        "triggerCharacters": ["("],
+                    },
+                },
+                "serverInfo": {
+                    "name": "clarity-lsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(serde_json::Value::Null)
+            }
+            "textDocument/hover" => self.get_hover(parse_params(params)?),
+            "textDocument/definition" => self.get_definition(parse_params(params)?),
+            "textDocument/completion" => self.get_completions(parse_params(params)?),
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method '{}'", method))),
+        }
+    }
+
+    fn handle_notification(
+        &mut self,
+        method: &str,
+        params: &serde_json::Value,
+    ) -> Vec<serde_json::Value> {
+        match method {
+            "exit" => {
+                self.exit_requested = true;
+                vec![]
+            }
+            "textDocument/didOpen" => match parse_params::<DidOpenTextDocumentParams>(params) {
+                Ok(params) => {
+                    self.documents
+                        .insert(params.text_document.uri, params.text_document.text);
+                    self.publish_diagnostics()
+                }
+                Err((_, message)) => {
+                    warn!("Ignoring {}: {}", method, message);
+                    vec![]
+                }
+            },
+            "textDocument/didChange" => match parse_params::<DidChangeTextDocumentParams>(params) {
+                Ok(mut params) => match params.content_changes.pop() {
+                    Some(change) => {
+                        self.documents.insert(params.text_document.uri, change.text);
+                        self.publish_diagnostics()
+                    }
+                    None => vec![],
+                },
+                Err((_, message)) => {
+                    warn!("Ignoring {}: {}", method, message);
+                    vec![]
+                }
+            },
+            "textDocument/didClose" => match parse_params::<DidCloseTextDocumentParams>(params) {
+                Ok(params) => {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(&uri);
+                    // the contracts that refer to it may now read it from disk
+                    let mut messages = vec![self.make_diagnostics_notification(&uri, vec![])];
+                    messages.append(&mut self.publish_diagnostics());
+                    messages
+                }
+                Err((_, message)) => {
+                    warn!("Ignoring {}: {}", method, message);
+                    vec![]
+                }
+            },
+            // i.e. `initialized`, `textDocument/didSave`, or `$/cancelRequest`
+            _ => vec![],
+        }
+    }
+
+    /// Get the URI of the file a document refers to a contract by: the file named after the
+    /// contract, in the document's directory
+    fn get_contract_uri(
+        &self,
+        uri: &str,
+        contract: &QualifiedContractIdentifier,
+    ) -> Option<String> {
+        let contract_uri = Url::parse(uri)
+            .ok()?
+            .join(&format!("{}.clar", contract.name))
+            .ok()?;
+        let is_file = contract_uri
+            .to_file_path()
+            .map(|path| path.is_file())
+            .unwrap_or(false);
+        let contract_uri = contract_uri.as_str().to_string();
+        if self.documents.contains_key(&contract_uri) || is_file {
+            Some(contract_uri)
+        } else {
+            None
+        }
+    }
+
+    fn get_source(&self, uri: &str) -> Option<String> {
+        if let Some(text) = self.documents.get(uri) {
+            return Some(text.clone());
+        }
+        let path = Url::parse(uri).ok()?.to_file_path().ok()?;
+        fs::read_to_string(path).ok()
+    }
+
+    fn make_diagnostics_notification(
+        &self,
+        uri: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) -> serde_json::Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {
+                "uri": uri,
+                "diagnostics": diagnostics,
+            }
+        })
+    }
+
+    /// Check every open document, since a change to one can affect the contracts that refer
+    /// to it
+    fn publish_diagnostics(&self) -> Vec<serde_json::Value> {
+        let mut uris: Vec<_> = self.documents.keys().collect();
+        uris.sort();
+        uris.into_iter()
+            .map(|uri| {
+                let load_source = |contract: &QualifiedContractIdentifier| {
+                    self.get_contract_uri(uri, contract)
+                        .and_then(|contract_uri| self.get_source(&contract_uri))
+                };
+                let diagnostics = match check_contract(
+                    &get_contract_identifier(uri),
+                    &self.documents[uri],
+                    &load_source,
+                ) {
+                    Ok(()) => vec![],
+                    Err(diagnostic) => {
+                        vec![to_lsp_diagnostic(uri, &self.documents[uri], diagnostic)]
+                    }
+                };
+                self.make_diagnostics_notification(uri, diagnostics)
+            })
+            .collect()
+    }
+
+    fn get_hover(&self, params: TextDocumentPositionParams) -> RequestResult {
+        let source = match self.get_source(&params.text_document.uri) {
+            Some(source) => source,
+            None => return Ok(serde_json::Value::Null),
+        };
+        let documentation =
+            get_word_at(&source, &params.position).and_then(|word| self.natives.get(word));
+        match documentation {
+            Some(documentation) => Ok(json!({
+                "contents": MarkupContent {
+                    kind: MARKUP_KIND_MARKDOWN.into(),
+                    value: documentation.markdown.clone(),
+                }
+            })),
+            None => Ok(serde_json::Value::Null),
+        }
+    }
+
+    /// Find where `name` is defined in the contract `contract`, as referred to by the document
+    /// `uri`
+    fn find_contract_definition<F>(
+        &self,
+        uri: &str,
+        contract: &QualifiedContractIdentifier,
+        name: &str,
+        filter: F,
+    ) -> Option<Location>
+    where
+        F: Fn(DefineFunctions) -> bool,
+    {
+        let contract_uri = self.get_contract_uri(uri, contract)?;
+        let source = self.get_source(&contract_uri)?;
+        let pre_expressions = parser::parse(&source).ok()?;
+        find_definitions(&pre_expressions)
+            .into_iter()
+            .find(|definition| definition.name.as_str() == name && filter(definition.define_type))
+            .map(|definition| Location {
+                range: Range::from_span(&definition.span, &source),
+                uri: contract_uri,
+            })
+    }
+
+    fn get_definition(&self, params: TextDocumentPositionParams) -> RequestResult {
+        let uri = &params.text_document.uri;
+        let source = match self.get_source(uri) {
+            Some(source) => source,
+            None => return Ok(serde_json::Value::Null),
+        };
+        let pre_expressions = match parser::parse(&source) {
+            Ok(pre_expressions) => pre_expressions,
+            Err(_) => return Ok(serde_json::Value::Null),
+        };
+        let (line, column) = params.position.to_line_column(&source);
+        let path = find_expressions_at(&pre_expressions, line, column);
+
+        let location = match find_contract_reference(&path, &get_contract_identifier(uri)) {
+            Some(ContractReference::Contract(contract)) => self
+                .get_contract_uri(uri, &contract)
+                .map(|contract_uri| Location {
+                    uri: contract_uri,
+                    range: Range::empty(),
+                }),
+            Some(ContractReference::Trait(contract, name)) => {
+                self.find_contract_definition(uri, &contract, &name, |define_type| {
+                    define_type == DefineFunctions::Trait
+                })
+            }
+            Some(ContractReference::Function(contract, name)) => {
+                self.find_contract_definition(uri, &contract, &name, |define_type| {
+                    define_type == DefineFunctions::PublicFunction
+                        || define_type == DefineFunctions::ReadOnlyFunction
+                })
+            }
+            None => {
+                let name = path.last().and_then(|expr| match expr.pre_expr {
+                    PreSymbolicExpressionType::Atom(ref name)
+                    | PreSymbolicExpressionType::TraitReference(ref name) => Some(name),
+                    _ => None,
+                });
+                name.and_then(|name| {
+                    find_definitions(&pre_expressions)
+                        .into_iter()
+                        .find(|definition| definition.name == *name)
+                })
+                .map(|definition| Location {
+                    uri: uri.clone(),
+                    range: Range::from_span(&definition.span, &source),
+                })
+            }
+        };
+
+        match location {
+            Some(location) => serde_json::to_value(location).map_err(|e| {
+                (
+                    INVALID_REQUEST,
+                    format!("Failed to serialize location: {}", e),
+                )
+            }),
+            None => Ok(serde_json::Value::Null),
+        }
+    }
+
+    fn get_completions(&self, params: TextDocumentPositionParams) -> RequestResult {
+        let mut items: Vec<_> = self
+            .natives
+            .iter()
+            .map(|(name, documentation)| CompletionItem {
+                label: name.clone(),
+                kind: documentation.kind,
+                detail: documentation.detail.clone(),
+                documentation: Some(MarkupContent {
+                    kind: MARKUP_KIND_MARKDOWN.into(),
+                    value: documentation.markdown.clone(),
+                }),
+            })
+            .collect();
+
+        // the names the document defines, as long as it parses
+        if let Some(pre_expressions) = self
+            .get_source(&params.text_document.uri)
+            .and_then(|source| parser::parse(&source).ok())
+        {
+            for definition in find_definitions(&pre_expressions).into_iter() {
+                items.push(CompletionItem {
+                    label: definition.name.to_string(),
+                    kind: get_completion_kind(definition.define_type),
+                    detail: definition.define_type.get_name(),
+                    documentation: None,
+                });
+            }
+        }
+
+        Ok(json!({
+            "isIncomplete": false,
+            "items": items,
+        }))
+    }
+}
+
+/// Run a language server over `input` and `output` until the client sends the `exit`
+/// notification or closes `input`. Returns whether the client sent the `shutdown` request
+/// first.
+pub fn serve<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> io::Result<bool> {
+    let mut server = LanguageServer::new();
+    while let Some(message) = read_message(input)? {
+        for reply in server.handle_message(&message).iter() {
+            write_message(output, reply)?;
+        }
+        if server.is_exiting() {
+            break;
+        }
+    }
+    Ok(server.is_shut_down())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    const DEFS_URI: &str = "file:///clarity-lsp-test/defs.clar";
+    const TOKEN_URI: &str = "file:///clarity-lsp-test/token.clar";
+
+    fn send(server: &mut LanguageServer, message: serde_json::Value) -> Vec<serde_json::Value> {
+        server.handle_message(serde_json::to_string(&message).unwrap().as_bytes())
+    }
+
+    fn request(
+        server: &mut LanguageServer,
+        method: &str,
+        params: serde_json::Value,
+    ) -> serde_json::Value {
+        let mut replies = send(
+            server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}),
+        );
+        assert_eq!(replies.len(), 1);
+        replies.pop().unwrap()
+    }
+
+    fn position_params(uri: &str, line: u32, character: u32) -> serde_json::Value {
+        json!({
+            "textDocument": {"uri": uri},
+            "position": {"line": line, "character": character},
+        })
+    }
+
+    fn open(server: &mut LanguageServer, uri: &str, text: &str) -> Vec<serde_json::Value> {
+        send(
+            server,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {
+                    "textDocument": {"uri": uri, "languageId": "clarity", "version": 1, "text": text}
+                }
+            }),
+        )
+    }
+
+    #[test]
+    fn test_get_contract_identifier() {
+        assert_eq!(
+            get_contract_identifier(TOKEN_URI),
+            QualifiedContractIdentifier::local("token").unwrap()
+        );
+        assert_eq!(
+            get_contract_identifier("untitled:Untitled-1"),
+            QualifiedContractIdentifier::transient()
+        );
+    }
+
+    #[test]
+    fn test_get_word_at() {
+        let text = "(define-read-only (get-balance)\n  (stx-get-balance tx-sender))";
+        let word_at = |line, character| get_word_at(text, &Position { line, character });
+        assert_eq!(word_at(0, 1), Some("define-read-only"));
+        assert_eq!(word_at(0, 16), Some("define-read-only"));
+        assert_eq!(word_at(0, 0), None);
+        assert_eq!(word_at(1, 5), Some("stx-get-balance"));
+        assert_eq!(word_at(1, 20), Some("tx-sender"));
+        assert_eq!(word_at(1, 40), None);
+        assert_eq!(word_at(2, 0), None);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut server = LanguageServer::new();
+
+        let messages = open(
+            &mut server,
+            TOKEN_URI,
+            "(impl-trait .defs.token-trait)\n\n(define-public (get-balance (owner principal))\n  (ok (+ u1 u1)))",
+        );
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(messages[0]["params"]["uri"], TOKEN_URI);
+        let diagnostics = messages[0]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]["message"]
+                .as_str()
+                .unwrap()
+                .contains("<token-trait>"),
+            "{}",
+            diagnostics[0]
+        );
+
+        // opening the trait's contract re-checks the contracts that refer to it
+        let messages = open(
+            &mut server,
+            DEFS_URI,
+            "(define-trait token-trait ((get-balance (principal) (response uint uint))))",
+        );
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["params"]["uri"], DEFS_URI);
+        assert_eq!(messages[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(messages[1]["params"]["uri"], TOKEN_URI);
+        assert_eq!(messages[1]["params"]["diagnostics"], json!([]));
+
+        let messages = send(
+            &mut server,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didChange",
+                "params": {
+                    "textDocument": {"uri": TOKEN_URI, "version": 2},
+                    "contentChanges": [{"text": "(impl-trait .defs.token-trait)\n\n(define-public (get-balance (owner principal))\n  (ok (+ u1 1)))"}]
+                }
+            }),
+        );
+        assert_eq!(messages.len(), 2);
+        let diagnostics = messages[1]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({"start": {"line": 3, "character": 6}, "end": {"line": 3, "character": 14}})
+        );
+        assert_eq!(diagnostics[0]["severity"], json!(SEVERITY_ERROR));
+
+        // parse errors are reported where they occur
+        let messages = open(&mut server, TOKEN_URI, "(define-constant a 1)\n   (ok u1))");
+        let diagnostics = messages[1]["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(
+            diagnostics[0]["range"],
+            json!({"start": {"line": 1, "character": 10}, "end": {"line": 1, "character": 11}})
+        );
+
+        let messages = send(
+            &mut server,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didClose",
+                "params": {"textDocument": {"uri": TOKEN_URI}}
+            }),
+        );
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["params"]["uri"], TOKEN_URI);
+        assert_eq!(messages[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(messages[1]["params"]["uri"], DEFS_URI);
+    }
+
+    #[test]
+    fn test_hover_and_completion() {
+        let mut server = LanguageServer::new();
+        open(
+            &mut server,
+            TOKEN_URI,
+            "(define-data-var total uint u0)\n(define-public (mint (amount uint))\n  (ok (var-set total (+ (var-get total) amount))))",
+        );
+
+        let response = request(
+            &mut server,
+            "textDocument/hover",
+            position_params(TOKEN_URI, 2, 8),
+        );
+        let hover = response["result"]["contents"]["value"].as_str().unwrap();
+        assert!(hover.starts_with("```clarity\n(var-set var-name expr1)\n```"));
+        assert_eq!(response["result"]["contents"]["kind"], "markdown");
+
+        let response = request(
+            &mut server,
+            "textDocument/hover",
+            position_params(TOKEN_URI, 0, 2),
+        );
+        let hover = response["result"]["contents"]["value"].as_str().unwrap();
+        assert!(hover.contains("define-data-var"));
+
+        // user-defined names are not documented
+        let response = request(
+            &mut server,
+            "textDocument/hover",
+            position_params(TOKEN_URI, 1, 17),
+        );
+        assert_eq!(response["result"], serde_json::Value::Null);
+
+        let response = request(
+            &mut server,
+            "textDocument/completion",
+            position_params(TOKEN_URI, 2, 3),
+        );
+        let items = response["result"]["items"].as_array().unwrap();
+        let find_item = |label: &str| {
+            items
+                .iter()
+                .find(|item| item["label"] == label)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(
+            find_item("stx-transfer?")["kind"],
+            json!(COMPLETION_KIND_FUNCTION)
+        );
+        assert_eq!(
+            find_item("block-height")["kind"],
+            json!(COMPLETION_KIND_KEYWORD)
+        );
+        assert_eq!(find_item("total")["kind"], json!(COMPLETION_KIND_VARIABLE));
+        assert_eq!(find_item("mint")["detail"], "define-public");
+    }
+
+    #[test]
+    fn test_definition() {
+        let mut server = LanguageServer::new();
+        open(
+            &mut server,
+            DEFS_URI,
+            "(define-trait token-trait\n  ((get-balance (principal) (response uint uint))))\n(define-public (get-balance (owner principal)) (ok u1))",
+        );
+        open(
+            &mut server,
+            TOKEN_URI,
+            "(use-trait token-trait .defs.token-trait)
+(define-constant owner tx-sender)
+(define-public (balance-of (token <token-trait>))
+  (contract-call? token get-balance owner))
+(define-public (defs-balance)
+  (contract-call? .defs get-balance owner))",
+        );
+
+        let definition_at = |server: &mut LanguageServer, line, character| {
+            request(
+                server,
+                "textDocument/definition",
+                position_params(TOKEN_URI, line, character),
+            )["result"]
+                .clone()
+        };
+
+        // a constant
+        assert_eq!(
+            definition_at(&mut server, 3, 37),
+            json!({
+                "uri": TOKEN_URI,
+                "range": {"start": {"line": 1, "character": 17}, "end": {"line": 1, "character": 22}}
+            })
+        );
+        // a trait reference resolves to its `use-trait`
+        assert_eq!(
+            definition_at(&mut server, 2, 37),
+            json!({
+                "uri": TOKEN_URI,
+                "range": {"start": {"line": 0, "character": 11}, "end": {"line": 0, "character": 22}}
+            })
+        );
+        // which refers to the trait's definition in another contract
+        assert_eq!(
+            definition_at(&mut server, 0, 30),
+            json!({
+                "uri": DEFS_URI,
+                "range": {"start": {"line": 0, "character": 14}, "end": {"line": 0, "character": 25}}
+            })
+        );
+        // a function called with contract-call?
+        assert_eq!(
+            definition_at(&mut server, 5, 25),
+            json!({
+                "uri": DEFS_URI,
+                "range": {"start": {"line": 2, "character": 16}, "end": {"line": 2, "character": 27}}
+            })
+        );
+        // a contract
+        assert_eq!(
+            definition_at(&mut server, 5, 19),
+            json!({
+                "uri": DEFS_URI,
+                "range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 0}}
+            })
+        );
+        // a function called on a trait can't be resolved, and neither can natives
+        assert_eq!(definition_at(&mut server, 3, 25), serde_json::Value::Null);
+        assert_eq!(definition_at(&mut server, 3, 5), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_serve() {
+        let mut input = vec![];
+        for message in [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"capabilities": {}}}),
+            json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {"query": ""}}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+            json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}),
+        ]
+        .iter()
+        {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = vec![];
+        assert!(serve(&mut Cursor::new(input), &mut output).unwrap());
+
+        let mut output = Cursor::new(output);
+        let mut replies = vec![];
+        while let Some(message) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_slice::<serde_json::Value>(&message).unwrap());
+        }
+        // nothing after `exit` is handled
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(
+            replies[0]["result"]["capabilities"]["definitionProvider"],
+            true
+        );
+        assert_eq!(replies[1]["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert_eq!(replies[2]["id"], 3);
+        assert_eq!(replies[2]["result"], serde_json::Value::Null);
+
+        let mut server = LanguageServer::new();
+        let replies = server.handle_message(b"{\"jsonrpc\": \"2.0\", \"id\": ");
+        assert_eq!(replies[0]["error"]["code"], json!(PARSE_ERROR));
+    }
+}
@@ -0,0 +1,368 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::io::{BufRead, Read, Write};
+
+use serde_json;
+
+use vm::representations::Span;
+
+/// JSON-RPC error code: the message is not valid JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code: the message is not a valid request
+pub const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code: the server does not implement the requested method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code: the request's parameters are malformed
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// `DiagnosticSeverity.Error`
+pub const SEVERITY_ERROR: u32 = 1;
+
+/// `MarkupKind.Markdown`
+pub const MARKUP_KIND_MARKDOWN: &str = "markdown";
+
+/// `CompletionItemKind`s
+pub const COMPLETION_KIND_FUNCTION: u32 = 3;
+pub const COMPLETION_KIND_VARIABLE: u32 = 6;
+pub const COMPLETION_KIND_INTERFACE: u32 = 8;
+pub const COMPLETION_KIND_KEYWORD: u32 = 14;
+pub const COMPLETION_KIND_CONSTANT: u32 = 21;
+pub const COMPLETION_KIND_STRUCT: u32 = 22;
+
+/// Read the body of one message, framed by a `Content-Length` header. Returns `None` once the
+/// input is exhausted.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.len() == 0 {
+            break;
+        }
+        // other headers (i.e. `Content-Type`) are ignored
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse::<usize>().map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid Content-Length header: {}", e),
+                    )
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header")
+    })?;
+    let mut body = vec![0u8; content_length];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Write one message, framed by a `Content-Length` header
+pub fn write_message<W: Write>(output: &mut W, message: &serde_json::Value) -> io::Result<()> {
+    let body = serde_json::to_string(message)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// A position in a document. Both fields count from 0, and `character` counts UTF-16 code
+/// units.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Get a line of `text`, counting from 0, the same way the Clarity parser splits lines
+fn get_line(text: &str, line: u32) -> &str {
+    text.split('\n').nth(line as usize).unwrap_or("")
+}
+
+impl Position {
+    /// Convert to the line and column of the Clarity parser in `text`, which count from 1.
+    /// Parser columns count bytes, so the UTF-16 offset is converted to a byte offset.
+    pub fn to_line_column(&self, text: &str) -> (u32, u32) {
+        let line = get_line(text, self.line);
+        let mut utf16_offset = 0;
+        let byte_offset = line
+            .char_indices()
+            .find(|(_, c)| {
+                utf16_offset += c.len_utf16() as u32;
+                utf16_offset > self.character
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(line.len());
+        (self.line + 1, byte_offset as u32 + 1)
+    }
+
+    /// Convert a line and byte column of the Clarity parser in `text`, which count from 1.
+    pub fn from_line_column(line: u32, column: u32, text: &str) -> Position {
+        let line = line.saturating_sub(1);
+        let byte_offset = column.saturating_sub(1) as usize;
+        let character = get_line(text, line)
+            .char_indices()
+            .take_while(|(index, _)| *index < byte_offset)
+            .map(|(_, c)| c.len_utf16() as u32)
+            .sum();
+        Position { line, character }
+    }
+}
+
+impl Range {
+    /// Convert a parser span in `text`, whose lines and columns count from 1 and whose end
+    /// column is inclusive. Spans that were never set (or whose end is unknown) become an empty
+    /// range at their start.
+    pub fn from_span(span: &Span, text: &str) -> Range {
+        let start = Position::from_line_column(span.start_line, span.start_column, text);
+        let end = if span.end_line == 0 {
+            start
+        } else {
+            Position::from_line_column(span.end_line, span.end_column + 1, text)
+        };
+        Range { start, end }
+    }
+
+    pub fn empty() -> Range {
+        let start = Position {
+            line: 0,
+            character: 0,
+        };
+        Range { start, end: start }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Location {
+    pub uri: String,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiagnosticRelatedInformation {
+    pub location: Location,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: u32,
+    pub source: String,
+    pub message: String,
+    #[serde(rename = "relatedInformation")]
+    pub related_information: Vec<DiagnosticRelatedInformation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarkupContent {
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: u32,
+    pub detail: String,
+    pub documentation: Option<MarkupContent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextDocumentItem {
+    pub uri: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextDocumentContentChangeEvent {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DidOpenTextDocumentParams {
+    #[serde(rename = "textDocument")]
+    pub text_document: TextDocumentItem,
+}
+
+/// The server only supports full-document sync, so each change carries the whole text
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DidChangeTextDocumentParams {
+    #[serde(rename = "textDocument")]
+    pub text_document: TextDocumentIdentifier,
+    #[serde(rename = "contentChanges")]
+    pub content_changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DidCloseTextDocumentParams {
+    #[serde(rename = "textDocument")]
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TextDocumentPositionParams {
+    #[serde(rename = "textDocument")]
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_message_framing() {
+        let mut output = vec![];
+        write_message(&mut output, &json!({"jsonrpc": "2.0", "method": "exit"})).unwrap();
+        write_message(
+            &mut output,
+            &json!({"jsonrpc": "2.0", "id": 1, "result": null}),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "Content-Length: 33\r\n\r\n{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}\
+             Content-Length: 38\r\n\r\n{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":null}"
+        );
+
+        let mut input = Cursor::new(output);
+        let first = read_message(&mut input).unwrap().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&first).unwrap(),
+            json!({"jsonrpc": "2.0", "method": "exit"})
+        );
+        let second = read_message(&mut input).unwrap().unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&second).unwrap()["id"],
+            json!(1)
+        );
+        assert_eq!(read_message(&mut input).unwrap(), None);
+
+        // headers other than Content-Length are skipped, and header names are case-insensitive
+        let mut input = Cursor::new(
+            "content-length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}"
+                .as_bytes()
+                .to_vec(),
+        );
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), b"{}".to_vec());
+
+        let mut input = Cursor::new("Content-Type: text/plain\r\n\r\n{}".as_bytes().to_vec());
+        assert!(read_message(&mut input).is_err());
+    }
+
+    #[test]
+    fn test_span_conversion() {
+        let span = Span {
+            start_line: 2,
+            start_column: 5,
+            end_line: 3,
+            end_column: 9,
+        };
+        let text = "(define-data-var a int 1)\n(ok 1)\n(ok (+ 1 2))";
+        assert_eq!(
+            Range::from_span(&span, text),
+            Range {
+                start: Position {
+                    line: 1,
+                    character: 4
+                },
+                end: Position {
+                    line: 2,
+                    character: 9
+                },
+            }
+        );
+        assert_eq!(Range::from_span(&Span::zero(), text), Range::empty());
+
+        // the parser reports unclosed lists without an end
+        let span = Span {
+            start_line: 4,
+            start_column: 1,
+            end_line: 0,
+            end_column: 0,
+        };
+        let text = "\n\n\n(list 1";
+        let range = Range::from_span(&span, text);
+        assert_eq!(range.start, range.end);
+        assert_eq!(range.start.to_line_column(text), (4, 1));
+    }
+
+    #[test]
+    fn test_span_conversion_utf16() {
+        // "é" is two bytes and one UTF-16 code unit, "🎶" is four bytes and two code units
+        let text = ";; é\n(ok u\"🎶\") (ok é)";
+        let span = Span {
+            start_line: 2,
+            start_column: 15,
+            end_line: 2,
+            end_column: 16,
+        };
+        let range = Range::from_span(&span, text);
+        assert_eq!(
+            range,
+            Range {
+                start: Position {
+                    line: 1,
+                    character: 12
+                },
+                end: Position {
+                    line: 1,
+                    character: 14
+                },
+            }
+        );
+        assert_eq!(range.start.to_line_column(text), (2, 15));
+        assert_eq!(
+            Position {
+                line: 0,
+                character: 3
+            }
+            .to_line_column(text),
+            (1, 4)
+        );
+        // positions past the end of a line clamp to the end of the line
+        assert_eq!(
+            Position {
+                line: 0,
+                character: 10
+            }
+            .to_line_column(text),
+            (1, 6)
+        );
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::ptr;
+
+use vm::representations::{ClarityName, PreSymbolicExpression, PreSymbolicExpressionType, Span};
+use vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use vm::DefineFunctions;
+
+/// A name defined by a top-level `define-*` or `use-trait` form
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: ClarityName,
+    pub define_type: DefineFunctions,
+    /// Where the name appears in its definition
+    pub span: Span,
+}
+
+/// What an expression refers to in another contract
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContractReference {
+    /// A contract principal
+    Contract(QualifiedContractIdentifier),
+    /// A trait defined by a contract
+    Trait(QualifiedContractIdentifier, ClarityName),
+    /// A public or read-only function called with `contract-call?`
+    Function(QualifiedContractIdentifier, ClarityName),
+}
+
+/// Find the names defined at the top level of a contract
+pub fn find_definitions(pre_expressions: &[PreSymbolicExpression]) -> Vec<Definition> {
+    let mut definitions = vec![];
+    for expr in pre_expressions.iter() {
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => continue,
+        };
+        let define_type = match list
+            .first()
+            .and_then(|keyword| keyword.match_atom())
+            .and_then(|keyword| DefineFunctions::lookup_by_name(keyword))
+        {
+            Some(define_type) => define_type,
+            None => continue,
+        };
+        let name_expr = match define_type {
+            DefineFunctions::PublicFunction
+            | DefineFunctions::PrivateFunction
+            | DefineFunctions::ReadOnlyFunction => list
+                .get(1)
+                .and_then(|signature| signature.match_list())
+                .and_then(|signature| signature.first()),
+            DefineFunctions::ImplTrait => None,
+            _ => list.get(1),
+        };
+        if let Some(name_expr) = name_expr {
+            if let Some(name) = name_expr.match_atom() {
+                definitions.push(Definition {
+                    name: name.clone(),
+                    define_type,
+                    span: name_expr.span().cloned().unwrap_or_else(Span::zero),
+                });
+            }
+        }
+    }
+    definitions
+}
+
+fn span_contains(span: Option<&Span>, line: u32, column: u32) -> bool {
+    match span {
+        Some(span) => {
+            (span.start_line, span.start_column) <= (line, column)
+                && (line, column) <= (span.end_line, span.end_column)
+        }
+        None => false,
+    }
+}
+
+/// Find the expressions that contain the given line and column (counting from 1), outermost
+/// first
+pub fn find_expressions_at(
+    pre_expressions: &[PreSymbolicExpression],
+    line: u32,
+    column: u32,
+) -> Vec<&PreSymbolicExpression> {
+    let mut path = vec![];
+    let mut exprs = pre_expressions;
+    while let Some(expr) = exprs
+        .iter()
+        .find(|expr| span_contains(expr.span(), line, column))
+    {
+        path.push(expr);
+        exprs = match expr.pre_expr {
+            PreSymbolicExpressionType::List(ref children)
+            | PreSymbolicExpressionType::Tuple(ref children) => &children[..],
+            _ => break,
+        };
+    }
+    path
+}
+
+/// Get the contract an expression names, if it is a contract principal. Sugared principals
+/// (`.contract`) are issued by `contract_identifier`'s issuer.
+fn match_contract(
+    expr: &PreSymbolicExpression,
+    contract_identifier: &QualifiedContractIdentifier,
+) -> Option<QualifiedContractIdentifier> {
+    match expr.pre_expr {
+        PreSymbolicExpressionType::SugaredContractIdentifier(ref name) => Some(
+            QualifiedContractIdentifier::new(contract_identifier.issuer.clone(), name.clone()),
+        ),
+        PreSymbolicExpressionType::AtomValue(Value::Principal(PrincipalData::Contract(ref id))) => {
+            Some(id.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Get what the innermost expression of `path` refers to in another contract, if anything
+pub fn find_contract_reference(
+    path: &[&PreSymbolicExpression],
+    contract_identifier: &QualifiedContractIdentifier,
+) -> Option<ContractReference> {
+    let expr = path.last()?;
+    match expr.pre_expr {
+        PreSymbolicExpressionType::SugaredFieldIdentifier(ref contract_name, ref name) => {
+            Some(ContractReference::Trait(
+                QualifiedContractIdentifier::new(
+                    contract_identifier.issuer.clone(),
+                    contract_name.clone(),
+                ),
+                name.clone(),
+            ))
+        }
+        PreSymbolicExpressionType::FieldIdentifier(ref trait_identifier) => {
+            Some(ContractReference::Trait(
+                trait_identifier.contract_identifier.clone(),
+                trait_identifier.name.clone(),
+            ))
+        }
+        PreSymbolicExpressionType::Atom(ref name) if path.len() >= 2 => {
+            // (contract-call? contract function-name ...)
+            let call = path[path.len() - 2].match_list()?;
+            let is_function_name = call.len() >= 3
+                && call[0].match_atom().map(|keyword| keyword.as_str()) == Some("contract-call?")
+                && ptr::eq(&call[2], *expr);
+            if !is_function_name {
+                return None;
+            }
+            let callee = match_contract(&call[1], contract_identifier)?;
+            Some(ContractReference::Function(callee, name.clone()))
+        }
+        _ => match_contract(expr, contract_identifier).map(ContractReference::Contract),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use vm::ast::parser;
+
+    #[test]
+    fn test_find_definitions() {
+        let pre_expressions = parser::parse(
+            "(use-trait token-trait .defs.token-trait)
+(impl-trait .defs.other-trait)
+(define-constant owner tx-sender)
+(define-map balances principal uint)
+(define-public (transfer (amount uint))
+  (ok amount))
+(+ 1 2)",
+        )
+        .unwrap();
+        let definitions: Vec<_> = find_definitions(&pre_expressions)
+            .into_iter()
+            .map(|definition| {
+                (
+                    definition.name.to_string(),
+                    definition.define_type,
+                    definition.span.start_line,
+                    definition.span.start_column,
+                    definition.span.end_column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            definitions,
+            vec![
+                ("token-trait".into(), DefineFunctions::UseTrait, 1, 12, 22),
+                ("owner".into(), DefineFunctions::Constant, 3, 18, 22),
+                ("balances".into(), DefineFunctions::Map, 4, 13, 20),
+                (
+                    "transfer".into(),
+                    DefineFunctions::PublicFunction,
+                    5,
+                    17,
+                    24
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_contract_reference() {
+        let contract_identifier = QualifiedContractIdentifier::local("caller").unwrap();
+        let source = "(impl-trait .defs.token-trait)
+(define-public (get-balance)
+  (contract-call? .token get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR.defs))";
+        let pre_expressions = parser::parse(source).unwrap();
+
+        let reference_at = |line, column| {
+            let path = find_expressions_at(&pre_expressions, line, column);
+            find_contract_reference(&path, &contract_identifier)
+        };
+        let defs = QualifiedContractIdentifier::local("defs").unwrap();
+        let token = QualifiedContractIdentifier::local("token").unwrap();
+
+        assert_eq!(
+            reference_at(1, 13),
+            Some(ContractReference::Trait(defs, "token-trait".into()))
+        );
+        assert_eq!(
+            reference_at(3, 20),
+            Some(ContractReference::Contract(token.clone()))
+        );
+        assert_eq!(
+            reference_at(3, 28),
+            Some(ContractReference::Function(token, "get-balance".into()))
+        );
+        assert_eq!(
+            reference_at(3, 40),
+            Some(ContractReference::Contract(
+                QualifiedContractIdentifier::parse(
+                    "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR.defs"
+                )
+                .unwrap()
+            ))
+        );
+        // the function being defined is not a reference to another contract
+        assert_eq!(reference_at(2, 18), None);
+        assert_eq!(reference_at(3, 4), None);
+    }
+}
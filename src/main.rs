@@ -617,6 +617,110 @@ simulating a miner.
         return;
     }
 
+    if argv[1] == "marf-compact" {
+        if argv.len() < 5 {
+            eprintln!("Usage: {} marf-compact MARF_PATH TIP DEPTH", argv[0]);
+            eprintln!("Prune the tries of the blocks more than DEPTH blocks below the index block hash TIP, and compact the MARF.");
+            eprintln!("No other process may have the MARF open.");
+            process::exit(1);
+        }
+        let marf_path = &argv[2];
+        if fs::metadata(marf_path).is_err() {
+            eprintln!("No such file or directory: {}", marf_path);
+            process::exit(1);
+        }
+        let tip = StacksBlockId::from_hex(&argv[3]).expect("Bad MARF block hash");
+        let depth: u32 = argv[4].parse().expect("Bad pruning depth");
+
+        let mut marf: MARF<StacksBlockId> =
+            MARF::from_path(marf_path).expect("Failed to open MARF");
+        let stats = match marf.prune(&tip, depth) {
+            Ok(stats) => stats,
+            Err(e) => {
+                eprintln!("Failed to prune MARF: {}", e);
+                process::exit(1);
+            }
+        };
+        marf.vacuum().expect("Failed to compact MARF");
+
+        println!("Horizon: {}", stats.horizon);
+        println!("Pruned tries: {}", stats.pruned_blocks);
+        println!(
+            "Pruned trie bytes: {} -> {}",
+            stats.bytes_before, stats.bytes_after
+        );
+        return;
+    }
+
+    if argv[1] == "marf-export" {
+        if argv.len() < 6 {
+            eprintln!(
+                "Usage: {} marf-export CHAINSTATE_DIR mainnet|testnet INDEX_BLOCK_HASH SNAPSHOT_PATH",
+                argv[0]
+            );
+            eprintln!("Write a snapshot of the chain state at a processed block, for marf-import to start a new node's chain state from.");
+            process::exit(1);
+        }
+        let chainstate_path = &argv[2];
+        let (mainnet, chain_id) = parse_network(&argv[3]);
+        let index_block_hash = StacksBlockId::from_hex(&argv[4]).expect("Bad index block hash");
+        let snapshot_path = &argv[5];
+
+        let (mut chainstate, _) = StacksChainState::open(mainnet, chain_id, chainstate_path)
+            .expect("Failed to open chainstate");
+
+        let mut file = io::BufWriter::new(
+            fs::File::create(snapshot_path).expect("Failed to create snapshot file"),
+        );
+        if let Err(e) = chainstate.export_chainstate_snapshot(&index_block_hash, &mut file) {
+            eprintln!("Failed to export snapshot: {:?}", e);
+            process::exit(1);
+        }
+        file.flush().expect("Failed to write snapshot file");
+        return;
+    }
+
+    if argv[1] == "marf-import" {
+        if argv.len() < 6 {
+            eprintln!(
+                "Usage: {} marf-import SNAPSHOT_PATH CHAINSTATE_DIR mainnet|testnet INDEX_BLOCK_HASH",
+                argv[0]
+            );
+            eprintln!("Create the chain state of a new node from a snapshot of the chain state at INDEX_BLOCK_HASH.");
+            eprintln!("The snapshot's state must hash to the roots in the block's header, but contract metadata, ancestors' burnchain data, and miner payments are taken as they are, so the snapshot should come from a trusted node.");
+            process::exit(1);
+        }
+        let snapshot_path = &argv[2];
+        let chainstate_path = &argv[3];
+        let (mainnet, chain_id) = parse_network(&argv[4]);
+        let index_block_hash = StacksBlockId::from_hex(&argv[5]).expect("Bad index block hash");
+
+        let mut file = io::BufReader::new(
+            fs::File::open(snapshot_path).expect("Failed to open snapshot file"),
+        );
+        match StacksChainState::import_chainstate_snapshot(
+            mainnet,
+            chain_id,
+            chainstate_path,
+            &index_block_hash,
+            &mut file,
+        ) {
+            Ok(header_info) => {
+                println!("Block: {}", index_block_hash);
+                println!("Height: {}", header_info.block_height);
+                println!(
+                    "State root: {}",
+                    header_info.anchored_header.state_index_root
+                );
+            }
+            Err(e) => {
+                eprintln!("Failed to import snapshot: {:?}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     if argv[1] == "get-ancestors" {
         let path = &argv[2];
         let tip = BlockHeaderHash::from_hex(&argv[3]).unwrap();
@@ -999,3 +1103,15 @@ simulating a miner.
         process::exit(1);
     }
 }
+
+/// Parse a "mainnet" or "testnet" argument into the network's mainnet flag and chain ID
+fn parse_network(network: &str) -> (bool, u32) {
+    match network {
+        "mainnet" => (true, core::CHAIN_ID_MAINNET),
+        "testnet" => (false, core::CHAIN_ID_TESTNET),
+        _ => {
+            eprintln!("Unknown network '{}': expected mainnet or testnet", network);
+            process::exit(1);
+        }
+    }
+}
@@ -335,6 +335,7 @@ pub struct ConnectionOptions {
     pub soft_max_clients_per_host: u64,
     pub max_neighbors_of_neighbor: u64,
     pub max_http_clients: u64,
+    pub max_subscribers: u64,
     pub neighbor_request_timeout: u64,
     pub num_initial_walks: u64,
     pub walk_retry_count: u64,
@@ -407,6 +408,7 @@ impl std::default::Default for ConnectionOptions {
             soft_max_clients_per_host: 10, // how many inbound connections we can have per IP address, before we start pruning them,
             max_neighbors_of_neighbor: 10,
             max_http_clients: 10,
+            max_subscribers: 5, // how many HTTP connections can hold event subscriptions at once
             neighbor_request_timeout: NEIGHBOR_REQUEST_TIMEOUT, // how long to wait for a neighbor request
             num_initial_walks: NUM_INITIAL_WALKS,
             walk_retry_count: WALK_RETRY_COUNT,
@@ -33,13 +33,17 @@ use serde_json;
 use burnchains::{Address, Txid};
 use chainstate::burn::BlockHeaderHash;
 use chainstate::stacks::{
-    StacksAddress, StacksBlock, StacksBlockId, StacksMicroblock, StacksPublicKey, StacksTransaction,
+    StacksAddress, StacksBlock, StacksBlockHeader, StacksBlockId, StacksMicroblock,
+    StacksPublicKey, StacksTransaction, TransactionPayload,
 };
 use net::atlas::Attachment;
 use net::codec::{read_next, write_next};
+use net::subscriptions::{SubscriptionFilter, SubscriptionTopic};
 use net::CallReadOnlyRequestBody;
 use net::ClientError;
+use net::ConfirmedTransactionResponse;
 use net::Error as net_error;
+use net::FeeRateEstimateRequestBody;
 use net::HttpContentType;
 use net::HttpRequestMetadata;
 use net::HttpRequestPreamble;
@@ -49,10 +53,15 @@ use net::HttpResponsePreamble;
 use net::HttpResponseType;
 use net::HttpVersion;
 use net::MessageSequence;
+use net::MultiProofKey;
+use net::MultiProofRequestBody;
 use net::NeighborAddress;
 use net::PeerAddress;
 use net::PeerHost;
 use net::ProtocolFamily;
+use net::RPCBlockHeaderResponse;
+use net::RPCDataVarKey;
+use net::RPCMapEntryKey;
 use net::StacksHttpMessage;
 use net::StacksHttpPreamble;
 use net::StacksMessageCodec;
@@ -63,7 +72,7 @@ use net::HTTP_PREAMBLE_MAX_NUM_HEADERS;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_MICROBLOCKS_UNCONFIRMED;
 use net::{GetAttachmentResponse, GetAttachmentsInvResponse, PostTransactionRequestBody};
-use net::{MAX_MESSAGE_LEN, MAX_PAYLOAD_LEN};
+use net::{MAX_MESSAGE_LEN, MAX_PAYLOAD_LEN, MULTI_PROOF_MAX_KEYS};
 
 use util::hash::hex_bytes;
 use util::hash::to_hex;
@@ -76,7 +85,7 @@ use vm::{
     ast::parser::{
         CLARITY_NAME_REGEX, CONTRACT_NAME_REGEX, PRINCIPAL_DATA_REGEX, STANDARD_PRINCIPAL_REGEX,
     },
-    types::{PrincipalData, BOUND_VALUE_SERIALIZATION_HEX},
+    types::{PrincipalData, QualifiedContractIdentifier, BOUND_VALUE_SERIALIZATION_HEX},
     ClarityName, ContractName, Value,
 };
 
@@ -92,12 +101,17 @@ use std::time::SystemTime;
 use time;
 
 use chainstate::burn::ConsensusHash;
+use chainstate::stacks::db::event_index::{EventCursor, EVENT_INDEX_DEFAULT_PAGE_SIZE};
 
 lazy_static! {
     static ref PATH_GETINFO: Regex = Regex::new(r#"^/v2/info$"#).unwrap();
     static ref PATH_GETPOXINFO: Regex = Regex::new(r#"^/v2/pox$"#).unwrap();
     static ref PATH_GETNEIGHBORS: Regex = Regex::new(r#"^/v2/neighbors$"#).unwrap();
     static ref PATH_GETBLOCK: Regex = Regex::new(r#"^/v2/blocks/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETBLOCK_BY_HEIGHT: Regex =
+        Regex::new(r#"^/v2/blocks/height/([0-9]{1,20})$"#).unwrap();
+    static ref PATH_GETHEADER_BY_HEIGHT: Regex =
+        Regex::new(r#"^/v2/headers/([0-9]{1,20})$"#).unwrap();
     static ref PATH_GETMICROBLOCKS_INDEXED: Regex =
         Regex::new(r#"^/v2/microblocks/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_GETMICROBLOCKS_CONFIRMED: Regex =
@@ -106,7 +120,11 @@ lazy_static! {
         Regex::new(r#"^/v2/microblocks/unconfirmed/([0-9a-f]{64})/([0-9]{1,5})$"#).unwrap();
     static ref PATH_GETTRANSACTION_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/transactions/unconfirmed/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETTRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_POSTTRANSACTION: Regex = Regex::new(r#"^/v2/transactions$"#).unwrap();
+    static ref PATH_POST_SIMULATE_TRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/simulate$"#).unwrap();
     static ref PATH_POSTBLOCK: Regex = Regex::new(r#"^/v2/blocks/upload/([0-9a-f]{40})$"#).unwrap();
     static ref PATH_POSTMICROBLOCK: Regex = Regex::new(r#"^/v2/microblocks$"#).unwrap();
     static ref PATH_GET_ACCOUNT: Regex = Regex::new(&format!(
@@ -119,6 +137,7 @@ lazy_static! {
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_POST_MULTI_PROOF: Regex = Regex::new("^/v2/proofs$").unwrap();
     static ref PATH_POST_CALL_READ_ONLY: Regex = Regex::new(&format!(
         "^/v2/contracts/call-read/(?P<address>{})/(?P<contract>{})/(?P<function>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
@@ -134,7 +153,14 @@ lazy_static! {
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_CONTRACT_EVENTS: Regex = Regex::new(&format!(
+        "^/v2/contracts/events/(?P<address>{})/(?P<contract>{})$",
+        *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX
+    ))
+    .unwrap();
+    static ref PATH_SUBSCRIBE: Regex = Regex::new("^/v2/subscribe$").unwrap();
     static ref PATH_GET_TRANSFER_COST: Regex = Regex::new("^/v2/fees/transfer$").unwrap();
+    static ref PATH_POST_FEE_RATE_ESTIMATE: Regex = Regex::new("^/v2/fees/transaction$").unwrap();
     static ref PATH_GET_ATTACHMENTS_INV: Regex = Regex::new("^/v2/attachments/inv$").unwrap();
     static ref PATH_GET_ATTACHMENT: Regex =
         Regex::new(r#"^/v2/attachments/([0-9a-f]{40})$"#).unwrap();
@@ -620,6 +646,14 @@ impl<'a, 'state, W: Write> HttpChunkedTransferWriter<'a, 'state, W> {
     pub fn corked(&self) -> bool {
         self.state.corked
     }
+
+    /// Send out any buffered chunk data, but without ending the stream (unlike flush())
+    pub fn flush_buffered(&mut self) -> io::Result<()> {
+        if self.state.chunk_buf.len() > 0 && !self.state.corked {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
 }
 
 impl<'a, 'state, W: Write> Write for HttpChunkedTransferWriter<'a, 'state, W> {
@@ -1452,6 +1486,16 @@ impl HttpRequestType {
                 &HttpRequestType::parse_getneighbors,
             ),
             ("GET", &PATH_GETBLOCK, &HttpRequestType::parse_getblock),
+            (
+                "GET",
+                &PATH_GETBLOCK_BY_HEIGHT,
+                &HttpRequestType::parse_getblock_by_height,
+            ),
+            (
+                "GET",
+                &PATH_GETHEADER_BY_HEIGHT,
+                &HttpRequestType::parse_getheader_by_height,
+            ),
             (
                 "GET",
                 &PATH_GETMICROBLOCKS_INDEXED,
@@ -1472,11 +1516,21 @@ impl HttpRequestType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpRequestType::parse_gettransaction_unconfirmed,
             ),
+            (
+                "GET",
+                &PATH_GETTRANSACTION,
+                &HttpRequestType::parse_gettransaction,
+            ),
             (
                 "POST",
                 &PATH_POSTTRANSACTION,
                 &HttpRequestType::parse_posttransaction,
             ),
+            (
+                "POST",
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpRequestType::parse_post_simulate_transaction,
+            ),
             ("POST", &PATH_POSTBLOCK, &HttpRequestType::parse_postblock),
             (
                 "POST",
@@ -1493,11 +1547,21 @@ impl HttpRequestType {
                 &PATH_GET_MAP_ENTRY,
                 &HttpRequestType::parse_get_map_entry,
             ),
+            (
+                "POST",
+                &PATH_POST_MULTI_PROOF,
+                &HttpRequestType::parse_post_multi_proof,
+            ),
             (
                 "GET",
                 &PATH_GET_TRANSFER_COST,
                 &HttpRequestType::parse_get_transfer_cost,
             ),
+            (
+                "POST",
+                &PATH_POST_FEE_RATE_ESTIMATE,
+                &HttpRequestType::parse_post_fee_rate_estimate,
+            ),
             (
                 "GET",
                 &PATH_GET_CONTRACT_SRC,
@@ -1508,11 +1572,17 @@ impl HttpRequestType {
                 &PATH_GET_CONTRACT_ABI,
                 &HttpRequestType::parse_get_contract_abi,
             ),
+            (
+                "GET",
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpRequestType::parse_get_contract_events,
+            ),
             (
                 "POST",
                 &PATH_POST_CALL_READ_ONLY,
                 &HttpRequestType::parse_call_read_only,
             ),
+            ("GET", &PATH_SUBSCRIBE, &HttpRequestType::parse_subscribe),
             (
                 "OPTIONS",
                 &PATH_OPTIONS_WILDCARD,
@@ -1646,6 +1716,49 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_post_fee_rate_estimate<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostFeeRateEstimate ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let mut bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: FeeRateEstimateRequestBody = serde_json::from_reader(&mut bound_fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        let payload_bytes = hex_bytes(&body.transaction_payload).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse transaction payload".into())
+        })?;
+        let payload =
+            TransactionPayload::consensus_deserialize(&mut &payload_bytes[..]).map_err(|e| {
+                net_error::ClientError(ClientError::Message(format!(
+                    "Failed to deserialize transaction payload: {:?}",
+                    e
+                )))
+            })?;
+
+        Ok(HttpRequestType::PostFeeRateEstimate(
+            HttpRequestMetadata::from_preamble(preamble),
+            payload,
+            body.estimated_len,
+        ))
+    }
+
     /// check whether the given option query string
     ///   sets proof=0 (setting proof to false).
     /// Defaults to _true_
@@ -1761,6 +1874,76 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_post_multi_proof<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostMultiProof ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let mut bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: MultiProofRequestBody = serde_json::from_reader(&mut bound_fd)
+            .map_err(|_e| net_error::DeserializeError("Failed to parse JSON body".into()))?;
+
+        let mut keys = vec![];
+        for account in body.accounts.iter() {
+            let principal = PrincipalData::parse(account).map_err(|_e| {
+                net_error::DeserializeError("Failed to parse account principal".into())
+            })?;
+            keys.push(MultiProofKey::Account(principal));
+        }
+        for data_var in body.data_vars.into_iter() {
+            let contract =
+                QualifiedContractIdentifier::parse(&data_var.contract).map_err(|_e| {
+                    net_error::DeserializeError("Failed to parse contract identifier".into())
+                })?;
+            let var_name = ClarityName::try_from(data_var.var)
+                .map_err(|_e| net_error::DeserializeError("Failed to parse var name".into()))?;
+            keys.push(MultiProofKey::DataVar(contract, var_name));
+        }
+        for map_entry in body.map_entries.into_iter() {
+            let contract =
+                QualifiedContractIdentifier::parse(&map_entry.contract).map_err(|_e| {
+                    net_error::DeserializeError("Failed to parse contract identifier".into())
+                })?;
+            let map_name = ClarityName::try_from(map_entry.map)
+                .map_err(|_e| net_error::DeserializeError("Failed to parse map name".into()))?;
+            let key = Value::try_deserialize_hex_untyped(&map_entry.key).map_err(|_e| {
+                net_error::DeserializeError("Failed to deserialize key value".into())
+            })?;
+            keys.push(MultiProofKey::MapEntry(contract, map_name, key));
+        }
+
+        if keys.len() == 0 || keys.len() > MULTI_PROOF_MAX_KEYS {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: expected between 1 and {} items to prove",
+                MULTI_PROOF_MAX_KEYS
+            )));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::PostMultiProof(
+            HttpRequestMetadata::from_preamble(preamble),
+            keys,
+            tip,
+        ))
+    }
+
     fn parse_call_read_only<R: Read>(
         protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1854,6 +2037,94 @@ impl HttpRequestType {
         )
     }
 
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        let (md, contract_addr, contract_name) =
+            HttpRequestType::parse_get_contract_arguments(preamble, captures)?;
+
+        let mut event_type = None;
+        let mut principal = None;
+        let mut cursor = None;
+        let mut limit = EVENT_INDEX_DEFAULT_PAGE_SIZE;
+
+        if let Some(query_string) = query {
+            for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                if key == "event_type" {
+                    event_type = Some(value.to_string());
+                } else if key == "principal" {
+                    principal = Some(PrincipalData::parse(&value).map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse principal".into())
+                    })?);
+                } else if key == "cursor" {
+                    cursor = Some(EventCursor::parse(&value).ok_or_else(|| {
+                        net_error::DeserializeError("Failed to parse cursor".into())
+                    })?);
+                } else if key == "limit" {
+                    limit = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse limit".into())
+                    })?;
+                }
+            }
+        }
+
+        Ok(HttpRequestType::GetContractEvents(
+            md,
+            contract_addr,
+            contract_name,
+            event_type,
+            principal,
+            cursor,
+            limit,
+            tip,
+        ))
+    }
+
+    fn parse_subscribe<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for Subscribe".to_string(),
+            ));
+        }
+
+        let mut filter = SubscriptionFilter::default();
+        if let Some(query_string) = query {
+            for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                if key == "topics" {
+                    filter.topics = value
+                        .split(',')
+                        .map(|topic| topic.parse::<SubscriptionTopic>())
+                        .collect::<Result<Vec<_>, _>>()?;
+                } else if key == "contract" {
+                    filter.contract =
+                        Some(QualifiedContractIdentifier::parse(&value).map_err(|_e| {
+                            net_error::DeserializeError("Failed to parse contract".into())
+                        })?);
+                } else if key == "principal" {
+                    filter.principal = Some(PrincipalData::parse(&value).map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse principal".into())
+                    })?);
+                }
+            }
+        }
+
+        Ok(HttpRequestType::Subscribe(
+            HttpRequestMetadata::from_preamble(preamble),
+            filter,
+        ))
+    }
+
     fn parse_get_contract_source<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1899,6 +2170,66 @@ impl HttpRequestType {
         ))
     }
 
+    /// Parse the height out of a request for a block or header at a given height.
+    fn get_block_height_capture(captures: &Captures) -> Result<u64, net_error> {
+        let height_str = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to block height group".to_string(),
+            ))?
+            .as_str();
+
+        height_str
+            .parse::<u64>()
+            .map_err(|_e| net_error::DeserializeError("Failed to parse block height".to_string()))
+    }
+
+    fn parse_getblock_by_height<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetBlockByHeight".to_string(),
+            ));
+        }
+
+        let height = HttpRequestType::get_block_height_capture(captures)?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetBlockByHeight(
+            HttpRequestMetadata::from_preamble(preamble),
+            height,
+            tip,
+        ))
+    }
+
+    fn parse_getheader_by_height<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetHeaderByHeight".to_string(),
+            ));
+        }
+
+        let height = HttpRequestType::get_block_height_capture(captures)?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetHeaderByHeight(
+            HttpRequestMetadata::from_preamble(preamble),
+            height,
+            tip,
+        ))
+    }
+
     fn parse_getmicroblocks_indexed<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2039,6 +2370,37 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_gettransaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetTransaction".to_string(),
+            ));
+        }
+
+        let txid_hex = regex
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to txid group".to_string(),
+            ))?
+            .as_str();
+
+        let txid = Txid::from_hex(&txid_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to decode txid hex".to_string()))?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            txid,
+            tip,
+        ))
+    }
+
     fn parse_posttransaction<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2248,6 +2610,64 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_post_simulate_transaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected non-zero-length body for PostSimulateTransaction"
+                    .to_string(),
+            ));
+        }
+
+        if preamble.get_content_length() > MAX_PAYLOAD_LEN {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: PostSimulateTransaction body is too big".to_string(),
+            ));
+        }
+
+        // content-type must be given, and must be application/octet-stream
+        match preamble.content_type {
+            None => {
+                return Err(net_error::DeserializeError(
+                    "Missing Content-Type for transaction".to_string(),
+                ));
+            }
+            Some(ref c) => {
+                if *c != HttpContentType::Bytes {
+                    return Err(net_error::DeserializeError(
+                        "Wrong Content-Type for transaction; expected application/octet-stream"
+                            .to_string(),
+                    ));
+                }
+            }
+        };
+
+        let mut bound_fd = BoundReader::from_reader(fd, preamble.get_content_length() as u64);
+
+        let tx = StacksTransaction::consensus_deserialize(&mut bound_fd).map_err(|e| {
+            if let net_error::DeserializeError(msg) = e {
+                net_error::ClientError(ClientError::Message(format!(
+                    "Failed to deserialize posted transaction: {}",
+                    msg
+                )))
+            } else {
+                e
+            }
+        })?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::PostSimulateTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            tx,
+            tip,
+        ))
+    }
+
     fn parse_get_attachment<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2337,17 +2757,25 @@ impl HttpRequestType {
             HttpRequestType::GetPoxInfo(ref md, _) => md,
             HttpRequestType::GetNeighbors(ref md) => md,
             HttpRequestType::GetBlock(ref md, _) => md,
+            HttpRequestType::GetBlockByHeight(ref md, ..) => md,
+            HttpRequestType::GetHeaderByHeight(ref md, ..) => md,
             HttpRequestType::GetMicroblocksIndexed(ref md, _) => md,
             HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
+            HttpRequestType::GetTransaction(ref md, ..) => md,
             HttpRequestType::PostTransaction(ref md, _, _) => md,
             HttpRequestType::PostBlock(ref md, ..) => md,
             HttpRequestType::PostMicroblock(ref md, ..) => md,
+            HttpRequestType::PostSimulateTransaction(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
             HttpRequestType::GetMapEntry(ref md, ..) => md,
+            HttpRequestType::PostMultiProof(ref md, ..) => md,
             HttpRequestType::GetTransferCost(ref md) => md,
+            HttpRequestType::PostFeeRateEstimate(ref md, ..) => md,
             HttpRequestType::GetContractABI(ref md, ..) => md,
+            HttpRequestType::GetContractEvents(ref md, ..) => md,
+            HttpRequestType::Subscribe(ref md, _) => md,
             HttpRequestType::GetContractSrc(ref md, ..) => md,
             HttpRequestType::CallReadOnlyFunction(ref md, ..) => md,
             HttpRequestType::OptionsPreflight(ref md, ..) => md,
@@ -2363,17 +2791,25 @@ impl HttpRequestType {
             HttpRequestType::GetPoxInfo(ref mut md, _) => md,
             HttpRequestType::GetNeighbors(ref mut md) => md,
             HttpRequestType::GetBlock(ref mut md, _) => md,
+            HttpRequestType::GetBlockByHeight(ref mut md, ..) => md,
+            HttpRequestType::GetHeaderByHeight(ref mut md, ..) => md,
             HttpRequestType::GetMicroblocksIndexed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
+            HttpRequestType::GetTransaction(ref mut md, ..) => md,
             HttpRequestType::PostTransaction(ref mut md, _, _) => md,
             HttpRequestType::PostBlock(ref mut md, ..) => md,
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
+            HttpRequestType::PostSimulateTransaction(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
             HttpRequestType::GetMapEntry(ref mut md, ..) => md,
+            HttpRequestType::PostMultiProof(ref mut md, ..) => md,
             HttpRequestType::GetTransferCost(ref mut md) => md,
+            HttpRequestType::PostFeeRateEstimate(ref mut md, ..) => md,
             HttpRequestType::GetContractABI(ref mut md, ..) => md,
+            HttpRequestType::GetContractEvents(ref mut md, ..) => md,
+            HttpRequestType::Subscribe(ref mut md, _) => md,
             HttpRequestType::GetContractSrc(ref mut md, ..) => md,
             HttpRequestType::CallReadOnlyFunction(ref mut md, ..) => md,
             HttpRequestType::OptionsPreflight(ref mut md, ..) => md,
@@ -2404,6 +2840,16 @@ impl HttpRequestType {
             HttpRequestType::GetBlock(_md, block_hash) => {
                 format!("/v2/blocks/{}", block_hash.to_hex())
             }
+            HttpRequestType::GetBlockByHeight(_md, height, tip_opt) => format!(
+                "/v2/blocks/height/{}{}",
+                height,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
+            HttpRequestType::GetHeaderByHeight(_md, height, tip_opt) => format!(
+                "/v2/headers/{}{}",
+                height,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetMicroblocksIndexed(_md, block_hash) => {
                 format!("/v2/microblocks/{}", block_hash.to_hex())
             }
@@ -2418,12 +2864,21 @@ impl HttpRequestType {
             HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
                 format!("/v2/transactions/unconfirmed/{}", txid)
             }
+            HttpRequestType::GetTransaction(_md, txid, tip_opt) => format!(
+                "/v2/transactions/{}{}",
+                txid,
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
             HttpRequestType::PostBlock(_md, ch, ..) => format!("/v2/blocks/upload/{}", &ch),
             HttpRequestType::PostMicroblock(_md, _, tip_opt) => format!(
                 "/v2/microblocks{}",
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::PostSimulateTransaction(_md, _, tip_opt) => format!(
+                "/v2/transactions/simulate{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetAccount(_md, principal, tip_opt, with_proof) => format!(
                 "/v2/accounts/{}{}",
                 &principal.to_string(),
@@ -2444,13 +2899,61 @@ impl HttpRequestType {
                 map_name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), *with_proof)
             ),
+            HttpRequestType::PostMultiProof(_md, _, tip_opt) => format!(
+                "/v2/proofs{}",
+                HttpRequestType::make_query_string(tip_opt.as_ref(), true)
+            ),
             HttpRequestType::GetTransferCost(_md) => "/v2/fees/transfer".into(),
+            HttpRequestType::PostFeeRateEstimate(_md, ..) => "/v2/fees/transaction".into(),
             HttpRequestType::GetContractABI(_, contract_addr, contract_name, tip_opt) => format!(
                 "/v2/contracts/interface/{}/{}{}",
                 contract_addr,
                 contract_name.as_str(),
                 HttpRequestType::make_query_string(tip_opt.as_ref(), true)
             ),
+            HttpRequestType::GetContractEvents(
+                _,
+                contract_addr,
+                contract_name,
+                event_type_opt,
+                principal_opt,
+                cursor_opt,
+                limit,
+                tip_opt,
+            ) => {
+                let mut query = form_urlencoded::Serializer::new(String::new());
+                if let Some(cursor) = cursor_opt {
+                    query.append_pair("cursor", &cursor.to_string());
+                }
+                query.append_pair("limit", &limit.to_string());
+                if let Some(event_type) = event_type_opt {
+                    query.append_pair("event_type", event_type);
+                }
+                if let Some(principal) = principal_opt {
+                    query.append_pair("principal", &principal.to_string());
+                }
+                if let Some(tip) = tip_opt {
+                    query.append_pair("tip", &tip.to_string());
+                }
+                format!(
+                    "/v2/contracts/events/{}/{}?{}",
+                    contract_addr,
+                    contract_name.as_str(),
+                    query.finish()
+                )
+            }
+            HttpRequestType::Subscribe(_, filter) => {
+                let topics: Vec<_> = filter.topics.iter().map(|topic| topic.as_str()).collect();
+                let mut query = form_urlencoded::Serializer::new(String::new());
+                query.append_pair("topics", &topics.join(","));
+                if let Some(ref contract) = filter.contract {
+                    query.append_pair("contract", &contract.to_string());
+                }
+                if let Some(ref principal) = filter.principal {
+                    query.append_pair("principal", &principal.to_string());
+                }
+                format!("/v2/subscribe?{}", query.finish())
+            }
             HttpRequestType::GetContractSrc(
                 _,
                 contract_addr,
@@ -2593,6 +3096,23 @@ impl HttpRequestType {
                 )?;
                 fd.write_all(&mb_bytes).map_err(net_error::WriteError)?;
             }
+            HttpRequestType::PostSimulateTransaction(md, tx, ..) => {
+                let mut tx_bytes = vec![];
+                write_next(&mut tx_bytes, tx)?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(tx_bytes.len() as u32),
+                    Some(&HttpContentType::Bytes),
+                    empty_headers,
+                )?;
+                fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
+            }
             HttpRequestType::GetMapEntry(
                 md,
                 _contract_addr,
@@ -2664,6 +3184,86 @@ impl HttpRequestType {
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
             }
+            HttpRequestType::PostMultiProof(md, keys, ..) => {
+                let mut request_body = MultiProofRequestBody {
+                    accounts: vec![],
+                    data_vars: vec![],
+                    map_entries: vec![],
+                };
+                for key in keys.iter() {
+                    match key {
+                        MultiProofKey::Account(principal) => {
+                            request_body.accounts.push(principal.to_string());
+                        }
+                        MultiProofKey::DataVar(contract, var_name) => {
+                            request_body.data_vars.push(RPCDataVarKey {
+                                contract: contract.to_string(),
+                                var: var_name.to_string(),
+                            });
+                        }
+                        MultiProofKey::MapEntry(contract, map_name, key) => {
+                            let mut key_bytes = vec![];
+                            key.serialize_write(&mut key_bytes)
+                                .map_err(net_error::WriteError)?;
+                            request_body.map_entries.push(RPCMapEntryKey {
+                                contract: contract.to_string(),
+                                map: map_name.to_string(),
+                                key: to_hex(&key_bytes),
+                            });
+                        }
+                    }
+                }
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize multi-proof request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostFeeRateEstimate(md, payload, estimated_len) => {
+                let request_body = FeeRateEstimateRequestBody {
+                    transaction_payload: to_hex(&payload.serialize_to_vec()),
+                    estimated_len: estimated_len.clone(),
+                };
+
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &request_body).map_err(|e| {
+                    net_error::SerializeError(format!(
+                        "Failed to serialize fee rate estimate request to JSON: {:?}",
+                        &e
+                    ))
+                })?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    empty_headers,
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
             other_type => {
                 let md = other_type.metadata();
                 let request_path = other_type.request_path();
@@ -2910,6 +3510,11 @@ impl HttpResponseType {
             (&PATH_GETPOXINFO, &HttpResponseType::parse_poxinfo),
             (&PATH_GETNEIGHBORS, &HttpResponseType::parse_neighbors),
             (&PATH_GETBLOCK, &HttpResponseType::parse_block),
+            (&PATH_GETBLOCK_BY_HEIGHT, &HttpResponseType::parse_block),
+            (
+                &PATH_GETHEADER_BY_HEIGHT,
+                &HttpResponseType::parse_block_header,
+            ),
             (&PATH_GET_MAP_ENTRY, &HttpResponseType::parse_get_map_entry),
             (
                 &PATH_GETMICROBLOCKS_INDEXED,
@@ -2927,7 +3532,15 @@ impl HttpResponseType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpResponseType::parse_transaction_unconfirmed,
             ),
+            (
+                &PATH_GETTRANSACTION,
+                &HttpResponseType::parse_transaction_confirmed,
+            ),
             (&PATH_POSTTRANSACTION, &HttpResponseType::parse_txid),
+            (
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpResponseType::parse_simulate_transaction,
+            ),
             (
                 &PATH_POSTBLOCK,
                 &HttpResponseType::parse_stacks_block_accepted,
@@ -2937,6 +3550,7 @@ impl HttpResponseType {
                 &HttpResponseType::parse_microblock_hash,
             ),
             (&PATH_GET_ACCOUNT, &HttpResponseType::parse_get_account),
+            (&PATH_POST_MULTI_PROOF, &HttpResponseType::parse_multi_proof),
             (
                 &PATH_GET_CONTRACT_SRC,
                 &HttpResponseType::parse_get_contract_src,
@@ -2945,10 +3559,18 @@ impl HttpResponseType {
                 &PATH_GET_CONTRACT_ABI,
                 &HttpResponseType::parse_get_contract_abi,
             ),
+            (
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpResponseType::parse_get_contract_events,
+            ),
             (
                 &PATH_POST_CALL_READ_ONLY,
                 &HttpResponseType::parse_call_read_only,
             ),
+            (
+                &PATH_POST_FEE_RATE_ESTIMATE,
+                &HttpResponseType::parse_fee_rate_estimate,
+            ),
             (
                 &PATH_GET_ATTACHMENT,
                 &HttpResponseType::parse_get_attachment,
@@ -3046,22 +3668,45 @@ impl HttpResponseType {
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
         Ok(HttpResponseType::Neighbors(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            neighbors_data,
+            neighbors_data,
+        ))
+    }
+
+    fn parse_block<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let block: StacksBlock =
+            HttpResponseType::parse_bytestream(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::Block(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            block,
         ))
     }
 
-    fn parse_block<R: Read>(
+    fn parse_block_header<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let block: StacksBlock =
-            HttpResponseType::parse_bytestream(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
-        Ok(HttpResponseType::Block(
+        let header_info: RPCBlockHeaderResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        // header must decode to an anchored block header
+        let header_bytes = hex_bytes(&header_info.header)
+            .map_err(|_| net_error::DeserializeError("Header is not hex-encoded".to_string()))?;
+        let _ = StacksBlockHeader::consensus_deserialize(&mut &header_bytes[..]).map_err(|_| {
+            net_error::DeserializeError("Header is not a well-formed block header".to_string())
+        })?;
+
+        Ok(HttpResponseType::BlockHeader(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            block,
+            header_info,
         ))
     }
 
@@ -3139,6 +3784,20 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let events = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::ContractEvents(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            events,
+        ))
+    }
+
     fn parse_call_read_only<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3154,6 +3813,51 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_multi_proof<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let multi_proof =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::MultiProof(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            multi_proof,
+        ))
+    }
+
+    fn parse_simulate_transaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let simulation =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::SimulateTransaction(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            simulation,
+        ))
+    }
+
+    fn parse_fee_rate_estimate<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let estimate =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::FeeRateEstimate(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            estimate,
+        ))
+    }
+
     fn parse_microblocks_unconfirmed<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3218,6 +3922,32 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_transaction_confirmed<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let confirmed: ConfirmedTransactionResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        // tx payload must decode to a transaction
+        let tx_bytes = hex_bytes(&confirmed.tx).map_err(|_| {
+            net_error::DeserializeError("Confirmed transaction is not hex-encoded".to_string())
+        })?;
+        let _ = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|_| {
+            net_error::DeserializeError(
+                "Confirmed transaction is not a well-formed Stacks transaction".to_string(),
+            )
+        })?;
+
+        Ok(HttpResponseType::ConfirmedTransaction(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            confirmed,
+        ))
+    }
+
     fn parse_txid<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3358,12 +4088,19 @@ impl HttpResponseType {
             HttpResponseType::StacksBlockAccepted(ref md, ..) => md,
             HttpResponseType::MicroblockHash(ref md, _) => md,
             HttpResponseType::TokenTransferCost(ref md, _) => md,
+            HttpResponseType::FeeRateEstimate(ref md, _) => md,
             HttpResponseType::GetMapEntry(ref md, _) => md,
             HttpResponseType::GetAccount(ref md, _) => md,
+            HttpResponseType::MultiProof(ref md, _) => md,
             HttpResponseType::GetContractABI(ref md, _) => md,
             HttpResponseType::GetContractSrc(ref md, _) => md,
+            HttpResponseType::ContractEvents(ref md, _) => md,
+            HttpResponseType::EventStream(ref md) => md,
             HttpResponseType::CallReadOnlyFunction(ref md, _) => md,
+            HttpResponseType::SimulateTransaction(ref md, _) => md,
+            HttpResponseType::BlockHeader(ref md, _) => md,
             HttpResponseType::UnconfirmedTransaction(ref md, _) => md,
+            HttpResponseType::ConfirmedTransaction(ref md, _) => md,
             HttpResponseType::GetAttachment(ref md, _) => md,
             HttpResponseType::GetAttachmentsInv(ref md, _) => md,
             HttpResponseType::OptionsPreflight(ref md) => md,
@@ -3454,18 +4191,51 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
+            HttpResponseType::ContractEvents(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::EventStream(ref md) => {
+                // only send the preamble.  The caller will need to figure out how to send along
+                // the events themselves.
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    200,
+                    "OK",
+                    None,
+                    &HttpContentType::EventStream,
+                    md.request_id,
+                    |ref mut fd| {
+                        fd.write_all("Cache-Control: no-cache\r\n".as_bytes())
+                            .map_err(net_error::WriteError)?;
+                        keep_alive_headers(fd, md)
+                    },
+                )?;
+            }
             HttpResponseType::TokenTransferCost(ref md, ref cost) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, cost)?;
             }
+            HttpResponseType::FeeRateEstimate(ref md, ref estimate) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, estimate)?;
+            }
             HttpResponseType::CallReadOnlyFunction(ref md, ref data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
+            HttpResponseType::SimulateTransaction(ref md, ref simulation) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, simulation)?;
+            }
             HttpResponseType::GetMapEntry(ref md, ref map_data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, map_data)?;
             }
+            HttpResponseType::MultiProof(ref md, ref multi_proof) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, multi_proof)?;
+            }
             HttpResponseType::PeerInfo(ref md, ref peer_info) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, peer_info)?;
@@ -3582,6 +4352,14 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, unconfirmed_status)?;
             }
+            HttpResponseType::ConfirmedTransaction(ref md, ref confirmed) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, confirmed)?;
+            }
+            HttpResponseType::BlockHeader(ref md, ref header_info) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, header_info)?;
+            }
             HttpResponseType::OptionsPreflight(ref md) => {
                 HttpResponsePreamble::new_serialized(
                     fd,
@@ -3676,6 +4454,8 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetPoxInfo(_, _) => "HTTP(GetPoxInfo)",
                 HttpRequestType::GetNeighbors(_) => "HTTP(GetNeighbors)",
                 HttpRequestType::GetBlock(_, _) => "HTTP(GetBlock)",
+                HttpRequestType::GetBlockByHeight(..) => "HTTP(GetBlockByHeight)",
+                HttpRequestType::GetHeaderByHeight(..) => "HTTP(GetHeaderByHeight)",
                 HttpRequestType::GetMicroblocksIndexed(_, _) => "HTTP(GetMicroblocksIndexed)",
                 HttpRequestType::GetMicroblocksConfirmed(_, _) => "HTTP(GetMicroblocksConfirmed)",
                 HttpRequestType::GetMicroblocksUnconfirmed(_, _, _) => {
@@ -3684,14 +4464,20 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetTransactionUnconfirmed(_, _) => {
                     "HTTP(GetTransactionUnconfirmed)"
                 }
+                HttpRequestType::GetTransaction(..) => "HTTP(GetTransaction)",
                 HttpRequestType::PostTransaction(_, _, _) => "HTTP(PostTransaction)",
                 HttpRequestType::PostBlock(..) => "HTTP(PostBlock)",
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
+                HttpRequestType::PostSimulateTransaction(..) => "HTTP(PostSimulateTransaction)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
                 HttpRequestType::GetMapEntry(..) => "HTTP(GetMapEntry)",
+                HttpRequestType::PostMultiProof(..) => "HTTP(PostMultiProof)",
                 HttpRequestType::GetTransferCost(_) => "HTTP(GetTransferCost)",
+                HttpRequestType::PostFeeRateEstimate(..) => "HTTP(PostFeeRateEstimate)",
                 HttpRequestType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpRequestType::GetContractSrc(..) => "HTTP(GetContractSrc)",
+                HttpRequestType::GetContractEvents(..) => "HTTP(GetContractEvents)",
+                HttpRequestType::Subscribe(..) => "HTTP(Subscribe)",
                 HttpRequestType::CallReadOnlyFunction(..) => "HTTP(CallReadOnlyFunction)",
                 HttpRequestType::GetAttachment(..) => "HTTP(GetAttachment)",
                 HttpRequestType::GetAttachmentsInv(..) => "HTTP(GetAttachmentsInv)",
@@ -3700,11 +4486,16 @@ impl MessageSequence for StacksHttpMessage {
             },
             StacksHttpMessage::Response(ref res) => match res {
                 HttpResponseType::TokenTransferCost(_, _) => "HTTP(TokenTransferCost)",
+                HttpResponseType::FeeRateEstimate(_, _) => "HTTP(FeeRateEstimate)",
                 HttpResponseType::GetMapEntry(_, _) => "HTTP(GetMapEntry)",
                 HttpResponseType::GetAccount(_, _) => "HTTP(GetAccount)",
+                HttpResponseType::MultiProof(_, _) => "HTTP(MultiProof)",
                 HttpResponseType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpResponseType::GetContractSrc(..) => "HTTP(GetContractSrc)",
+                HttpResponseType::ContractEvents(..) => "HTTP(ContractEvents)",
+                HttpResponseType::EventStream(..) => "HTTP(EventStream)",
                 HttpResponseType::CallReadOnlyFunction(..) => "HTTP(CallReadOnlyFunction)",
+                HttpResponseType::SimulateTransaction(..) => "HTTP(SimulateTransaction)",
                 HttpResponseType::GetAttachment(_, _) => "HTTP(GetAttachment)",
                 HttpResponseType::GetAttachmentsInv(_, _) => "HTTP(GetAttachmentsInv)",
                 HttpResponseType::PeerInfo(_, _) => "HTTP(PeerInfo)",
@@ -3717,7 +4508,9 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::TransactionID(_, _) => "HTTP(Transaction)",
                 HttpResponseType::StacksBlockAccepted(..) => "HTTP(StacksBlockAccepted)",
                 HttpResponseType::MicroblockHash(_, _) => "HTTP(MicroblockHash)",
+                HttpResponseType::BlockHeader(_, _) => "HTTP(BlockHeader)",
                 HttpResponseType::UnconfirmedTransaction(_, _) => "HTTP(UnconfirmedTransaction)",
+                HttpResponseType::ConfirmedTransaction(_, _) => "HTTP(ConfirmedTransaction)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
                 HttpResponseType::BadRequestJSON(..) | HttpResponseType::BadRequest(..) => {
                     "HTTP(400)"
@@ -4233,11 +5026,15 @@ impl ProtocolFamily for StacksHttp {
 mod test {
     use super::*;
     use net::codec::test::check_codec_and_corruption;
+    use net::rpc::SINGLESIG_TX_ENVELOPE_LEN;
     use net::test::*;
     use net::RPCNeighbor;
     use net::RPCNeighborsInfo;
+    use net::RPCTransactionReceipt;
+    use net::{ContractEventsResponse, RPCIndexedEvent};
     use std::error::Error;
 
+    use burnchains::BurnchainHeaderHash;
     use burnchains::Txid;
     use chainstate::stacks::db::blocks::test::make_sample_microblock_stream;
     use chainstate::stacks::test::make_codec_test_block;
@@ -4259,6 +5056,8 @@ mod test {
     use util::hash::MerkleTree;
     use util::hash::Sha512Trunc256Sum;
 
+    use vm::costs::ExecutionCost;
+
     use rand;
     use rand::RngCore;
 
@@ -5308,6 +6107,9 @@ mod test {
             "GET /v2/neighbors HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
             "GET /v2/blocks/1111111111111111111111111111111111111111111111111111111111111111 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
             "GET /v2/microblocks/1111111111111111111111111111111111111111111111111111111111111111 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
+            "GET /v2/blocks/height/1 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
+            "GET /v2/headers/1 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
+            "GET /v2/transactions/1111111111111111111111111111111111111111111111111111111111111111 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
             "POST /v2/transactions HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 0\r\n\r\n",
         ];
         for bad_content_length in bad_content_lengths {
@@ -5338,6 +6140,117 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_http_post_fee_rate_estimate_codec() {
+        let md = HttpRequestMetadata {
+            version: HttpVersion::Http11,
+            peer: PeerHost::DNS("www.foo.com".to_string(), 80),
+            keep_alive: true,
+        };
+        let tx = make_test_transaction();
+
+        // a singlesig transaction's envelope is as long as the RPC interface assumes
+        assert_eq!(
+            (tx.serialize_to_vec().len() - tx.payload.serialize_to_vec().len()) as u64,
+            SINGLESIG_TX_ENVELOPE_LEN
+        );
+
+        for estimated_len in [None, Some(1234)].iter() {
+            let req = HttpRequestType::PostFeeRateEstimate(
+                md.clone(),
+                tx.payload.clone(),
+                estimated_len.clone(),
+            );
+
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(req.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (msg, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match msg {
+                StacksHttpMessage::Request(parsed) => assert_eq!(parsed, req),
+                _ => panic!("expected a request"),
+            }
+        }
+
+        // the payload must be hex-encoded in a JSON body
+        let bad_bodies = vec![
+            "POST /v2/fees/transaction HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Type: application/octet-stream\r\nContent-Length: 2\r\n\r\n{}",
+            "POST /v2/fees/transaction HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Type: application/json\r\nContent-Length: 28\r\n\r\n{\"transaction_payload\":\"zz\"}",
+        ];
+        for bad_body in bad_bodies {
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(bad_body.as_bytes()).unwrap();
+            assert!(http
+                .read_payload(&preamble, &bad_body.as_bytes()[offset..])
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_http_post_multi_proof_codec() {
+        let md = HttpRequestMetadata {
+            version: HttpVersion::Http11,
+            peer: PeerHost::DNS("www.foo.com".to_string(), 80),
+            keep_alive: true,
+        };
+        let principal = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let contract =
+            QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello")
+                .unwrap();
+        let keys = vec![
+            MultiProofKey::Account(principal.clone()),
+            MultiProofKey::Account(PrincipalData::Contract(contract.clone())),
+            MultiProofKey::DataVar(contract.clone(), "bar".into()),
+            MultiProofKey::MapEntry(contract, "unit-map".into(), Value::Principal(principal)),
+        ];
+
+        for tip_opt in [None, Some(StacksBlockId([0x11; 32]))].iter() {
+            let req = HttpRequestType::PostMultiProof(md.clone(), keys.clone(), tip_opt.clone());
+
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new();
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(req.clone()))
+                .unwrap();
+
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (msg, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match msg {
+                StacksHttpMessage::Request(parsed) => assert_eq!(parsed, req),
+                _ => panic!("expected a request"),
+            }
+        }
+
+        // there must be something to prove, and every item must parse
+        let bad_bodies = vec![
+            "POST /v2/proofs HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}",
+            "POST /v2/proofs HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Type: application/json\r\nContent-Length: 21\r\n\r\n{\"accounts\":[\"nope\"]}",
+            "POST /v2/proofs HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Type: application/json\r\nContent-Length: 101\r\n\r\n{\"map_entries\":[{\"contract\":\"ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello\",\"map\":\"m\",\"key\":\"zz\"}]}",
+        ];
+        for bad_body in bad_bodies {
+            let mut http = StacksHttp::new();
+            let (preamble, offset) = http.read_preamble(bad_body.as_bytes()).unwrap();
+            assert!(http
+                .read_payload(&preamble, &bad_body.as_bytes()[offset..])
+                .is_err());
+        }
+
+        // too many items to prove
+        let req = HttpRequestType::PostMultiProof(
+            md.clone(),
+            vec![keys[0].clone(); MULTI_PROOF_MAX_KEYS + 1],
+            None,
+        );
+        let mut bytes = vec![];
+        let mut http = StacksHttp::new();
+        http.write_message(&mut bytes, &StacksHttpMessage::Request(req))
+            .unwrap();
+        let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+        assert!(http.read_payload(&preamble, &bytes[offset..]).is_err());
+    }
+
     #[test]
     fn test_http_response_type_codec() {
         let test_neighbors_info = RPCNeighborsInfo {
@@ -5393,6 +6306,55 @@ mod test {
             .consensus_serialize(&mut test_microblock_info_bytes)
             .unwrap();
 
+        let test_header_info = RPCBlockHeaderResponse {
+            header: to_hex(&test_block_info.header.serialize_to_vec()),
+            index_block_hash: StacksBlockId([0x01; 32]),
+            block_hash: test_block_info.block_hash(),
+            consensus_hash: ConsensusHash([0x02; 20]),
+            parent_block_id: StacksBlockId([0x03; 32]),
+            block_height: 5,
+            burn_header_hash: BurnchainHeaderHash([0x04; 32]),
+            burn_header_height: 6,
+            burn_header_timestamp: 7,
+        };
+
+        let test_tx = make_test_transaction();
+        let test_confirmed_tx = ConfirmedTransactionResponse {
+            tx: to_hex(&test_tx.serialize_to_vec()),
+            index_block_hash: StacksBlockId([0x01; 32]),
+            block_hash: test_block_info.block_hash(),
+            block_height: 5,
+            tx_index: 1,
+            microblock_hash: Some(test_microblock_info[0].block_hash()),
+            microblock_sequence: Some(0),
+            receipt: RPCTransactionReceipt {
+                result: "0x0703".to_string(),
+                post_condition_aborted: false,
+                execution_cost: ExecutionCost {
+                    write_length: 1,
+                    write_count: 2,
+                    read_length: 3,
+                    read_count: 4,
+                    runtime: 5,
+                },
+            },
+        };
+
+        let test_contract_events = ContractEventsResponse {
+            limit: 2,
+            events: vec![RPCIndexedEvent {
+                index_block_hash: StacksBlockId([0x01; 32]),
+                block_height: 5,
+                event: json!({
+                    "txid": format!("0x{}", test_tx.txid()),
+                    "event_index": 0,
+                    "committed": true,
+                    "type": "contract_event",
+                }),
+            }],
+            next_cursor: Some("5-0".to_string()),
+        };
+
         let tests = vec![
             // length is known
             (
@@ -5478,6 +6440,35 @@ mod test {
                 ),
                 "/v2/transactions".to_string(),
             ),
+            (
+                HttpResponseType::Block(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    test_block_info.clone(),
+                ),
+                "/v2/blocks/height/5".to_string(),
+            ),
+            (
+                HttpResponseType::BlockHeader(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    test_header_info.clone(),
+                ),
+                "/v2/headers/5".to_string(),
+            ),
+            (
+                HttpResponseType::ConfirmedTransaction(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    test_confirmed_tx.clone(),
+                ),
+                format!("/v2/transactions/{}", test_tx.txid()),
+            ),
+            (
+                HttpResponseType::ContractEvents(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true),
+                    test_contract_events.clone(),
+                ),
+                "/v2/contracts/events/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world"
+                    .to_string(),
+            ),
             // errors without error messages
             (
                 HttpResponseType::BadRequest(
@@ -5663,6 +6654,38 @@ mod test {
                 true,
                 123,
             ),
+            HttpResponsePreamble::new(
+                200,
+                "OK".to_string(),
+                None,
+                HttpContentType::Bytes,
+                true,
+                123,
+            ),
+            HttpResponsePreamble::new(
+                200,
+                "OK".to_string(),
+                None,
+                HttpContentType::JSON,
+                true,
+                123,
+            ),
+            HttpResponsePreamble::new(
+                200,
+                "OK".to_string(),
+                None,
+                HttpContentType::JSON,
+                true,
+                123,
+            ),
+            HttpResponsePreamble::new(
+                200,
+                "OK".to_string(),
+                None,
+                HttpContentType::JSON,
+                true,
+                123,
+            ),
             // errors
             HttpResponsePreamble::new_error(400, 123, None),
             HttpResponsePreamble::new_error(401, 123, None),
@@ -5698,9 +6721,22 @@ mod test {
                 .unwrap()
                 .as_bytes()
                 .to_vec(),
-            test_block_info_bytes,
+            test_block_info_bytes.clone(),
             test_microblock_info_bytes,
             Txid([0x1; 32]).to_hex().as_bytes().to_vec(),
+            test_block_info_bytes,
+            serde_json::to_string(&test_header_info)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+            serde_json::to_string(&test_confirmed_tx)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+            serde_json::to_string(&test_contract_events)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
             // errors
             vec![],
             vec![],
@@ -6190,6 +7226,134 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_parse_contract_events_query() {
+        let mut http = StacksHttp::new();
+        let request_text = "GET /v2/contracts/events/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world?event_type=ft_transfer_event&principal=STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW&cursor=10-3&limit=20 HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::GetContractEvents(
+                _,
+                contract_addr,
+                contract_name,
+                event_type,
+                principal,
+                cursor,
+                limit,
+                tip,
+            )) => {
+                assert_eq!(
+                    contract_addr,
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                );
+                assert_eq!(contract_name.as_str(), "hello-world");
+                assert_eq!(event_type, Some("ft_transfer_event".to_string()));
+                assert_eq!(
+                    principal,
+                    Some(PrincipalData::parse("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap())
+                );
+                assert_eq!(
+                    cursor,
+                    Some(EventCursor {
+                        block_height: 10,
+                        event_index: 3
+                    })
+                );
+                assert_eq!(limit, 20);
+                assert_eq!(tip, None);
+            }
+            _ => panic!("Expected GetContractEvents, got {:?}", &message),
+        }
+
+        // defaults apply, and the request path reproduces the query
+        let request_text = "GET /v2/contracts/events/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(req) => assert_eq!(
+                req.request_path(),
+                format!(
+                    "/v2/contracts/events/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world?limit={}",
+                    EVENT_INDEX_DEFAULT_PAGE_SIZE
+                )
+            ),
+            _ => panic!("Expected a request, got {:?}", &message),
+        }
+
+        // bad principals, numbers, and cursors are rejected
+        let request_text = "GET /v2/contracts/events/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world?limit=lots HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .is_err());
+
+        let request_text = "GET /v2/contracts/events/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world?cursor=10 HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .is_err());
+    }
+
+    #[test]
+    fn test_http_parse_subscribe_query() {
+        let mut http = StacksHttp::new();
+        let request_text = "GET /v2/subscribe?topics=blocks,events&contract=ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world&principal=STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(HttpRequestType::Subscribe(_, filter)) => {
+                assert_eq!(
+                    filter.topics,
+                    vec![SubscriptionTopic::Blocks, SubscriptionTopic::Events]
+                );
+                assert_eq!(
+                    filter.contract,
+                    Some(
+                        QualifiedContractIdentifier::parse(
+                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world"
+                        )
+                        .unwrap()
+                    )
+                );
+                assert_eq!(
+                    filter.principal,
+                    Some(PrincipalData::parse("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap())
+                );
+            }
+            _ => panic!("Expected Subscribe, got {:?}", &message),
+        }
+
+        // all topics by default, and the request path reproduces the query
+        let request_text = "GET /v2/subscribe HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .unwrap();
+        match message {
+            StacksHttpMessage::Request(req) => assert_eq!(
+                req.request_path(),
+                "/v2/subscribe?topics=blocks%2Cmicroblocks%2Cmempool%2Cevents"
+            ),
+            _ => panic!("Expected a request, got {:?}", &message),
+        }
+
+        // unknown topics are rejected
+        let request_text =
+            "GET /v2/subscribe?topics=blocks,burn_blocks HTTP/1.1\r\nHost: localhost:6270\r\n\r\n";
+        let (preamble, offset) = http.read_preamble(request_text.as_bytes()).unwrap();
+        assert!(http
+            .read_payload(&preamble, &request_text.as_bytes()[offset..])
+            .is_err());
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
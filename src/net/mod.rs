@@ -31,6 +31,7 @@ pub mod prune;
 pub mod relay;
 pub mod rpc;
 pub mod server;
+pub mod subscriptions;
 
 use std::borrow::Borrow;
 use std::cmp::PartialEq;
@@ -74,16 +75,18 @@ use chainstate::burn::ConsensusHash;
 use chainstate::burn::db::sortdb::PoxId;
 
 use chainstate::stacks::db::blocks::MemPoolRejection;
+use chainstate::stacks::db::event_index::EventCursor;
 use chainstate::stacks::{
     Error as chain_error, StacksAddress, StacksBlock, StacksBlockId, StacksMicroblock,
-    StacksPublicKey, StacksTransaction,
+    StacksPublicKey, StacksTransaction, TransactionPayload,
 };
 
 use chainstate::stacks::Error as chainstate_error;
 
 use vm::{
-    analysis::contract_interface_builder::ContractInterface, types::PrincipalData, ClarityName,
-    ContractName, Value,
+    analysis::contract_interface_builder::ContractInterface,
+    types::{PrincipalData, QualifiedContractIdentifier},
+    ClarityName, ContractName, Value,
 };
 
 use util::hash::Hash160;
@@ -108,12 +111,14 @@ use serde::ser::Error as ser_Error;
 
 use chainstate::stacks::index::Error as marf_error;
 use vm::clarity::Error as clarity_error;
+use vm::costs::ExecutionCost;
 
 use crate::util::hash::Sha256Sum;
 
 use self::dns::*;
 
 use net::atlas::{Attachment, AttachmentInstance};
+use net::subscriptions::SubscriptionFilter;
 
 use core::POX_REWARD_CYCLE_LENGTH;
 
@@ -616,6 +621,7 @@ pub enum HttpContentType {
     Bytes,
     Text,
     JSON,
+    EventStream,
 }
 
 impl fmt::Display for HttpContentType {
@@ -630,6 +636,7 @@ impl HttpContentType {
             HttpContentType::Bytes => "application/octet-stream",
             HttpContentType::Text => "text/plain",
             HttpContentType::JSON => "application/json",
+            HttpContentType::EventStream => "text/event-stream",
         }
     }
 }
@@ -645,6 +652,8 @@ impl FromStr for HttpContentType {
             Ok(HttpContentType::Text)
         } else if s == "application/json" {
             Ok(HttpContentType::JSON)
+        } else if s == "text/event-stream" {
+            Ok(HttpContentType::EventStream)
         } else {
             Err(Error::DeserializeError(
                 "Unsupported HTTP content type".to_string(),
@@ -1081,6 +1090,119 @@ pub struct CallReadOnlyResponse {
     pub cause: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeRateEstimateRequestBody {
+    /// hex-encoded, consensus-serialized transaction payload
+    pub transaction_payload: String,
+    /// length of the whole transaction, if known.  Defaults to the payload's length plus the
+    /// length of a singlesig transaction's envelope.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_len: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeEstimate {
+    pub fee_rate: f64,
+    pub fee: u64,
+}
+
+/// Struct given back from a call to `/v2/fees/transaction`.  `estimations` holds the low, medium
+/// and high fee suggestions, in that order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeEstimateResponse {
+    pub estimated_cost: ExecutionCost,
+    pub estimated_len: u64,
+    pub estimations: Vec<RPCFeeEstimate>,
+}
+
+/// What a simulated transaction did.  `result` is the hex-encoded, consensus-serialized Clarity
+/// value the transaction evaluated to, and `events` are encoded as they would be for an event
+/// observer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCSimulatedTransactionReceipt {
+    pub result: String,
+    pub post_condition_aborted: bool,
+    pub events: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+    pub fee: u64,
+}
+
+/// Struct given back from a call to `/v2/transactions/simulate`.  If the transaction could not be
+/// applied to the chain tip (e.g. its nonce is wrong, or it would be invalid in a block), then
+/// `okay` is false and `cause` says why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulateTransactionResponse {
+    pub okay: bool,
+    pub txid: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<RPCSimulatedTransactionReceipt>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+}
+
+/// What a confirmed transaction did.  `result` is the hex-encoded, consensus-serialized Clarity
+/// value the transaction evaluated to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCTransactionReceipt {
+    pub result: String,
+    pub post_condition_aborted: bool,
+    pub execution_cost: ExecutionCost,
+}
+
+/// Struct given back from a call to `/v2/transactions/{txid}`.  `tx_index` is the transaction's
+/// position in its anchored block, or in its microblock if `microblock_hash` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmedTransactionResponse {
+    pub tx: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub block_height: u64,
+    pub tx_index: u32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microblock_hash: Option<BlockHeaderHash>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microblock_sequence: Option<u16>,
+    pub receipt: RPCTransactionReceipt,
+}
+
+/// An indexed event, as given back from a call to `/v2/contracts/events/{address}/{name}`.
+/// `event` is the event as it is sent to event observers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCIndexedEvent {
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub event: serde_json::Value,
+}
+
+/// Struct given back from a call to `/v2/contracts/events/{address}/{name}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractEventsResponse {
+    pub limit: u64,
+    pub events: Vec<RPCIndexedEvent>,
+    /// Where the next page starts, if this page has any events
+    pub next_cursor: Option<String>,
+}
+
+/// Struct given back from a call to `/v2/headers/{height}`.  `header` is the hex-encoded,
+/// consensus-serialized anchored block header.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCBlockHeaderResponse {
+    pub header: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub consensus_hash: ConsensusHash,
+    pub parent_block_id: StacksBlockId,
+    pub block_height: u64,
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub burn_header_height: u32,
+    pub burn_header_timestamp: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountEntryResponse {
     pub balance: String,
@@ -1095,6 +1217,62 @@ pub struct AccountEntryResponse {
     pub nonce_proof: Option<String>,
 }
 
+/// A data var to prove with a call to `/v2/proofs`.  `contract` is the fully-qualified contract
+/// identifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCDataVarKey {
+    pub contract: String,
+    pub var: String,
+}
+
+/// A data map entry to prove with a call to `/v2/proofs`.  `key` is the hex-encoded,
+/// consensus-serialized Clarity value of the map key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCMapEntryKey {
+    pub contract: String,
+    pub map: String,
+    pub key: String,
+}
+
+/// Body of a POST to `/v2/proofs`.  Each account proves both its balance and its nonce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiProofRequestBody {
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    #[serde(default)]
+    pub data_vars: Vec<RPCDataVarKey>,
+    #[serde(default)]
+    pub map_entries: Vec<RPCMapEntryKey>,
+}
+
+/// A piece of Clarity state that can be proven with a call to `/v2/proofs`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultiProofKey {
+    Account(PrincipalData),
+    DataVar(QualifiedContractIdentifier, ClarityName),
+    MapEntry(QualifiedContractIdentifier, ClarityName, Value),
+}
+
+/// A MARF key given back from a call to `/v2/proofs`.  `data` is the value stored under the key
+/// in the Clarity database, exactly as the proof commits to it, or None if the key has no value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCProvenEntry {
+    pub key: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+/// Struct given back from a call to `/v2/proofs`.  `proof` is the hex-encoded,
+/// consensus-serialized multi-proof of every entry that has data, in the order they appear in
+/// `entries`, against the state root of the block `index_block_hash`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiProofResponse {
+    pub index_block_hash: StacksBlockId,
+    pub entries: Vec<RPCProvenEntry>,
+    pub proof: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UnconfirmedTransactionStatus {
     Microblock {
@@ -1211,19 +1389,32 @@ pub enum HttpRequestType {
     GetPoxInfo(HttpRequestMetadata, Option<StacksBlockId>),
     GetNeighbors(HttpRequestMetadata),
     GetBlock(HttpRequestMetadata, StacksBlockId),
+    GetBlockByHeight(HttpRequestMetadata, u64, Option<StacksBlockId>),
+    GetHeaderByHeight(HttpRequestMetadata, u64, Option<StacksBlockId>),
     GetMicroblocksIndexed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksConfirmed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksUnconfirmed(HttpRequestMetadata, StacksBlockId, u16),
     GetTransactionUnconfirmed(HttpRequestMetadata, Txid),
+    GetTransaction(HttpRequestMetadata, Txid, Option<StacksBlockId>),
     PostTransaction(HttpRequestMetadata, StacksTransaction, Option<Attachment>),
     PostBlock(HttpRequestMetadata, ConsensusHash, StacksBlock),
     PostMicroblock(HttpRequestMetadata, StacksMicroblock, Option<StacksBlockId>),
+    PostSimulateTransaction(
+        HttpRequestMetadata,
+        StacksTransaction,
+        Option<StacksBlockId>,
+    ),
     GetAccount(
         HttpRequestMetadata,
         PrincipalData,
         Option<StacksBlockId>,
         bool,
     ),
+    PostMultiProof(
+        HttpRequestMetadata,
+        Vec<MultiProofKey>,
+        Option<StacksBlockId>,
+    ),
     GetMapEntry(
         HttpRequestMetadata,
         StacksAddress,
@@ -1243,6 +1434,7 @@ pub enum HttpRequestType {
         Option<StacksBlockId>,
     ),
     GetTransferCost(HttpRequestMetadata),
+    PostFeeRateEstimate(HttpRequestMetadata, TransactionPayload, Option<u64>),
     GetContractSrc(
         HttpRequestMetadata,
         StacksAddress,
@@ -1256,6 +1448,19 @@ pub enum HttpRequestType {
         ContractName,
         Option<StacksBlockId>,
     ),
+    /// contract, event type filter, principal filter, offset, limit, tip
+    GetContractEvents(
+        HttpRequestMetadata,
+        StacksAddress,
+        ContractName,
+        Option<String>,
+        Option<PrincipalData>,
+        Option<EventCursor>,
+        u64,
+        Option<StacksBlockId>,
+    ),
+    /// stream the events that match the filter as server-sent events
+    Subscribe(HttpRequestMetadata, SubscriptionFilter),
     OptionsPreflight(HttpRequestMetadata, String),
     GetAttachment(HttpRequestMetadata, Hash160),
     GetAttachmentsInv(HttpRequestMetadata, Option<StacksBlockId>, HashSet<u32>),
@@ -1344,12 +1549,19 @@ pub enum HttpResponseType {
     StacksBlockAccepted(HttpResponseMetadata, StacksBlockId, bool),
     MicroblockHash(HttpResponseMetadata, BlockHeaderHash),
     TokenTransferCost(HttpResponseMetadata, u64),
+    FeeRateEstimate(HttpResponseMetadata, RPCFeeEstimateResponse),
     GetMapEntry(HttpResponseMetadata, MapEntryResponse),
     CallReadOnlyFunction(HttpResponseMetadata, CallReadOnlyResponse),
+    SimulateTransaction(HttpResponseMetadata, SimulateTransactionResponse),
     GetAccount(HttpResponseMetadata, AccountEntryResponse),
+    MultiProof(HttpResponseMetadata, MultiProofResponse),
     GetContractABI(HttpResponseMetadata, ContractInterface),
     GetContractSrc(HttpResponseMetadata, ContractSrcResponse),
+    ContractEvents(HttpResponseMetadata, ContractEventsResponse),
+    EventStream(HttpResponseMetadata),
+    BlockHeader(HttpResponseMetadata, RPCBlockHeaderResponse),
     UnconfirmedTransaction(HttpResponseMetadata, UnconfirmedTransactionResponse),
+    ConfirmedTransaction(HttpResponseMetadata, ConfirmedTransactionResponse),
     GetAttachment(HttpResponseMetadata, GetAttachmentResponse),
     GetAttachmentsInv(HttpResponseMetadata, GetAttachmentsInvResponse),
     OptionsPreflight(HttpResponseMetadata),
@@ -1512,6 +1724,10 @@ pub const GETPOXINV_MAX_BITLEN: u64 = 8;
 // message.
 pub const BLOCKS_PUSHED_MAX: u32 = 32;
 
+// maximum number of items that can be proven in one request for a MARF multi-proof.  This bounds
+// the number of MARF walks a client can ask a node to do at once.
+pub const MULTI_PROOF_MAX_KEYS: usize = 256;
+
 macro_rules! impl_byte_array_message_codec {
     ($thing:ident, $len:expr) => {
         impl ::net::StacksMessageCodec for $thing {
@@ -1691,6 +1907,7 @@ pub struct NetworkResult {
     pub uploaded_microblocks: Vec<MicroblocksData>,    // microblocks sent to us by the http server
     pub uploaded_attachments: Vec<Attachment>,         // attachments sent to us by the http server
     pub attachments: Vec<AttachmentInstance>,
+    pub dropped_transactions: Vec<(Txid, MemPoolDropReason)>, // transactions evicted from the mempool while handling http uploads
     pub num_state_machine_passes: u64,
     pub num_inv_sync_passes: u64,
 }
@@ -1710,6 +1927,7 @@ impl NetworkResult {
             uploaded_microblocks: vec![],
             uploaded_attachments: vec![],
             attachments: vec![],
+            dropped_transactions: vec![],
             num_state_machine_passes: num_state_machine_passes,
             num_inv_sync_passes: num_inv_sync_passes,
         }
@@ -2926,6 +3144,18 @@ pub mod test {
             &mut self.stacks_node.as_mut().unwrap().chainstate
         }
 
+        /// Set the burnchain height at which Clarity 2 activates, both for the blocks this peer
+        /// mines and for the blocks its coordinator processes.
+        pub fn set_clarity_2_activation_height(&mut self, height: u64) {
+            self.chainstate()
+                .clarity_state
+                .set_clarity_2_activation_height(height);
+            self.coord
+                .chainstate_mut()
+                .clarity_state
+                .set_clarity_2_activation_height(height);
+        }
+
         pub fn sortdb(&mut self) -> &mut SortitionDB {
             self.sortdb.as_mut().unwrap()
         }
@@ -4269,6 +4269,11 @@ impl PeerNetwork {
             Ok(())
         })?;
 
+        // report any transactions that http uploads evicted from the mempool
+        network_result
+            .dropped_transactions
+            .append(&mut mempool.take_dropped_txs());
+
         self.dispatch_network(
             &mut network_result,
             sortdb,
@@ -22,6 +22,7 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::mem;
 
 use core::mempool::MemPoolDB;
 
@@ -90,6 +91,8 @@ pub struct RelayerStats {
 
 pub struct ProcessedNetReceipts {
     pub mempool_txs_added: Vec<StacksTransaction>,
+    pub mempool_txs_dropped: Vec<(Txid, MemPoolDropReason)>,
+    pub microblocks_added: Vec<MicroblocksData>,
 }
 
 /// Private trait for keeping track of messages that can be relayed, so we can identify the peers
@@ -1196,6 +1199,7 @@ impl Relayer {
         mempool: &mut MemPoolDB,
         coord_comms: Option<&CoordinatorChannels>,
     ) -> Result<ProcessedNetReceipts, net_error> {
+        let mut microblocks_added = vec![];
         match Relayer::process_new_blocks(network_result, sortdb, chainstate, coord_comms) {
             Ok((new_blocks, new_confirmed_microblocks, new_microblocks, bad_block_neighbors)) => {
                 // attempt to relay messages (note that this is all best-effort).
@@ -1248,6 +1252,7 @@ impl Relayer {
                             mblocks_msg.microblocks.len(),
                             &mblocks_msg.index_anchor_block
                         );
+                        microblocks_added.push(mblocks_msg.clone());
                         let msg = StacksMessageType::Microblocks(mblocks_msg);
                         if let Err(e) = self.p2p.broadcast_message(relayers, msg) {
                             warn!("Failed to broadcast microblock: {:?}", &e);
@@ -1286,7 +1291,17 @@ impl Relayer {
             }
         }
 
-        let receipts = ProcessedNetReceipts { mempool_txs_added };
+        // report everything evicted from the mempool, whether by http uploads or by the
+        // transactions we just stored
+        let mut mempool_txs_dropped =
+            mem::replace(&mut network_result.dropped_transactions, vec![]);
+        mempool_txs_dropped.append(&mut mempool.take_dropped_txs());
+
+        let receipts = ProcessedNetReceipts {
+            mempool_txs_added,
+            mempool_txs_dropped,
+            microblocks_added,
+        };
 
         // finally, refresh the unconfirmed chainstate, if need be
         if network_result.has_microblocks() {
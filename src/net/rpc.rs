@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
 use std::fmt;
 use std::io;
 use std::io::prelude::*;
@@ -33,6 +34,9 @@ use net::http::*;
 use net::p2p::PeerMap;
 use net::p2p::PeerNetwork;
 use net::relay::Relayer;
+use net::subscriptions::{
+    SubscriptionEvent, SubscriptionFilter, SUBSCRIPTION_HEARTBEAT_FRAME, SUBSCRIPTION_MAX_BACKLOG,
+};
 use net::BlocksData;
 use net::ClientError;
 use net::Error as net_error;
@@ -56,8 +60,11 @@ use net::UrlString;
 use net::HTTP_REQUEST_ID_RESERVED;
 use net::MAX_NEIGHBORS_DATA_LEN;
 use net::{
-    AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ContractSrcResponse,
-    GetAttachmentResponse, GetAttachmentsInvResponse, MapEntryResponse,
+    AccountEntryResponse, AttachmentPage, CallReadOnlyResponse, ConfirmedTransactionResponse,
+    ContractEventsResponse, ContractSrcResponse, GetAttachmentResponse, GetAttachmentsInvResponse,
+    MapEntryResponse, MultiProofKey, MultiProofResponse, RPCBlockHeaderResponse, RPCFeeEstimate,
+    RPCFeeEstimateResponse, RPCIndexedEvent, RPCProvenEntry, RPCSimulatedTransactionReceipt,
+    RPCTransactionReceipt, SimulateTransactionResponse,
 };
 use net::{RPCNeighbor, RPCNeighborsInfo};
 use net::{RPCPeerInfoData, RPCPoxInfoData};
@@ -74,8 +81,12 @@ use chainstate::burn::db::sortdb::SortitionDB;
 use chainstate::burn::BlockHeaderHash;
 use chainstate::burn::ConsensusHash;
 use chainstate::stacks::db::{
-    blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, BlockStreamData, StacksChainState,
+    blocks::MINIMUM_TX_FEE, blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, event_index::EventCursor,
+    event_index::EventFilter, event_index::EVENT_INDEX_MAX_PAGE_SIZE, fee_rates::FeeRateEstimate,
+    fee_rates::FEE_RATE_HISTORY_WINDOW, BlockStreamData, StacksChainState, StacksHeaderInfo,
 };
+use chainstate::stacks::index::proofs::TrieMultiProof;
+use chainstate::stacks::index::MarfTrieId;
 use chainstate::stacks::Error as chain_error;
 use chainstate::stacks::*;
 use monitoring;
@@ -107,6 +118,10 @@ use rand::thread_rng;
 
 pub const STREAM_CHUNK_SIZE: u64 = 4096;
 
+/// Length of everything in a singlesig transaction besides its payload: version, chain ID,
+/// spending condition, anchor mode, post-condition mode, and an empty post-condition list.
+pub const SINGLESIG_TX_ENVELOPE_LEN: u64 = 115;
+
 #[derive(Default)]
 pub struct RPCHandlerArgs<'a> {
     pub exit_at_block_height: Option<&'a u64>,
@@ -140,6 +155,18 @@ pub struct ConversationHttp {
     pending_request: Option<ReplyHandleHttp>,
     pending_response: Option<HttpResponseType>,
     pending_error_response: Option<HttpResponseType>,
+
+    // ongoing event subscription, if any, and the events waiting to be streamed to it.  Events
+    // are only streamed once all earlier replies have been sent.  Each batch of events is written
+    // to its own reply handle, which is kept here until it's been flushed.
+    subscription: Option<(
+        Option<ReplyHandleHttp>,
+        HttpChunkedTransferWriterState,
+        SubscriptionFilter,
+    )>,
+    pending_events: VecDeque<Vec<u8>>,
+    // whether or not the node already has as many event subscribers as it allows
+    subscriptions_full: bool,
 }
 
 impl fmt::Display for ConversationHttp {
@@ -409,6 +436,9 @@ impl ConversationHttp {
             pending_request: None,
             pending_response: None,
             pending_error_response: None,
+            subscription: None,
+            pending_events: VecDeque::new(),
+            subscriptions_full: false,
             keep_alive: true,
             total_request_count: 0,
             total_reply_count: 0,
@@ -790,6 +820,120 @@ impl ConversationHttp {
         }
     }
 
+    /// Load the header of the block at the given height in the fork ending at `tip`.  If there is
+    /// no such block, or it could not be loaded, then reply with an error and return None.
+    fn handle_load_header_at_height<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip: &StacksBlockId,
+        height: u64,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<StacksHeaderInfo>, net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let header_res = chainstate.index_conn().and_then(|index_conn| {
+            StacksChainState::get_block_header_at_height(&index_conn, tip, height)
+        });
+        match header_res {
+            Ok(Some(header)) => Ok(Some(header)),
+            Ok(None) => ConversationHttp::handle_notfound(
+                http,
+                fd,
+                response_metadata,
+                format!("No block at height {} in the fork of {}", height, tip),
+            )
+            .map(|_| None),
+            Err(e) => ConversationHttp::handle_server_error(
+                http,
+                fd,
+                response_metadata,
+                format!("Failed to load block at height {}: {:?}", height, &e),
+            )
+            .map(|_| None),
+        }
+    }
+
+    /// Handle a GET block by height in the fork ending at `tip`.  Start streaming the reply, as
+    /// in handle_getblock.
+    fn handle_getblock_by_height<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip: &StacksBlockId,
+        height: u64,
+        chainstate: &StacksChainState,
+    ) -> Result<Option<BlockStreamData>, net_error> {
+        match ConversationHttp::handle_load_header_at_height(
+            http, fd, req, tip, height, chainstate,
+        )? {
+            Some(header) => ConversationHttp::handle_getblock(
+                http,
+                fd,
+                req,
+                &header.index_block_hash(),
+                chainstate,
+            ),
+            None => Ok(None),
+        }
+    }
+
+    /// Handle a GET block header by height in the fork ending at `tip`.
+    /// The response will be synchronously written to the fd.
+    fn handle_getheader_by_height<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        tip: &StacksBlockId,
+        height: u64,
+        chainstate: &StacksChainState,
+    ) -> Result<(), net_error> {
+        let header = match ConversationHttp::handle_load_header_at_height(
+            http, fd, req, tip, height, chainstate,
+        )? {
+            Some(header) => header,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let response_metadata = HttpResponseMetadata::from(req);
+        let index_block_hash = header.index_block_hash();
+        let parent_block_id_res = if header.block_height == 0 {
+            // the genesis block has no parent
+            Ok(Some(StacksBlockId::sentinel()))
+        } else {
+            StacksChainState::get_parent_block_id(chainstate.db(), &index_block_hash)
+        };
+        let response = match parent_block_id_res {
+            Ok(Some(parent_block_id)) => HttpResponseType::BlockHeader(
+                response_metadata,
+                RPCBlockHeaderResponse {
+                    header: to_hex(&header.anchored_header.serialize_to_vec()),
+                    index_block_hash,
+                    block_hash: header.anchored_header.block_hash(),
+                    consensus_hash: header.consensus_hash,
+                    parent_block_id,
+                    block_height: header.block_height,
+                    burn_header_hash: header.burn_header_hash,
+                    burn_header_height: header.burn_header_height,
+                    burn_header_timestamp: header.burn_header_timestamp,
+                },
+            ),
+            Ok(None) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("No parent for block {}", &index_block_hash),
+            ),
+            Err(e) => HttpResponseType::ServerError(
+                response_metadata,
+                format!(
+                    "Failed to load parent of block {}: {:?}",
+                    &index_block_hash, &e
+                ),
+            ),
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET confirmed microblock stream, by _anchor block hash_.  Start streaming the reply.
     /// The response's preamble (but not the block data) will be synchronously written to the fd
     /// (so use a fd that can buffer!)
@@ -1000,6 +1144,74 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a POST to estimate the fee a transaction payload should pay.  The payload's cost is
+    /// estimated statically, and the low/medium/high fee rates are taken from the transactions
+    /// mined in the last FEE_RATE_HISTORY_WINDOW blocks (and their microblock streams) of the
+    /// fork ending at the given tip.  Never suggests less than the minimum relay fee.
+    fn handle_post_fee_rate_estimate<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        payload: &TransactionPayload,
+        estimated_len: Option<u64>,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let estimated_cost = estimate_payload_cost(payload);
+        let estimated_len = estimated_len
+            .unwrap_or((payload.serialize_to_vec().len() as u64) + SINGLESIG_TX_ENVELOPE_LEN);
+        let capacity =
+            compute_block_capacity(estimated_len, &estimated_cost, &chainstate.block_limit);
+        let minimum_fee = cmp::max(
+            MINIMUM_TX_FEE,
+            MINIMUM_TX_FEE_RATE_PER_BYTE.saturating_mul(estimated_len),
+        );
+
+        let response = match StacksChainState::get_fee_rate_estimate(
+            chainstate.db(),
+            tip,
+            FEE_RATE_HISTORY_WINDOW,
+        ) {
+            Ok(estimate_opt) => {
+                // with no recent history, just suggest the minimum fee
+                let minimum_fee_rate = (minimum_fee as f64) / capacity;
+                let estimate = estimate_opt.unwrap_or(FeeRateEstimate {
+                    low: minimum_fee_rate,
+                    medium: minimum_fee_rate,
+                    high: minimum_fee_rate,
+                });
+
+                let estimations = vec![estimate.low, estimate.medium, estimate.high]
+                    .into_iter()
+                    .map(|fee_rate| {
+                        let fee = cmp::max(minimum_fee, (fee_rate * capacity).ceil() as u64);
+                        RPCFeeEstimate {
+                            fee_rate: (fee as f64) / capacity,
+                            fee,
+                        }
+                    })
+                    .collect();
+
+                HttpResponseType::FeeRateEstimate(
+                    response_metadata,
+                    RPCFeeEstimateResponse {
+                        estimated_cost,
+                        estimated_len,
+                        estimations,
+                    },
+                )
+            }
+            Err(e) => HttpResponseType::ServerError(
+                response_metadata,
+                format!("Failed to load fee rate history: {:?}", &e),
+            ),
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET on an existing account, given the current chain tip.  Optionally supplies a
     /// MARF proof for each account detail loaded from the chain tip.
     fn handle_get_account_entry<W: Write>(
@@ -1119,6 +1331,82 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a POST for a MARF multi-proof of many accounts, data vars and map entries at once,
+    /// given the current chain tip.  Gives back each item's MARF key with the data stored under
+    /// it, along with one proof for all of the keys that have data.
+    fn handle_post_multi_proof<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        keys: &[MultiProofKey],
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let mut marf_keys = vec![];
+        for key in keys.iter() {
+            match key {
+                MultiProofKey::Account(principal) => {
+                    marf_keys.push(ClarityDatabase::make_key_for_account_balance(principal));
+                    marf_keys.push(ClarityDatabase::make_key_for_account_nonce(principal));
+                }
+                MultiProofKey::DataVar(contract_identifier, var_name) => {
+                    marf_keys.push(ClarityDatabase::make_key_for_data_var(
+                        contract_identifier,
+                        var_name,
+                    ));
+                }
+                MultiProofKey::MapEntry(contract_identifier, map_name, key) => {
+                    marf_keys.push(ClarityDatabase::make_key_for_data_map_entry(
+                        contract_identifier,
+                        map_name,
+                        key,
+                    ));
+                }
+            }
+        }
+
+        let response =
+            match chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let mut entries = Vec::with_capacity(marf_keys.len());
+                    let mut proofs = vec![];
+                    for key in marf_keys.into_iter() {
+                        let data =
+                            clarity_db
+                                .get_with_proof::<String>(&key)
+                                .map(|(data, proof)| {
+                                    proofs.push(proof);
+                                    data
+                                });
+                        entries.push(RPCProvenEntry { key, data });
+                    }
+
+                    TrieMultiProof::from_proofs(&proofs).map(|multi_proof| MultiProofResponse {
+                        index_block_hash: tip.clone(),
+                        entries,
+                        proof: format!("0x{}", multi_proof.to_hex()),
+                    })
+                })
+            }) {
+                Ok(Some(Ok(data))) => HttpResponseType::MultiProof(response_metadata, data),
+                Ok(Some(Err(e))) => {
+                    warn!("Failed to make MARF multi-proof at {}: {:?}", tip, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        "Failed to make MARF multi-proof".into(),
+                    )
+                }
+                Ok(None) | Err(_) => {
+                    HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
+                }
+            };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a POST to run a read-only function call with the given parameters on the given chain
     /// tip.  Returns the result of the function call.  Returns a CallReadOnlyResponse on success.
     fn handle_readonly_function_call<W: Write>(
@@ -1188,6 +1476,92 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a POST to simulate a transaction.  The transaction is run against the state at the
+    /// given chain tip, and its receipt is returned without committing anything.  Like a
+    /// read-only function call, it may only consume the read-only call budget -- except that it
+    /// may also write as much as a block can, since its writes are discarded.  Transactions that
+    /// are estimated to cost more than that are rejected without being run.
+    fn handle_post_simulate_transaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        tx: &StacksTransaction,
+        options: &ConnectionOptions,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        let txid = tx.txid();
+
+        let cost_limit = ExecutionCost {
+            write_length: chainstate.block_limit.write_length,
+            write_count: chainstate.block_limit.write_count,
+            ..options.read_only_call_limit.clone()
+        };
+        let estimated_cost = estimate_tx_cost(tx);
+        if estimated_cost.exceeds(&cost_limit) {
+            debug!(
+                "Will not simulate transaction {}: estimated cost {:?} exceeds {:?}",
+                &txid, &estimated_cost, &cost_limit
+            );
+            let response = HttpResponseType::BadRequest(
+                response_metadata,
+                format!(
+                    "Transaction's estimated cost exceeds the simulation limit of {:?}",
+                    &cost_limit
+                ),
+            );
+            return response.send(http, fd).map(|_| ());
+        }
+
+        let result = chainstate.simulate_transaction(&sortdb.index_conn(), tip, tx, cost_limit);
+        let response = match result {
+            Ok(Some((fee, receipt))) => {
+                let events = receipt
+                    .events
+                    .iter()
+                    .enumerate()
+                    .map(|(i, event)| event.json_serialize(i, &txid, false))
+                    .collect();
+                HttpResponseType::SimulateTransaction(
+                    response_metadata,
+                    SimulateTransactionResponse {
+                        okay: true,
+                        txid: format!("0x{}", txid.to_hex()),
+                        receipt: Some(RPCSimulatedTransactionReceipt {
+                            result: format!("0x{}", receipt.result.serialize()),
+                            post_condition_aborted: receipt.post_condition_aborted,
+                            events: events,
+                            execution_cost: receipt.execution_cost,
+                            fee: fee,
+                        }),
+                        cause: None,
+                    },
+                )
+            }
+            Ok(None) => HttpResponseType::NotFound(response_metadata, "Chain tip not found".into()),
+            Err(chain_error::DBError(e)) => {
+                warn!("Failed to simulate transaction {}: {:?}", &txid, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to simulate transaction"),
+                )
+            }
+            Err(e) => HttpResponseType::SimulateTransaction(
+                response_metadata,
+                SimulateTransactionResponse {
+                    okay: false,
+                    txid: format!("0x{}", txid.to_hex()),
+                    receipt: None,
+                    cause: Some(e.to_string()),
+                },
+            ),
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET to fetch a contract's source code, given the chain tip.  Optionally returns a
     /// MARF proof as well.
     fn handle_get_contract_src<W: Write>(
@@ -1279,6 +1653,66 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a GET to page through the indexed events emitted by (or about the assets of) a
+    /// contract in the fork ending at `tip`.  Only works if the event index is enabled.
+    fn handle_get_contract_events<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        event_type: &Option<String>,
+        principal: &Option<PrincipalData>,
+        cursor: &Option<EventCursor>,
+        limit: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+        if !chainstate.event_index {
+            let response =
+                HttpResponseType::NotFound(response_metadata, "Event index disabled".into());
+            return response.send(http, fd).map(|_| ());
+        }
+
+        let filter = EventFilter {
+            contract_identifier: Some(QualifiedContractIdentifier::new(
+                contract_addr.clone().into(),
+                contract_name.clone(),
+            )),
+            event_type: event_type.clone(),
+            principal: principal.clone(),
+        };
+        let limit = cmp::min(limit, EVENT_INDEX_MAX_PAGE_SIZE);
+
+        let response = match chainstate.index_conn().and_then(|index_conn| {
+            StacksChainState::get_indexed_events(&index_conn, tip, &filter, cursor.as_ref(), limit)
+        }) {
+            Ok(events) => HttpResponseType::ContractEvents(
+                response_metadata,
+                ContractEventsResponse {
+                    limit,
+                    next_cursor: events
+                        .last()
+                        .map(|last_event| EventCursor::from(last_event).to_string()),
+                    events: events
+                        .into_iter()
+                        .map(|indexed_event| RPCIndexedEvent {
+                            index_block_hash: indexed_event.index_block_hash,
+                            block_height: indexed_event.block_height,
+                            event: indexed_event.event,
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => HttpResponseType::ServerError(
+                response_metadata,
+                format!("Failed to query events: {:?}", &e),
+            ),
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET unconfirmed microblock stream.  Start streaming the reply.
     /// The response's preamble (but not the block data) will be synchronously written to the fd
     /// (so use a fd that can buffer!)
@@ -1418,6 +1852,78 @@ impl ConversationHttp {
         return response.send(http, fd).map(|_| ());
     }
 
+    /// Handle a GET confirmed transaction, mined in the fork ending at `tip`.
+    /// The response will be synchronously written to the fd.
+    fn handle_gettransaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        txid: &Txid,
+    ) -> Result<(), net_error> {
+        let response_metadata = HttpResponseMetadata::from(req);
+
+        let info_res = chainstate.index_conn().and_then(|index_conn| {
+            StacksChainState::get_confirmed_transaction_info(&index_conn, tip, txid)
+        });
+        let info = match info_res {
+            Ok(Some(info)) => info,
+            Ok(None) => {
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    format!("No such confirmed transaction {}", txid),
+                );
+                return response.send(http, fd).map(|_| ());
+            }
+            Err(e) => {
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to query transaction {}: {:?}", txid, &e),
+                );
+                return response.send(http, fd).map(|_| ());
+            }
+        };
+
+        let tx_and_header_res = chainstate
+            .load_confirmed_transaction(&info)
+            .and_then(|tx_opt| {
+                StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    chainstate.db(),
+                    &info.index_block_hash,
+                )
+                .map(|header_opt| tx_opt.and_then(|tx| header_opt.map(|header| (tx, header))))
+            });
+        let response = match tx_and_header_res {
+            Ok(Some((tx, header))) => HttpResponseType::ConfirmedTransaction(
+                response_metadata,
+                ConfirmedTransactionResponse {
+                    tx: to_hex(&tx.serialize_to_vec()),
+                    index_block_hash: info.index_block_hash,
+                    block_hash: header.anchored_header.block_hash(),
+                    block_height: info.block_height,
+                    tx_index: info.tx_index,
+                    microblock_hash: info.microblock_hash,
+                    microblock_sequence: info.microblock_sequence,
+                    receipt: RPCTransactionReceipt {
+                        result: format!("0x{}", info.result.serialize()),
+                        post_condition_aborted: info.post_condition_aborted,
+                        execution_cost: info.execution_cost,
+                    },
+                },
+            ),
+            Ok(None) => HttpResponseType::NotFound(
+                response_metadata,
+                format!("Block data for transaction {} is not available", txid),
+            ),
+            Err(e) => HttpResponseType::ServerError(
+                response_metadata,
+                format!("Failed to load transaction {}: {:?}", txid, &e),
+            ),
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
     /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
     /// will return different values here.
@@ -1729,6 +2235,7 @@ impl ConversationHttp {
         let mut reply = self.connection.make_relay_handle(self.conn_id)?;
         let keep_alive = req.metadata().keep_alive;
         let mut ret = None;
+        let mut new_subscription = None;
 
         let stream_opt = match req {
             HttpRequestType::GetInfo(ref _md) => {
@@ -1786,6 +2293,47 @@ impl ConversationHttp {
                     chainstate,
                 )?
             }
+            HttpRequestType::GetBlockByHeight(ref _md, ref height, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_getblock_by_height(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        &tip,
+                        *height,
+                        chainstate,
+                    )?
+                } else {
+                    None
+                }
+            }
+            HttpRequestType::GetHeaderByHeight(ref _md, ref height, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_getheader_by_height(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        &tip,
+                        *height,
+                        chainstate,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetMicroblocksIndexed(ref _md, ref index_head_hash) => {
                 ConversationHttp::handle_getmicroblocks_indexed(
                     &mut self.connection.protocol,
@@ -1827,7 +2375,7 @@ impl ConversationHttp {
                 )?;
                 None
             }
-            HttpRequestType::GetAccount(ref _md, ref principal, ref tip_opt, ref with_proof) => {
+            HttpRequestType::GetTransaction(ref _md, ref txid, ref tip_opt) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
@@ -1836,22 +2384,42 @@ impl ConversationHttp {
                     sortdb,
                     chainstate,
                 )? {
-                    ConversationHttp::handle_get_account_entry(
+                    ConversationHttp::handle_gettransaction(
                         &mut self.connection.protocol,
                         &mut reply,
                         &req,
-                        sortdb,
                         chainstate,
                         &tip,
-                        principal,
-                        *with_proof,
+                        txid,
                     )?;
                 }
                 None
             }
-            HttpRequestType::GetMapEntry(
-                ref _md,
-                ref contract_addr,
+            HttpRequestType::GetAccount(ref _md, ref principal, ref tip_opt, ref with_proof) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_account_entry(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        principal,
+                        *with_proof,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetMapEntry(
+                ref _md,
+                ref contract_addr,
                 ref contract_name,
                 ref map_name,
                 ref key,
@@ -1882,6 +2450,27 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::PostMultiProof(ref _md, ref keys, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_post_multi_proof(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        keys,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetTransferCost(ref _md) => {
                 ConversationHttp::handle_token_transfer_cost(
                     &mut self.connection.protocol,
@@ -1890,6 +2479,27 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::PostFeeRateEstimate(ref _md, ref payload, ref estimated_len) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    None,
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_post_fee_rate_estimate(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        payload,
+                        *estimated_len,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetContractABI(
                 ref _md,
                 ref contract_addr,
@@ -1917,6 +2527,40 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetContractEvents(
+                ref _md,
+                ref contract_addr,
+                ref contract_name,
+                ref event_type,
+                ref principal,
+                ref cursor,
+                ref limit,
+                ref tip_opt,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_get_contract_events(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        contract_addr,
+                        contract_name,
+                        event_type,
+                        principal,
+                        cursor,
+                        *limit,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::CallReadOnlyFunction(
                 ref _md,
                 ref ctrct_addr,
@@ -2065,6 +2709,28 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::PostSimulateTransaction(ref _md, ref tx, ref tip_opt) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_opt.as_ref(),
+                    sortdb,
+                    chainstate,
+                )? {
+                    ConversationHttp::handle_post_simulate_transaction(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        tx,
+                        &self.connection.options,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::PostMicroblock(ref _md, ref mblock, ref tip_opt) => {
                 if let Some((consensus_hash, block_hash)) =
                     ConversationHttp::handle_load_stacks_chain_tip_hashes(
@@ -2097,6 +2763,29 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::Subscribe(ref _md, ref filter) => {
+                let response_metadata = HttpResponseMetadata::from(&req);
+                if self.subscriptions_full && self.subscription.is_none() {
+                    debug!(
+                        "Too many event subscribers; rejecting subscription from {:?}",
+                        &self
+                    );
+                    let response = HttpResponseType::ServiceUnavailable(
+                        response_metadata,
+                        "Too many event subscribers".to_string(),
+                    );
+                    response
+                        .send(&mut self.connection.protocol, &mut reply)
+                        .map(|_| ())?;
+                } else {
+                    let response = HttpResponseType::EventStream(response_metadata);
+                    response
+                        .send(&mut self.connection.protocol, &mut reply)
+                        .map(|_| ())?;
+                    new_subscription = Some(filter.clone());
+                }
+                None
+            }
             HttpRequestType::OptionsPreflight(ref _md, ref _path) => {
                 let response_metadata = HttpResponseMetadata::from(&req);
                 let response = HttpResponseType::OptionsPreflight(response_metadata);
@@ -2124,6 +2813,16 @@ impl ConversationHttp {
             }
         };
 
+        if let Some(filter) = new_subscription {
+            // the event stream never ends, so it doesn't count as a reply
+            self.subscription = Some((
+                Some(reply),
+                HttpChunkedTransferWriterState::new(STREAM_CHUNK_SIZE as usize),
+                filter,
+            ));
+            return Ok(ret);
+        }
+
         match stream_opt {
             None => {
                 self.reply_streams.push_back((reply, None, keep_alive));
@@ -2252,6 +2951,41 @@ impl ConversationHttp {
             }
         }
 
+        // once all replies are sent, stream any pending events to our subscriber
+        if self.reply_streams.len() == 0 {
+            if let Some((ref mut reply_opt, ref mut http_chunk_state, _)) = self.subscription {
+                if reply_opt.is_none() && self.pending_events.len() > 0 {
+                    let mut reply = self.connection.make_relay_handle(self.conn_id)?;
+                    {
+                        let mut encoder = HttpChunkedTransferWriter::from_writer_state(
+                            &mut reply,
+                            http_chunk_state,
+                        );
+                        while let Some(frame) = self.pending_events.pop_front() {
+                            encoder
+                                .write_all(&frame)
+                                .and_then(|_| encoder.flush_buffered())
+                                .map_err(|e| {
+                                    test_debug!("Write error on event stream: {:?}", &e);
+                                    net_error::WriteError(e)
+                                })?;
+                        }
+                    }
+                    self.last_response_timestamp = get_epoch_time_secs();
+                    *reply_opt = Some(reply);
+                }
+
+                let flushed = match reply_opt {
+                    Some(ref mut reply) => reply.try_flush()?,
+                    None => false,
+                };
+                if flushed {
+                    // this batch of events is on its way
+                    *reply_opt = None;
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -2326,11 +3060,70 @@ impl ConversationHttp {
     }
 
     /// Is the connection idle?
+    /// A connection with an event subscription is never idle.
     pub fn is_idle(&self) -> bool {
         self.pending_response.is_none()
             && self.connection.inbox_len() == 0
             && self.connection.outbox_len() == 0
             && self.reply_streams.len() == 0
+            && self.subscription.is_none()
+    }
+
+    /// Is the remote peer subscribed to events?
+    pub fn is_subscribed(&self) -> bool {
+        self.subscription.is_some()
+    }
+
+    /// Tell the conversation whether or not the node has as many event subscribers as it allows
+    /// (see `ConnectionOptions::max_subscribers`).  If so, new subscriptions are refused.
+    pub fn set_subscriptions_full(&mut self, full: bool) {
+        self.subscriptions_full = full;
+    }
+
+    /// Are there events waiting to be streamed to the remote peer?
+    pub fn has_pending_events(&self) -> bool {
+        self.pending_events.len() > 0
+    }
+
+    /// Queue up an event to stream to the remote peer, if it's subscribed to it.  `frame` is the
+    /// event's encoding as a server-sent event.
+    /// Fails if the remote peer has too many events waiting, in which case it's too slow to keep
+    /// up and should be disconnected.
+    pub fn publish_event(
+        &mut self,
+        event: &SubscriptionEvent,
+        frame: &[u8],
+    ) -> Result<(), net_error> {
+        let subscribed = match self.subscription {
+            Some((_, _, ref filter)) => filter.matches(event),
+            None => false,
+        };
+        if !subscribed {
+            return Ok(());
+        }
+        if self.pending_events.len() >= SUBSCRIPTION_MAX_BACKLOG {
+            debug!(
+                "{:?}: Subscriber has {} events pending",
+                &self,
+                self.pending_events.len()
+            );
+            return Err(net_error::OutboxOverflow);
+        }
+        self.pending_events.push_back(frame.to_vec());
+        Ok(())
+    }
+
+    /// If we haven't sent the subscribed remote peer anything in `interval` seconds, then queue up
+    /// a heartbeat so neither side times out the connection.
+    pub fn heartbeat(&mut self, interval: u64) -> () {
+        if self.subscription.is_none() || self.pending_events.len() > 0 {
+            return;
+        }
+        let last_sent = cmp::max(self.last_response_timestamp, self.connection_time);
+        if last_sent + interval <= get_epoch_time_secs() {
+            self.pending_events
+                .push_back(SUBSCRIPTION_HEARTBEAT_FRAME.to_vec());
+        }
     }
 
     /// Is the conversation out of pending data?
@@ -2394,6 +3187,12 @@ impl ConversationHttp {
 
             match msg {
                 StacksHttpMessage::Request(req) => {
+                    if self.subscription.is_some() {
+                        // the event stream never ends, so there's no way to reply
+                        test_debug!("{:?}: Dropping HTTP request on event stream", &self);
+                        continue;
+                    }
+
                     // new request
                     self.total_request_count += 1;
                     self.last_request_timestamp = get_epoch_time_secs();
@@ -2516,6 +3315,32 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new get-block-by-height request to this endpoint
+    pub fn new_getblock_by_height(
+        &self,
+        height: u64,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetBlockByHeight(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            height,
+            tip_opt,
+        )
+    }
+
+    /// Make a new get-header-by-height request to this endpoint
+    pub fn new_getheader_by_height(
+        &self,
+        height: u64,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetHeaderByHeight(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            height,
+            tip_opt,
+        )
+    }
+
     /// Make a new get-microblocks request to this endpoint
     pub fn new_getmicroblocks_indexed(
         &self,
@@ -2559,6 +3384,19 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new get-confirmed-tx request
+    pub fn new_gettransaction(
+        &self,
+        txid: Txid,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            txid,
+            tip_opt,
+        )
+    }
+
     /// Make a new post-transaction request
     pub fn new_post_transaction(&self, tx: StacksTransaction) -> HttpRequestType {
         HttpRequestType::PostTransaction(
@@ -2626,6 +3464,19 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for a MARF multi-proof
+    pub fn new_post_multi_proof(
+        &self,
+        keys: Vec<MultiProofKey>,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::PostMultiProof(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            keys,
+            tip_opt,
+        )
+    }
+
     /// Make a new request to get a contract's source
     pub fn new_getcontractsrc(
         &self,
@@ -2658,6 +3509,37 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request to page through a contract's indexed events
+    pub fn new_getcontractevents(
+        &self,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        event_type: Option<String>,
+        principal: Option<PrincipalData>,
+        cursor: Option<EventCursor>,
+        limit: u64,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::GetContractEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            contract_addr,
+            contract_name,
+            event_type,
+            principal,
+            cursor,
+            limit,
+            tip_opt,
+        )
+    }
+
+    /// Make a new request to subscribe to events
+    pub fn new_subscribe(&self, filter: SubscriptionFilter) -> HttpRequestType {
+        HttpRequestType::Subscribe(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            filter,
+        )
+    }
+
     /// Make a new request to run a read-only function
     pub fn new_callreadonlyfunction(
         &self,
@@ -2679,6 +3561,19 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request to simulate a transaction
+    pub fn new_post_simulate_transaction(
+        &self,
+        tx: StacksTransaction,
+        tip_opt: Option<StacksBlockId>,
+    ) -> HttpRequestType {
+        HttpRequestType::PostSimulateTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone()),
+            tx,
+            tip_opt,
+        )
+    }
+
     /// Make a new request for attachment inventory page
     pub fn new_getattachmentsinv(
         &self,
@@ -2717,6 +3612,7 @@ mod test {
     use chainstate::stacks::test::*;
     use chainstate::stacks::Error as chain_error;
     use chainstate::stacks::*;
+    use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 
     use address::*;
 
@@ -2726,6 +3622,7 @@ mod test {
 
     use std::convert::TryInto;
 
+    use vm::database::NULL_BURN_STATE_DB;
     use vm::types::*;
 
     const TEST_CONTRACT: &'static str = "
@@ -2741,6 +3638,13 @@ mod test {
         (begin
           (map-set unit-map { account: 'ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R } { units: 123 }))";
 
+    const TEST_EVENTS_CONTRACT: &'static str = "
+        (define-fungible-token stackaroo)
+        (begin
+          (print \"hello world\")
+          (print \"goodbye world\")
+          (ft-mint? stackaroo u100 'ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R))";
+
     fn convo_send_recv(
         sender: &mut ConversationHttp,
         sender_chainstate: &mut StacksChainState,
@@ -2847,6 +3751,10 @@ mod test {
         let mut peer_1 = TestPeer::new(peer_1_config);
         let mut peer_2 = TestPeer::new(peer_2_config);
 
+        // the server indexes events
+        peer_2.coord.chainstate_mut().event_index = true;
+        peer_2.chainstate().event_index = true;
+
         // mine one block with a contract in it
         // first the coinbase
         // make a coinbase for this miner
@@ -2880,6 +3788,25 @@ mod test {
         tx_signer.sign_origin(&privk1).unwrap();
         let tx_contract_signed = tx_signer.get_tx().unwrap();
 
+        // and a contract that emits events, for the event index
+        let mut tx_events_contract = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk2).unwrap(),
+            TransactionPayload::new_smart_contract(
+                &format!("hello-events"),
+                &TEST_EVENTS_CONTRACT.to_string(),
+            )
+            .unwrap(),
+        );
+
+        tx_events_contract.chain_id = 0x80000000;
+        tx_events_contract.auth.set_origin_nonce(0);
+        tx_events_contract.set_tx_fee(0);
+
+        let mut tx_signer = StacksTransactionSigner::new(&tx_events_contract);
+        tx_signer.sign_origin(&privk2).unwrap();
+        let tx_events_contract_signed = tx_signer.get_tx().unwrap();
+
         // update account and state in a microblock that will be unconfirmed
         let mut tx_cc = StacksTransaction::new(
             TransactionVersion::Testnet,
@@ -2935,7 +3862,7 @@ mod test {
         let mut anchor_size = 0;
 
         // make a block and a microblock.
-        // Put the coinbase and smart-contract in the anchored block.
+        // Put the coinbase and smart-contracts in the anchored block.
         // Put the contract-call in the microblock
         let (burn_ops, stacks_block, microblocks) = peer_1.make_tenure(
             |ref mut miner, ref mut sortdb, ref mut chainstate, vrf_proof, ref parent_opt, _| {
@@ -2972,7 +3899,11 @@ mod test {
                         block_builder,
                         chainstate,
                         &sortdb.index_conn(),
-                        vec![tx_coinbase_signed.clone(), tx_contract_signed.clone()],
+                        vec![
+                            tx_coinbase_signed.clone(),
+                            tx_contract_signed.clone(),
+                            tx_events_contract_signed.clone(),
+                        ],
                     )
                     .unwrap();
 
@@ -3438,45 +4369,204 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_get_indexed_microblocks() {
-        let server_microblocks_cell = RefCell::new(vec![]);
+    fn test_rpc_getblock_by_height() {
+        let server_tip_cell = RefCell::new(None);
 
         test_rpc(
-            "test_rpc_indexed_microblocks",
-            40040,
-            40041,
-            50040,
-            50041,
+            "test_rpc_getblock_by_height",
+            40210,
+            40211,
+            50210,
+            50211,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let privk = StacksPrivateKey::from_hex(
-                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
-                )
-                .unwrap();
-
-                let parent_block = make_codec_test_block(25);
-                let parent_consensus_hash = ConsensusHash([0x02; 20]);
-                let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
-                    &parent_consensus_hash,
-                    &parent_block.block_hash(),
-                );
-
-                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
-                mblocks.truncate(15);
-
-                let mut child_block = make_codec_test_block(25);
-                let child_consensus_hash = ConsensusHash([0x03; 20]);
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let (_, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
+                peer_server.sortdb = Some(sortdb);
 
-                child_block.header.parent_block = parent_block.block_hash();
-                child_block.header.parent_microblock =
-                    mblocks.last().as_ref().unwrap().block_hash();
-                child_block.header.parent_microblock_sequence =
-                    mblocks.last().as_ref().unwrap().header.sequence;
+                *server_tip_cell.borrow_mut() = Some(block_hash);
 
-                store_staging_block(
-                    peer_server.chainstate(),
+                // the server has mined one block
+                convo_client.new_getblock_by_height(1, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::Block(response_md, block_info) => {
+                        assert_eq!(
+                            block_info.block_hash(),
+                            (*server_tip_cell.borrow()).clone().unwrap()
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getheader_by_height() {
+        let server_tip_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_getheader_by_height",
+            40220,
+            40221,
+            50220,
+            50221,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                *server_tip_cell.borrow_mut() = Some(StacksBlockHeader::make_index_block_hash(
+                    &consensus_hash,
+                    &block_hash,
+                ));
+
+                convo_client.new_getheader_by_height(1, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::BlockHeader(response_md, header_info) => {
+                        assert_eq!(
+                            header_info.index_block_hash,
+                            (*server_tip_cell.borrow()).clone().unwrap()
+                        );
+                        assert_eq!(header_info.block_height, 1);
+
+                        let header = StacksBlockHeader::consensus_deserialize(
+                            &mut &hex_bytes(&header_info.header).unwrap()[..],
+                        )
+                        .unwrap();
+                        assert_eq!(header.block_hash(), header_info.block_hash);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getheader_by_height_genesis() {
+        test_rpc(
+            "test_rpc_getheader_by_height_genesis",
+            40300,
+            40301,
+            50300,
+            50301,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| { convo_client.new_getheader_by_height(0, None) },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::BlockHeader(response_md, header_info) => {
+                        assert_eq!(
+                            header_info.index_block_hash,
+                            StacksBlockHeader::make_index_block_hash(
+                                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                                &FIRST_STACKS_BLOCK_HASH
+                            )
+                        );
+                        assert_eq!(header_info.block_height, 0);
+                        assert_eq!(header_info.parent_block_id, StacksBlockId::sentinel());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_getheader_by_height() {
+        test_rpc(
+            "test_rpc_missing_getheader_by_height",
+            40230,
+            40231,
+            50230,
+            50231,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // the server has only mined one block
+                convo_client.new_getheader_by_height(2, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_indexed_microblocks() {
+        let server_microblocks_cell = RefCell::new(vec![]);
+
+        test_rpc(
+            "test_rpc_indexed_microblocks",
+            40040,
+            40041,
+            50040,
+            50041,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "eb05c83546fdd2c79f10f5ad5434a90dd28f7e3acb7c092157aa1bc3656b012c01",
+                )
+                .unwrap();
+
+                let parent_block = make_codec_test_block(25);
+                let parent_consensus_hash = ConsensusHash([0x02; 20]);
+                let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
+                    &parent_consensus_hash,
+                    &parent_block.block_hash(),
+                );
+
+                let mut mblocks = make_sample_microblock_stream(&privk, &parent_block.block_hash());
+                mblocks.truncate(15);
+
+                let mut child_block = make_codec_test_block(25);
+                let child_consensus_hash = ConsensusHash([0x03; 20]);
+
+                child_block.header.parent_block = parent_block.block_hash();
+                child_block.header.parent_microblock =
+                    mblocks.last().as_ref().unwrap().block_hash();
+                child_block.header.parent_microblock_sequence =
+                    mblocks.last().as_ref().unwrap().header.sequence;
+
+                store_staging_block(
+                    peer_server.chainstate(),
                     &parent_consensus_hash,
                     &parent_block,
                     &ConsensusHash([0x01; 20]),
@@ -3793,6 +4883,311 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_confirmed_transaction() {
+        let server_txid_cell = RefCell::new(None);
+
+        test_rpc(
+            "test_rpc_confirmed_transaction",
+            40240,
+            40241,
+            50240,
+            50241,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let sortdb = peer_server.sortdb.take().unwrap();
+                let (consensus_hash, block_hash) =
+                    SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                // the server's tip has the coinbase and the smart contract
+                let block = StacksChainState::load_block(
+                    &peer_server.chainstate().blocks_path,
+                    &consensus_hash,
+                    &block_hash,
+                )
+                .unwrap()
+                .unwrap();
+                let txid = block.txs[1].txid();
+
+                *server_txid_cell.borrow_mut() = Some(txid.clone());
+
+                convo_client.new_gettransaction(txid, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::ConfirmedTransaction(response_md, confirmed) => {
+                        let tx = StacksTransaction::consensus_deserialize(
+                            &mut &hex_bytes(&confirmed.tx).unwrap()[..],
+                        )
+                        .unwrap();
+                        assert_eq!(tx.txid(), (*server_txid_cell.borrow()).clone().unwrap());
+                        assert_eq!(confirmed.block_height, 1);
+                        assert_eq!(confirmed.tx_index, 1);
+                        assert_eq!(confirmed.microblock_hash, None);
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&confirmed.receipt.result).unwrap(),
+                            Value::okay_true()
+                        );
+                        assert!(!confirmed.receipt.post_condition_aborted);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_missing_confirmed_transaction() {
+        test_rpc(
+            "test_rpc_missing_confirmed_transaction",
+            40250,
+            40251,
+            50250,
+            50251,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_gettransaction(Txid([0x11; 32]), None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events() {
+        test_rpc(
+            "test_rpc_get_contract_events",
+            40260,
+            40261,
+            50260,
+            50261,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_getcontractevents(
+                    StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap(),
+                    "hello-events".try_into().unwrap(),
+                    None,
+                    None,
+                    None,
+                    2,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::ContractEvents(response_md, data) => {
+                        assert_eq!(data.limit, 2);
+                        assert_eq!(data.events.len(), 2);
+                        for (i, event) in data.events.iter().enumerate() {
+                            assert_eq!(event.block_height, 1);
+                            assert_eq!(event.event["event_index"], i);
+                            assert_eq!(event.event["type"], "contract_event");
+                            assert_eq!(
+                                event.event["contract_event"]["contract_identifier"],
+                                "STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW.hello-events"
+                            );
+                        }
+                        assert_eq!(data.next_cursor, Some("1-1".to_string()));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events_next_page() {
+        test_rpc(
+            "test_rpc_get_contract_events_next_page",
+            40310,
+            40311,
+            50310,
+            50311,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // the page after the two prints only has the mint
+                convo_client.new_getcontractevents(
+                    StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap(),
+                    "hello-events".try_into().unwrap(),
+                    None,
+                    None,
+                    EventCursor::parse("1-1"),
+                    2,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::ContractEvents(response_md, data) => {
+                        assert_eq!(data.events.len(), 1);
+                        assert_eq!(data.events[0].event["event_index"], 2);
+                        assert_eq!(data.events[0].event["type"], "ft_mint_event");
+                        assert_eq!(data.next_cursor, Some("1-2".to_string()));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events_filtered() {
+        test_rpc(
+            "test_rpc_get_contract_events_filtered",
+            40270,
+            40271,
+            50270,
+            50271,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // only the mint involves this principal
+                convo_client.new_getcontractevents(
+                    StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap(),
+                    "hello-events".try_into().unwrap(),
+                    None,
+                    Some(
+                        PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+                    ),
+                    None,
+                    10,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::ContractEvents(response_md, data) => {
+                        assert_eq!(data.events.len(), 1);
+                        assert_eq!(data.events[0].event["type"], "ft_mint_event");
+                        assert_eq!(
+                            data.events[0].event["ft_mint_event"]["recipient"],
+                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R"
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events_by_type() {
+        test_rpc(
+            "test_rpc_get_contract_events_by_type",
+            40320,
+            40321,
+            50320,
+            50321,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // there are no prints after the second one
+                convo_client.new_getcontractevents(
+                    StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap(),
+                    "hello-events".try_into().unwrap(),
+                    Some("contract_event".to_string()),
+                    None,
+                    EventCursor::parse("1-1"),
+                    10,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::ContractEvents(response_md, data) => {
+                        assert_eq!(data.events.len(), 0);
+                        assert_eq!(data.next_cursor, None);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events_disabled() {
+        test_rpc(
+            "test_rpc_get_contract_events_disabled",
+            40280,
+            40281,
+            50280,
+            50281,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                peer_server.chainstate().event_index = false;
+                convo_client.new_getcontractevents(
+                    StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW").unwrap(),
+                    "hello-events".try_into().unwrap(),
+                    None,
+                    None,
+                    None,
+                    10,
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_missing_getblock() {
@@ -4235,6 +5630,144 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_multi_proof() {
+        test_rpc(
+            "test_rpc_multi_proof",
+            40290,
+            40291,
+            50290,
+            50291,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let principal =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                let other_principal =
+                    StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW")
+                        .unwrap()
+                        .to_account_principal();
+                let contract = QualifiedContractIdentifier::new(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .into(),
+                    "hello-world".try_into().unwrap(),
+                );
+                convo_client.new_post_multi_proof(
+                    vec![
+                        MultiProofKey::Account(principal.clone()),
+                        MultiProofKey::DataVar(contract.clone(), "bar".try_into().unwrap()),
+                        MultiProofKey::MapEntry(
+                            contract.clone(),
+                            "unit-map".try_into().unwrap(),
+                            Value::Tuple(
+                                TupleData::from_data(vec![(
+                                    "account".into(),
+                                    Value::Principal(principal),
+                                )])
+                                .unwrap(),
+                            ),
+                        ),
+                        MultiProofKey::MapEntry(
+                            contract,
+                            "unit-map".try_into().unwrap(),
+                            Value::Tuple(
+                                TupleData::from_data(vec![(
+                                    "account".into(),
+                                    Value::Principal(other_principal),
+                                )])
+                                .unwrap(),
+                            ),
+                        ),
+                    ],
+                    None,
+                )
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::MultiProof(response_md, data) => {
+                        // balance, nonce, var, and map entry, but nothing for the unset entry
+                        assert_eq!(data.entries.len(), 5);
+                        assert!(data.entries[..4].iter().all(|entry| entry.data.is_some()));
+                        assert!(data.entries[4].data.is_none());
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(
+                                data.entries[3].data.as_ref().unwrap()
+                            )
+                            .unwrap(),
+                            Value::some(Value::Tuple(
+                                TupleData::from_data(vec![("units".into(), Value::Int(123))])
+                                    .unwrap()
+                            ))
+                            .unwrap()
+                        );
+
+                        // check the proof the way a light client would: against the tip
+                        // header's state root, using the state roots of its ancestors' headers
+                        let chainstate = peer_server.chainstate();
+                        let tip =
+                            StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                                chainstate.db(),
+                                &data.index_block_hash,
+                            )
+                            .unwrap()
+                            .unwrap();
+                        let mut root_to_block = HashMap::new();
+                        for height in 0..(tip.block_height + 1) {
+                            let header = StacksChainState::get_index_tip_ancestor_conn(
+                                &chainstate.index_conn().unwrap(),
+                                &data.index_block_hash,
+                                height,
+                            )
+                            .unwrap()
+                            .unwrap();
+                            root_to_block.insert(
+                                header.anchored_header.state_index_root.clone(),
+                                header.index_block_hash(),
+                            );
+                        }
+
+                        let proof_bytes = hex_bytes(&data.proof[2..]).unwrap();
+                        let multi_proof = TrieMultiProof::<StacksBlockId>::consensus_deserialize(
+                            &mut &proof_bytes[..],
+                        )
+                        .unwrap();
+                        let proven: Vec<_> = data
+                            .entries
+                            .iter()
+                            .filter_map(|entry| {
+                                entry.data.clone().map(|data| (entry.key.clone(), data))
+                            })
+                            .collect();
+                        assert!(multi_proof.verify_entries(
+                            &proven,
+                            &tip.anchored_header.state_index_root,
+                            &root_to_block
+                        ));
+
+                        // a wrong balance doesn't verify
+                        let mut bad_entries = proven.clone();
+                        bad_entries[0].1 = ClaritySerializable::serialize(&STXBalance::zero());
+                        assert!(!multi_proof.verify_entries(
+                            &bad_entries,
+                            &tip.anchored_header.state_index_root,
+                            &root_to_block
+                        ));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_get_contract_abi() {
@@ -4355,6 +5888,169 @@ mod test {
         );
     }
 
+    fn make_simulated_contract_call(nonce: u64, args: Vec<Value>) -> StacksTransaction {
+        // ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R, which published hello-world
+        let privk = StacksPrivateKey::from_hex(
+            "9f1f85a512a96a244e4c0d762788500687feb97481639572e3bffbd6860e6ab001",
+        )
+        .unwrap();
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk).unwrap(),
+            TransactionPayload::new_contract_call(
+                StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+                "hello-world",
+                "set-bar",
+                args,
+            )
+            .unwrap(),
+        );
+        tx.chain_id = 0x80000000;
+        tx.auth.set_origin_nonce(nonce);
+        tx.set_tx_fee(100);
+        tx
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_transaction() {
+        test_rpc(
+            "test_rpc_simulate_transaction",
+            40190,
+            40191,
+            50190,
+            50191,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // the transaction does not need to be signed
+                let tx = make_simulated_contract_call(2, vec![Value::Int(6), Value::Int(2)]);
+                convo_client.new_post_simulate_transaction(tx, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::SimulateTransaction(_md, data) => {
+                        assert!(data.okay);
+                        assert!(data.cause.is_none());
+
+                        let receipt = data.receipt.clone().unwrap();
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&receipt.result).unwrap(),
+                            Value::okay(Value::Int(3)).unwrap()
+                        );
+                        assert!(!receipt.post_condition_aborted);
+                        assert_eq!(receipt.fee, 100);
+                        assert!(receipt.execution_cost.runtime > 0);
+
+                        // nothing was committed
+                        let (consensus_hash, block_hash) =
+                            SortitionDB::get_canonical_stacks_chain_tip_hash(
+                                peer_server.sortdb.as_ref().unwrap().conn(),
+                            )
+                            .unwrap();
+                        let account = peer_server
+                            .chainstate()
+                            .with_read_only_clarity_tx(
+                                &NULL_BURN_STATE_DB,
+                                &StacksBlockHeader::make_index_block_hash(
+                                    &consensus_hash,
+                                    &block_hash,
+                                ),
+                                |clarity_tx| {
+                                    StacksChainState::get_account(
+                                        clarity_tx,
+                                        &StacksAddress::from_string(
+                                            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R",
+                                        )
+                                        .unwrap()
+                                        .to_account_principal(),
+                                    )
+                                },
+                            )
+                            .unwrap();
+                        assert_eq!(account.nonce, 2);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_transaction_bad_nonce() {
+        test_rpc(
+            "test_rpc_simulate_transaction_bad_nonce",
+            40200,
+            40201,
+            50200,
+            50201,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let tx = make_simulated_contract_call(0, vec![Value::Int(6), Value::Int(2)]);
+                convo_client.new_post_simulate_transaction(tx, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::SimulateTransaction(_md, data) => {
+                        assert!(!data.okay);
+                        assert!(data.receipt.is_none());
+                        assert!(data.cause.clone().unwrap().find("nonce").is_some());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_transaction_over_cost_limit() {
+        test_rpc(
+            "test_rpc_simulate_transaction_over_cost_limit",
+            40330,
+            40331,
+            50330,
+            50331,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // a contract-call is estimated to need more reads than this
+                convo_server
+                    .connection
+                    .options
+                    .read_only_call_limit
+                    .read_count = 1;
+                let tx = make_simulated_contract_call(2, vec![Value::Int(6), Value::Int(2)]);
+                convo_client.new_post_simulate_transaction(tx, None)
+            },
+            |ref http_request, ref http_response, ref mut peer_client, ref mut peer_server| {
+                match http_response {
+                    HttpResponseType::BadRequest(_md, msg) => {
+                        assert!(msg.find("simulation limit").is_some());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_call_read_only_unconfirmed() {
@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
 use std::io::Error as io_error;
 use std::io::ErrorKind;
 use std::io::{Read, Write};
@@ -38,6 +39,7 @@ use net::http::*;
 use net::p2p::PeerMap;
 use net::poll::*;
 use net::rpc::*;
+use net::subscriptions::SubscriptionEvent;
 use net::Error as net_error;
 use net::*;
 
@@ -81,6 +83,9 @@ pub struct HttpPeer {
 
     // connection options
     pub connection_opts: ConnectionOptions,
+
+    // events to stream to subscribed clients, if the node publishes them
+    subscriptions: Option<Receiver<SubscriptionEvent>>,
 }
 
 impl HttpPeer {
@@ -102,6 +107,7 @@ impl HttpPeer {
 
             burnchain: burnchain,
             connection_opts: conn_opts,
+            subscriptions: None,
         }
     }
 
@@ -109,6 +115,11 @@ impl HttpPeer {
         self.http_server_handle = h;
     }
 
+    /// Set the channel over which the node publishes events to stream to subscribed clients
+    pub fn set_subscriptions(&mut self, subscriptions: Receiver<SubscriptionEvent>) -> () {
+        self.subscriptions = Some(subscriptions);
+    }
+
     /// Is there a HTTP conversation open to this data_url that is not in progress?
     pub fn find_free_conversation(&self, data_url: &UrlString) -> Option<usize> {
         for (event_id, convo) in self.peers.iter() {
@@ -598,6 +609,11 @@ impl HttpPeer {
     ) -> (Vec<StacksMessageType>, Vec<usize>) {
         let mut to_remove = vec![];
         let mut msgs = vec![];
+        let mut num_subscribers = self
+            .peers
+            .values()
+            .filter(|convo| convo.is_subscribed())
+            .count() as u64;
         for event_id in &poll_state.ready {
             if !self.sockets.contains_key(&event_id) {
                 test_debug!("Rogue socket event {}", event_id);
@@ -617,7 +633,11 @@ impl HttpPeer {
                 Some(ref mut convo) => {
                     // activity on a http socket
                     test_debug!("Process HTTP data from {:?}", convo);
-                    match HttpPeer::process_http_conversation(
+                    let was_subscribed = convo.is_subscribed();
+                    convo.set_subscriptions_full(
+                        num_subscribers >= self.connection_opts.max_subscribers,
+                    );
+                    let result = HttpPeer::process_http_conversation(
                         &self.chain_view,
                         peers,
                         sortdb,
@@ -629,7 +649,11 @@ impl HttpPeer {
                         client_sock,
                         convo,
                         handler_args,
-                    ) {
+                    );
+                    if !was_subscribed && convo.is_subscribed() {
+                        num_subscribers += 1;
+                    }
+                    match result {
                         Ok((alive, mut new_msgs)) => {
                             if !alive {
                                 to_remove.push(*event_id);
@@ -677,6 +701,63 @@ impl HttpPeer {
         close
     }
 
+    /// Stream newly-published events to subscribed conversations, and keep idle subscriptions
+    /// alive.  Subscribers' sockets aren't necessarily ready, so send out what we can now.
+    /// Return the list of conversation event IDs to close (i.e. they're broken, or can't keep up)
+    fn process_subscriptions(&mut self, chainstate: &mut StacksChainState) -> Vec<usize> {
+        let mut close = vec![];
+
+        let mut events = vec![];
+        if let Some(ref subscriptions) = self.subscriptions {
+            loop {
+                match subscriptions.try_recv() {
+                    Ok(event) => {
+                        events.push(event);
+                    }
+                    Err(TryRecvError::Empty) => {
+                        break;
+                    }
+                    Err(TryRecvError::Disconnected) => {
+                        debug!("Event subscription channel disconnected");
+                        break;
+                    }
+                }
+            }
+        }
+
+        let heartbeat_interval = cmp::max(1, self.connection_opts.idle_timeout / 2);
+        for event in events.iter() {
+            let frame = event.frame();
+            for (event_id, convo) in self.peers.iter_mut() {
+                if let Err(e) = convo.publish_event(event, &frame) {
+                    info!("Dropping HTTP event subscriber {:?}: {:?}", convo, &e);
+                    close.push(*event_id);
+                }
+            }
+        }
+
+        for (event_id, convo) in self.peers.iter_mut() {
+            if !convo.is_subscribed() {
+                continue;
+            }
+            convo.heartbeat(heartbeat_interval);
+            if !convo.has_pending_events() {
+                continue;
+            }
+            if let Some(client_sock) = self.sockets.get_mut(event_id) {
+                if let Err(e) = HttpPeer::saturate_http_socket(client_sock, convo, chainstate) {
+                    debug!(
+                        "Failed to send events to HTTP subscriber {:?}: {:?}",
+                        convo, &e
+                    );
+                    close.push(*event_id);
+                }
+            }
+        }
+
+        close
+    }
+
     /// Update HTTP server state
     /// -- accept new connections
     /// -- send data on ready sockets
@@ -721,6 +802,13 @@ impl HttpPeer {
             self.deregister_http(network_state, error_event);
         }
 
+        // stream events to subscribers
+        let close_events = self.process_subscriptions(chainstate);
+        for close_event in close_events {
+            debug!("Close HTTP subscription on event {}", close_event);
+            self.deregister_http(network_state, close_event);
+        }
+
         // move conversations along
         let close_events = self.flush_conversations(chainstate);
         for close_event in close_events {
@@ -746,6 +834,7 @@ mod test {
     use net::codec::*;
     use net::http::*;
     use net::rpc::*;
+    use net::subscriptions::*;
     use net::test::*;
     use net::*;
     use std::cell::RefCell;
@@ -1393,4 +1482,131 @@ mod test {
             |client_id, http_response_bytes_res| true,
         );
     }
+
+    #[test]
+    fn test_http_subscribe() {
+        let peer_config = TestPeerConfig::new("test_http_subscribe", 51090, 51091);
+        let mut peer = TestPeer::new(peer_config);
+
+        let (subscriptions_sx, subscriptions_rx) = sync_channel(SUBSCRIPTION_CHANNEL_CAPACITY);
+        peer.network.http.set_subscriptions(subscriptions_rx);
+
+        let (http_sx, http_rx) = sync_channel(1);
+        let http_thread = thread::spawn(move || loop {
+            peer.step().unwrap();
+
+            // asked to yield?
+            if http_rx.try_recv().is_ok() {
+                break;
+            }
+        });
+
+        let mut sock =
+            TcpStream::connect(&"127.0.0.1:51091".parse::<SocketAddr>().unwrap()).unwrap();
+        sock.set_read_timeout(Some(std::time::Duration::from_secs(60)))
+            .unwrap();
+
+        let mut filter = SubscriptionFilter::default();
+        filter.topics = vec![SubscriptionTopic::Mempool];
+        let request = HttpRequestType::Subscribe(
+            HttpRequestMetadata::from_host(PeerHost::from_host_port(
+                "127.0.0.1".to_string(),
+                51091,
+            )),
+            filter,
+        );
+        sock.write_all(&StacksHttp::serialize_request(&request).unwrap())
+            .unwrap();
+
+        let read_until = |sock: &mut TcpStream, received: &mut String, needle: &str| {
+            while !received.contains(needle) {
+                let mut buf = [0u8; 4096];
+                let nr = sock.read(&mut buf).unwrap();
+                assert!(nr > 0, "Connection closed before receiving '{}'", needle);
+                received.push_str(&String::from_utf8_lossy(&buf[0..nr]));
+            }
+        };
+
+        // the stream starts once the subscription is set up
+        let mut received = String::new();
+        read_until(&mut sock, &mut received, "\r\n\r\n");
+        assert!(received.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(received.contains("Content-Type: text/event-stream\r\n"));
+        assert!(received.contains("Transfer-Encoding: chunked\r\n"));
+
+        // only events on subscribed topics are streamed
+        let block_event = SubscriptionEvent::new(SubscriptionTopic::Blocks, json!({}));
+        let mempool_event = SubscriptionEvent::new(SubscriptionTopic::Mempool, json!(["0x00"]));
+        subscriptions_sx.send(block_event).unwrap();
+        subscriptions_sx.send(mempool_event).unwrap();
+
+        read_until(
+            &mut sock,
+            &mut received,
+            "event: new_mempool_tx\ndata: [\"0x00\"]\n\n",
+        );
+        assert!(!received.contains("new_block"));
+
+        http_sx.send(true).unwrap();
+        http_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_http_subscribe_max_subscribers() {
+        let mut peer_config =
+            TestPeerConfig::new("test_http_subscribe_max_subscribers", 51092, 51093);
+        peer_config.connection_opts.max_subscribers = 1;
+        let mut peer = TestPeer::new(peer_config);
+
+        let (_subscriptions_sx, subscriptions_rx) = sync_channel(SUBSCRIPTION_CHANNEL_CAPACITY);
+        peer.network.http.set_subscriptions(subscriptions_rx);
+
+        let (http_sx, http_rx) = sync_channel(1);
+        let http_thread = thread::spawn(move || loop {
+            peer.step().unwrap();
+
+            // asked to yield?
+            if http_rx.try_recv().is_ok() {
+                break;
+            }
+        });
+
+        let subscribe = || {
+            let mut sock =
+                TcpStream::connect(&"127.0.0.1:51093".parse::<SocketAddr>().unwrap()).unwrap();
+            sock.set_read_timeout(Some(std::time::Duration::from_secs(60)))
+                .unwrap();
+
+            let request = HttpRequestType::Subscribe(
+                HttpRequestMetadata::from_host(PeerHost::from_host_port(
+                    "127.0.0.1".to_string(),
+                    51093,
+                )),
+                SubscriptionFilter::default(),
+            );
+            sock.write_all(&StacksHttp::serialize_request(&request).unwrap())
+                .unwrap();
+
+            let mut received = String::new();
+            while !received.contains("\r\n\r\n") {
+                let mut buf = [0u8; 4096];
+                let nr = sock.read(&mut buf).unwrap();
+                assert!(nr > 0, "Connection closed before receiving a response");
+                received.push_str(&String::from_utf8_lossy(&buf[0..nr]));
+            }
+            (sock, received)
+        };
+
+        // the first subscriber gets its stream...
+        let (_sock_1, received_1) = subscribe();
+        assert!(received_1.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(received_1.contains("Content-Type: text/event-stream\r\n"));
+
+        // ...but there's no room for a second one
+        let (_sock_2, received_2) = subscribe();
+        assert!(received_2.starts_with("HTTP/1.1 503 "));
+
+        http_sx.send(true).unwrap();
+        http_thread.join().unwrap();
+    }
 }
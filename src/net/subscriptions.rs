@@ -0,0 +1,282 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+/// This module contains the events that the RPC server streams to subscribed clients as
+/// server-sent events, and the filters clients use to pick which ones they get.
+use std::fmt;
+use std::str::FromStr;
+
+use chainstate::stacks::db::event_index::get_event_keys;
+use chainstate::stacks::events::StacksTransactionEvent;
+use chainstate::stacks::{StacksTransaction, TransactionPayload};
+
+use net::Error as net_error;
+
+use vm::types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData};
+
+/// How many events the node can queue up for the RPC server before it starts dropping them.
+pub const SUBSCRIPTION_CHANNEL_CAPACITY: usize = 1024;
+
+/// How many events can be waiting to be sent to a single subscriber before the RPC server gives
+/// up on it and closes its connection.
+pub const SUBSCRIPTION_MAX_BACKLOG: usize = 1024;
+
+/// Sent to a subscriber that hasn't been sent anything in a while, so that neither it nor the
+/// RPC server times out the connection.
+pub const SUBSCRIPTION_HEARTBEAT_FRAME: &[u8] = b": heartbeat\n\n";
+
+/// The kinds of events a client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionTopic {
+    Blocks,
+    Microblocks,
+    Mempool,
+    Events,
+}
+
+impl SubscriptionTopic {
+    pub const ALL: [SubscriptionTopic; 4] = [
+        SubscriptionTopic::Blocks,
+        SubscriptionTopic::Microblocks,
+        SubscriptionTopic::Mempool,
+        SubscriptionTopic::Events,
+    ];
+
+    /// The name of the topic, as given in a subscription request
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SubscriptionTopic::Blocks => "blocks",
+            SubscriptionTopic::Microblocks => "microblocks",
+            SubscriptionTopic::Mempool => "mempool",
+            SubscriptionTopic::Events => "events",
+        }
+    }
+
+    /// The name of the server-sent event that carries this topic's payloads.  Where there's an
+    /// event observer endpoint with the same payload, this is that endpoint's name.
+    pub fn event_name(&self) -> &'static str {
+        match *self {
+            SubscriptionTopic::Blocks => "new_block",
+            SubscriptionTopic::Microblocks => "new_microblocks",
+            SubscriptionTopic::Mempool => "new_mempool_tx",
+            SubscriptionTopic::Events => "new_event",
+        }
+    }
+}
+
+impl fmt::Display for SubscriptionTopic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for SubscriptionTopic {
+    type Err = net_error;
+
+    fn from_str(s: &str) -> Result<SubscriptionTopic, net_error> {
+        for topic in SubscriptionTopic::ALL.iter() {
+            if topic.as_str() == s {
+                return Ok(*topic);
+            }
+        }
+        Err(net_error::DeserializeError(format!(
+            "Unknown subscription topic '{}'",
+            s
+        )))
+    }
+}
+
+/// Which events a subscriber wants.  An event is sent if its topic is one of `topics` and it
+/// involves the given contract and principal (if any).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionFilter {
+    pub topics: Vec<SubscriptionTopic>,
+    pub contract: Option<QualifiedContractIdentifier>,
+    pub principal: Option<PrincipalData>,
+}
+
+impl Default for SubscriptionFilter {
+    fn default() -> SubscriptionFilter {
+        SubscriptionFilter {
+            topics: SubscriptionTopic::ALL.to_vec(),
+            contract: None,
+            principal: None,
+        }
+    }
+}
+
+impl SubscriptionFilter {
+    pub fn matches(&self, event: &SubscriptionEvent) -> bool {
+        if !self.topics.contains(&event.topic) {
+            return false;
+        }
+        if let Some(ref contract) = self.contract {
+            if !event.contracts.contains(contract) {
+                return false;
+            }
+        }
+        if let Some(ref principal) = self.principal {
+            if !event.principals.contains(principal) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// An event to stream to subscribers, along with the contracts and principals it involves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionEvent {
+    pub topic: SubscriptionTopic,
+    pub payload: serde_json::Value,
+    pub contracts: Vec<QualifiedContractIdentifier>,
+    pub principals: Vec<PrincipalData>,
+}
+
+impl SubscriptionEvent {
+    pub fn new(topic: SubscriptionTopic, payload: serde_json::Value) -> SubscriptionEvent {
+        SubscriptionEvent {
+            topic,
+            payload,
+            contracts: vec![],
+            principals: vec![],
+        }
+    }
+
+    fn add_contract(&mut self, contract: &QualifiedContractIdentifier) {
+        if !self.contracts.contains(contract) {
+            self.contracts.push(contract.clone());
+        }
+        self.add_principal(&PrincipalData::Contract(contract.clone()));
+    }
+
+    fn add_principal(&mut self, principal: &PrincipalData) {
+        if !self.principals.contains(principal) {
+            self.principals.push(principal.clone());
+        }
+    }
+
+    /// Mark this event as involving a transaction's origin and sponsor, the contract it calls or
+    /// deploys, and the recipient of the STX it transfers.
+    pub fn add_transaction_keys(&mut self, tx: &StacksTransaction) {
+        let origin = tx.origin_address();
+        self.add_principal(&origin.clone().into());
+        if let Some(sponsor) = tx.sponsor_address() {
+            self.add_principal(&sponsor.into());
+        }
+        match tx.payload {
+            TransactionPayload::TokenTransfer(ref recipient, ..) => {
+                self.add_principal(recipient);
+            }
+            TransactionPayload::ContractCall(ref contract_call) => {
+                self.add_contract(&contract_call.to_clarity_contract_id());
+            }
+            TransactionPayload::SmartContract(ref smart_contract) => {
+                self.add_contract(&QualifiedContractIdentifier::new(
+                    StandardPrincipalData::from(origin),
+                    smart_contract.name.clone(),
+                ));
+            }
+            TransactionPayload::PoisonMicroblock(..) | TransactionPayload::Coinbase(..) => {}
+        }
+    }
+
+    /// Mark this event as involving the contract and principals that a transaction event
+    /// involves, i.e. the keys it's stored under in the event index.
+    pub fn add_event_keys(&mut self, event: &StacksTransactionEvent) {
+        let (_, contract_opt, principals) = get_event_keys(event);
+        if let Some(contract) = contract_opt {
+            self.add_contract(contract);
+        }
+        for principal in principals.into_iter() {
+            self.add_principal(principal);
+        }
+    }
+
+    /// Encode this event as a server-sent event
+    pub fn frame(&self) -> Vec<u8> {
+        format!(
+            "event: {}\ndata: {}\n\n",
+            self.topic.event_name(),
+            &self.payload
+        )
+        .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chainstate::stacks::events::SmartContractEventData;
+    use vm::types::Value;
+
+    #[test]
+    fn subscription_filter_matches() {
+        let contract_id =
+            QualifiedContractIdentifier::new(StandardPrincipalData(1, [0x01; 20]), "token".into());
+        let other_contract_id =
+            QualifiedContractIdentifier::new(StandardPrincipalData(1, [0x01; 20]), "other".into());
+        let principal = PrincipalData::from(StandardPrincipalData(1, [0x02; 20]));
+
+        let mut event =
+            SubscriptionEvent::new(SubscriptionTopic::Events, json!({ "txid": "0x00" }));
+        event.add_event_keys(&StacksTransactionEvent::SmartContractEvent(
+            SmartContractEventData {
+                key: (contract_id.clone(), "print".to_string()),
+                value: Value::Int(1),
+            },
+        ));
+        assert_eq!(event.contracts, vec![contract_id.clone()]);
+        assert_eq!(
+            event.principals,
+            vec![PrincipalData::Contract(contract_id.clone())]
+        );
+
+        assert!(SubscriptionFilter::default().matches(&event));
+
+        let mut filter = SubscriptionFilter::default();
+        filter.topics = vec![SubscriptionTopic::Blocks, SubscriptionTopic::Mempool];
+        assert!(!filter.matches(&event));
+
+        let mut filter = SubscriptionFilter::default();
+        filter.contract = Some(contract_id.clone());
+        assert!(filter.matches(&event));
+        filter.contract = Some(other_contract_id);
+        assert!(!filter.matches(&event));
+
+        let mut filter = SubscriptionFilter::default();
+        filter.principal = Some(PrincipalData::Contract(contract_id.clone()));
+        assert!(filter.matches(&event));
+        filter.principal = Some(principal);
+        assert!(!filter.matches(&event));
+    }
+
+    #[test]
+    fn subscription_event_frame() {
+        let event = SubscriptionEvent::new(SubscriptionTopic::Mempool, json!(["0x0011"]));
+        assert_eq!(
+            String::from_utf8(event.frame()).unwrap(),
+            "event: new_mempool_tx\ndata: [\"0x0011\"]\n\n"
+        );
+
+        assert_eq!(
+            "microblocks".parse::<SubscriptionTopic>().unwrap(),
+            SubscriptionTopic::Microblocks
+        );
+        assert!("transactions".parse::<SubscriptionTopic>().is_err());
+    }
+}
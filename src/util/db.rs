@@ -193,6 +193,13 @@ impl FromColumn<i64> for i64 {
     }
 }
 
+impl FromColumn<f64> for f64 {
+    fn from_column<'a>(row: &'a Row, column_name: &str) -> Result<f64, Error> {
+        let x: f64 = row.get_unwrap(column_name);
+        Ok(x)
+    }
+}
+
 impl FromColumn<QualifiedContractIdentifier> for QualifiedContractIdentifier {
     fn from_column<'a>(
         row: &'a Row,
@@ -26,6 +26,7 @@ use vm::types::{parse_name_type_pairs, PrincipalData, TupleTypeSignature, TypeSi
 
 use std::collections::HashMap;
 use vm::variables::NativeVariables;
+use vm::ClarityVersion;
 
 pub use super::errors::{
     check_argument_count, check_arguments_at_least, CheckError, CheckErrors, CheckResult,
@@ -42,7 +43,9 @@ mod tests;
 ///  any database operations, traits, or iterating operations (e.g., list
 ///  operations)
 ///
-pub struct ArithmeticOnlyChecker();
+pub struct ArithmeticOnlyChecker<'a> {
+    clarity_version: &'a ClarityVersion,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Error {
@@ -65,14 +68,16 @@ impl std::fmt::Display for Error {
     }
 }
 
-impl ArithmeticOnlyChecker {
+impl<'a> ArithmeticOnlyChecker<'a> {
     pub fn check_contract_cost_eligible(contract_analysis: &mut ContractAnalysis) {
         let is_eligible = ArithmeticOnlyChecker::run(contract_analysis).is_ok();
         contract_analysis.is_cost_contract_eligible = is_eligible;
     }
 
     pub fn run(contract_analysis: &ContractAnalysis) -> Result<(), Error> {
-        let checker = ArithmeticOnlyChecker();
+        let checker = ArithmeticOnlyChecker {
+            clarity_version: &contract_analysis.clarity_version,
+        };
         for exp in contract_analysis.expressions.iter() {
             checker.check_top_levels(&exp)?;
         }
@@ -154,7 +159,7 @@ impl ArithmeticOnlyChecker {
         function: &str,
         args: &[SymbolicExpression],
     ) -> Option<Result<(), Error>> {
-        NativeFunctions::lookup_by_name(function)
+        NativeFunctions::lookup_by_name_at_version(function, self.clarity_version)
             .map(|function| self.check_native_function(function, args))
     }
 
@@ -165,14 +170,18 @@ impl ArithmeticOnlyChecker {
     ) -> Result<(), Error> {
         use vm::functions::NativeFunctions::*;
         match function {
-            FetchVar | GetBlockInfo | GetTokenBalance | GetAssetOwner | FetchEntry | SetEntry
-            | DeleteEntry | InsertEntry | SetVar | MintAsset | MintToken | TransferAsset
-            | TransferToken | ContractCall | StxTransfer | StxBurn | AtBlock | GetStxBalance
+            FetchVar | GetBlockInfo | GetBurnBlockInfo | GetTokenBalance | GetAssetOwner
+            | FetchEntry | SetEntry | DeleteEntry | InsertEntry | SetVar | MintAsset
+            | MintToken | TransferAsset | TransferToken | ContractCall | StxTransfer
+            | StxTransferMemo | StxBurn | AtBlock | GetStxBalance | GetStxAccount
             | GetTokenSupply | BurnToken | BurnAsset => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Append | Concat | AsMaxLen | ContractOf | PrincipalOf | ListCons | Print
-            | AsContract | ElementAt | IndexOf | Map | Filter | Fold => {
+            | AsContract | ElementAt | IndexOf | Map | Filter | Fold | Slice | ReplaceAt
+            | IntToAscii | IntToUtf8 | StringToInt | StringToUInt | BuffToIntLe | BuffToIntBe
+            | BuffToUIntLe | BuffToUIntBe | IntToBuffLe | IntToBuffBe | PrincipalDestruct
+            | PrincipalConstruct => {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Sha512 | Sha512Trunc256 | Secp256k1Recover | Secp256k1Verify | Hash160 | Sha256
@@ -180,10 +189,11 @@ impl ArithmeticOnlyChecker {
                 return Err(Error::FunctionNotPermitted(function));
             }
             Add | Subtract | Divide | Multiply | CmpGeq | CmpLeq | CmpLess | CmpGreater
-            | Modulo | Power | Sqrti | Log2 | BitwiseXOR | And | Or | Not | Equals | If
-            | ConsSome | ConsOkay | ConsError | DefaultTo | UnwrapRet | UnwrapErrRet | IsOkay
-            | IsNone | Asserts | Unwrap | UnwrapErr | IsErr | IsSome | TryRet | ToUInt | ToInt
-            | Len | Begin | TupleMerge => self.check_all(args),
+            | Modulo | Power | Sqrti | Log2 | BitwiseXOR | BitwiseAnd | BitwiseOr | BitwiseNot
+            | BitwiseLShift | BitwiseRShift | And | Or | Not | Equals | If | ConsSome
+            | ConsOkay | ConsError | DefaultTo | UnwrapRet | UnwrapErrRet | IsOkay | IsNone
+            | Asserts | Unwrap | UnwrapErr | IsErr | IsSome | TryRet | ToUInt | ToInt | Len
+            | Begin | TupleMerge => self.check_all(args),
             // we need to treat all the remaining functions specially, because these
             //   do not eval all of their arguments (rather, one or more of their arguments
             //   is a name)
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use chainstate::stacks::boot::BOOT_CODE_COSTS;
+use chainstate::stacks::boot::{BOOT_CODE_COSTS, BOOT_CODE_COSTS_2};
 use vm::analysis::{
     arithmetic_checker::ArithmeticOnlyChecker, arithmetic_checker::Error,
     arithmetic_checker::Error::*, mem_type_check, ContractAnalysis,
@@ -26,6 +26,7 @@ use vm::functions::define::DefineFunctions;
 use vm::functions::NativeFunctions;
 use vm::types::QualifiedContractIdentifier;
 use vm::variables::NativeVariables;
+use vm::ClarityVersion;
 
 fn arithmetic_check(contract: &str) -> Result<(), Error> {
     let contract_identifier = QualifiedContractIdentifier::transient();
@@ -35,6 +36,7 @@ fn arithmetic_check(contract: &str) -> Result<(), Error> {
         contract_identifier,
         expressions,
         LimitedCostTracker::new_free(),
+        ClarityVersion::latest(),
     );
 
     ArithmeticOnlyChecker::run(&analysis)
@@ -48,6 +50,7 @@ fn check_good(contract: &str) {
 #[test]
 fn test_boot_definitions() {
     check_good(BOOT_CODE_COSTS);
+    check_good(BOOT_CODE_COSTS_2);
 }
 
 #[test]
@@ -233,6 +236,8 @@ fn test_functions() {
                     (unwrap-err-panic (err u5)))))",
         "(define-private (foo) (to-int (to-uint 34)))
          (define-private (bar) (foo))",
+        "(define-private (foo (flags uint))
+           (bit-and (bit-or flags (bit-shift-left u1 u4)) (bit-not (bit-shift-right u8 u1))))",
         "(define-private (foo) (begin
            (is-some (some 4))
            (is-none (some 4))
@@ -124,6 +124,7 @@ pub enum CheckErrors {
 
     // get-block-info? errors
     NoSuchBlockInfoProperty(String),
+    NoSuchBurnBlockInfoProperty(String),
     GetBlockInfoExpectPropertyName,
 
     NameAlreadyUsed(String),
@@ -365,6 +366,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::ContractAlreadyExists(contract_identifier) => format!("contract name '{}' conflicts with existing contract", contract_identifier),
             CheckErrors::ContractCallExpectName => format!("missing contract name for call"),
             CheckErrors::NoSuchBlockInfoProperty(property_name) => format!("use of block unknown property '{}'", property_name),
+            CheckErrors::NoSuchBurnBlockInfoProperty(property_name) => format!("use of burn block unknown property '{}'", property_name),
             CheckErrors::GetBlockInfoExpectPropertyName => format!("missing property name for block info introspection"),
             CheckErrors::NameAlreadyUsed(name) => format!("defining '{}' conflicts with previous value", name),
             CheckErrors::NonFunctionApplication => format!("expecting expression of type function"),
@@ -426,6 +428,9 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::NoSuchBlockInfoProperty(_) => Some(format!(
                 "properties available: time, header-hash, burnchain-header-hash, vrf-seed"
             )),
+            CheckErrors::NoSuchBurnBlockInfoProperty(_) => {
+                Some(format!("properties available: header-hash, pox-addrs"))
+            }
             _ => None,
         }
     }
@@ -26,6 +26,7 @@ use vm::types::{parse_name_type_pairs, PrincipalData, TupleTypeSignature, TypeSi
 
 use std::collections::HashMap;
 use vm::variables::NativeVariables;
+use vm::ClarityVersion;
 
 pub use super::errors::{
     check_argument_count, check_arguments_at_least, CheckError, CheckErrors, CheckResult,
@@ -38,6 +39,7 @@ mod tests;
 pub struct ReadOnlyChecker<'a, 'b> {
     db: &'a mut AnalysisDatabase<'b>,
     defined_functions: HashMap<ClarityName, bool>,
+    clarity_version: ClarityVersion,
 }
 
 impl<'a, 'b> AnalysisPass for ReadOnlyChecker<'a, 'b> {
@@ -45,17 +47,21 @@ impl<'a, 'b> AnalysisPass for ReadOnlyChecker<'a, 'b> {
         contract_analysis: &mut ContractAnalysis,
         analysis_db: &mut AnalysisDatabase,
     ) -> CheckResult<()> {
-        let mut command = ReadOnlyChecker::new(analysis_db);
+        let mut command = ReadOnlyChecker::new(analysis_db, contract_analysis.clarity_version);
         command.run(contract_analysis)?;
         Ok(())
     }
 }
 
 impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
-    fn new(db: &'a mut AnalysisDatabase<'b>) -> ReadOnlyChecker<'a, 'b> {
+    fn new(
+        db: &'a mut AnalysisDatabase<'b>,
+        clarity_version: ClarityVersion,
+    ) -> ReadOnlyChecker<'a, 'b> {
         Self {
             db,
             defined_functions: HashMap::new(),
+            clarity_version,
         }
     }
 
@@ -159,7 +165,7 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
         function: &str,
         args: &[SymbolicExpression],
     ) -> Option<CheckResult<bool>> {
-        NativeFunctions::lookup_by_name(function)
+        NativeFunctions::lookup_by_name_at_version(function, &self.clarity_version)
             .map(|function| self.check_native_function(&function, args))
     }
 
@@ -172,14 +178,18 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
 
         match function {
             Add | Subtract | Divide | Multiply | CmpGeq | CmpLeq | CmpLess | CmpGreater
-            | Modulo | Power | Sqrti | Log2 | BitwiseXOR | And | Or | Not | Hash160 | Sha256
-            | Keccak256 | Equals | If | Sha512 | Sha512Trunc256 | Secp256k1Recover
-            | Secp256k1Verify | ConsSome | ConsOkay | ConsError | DefaultTo | UnwrapRet
-            | UnwrapErrRet | IsOkay | IsNone | Asserts | Unwrap | UnwrapErr | Match | IsErr
-            | IsSome | TryRet | ToUInt | ToInt | Append | Concat | AsMaxLen | ContractOf
-            | PrincipalOf | ListCons | GetBlockInfo | TupleGet | TupleMerge | Len | Print
-            | AsContract | Begin | FetchVar | GetStxBalance | GetTokenBalance | GetAssetOwner
-            | GetTokenSupply | ElementAt | IndexOf => self.check_all_read_only(args),
+            | Modulo | Power | Sqrti | Log2 | BitwiseXOR | BitwiseAnd | BitwiseOr | BitwiseNot
+            | BitwiseLShift | BitwiseRShift | And | Or | Not | Hash160 | Sha256 | Keccak256
+            | Equals | If | Sha512 | Sha512Trunc256 | Secp256k1Recover | Secp256k1Verify
+            | ConsSome | ConsOkay | ConsError | DefaultTo | UnwrapRet | UnwrapErrRet | IsOkay
+            | IsNone | Asserts | Unwrap | UnwrapErr | Match | IsErr | IsSome | TryRet | ToUInt
+            | ToInt | Append | Concat | AsMaxLen | ContractOf | PrincipalOf | ListCons
+            | GetBlockInfo | GetBurnBlockInfo | TupleGet | TupleMerge | Len | Print
+            | AsContract | Begin | FetchVar | GetStxBalance | GetStxAccount | GetTokenBalance
+            | GetAssetOwner | GetTokenSupply | ElementAt | IndexOf | Slice | ReplaceAt
+            | IntToAscii | IntToUtf8 | StringToInt | StringToUInt | BuffToIntLe | BuffToIntBe
+            | BuffToUIntLe | BuffToUIntBe | IntToBuffLe | IntToBuffBe | PrincipalDestruct
+            | PrincipalConstruct => self.check_all_read_only(args),
             AtBlock => {
                 check_argument_count(2, args)?;
 
@@ -194,8 +204,9 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
                 check_argument_count(2, args)?;
                 self.check_all_read_only(args)
             }
-            StxTransfer | StxBurn | SetEntry | DeleteEntry | InsertEntry | SetVar | MintAsset
-            | MintToken | TransferAsset | TransferToken | BurnAsset | BurnToken => {
+            StxTransfer | StxTransferMemo | StxBurn | SetEntry | DeleteEntry | InsertEntry
+            | SetVar | MintAsset | MintToken | TransferAsset | TransferToken | BurnAsset
+            | BurnToken => {
                 self.check_all_read_only(args)?;
                 Ok(false)
             }
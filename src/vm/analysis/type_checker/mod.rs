@@ -36,6 +36,7 @@ use vm::types::{
     QualifiedContractIdentifier, TupleTypeSignature, TypeSignature, Value,
 };
 use vm::variables::NativeVariables;
+use vm::ClarityVersion;
 
 pub use super::types::{AnalysisPass, ContractAnalysis};
 use super::AnalysisDatabase;
@@ -75,6 +76,7 @@ pub struct TypeChecker<'a, 'b> {
     function_return_tracker: Option<Option<TypeSignature>>,
     db: &'a mut AnalysisDatabase<'b>,
     pub cost_track: LimitedCostTracker,
+    clarity_version: ClarityVersion,
 }
 
 impl CostTracker for TypeChecker<'_, '_> {
@@ -115,7 +117,8 @@ impl AnalysisPass for TypeChecker<'_, '_> {
         analysis_db: &mut AnalysisDatabase,
     ) -> CheckResult<()> {
         let cost_track = contract_analysis.take_contract_cost_tracker();
-        let mut command = TypeChecker::new(analysis_db, cost_track);
+        let mut command =
+            TypeChecker::new(analysis_db, cost_track, contract_analysis.clarity_version);
         // run the analysis, and replace the cost tracker whether or not the
         //   analysis succeeded.
         match command.run(contract_analysis) {
@@ -234,6 +237,28 @@ impl FunctionType {
 
                 Ok(TypeSignature::BoolType)
             }
+            FunctionType::ArithmeticShift => {
+                check_argument_count(2, args)?;
+                let (first, second) = (&args[0], &args[1]);
+                analysis_typecheck_cost(accounting, &TypeSignature::IntType, first)?;
+                analysis_typecheck_cost(accounting, &TypeSignature::UIntType, second)?;
+
+                if first != &TypeSignature::IntType && first != &TypeSignature::UIntType {
+                    return Err(CheckErrors::UnionTypeError(
+                        vec![TypeSignature::IntType, TypeSignature::UIntType],
+                        first.clone(),
+                    )
+                    .into());
+                }
+
+                if second != &TypeSignature::UIntType {
+                    return Err(
+                        CheckErrors::TypeError(TypeSignature::UIntType, second.clone()).into(),
+                    );
+                }
+
+                Ok(first.clone())
+            }
         }
     }
 
@@ -315,10 +340,12 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
     fn new(
         db: &'a mut AnalysisDatabase<'b>,
         cost_track: LimitedCostTracker,
+        clarity_version: ClarityVersion,
     ) -> TypeChecker<'a, 'b> {
         Self {
             db,
             cost_track,
+            clarity_version,
             contract_context: ContractContext::new(),
             function_return_tracker: None,
             type_map: TypeMap::new(),
@@ -606,7 +633,9 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         args: &[SymbolicExpression],
         context: &TypingContext,
     ) -> Option<TypeResult> {
-        if let Some(ref native_function) = NativeFunctions::lookup_by_name(function) {
+        if let Some(ref native_function) =
+            NativeFunctions::lookup_by_name_at_version(function, &self.clarity_version)
+        {
             let typed_function = TypedNativeFunction::type_native_function(native_function);
             Some(typed_function.type_check_appliction(self, args, context))
         } else {
@@ -20,11 +20,13 @@ use super::{
 use std::convert::TryFrom;
 use vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use vm::errors::{Error as InterpError, RuntimeErrorType};
+use vm::functions::MAX_INTEGER_STRING_LEN;
 use vm::functions::{handle_binding_list, NativeFunctions};
+use vm::types::signatures::{BufferLength, StringUTF8Length};
 use vm::types::{
-    BlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature, FunctionType, PrincipalData,
-    TupleTypeSignature, TypeSignature, Value, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
-    MAX_VALUE_SIZE,
+    BlockInfoProperty, BurnBlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature,
+    FunctionType, PrincipalData, SequenceSubtype, StringSubtype, TupleTypeSignature, TypeSignature,
+    Value, BUFF_1, BUFF_16, BUFF_20, BUFF_32, BUFF_33, BUFF_34, BUFF_64, BUFF_65, MAX_VALUE_SIZE,
 };
 use vm::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 
@@ -474,6 +476,56 @@ fn check_principal_of(
     Ok(TypeSignature::new_response(TypeSignature::PrincipalType, TypeSignature::UIntType).unwrap())
 }
 
+fn check_principal_destruct(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+    checker.type_check_expects(&args[0], context, &TypeSignature::PrincipalType)?;
+    let destructed_type: TypeSignature = TupleTypeSignature::try_from(vec![
+        ("version".into(), BUFF_1.clone()),
+        ("hash-bytes".into(), BUFF_20.clone()),
+        (
+            "name".into(),
+            TypeSignature::new_option(TypeSignature::contract_name_string_ascii_type()).unwrap(),
+        ),
+    ])
+    .unwrap()
+    .into();
+    Ok(TypeSignature::new_response(destructed_type.clone(), destructed_type).unwrap())
+}
+
+fn check_principal_construct(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_arguments_at_least(2, args)?;
+    if args.len() > 3 {
+        return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+    }
+    checker.type_check_expects(&args[0], context, &BUFF_1)?;
+    checker.type_check_expects(&args[1], context, &BUFF_20)?;
+    if let Some(name) = args.get(2) {
+        checker.type_check_expects(
+            name,
+            context,
+            &TypeSignature::contract_name_string_ascii_type(),
+        )?;
+    }
+    let error_type: TypeSignature = TupleTypeSignature::try_from(vec![
+        ("error_code".into(), TypeSignature::UIntType),
+        (
+            "value".into(),
+            TypeSignature::new_option(TypeSignature::PrincipalType).unwrap(),
+        ),
+    ])
+    .unwrap()
+    .into();
+    Ok(TypeSignature::new_response(TypeSignature::PrincipalType, error_type).unwrap())
+}
+
 fn check_secp256k1_recover(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -518,6 +570,27 @@ fn check_get_block_info(
     Ok(TypeSignature::new_option(block_info_prop.type_result())?)
 }
 
+fn check_get_burn_block_info(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let block_info_prop_str = args[0]
+        .match_atom()
+        .ok_or(CheckError::new(CheckErrors::GetBlockInfoExpectPropertyName))?;
+
+    let block_info_prop =
+        BurnBlockInfoProperty::lookup_by_name(block_info_prop_str).ok_or(CheckError::new(
+            CheckErrors::NoSuchBurnBlockInfoProperty(block_info_prop_str.to_string()),
+        ))?;
+
+    checker.type_check_expects(&args[1], &context, &TypeSignature::UIntType)?;
+
+    Ok(TypeSignature::new_option(block_info_prop.type_result())?)
+}
+
 impl TypedNativeFunction {
     pub fn type_check_appliction(
         &self,
@@ -538,13 +611,18 @@ impl TypedNativeFunction {
         use self::TypedNativeFunction::{Simple, Special};
         use vm::functions::NativeFunctions::*;
         match function {
-            Add | Subtract | Divide | Multiply => {
+            Add | Subtract | Divide | Multiply | BitwiseAnd | BitwiseOr => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticVariadic))
             }
             CmpGeq | CmpLeq | CmpLess | CmpGreater => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticComparison))
             }
-            Sqrti | Log2 => Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary)),
+            Sqrti | Log2 | BitwiseNot => {
+                Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary))
+            }
+            BitwiseLShift | BitwiseRShift => {
+                Simple(SimpleNativeFunction(FunctionType::ArithmeticShift))
+            }
             Modulo | Power | BitwiseXOR => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticBinary))
             }
@@ -626,6 +704,20 @@ impl TypedNativeFunction {
                 )],
                 returns: TypeSignature::UIntType,
             }))),
+            GetStxAccount => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("owner".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TupleTypeSignature::try_from(vec![
+                    ("locked".into(), TypeSignature::UIntType),
+                    ("unlock-height".into(), TypeSignature::UIntType),
+                    ("unlocked".into(), TypeSignature::UIntType),
+                ])
+                .unwrap()
+                .into(),
+            }))),
             StxTransfer => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![
                     FunctionArg::new(
@@ -650,6 +742,35 @@ impl TypedNativeFunction {
                 )
                 .unwrap(),
             }))),
+            StxTransferMemo => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![
+                    FunctionArg::new(
+                        TypeSignature::UIntType,
+                        ClarityName::try_from("amount".to_owned())
+                            .expect("FAIL: ClarityName failed to accept default arg name"),
+                    ),
+                    FunctionArg::new(
+                        TypeSignature::PrincipalType,
+                        ClarityName::try_from("sender".to_owned())
+                            .expect("FAIL: ClarityName failed to accept default arg name"),
+                    ),
+                    FunctionArg::new(
+                        TypeSignature::PrincipalType,
+                        ClarityName::try_from("recipient".to_owned())
+                            .expect("FAIL: ClarityName failed to accept default arg name"),
+                    ),
+                    FunctionArg::new(
+                        BUFF_34,
+                        ClarityName::try_from("memo".to_owned())
+                            .expect("FAIL: ClarityName failed to accept default arg name"),
+                    ),
+                ],
+                returns: TypeSignature::new_response(
+                    TypeSignature::BoolType,
+                    TypeSignature::UIntType,
+                )
+                .unwrap(),
+            }))),
             StxBurn => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![
                     FunctionArg::new(
@@ -669,6 +790,72 @@ impl TypedNativeFunction {
                 )
                 .unwrap(),
             }))),
+            IntToAscii => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                    BufferLength::try_from(MAX_INTEGER_STRING_LEN).unwrap(),
+                ))),
+            ))),
+            IntToUtf8 => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+                    StringUTF8Length::try_from(MAX_INTEGER_STRING_LEN).unwrap(),
+                ))),
+            ))),
+            StringToInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::OptionalType(Box::new(TypeSignature::IntType)),
+            ))),
+            StringToUInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::OptionalType(Box::new(TypeSignature::UIntType)),
+            ))),
+            BuffToIntLe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::IntType,
+            }))),
+            BuffToIntBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::IntType,
+            }))),
+            BuffToUIntLe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::UIntType,
+            }))),
+            BuffToUIntBe => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    BUFF_16.clone(),
+                    ClarityName::try_from("value".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::UIntType,
+            }))),
+            IntToBuffLe => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                BUFF_16.clone(),
+            ))),
+            IntToBuffBe => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                BUFF_16.clone(),
+            ))),
             GetTokenBalance => Special(SpecialNativeFunction(&assets::check_special_get_balance)),
             GetAssetOwner => Special(SpecialNativeFunction(&assets::check_special_get_owner)),
             TransferToken => Special(SpecialNativeFunction(&assets::check_special_transfer_token)),
@@ -694,6 +881,8 @@ impl TypedNativeFunction {
             Len => Special(SpecialNativeFunction(&sequences::check_special_len)),
             ElementAt => Special(SpecialNativeFunction(&sequences::check_special_element_at)),
             IndexOf => Special(SpecialNativeFunction(&sequences::check_special_index_of)),
+            Slice => Special(SpecialNativeFunction(&sequences::check_special_slice)),
+            ReplaceAt => Special(SpecialNativeFunction(&sequences::check_special_replace_at)),
             ListCons => Special(SpecialNativeFunction(&check_special_list_cons)),
             FetchEntry => Special(SpecialNativeFunction(&maps::check_special_fetch_entry)),
             SetEntry => Special(SpecialNativeFunction(&maps::check_special_set_entry)),
@@ -708,7 +897,10 @@ impl TypedNativeFunction {
             ContractCall => Special(SpecialNativeFunction(&check_contract_call)),
             ContractOf => Special(SpecialNativeFunction(&check_contract_of)),
             PrincipalOf => Special(SpecialNativeFunction(&check_principal_of)),
+            PrincipalDestruct => Special(SpecialNativeFunction(&check_principal_destruct)),
+            PrincipalConstruct => Special(SpecialNativeFunction(&check_principal_construct)),
             GetBlockInfo => Special(SpecialNativeFunction(&check_get_block_info)),
+            GetBurnBlockInfo => Special(SpecialNativeFunction(&check_get_burn_block_info)),
             ConsSome => Special(SpecialNativeFunction(&options::check_special_some)),
             ConsOkay => Special(SpecialNativeFunction(&options::check_special_okay)),
             ConsError => Special(SpecialNativeFunction(&options::check_special_error)),
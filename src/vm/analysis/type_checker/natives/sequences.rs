@@ -37,7 +37,9 @@ fn get_simple_native_or_user_define(
     checker: &mut TypeChecker,
 ) -> CheckResult<FunctionType> {
     runtime_cost(ClarityCostFunction::AnalysisLookupFunction, checker, 0)?;
-    if let Some(ref native_function) = NativeFunctions::lookup_by_name(function_name) {
+    if let Some(ref native_function) =
+        NativeFunctions::lookup_by_name_at_version(function_name, &checker.clarity_version)
+    {
         if let TypedNativeFunction::Simple(SimpleNativeFunction(function_type)) =
             TypedNativeFunction::type_native_function(native_function)
         {
@@ -389,3 +391,45 @@ pub fn check_special_index_of(
 
     TypeSignature::new_option(TypeSignature::UIntType).map_err(|e| e.into())
 }
+
+pub fn check_special_slice(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(3, args)?;
+
+    let sequence_type = checker.type_check(&args[0], context)?;
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+    checker.type_check_expects(&args[2], context, &TypeSignature::UIntType)?;
+
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+
+    match sequence_type {
+        TypeSignature::SequenceType(_) => {
+            TypeSignature::new_option(sequence_type).map_err(|e| e.into())
+        }
+        _ => Err(CheckErrors::ExpectedSequence(sequence_type).into()),
+    }
+}
+
+pub fn check_special_replace_at(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(3, args)?;
+
+    let sequence_type = checker.type_check(&args[0], context)?;
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+
+    let expected_element_type = match sequence_type {
+        TypeSignature::SequenceType(ref sequence_subtype) => Ok(sequence_subtype.unit_type()),
+        _ => Err(CheckErrors::ExpectedSequence(sequence_type.clone())),
+    }?;
+
+    checker.type_check_expects(&args[1], context, &TypeSignature::UIntType)?;
+    checker.type_check_expects(&args[2], context, &expected_element_type)?;
+
+    TypeSignature::new_option(sequence_type).map_err(|e| e.into())
+}
@@ -26,7 +26,7 @@ use vm::contexts::OwnedEnvironment;
 use vm::representations::SymbolicExpression;
 use vm::types::{
     FixedFunction, FunctionType, PrincipalData, QualifiedContractIdentifier, TypeSignature, Value,
-    BUFF_32, BUFF_64,
+    BUFF_16, BUFF_32, BUFF_34, BUFF_64,
 };
 
 use vm::database::MemoryBackingStore;
@@ -94,6 +94,48 @@ fn test_get_block_info() {
     }
 }
 
+#[test]
+fn test_get_burn_block_info() {
+    let good = [
+        "(get-burn-block-info? header-hash u1)",
+        "(get-burn-block-info? header-hash (* u2 u3))",
+        "(get-burn-block-info? pox-addrs u1)",
+    ];
+    let expected = [
+        "(optional (buff 32))",
+        "(optional (buff 32))",
+        "(optional (tuple (addrs (list 2 (tuple (hashbytes (buff 20)) (version (buff 1))))) (payout uint)))",
+    ];
+
+    let bad = [
+        "(get-burn-block-info? none u1)",
+        "(get-burn-block-info? time u1)",
+        "(get-burn-block-info? header-hash true)",
+        "(get-burn-block-info? header-hash 1)",
+        "(get-burn-block-info? header-hash)",
+        "(get-burn-block-info? header-hash u1 u2)",
+    ];
+    let bad_expected = [
+        CheckErrors::NoSuchBurnBlockInfoProperty("none".to_string()),
+        CheckErrors::NoSuchBurnBlockInfoProperty("time".to_string()),
+        CheckErrors::TypeError(UIntType, BoolType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::IncorrectArgumentCount(2, 3),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_define_trait() {
     let good = [
@@ -181,8 +223,16 @@ fn test_stx_ops() {
         "(stx-burn? u10 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
         "(stx-transfer? u10 tx-sender 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
         "(stx-get-balance 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+        "(stx-account 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+        "(stx-transfer-memo? u10 tx-sender 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G 0x0102)",
+    ];
+    let expected = [
+        "(response bool uint)",
+        "(response bool uint)",
+        "uint",
+        "(tuple (locked uint) (unlock-height uint) (unlocked uint))",
+        "(response bool uint)",
     ];
-    let expected = ["(response bool uint)", "(response bool uint)", "uint"];
 
     let bad = [
         "(stx-transfer? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
@@ -194,7 +244,12 @@ fn test_stx_ops() {
         "(stx-burn? u4 true)",
         "(stx-burn? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         "(stx-get-balance true)",
-        "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)"
+        "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        "(stx-account true)",
+        "(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        "(stx-transfer-memo? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+        "(stx-transfer-memo? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G u1)",
+        "(stx-transfer-memo? u4 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G 0x0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20212223)"
     ];
     let bad_expected = [
         CheckErrors::IncorrectArgumentCount(3, 2),
@@ -207,6 +262,11 @@ fn test_stx_ops() {
         CheckErrors::IncorrectArgumentCount(2, 3),
         CheckErrors::TypeError(PrincipalType, BoolType),
         CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(PrincipalType, BoolType),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::IncorrectArgumentCount(4, 3),
+        CheckErrors::TypeError(BUFF_34, UIntType),
+        CheckErrors::TypeError(BUFF_34, buff_type(35)),
     ];
 
     for (good_test, expected) in good.iter().zip(expected.iter()) {
@@ -494,6 +554,87 @@ fn test_simple_arithmetic_checks() {
     }
 }
 
+#[test]
+fn test_bitwise_checks() {
+    let good = [
+        "(bit-and 1 2 3)",
+        "(bit-or u1 u2)",
+        "(bit-not -1)",
+        "(bit-shift-left u1 u2)",
+        "(bit-shift-right -4 (+ u1 u1))",
+    ];
+    let expected = ["int", "uint", "int", "uint", "int"];
+    let bad = [
+        "(bit-and)",
+        "(bit-or 1 u2)",
+        "(bit-not 1 2)",
+        "(bit-shift-left 1 2)",
+        "(bit-shift-right u1)",
+        "(bit-shift-right true u1)",
+    ];
+    let bad_expected = [
+        CheckErrors::RequiresAtLeastArguments(1, 0),
+        CheckErrors::TypeError(IntType, UIntType),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_principal_destruct_and_construct() {
+    let good = [
+        "(principal-destruct? tx-sender)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x 0x \"foo\")",
+    ];
+    let expected = [
+        "(response (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))) (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))))",
+        "(response principal (tuple (error_code uint) (value (optional principal))))",
+        "(response principal (tuple (error_code uint) (value (optional principal))))",
+    ];
+    let bad = [
+        "(principal-destruct? 0x1a)",
+        "(principal-construct? 0x1a)",
+        "(principal-construct? 0x1a1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 u\"foo\")",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"foo\" \"bar\")",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(PrincipalType, buff_type(1)),
+        CheckErrors::RequiresAtLeastArguments(2, 1),
+        CheckErrors::TypeError(buff_type(1), buff_type(2)),
+        CheckErrors::TypeError(
+            TypeSignature::contract_name_string_ascii_type(),
+            SequenceType(StringType(UTF8(3u32.try_into().unwrap()))),
+        ),
+        CheckErrors::IncorrectArgumentCount(3, 4),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_simple_hash_checks() {
     let good = [
@@ -715,6 +856,120 @@ fn test_element_at() {
     }
 }
 
+#[test]
+fn test_slice_and_replace_at() {
+    let good = [
+        "(slice? (list 1 2 3 4 5) u1 u3)",
+        "(slice? \"abcd\" u1 u3)",
+        "(slice? 0xfedb u1 u2)",
+        "(slice? u\"abcd\" (+ u1 u2) u4)",
+        "(replace-at? (list 1 2 3 4 5) u1 6)",
+        "(replace-at? \"abcd\" u1 \"e\")",
+        "(replace-at? 0xfedb u1 0x01)",
+        "(replace-at? u\"abcd\" u1 u\"e\")",
+    ];
+
+    let expected = [
+        "(optional (list 5 int))",
+        "(optional (string-ascii 4))",
+        "(optional (buff 2))",
+        "(optional (string-utf8 4))",
+        "(optional (list 5 int))",
+        "(optional (string-ascii 4))",
+        "(optional (buff 2))",
+        "(optional (string-utf8 4))",
+    ];
+
+    let bad = [
+        "(slice? 3 u1 u2)",
+        "(slice? (list 1 2 3) 1 u2)",
+        "(slice? (list 1 2 3) u1)",
+        "(replace-at? 3 u1 4)",
+        "(replace-at? (list 1 2 3) u1 u4)",
+        "(replace-at? \"abcd\" u1 \"ef\")",
+    ];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeError(TypeSignature::UIntType, TypeSignature::IntType),
+        CheckErrors::IncorrectArgumentCount(3, 2),
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::UIntType),
+        CheckErrors::TypeError(
+            TypeSignature::min_string_ascii(),
+            TypeSignature::SequenceType(StringType(ASCII(2u32.try_into().unwrap()))),
+        ),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_integer_conversions() {
+    let good = [
+        "(int-to-ascii 1)",
+        "(int-to-utf8 u1)",
+        "(string-to-int? \"1\")",
+        "(string-to-uint? u\"1\")",
+        "(buff-to-int-le 0x01)",
+        "(buff-to-uint-be 0x01)",
+        "(int-to-buff-le -1)",
+        "(int-to-buff-be u1)",
+    ];
+
+    let expected = [
+        "(string-ascii 40)",
+        "(string-utf8 40)",
+        "(optional int)",
+        "(optional uint)",
+        "int",
+        "uint",
+        "(buff 16)",
+        "(buff 16)",
+    ];
+
+    let bad = [
+        "(int-to-ascii true)",
+        "(string-to-int? 1)",
+        "(buff-to-int-be 0x0102030405060708090a0b0c0d0e0f1011)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::UnionTypeError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            TypeSignature::BoolType,
+        ),
+        CheckErrors::UnionTypeError(
+            vec![
+                TypeSignature::max_string_ascii(),
+                TypeSignature::max_string_utf8(),
+            ],
+            TypeSignature::IntType,
+        ),
+        CheckErrors::TypeError(BUFF_16.clone(), buff_type(17)),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_eqs() {
     let good = [
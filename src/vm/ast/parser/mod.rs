@@ -127,6 +127,19 @@ lazy_static! {
 }
 
 pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+    let mut position = (1, 1);
+    lex_at(input, &mut position).map_err(|mut e| {
+        // lexing errors don't carry an expression, so point at the token that failed to lex
+        if e.diagnostic.spans.is_empty() {
+            let (line, column) = position;
+            e.diagnostic.add_span(line, column, line, column);
+        }
+        e
+    })
+}
+
+/// Lex `input`, keeping `position` at the line and column of the token being lexed.
+fn lex_at(input: &str, position: &mut (u32, u32)) -> ParseResult<Vec<(LexItem, u32, u32)>> {
     // Aaron: I'd like these to be static, but that'd require using
     //    lazy_static (or just hand implementing that), and I'm not convinced
     //    it's worth either (1) an extern macro, or (2) the complexity of hand implementing.
@@ -199,16 +212,19 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
     let mut column_pos: u32 = 1;
     let mut did_match = true;
     while did_match && munch_index < input.len() {
-        if let Some(next_line_ix) = next_line_break {
-            if munch_index > next_line_ix {
-                next_line_break = line_indices.pop();
-                column_pos = 1;
-                current_line = current_line
-                    .checked_add(1)
-                    .ok_or(ParseError::new(ParseErrors::ProgramTooLarge))?;
+        // a whitespace token can span several line breaks
+        while let Some(next_line_ix) = next_line_break {
+            if munch_index <= next_line_ix {
+                break;
             }
+            next_line_break = line_indices.pop();
+            column_pos = 1;
+            current_line = current_line
+                .checked_add(1)
+                .ok_or(ParseError::new(ParseErrors::ProgramTooLarge))?;
         }
 
+        *position = (current_line, column_pos);
         did_match = false;
         let current_slice = &input[munch_index..];
         for matcher in lex_matchers.iter() {
@@ -293,7 +309,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 str_value.clone(),
                             ))),
                         }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                     TokenType::IntLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
@@ -303,7 +319,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 str_value.clone(),
                             ))),
                         }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                     TokenType::FullyQualifiedContractIdentifierLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
@@ -314,7 +330,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                     ParseErrors::FailedParsingPrincipal(str_value.clone()),
                                 )),
                             }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                     TokenType::SugaredContractIdentifierLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
@@ -324,7 +340,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 str_value.clone(),
                             ))),
                         }?;
-                        Ok(LexItem::SugaredContractIdentifier(str_value.len(), value))
+                        Ok(LexItem::SugaredContractIdentifier(whole_match.end(), value))
                     }
                     TokenType::FullyQualifiedFieldIdentifierLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
@@ -334,7 +350,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 str_value.clone(),
                             ))),
                         }?;
-                        Ok(LexItem::FieldIdentifier(str_value.len(), value))
+                        Ok(LexItem::FieldIdentifier(whole_match.end(), value))
                     }
                     TokenType::SugaredFieldIdentifierLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
@@ -346,7 +362,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 ))),
                             }?;
                         Ok(LexItem::SugaredFieldIdentifier(
-                            str_value.len(),
+                            whole_match.end(),
                             contract_name,
                             field_name,
                         ))
@@ -359,14 +375,14 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 str_value.clone(),
                             ))),
                         }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                     TokenType::TraitReferenceLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
                         let data = str_value.clone().try_into().map_err(|_| {
                             ParseError::new(ParseErrors::IllegalVariableName(str_value.to_string()))
                         })?;
-                        Ok(LexItem::TraitReference(str_value.len(), data))
+                        Ok(LexItem::TraitReference(whole_match.end(), data))
                     }
                     TokenType::HexStringLiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
@@ -382,11 +398,10 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                                 str_value.clone(),
                             ))),
                         }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                     TokenType::StringASCIILiteral => {
                         let str_value = get_value_or_err(current_slice, captures)?;
-                        let str_value_len = str_value.len();
                         let unescaped_str = unescape_ascii_chars(str_value, false)?;
                         let byte_vec = unescaped_str.as_bytes().to_vec();
 
@@ -394,11 +409,10 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             Ok(parsed) => Ok(parsed),
                             Err(_e) => Err(ParseError::new(ParseErrors::InvalidCharactersDetected)),
                         }?;
-                        Ok(LexItem::LiteralValue(str_value_len, value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                     TokenType::StringUTF8Literal => {
                         let str_value = get_value_or_err(current_slice, captures)?;
-                        let str_value_len = str_value.len();
                         let unescaped_str = unescape_ascii_chars(str_value, true)?;
 
                         let value = match Value::string_utf8_from_string_utf8_literal(unescaped_str)
@@ -406,7 +420,7 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
                             Ok(parsed) => Ok(parsed),
                             Err(_e) => Err(ParseError::new(ParseErrors::InvalidCharactersDetected)),
                         }?;
-                        Ok(LexItem::LiteralValue(str_value_len, value))
+                        Ok(LexItem::LiteralValue(whole_match.end(), value))
                     }
                 }?;
 
@@ -502,7 +516,15 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                                     }
                                 })
                                 .collect();
-                            let checked_list = checked_list?;
+                            let checked_list = checked_list.map_err(|mut e| {
+                                e.diagnostic.add_span(
+                                    start_line,
+                                    start_column,
+                                    line_pos,
+                                    column_pos,
+                                );
+                                e
+                            })?;
                             let mut pre_expr = PreSymbolicExpression::list(checked_list);
                             pre_expr.set_span(start_line, start_column, line_pos, column_pos);
                             handle_expression(&mut parse_stack, &mut output_list, pre_expr);
@@ -524,7 +546,11 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                         "Closing parenthesis expected ({}, {})",
                         line_pos, column_pos
                     );
-                    return Err(ParseError::new(ParseErrors::ClosingParenthesisUnexpected));
+                    let mut error = ParseError::new(ParseErrors::ClosingParenthesisUnexpected);
+                    error
+                        .diagnostic
+                        .add_span(line_pos, column_pos, line_pos, column_pos);
+                    return Err(error);
                 }
             }
             LexItem::LeftCurly => {
@@ -588,7 +614,11 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                         "Closing tuple literal unexpected ({}, {})",
                         line_pos, column_pos
                     );
-                    return Err(ParseError::new(ParseErrors::ClosingTupleLiteralUnexpected));
+                    let mut error = ParseError::new(ParseErrors::ClosingTupleLiteralUnexpected);
+                    error
+                        .diagnostic
+                        .add_span(line_pos, column_pos, line_pos, column_pos);
+                    return Err(error);
                 }
             }
             LexItem::Variable(value) => {
@@ -641,8 +671,8 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
                 pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
                 handle_expression(&mut parse_stack, &mut output_list, pre_expr);
             }
-            LexItem::TraitReference(_length, value) => {
-                let end_column = column_pos + (value.len() as u32) - 1;
+            LexItem::TraitReference(length, value) => {
+                let end_column = column_pos + (length as u32) - 1;
                 let value = value.clone().try_into().map_err(|_| {
                     ParseError::new(ParseErrors::IllegalVariableName(value.to_string()))
                 })?;
@@ -652,7 +682,13 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
             }
             LexItem::ColonSeparator => {
                 match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected)),
+                    None => {
+                        let mut error = ParseError::new(ParseErrors::ColonSeparatorUnexpected);
+                        error
+                            .diagnostic
+                            .add_span(line_pos, column_pos, line_pos, column_pos);
+                        return Err(error);
+                    }
                     Some((ref mut list, ..)) => {
                         list.push(ParseStackItem::Colon);
                     }
@@ -660,7 +696,13 @@ pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSy
             }
             LexItem::CommaSeparator => {
                 match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected)),
+                    None => {
+                        let mut error = ParseError::new(ParseErrors::CommaSeparatorUnexpected);
+                        error
+                            .diagnostic
+                            .add_span(line_pos, column_pos, line_pos, column_pos);
+                        return Err(error);
+                    }
                     Some((ref mut list, ..)) => {
                         list.push(ParseStackItem::Comma);
                     }
@@ -697,7 +739,7 @@ pub fn parse(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
 mod test {
     use vm::ast;
     use vm::ast::errors::{ParseError, ParseErrors};
-    use vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType};
+    use vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType, Span};
     use vm::types::TraitIdentifier;
     use vm::types::{CharType, PrincipalData, QualifiedContractIdentifier, SequenceData, Value};
 
@@ -881,6 +923,116 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_literal_spans() {
+        // spans cover the whole token, including quotes and prefixes, and keep track of lines
+        //  across blank lines
+        let input = "(\"ab\" u12\n\n\n 0x01 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)";
+        let program = vec![make_list(
+            1,
+            1,
+            4,
+            49,
+            Box::new([
+                make_atom_value(
+                    Value::string_ascii_from_bytes("ab".as_bytes().to_vec()).unwrap(),
+                    1,
+                    2,
+                    1,
+                    5,
+                ),
+                make_atom_value(Value::UInt(12), 1, 7, 1, 9),
+                make_atom_value(Value::buff_from(vec![1]).unwrap(), 4, 2, 4, 5),
+                make_atom_value(
+                    Value::Principal(
+                        PrincipalData::parse_standard_principal(
+                            "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR",
+                        )
+                        .unwrap()
+                        .into(),
+                    ),
+                    4,
+                    7,
+                    4,
+                    48,
+                ),
+            ]),
+        )];
+
+        let parsed = ast::parser::parse(&input);
+        assert_eq!(
+            Ok(program),
+            parsed,
+            "Should match expected symbolic expression"
+        );
+    }
+
+    #[test]
+    fn test_parse_identifier_spans() {
+        // spans of identifiers, trait references, and utf8 strings include their prefixes and
+        //  delimiters
+        let input = "(.my-contract .my-contract.my-trait\n  <a> u\"ab\")";
+        let parsed = ast::parser::parse(&input).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(
+            parsed[0].span,
+            Span {
+                start_line: 1,
+                start_column: 1,
+                end_line: 2,
+                end_column: 12
+            }
+        );
+
+        let spans: Vec<_> = parsed[0]
+            .match_list()
+            .unwrap()
+            .iter()
+            .map(|expr| {
+                (
+                    expr.span.start_line,
+                    expr.span.start_column,
+                    expr.span.end_line,
+                    expr.span.end_column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(1, 2, 1, 13), (1, 15, 1, 35), (2, 3, 2, 5), (2, 7, 2, 11)]
+        );
+    }
+
+    #[test]
+    fn test_parse_failure_spans() {
+        let error_span = |input: &str| {
+            let spans = ast::parser::parse(input).unwrap_err().diagnostic.spans;
+            spans
+                .iter()
+                .map(|span| {
+                    (
+                        span.start_line,
+                        span.start_column,
+                        span.end_line,
+                        span.end_column,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // lexing errors point at the token that failed to lex, even after blank lines
+        assert_eq!(
+            error_span("(let ((x 1))\n\n  (y#not 2))"),
+            vec![(3, 5, 3, 5)]
+        );
+        // unexpected closing delimiters and separators point at themselves
+        assert_eq!(error_span("(let ((x 1) (y 2))))"), vec![(1, 20, 1, 20)]);
+        assert_eq!(error_span("{id: 37}}"), vec![(1, 9, 1, 9)]);
+        assert_eq!(error_span("\n, 1"), vec![(2, 1, 2, 1)]);
+        // errors in a list's contents point at the list
+        assert_eq!(error_span("(1 : 2)"), vec![(1, 1, 1, 7)]);
+    }
+
     #[test]
     fn test_parse_tuple_literal() {
         let input = "{id: 1337 }";
@@ -174,17 +174,28 @@ impl DefinedFunction {
             }
         }
 
+        env.global_context
+            .begin_coverage(&env.contract_context.contract_identifier);
+        env.global_context
+            .begin_debug_function(&env.contract_context.contract_identifier, &self.name);
+        env.global_context
+            .cost_track
+            .begin_profile_frame(|| self.identifier.to_string());
         let result = eval(&self.body, env, &context);
+        env.global_context.cost_track.end_profile_frame();
+        env.global_context.end_coverage();
 
         // if the error wasn't actually an error, but a function return,
         //    pull that out and return it.
-        match result {
+        let result = match result {
             Ok(r) => Ok(r),
             Err(e) => match e {
                 Error::ShortReturn(v) => Ok(v.into()),
                 _ => Err(e),
             },
-        }
+        };
+        env.global_context.finish_debug_function(&result);
+        result
     }
 
     pub fn check_trait_expectations(
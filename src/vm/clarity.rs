@@ -22,14 +22,16 @@ use vm::ast::{errors::ParseError, errors::ParseErrors, ContractAST};
 use vm::contexts::{AssetMap, Environment, OwnedEnvironment};
 use vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
 use vm::database::{
-    marf::WritableMarfStore, BurnStateDB, ClarityDatabase, HeadersDB, MarfedKV, RollbackWrapper,
-    RollbackWrapperPersistedLog, SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+    marf::WritableMarfStore, BurnStateDB, ClarityBackingStore, ClarityDatabase, HeadersDB,
+    MarfedKV, RollbackWrapper, RollbackWrapperPersistedLog, SqliteConnection, NULL_BURN_STATE_DB,
+    NULL_HEADER_DB,
 };
 use vm::errors::Error as InterpreterError;
 use vm::representations::SymbolicExpression;
 use vm::types::{
-    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, TypeSignature, Value,
+    AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, TypeSignature, Value,
 };
+use vm::ClarityVersion;
 
 use chainstate::burn::BlockHeaderHash;
 use chainstate::stacks::events::StacksTransactionEvent;
@@ -40,10 +42,12 @@ use chainstate::stacks::StacksBlockId;
 use chainstate::stacks::StacksMicroblockHeader;
 
 use chainstate::stacks::boot::{
-    boot_code_id, BOOT_CODE_COSTS, BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING,
-    BOOT_CODE_POX_TESTNET,
+    boot_code_id, BOOT_CODE_COSTS, BOOT_CODE_COSTS_2,
+    BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING, BOOT_CODE_POX_TESTNET,
 };
 
+use core::{CLARITY_2_ACTIVATION_HEIGHT_MAINNET, CLARITY_2_ACTIVATION_HEIGHT_TESTNET};
+
 use std::error;
 use std::fmt;
 
@@ -66,6 +70,7 @@ pub struct ClarityInstance {
     datastore: MarfedKV,
     block_limit: ExecutionCost,
     mainnet: bool,
+    clarity_2_activation_height: u64,
 }
 
 ///
@@ -86,7 +91,7 @@ pub struct ClarityBlockConnection<'a> {
 ///   rollback the transaction by dropping this struct.
 pub struct ClarityTransactionConnection<'a, 'b> {
     log: Option<RollbackWrapperPersistedLog>,
-    store: &'a mut WritableMarfStore<'b>,
+    store: &'a mut (dyn ClarityBackingStore + 'b),
     header_db: &'a dyn HeadersDB,
     burn_state_db: &'a dyn BurnStateDB,
     cost_track: &'a mut Option<LimitedCostTracker>,
@@ -97,6 +102,7 @@ pub struct ClarityReadOnlyConnection<'a> {
     datastore: ReadOnlyMarfStore<'a>,
     header_db: &'a dyn HeadersDB,
     burn_state_db: &'a dyn BurnStateDB,
+    mainnet: bool,
 }
 
 #[derive(Debug)]
@@ -209,6 +215,70 @@ macro_rules! using {
 }
 
 impl ClarityBlockConnection<'_> {
+    /// Activate Clarity 2 in this block if this fork has reached `activation_height` and has not
+    /// activated it yet.  The height is compared against the burnchain height of this block's
+    /// parent -- i.e. `burn-block-height` -- so that the miner and every validator agree on
+    /// which block activates it.
+    ///
+    /// Activation publishes the `costs-2` boot contract, which defines the costs of the Clarity 2
+    /// native functions, and records that contracts published from now on are Clarity 2
+    /// contracts.  Contracts published before activation stay Clarity 1 contracts.
+    ///
+    /// Returns true if this block activated Clarity 2.
+    fn initialize_clarity_2(&mut self, activation_height: u64) -> bool {
+        // burnchain heights are 32-bit, so a height beyond that is never reached
+        if activation_height > u32::MAX as u64 {
+            return false;
+        }
+
+        let burn_height = {
+            let mut db =
+                ClarityDatabase::new(&mut self.datastore, &self.header_db, &self.burn_state_db);
+            db.begin();
+            let clarity_version = db.get_clarity_version();
+            let burn_height = db.get_current_burnchain_block_height();
+            db.roll_back();
+            if clarity_version >= ClarityVersion::Clarity2 {
+                return false;
+            }
+            burn_height
+        };
+        if (burn_height as u64) < activation_height {
+            return false;
+        }
+
+        info!("Activating Clarity 2"; "burn_block_height" => burn_height);
+        self.publish_costs_2();
+        true
+    }
+
+    /// Switch newly-published contracts to Clarity 2, and publish the `costs-2` boot contract
+    /// as the first of them.  The upgrade is not charged to the block.
+    fn publish_costs_2(&mut self) {
+        let costs_2_id = boot_code_id("costs-2", self.mainnet);
+        let block_cost_tracker = self.set_cost_tracker(LimitedCostTracker::new_free());
+
+        self.as_transaction(|clarity_tx| {
+            clarity_tx
+                .with_clarity_db(|db| {
+                    db.set_clarity_version(ClarityVersion::Clarity2);
+                    Ok(())
+                })
+                .expect("FATAL: failed to activate Clarity 2");
+            let (ast, analysis) = clarity_tx
+                .analyze_smart_contract(&costs_2_id, BOOT_CODE_COSTS_2)
+                .expect("FATAL: failed to analyze the costs-2 boot contract");
+            clarity_tx
+                .initialize_smart_contract(&costs_2_id, &ast, BOOT_CODE_COSTS_2, |_, _| false)
+                .expect("FATAL: failed to publish the costs-2 boot contract");
+            clarity_tx
+                .save_analysis(&costs_2_id, &analysis)
+                .expect("FATAL: failed to store the costs-2 boot contract analysis");
+        });
+
+        self.set_cost_tracker(block_cost_tracker);
+    }
+
     /// Reset the block's total execution to the given cost, if there is a cost tracker at all.
     /// Used by the miner to "undo" applying a transaction that exceeded the budget.
     pub fn reset_block_cost(&mut self, cost: ExecutionCost) -> () {
@@ -233,17 +303,42 @@ impl ClarityBlockConnection<'_> {
             None => ExecutionCost::zero(),
         }
     }
+
+    /// Attribute the costs of the transactions processed from now on to the functions that
+    /// incur them.  The profile can be had from the cost tracker returned when the block is
+    /// committed.
+    pub fn enable_profiling(&mut self) {
+        if let Some(ref mut cost_tracker) = self.cost_track {
+            cost_tracker.enable_profiling();
+        }
+    }
 }
 
 impl ClarityInstance {
     pub fn new(mainnet: bool, datastore: MarfedKV, block_limit: ExecutionCost) -> ClarityInstance {
+        let clarity_2_activation_height = if mainnet {
+            CLARITY_2_ACTIVATION_HEIGHT_MAINNET
+        } else {
+            CLARITY_2_ACTIVATION_HEIGHT_TESTNET
+        };
         ClarityInstance {
             datastore,
             block_limit,
             mainnet,
+            clarity_2_activation_height,
         }
     }
 
+    /// Override the burnchain height at which Clarity 2 activates.  Every node on a network must
+    /// use the same height, so this is only meant for test networks.
+    pub fn set_clarity_2_activation_height(&mut self, height: u64) {
+        self.clarity_2_activation_height = height;
+    }
+
+    pub fn get_clarity_2_activation_height(&self) -> u64 {
+        self.clarity_2_activation_height
+    }
+
     pub fn with_marf<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut MARF<StacksBlockId>) -> R,
@@ -262,23 +357,30 @@ impl ClarityInstance {
         header_db: &'a dyn HeadersDB,
         burn_state_db: &'a dyn BurnStateDB,
     ) -> ClarityBlockConnection<'a> {
-        let mut datastore = self.datastore.begin(current, next);
-
-        let cost_track = {
-            let mut clarity_db = datastore.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
-            Some(
-                LimitedCostTracker::new(self.mainnet, self.block_limit.clone(), &mut clarity_db)
-                    .expect("FAIL: problem instantiating cost tracking"),
-            )
-        };
+        let datastore = self.datastore.begin(current, next);
 
-        ClarityBlockConnection {
+        let mut conn = ClarityBlockConnection {
             datastore,
             header_db,
             burn_state_db,
-            cost_track,
+            cost_track: Some(LimitedCostTracker::new_free()),
             mainnet: self.mainnet,
-        }
+        };
+
+        conn.initialize_clarity_2(self.clarity_2_activation_height);
+
+        // load the cost functions after the upgrade, so that the block which activates
+        //  Clarity 2 can already run the natives priced by costs-2.
+        let cost_track = {
+            let mut clarity_db = conn
+                .datastore
+                .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            LimitedCostTracker::new(self.mainnet, self.block_limit.clone(), &mut clarity_db)
+                .expect("FAIL: problem instantiating cost tracking")
+        };
+        conn.set_cost_tracker(cost_track);
+
+        conn
     }
 
     pub fn begin_genesis_block<'a>(
@@ -301,7 +403,7 @@ impl ClarityInstance {
         }
     }
 
-    /// begin a genesis block with the default cost contract
+    /// begin a genesis block with the default cost contracts, and Clarity 2 active,
     ///  used in testing + benchmarking
     pub fn begin_test_genesis_block<'a>(
         &'a mut self,
@@ -371,6 +473,8 @@ impl ClarityInstance {
                 .unwrap();
         });
 
+        conn.publish_costs_2();
+
         conn
     }
 
@@ -421,6 +525,7 @@ impl ClarityInstance {
             datastore,
             header_db,
             burn_state_db,
+            mainnet: self.mainnet,
         })
     }
 
@@ -537,6 +642,37 @@ impl ClarityConnection for ClarityReadOnlyConnection<'_> {
     }
 }
 
+impl<'a> ClarityReadOnlyConnection<'a> {
+    /// Process a transaction against this read-only state, with a fresh cost tracker limited to
+    /// `block_limit`.  The transaction's writes are kept in its edit log and discarded once
+    /// `todo` returns, so nothing is ever written to the datastore.
+    pub fn as_simulated_transaction<F, R>(&mut self, block_limit: ExecutionCost, todo: F) -> R
+    where
+        F: FnOnce(&mut ClarityTransactionConnection) -> R,
+    {
+        let mut cost_track = {
+            let mut clarity_db = self
+                .datastore
+                .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            Some(
+                LimitedCostTracker::new(self.mainnet, block_limit, &mut clarity_db)
+                    .expect("FAIL: problem instantiating cost tracking"),
+            )
+        };
+        let mut log = RollbackWrapperPersistedLog::new();
+        log.nest();
+        let mut tx = ClarityTransactionConnection {
+            store: &mut self.datastore,
+            cost_track: &mut cost_track,
+            header_db: self.header_db,
+            burn_state_db: self.burn_state_db,
+            log: Some(log),
+            mainnet: self.mainnet,
+        };
+        todo(&mut tx)
+    }
+}
+
 impl<'a> ClarityBlockConnection<'a> {
     /// Rolls back all changes in the current block by
     /// (1) dropping all writes from the current MARF tip,
@@ -730,11 +866,13 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
     }
 
     /// Analyze a provided smart contract, but do not write the analysis to the AnalysisDatabase
+    /// The contract is analyzed with the Clarity version in effect in this fork.
     pub fn analyze_smart_contract(
         &mut self,
         identifier: &QualifiedContractIdentifier,
         contract_content: &str,
     ) -> Result<(ContractAST, ContractAnalysis), Error> {
+        let clarity_version = self.with_clarity_db_readonly(|db| db.get_clarity_version());
         using!(self.cost_track, "cost tracker", |mut cost_track| {
             self.inner_with_analysis_db(|db| {
                 let ast_result = ast::build_ast(identifier, contract_content, &mut cost_track);
@@ -750,6 +888,7 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
                     db,
                     false,
                     cost_track,
+                    clarity_version,
                 );
 
                 match result {
@@ -845,9 +984,14 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
         from: &PrincipalData,
         to: &PrincipalData,
         amount: u128,
+        memo: &BuffData,
     ) -> Result<(Value, AssetMap, Vec<StacksTransactionEvent>), Error> {
         self.with_abort_callback(
-            |vm_env| vm_env.stx_transfer(from, to, amount).map_err(Error::from),
+            |vm_env| {
+                vm_env
+                    .stx_transfer(from, to, amount, memo)
+                    .map_err(Error::from)
+            },
             |_, _| false,
         )
         .and_then(|(value, assets, events, _)| Ok((value, assets, events)))
@@ -911,10 +1055,16 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
     where
         F: FnOnce(&AssetMap, &mut ClarityDatabase) -> bool,
     {
+        let clarity_version = self.with_clarity_db_readonly(|db| db.get_clarity_version());
         let (_, asset_map, events, aborted) = self.with_abort_callback(
             |vm_env| {
                 vm_env
-                    .initialize_contract_from_ast(identifier.clone(), contract_ast, contract_str)
+                    .initialize_contract_from_ast(
+                        identifier.clone(),
+                        contract_ast,
+                        contract_str,
+                        clarity_version,
+                    )
                     .map_err(Error::from)
             },
             abort_call_back,
@@ -994,6 +1144,8 @@ impl<'a, 'b> ClarityTransactionConnection<'a, 'b> {
 mod tests {
     use super::*;
     use chainstate::stacks::index::storage::TrieFileStorage;
+    use chainstate::stacks::StacksBlockHeader;
+    use core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
     use rusqlite::NO_PARAMS;
     use std::fs;
     use vm::analysis::errors::CheckErrors;
@@ -1236,6 +1388,141 @@ mod tests {
         assert!(conn.get_contract_hash(&contract_identifier).is_ok());
     }
 
+    #[test]
+    pub fn test_clarity_2_activation() {
+        let marf = MarfedKV::temporary();
+        let mut clarity_instance = ClarityInstance::new(false, marf, ExecutionCost::max_value());
+        let genesis = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+        );
+
+        {
+            let mut conn = clarity_instance.begin_genesis_block(
+                &StacksBlockId::sentinel(),
+                &genesis,
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+            for (name, code) in [
+                ("costs", BOOT_CODE_COSTS),
+                ("cost-voting", BOOT_CODE_COST_VOTING.as_str()),
+            ]
+            .iter()
+            {
+                let contract_id = boot_code_id(name, false);
+                conn.as_transaction(|tx| {
+                    let (ast, analysis) = tx.analyze_smart_contract(&contract_id, code).unwrap();
+                    tx.initialize_smart_contract(&contract_id, &ast, code, |_, _| false)
+                        .unwrap();
+                    tx.save_analysis(&contract_id, &analysis).unwrap();
+                });
+            }
+            conn.commit_block();
+        }
+
+        let sender: PrincipalData = StandardPrincipalData::transient().into();
+        let legacy_id = QualifiedContractIdentifier::local("legacy").unwrap();
+        let legacy = "(define-read-only (int-to-ascii (n int)) (+ n 1))
+                      (define-read-only (call-it) (int-to-ascii 1))
+                      (define-read-only (bind-it) (let ((slice? 2)) slice?))";
+        let native_id = QualifiedContractIdentifier::local("native").unwrap();
+        let native = "(define-read-only (call-it) (int-to-ascii 1))";
+
+        // the parent of both blocks below was mined at burn height 0, so Clarity 2 is not yet
+        //  active in this fork...
+        clarity_instance.set_clarity_2_activation_height(1);
+        {
+            let mut conn = clarity_instance.begin_block(
+                &genesis,
+                &StacksBlockId([1 as u8; 32]),
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+            assert!(!conn
+                .with_clarity_db_readonly(|db| db.has_contract(&boot_code_id("costs-2", false))));
+
+            conn.as_transaction(|tx| {
+                // ...so a Clarity 1 contract may use the names of Clarity 2 natives
+                let (ast, analysis) = tx.analyze_smart_contract(&legacy_id, legacy).unwrap();
+                assert_eq!(analysis.clarity_version, ClarityVersion::Clarity1);
+                tx.initialize_smart_contract(&legacy_id, &ast, legacy, |_, _| false)
+                    .unwrap();
+                tx.save_analysis(&legacy_id, &analysis).unwrap();
+
+                for (function, expected) in [("call-it", 2), ("bind-it", 2)].iter() {
+                    let (result, ..) = tx
+                        .run_contract_call(&sender, &legacy_id, function, &[], |_, _| false)
+                        .unwrap();
+                    assert_eq!(result, Value::Int(*expected));
+                }
+
+                // ...but cannot call them
+                match tx.analyze_smart_contract(&native_id, native).unwrap_err() {
+                    Error::Analysis(e) => {
+                        assert_eq!(e.err, CheckErrors::UnknownFunction("int-to-ascii".into()))
+                    }
+                    e => panic!("Unexpected error: {:?}", e),
+                }
+            });
+            conn.commit_block();
+        }
+
+        // ...and it activates in a sibling block that has reached the activation height
+        clarity_instance.set_clarity_2_activation_height(0);
+        {
+            let mut conn = clarity_instance.begin_block(
+                &genesis,
+                &StacksBlockId([2 as u8; 32]),
+                &NULL_HEADER_DB,
+                &NULL_BURN_STATE_DB,
+            );
+            assert!(conn
+                .with_clarity_db_readonly(|db| db.has_contract(&boot_code_id("costs-2", false))));
+            assert_eq!(conn.cost_so_far(), ExecutionCost::zero());
+            // costs-2 is itself a Clarity 2 contract
+            let costs_2 = conn
+                .with_clarity_db_readonly(|db| db.get_contract(&boot_code_id("costs-2", false)))
+                .unwrap();
+            assert_eq!(
+                costs_2.contract_context.get_clarity_version(),
+                &ClarityVersion::Clarity2
+            );
+
+            conn.as_transaction(|tx| {
+                let (ast, analysis) = tx.analyze_smart_contract(&native_id, native).unwrap();
+                assert_eq!(analysis.clarity_version, ClarityVersion::Clarity2);
+                tx.initialize_smart_contract(&native_id, &ast, native, |_, _| false)
+                    .unwrap();
+                tx.save_analysis(&native_id, &analysis).unwrap();
+
+                let (result, ..) = tx
+                    .run_contract_call(&sender, &native_id, "call-it", &[], |_, _| false)
+                    .unwrap();
+                assert_eq!(
+                    result,
+                    Value::string_ascii_from_bytes(b"1".to_vec()).unwrap()
+                );
+
+                // Clarity 2 contracts cannot redefine the new natives
+                let (ast, _) = tx.analyze_smart_contract(&legacy_id, legacy).unwrap();
+                match tx
+                    .initialize_smart_contract(&legacy_id, &ast, legacy, |_, _| false)
+                    .unwrap_err()
+                {
+                    Error::Interpreter(InterpreterError::Unchecked(e)) => {
+                        assert_eq!(e, CheckErrors::NameAlreadyUsed("int-to-ascii".into()))
+                    }
+                    e => panic!("Unexpected error: {:?}", e),
+                }
+            });
+
+            // the new natives are charged the costs defined in costs-2
+            assert!(conn.cost_so_far().runtime > 0);
+            conn.commit_block();
+        }
+    }
+
     #[test]
     pub fn test_block_roll_back() {
         let marf = MarfedKV::temporary();
@@ -23,24 +23,27 @@ use vm::ast;
 use vm::ast::ContractAST;
 use vm::callables::{DefinedFunction, FunctionIdentifier};
 use vm::contracts::Contract;
+use vm::costs::profiler::CostProfile;
 use vm::costs::{
     cost_functions, runtime_cost, ClarityCostFunctionReference, CostErrors, CostTracker,
     ExecutionCost, LimitedCostTracker,
 };
+use vm::coverage::CoverageReporter;
 use vm::database::{
     ClarityDatabase, DataMapMetadata, DataVariableMetadata, FungibleTokenMetadata,
     NonFungibleTokenMetadata,
 };
+use vm::debug::Debugger;
 use vm::errors::{CheckErrors, InterpreterError, InterpreterResult as Result, RuntimeErrorType};
 use vm::functions::handle_contract_call_special_cases;
 use vm::representations::{ClarityName, ContractName, SymbolicExpression};
 use vm::stx_transfer_consolidated;
 use vm::types::signatures::FunctionSignature;
 use vm::types::{
-    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature,
-    Value,
+    AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, TraitIdentifier,
+    TypeSignature, Value,
 };
-use vm::{eval, is_reserved};
+use vm::{eval, is_reserved, ClarityVersion};
 
 use chainstate::burn::{BlockHeaderHash, VRFSeed};
 use chainstate::stacks::db::StacksChainState;
@@ -109,6 +112,8 @@ pub struct GlobalContext<'a> {
     read_only: Vec<bool>,
     pub cost_track: LimitedCostTracker,
     pub mainnet: bool,
+    pub coverage: Option<CoverageReporter>,
+    pub debugger: Option<Debugger>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -127,6 +132,8 @@ pub struct ContractContext {
     pub meta_nft: HashMap<ClarityName, NonFungibleTokenMetadata>,
     pub meta_ft: HashMap<ClarityName, FungibleTokenMetadata>,
     pub data_size: u64,
+    #[serde(default)]
+    pub clarity_version: ClarityVersion,
 }
 
 pub struct LocalContext<'a> {
@@ -446,7 +453,10 @@ impl<'a> OwnedEnvironment<'a> {
     pub fn new(database: ClarityDatabase<'a>) -> OwnedEnvironment<'a> {
         OwnedEnvironment {
             context: GlobalContext::new(false, database, LimitedCostTracker::new_free()),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::latest(),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -458,7 +468,10 @@ impl<'a> OwnedEnvironment<'a> {
 
         OwnedEnvironment {
             context: GlobalContext::new(false, database, cost_track),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::latest(),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -466,7 +479,10 @@ impl<'a> OwnedEnvironment<'a> {
     pub fn new_free(mainnet: bool, database: ClarityDatabase<'a>) -> OwnedEnvironment<'a> {
         OwnedEnvironment {
             context: GlobalContext::new(mainnet, database, LimitedCostTracker::new_free()),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::latest(),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -478,11 +494,37 @@ impl<'a> OwnedEnvironment<'a> {
     ) -> OwnedEnvironment<'a> {
         OwnedEnvironment {
             context: GlobalContext::new(mainnet, database, cost_tracker),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::latest(),
+            ),
             call_stack: CallStack::new(),
         }
     }
 
+    /// Record which expressions of which contracts get evaluated in this environment
+    pub fn set_coverage_reporter(&mut self, reporter: CoverageReporter) {
+        self.context.coverage = Some(reporter);
+    }
+
+    pub fn take_coverage_reporter(&mut self) -> Option<CoverageReporter> {
+        self.context.coverage.take()
+    }
+
+    /// Debug the code evaluated in this environment
+    pub fn set_debugger(&mut self, debugger: Debugger) {
+        self.context.debugger = Some(debugger);
+    }
+
+    pub fn take_debugger(&mut self) -> Option<Debugger> {
+        self.context.debugger.take()
+    }
+
+    /// Take the profile of the costs charged so far, if the cost tracker is profiling
+    pub fn take_cost_profile(&mut self) -> Option<CostProfile> {
+        self.context.cost_track.take_profile()
+    }
+
     pub fn get_exec_environment<'b>(&'b mut self, sender: Option<Value>) -> Environment<'b, 'a> {
         Environment::new(
             &mut self.context,
@@ -538,6 +580,7 @@ impl<'a> OwnedEnvironment<'a> {
         contract_identifier: QualifiedContractIdentifier,
         contract_content: &ContractAST,
         contract_string: &str,
+        version: ClarityVersion,
     ) -> Result<((), AssetMap, Vec<StacksTransactionEvent>)> {
         self.execute_in_env(
             Value::from(contract_identifier.issuer.clone()),
@@ -546,6 +589,7 @@ impl<'a> OwnedEnvironment<'a> {
                     contract_identifier,
                     contract_content,
                     contract_string,
+                    version,
                 )
             },
         )
@@ -568,9 +612,10 @@ impl<'a> OwnedEnvironment<'a> {
         from: &PrincipalData,
         to: &PrincipalData,
         amount: u128,
+        memo: &BuffData,
     ) -> Result<(Value, AssetMap, Vec<StacksTransactionEvent>)> {
         self.execute_in_env(Value::Principal(from.clone()), |exec_env| {
-            exec_env.stx_transfer(from, to, amount)
+            exec_env.stx_transfer(from, to, amount, memo)
         })
     }
 
@@ -962,13 +1007,20 @@ impl<'a, 'b> Environment<'a, 'b> {
         result
     }
 
+    /// Publish a contract with the latest Clarity version.  Contracts published on-chain go
+    ///  through `initialize_contract_from_ast()` with the version in effect at their block.
     pub fn initialize_contract(
         &mut self,
         contract_identifier: QualifiedContractIdentifier,
         contract_content: &str,
     ) -> Result<()> {
         let contract_ast = ast::build_ast(&contract_identifier, contract_content, self)?;
-        self.initialize_contract_from_ast(contract_identifier, &contract_ast, &contract_content)
+        self.initialize_contract_from_ast(
+            contract_identifier,
+            &contract_ast,
+            &contract_content,
+            ClarityVersion::latest(),
+        )
     }
 
     pub fn initialize_contract_from_ast(
@@ -976,6 +1028,7 @@ impl<'a, 'b> Environment<'a, 'b> {
         contract_identifier: QualifiedContractIdentifier,
         contract_content: &ContractAST,
         contract_string: &str,
+        version: ClarityVersion,
     ) -> Result<()> {
         self.global_context.begin();
 
@@ -1011,6 +1064,7 @@ impl<'a, 'b> Environment<'a, 'b> {
                 contract_identifier.clone(),
                 contract_content,
                 &mut self.global_context,
+                version,
             );
             self.drop_memory(memory_use);
             result
@@ -1045,9 +1099,10 @@ impl<'a, 'b> Environment<'a, 'b> {
         from: &PrincipalData,
         to: &PrincipalData,
         amount: u128,
+        memo: &BuffData,
     ) -> Result<Value> {
         self.global_context.begin();
-        let result = stx_transfer_consolidated(self, from, to, amount);
+        let result = stx_transfer_consolidated(self, from, to, amount, memo);
         match result {
             Ok(value) => match value.clone().expect_result() {
                 Ok(_) => {
@@ -1109,11 +1164,13 @@ impl<'a, 'b> Environment<'a, 'b> {
         sender: PrincipalData,
         recipient: PrincipalData,
         amount: u128,
+        memo: BuffData,
     ) -> Result<()> {
         let event_data = STXTransferEventData {
             sender,
             recipient,
             amount,
+            memo,
         };
 
         if let Some(batch) = self.global_context.event_batches.last_mut() {
@@ -1278,6 +1335,41 @@ impl<'a> GlobalContext<'a> {
             asset_maps: Vec::new(),
             event_batches: Vec::new(),
             mainnet,
+            coverage: None,
+            debugger: None,
+        }
+    }
+
+    /// Attribute the expressions evaluated from now on to `contract` in the coverage report, if
+    /// coverage is being recorded
+    pub fn begin_coverage(&mut self, contract: &QualifiedContractIdentifier) {
+        if let Some(ref mut coverage) = self.coverage {
+            coverage.begin_code(contract);
+        }
+    }
+
+    /// Undo the last call to `begin_coverage`
+    pub fn end_coverage(&mut self) {
+        if let Some(ref mut coverage) = self.coverage {
+            coverage.end_code();
+        }
+    }
+
+    /// Tell the debugger, if there is one, that a user-defined function is being called
+    pub fn begin_debug_function(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        name: &ClarityName,
+    ) {
+        if let Some(ref mut debugger) = self.debugger {
+            debugger.begin_function(contract, name);
+        }
+    }
+
+    /// Tell the debugger, if there is one, that the last user-defined function called returned
+    pub fn finish_debug_function(&mut self, result: &Result<Value>) {
+        if let Some(ref mut debugger) = self.debugger {
+            debugger.finish_function(result);
         }
     }
 
@@ -1442,7 +1534,7 @@ impl<'a> GlobalContext<'a> {
 }
 
 impl ContractContext {
-    pub fn new(contract_identifier: QualifiedContractIdentifier) -> Self {
+    pub fn new(contract_identifier: QualifiedContractIdentifier, version: ClarityVersion) -> Self {
         Self {
             contract_identifier,
             variables: HashMap::new(),
@@ -1455,9 +1547,14 @@ impl ContractContext {
             meta_data_var: HashMap::new(),
             meta_nft: HashMap::new(),
             meta_ft: HashMap::new(),
+            clarity_version: version,
         }
     }
 
+    pub fn get_clarity_version(&self) -> &ClarityVersion {
+        &self.clarity_version
+    }
+
     pub fn lookup_variable(&self, name: &str) -> Option<&Value> {
         self.variables.get(name)
     }
@@ -1478,7 +1575,7 @@ impl ContractContext {
     }
 
     pub fn is_name_used(&self, name: &str) -> bool {
-        is_reserved(name)
+        is_reserved(name, &self.clarity_version)
             || self.variables.contains_key(name)
             || self.functions.contains_key(name)
             || self.persisted_names.contains(name)
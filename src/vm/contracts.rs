@@ -15,13 +15,19 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::convert::TryInto;
+use vm::analysis::ContractAnalysis;
 use vm::ast::ContractAST;
-use vm::callables::CallableType;
+use vm::callables::{CallableType, DefineType, DefinedFunction};
 use vm::contexts::{ContractContext, Environment, GlobalContext, LocalContext};
-use vm::errors::InterpreterResult as Result;
+use vm::costs::MemoryConsumer;
+use vm::database::{
+    DataMapMetadata, DataVariableMetadata, FungibleTokenMetadata, NonFungibleTokenMetadata,
+};
+use vm::errors::{CheckErrors, InterpreterResult as Result};
 use vm::representations::SymbolicExpression;
-use vm::types::QualifiedContractIdentifier;
-use vm::{apply, eval_all, Value};
+use vm::representations::SymbolicExpressionType::{AtomValue, LiteralValue};
+use vm::types::{parse_name_type_pairs, QualifiedContractIdentifier, TypeSignature};
+use vm::{apply, eval_all, ClarityName, ClarityVersion, DefineFunctionsParsed, Value};
 
 #[derive(Serialize, Deserialize)]
 pub struct Contract {
@@ -35,8 +41,9 @@ impl Contract {
         contract_identifier: QualifiedContractIdentifier,
         contract: &ContractAST,
         global_context: &mut GlobalContext,
+        version: ClarityVersion,
     ) -> Result<Contract> {
-        let mut contract_context = ContractContext::new(contract_identifier);
+        let mut contract_context = ContractContext::new(contract_identifier, version);
 
         eval_all(&contract.expressions, &mut contract_context, global_context)?;
 
@@ -44,4 +51,174 @@ impl Contract {
             contract_context: contract_context,
         })
     }
+
+    /// Rebuild a published contract from its AST and analysis, without evaluating it.  This is
+    /// for contracts whose metadata comes from a chain state snapshot.  Only evaluation knows
+    /// the values of the constants and the supplies of the bounded fungible tokens, so those
+    /// are taken from `published`, which must define them with values that the source and the
+    /// analysis allow.
+    pub fn rebuild_from_ast(
+        contract_identifier: QualifiedContractIdentifier,
+        contract: &ContractAST,
+        analysis: &ContractAnalysis,
+        version: ClarityVersion,
+        published: &ContractContext,
+    ) -> Result<Contract> {
+        let mut contract_context = ContractContext::new(contract_identifier, version);
+        let context_name = contract_context.contract_identifier.to_string();
+
+        for exp in contract.expressions.iter() {
+            let define = match DefineFunctionsParsed::try_parse(exp)? {
+                Some(define) => define,
+                None => continue,
+            };
+            match define {
+                DefineFunctionsParsed::Constant { name, value } => {
+                    let published_value = published
+                        .variables
+                        .get(name)
+                        .ok_or_else(|| CheckErrors::UndefinedVariable(name.to_string()))?;
+                    let value_type = analysis
+                        .get_variable_type(name)
+                        .ok_or_else(|| CheckErrors::UndefinedVariable(name.to_string()))?;
+                    check_published_value(value, value_type, published_value)?;
+
+                    contract_context.data_size += published_value.get_memory_use();
+                    contract_context
+                        .variables
+                        .insert(name.clone(), published_value.clone());
+                }
+                DefineFunctionsParsed::PrivateFunction { signature, body } => {
+                    let (name, function) =
+                        rebuild_function(signature, body, DefineType::Private, &context_name)?;
+                    contract_context.functions.insert(name, function);
+                }
+                DefineFunctionsParsed::ReadOnlyFunction { signature, body } => {
+                    let (name, function) =
+                        rebuild_function(signature, body, DefineType::ReadOnly, &context_name)?;
+                    contract_context.functions.insert(name, function);
+                }
+                DefineFunctionsParsed::PublicFunction { signature, body } => {
+                    let (name, function) =
+                        rebuild_function(signature, body, DefineType::Public, &context_name)?;
+                    contract_context.functions.insert(name, function);
+                }
+                DefineFunctionsParsed::NonFungibleToken { name, nft_type } => {
+                    let key_type = TypeSignature::parse_type_repr(nft_type, &mut ())?;
+                    contract_context.persisted_names.insert(name.clone());
+                    contract_context
+                        .meta_nft
+                        .insert(name.clone(), NonFungibleTokenMetadata { key_type });
+                }
+                DefineFunctionsParsed::BoundedFungibleToken { name, max_supply } => {
+                    let published_supply = published
+                        .meta_ft
+                        .get(name)
+                        .and_then(|metadata| metadata.total_supply)
+                        .ok_or_else(|| CheckErrors::NoSuchFT(name.to_string()))?;
+                    check_published_value(
+                        max_supply,
+                        &TypeSignature::UIntType,
+                        &Value::UInt(published_supply),
+                    )?;
+
+                    contract_context.persisted_names.insert(name.clone());
+                    contract_context.meta_ft.insert(
+                        name.clone(),
+                        FungibleTokenMetadata {
+                            total_supply: Some(published_supply),
+                        },
+                    );
+                }
+                DefineFunctionsParsed::UnboundedFungibleToken { name } => {
+                    contract_context.persisted_names.insert(name.clone());
+                    contract_context
+                        .meta_ft
+                        .insert(name.clone(), FungibleTokenMetadata { total_supply: None });
+                }
+                DefineFunctionsParsed::Map {
+                    name,
+                    key_type,
+                    value_type,
+                } => {
+                    let key_type = TypeSignature::parse_type_repr(key_type, &mut ())?;
+                    let value_type = TypeSignature::parse_type_repr(value_type, &mut ())?;
+                    contract_context.persisted_names.insert(name.clone());
+                    contract_context.meta_data_map.insert(
+                        name.clone(),
+                        DataMapMetadata {
+                            key_type,
+                            value_type,
+                        },
+                    );
+                }
+                DefineFunctionsParsed::PersistedVariable {
+                    name, data_type, ..
+                } => {
+                    let value_type = TypeSignature::parse_type_repr(data_type, &mut ())?;
+                    contract_context.persisted_names.insert(name.clone());
+                    contract_context
+                        .meta_data_var
+                        .insert(name.clone(), DataVariableMetadata { value_type });
+                }
+                DefineFunctionsParsed::Trait { name, functions } => {
+                    let trait_signature = TypeSignature::parse_trait_type_repr(functions, &mut ())?;
+                    contract_context
+                        .defined_traits
+                        .insert(name.clone(), trait_signature);
+                }
+                DefineFunctionsParsed::UseTrait { .. } => {}
+                DefineFunctionsParsed::ImplTrait { trait_identifier } => {
+                    contract_context
+                        .implemented_traits
+                        .insert(trait_identifier.clone());
+                }
+            }
+        }
+
+        Ok(Contract {
+            contract_context: contract_context,
+        })
+    }
+}
+
+/// Check a value that evaluating `expression` produced when the contract was published: it
+/// must have the expected type, and be the expression's value if the expression is a literal.
+fn check_published_value(
+    expression: &SymbolicExpression,
+    expected_type: &TypeSignature,
+    value: &Value,
+) -> Result<()> {
+    let literal = match expression.expr {
+        AtomValue(ref literal) | LiteralValue(ref literal) => Some(literal),
+        _ => None,
+    };
+    if !expected_type.admits(value) || literal.map_or(false, |literal| literal != value) {
+        return Err(CheckErrors::TypeValueError(expected_type.clone(), value.clone()).into());
+    }
+    Ok(())
+}
+
+fn rebuild_function(
+    signature: &[SymbolicExpression],
+    body: &SymbolicExpression,
+    define_type: DefineType,
+    context_name: &str,
+) -> Result<(ClarityName, DefinedFunction)> {
+    let (function_symbol, arg_symbols) = signature
+        .split_first()
+        .ok_or(CheckErrors::DefineFunctionBadSignature)?;
+    let function_name = function_symbol
+        .match_atom()
+        .ok_or(CheckErrors::ExpectedName)?;
+    let arguments = parse_name_type_pairs(arg_symbols, &mut ())?;
+
+    let function = DefinedFunction::new(
+        arguments,
+        body.clone(),
+        define_type,
+        function_name,
+        context_name,
+    );
+    Ok((function_name.clone(), function))
 }
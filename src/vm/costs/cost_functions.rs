@@ -130,4 +130,43 @@ define_named_enum!(ClarityCostFunction {
     NftOwner("cost_nft_owner"),
     NftBurn("cost_nft_burn"),
     PoisonMicroblock("poison_microblock"),
+    IntToAscii("cost_int_to_ascii"),
+    IntToUtf8("cost_int_to_utf8"),
+    StringToInt("cost_string_to_int"),
+    StringToUInt("cost_string_to_uint"),
+    BuffToIntLe("cost_buff_to_int_le"),
+    BuffToIntBe("cost_buff_to_int_be"),
+    BuffToUIntLe("cost_buff_to_uint_le"),
+    BuffToUIntBe("cost_buff_to_uint_be"),
+    IntToBuffLe("cost_int_to_buff_le"),
+    IntToBuffBe("cost_int_to_buff_be"),
+    Slice("cost_slice"),
+    ReplaceAt("cost_replace_at"),
+    BitwiseAnd("cost_bitwise_and"),
+    BitwiseOr("cost_bitwise_or"),
+    BitwiseNot("cost_bitwise_not"),
+    BitwiseLShift("cost_bitwise_left_shift"),
+    BitwiseRShift("cost_bitwise_right_shift"),
+    PrincipalDestruct("cost_principal_destruct"),
+    PrincipalConstruct("cost_principal_construct"),
+    StxGetAccount("cost_stx_account"),
+    GetBurnBlockInfo("cost_burn_block_info"),
+    StxTransferMemo("cost_stx_transfer_memo"),
 });
+
+impl ClarityCostFunction {
+    /// The name of the boot contract that defines this cost function's default cost.  The costs
+    ///  of the native functions introduced in Clarity 2 are defined in `costs-2`, which is
+    ///  published when Clarity 2 activates.
+    pub fn get_boot_contract_name(&self) -> &'static str {
+        use self::ClarityCostFunction::*;
+        match self {
+            IntToAscii | IntToUtf8 | StringToInt | StringToUInt | BuffToIntLe | BuffToIntBe
+            | BuffToUIntLe | BuffToUIntBe | IntToBuffLe | IntToBuffBe | Slice | ReplaceAt
+            | BitwiseAnd | BitwiseOr | BitwiseNot | BitwiseLShift | BitwiseRShift
+            | PrincipalDestruct | PrincipalConstruct | StxGetAccount | GetBurnBlockInfo
+            | StxTransferMemo => "costs-2",
+            _ => "costs",
+        }
+    }
+}
@@ -16,6 +16,7 @@
 
 pub mod constants;
 pub mod cost_functions;
+pub mod profiler;
 
 use regex::internal::Exec;
 use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
@@ -30,6 +31,7 @@ use chainstate::stacks::boot::boot_code_id;
 use vm::ast::ContractAST;
 use vm::contexts::{ContractContext, Environment, GlobalContext, OwnedEnvironment};
 use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::profiler::CostProfile;
 use vm::database::{marf::NullBackingStore, ClarityDatabase, MemoryBackingStore};
 use vm::errors::{Error, InterpreterResult};
 use vm::types::signatures::FunctionType::Fixed;
@@ -234,6 +236,7 @@ pub struct LimitedCostTracker {
     memory_limit: u64,
     free: bool,
     mainnet: bool,
+    profile: Option<CostProfile>,
 }
 
 #[cfg(test)]
@@ -329,6 +332,91 @@ fn store_state_summary(
     Ok(())
 }
 
+/// Check the cost state summary that a chain state snapshot holds for the fork of the open chain
+/// tip: each cost function that it installs must be one that a processed confirmed proposal of
+/// the cost-voting contract installs.
+pub fn check_state_summary(
+    mainnet: bool,
+    clarity_db: &mut ClarityDatabase,
+    serialized_summary: &str,
+) -> bool {
+    let summary: SerializedCostStateSummary = match serde_json::from_str(serialized_summary) {
+        Ok(summary) => summary,
+        Err(_) => return false,
+    };
+    let last_processed_count = clarity_db
+        .get_value("vm-costs::last_processed_count", &TypeSignature::UIntType)
+        .unwrap_or(Value::UInt(0))
+        .expect_u128();
+    let cost_voting_contract = boot_code_id("cost-voting", mainnet);
+
+    let mut installed = vec![];
+    for confirmed_proposal in 0..last_processed_count {
+        let entry = clarity_db
+            .fetch_entry_unknown_descriptor(
+                &cost_voting_contract,
+                "confirmed-proposals",
+                &Value::from(
+                    TupleData::from_data(vec![(
+                        "confirmed-id".into(),
+                        Value::UInt(confirmed_proposal),
+                    )])
+                    .expect("BUG: failed to construct simple tuple"),
+                ),
+            )
+            .expect("BUG: Failed querying confirmed-proposals")
+            .expect_optional()
+            .expect("BUG: confirmed-proposal-count exceeds stored proposals")
+            .expect_tuple();
+        let field = |name: &str| {
+            entry
+                .get(name)
+                .expect("BUG: malformed cost proposal tuple")
+                .clone()
+        };
+        if let (PrincipalData::Contract(target_contract), PrincipalData::Contract(cost_contract)) = (
+            field("function-contract").expect_principal(),
+            field("cost-function-contract").expect_principal(),
+        ) {
+            installed.push((
+                target_contract,
+                field("function-name").expect_ascii(),
+                ClarityCostFunctionReference {
+                    contract_id: cost_contract,
+                    function_name: field("cost-function-name").expect_ascii(),
+                },
+            ));
+        }
+    }
+
+    let is_installed = |target_contract: &QualifiedContractIdentifier,
+                        target_function: &str,
+                        reference: &ClarityCostFunctionReference| {
+        installed
+            .iter()
+            .any(|(contract, function, installed_reference)| {
+                contract == target_contract
+                    && function == target_function
+                    && installed_reference == reference
+            })
+    };
+    summary
+        .cost_function_references
+        .iter()
+        .all(|(cost_function, reference)| {
+            is_installed(
+                &boot_code_id(cost_function.get_boot_contract_name(), mainnet),
+                cost_function.get_name_str(),
+                reference,
+            )
+        })
+        && summary.contract_call_circuits.iter().all(
+            |((target_contract, target_function), reference)| {
+                is_installed(target_contract, target_function, reference)
+            },
+        )
+}
+
 ///
 /// This method loads a cost state summary structure from the currently open stacks chain tip
 ///   In doing so, it reads from the cost-voting contract to find any newly confirmed proposals,
@@ -505,9 +593,15 @@ fn load_cost_functions(
             }
         };
 
-        if target_contract == boot_code_id("costs", mainnet) {
+        // costs-2 only holds boot code cost functions once Clarity 2 has published it
+        if target_contract == boot_code_id("costs", mainnet)
+            || (target_contract == boot_code_id("costs-2", mainnet)
+                && clarity_db.has_contract(&target_contract))
+        {
             // refering to one of the boot code cost functions
-            let target = match ClarityCostFunction::lookup_by_name(&target_function) {
+            let target = match ClarityCostFunction::lookup_by_name(&target_function)
+                .filter(|f| target_contract == boot_code_id(f.get_boot_contract_name(), mainnet))
+            {
                 Some(cost_func) => cost_func,
                 None => {
                     warn!("Confirmed cost proposal invalid: function-name does not reference a Clarity cost function";
@@ -589,6 +683,7 @@ impl LimitedCostTracker {
             memory: 0,
             free: false,
             mainnet,
+            profile: None,
         };
         assert!(clarity_db.is_stack_empty());
         cost_tracker.load_costs(clarity_db, true)?;
@@ -610,6 +705,7 @@ impl LimitedCostTracker {
             memory: 0,
             free: false,
             mainnet,
+            profile: None,
         };
         cost_tracker.load_costs(clarity_db, false)?;
         Ok(cost_tracker)
@@ -632,6 +728,7 @@ impl LimitedCostTracker {
             memory_limit: CLARITY_MEMORY_LIMIT,
             free: true,
             mainnet: false,
+            profile: None,
         }
     }
 
@@ -655,9 +752,21 @@ impl LimitedCostTracker {
         let mut cost_contracts = HashMap::new();
         let mut m = HashMap::new();
         for f in ClarityCostFunction::ALL.iter() {
-            let cost_function_ref = cost_function_references.remove(&f).unwrap_or_else(|| {
-                ClarityCostFunctionReference::new(boot_costs_id.clone(), f.get_name())
-            });
+            let cost_function_ref = match cost_function_references.remove(&f) {
+                Some(cost_function_ref) => cost_function_ref,
+                None => {
+                    let boot_contract_id = boot_code_id(f.get_boot_contract_name(), self.mainnet);
+                    if boot_contract_id != boot_costs_id
+                        && !cost_contracts.contains_key(&boot_contract_id)
+                        && !clarity_db.has_contract(&boot_contract_id)
+                    {
+                        // costs-2 is only published when Clarity 2 activates.  Until then,
+                        //  no contract can call the natives that it prices.
+                        continue;
+                    }
+                    ClarityCostFunctionReference::new(boot_contract_id, f.get_name())
+                }
+            };
             if !cost_contracts.contains_key(&cost_function_ref.contract_id) {
                 let contract_context = match clarity_db.get_contract(&cost_function_ref.contract_id)
                 {
@@ -713,6 +822,36 @@ impl LimitedCostTracker {
     pub fn get_limit(&self) -> ExecutionCost {
         self.limit.clone()
     }
+
+    /// Start attributing the costs charged to this tracker to the functions that incur them
+    pub fn enable_profiling(&mut self) {
+        if self.profile.is_none() {
+            self.profile = Some(CostProfile::new());
+        }
+    }
+
+    pub fn get_profile(&self) -> Option<&CostProfile> {
+        self.profile.as_ref()
+    }
+
+    pub fn take_profile(&mut self) -> Option<CostProfile> {
+        self.profile.take()
+    }
+
+    /// Attribute the costs charged from now on to a call of the function named by `make_name`,
+    /// if profiling
+    pub fn begin_profile_frame<F: FnOnce() -> String>(&mut self, make_name: F) {
+        if let Some(ref mut profile) = self.profile {
+            profile.begin_frame(make_name());
+        }
+    }
+
+    /// Undo the last call to `begin_profile_frame`
+    pub fn end_profile_frame(&mut self) {
+        if let Some(ref mut profile) = self.profile {
+            profile.end_frame();
+        }
+    }
 }
 
 fn parse_cost(
@@ -801,6 +940,9 @@ fn add_cost(
     cost: ExecutionCost,
 ) -> std::result::Result<(), CostErrors> {
     s.total.add(&cost)?;
+    if let Some(ref mut profile) = s.profile {
+        profile.add_cost(&cost);
+    }
     if s.total.exceeds(&s.limit) {
         Err(CostErrors::CostBalanceExceeded(
             s.total.clone(),
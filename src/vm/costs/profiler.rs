@@ -0,0 +1,302 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+use vm::costs::ExecutionCost;
+
+/// The name given to costs incurred outside of any function, such as contract analysis and
+/// top-level contract code
+pub const PROFILE_TOP_LEVEL: &str = "top-level";
+
+/// A dimension of `ExecutionCost`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileMetric {
+    Runtime,
+    ReadCount,
+    ReadLength,
+    WriteCount,
+    WriteLength,
+}
+
+impl ProfileMetric {
+    pub fn get(&self, cost: &ExecutionCost) -> u64 {
+        match *self {
+            ProfileMetric::Runtime => cost.runtime,
+            ProfileMetric::ReadCount => cost.read_count,
+            ProfileMetric::ReadLength => cost.read_length,
+            ProfileMetric::WriteCount => cost.write_count,
+            ProfileMetric::WriteLength => cost.write_length,
+        }
+    }
+}
+
+impl fmt::Display for ProfileMetric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            ProfileMetric::Runtime => "runtime",
+            ProfileMetric::ReadCount => "read_count",
+            ProfileMetric::ReadLength => "read_length",
+            ProfileMetric::WriteCount => "write_count",
+            ProfileMetric::WriteLength => "write_length",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ProfileMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ProfileMetric, String> {
+        match s {
+            "runtime" => Ok(ProfileMetric::Runtime),
+            "read_count" => Ok(ProfileMetric::ReadCount),
+            "read_length" => Ok(ProfileMetric::ReadLength),
+            "write_count" => Ok(ProfileMetric::WriteCount),
+            "write_length" => Ok(ProfileMetric::WriteLength),
+            _ => Err(format!("Unknown cost metric '{}'", s)),
+        }
+    }
+}
+
+/// The costs attributed to one function
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCost {
+    /// A native function's name, or a user-defined function's `contract:name`
+    pub name: String,
+    pub calls: u64,
+    /// What the function cost, not counting the functions it called
+    pub self_cost: ExecutionCost,
+    /// What the function cost, counting the functions it called
+    pub total_cost: ExecutionCost,
+}
+
+/// Attributes the costs charged to a `LimitedCostTracker` to the call stack of native functions,
+/// user-defined functions, and contract-calls that incurred them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CostProfile {
+    // the functions being called, innermost last
+    stack: Vec<String>,
+    // the cost incurred by each call stack, not counting the functions it called
+    costs: BTreeMap<Vec<String>, ExecutionCost>,
+    calls: HashMap<String, u64>,
+}
+
+impl CostProfile {
+    pub fn new() -> CostProfile {
+        CostProfile::default()
+    }
+
+    pub fn begin_frame(&mut self, name: String) {
+        *self.calls.entry(name.clone()).or_insert(0) += 1;
+        self.stack.push(name);
+    }
+
+    pub fn end_frame(&mut self) {
+        self.stack.pop();
+    }
+
+    pub fn add_cost(&mut self, cost: &ExecutionCost) {
+        if self.costs.contains_key(&self.stack) {
+            if let Some(stack_cost) = self.costs.get_mut(&self.stack) {
+                // the tracker's total overflows before any part of it does
+                let _ = stack_cost.add(cost);
+            }
+        } else {
+            self.costs.insert(self.stack.clone(), cost.clone());
+        }
+    }
+
+    /// Get the cost of each function that was called (plus the top-level code), most expensive
+    /// (by total runtime) first
+    pub fn get_function_costs(&self) -> Vec<FunctionCost> {
+        let mut function_costs: HashMap<&str, FunctionCost> = HashMap::new();
+        for (stack, cost) in self.costs.iter() {
+            let self_name = stack
+                .last()
+                .map(|name| name.as_str())
+                .unwrap_or(PROFILE_TOP_LEVEL);
+            // a function's total cost counts what it cost in each call stack once, even if it
+            // appears in the stack more than once
+            let names: BTreeSet<&str> = stack
+                .iter()
+                .map(|name| name.as_str())
+                .chain(Some(self_name))
+                .collect();
+            for name in names.into_iter() {
+                let function_cost = function_costs.entry(name).or_insert_with(|| FunctionCost {
+                    name: name.to_string(),
+                    calls: self.calls.get(name).cloned().unwrap_or(0),
+                    self_cost: ExecutionCost::zero(),
+                    total_cost: ExecutionCost::zero(),
+                });
+                let _ = function_cost.total_cost.add(cost);
+                if name == self_name {
+                    let _ = function_cost.self_cost.add(cost);
+                }
+            }
+        }
+
+        let mut function_costs: Vec<_> = function_costs.into_iter().map(|(_, c)| c).collect();
+        function_costs.sort_by(|a, b| {
+            b.total_cost
+                .runtime
+                .cmp(&a.total_cost.runtime)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        function_costs
+    }
+
+    /// Write one dimension of the profile in the folded-stack format that flame graph tools
+    /// read: one line per call stack, listing its frames outermost first and separated by `;`,
+    /// followed by what that stack cost.
+    pub fn write_folded<W: Write>(&self, fd: &mut W, metric: ProfileMetric) -> io::Result<()> {
+        for (stack, cost) in self.costs.iter() {
+            let amount = metric.get(cost);
+            if amount == 0 {
+                continue;
+            }
+            if stack.len() == 0 {
+                writeln!(fd, "{} {}", PROFILE_TOP_LEVEL, amount)?;
+            } else {
+                writeln!(fd, "{} {}", stack.join(";"), amount)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a table of each function's calls and self cost, plus its total runtime
+    pub fn write_table<W: Write>(&self, fd: &mut W) -> io::Result<()> {
+        let function_costs = self.get_function_costs();
+        let name_width = function_costs
+            .iter()
+            .map(|function_cost| function_cost.name.len())
+            .chain(Some("function".len()))
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            fd,
+            "{:<width$} {:>8} {:>14} {:>14} {:>10} {:>12} {:>11} {:>12}",
+            "function",
+            "calls",
+            "total_runtime",
+            "runtime",
+            "read_count",
+            "read_length",
+            "write_count",
+            "write_length",
+            width = name_width
+        )?;
+        for function_cost in function_costs.iter() {
+            writeln!(
+                fd,
+                "{:<width$} {:>8} {:>14} {:>14} {:>10} {:>12} {:>11} {:>12}",
+                function_cost.name,
+                function_cost.calls,
+                function_cost.total_cost.runtime,
+                function_cost.self_cost.runtime,
+                function_cost.self_cost.read_count,
+                function_cost.self_cost.read_length,
+                function_cost.self_cost.write_count,
+                function_cost.self_cost.write_length,
+                width = name_width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cost(runtime: u64, read_count: u64) -> ExecutionCost {
+        ExecutionCost {
+            runtime,
+            read_count,
+            read_length: 0,
+            write_count: 0,
+            write_length: 0,
+        }
+    }
+
+    #[test]
+    fn test_cost_profile() {
+        let mut profile = CostProfile::new();
+        profile.add_cost(&cost(5, 0));
+        profile.begin_frame("S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!".into());
+        profile.add_cost(&cost(10, 0));
+        for _ in 0..2 {
+            profile.begin_frame("+".into());
+            profile.add_cost(&cost(3, 0));
+            profile.begin_frame("+".into());
+            profile.add_cost(&cost(1, 0));
+            profile.end_frame();
+            profile.end_frame();
+        }
+        profile.begin_frame("map-get?".into());
+        profile.add_cost(&cost(20, 1));
+        profile.end_frame();
+        profile.end_frame();
+
+        let mut folded = vec![];
+        profile
+            .write_folded(&mut folded, ProfileMetric::Runtime)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(folded).unwrap(),
+            "top-level 5
+S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint! 10
+S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;+ 6
+S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;+;+ 2
+S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;map-get? 20
+"
+        );
+
+        let mut folded = vec![];
+        profile
+            .write_folded(&mut folded, ProfileMetric::ReadCount)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(folded).unwrap(),
+            "S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;map-get? 1\n"
+        );
+
+        let function_costs = profile.get_function_costs();
+        let names: Vec<_> = function_costs.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!",
+                "map-get?",
+                "+",
+                "top-level"
+            ]
+        );
+        // nested calls of a function count toward its total once
+        assert_eq!(function_costs[2].calls, 4);
+        assert_eq!(function_costs[2].self_cost, cost(8, 0));
+        assert_eq!(function_costs[2].total_cost, cost(8, 0));
+        assert_eq!(function_costs[0].self_cost, cost(10, 0));
+        assert_eq!(function_costs[0].total_cost, cost(38, 1));
+        assert_eq!(function_costs[3].total_cost, cost(5, 0));
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::io::Write;
+
+use vm::functions::define::DefineFunctionsParsed;
+use vm::representations::SymbolicExpression;
+use vm::representations::SymbolicExpressionType::List;
+use vm::types::QualifiedContractIdentifier;
+
+/// Records how many times each expression of each contract is evaluated, so that code coverage
+/// reports can be made.  Expressions are identified by their contract and their id in the
+/// contract's AST.
+///
+/// Only expressions in a contract's own code are recorded: its top-level expressions while it's
+/// being initialized, and the bodies of its functions whenever they're called.  Expressions in
+/// programs evaluated in a contract's context (e.g. by `eval_read_only`) are not.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CoverageReporter {
+    hits: HashMap<QualifiedContractIdentifier, HashMap<u64, u64>>,
+    // the contracts whose code is being evaluated, innermost last
+    code_stack: Vec<QualifiedContractIdentifier>,
+}
+
+impl CoverageReporter {
+    pub fn new() -> CoverageReporter {
+        CoverageReporter::default()
+    }
+
+    /// Start attributing evaluated expressions to `contract`
+    pub fn begin_code(&mut self, contract: &QualifiedContractIdentifier) {
+        self.code_stack.push(contract.clone());
+    }
+
+    /// Go back to attributing evaluated expressions to whichever contract's code was being
+    /// evaluated before the last call to `begin_code`
+    pub fn end_code(&mut self) {
+        self.code_stack.pop();
+    }
+
+    pub fn report_eval(&mut self, expr: &SymbolicExpression) {
+        if let Some(contract) = self.code_stack.last() {
+            if !self.hits.contains_key(contract) {
+                self.hits.insert(contract.clone(), HashMap::new());
+            }
+            if let Some(contract_hits) = self.hits.get_mut(contract) {
+                *contract_hits.entry(expr.id).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Add to the number of times an expression was evaluated, e.g. to merge in hits recorded by
+    /// an earlier run
+    pub fn add_hits(&mut self, contract: &QualifiedContractIdentifier, expr_id: u64, hits: u64) {
+        *self
+            .hits
+            .entry(contract.clone())
+            .or_insert_with(HashMap::new)
+            .entry(expr_id)
+            .or_insert(0) += hits;
+    }
+
+    pub fn get_hits(&self) -> &HashMap<QualifiedContractIdentifier, HashMap<u64, u64>> {
+        &self.hits
+    }
+
+    /// Write an LCOV record for a contract's line and function coverage.  `source_path` names the
+    /// contract's source file and `expressions` is its AST, as parsed when the contract was
+    /// initialized.
+    pub fn write_lcov<W: Write>(
+        &self,
+        fd: &mut W,
+        contract: &QualifiedContractIdentifier,
+        source_path: &str,
+        expressions: &[SymbolicExpression],
+    ) -> io::Result<()> {
+        let no_hits = HashMap::new();
+        let hits = self.hits.get(contract).unwrap_or(&no_hits);
+
+        // line number -> most times an expression starting on that line was evaluated
+        let mut lines = BTreeMap::new();
+        // (line number, function name, times the function was called)
+        let mut functions = vec![];
+
+        for expr in expressions.iter() {
+            match DefineFunctionsParsed::try_parse(expr) {
+                Ok(Some(DefineFunctionsParsed::Constant { value, .. })) => {
+                    CoverageReporter::add_line_hits(value, hits, &mut lines);
+                }
+                Ok(Some(DefineFunctionsParsed::PersistedVariable { initial, .. })) => {
+                    CoverageReporter::add_line_hits(initial, hits, &mut lines);
+                }
+                Ok(Some(DefineFunctionsParsed::BoundedFungibleToken { max_supply, .. })) => {
+                    CoverageReporter::add_line_hits(max_supply, hits, &mut lines);
+                }
+                Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body }))
+                | Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body }))
+                | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => {
+                    if let (Some(line), Some(name)) = (
+                        expr.span().map(|span| span.start_line),
+                        signature.get(0).and_then(|name| name.match_atom()),
+                    ) {
+                        let calls = hits.get(&body.id).cloned().unwrap_or(0);
+                        functions.push((line, name.to_string(), calls));
+                    }
+                    CoverageReporter::add_line_hits(body, hits, &mut lines);
+                }
+                Ok(Some(_)) => {
+                    // defines a map, token, or trait, which has no code to evaluate
+                }
+                Ok(None) | Err(_) => {
+                    CoverageReporter::add_line_hits(expr, hits, &mut lines);
+                }
+            }
+        }
+
+        writeln!(fd, "TN:")?;
+        writeln!(fd, "SF:{}", source_path)?;
+        for (line, name, _) in functions.iter() {
+            writeln!(fd, "FN:{},{}", line, name)?;
+        }
+        for (_, name, calls) in functions.iter() {
+            writeln!(fd, "FNDA:{},{}", calls, name)?;
+        }
+        writeln!(fd, "FNF:{}", functions.len())?;
+        writeln!(
+            fd,
+            "FNH:{}",
+            functions.iter().filter(|(_, _, calls)| *calls > 0).count()
+        )?;
+        for (line, line_hits) in lines.iter() {
+            writeln!(fd, "DA:{},{}", line, line_hits)?;
+        }
+        writeln!(fd, "LF:{}", lines.len())?;
+        writeln!(
+            fd,
+            "LH:{}",
+            lines.values().filter(|line_hits| **line_hits > 0).count()
+        )?;
+        writeln!(fd, "end_of_record")
+    }
+
+    /// Record the lines of the expressions in `expr` that get evaluated, along with how often
+    /// they were
+    fn add_line_hits(
+        expr: &SymbolicExpression,
+        hits: &HashMap<u64, u64>,
+        lines: &mut BTreeMap<u32, u64>,
+    ) {
+        if let Some(line) = expr.span().map(|span| span.start_line) {
+            let expr_hits = hits.get(&expr.id).cloned().unwrap_or(0);
+            let line_hits = lines.entry(line).or_insert(0);
+            *line_hits = cmp::max(*line_hits, expr_hits);
+        }
+        if let List(ref children) = expr.expr {
+            for (i, child) in children.iter().enumerate() {
+                if i == 0 && child.match_atom().is_some() {
+                    // the function name isn't evaluated
+                    continue;
+                }
+                CoverageReporter::add_line_hits(child, hits, lines);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use vm::ast::build_ast;
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::MemoryBackingStore;
+    use vm::types::{PrincipalData, StandardPrincipalData};
+    use vm::{SymbolicExpression, Value};
+
+    #[test]
+    fn test_coverage_lcov() {
+        let contract = "(define-data-var counter int 0)
+(define-public (incr (n int))
+  (if (> n 0)
+      (ok (var-set counter (+ (var-get counter) n)))
+      (err u1)))
+(define-read-only (get-counter)
+  (var-get counter))";
+
+        let contract_id = QualifiedContractIdentifier::new(
+            StandardPrincipalData(1, [0x01; 20]),
+            "counter".into(),
+        );
+        let sender = Value::Principal(PrincipalData::Standard(StandardPrincipalData(
+            1, [0x02; 20],
+        )));
+
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db());
+        owned_env.set_coverage_reporter(CoverageReporter::new());
+
+        owned_env
+            .initialize_contract(contract_id.clone(), contract)
+            .unwrap();
+        owned_env
+            .execute_transaction(
+                sender.clone(),
+                contract_id.clone(),
+                "incr",
+                &[SymbolicExpression::atom_value(Value::Int(2))],
+            )
+            .unwrap();
+        owned_env
+            .execute_transaction(
+                sender,
+                contract_id.clone(),
+                "incr",
+                &[SymbolicExpression::atom_value(Value::Int(3))],
+            )
+            .unwrap();
+        // programs evaluated in the contract's context aren't part of its code
+        owned_env
+            .get_exec_environment(None)
+            .eval_read_only(&contract_id, "(var-get counter)")
+            .unwrap();
+
+        let reporter = owned_env.take_coverage_reporter().unwrap();
+        let expressions = build_ast(&contract_id, contract, &mut ())
+            .unwrap()
+            .expressions;
+
+        let mut lcov = vec![];
+        reporter
+            .write_lcov(&mut lcov, &contract_id, "counter.clar", &expressions)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(lcov).unwrap(),
+            "TN:
+SF:counter.clar
+FN:2,incr
+FN:6,get-counter
+FNDA:2,incr
+FNDA:0,get-counter
+FNF:2
+FNH:1
+DA:1,1
+DA:3,2
+DA:4,2
+DA:5,0
+DA:7,0
+LF:5
+LH:3
+end_of_record
+"
+        );
+    }
+}
@@ -28,6 +28,7 @@ use vm::types::{
     OptionalData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, TupleData,
     TupleTypeSignature, TypeSignature, Value, NONE,
 };
+use vm::version::ClarityVersion;
 
 use burnchains::BurnchainHeaderHash;
 use chainstate::burn::{BlockHeaderHash, ConsensusHash, VRFSeed};
@@ -97,6 +98,7 @@ pub trait HeadersDB {
     fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64>;
     fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32>;
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress>;
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash>;
 }
 
 pub trait BurnStateDB {
@@ -106,6 +108,17 @@ pub trait BurnStateDB {
         height: u32,
         sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash>;
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId>;
+    /// Get the PoX reward addresses paid at the given burnchain height, as Clarity PoX address
+    /// tuples, and the amount each was paid
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)>;
 }
 
 fn get_stacks_header_info(conn: &DBConn, id_bhh: &StacksBlockId) -> Option<StacksHeaderInfo> {
@@ -158,6 +171,10 @@ impl HeadersDB for DBConn {
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         get_miner_info(self, id_bhh).map(|x| x.address)
     }
+
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        get_stacks_header_info(self, id_bhh).map(|x| x.consensus_hash)
+    }
 }
 
 impl HeadersDB for &dyn HeadersDB {
@@ -182,6 +199,21 @@ impl HeadersDB for &dyn HeadersDB {
     fn get_miner_address(&self, bhh: &StacksBlockId) -> Option<StacksAddress> {
         (*self).get_miner_address(bhh)
     }
+    fn get_consensus_hash_for_block(&self, bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        (*self).get_consensus_hash_for_block(bhh)
+    }
+}
+
+fn get_pox_payout_tuples(
+    conn: &DBConn,
+    sortition_id: &SortitionId,
+) -> Option<(Vec<TupleData>, u128)> {
+    let (addrs, payout) = SortitionDB::get_reward_set_payouts_at(conn, sortition_id)
+        .expect("Unexpected SQL failure querying block commits");
+    Some((
+        addrs.iter().map(|addr| addr.as_clarity_tuple()).collect(),
+        payout,
+    ))
 }
 
 impl BurnStateDB for SortitionHandleTx<'_> {
@@ -209,6 +241,35 @@ impl BurnStateDB for SortitionHandleTx<'_> {
             _ => return None,
         }
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        match SortitionDB::get_block_snapshot_consensus(self.tx(), consensus_hash) {
+            Ok(Some(x)) => Some(x.sortition_id),
+            _ => return None,
+        }
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let readonly_marf = self
+            .index()
+            .reopen_readonly()
+            .expect("BUG: failure trying to get a read-only interface into the sortition db.");
+        let mut context = self.context.clone();
+        context.chain_tip = sortition_id.clone();
+        let db_handle = SortitionHandleConn::new(&readonly_marf, context);
+        let snapshot = match db_handle.get_block_snapshot_by_height(height as u64) {
+            Ok(Some(x)) => x,
+            _ => return None,
+        };
+        get_pox_payout_tuples(self.tx(), &snapshot.sortition_id)
+    }
 }
 
 impl BurnStateDB for SortitionDBConn<'_> {
@@ -230,6 +291,29 @@ impl BurnStateDB for SortitionDBConn<'_> {
             _ => return None,
         }
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        match SortitionDB::get_block_snapshot_consensus(self.conn(), consensus_hash) {
+            Ok(Some(x)) => Some(x.sortition_id),
+            _ => return None,
+        }
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let db_handle = SortitionHandleConn::open_reader(self, &sortition_id).ok()?;
+        let snapshot = match db_handle.get_block_snapshot_by_height(height as u64) {
+            Ok(Some(x)) => x,
+            _ => return None,
+        };
+        get_pox_payout_tuples(self.conn(), &snapshot.sortition_id)
+    }
 }
 
 impl BurnStateDB for &dyn BurnStateDB {
@@ -244,6 +328,21 @@ impl BurnStateDB for &dyn BurnStateDB {
     ) -> Option<BurnchainHeaderHash> {
         (*self).get_burn_header_hash(height, sortition_id)
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        (*self).get_sortition_id_from_consensus_hash(consensus_hash)
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        (*self).get_pox_payout_addrs(height, sortition_id)
+    }
 }
 
 pub struct NullHeadersDB {}
@@ -315,6 +414,18 @@ impl HeadersDB for NullHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        if *id_bhh
+            == StacksBlockHeader::make_index_block_hash(
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+            )
+        {
+            Some(FIRST_BURNCHAIN_CONSENSUS_HASH)
+        } else {
+            None
+        }
+    }
 }
 
 impl BurnStateDB for NullBurnStateDB {
@@ -329,6 +440,21 @@ impl BurnStateDB for NullBurnStateDB {
     ) -> Option<BurnchainHeaderHash> {
         None
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        _consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        None
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        _height: u32,
+        _sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        None
+    }
 }
 
 impl<'a> ClarityDatabase<'a> {
@@ -400,6 +526,21 @@ impl<'a> ClarityDatabase<'a> {
         self.store.get_value(key, expected)
     }
 
+    /// The Clarity version that contracts published in this fork are analyzed and run with.
+    /// Nothing is stored until Clarity 2 activates, so a fork without the key is on Clarity 1.
+    pub fn get_clarity_version(&mut self) -> ClarityVersion {
+        self.get_value("vm-epoch::clarity-version", &TypeSignature::UIntType)
+            .map(|version| {
+                ClarityVersion::from_u128(version.expect_u128())
+                    .expect("FATAL: unknown Clarity version in the Clarity database")
+            })
+            .unwrap_or(ClarityVersion::Clarity1)
+    }
+
+    pub fn set_clarity_version(&mut self, version: ClarityVersion) {
+        self.put("vm-epoch::clarity-version", &Value::UInt(version.to_u128()));
+    }
+
     pub fn get_with_proof<T>(&mut self, key: &str) -> Option<(T, TrieMerkleProof<StacksBlockId>)>
     where
         T: ClarityDeserializable<T>,
@@ -867,6 +1008,13 @@ impl<'a> ClarityDatabase<'a> {
         self.set_variable(contract_identifier, variable_name, value, &descriptor)
     }
 
+    pub fn make_key_for_data_var(
+        contract_identifier: &QualifiedContractIdentifier,
+        variable_name: &str,
+    ) -> String {
+        ClarityDatabase::make_key_for_trip(contract_identifier, StoreType::Variable, variable_name)
+    }
+
     pub fn set_variable(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -1503,4 +1651,41 @@ impl<'a> ClarityDatabase<'a> {
         self.burn_state_db
             .get_burn_header_hash(height, sortition_id)
     }
+
+    /// Get the sortition in which the parent of the block being evaluated was chosen, i.e. the
+    /// burnchain fork that this block's view of the burnchain comes from.
+    fn get_sortition_id_for_stacks_tip(&mut self) -> Option<SortitionId> {
+        let cur_stacks_height = self.store.get_current_block_height();
+        if cur_stacks_height == 0 {
+            return None;
+        }
+        let parent_id_bhh = self.get_index_block_header_hash(cur_stacks_height - 1);
+        let consensus_hash = self
+            .headers_db
+            .get_consensus_hash_for_block(&parent_id_bhh)?;
+        self.burn_state_db
+            .get_sortition_id_from_consensus_hash(&consensus_hash)
+    }
+
+    /// Get the header hash of the burnchain block at the given height, in the burnchain fork of
+    /// this block's parent
+    pub fn get_burnchain_block_header_hash_for_burnchain_height(
+        &mut self,
+        burnchain_block_height: u32,
+    ) -> Option<BurnchainHeaderHash> {
+        let sortition_id = self.get_sortition_id_for_stacks_tip()?;
+        self.burn_state_db
+            .get_burn_header_hash(burnchain_block_height, &sortition_id)
+    }
+
+    /// Get the PoX reward addresses paid by the burnchain block at the given height, and the
+    /// amount each was paid, in the burnchain fork of this block's parent
+    pub fn get_pox_payout_addrs_for_burnchain_height(
+        &mut self,
+        burnchain_block_height: u32,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let sortition_id = self.get_sortition_id_for_stacks_tip()?;
+        self.burn_state_db
+            .get_pox_payout_addrs(burnchain_block_height, &sortition_id)
+    }
 }
@@ -14,28 +14,37 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Write};
 use std::path::PathBuf;
 
 use burnchains::BurnchainHeaderHash;
 use chainstate::burn::{BlockHeaderHash, VRFSeed};
+use chainstate::stacks::boot::boot_code_id;
 use chainstate::stacks::index::marf::{MarfConnection, MarfTransaction, MARF};
 use chainstate::stacks::index::proofs::TrieMerkleProof;
+use chainstate::stacks::index::snapshot::{read_data, read_u32, write_data, write_u32};
 use chainstate::stacks::index::storage::TrieFileStorage;
 use chainstate::stacks::index::{Error as MarfError, MARFValue, MarfTrieId, TrieHash};
 use chainstate::stacks::{StacksBlockHeader, StacksBlockId};
 use rusqlite::Connection;
 use std::convert::TryInto;
 use util::hash::{hex_bytes, to_hex, Hash160, Sha512Trunc256Sum};
-use vm::analysis::AnalysisDatabase;
+use vm::analysis::{run_analysis, AnalysisDatabase};
+use vm::ast;
+use vm::contracts::Contract;
+use vm::costs::{check_state_summary, LimitedCostTracker};
+use vm::database::clarity_db::StoreType;
 use vm::database::{
     BurnStateDB, ClarityDatabase, ClarityDeserializable, ClaritySerializable, HeadersDB,
     SqliteConnection, NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use vm::errors::{
-    CheckErrors, IncomparableError, InterpreterError, InterpreterResult as Result,
+    CheckErrors, Error, IncomparableError, InterpreterError, InterpreterResult as Result,
     InterpreterResult, RuntimeErrorType,
 };
-use vm::types::QualifiedContractIdentifier;
+use vm::types::{QualifiedContractIdentifier, TypeSignature};
+use vm::ClarityVersion;
 
 use util::db::IndexDBConn;
 
@@ -202,6 +211,122 @@ impl ClarityDeserializable<ContractCommitment> for ContractCommitment {
     }
 }
 
+fn marf_failure(err: MarfError) -> Error {
+    InterpreterError::MarfFailure(IncomparableError { err }).into()
+}
+
+fn read_string<R: Read>(fd: &mut R) -> Result<String> {
+    let bytes = read_data(fd).map_err(marf_failure)?;
+    String::from_utf8(bytes).map_err(|_| {
+        marf_failure(MarfError::CorruptionError(
+            "Snapshot string is not UTF-8".to_string(),
+        ))
+    })
+}
+
+fn snapshot_corruption(msg: String) -> Error {
+    marf_failure(MarfError::CorruptionError(msg))
+}
+
+/// A contract whose metadata is in a snapshot, with its source and its published `Contract` as
+/// the snapshot has them at the contract's block
+struct SnapshotContract {
+    contract_identifier: QualifiedContractIdentifier,
+    block: StacksBlockId,
+    source: String,
+    published: String,
+}
+
+fn parse_published_contract(json: &str) -> Option<Contract> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    // see clarity_serializable!(): deep contract ASTs need an unlimited recursion depth
+    deserializer.disable_recursion_limit();
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    serde::Deserialize::deserialize(deserializer).ok()
+}
+
+/// Store the metadata that publishing the snapshot's contract stored, rebuilt from its source
+fn import_contract_metadata(
+    store: &mut ReadOnlyMarfStore,
+    contract: &SnapshotContract,
+    version: ClarityVersion,
+) -> Result<()> {
+    let contract_identifier = &contract.contract_identifier;
+    let mut contract_ast = ast::build_ast(contract_identifier, &contract.source, &mut ())?;
+    let analysis = run_analysis(
+        contract_identifier,
+        &mut contract_ast.expressions,
+        &mut AnalysisDatabase::new(store),
+        false,
+        LimitedCostTracker::new_free(),
+        version,
+    )
+    .map_err(|(e, _)| Error::from(e.err))?;
+
+    let published = parse_published_contract(&contract.published).ok_or_else(|| {
+        snapshot_corruption(format!("Malformed contract {}", contract_identifier))
+    })?;
+    let rebuilt = Contract::rebuild_from_ast(
+        contract_identifier.clone(),
+        &contract_ast,
+        &analysis,
+        version,
+        &published.contract_context,
+    )?;
+
+    let context = &rebuilt.contract_context;
+    let mut entries = vec![
+        (
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-size"),
+            (contract.source.len() as u64).serialize(),
+        ),
+        (
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-src"),
+            contract.source.clone(),
+        ),
+        (
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-data-size"),
+            context.data_size.serialize(),
+        ),
+        (
+            AnalysisDatabase::storage_key().to_string(),
+            analysis.serialize(),
+        ),
+    ];
+    for (name, metadata) in context.meta_data_var.iter() {
+        let key = ClarityDatabase::make_metadata_key(StoreType::VariableMeta, name);
+        entries.push((key, metadata.serialize()));
+    }
+    for (name, metadata) in context.meta_data_map.iter() {
+        let key = ClarityDatabase::make_metadata_key(StoreType::DataMapMeta, name);
+        entries.push((key, metadata.serialize()));
+    }
+    for (name, metadata) in context.meta_ft.iter() {
+        let key = ClarityDatabase::make_metadata_key(StoreType::FungibleTokenMeta, name);
+        entries.push((key, metadata.serialize()));
+    }
+    for (name, metadata) in context.meta_nft.iter() {
+        let key = ClarityDatabase::make_metadata_key(StoreType::NonFungibleTokenMeta, name);
+        entries.push((key, metadata.serialize()));
+    }
+    entries.push((
+        ClarityDatabase::make_metadata_key(StoreType::Contract, "contract"),
+        rebuilt.serialize(),
+    ));
+
+    let contract_name = contract_identifier.to_string();
+    for (key, value) in entries.iter() {
+        SqliteConnection::insert_metadata(
+            store.get_side_store(),
+            &contract.block,
+            &contract_name,
+            key,
+            value,
+        );
+    }
+    Ok(())
+}
+
 impl MarfedKV {
     fn setup_db(path_str: &str, unconfirmed: bool) -> Result<MARF<StacksBlockId>> {
         let mut path = PathBuf::from(path_str);
@@ -316,6 +441,16 @@ impl MarfedKV {
                     err: MarfError::NotFoundError,
                 })
             })?;
+            if self.marf.is_block_pruned(at_block).unwrap_or(false) {
+                debug!(
+                    "Failed to open read only connection at {}: state was pruned",
+                    at_block
+                );
+                return Err(InterpreterError::MarfFailure(IncomparableError {
+                    err: MarfError::PrunedBlockError(at_block.to_string()),
+                })
+                .into());
+            }
             at_block.clone()
         } else {
             self.chain_tip.clone()
@@ -381,6 +516,287 @@ impl MarfedKV {
         }
     }
 
+    /// Write a snapshot of the state at `block`: the MARF's snapshot (see
+    /// `snapshot::export_snapshot()`), then the side store's values for the block's keys, and
+    /// the contract metadata written in the block and its ancestors.
+    pub fn export_snapshot<W: Write>(
+        marf: &mut MARF<StacksBlockId>,
+        block: &StacksBlockId,
+        fd: &mut W,
+    ) -> Result<()> {
+        let values = marf.export_snapshot(block, fd).map_err(marf_failure)?;
+        let height = marf
+            .get_block_height_of(block, block)
+            .map_err(marf_failure)?
+            .ok_or_else(|| marf_failure(MarfError::NotFoundError))?;
+
+        let conn = marf.sqlite_conn();
+        let mut seen = HashSet::new();
+        let mut entries = vec![];
+        for value in values.into_iter() {
+            let key = value.to_hex();
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+            // the MARF's own bookkeeping values have no side store entries
+            if let Some(entry) = SqliteConnection::get(conn, &key) {
+                entries.push((value, entry));
+            }
+        }
+        write_u32(fd, entries.len() as u32).map_err(marf_failure)?;
+        for (value, entry) in entries.iter() {
+            fd.write_all(value.as_bytes())
+                .map_err(|e| marf_failure(MarfError::IOError(e)))?;
+            write_data(fd, entry.as_bytes()).map_err(marf_failure)?;
+        }
+
+        let mut metadata = vec![];
+        for ancestor_height in 0..=height {
+            let ancestor = marf
+                .get_bhh_at_height(block, ancestor_height)
+                .map_err(marf_failure)?
+                .ok_or_else(|| marf_failure(MarfError::NotFoundError))?;
+            for (key, value) in SqliteConnection::get_block_metadata(marf.sqlite_conn(), &ancestor)
+            {
+                metadata.push((ancestor.clone(), key, value));
+            }
+        }
+        write_u32(fd, metadata.len() as u32).map_err(marf_failure)?;
+        for (ancestor, key, value) in metadata.iter() {
+            fd.write_all(ancestor.as_bytes())
+                .map_err(|e| marf_failure(MarfError::IOError(e)))?;
+            write_data(fd, key.as_bytes()).map_err(marf_failure)?;
+            write_data(fd, value.as_bytes()).map_err(marf_failure)?;
+        }
+        Ok(())
+    }
+
+    /// Create the state at `path_str` from a snapshot of the state at `block`, as written by
+    /// `export_snapshot()`. The MARF must hash to `root_hash`, and the side store's values must
+    /// be the block's values. Contract metadata is not covered by the MARF, so it is rebuilt
+    /// from the contracts' sources (see `import_snapshot_metadata()`).
+    pub fn import_snapshot<R: Read>(
+        path_str: &str,
+        block: &StacksBlockId,
+        root_hash: &TrieHash,
+        mainnet: bool,
+        fd: &mut R,
+    ) -> Result<MarfedKV> {
+        let mut marf = MarfedKV::setup_db(path_str, false)?;
+        let mut metadata = HashMap::new();
+        {
+            let mut tx = marf.begin_tx().map_err(marf_failure)?;
+            let values = tx
+                .import_snapshot(fd, block, root_hash)
+                .map_err(marf_failure)?;
+            let keys = values
+                .iter()
+                .map(|value| value.to_hex())
+                .collect::<HashSet<String>>();
+
+            let num_entries = read_u32(fd).map_err(marf_failure)?;
+            for _ in 0..num_entries {
+                let mut key = [0u8; 40];
+                fd.read_exact(&mut key)
+                    .map_err(|e| marf_failure(MarfError::IOError(e)))?;
+                let entry = read_string(fd)?;
+                let value = MARFValue::from_value(&entry);
+                if value.as_bytes() != &key[..] || !keys.contains(&value.to_hex()) {
+                    return Err(marf_failure(MarfError::CorruptionError(format!(
+                        "Side store entry {} is not a value of {}",
+                        to_hex(&key),
+                        block
+                    ))));
+                }
+                SqliteConnection::put(tx.sqlite_tx(), &value.to_hex(), &entry);
+            }
+
+            let num_metadata = read_u32(fd).map_err(marf_failure)?;
+            for _ in 0..num_metadata {
+                let mut ancestor_bytes = [0u8; 32];
+                fd.read_exact(&mut ancestor_bytes)
+                    .map_err(|e| marf_failure(MarfError::IOError(e)))?;
+                let ancestor = StacksBlockId(ancestor_bytes);
+                if tx
+                    .get_block_height_of(&ancestor, block)
+                    .map_err(marf_failure)?
+                    .is_none()
+                {
+                    return Err(marf_failure(MarfError::CorruptionError(format!(
+                        "Metadata of {}, which is not an ancestor of {}",
+                        ancestor, block
+                    ))));
+                }
+                let key = read_string(fd)?;
+                let value = read_string(fd)?;
+                metadata.insert((ancestor, key), value);
+            }
+            tx.commit().map_err(marf_failure)?;
+        }
+
+        let mut marfed_kv = MarfedKV {
+            marf,
+            chain_tip: block.clone(),
+        };
+        marfed_kv.import_snapshot_metadata(mainnet, &metadata)?;
+        Ok(marfed_kv)
+    }
+
+    /// Store the contract metadata of a snapshot of the state at the chain tip.  For each
+    /// contract, only its source and its published `Contract` are taken from the snapshot, at
+    /// the contract's block.  The source must hash to the contract's hash in the MARF, and the
+    /// rest is derived from it as publishing the contract did, with the constants and token
+    /// supplies checked by `Contract::rebuild_from_ast()`.  Contracts are analyzed in the order
+    /// of their blocks, so each one sees the contracts that it could have used.  The cost-voting
+    /// contract's state summary is kept for the block that last processed it, if
+    /// `check_state_summary()` accepts it.
+    fn import_snapshot_metadata(
+        &mut self,
+        mainnet: bool,
+        metadata: &HashMap<(StacksBlockId, String), String>,
+    ) -> Result<()> {
+        let block = self.chain_tip.clone();
+        let mut contract_identifiers = HashSet::new();
+        for (_, key) in metadata.keys() {
+            let contract_identifier = key
+                .strip_prefix("clr-meta::")
+                .and_then(|rest| rest.find("::").map(|end| &rest[..end]))
+                .and_then(|name| QualifiedContractIdentifier::parse(name).ok())
+                .ok_or_else(|| snapshot_corruption(format!("Malformed metadata key {}", key)))?;
+            contract_identifiers.insert(contract_identifier);
+        }
+
+        let mut store = self.begin_read_only(Some(&block));
+        let snapshot_entry = |contract_identifier: &QualifiedContractIdentifier,
+                              at_block: &StacksBlockId,
+                              key: &str| {
+            let metadata_key = format!("clr-meta::{}::{}", contract_identifier, key);
+            metadata
+                .get(&(at_block.clone(), metadata_key))
+                .ok_or_else(|| {
+                    snapshot_corruption(format!(
+                        "No {} metadata of {} at {}",
+                        key, contract_identifier, at_block
+                    ))
+                })
+        };
+
+        let mut contracts_by_height = BTreeMap::new();
+        for contract_identifier in contract_identifiers.into_iter() {
+            let commitment = store
+                .get(&MarfedKV::make_contract_hash_key(&contract_identifier))
+                .map(|x| ContractCommitment::deserialize(&x))
+                .ok_or_else(|| {
+                    snapshot_corruption(format!(
+                        "Metadata of {}, which is not a contract at {}",
+                        contract_identifier, block
+                    ))
+                })?;
+            let contract_block = store
+                .get_block_at_height(commitment.block_height)
+                .ok_or_else(|| marf_failure(MarfError::NotFoundError))?;
+            let source = snapshot_entry(
+                &contract_identifier,
+                &contract_block,
+                &ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-src"),
+            )?;
+            if Sha512Trunc256Sum::from_data(source.as_bytes()) != commitment.hash {
+                return Err(snapshot_corruption(format!(
+                    "Source of {} does not hash to its contract hash",
+                    contract_identifier
+                )));
+            }
+            let published = snapshot_entry(
+                &contract_identifier,
+                &contract_block,
+                &ClarityDatabase::make_metadata_key(StoreType::Contract, "contract"),
+            )?;
+            contracts_by_height
+                .entry(commitment.block_height)
+                .or_insert_with(Vec::new)
+                .push(SnapshotContract {
+                    contract_identifier,
+                    block: contract_block,
+                    source: source.clone(),
+                    published: published.clone(),
+                });
+        }
+
+        // contracts published from the block that published costs-2 on are Clarity 2 contracts
+        let clarity_2_height = store
+            .get(&MarfedKV::make_contract_hash_key(&boot_code_id(
+                "costs-2", mainnet,
+            )))
+            .map(|x| ContractCommitment::deserialize(&x).block_height);
+        for (height, mut pending) in contracts_by_height.into_iter() {
+            let version = match clarity_2_height {
+                Some(clarity_2_height) if height >= clarity_2_height => ClarityVersion::Clarity2,
+                _ => ClarityVersion::Clarity1,
+            };
+            // the snapshot does not keep the order of the contracts of one block, so retry the
+            // ones that use a contract of their block until none of them make progress
+            pending.sort_by_key(|contract| contract.contract_identifier.to_string());
+            while !pending.is_empty() {
+                let num_pending = pending.len();
+                let mut failed = vec![];
+                for contract in pending.into_iter() {
+                    if let Err(e) = import_contract_metadata(&mut store, &contract, version) {
+                        failed.push((contract, e));
+                    }
+                }
+                if failed.len() == num_pending {
+                    let (contract, e) = failed.remove(0);
+                    return Err(snapshot_corruption(format!(
+                        "Contract {} does not match its source: {:?}",
+                        contract.contract_identifier, e
+                    )));
+                }
+                pending = failed.into_iter().map(|(contract, _)| contract).collect();
+            }
+        }
+
+        let cost_voting_contract = boot_code_id("cost-voting", mainnet);
+        let last_processed_at = {
+            let mut clarity_db = store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+            clarity_db.begin();
+            let last_processed_at = clarity_db
+                .get_value(
+                    "vm-costs::last-processed-at-height",
+                    &TypeSignature::UIntType,
+                )
+                .map(|v| v.expect_u128());
+            clarity_db.roll_back();
+            last_processed_at
+        };
+        if let Some(height) = last_processed_at {
+            let summary_block = store
+                .get_block_at_height(height as u32)
+                .ok_or_else(|| marf_failure(MarfError::NotFoundError))?;
+            let summary = snapshot_entry(&cost_voting_contract, &summary_block, "::state_summary")?;
+            let accepted = {
+                let mut clarity_db = store.as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+                clarity_db.begin();
+                let accepted = check_state_summary(mainnet, &mut clarity_db, summary);
+                clarity_db.roll_back();
+                accepted
+            };
+            if !accepted {
+                return Err(snapshot_corruption(format!(
+                    "Cost state summary of {} is not one that its confirmed proposals install",
+                    block
+                )));
+            }
+            SqliteConnection::insert_metadata(
+                store.get_side_store(),
+                &summary_block,
+                &cost_voting_contract.to_string(),
+                "::state_summary",
+                summary,
+            );
+        }
+        Ok(())
+    }
+
     pub fn get_chain_tip(&self) -> &StacksBlockId {
         &self.chain_tip
     }
@@ -440,21 +856,29 @@ impl<'a> ClarityBackingStore for ReadOnlyMarfStore<'a> {
     fn set_block_hash(&mut self, bhh: StacksBlockId) -> Result<StacksBlockId> {
         self.marf
             .check_ancestor_block_hash(&bhh)
-            .map_err(|e| match e {
-                MarfError::NotFoundError => {
-                    test_debug!("No such block {:?} (NotFoundError)", &bhh);
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
-                }
-                MarfError::NonMatchingForks(_bh1, _bh2) => {
-                    test_debug!(
-                        "No such block {:?} (NonMatchingForks({}, {}))",
-                        &bhh,
-                        BlockHeaderHash(_bh1),
-                        BlockHeaderHash(_bh2)
-                    );
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
+            .map_err(|e| -> Error {
+                match e {
+                    MarfError::NotFoundError => {
+                        test_debug!("No such block {:?} (NotFoundError)", &bhh);
+                        RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0)).into()
+                    }
+                    MarfError::NonMatchingForks(_bh1, _bh2) => {
+                        test_debug!(
+                            "No such block {:?} (NonMatchingForks({}, {}))",
+                            &bhh,
+                            BlockHeaderHash(_bh1),
+                            BlockHeaderHash(_bh2)
+                        );
+                        RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0)).into()
+                    }
+                    // this node no longer has the state, but other nodes may -- so this must not
+                    // be treated as a runtime error of the transaction
+                    MarfError::PrunedBlockError(_) => {
+                        debug!("State at block {:?} was pruned", &bhh);
+                        InterpreterError::MarfFailure(IncomparableError { err: e }).into()
+                    }
+                    _ => panic!("ERROR: Unexpected MARF failure: {}", e),
                 }
-                _ => panic!("ERROR: Unexpected MARF failure: {}", e),
             })?;
 
         let result = Ok(self.chain_tip);
@@ -777,21 +1201,29 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
     fn set_block_hash(&mut self, bhh: StacksBlockId) -> Result<StacksBlockId> {
         self.marf
             .check_ancestor_block_hash(&bhh)
-            .map_err(|e| match e {
-                MarfError::NotFoundError => {
-                    test_debug!("No such block {:?} (NotFoundError)", &bhh);
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
-                }
-                MarfError::NonMatchingForks(_bh1, _bh2) => {
-                    test_debug!(
-                        "No such block {:?} (NonMatchingForks({}, {}))",
-                        &bhh,
-                        BlockHeaderHash(_bh1),
-                        BlockHeaderHash(_bh2)
-                    );
-                    RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0))
+            .map_err(|e| -> Error {
+                match e {
+                    MarfError::NotFoundError => {
+                        test_debug!("No such block {:?} (NotFoundError)", &bhh);
+                        RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0)).into()
+                    }
+                    MarfError::NonMatchingForks(_bh1, _bh2) => {
+                        test_debug!(
+                            "No such block {:?} (NonMatchingForks({}, {}))",
+                            &bhh,
+                            BlockHeaderHash(_bh1),
+                            BlockHeaderHash(_bh2)
+                        );
+                        RuntimeErrorType::UnknownBlockHeaderHash(BlockHeaderHash(bhh.0)).into()
+                    }
+                    // this node no longer has the state, but other nodes may -- so this must not
+                    // be treated as a runtime error of the transaction
+                    MarfError::PrunedBlockError(_) => {
+                        debug!("State at block {:?} was pruned", &bhh);
+                        InterpreterError::MarfFailure(IncomparableError { err: e }).into()
+                    }
+                    _ => panic!("ERROR: Unexpected MARF failure: {}", e),
                 }
-                _ => panic!("ERROR: Unexpected MARF failure: {}", e),
             })?;
 
         let result = Ok(self.chain_tip);
@@ -152,6 +152,35 @@ impl SqliteConnection {
         }
     }
 
+    /// Get the metadata written in a block, as (key, value) pairs
+    pub fn get_block_metadata(conn: &Connection, bhh: &StacksBlockId) -> Vec<(String, String)> {
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM metadata_table WHERE blockhash = ? ORDER BY key")
+            .expect(SQL_FAIL_MESSAGE);
+        let rows = stmt
+            .query_map(&[bhh], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<std::result::Result<Vec<_>, _>>());
+        match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Failed to query metadata of {}: {:?}", &bhh, &e);
+                panic!(SQL_FAIL_MESSAGE);
+            }
+        }
+    }
+
+    /// Store metadata as read by `get_block_metadata()`
+    pub fn put_block_metadata(conn: &Connection, bhh: &StacksBlockId, key: &str, value: &str) {
+        let params: [&dyn ToSql; 3] = [&bhh, &key, &value];
+        if let Err(e) = conn.execute(
+            "REPLACE INTO metadata_table (blockhash, key, value) VALUES (?, ?, ?)",
+            &params,
+        ) {
+            error!("Failed to insert ({},{}): {:?}", &bhh, &key, &e);
+            panic!(SQL_FAIL_MESSAGE);
+        }
+    }
+
     pub fn has_entry(conn: &Connection, key: &str) -> bool {
         sqlite_has_entry(conn, key)
     }
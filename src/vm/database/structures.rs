@@ -238,6 +238,13 @@ impl<'db, 'conn> STXBalanceSnapshot<'db, 'conn> {
         }
     }
 
+    /// Get the amount of STX that is still locked, and the burn block height at which it unlocks
+    /// (both 0 if no STX are locked)
+    pub fn get_locked_balance(&self) -> (u128, u64) {
+        self.balance
+            .get_locked_balance_at_burn_block(self.burn_block_height)
+    }
+
     pub fn has_locked_tokens(&self) -> bool {
         self.balance
             .has_locked_tokens_at_burn_block(self.burn_block_height)
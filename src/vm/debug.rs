@@ -0,0 +1,579 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use vm::ast::parse;
+use vm::contexts::{Environment, LocalContext};
+use vm::errors::InterpreterResult as Result;
+use vm::eval;
+use vm::representations::{ClarityName, SymbolicExpression};
+use vm::types::{QualifiedContractIdentifier, Value};
+
+const HELP: &str = "Commands:
+  step, s                 evaluate the next expression, stepping into function calls
+  next, n                 evaluate the current expression, stopping at the one after it
+  finish, f               run until the current function returns
+  continue, c             run until the next breakpoint
+  break, b <location>     stop at a line (`12`), a function (`transfer`), or either of these in
+                          a given contract (`SP000000000000000000002Q6VF78.pox:transfer`)
+  delete, d <number>      remove a breakpoint
+  breakpoints             list the breakpoints
+  backtrace, bt           list the functions being called, innermost last
+  locals                  print the local variables in scope
+  var <name>              print the value of a data var
+  map <name> <key>        print the entry of a map for a key
+  print, p <expression>   evaluate an expression where execution stopped, without side effects
+  quit, q                 stop debugging and run the rest of the program
+  help, h                 print this message";
+
+/// Where to stop.  A breakpoint without a contract applies to every contract.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    Line {
+        contract: Option<QualifiedContractIdentifier>,
+        line: u32,
+    },
+    Function {
+        contract: Option<QualifiedContractIdentifier>,
+        name: ClarityName,
+    },
+}
+
+impl Breakpoint {
+    fn applies_to(&self, contract: &QualifiedContractIdentifier) -> bool {
+        let bp_contract = match self {
+            Breakpoint::Line { contract, .. } => contract,
+            Breakpoint::Function { contract, .. } => contract,
+        };
+        match bp_contract {
+            Some(bp_contract) => bp_contract == contract,
+            None => true,
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Line {
+                contract: Some(contract),
+                line,
+            } => write!(f, "{}:{}", contract, line),
+            Breakpoint::Line {
+                contract: None,
+                line,
+            } => write!(f, "line {}", line),
+            Breakpoint::Function {
+                contract: Some(contract),
+                name,
+            } => write!(f, "{}:{}", contract, name),
+            Breakpoint::Function {
+                contract: None,
+                name,
+            } => write!(f, "function {}", name),
+        }
+    }
+}
+
+impl Breakpoint {
+    /// Parse a breakpoint location, given as `<line>`, `<function>`, `<contract>:<line>`, or
+    /// `<contract>:<function>`
+    pub fn parse(location: &str) -> std::result::Result<Breakpoint, String> {
+        let (contract, location) = match location.rfind(':') {
+            Some(index) => {
+                let contract = QualifiedContractIdentifier::parse(&location[..index])
+                    .map_err(|e| format!("Invalid contract identifier: {}", e))?;
+                (Some(contract), &location[index + 1..])
+            }
+            None => (None, location),
+        };
+        if let Ok(line) = location.parse::<u32>() {
+            Ok(Breakpoint::Line { contract, line })
+        } else {
+            let name = ClarityName::try_from(location.to_string())
+                .map_err(|_| format!("Invalid line number or function name: {}", location))?;
+            Ok(Breakpoint::Function { contract, name })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StepMode {
+    /// stop at the next expression
+    StepIn,
+    /// stop at the next expression that is at most this deep
+    StepOver(usize),
+    /// stop at the next expression evaluated once fewer than this many functions are being called
+    StepOut(usize),
+    /// only stop at breakpoints
+    Continue,
+    /// never stop again
+    Detached,
+}
+
+/// An interactive debugger for Clarity code.  When a debugger is attached to a `GlobalContext`,
+/// the evaluator calls it before and after each expression, and it stops execution whenever the
+/// user asked it to, reading commands from `input` and writing to `output` until told to go on.
+///
+/// It starts out stopped at the first expression it sees, so breakpoints can be set before
+/// anything runs.
+pub struct Debugger {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    sources: HashMap<QualifiedContractIdentifier, String>,
+    breakpoints: BTreeMap<usize, Breakpoint>,
+    next_breakpoint: usize,
+    mode: StepMode,
+    // the contract and line of each expression being evaluated, innermost last
+    expr_stack: Vec<(QualifiedContractIdentifier, Option<u32>)>,
+    // the functions being called, innermost last
+    frames: Vec<(QualifiedContractIdentifier, ClarityName)>,
+    // the function breakpoint hit by the function call that was just made, if any
+    function_hit: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(input: Box<dyn BufRead>, output: Box<dyn Write>) -> Debugger {
+        Debugger {
+            input,
+            output,
+            sources: HashMap::new(),
+            breakpoints: BTreeMap::new(),
+            next_breakpoint: 1,
+            mode: StepMode::StepIn,
+            expr_stack: vec![],
+            frames: vec![],
+            function_hit: None,
+        }
+    }
+
+    /// Give the source code of a contract, so that the lines execution stops at can be shown
+    pub fn add_source(&mut self, contract: &QualifiedContractIdentifier, source: &str) {
+        self.sources.insert(contract.clone(), source.to_string());
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        let number = self.next_breakpoint;
+        self.next_breakpoint += 1;
+        self.breakpoints.insert(number, breakpoint);
+        number
+    }
+
+    /// Called by the evaluator before it evaluates `expr`
+    pub fn begin_eval(env: &mut Environment, context: &LocalContext, expr: &SymbolicExpression) {
+        // the debugger is taken out of the global context while it runs, so that expressions it
+        // evaluates for the user are neither debugged nor recorded
+        if let Some(mut debugger) = env.global_context.debugger.take() {
+            let coverage = env.global_context.coverage.take();
+            debugger.did_begin_eval(env, context, expr);
+            env.global_context.coverage = coverage;
+            env.global_context.debugger = Some(debugger);
+        }
+    }
+
+    /// Called by the evaluator after it evaluates `expr`
+    pub fn finish_eval(env: &mut Environment, result: &Result<Value>) {
+        if let Some(ref mut debugger) = env.global_context.debugger {
+            let depth = debugger.expr_stack.len();
+            debugger.expr_stack.pop();
+            if let (StepMode::StepOver(over_depth), Ok(value)) = (debugger.mode, result) {
+                if depth == over_depth {
+                    debugger.println(&format!("=> {}", value));
+                }
+            }
+        }
+    }
+
+    /// Called when a user-defined function is called, before its body is evaluated
+    pub fn begin_function(&mut self, contract: &QualifiedContractIdentifier, name: &ClarityName) {
+        self.frames.push((contract.clone(), name.clone()));
+        self.function_hit = self
+            .breakpoints
+            .iter()
+            .find(|(_, breakpoint)| match breakpoint {
+                Breakpoint::Function { name: bp_name, .. } => {
+                    bp_name == name && breakpoint.applies_to(contract)
+                }
+                _ => false,
+            })
+            .map(|(number, _)| *number);
+    }
+
+    /// Called when a user-defined function returns
+    pub fn finish_function(&mut self, result: &Result<Value>) {
+        if let StepMode::StepOut(frame_count) = self.mode {
+            if self.frames.len() == frame_count {
+                if let (Some((_, name)), Ok(value)) = (self.frames.last(), result) {
+                    let message = format!("{} returned {}", name, value);
+                    self.println(&message);
+                }
+            }
+        }
+        self.frames.pop();
+    }
+
+    fn did_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        let contract = &env.contract_context.contract_identifier;
+        let location = expr.span().map(|span| (span.start_line, span.start_column));
+        let line = location.map(|(line, _)| line);
+
+        // line breakpoints stop at the first expression evaluated on their line, not at each
+        // expression nested in it
+        let enters_line = match self.expr_stack.last() {
+            Some((parent_contract, parent_line)) => {
+                parent_contract != contract || *parent_line != line
+            }
+            None => true,
+        };
+        self.expr_stack.push((contract.clone(), line));
+
+        let mut hit = self.function_hit.take();
+        if hit.is_none() && enters_line {
+            hit = self
+                .breakpoints
+                .iter()
+                .find(|(_, breakpoint)| match breakpoint {
+                    Breakpoint::Line { line: bp_line, .. } => {
+                        Some(*bp_line) == line && breakpoint.applies_to(contract)
+                    }
+                    _ => false,
+                })
+                .map(|(number, _)| *number);
+        }
+
+        let stop = match self.mode {
+            StepMode::Detached => false,
+            StepMode::StepIn => true,
+            StepMode::StepOver(depth) => self.expr_stack.len() <= depth || hit.is_some(),
+            StepMode::StepOut(frame_count) => self.frames.len() < frame_count || hit.is_some(),
+            StepMode::Continue => hit.is_some(),
+        };
+        if !stop {
+            return;
+        }
+
+        let mut message = match hit {
+            Some(number) => format!("Breakpoint {}, ", number),
+            None => "Stopped at ".to_string(),
+        };
+        match location {
+            Some((line, column)) => {
+                message.push_str(&format!("{}:{}:{}", contract, line, column));
+                match self
+                    .sources
+                    .get(contract)
+                    .and_then(|source| source.lines().nth((line as usize).saturating_sub(1)))
+                {
+                    Some(source_line) => {
+                        message.push_str(&format!("\n{:>5} | {}", line, source_line))
+                    }
+                    None => message.push_str(&format!("\n{}", expr)),
+                }
+            }
+            None => message.push_str(&format!("{}\n{}", contract, expr)),
+        }
+        self.println(&message);
+
+        self.command_loop(env, context);
+    }
+
+    /// Read and run commands until one of them resumes execution
+    fn command_loop(&mut self, env: &mut Environment, context: &LocalContext) {
+        loop {
+            self.print("(debug) ");
+            let mut line = String::new();
+            match self.input.read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    // out of input, so nobody's left to tell us when to stop
+                    self.mode = StepMode::Detached;
+                    return;
+                }
+                Ok(_) => {}
+            }
+
+            let line = line.trim();
+            let (command, rest) = match line.find(char::is_whitespace) {
+                Some(index) => (&line[..index], line[index..].trim()),
+                None => (line, ""),
+            };
+
+            match command {
+                "step" | "s" => {
+                    self.mode = StepMode::StepIn;
+                    return;
+                }
+                "next" | "n" => {
+                    self.mode = StepMode::StepOver(self.expr_stack.len());
+                    return;
+                }
+                "finish" | "f" => {
+                    if self.frames.len() == 0 {
+                        self.println("Not in a function");
+                        continue;
+                    }
+                    self.mode = StepMode::StepOut(self.frames.len());
+                    return;
+                }
+                "continue" | "c" => {
+                    self.mode = StepMode::Continue;
+                    return;
+                }
+                "quit" | "q" => {
+                    self.mode = StepMode::Detached;
+                    return;
+                }
+                "break" | "b" => match Breakpoint::parse(rest) {
+                    Ok(breakpoint) => {
+                        let message =
+                            format!("Breakpoint {} at {}", self.next_breakpoint, &breakpoint);
+                        self.add_breakpoint(breakpoint);
+                        self.println(&message);
+                    }
+                    Err(e) => self.println(&e),
+                },
+                "delete" | "d" => {
+                    match rest
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|number| self.breakpoints.remove(&number))
+                    {
+                        Some(_) => {}
+                        None => self.println(&format!("No breakpoint number {}", rest)),
+                    }
+                }
+                "breakpoints" => {
+                    let mut message = String::new();
+                    for (number, breakpoint) in self.breakpoints.iter() {
+                        message.push_str(&format!("{}: {}\n", number, breakpoint));
+                    }
+                    self.print(&message);
+                }
+                "backtrace" | "bt" => {
+                    let mut message = String::new();
+                    for (i, (contract, name)) in self.frames.iter().enumerate() {
+                        message.push_str(&format!("#{} {}:{}\n", i, contract, name));
+                    }
+                    self.print(&message);
+                }
+                "locals" => {
+                    let message = get_locals(context)
+                        .iter()
+                        .map(|(name, value)| format!("{} = {}\n", name, value))
+                        .collect::<String>();
+                    self.print(&message);
+                }
+                "var" => self.print_eval(env, context, &format!("(var-get {})", rest)),
+                "map" => match rest.find(char::is_whitespace) {
+                    Some(index) => self.print_eval(
+                        env,
+                        context,
+                        &format!("(map-get? {} {})", &rest[..index], &rest[index..]),
+                    ),
+                    None => self.println("Usage: map <name> <key>"),
+                },
+                "print" | "p" => self.print_eval(env, context, rest),
+                "help" | "h" => self.println(HELP),
+                "" => {}
+                _ => self.println(&format!("Unknown command: {}", command)),
+            }
+        }
+    }
+
+    /// Evaluate a program where execution stopped and print its result.  Its side effects are
+    /// rolled back.
+    fn print_eval(&mut self, env: &mut Environment, context: &LocalContext, program: &str) {
+        let message = match parse(&env.contract_context.contract_identifier, program) {
+            Ok(expressions) => {
+                env.global_context.begin_read_only();
+                let mut result = Ok(Value::none());
+                for expr in expressions.iter() {
+                    result = eval(expr, env, context);
+                    if result.is_err() {
+                        break;
+                    }
+                }
+                env.global_context.roll_back();
+                match result {
+                    Ok(value) => format!("{}", value),
+                    Err(e) => format!("Error: {}", e),
+                }
+            }
+            Err(e) => format!("Error: {}", e),
+        };
+        self.println(&message);
+    }
+
+    fn print(&mut self, message: &str) {
+        // the debugger can't do anything about a broken output, so it doesn't try
+        let _ = write!(self.output, "{}", message);
+        let _ = self.output.flush();
+    }
+
+    fn println(&mut self, message: &str) {
+        let _ = writeln!(self.output, "{}", message);
+        let _ = self.output.flush();
+    }
+}
+
+/// Get the variables bound in a local context and its parents, and the contracts bound to its
+/// function's trait arguments
+fn get_locals(context: &LocalContext) -> BTreeMap<String, String> {
+    let mut locals = BTreeMap::new();
+    let mut next_context = Some(context);
+    while let Some(context) = next_context {
+        for (name, value) in context.variables.iter() {
+            locals
+                .entry(name.to_string())
+                .or_insert_with(|| format!("{}", value));
+        }
+        next_context = context.parent;
+    }
+    for (name, (contract, _)) in context.function_context().callable_contracts.iter() {
+        locals
+            .entry(name.to_string())
+            .or_insert_with(|| format!("'{}", contract));
+    }
+    locals
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    use vm::contexts::OwnedEnvironment;
+    use vm::database::MemoryBackingStore;
+    use vm::types::{PrincipalData, StandardPrincipalData};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_debugger() {
+        let contract = "(define-data-var counter int 0)
+(define-map totals principal int)
+(define-private (add (a int) (b int))
+  (+ a b))
+(define-public (incr (n int))
+  (let ((sum (add (var-get counter) n)))
+    (var-set counter sum)
+    (map-set totals tx-sender sum)
+    (ok sum)))";
+
+        let contract_id = QualifiedContractIdentifier::new(
+            StandardPrincipalData(1, [0x01; 20]),
+            "counter".into(),
+        );
+        let sender = Value::Principal(PrincipalData::Standard(StandardPrincipalData(
+            1, [0x02; 20],
+        )));
+
+        let commands = "break add
+break 8
+continue
+locals
+backtrace
+finish
+locals
+next
+var counter
+print (var-set counter 100)
+var counter
+map totals tx-sender
+delete 2
+breakpoints
+next
+quit
+";
+        let output = SharedBuffer::default();
+        let mut debugger = Debugger::new(
+            Box::new(Cursor::new(commands.as_bytes().to_vec())),
+            Box::new(output.clone()),
+        );
+        debugger.add_source(&contract_id, contract);
+
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db());
+        owned_env
+            .initialize_contract(contract_id.clone(), contract)
+            .unwrap();
+
+        owned_env.set_debugger(debugger);
+        let (result, ..) = owned_env
+            .execute_transaction(
+                sender,
+                contract_id.clone(),
+                "incr",
+                &[SymbolicExpression::atom_value(Value::Int(2))],
+            )
+            .unwrap();
+        assert_eq!(result, Value::okay(Value::Int(2)).unwrap());
+        assert!(owned_env.take_debugger().is_some());
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            output,
+            "Stopped at S1G2081040G2081040G2081040G208105NK8PE5.counter:6:3
+    6 |   (let ((sum (add (var-get counter) n)))
+(debug) Breakpoint 1 at function add
+(debug) Breakpoint 2 at line 8
+(debug) Breakpoint 1, S1G2081040G2081040G2081040G208105NK8PE5.counter:4:3
+    4 |   (+ a b))
+(debug) a = 0
+b = 2
+(debug) #0 S1G2081040G2081040G2081040G208105NK8PE5.counter:incr
+#1 S1G2081040G2081040G2081040G208105NK8PE5.counter:add
+(debug) add returned 2
+Stopped at S1G2081040G2081040G2081040G208105NK8PE5.counter:7:5
+    7 |     (var-set counter sum)
+(debug) n = 2
+sum = 2
+(debug) => true
+Breakpoint 2, S1G2081040G2081040G2081040G208105NK8PE5.counter:8:5
+    8 |     (map-set totals tx-sender sum)
+(debug) 2
+(debug) Error: Unchecked(WriteAttemptedInReadOnly)
+(debug) 2
+(debug) none
+(debug) (debug) 1: function add
+(debug) => true
+Stopped at S1G2081040G2081040G2081040G208105NK8PE5.counter:9:5
+    9 |     (ok sum)))
+(debug) "
+        );
+    }
+}
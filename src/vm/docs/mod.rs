@@ -20,6 +20,7 @@ use vm::functions::define::DefineFunctions;
 use vm::functions::NativeFunctions;
 use vm::types::{FixedFunction, FunctionType, Value};
 use vm::variables::NativeVariables;
+use vm::ClarityVersion;
 
 pub mod contracts;
 
@@ -253,6 +254,68 @@ const XOR_API: SimpleFunctionAPI = SimpleFunctionAPI {
 ",
 };
 
+const BITWISE_AND_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-and i1 i2...)",
+    description: "Returns the result of bitwise and'ing a variable number of integer inputs.",
+    example: "(bit-and 24 16) ;; Returns 16
+(bit-and 28 24 -1) ;; Returns 24
+(bit-and u24 u16) ;; Returns u16
+(bit-and -128 -64) ;; Returns -128
+",
+};
+
+const BITWISE_OR_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-or i1 i2...)",
+    description:
+        "Returns the result of bitwise inclusive or'ing a variable number of integer inputs.",
+    example: "(bit-or 4 8) ;; Returns 12
+(bit-or 1 2 4) ;; Returns 7
+(bit-or u4 u8) ;; Returns u12
+(bit-or -64 -32) ;; Returns -32
+",
+};
+
+const BITWISE_NOT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-not i1)",
+    description: "Returns the one's complement of `i1`: every bit that is `1` in `i1` is `0` in the result, and every bit that is `0` in `i1` is `1` in the result.",
+    example: "(bit-not 3) ;; Returns -4
+(bit-not u128) ;; Returns u340282366920938463463374607431768211327
+(bit-not 128) ;; Returns -129
+(bit-not -128) ;; Returns 127
+"
+};
+
+const BITWISE_LEFT_SHIFT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-shift-left i1 shamt)",
+    description: "Shifts all the bits in `i1` to the left by the number of places specified in `shamt` modulo 128 (the bit width of Clarity integers).
+Bits shifted out of `i1` are discarded, so unlike `*`, this function never causes an overflow error.",
+    example: "(bit-shift-left 2 u1) ;; Returns 4
+(bit-shift-left 16 u2) ;; Returns 64
+(bit-shift-left -64 u1) ;; Returns -128
+(bit-shift-left u4 u2) ;; Returns u16
+(bit-shift-left 1 u128) ;; Returns 1
+(bit-shift-left 1 u127) ;; Returns -170141183460469231731687303715884105728
+"
+};
+
+const BITWISE_RIGHT_SHIFT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-shift-right i1 shamt)",
+    description: "Shifts all the bits in `i1` to the right by the number of places specified in `shamt` modulo 128 (the bit width of Clarity integers).
+When `i1` is a `uint`, the vacated bits are filled with zeros. When `i1` is an `int`, they are filled with copies of its sign bit, so the result is `i1` divided by `2^shamt`, rounded towards negative infinity.",
+    example: "(bit-shift-right 2 u1) ;; Returns 1
+(bit-shift-right 128 u2) ;; Returns 32
+(bit-shift-right -64 u1) ;; Returns -32
+(bit-shift-right u128 u2) ;; Returns u32
+(bit-shift-right 2 u129) ;; Returns 1
+(bit-shift-right -1 u127) ;; Returns -1
+"
+};
+
 const AND_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(and b1 b2 ...)",
@@ -337,6 +400,7 @@ pub fn get_input_type_string(function_type: &FunctionType) -> String {
         FunctionType::ArithmeticBinary | FunctionType::ArithmeticComparison => {
             "int, int | uint, uint".to_string()
         }
+        FunctionType::ArithmeticShift => "int, uint | uint, uint".to_string(),
     }
 }
 
@@ -347,7 +411,8 @@ pub fn get_output_type_string(function_type: &FunctionType) -> String {
         FunctionType::UnionArgs(_, ref out_type) => format!("{}", out_type),
         FunctionType::ArithmeticVariadic
         | FunctionType::ArithmeticUnary
-        | FunctionType::ArithmeticBinary => "int | uint".to_string(),
+        | FunctionType::ArithmeticBinary
+        | FunctionType::ArithmeticShift => "int | uint".to_string(),
         FunctionType::ArithmeticComparison => "bool".to_string(),
     }
 }
@@ -825,6 +890,49 @@ const PRINCIPAL_OF_API: SpecialAPI = SpecialAPI {
     example: "(principal-of? 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110) ;; Returns (ok ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)"
 };
 
+const PRINCIPAL_DESTRUCT_API: SpecialAPI = SpecialAPI {
+    input_type: "principal",
+    output_type: "(response (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))) (tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1))))",
+    signature: "(principal-destruct? principal-address)",
+    description: "The `principal-destruct?` function decomposes a principal into its parts: the
+`version` byte and `hash-bytes` of its address (for a contract principal, the address of the
+contract's deployer), and for a contract principal, the contract's `name`.
+
+If the principal's version byte is one of those used by the network this code runs on, this
+function returns the parts in an `ok` response. Otherwise, it returns them in an `err` response.",
+    example: "(principal-destruct? 'ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP) ;; Returns (ok (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x1a)))
+(principal-destruct? 'ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP.foo) ;; Returns (ok (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name (some \"foo\")) (version 0x1a)))
+(principal-destruct? 'SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ) ;; Returns (err (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x16)))
+",
+};
+
+const PRINCIPAL_CONSTRUCT_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff 1), (buff 20), [(string-ascii 40)]",
+    output_type: "(response principal { error_code: uint, value: (optional principal) })",
+    signature: "(principal-construct? version-byte hash-bytes [contract-name])",
+    description: "The `principal-construct?` function builds a principal from a `version-byte`
+and `hash-bytes`. If a `contract-name` is given, it builds a contract principal, with the
+standard principal made from the `version-byte` and `hash-bytes` as its deployer. This is the
+inverse of `principal-destruct?`.
+
+If the principal's version byte is one of those used by the network this code runs on, this
+function returns it in an `ok` response. Otherwise, it returns an `err` response holding a tuple
+with an `error_code` and an optional `value`:
+
+`error_code: u0` -- the version byte is valid, but for another network. The principal is
+returned as the `value`.
+`error_code: u1` -- the `version-byte` is not exactly 1 byte with a value less than 32, or the
+`hash-bytes` are not exactly 20 bytes.
+`error_code: u2` -- the `contract-name` is not a valid contract name.
+",
+    example: "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816) ;; Returns (ok ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)
+(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"foo\") ;; Returns (ok ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP.foo)
+(principal-construct? 0x16 0x55c33a76868c1cdd2faedb909f13af348fd8a816) ;; Returns (err (tuple (error_code u0) (value (some SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ))))
+(principal-construct? 0x20 0x55c33a76868c1cdd2faedb909f13af348fd8a816) ;; Returns (err (tuple (error_code u1) (value none)))
+(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"\") ;; Returns (err (tuple (error_code u2) (value none)))
+",
+};
+
 const AT_BLOCK: SpecialAPI = SpecialAPI {
     input_type: "(buff 32), A",
     output_type: "A",
@@ -1116,6 +1224,30 @@ The `id-header-hash` is the block identifier value that must be used as input to
 "
 };
 
+const GET_BURN_BLOCK_INFO_API: SpecialAPI = SpecialAPI {
+    input_type: "BurnBlockInfoPropertyName, BlockHeightInt",
+    output_type: "(optional buff) | (optional (tuple (addrs (list 2 (tuple (hashbytes (buff 20)) (version (buff 1))))) (payout uint)))",
+    signature: "(get-burn-block-info? prop-name block-height-expr)",
+    description: "The `get-burn-block-info?` function fetches data for a block of the given *burnchain* block height. The
+value and type returned are determined by the specified `BurnBlockInfoPropertyName`. Valid values for `block-height-expr` are
+burnchain block heights at or below the burnchain height of the Stacks block's parent (i.e., the value of `burn-block-height`).
+If the provided `BlockHeightInt` does not correspond to such a burnchain block, the function returns `none`. The currently
+available property names are `header-hash` and `pox-addrs`.
+
+The `header-hash` property returns a 32-byte buffer representing the header hash of the burnchain block at
+burnchain height `block-height-expr`.
+
+The `pox-addrs` property returns a tuple with two items: a list of up to two PoX addresses that received a PoX payout at that block height, and the amount of burnchain
+tokens paid to each address. Each PoX address is a tuple with a `version` byte and `hashbytes`, in the same form the
+PoX contract uses. The list is empty if the burnchain block's block commits burned their tokens instead (for example,
+because no PoX anchor block was chosen, or because the block is in a reward cycle's prepare phase).
+",
+    example: "
+(get-burn-block-info? header-hash u677050) ;; Returns (some 0xe67141016c88a7f1203eca0b4312f2ed141531f59303a1c267d7d83ab6b977d8)
+(get-burn-block-info? pox-addrs u677050) ;; Returns (some (tuple (addrs ((tuple (hashbytes 0x7354ae2c8afc8c2ad6c2ebd4ef8c09e32dbfb37c) (version 0x00)) (tuple (hashbytes 0xd52ae4a11d4f0ccd2a4a3f4d2b36a6c7e2b12b3a) (version 0x01)))) (payout u123)))
+"
+};
+
 const DEFINE_TOKEN_API: DefineAPI = DefineAPI {
     input_type: "TokenName, <uint>",
     output_type: "Not Applicable",
@@ -1515,6 +1647,23 @@ principal isn't materialized, it returns 0.
 ",
 };
 
+const STX_GET_ACCOUNT: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(stx-account owner)",
+    description: "`stx-account` is used to query the STX account of the `owner` principal.
+
+This function returns a tuple with the `unlocked` STX balance of the `owner` principal (the
+amount that `stx-get-balance` returns), the amount of STX that is `locked` (e.g., by PoX), and
+the burnchain block height at which the locked STX will `unlock-height`. If no STX are locked,
+`locked` and `unlock-height` are 0. In the event that the `owner` principal isn't
+materialized, all three are 0.
+",
+    example: "
+(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; Returns (tuple (locked u0) (unlock-height u0) (unlocked u0))
+(stx-account (as-contract tx-sender)) ;; Returns (tuple (locked u0) (unlock-height u0) (unlocked u1000))
+",
+};
+
 const STX_TRANSFER: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(stx-transfer? amount sender recipient)",
@@ -1537,6 +1686,21 @@ one of the following error codes:
 "
 };
 
+const STX_TRANSFER_MEMO: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(stx-transfer-memo? amount sender recipient memo)",
+    description: "`stx-transfer-memo?` is similar to `stx-transfer?`, except that it adds a `memo` field.
+
+This function returns (ok true) if the transfer is successful, or, on an error, returns the same codes as `stx-transfer?`.
+The `memo` is recorded in the transfer's event (and in the events sent to event observers), so that the
+recipient can tell which deposit the transfer is for.
+",
+    example: "
+(as-contract
+  (stx-transfer-memo? u60 tx-sender 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 0x010203)) ;; Returns (ok true)
+"
+};
+
 const STX_BURN: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(stx-burn? amount sender)",
@@ -1558,7 +1722,172 @@ one of the following error codes:
 "
 };
 
+const INT_TO_ASCII_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-ascii value)",
+    description: "Converts an integer, either `int` or `uint`, to its decimal representation as a
+`string-ascii`.",
+    example: "(int-to-ascii 12) ;; Returns \"12\"
+(int-to-ascii u12) ;; Returns \"12\"
+(int-to-ascii -7) ;; Returns \"-7\"
+",
+};
+
+const INT_TO_UTF8_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-utf8 value)",
+    description: "Converts an integer, either `int` or `uint`, to its decimal representation as a
+`string-utf8`.",
+    example: "(int-to-utf8 12) ;; Returns u\"12\"
+(int-to-utf8 u12) ;; Returns u\"12\"
+(int-to-utf8 -7) ;; Returns u\"-7\"
+",
+};
+
+const STRING_TO_INT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-int? string)",
+    description: "Parses a `string-ascii` or `string-utf8` containing the decimal representation of
+an integer, with an optional leading `-`, and returns it as an `(optional int)`. If the string is not
+a valid integer, or the integer does not fit in an `int`, this function returns `none`.",
+    example: "(string-to-int? \"1\") ;; Returns (some 1)
+(string-to-int? u\"-1\") ;; Returns (some -1)
+(string-to-int? \"a\") ;; Returns none
+"
+};
+
+const STRING_TO_UINT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-uint? string)",
+    description: "Parses a `string-ascii` or `string-utf8` containing the decimal representation of
+an unsigned integer, and returns it as an `(optional uint)`. If the string is not a valid unsigned
+integer, or the integer does not fit in a `uint`, this function returns `none`.",
+    example: "(string-to-uint? \"1\") ;; Returns (some u1)
+(string-to-uint? u\"1\") ;; Returns (some u1)
+(string-to-uint? \"-1\") ;; Returns none
+",
+};
+
+const BUFF_TO_INT_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-le buffer)",
+    description: "Converts a buffer of at most 16 bytes to an `int`, reading it as a
+little-endian, two's complement integer. A buffer shorter than 16 bytes is padded with zero bytes
+at its end, so its sign bit is never set.",
+    example: "(buff-to-int-le 0x01) ;; Returns 1
+(buff-to-int-le 0x01000000000000000000000000000000) ;; Returns 1
+(buff-to-int-le 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-le 0x) ;; Returns 0
+",
+};
+
+const BUFF_TO_INT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-be buffer)",
+    description: "Converts a buffer of at most 16 bytes to an `int`, reading it as a big-endian,
+two's complement integer. A buffer shorter than 16 bytes is padded with zero bytes at its start,
+so its sign bit is never set.",
+    example: "(buff-to-int-be 0x01) ;; Returns 1
+(buff-to-int-be 0x00000000000000000000000000000001) ;; Returns 1
+(buff-to-int-be 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-be 0x) ;; Returns 0
+",
+};
+
+const BUFF_TO_UINT_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-le buffer)",
+    description: "Converts a buffer of at most 16 bytes to a `uint`, reading it as a little-endian
+integer. A buffer shorter than 16 bytes is padded with zero bytes at its end.",
+    example: "(buff-to-uint-le 0x01) ;; Returns u1
+(buff-to-uint-le 0x0001) ;; Returns u256
+(buff-to-uint-le 0xffffffffffffffffffffffffffffffff) ;; Returns u340282366920938463463374607431768211455
+(buff-to-uint-le 0x) ;; Returns u0
+"
+};
+
+const BUFF_TO_UINT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-be buffer)",
+    description: "Converts a buffer of at most 16 bytes to a `uint`, reading it as a big-endian
+integer. A buffer shorter than 16 bytes is padded with zero bytes at its start.",
+    example: "(buff-to-uint-be 0x01) ;; Returns u1
+(buff-to-uint-be 0x0100) ;; Returns u256
+(buff-to-uint-be 0xffffffffffffffffffffffffffffffff) ;; Returns u340282366920938463463374607431768211455
+(buff-to-uint-be 0x) ;; Returns u0
+"
+};
+
+const INT_TO_BUFF_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-buff-le value)",
+    description: "Converts an integer, either `int` or `uint`, to a 16-byte buffer holding its
+little-endian (and for `int`, two's complement) representation. This is the inverse of
+`buff-to-int-le` and `buff-to-uint-le`.",
+    example: "(int-to-buff-le 1) ;; Returns 0x01000000000000000000000000000000
+(int-to-buff-le u256) ;; Returns 0x00010000000000000000000000000000
+(int-to-buff-le -1) ;; Returns 0xffffffffffffffffffffffffffffffff
+",
+};
+
+const INT_TO_BUFF_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-buff-be value)",
+    description: "Converts an integer, either `int` or `uint`, to a 16-byte buffer holding its
+big-endian (and for `int`, two's complement) representation. This is the inverse of
+`buff-to-int-be` and `buff-to-uint-be`.",
+    example: "(int-to-buff-be 1) ;; Returns 0x00000000000000000000000000000001
+(int-to-buff-be u256) ;; Returns 0x00000000000000000000000000000100
+(int-to-buff-be -1) ;; Returns 0xffffffffffffffffffffffffffffffff
+",
+};
+
+const SLICE_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, uint, uint",
+    output_type: "(optional sequence_A)",
+    signature: "(slice? sequence left-position right-position)",
+    description: "The `slice?` function returns the part of `sequence` that starts at
+`left-position` (inclusive) and ends at `right-position` (exclusive). If `left-position` is
+greater than `right-position`, or `right-position` is greater than `(len sequence)`, this
+function returns `none`.",
+    example: "(slice? \"blockstack\" u5 u10) ;; Returns (some \"stack\")
+(slice? (list 1 2 3 4 5) u5 u9) ;; Returns none
+(slice? (list 1 2 3 4 5) u3 u4) ;; Returns (some (4))
+(slice? \"abcd\" u1 u3) ;; Returns (some \"bc\")
+(slice? \"abcd\" u2 u2) ;; Returns (some \"\")
+(slice? \"abcd\" u3 u1) ;; Returns none
+",
+};
+
+const REPLACE_AT_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, uint, A",
+    output_type: "(optional sequence_A)",
+    signature: "(replace-at? sequence index element)",
+    description: "The `replace-at?` function returns a copy of `sequence` with the item at `index`
+replaced by `element`. For strings and buffers, `element` must be a 1-length string or buffer.
+If `index` is greater than or equal to `(len sequence)`, this function returns `none`.",
+    example: "(replace-at? u\"ab\" u1 u\"c\") ;; Returns (some u\"ac\")
+(replace-at? 0x00112233 u2 0x44) ;; Returns (some 0x00114433)
+(replace-at? \"abcd\" u3 \"e\") ;; Returns (some \"abce\")
+(replace-at? (list 1) u0 10) ;; Returns (some (10))
+(replace-at? (list (some 1) none) u1 (some 2)) ;; Returns (some ((some 1) (some 2)))
+(replace-at? \"abcd\" u4 \"e\") ;; Returns none
+",
+};
+
 fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
+    let mut api = make_native_api_reference(function);
+    if function.get_version() > ClarityVersion::Clarity1 {
+        api.description = format!(
+            "{} Available to contracts published after {} activates.",
+            api.description,
+            function.get_version()
+        );
+    }
+    api
+}
+
+fn make_native_api_reference(function: &NativeFunctions) -> FunctionAPI {
     use vm::functions::NativeFunctions::*;
     let name = function.get_name();
     match function {
@@ -1616,6 +1945,7 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         PrincipalOf => make_for_special(&PRINCIPAL_OF_API, name),
         AsContract => make_for_special(&AS_CONTRACT_API, name),
         GetBlockInfo => make_for_special(&GET_BLOCK_INFO_API, name),
+        GetBurnBlockInfo => make_for_special(&GET_BURN_BLOCK_INFO_API, name),
         ConsOkay => make_for_special(&CONS_OK_API, name),
         ConsError => make_for_special(&CONS_ERR_API, name),
         ConsSome => make_for_special(&CONS_SOME_API, name),
@@ -1642,8 +1972,29 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         GetTokenSupply => make_for_special(&GET_TOKEN_SUPPLY, name),
         AtBlock => make_for_special(&AT_BLOCK, name),
         GetStxBalance => make_for_simple_native(&STX_GET_BALANCE, &GetStxBalance, name),
+        GetStxAccount => make_for_simple_native(&STX_GET_ACCOUNT, &GetStxAccount, name),
         StxTransfer => make_for_simple_native(&STX_TRANSFER, &StxTransfer, name),
+        StxTransferMemo => make_for_simple_native(&STX_TRANSFER_MEMO, &StxTransferMemo, name),
         StxBurn => make_for_simple_native(&STX_BURN, &StxBurn, name),
+        IntToAscii => make_for_simple_native(&INT_TO_ASCII_API, &IntToAscii, name),
+        IntToUtf8 => make_for_simple_native(&INT_TO_UTF8_API, &IntToUtf8, name),
+        StringToInt => make_for_simple_native(&STRING_TO_INT_API, &StringToInt, name),
+        StringToUInt => make_for_simple_native(&STRING_TO_UINT_API, &StringToUInt, name),
+        BuffToIntLe => make_for_simple_native(&BUFF_TO_INT_LE_API, &BuffToIntLe, name),
+        BuffToIntBe => make_for_simple_native(&BUFF_TO_INT_BE_API, &BuffToIntBe, name),
+        BuffToUIntLe => make_for_simple_native(&BUFF_TO_UINT_LE_API, &BuffToUIntLe, name),
+        BuffToUIntBe => make_for_simple_native(&BUFF_TO_UINT_BE_API, &BuffToUIntBe, name),
+        IntToBuffLe => make_for_simple_native(&INT_TO_BUFF_LE_API, &IntToBuffLe, name),
+        IntToBuffBe => make_for_simple_native(&INT_TO_BUFF_BE_API, &IntToBuffBe, name),
+        BitwiseAnd => make_for_simple_native(&BITWISE_AND_API, &BitwiseAnd, name),
+        BitwiseOr => make_for_simple_native(&BITWISE_OR_API, &BitwiseOr, name),
+        BitwiseNot => make_for_simple_native(&BITWISE_NOT_API, &BitwiseNot, name),
+        BitwiseLShift => make_for_simple_native(&BITWISE_LEFT_SHIFT_API, &BitwiseLShift, name),
+        BitwiseRShift => make_for_simple_native(&BITWISE_RIGHT_SHIFT_API, &BitwiseRShift, name),
+        PrincipalDestruct => make_for_special(&PRINCIPAL_DESTRUCT_API, name),
+        PrincipalConstruct => make_for_special(&PRINCIPAL_CONSTRUCT_API, name),
+        Slice => make_for_special(&SLICE_API, name),
+        ReplaceAt => make_for_special(&REPLACE_AT_API, name),
     }
 }
 
@@ -1739,17 +2090,18 @@ mod test {
     use super::make_json_api_reference;
     use burnchains::BurnchainHeaderHash;
     use chainstate::burn::db::sortdb::SortitionId;
-    use chainstate::burn::{BlockHeaderHash, VRFSeed};
+    use chainstate::burn::{BlockHeaderHash, ConsensusHash, VRFSeed};
     use chainstate::stacks::{index::MarfTrieId, StacksAddress, StacksBlockId};
+    use util::hash::hex_bytes;
 
     use vm::{
         ast,
         contexts::OwnedEnvironment,
         database::{BurnStateDB, HeadersDB, MarfedKV, STXBalance},
         eval_all, execute,
-        types::PrincipalData,
-        ContractContext, Error, GlobalContext, LimitedCostTracker, QualifiedContractIdentifier,
-        Value,
+        types::{PrincipalData, TupleData},
+        ClarityVersion, ContractContext, Error, GlobalContext, LimitedCostTracker,
+        QualifiedContractIdentifier, Value,
     };
 
     struct DocHeadersDB {}
@@ -1790,6 +2142,9 @@ mod test {
         fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
             None
         }
+        fn get_consensus_hash_for_block(&self, _id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+            Some(ConsensusHash([0; 20]))
+        }
     }
 
     struct DocBurnStateDB {}
@@ -1811,6 +2166,45 @@ mod test {
                 .unwrap(),
             )
         }
+        fn get_sortition_id_from_consensus_hash(
+            &self,
+            _consensus_hash: &ConsensusHash,
+        ) -> Option<SortitionId> {
+            Some(SortitionId([0; 32]))
+        }
+        fn get_pox_payout_addrs(
+            &self,
+            _height: u32,
+            _sortition_id: &SortitionId,
+        ) -> Option<(Vec<TupleData>, u128)> {
+            Some((
+                vec![
+                    TupleData::from_data(vec![
+                        ("version".into(), Value::buff_from(vec![0u8]).unwrap()),
+                        (
+                            "hashbytes".into(),
+                            Value::buff_from(
+                                hex_bytes("7354ae2c8afc8c2ad6c2ebd4ef8c09e32dbfb37c").unwrap(),
+                            )
+                            .unwrap(),
+                        ),
+                    ])
+                    .unwrap(),
+                    TupleData::from_data(vec![
+                        ("version".into(), Value::buff_from(vec![1u8]).unwrap()),
+                        (
+                            "hashbytes".into(),
+                            Value::buff_from(
+                                hex_bytes("d52ae4a11d4f0ccd2a4a3f4d2b36a6c7e2b12b3a").unwrap(),
+                            )
+                            .unwrap(),
+                        ),
+                    ])
+                    .unwrap(),
+                ],
+                123,
+            ))
+        }
     }
 
     fn docs_execute(marf: &mut MarfedKV, program: &str) {
@@ -1836,7 +2230,8 @@ mod test {
 
         let conn = store.as_clarity_db(&DOC_HEADER_DB, &DOC_POX_STATE_DB);
         let contract_id = QualifiedContractIdentifier::local("docs-test").unwrap();
-        let mut contract_context = ContractContext::new(contract_id.clone());
+        let mut contract_context =
+            ContractContext::new(contract_id.clone(), ClarityVersion::latest());
         let mut global_context = GlobalContext::new(false, conn, LimitedCostTracker::new_free());
 
         global_context
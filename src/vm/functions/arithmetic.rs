@@ -52,6 +52,25 @@ macro_rules! type_force_binary_arithmetic {
     }};
 }
 
+// This macro checks that the shift amount is a `uint`, and then dispatches the evaluation
+//   to the arithmetic type handler for the value being shifted.
+macro_rules! type_force_shift_arithmetic {
+    ($function: ident, $x: expr, $shift: expr) => {{
+        match ($x, $shift) {
+            (Value::Int(x), Value::UInt(shift)) => I128Ops::$function(x, shift),
+            (Value::UInt(x), Value::UInt(shift)) => U128Ops::$function(x, shift),
+            (Value::Int(_), shift) | (Value::UInt(_), shift) => {
+                Err(CheckErrors::TypeValueError(TypeSignature::UIntType, shift).into())
+            }
+            (x, _) => Err(CheckErrors::UnionTypeValueError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                x,
+            )
+            .into()),
+        }
+    }};
+}
+
 macro_rules! type_force_unary_arithmetic {
     ($function: ident, $x: expr) => {{
         match $x {
@@ -122,6 +141,23 @@ macro_rules! make_arithmetic_ops {
             fn xor(x: $type, y: $type) -> InterpreterResult<Value> {
                 Self::make_value(x ^ y)
             }
+            fn bitwise_and(args: &[$type]) -> InterpreterResult<Value> {
+                Self::make_value(args.iter().fold(!0, |acc: $type, x: &$type| acc & *x))
+            }
+            fn bitwise_or(args: &[$type]) -> InterpreterResult<Value> {
+                Self::make_value(args.iter().fold(0, |acc: $type, x: &$type| acc | *x))
+            }
+            fn bitwise_not(x: $type) -> InterpreterResult<Value> {
+                Self::make_value(!x)
+            }
+            // shifts only look at the low 7 bits of the shift amount, so shifting by 128 or
+            //   more wraps around rather than failing. Right shifts of `int`s are arithmetic.
+            fn shift_left(x: $type, shift: u128) -> InterpreterResult<Value> {
+                Self::make_value(x.wrapping_shl((shift % 128) as u32))
+            }
+            fn shift_right(x: $type, shift: u128) -> InterpreterResult<Value> {
+                Self::make_value(x.wrapping_shr((shift % 128) as u32))
+            }
             fn leq(x: $type, y: $type) -> InterpreterResult<Value> {
                 Ok(Value::Bool(x <= y))
             }
@@ -246,6 +282,21 @@ make_arithmetic_ops!(I128Ops, i128);
 pub fn native_xor(a: Value, b: Value) -> InterpreterResult<Value> {
     type_force_binary_arithmetic!(xor, a, b)
 }
+pub fn native_bitwise_and(mut args: Vec<Value>) -> InterpreterResult<Value> {
+    type_force_variadic_arithmetic!(bitwise_and, args)
+}
+pub fn native_bitwise_or(mut args: Vec<Value>) -> InterpreterResult<Value> {
+    type_force_variadic_arithmetic!(bitwise_or, args)
+}
+pub fn native_bitwise_not(a: Value) -> InterpreterResult<Value> {
+    type_force_unary_arithmetic!(bitwise_not, a)
+}
+pub fn native_bitwise_left_shift(a: Value, b: Value) -> InterpreterResult<Value> {
+    type_force_shift_arithmetic!(shift_left, a, b)
+}
+pub fn native_bitwise_right_shift(a: Value, b: Value) -> InterpreterResult<Value> {
+    type_force_shift_arithmetic!(shift_right, a, b)
+}
 pub fn native_geq(a: Value, b: Value) -> InterpreterResult<Value> {
     type_force_binary_arithmetic!(geq, a, b)
 }
@@ -25,7 +25,8 @@ use vm::errors::{
 };
 use vm::representations::SymbolicExpression;
 use vm::types::{
-    AssetIdentifier, BlockInfoProperty, BuffData, OptionalData, PrincipalData, TypeSignature, Value,
+    AssetIdentifier, BlockInfoProperty, BuffData, OptionalData, PrincipalData, SequenceData,
+    TupleData, TypeSignature, Value,
 };
 use vm::{eval, Environment, LocalContext};
 
@@ -96,14 +97,51 @@ pub fn special_stx_balance(
     }
 }
 
+pub fn special_stx_account(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    runtime_cost(ClarityCostFunction::StxGetAccount, env, 0)?;
+
+    let owner = eval(&args[0], env, context)?;
+
+    if let Value::Principal(ref principal) = owner {
+        let (unlocked, (locked, unlock_height)) = {
+            let snapshot = env
+                .global_context
+                .database
+                .get_stx_balance_snapshot(principal);
+            (
+                snapshot.get_available_balance(),
+                snapshot.get_locked_balance(),
+            )
+        };
+        Ok(Value::Tuple(TupleData::from_data(vec![
+            ("locked".into(), Value::UInt(locked)),
+            (
+                "unlock-height".into(),
+                Value::UInt(u128::from(unlock_height)),
+            ),
+            ("unlocked".into(), Value::UInt(unlocked)),
+        ])?))
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into())
+    }
+}
+
 /// Do a "consolidated" STX transfer.
 /// If the 'from' principal has locked STX, and they have unlocked, then process the STX unlock
 /// and update its balance in addition to spending tokens out of it.
+/// The `memo` is recorded in the transfer event.
 pub fn stx_transfer_consolidated(
     env: &mut Environment,
     from: &PrincipalData,
     to: &PrincipalData,
     amount: u128,
+    memo: &BuffData,
 ) -> Result<Value> {
     if amount == 0 {
         return clarity_ecode!(StxErrorCodes::NON_POSITIVE_AMOUNT);
@@ -139,7 +177,7 @@ pub fn stx_transfer_consolidated(
     sender_snapshot.transfer_to(to, amount)?;
 
     env.global_context.log_stx_transfer(&from, amount)?;
-    env.register_stx_transfer_event(from.clone(), to.clone(), amount)?;
+    env.register_stx_transfer_event(from.clone(), to.clone(), amount, memo.clone())?;
     Ok(Value::okay_true())
 }
 
@@ -159,7 +197,34 @@ pub fn special_stx_transfer(
     if let (Value::Principal(ref from), Value::Principal(ref to), Value::UInt(amount)) =
         (&from_val, to_val, amount_val)
     {
-        stx_transfer_consolidated(env, from, to, amount)
+        stx_transfer_consolidated(env, from, to, amount, &BuffData::empty())
+    } else {
+        Err(CheckErrors::BadTransferSTXArguments.into())
+    }
+}
+
+pub fn special_stx_transfer_memo(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(4, args)?;
+
+    runtime_cost(ClarityCostFunction::StxTransferMemo, env, 0)?;
+
+    let amount_val = eval(&args[0], env, context)?;
+    let from_val = eval(&args[1], env, context)?;
+    let to_val = eval(&args[2], env, context)?;
+    let memo_val = eval(&args[3], env, context)?;
+
+    if let (
+        Value::Principal(ref from),
+        Value::Principal(ref to),
+        Value::UInt(amount),
+        Value::Sequence(SequenceData::Buffer(ref memo)),
+    ) = (&from_val, to_val, amount_val, memo_val)
+    {
+        stx_transfer_consolidated(env, from, to, amount, memo)
     } else {
         Err(CheckErrors::BadTransferSTXArguments.into())
     }
@@ -0,0 +1,150 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use vm::errors::{CheckErrors, InterpreterResult as Result};
+use vm::types::{CharType, SequenceData, TypeSignature, Value, BUFF_16};
+
+/// The number of characters in `i128::MIN`: the longest string that `int-to-ascii` or
+/// `int-to-utf8` can return, and that `string-to-int?` or `string-to-uint?` can parse
+pub const MAX_INTEGER_STRING_LEN: usize = 40;
+
+fn int_to_string(input: Value) -> Result<String> {
+    match input {
+        Value::Int(int_val) => Ok(int_val.to_string()),
+        Value::UInt(uint_val) => Ok(uint_val.to_string()),
+        _ => Err(CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            input,
+        )
+        .into()),
+    }
+}
+
+pub fn native_int_to_ascii(input: Value) -> Result<Value> {
+    Value::string_ascii_from_bytes(int_to_string(input)?.into_bytes())
+}
+
+pub fn native_int_to_utf8(input: Value) -> Result<Value> {
+    Value::string_utf8_from_bytes(int_to_string(input)?.into_bytes())
+}
+
+/// Get the text of a string value, if it could be the decimal representation of an integer
+fn get_integer_string(input: Value) -> Result<Option<String>> {
+    let bytes = match input {
+        Value::Sequence(SequenceData::String(CharType::ASCII(data))) => data.data,
+        Value::Sequence(SequenceData::String(CharType::UTF8(data))) => {
+            if data.data.iter().any(|c| c.len() != 1) {
+                // a decimal integer is all ASCII
+                return Ok(None);
+            }
+            data.data.into_iter().map(|c| c[0]).collect()
+        }
+        _ => {
+            return Err(CheckErrors::UnionTypeValueError(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                input,
+            )
+            .into())
+        }
+    };
+    // Rust's integer parsing accepts a leading `+`, but Clarity's integer literals don't have one
+    if bytes.len() > MAX_INTEGER_STRING_LEN || bytes.first() == Some(&b'+') {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(bytes).ok())
+}
+
+pub fn native_string_to_int(input: Value) -> Result<Value> {
+    match get_integer_string(input)?.and_then(|string| string.parse::<i128>().ok()) {
+        Some(int_val) => Value::some(Value::Int(int_val)),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn native_string_to_uint(input: Value) -> Result<Value> {
+    match get_integer_string(input)?.and_then(|string| string.parse::<u128>().ok()) {
+        Some(uint_val) => Value::some(Value::UInt(uint_val)),
+        None => Ok(Value::none()),
+    }
+}
+
+/// Get the bytes of a buffer of up to 16 bytes, zero-padded to 16 bytes on the right (for
+/// little-endian integers) or on the left (for big-endian integers)
+fn get_padded_buffer(input: Value, little_endian: bool) -> Result<[u8; 16]> {
+    match input {
+        Value::Sequence(SequenceData::Buffer(ref data)) if data.data.len() <= 16 => {
+            let mut bytes = [0u8; 16];
+            if little_endian {
+                bytes[..data.data.len()].copy_from_slice(&data.data);
+            } else {
+                bytes[16 - data.data.len()..].copy_from_slice(&data.data);
+            }
+            Ok(bytes)
+        }
+        _ => Err(CheckErrors::TypeValueError(BUFF_16.clone(), input).into()),
+    }
+}
+
+pub fn native_buff_to_int_le(input: Value) -> Result<Value> {
+    Ok(Value::Int(i128::from_le_bytes(get_padded_buffer(
+        input, true,
+    )?)))
+}
+
+pub fn native_buff_to_int_be(input: Value) -> Result<Value> {
+    Ok(Value::Int(i128::from_be_bytes(get_padded_buffer(
+        input, false,
+    )?)))
+}
+
+pub fn native_buff_to_uint_le(input: Value) -> Result<Value> {
+    Ok(Value::UInt(u128::from_le_bytes(get_padded_buffer(
+        input, true,
+    )?)))
+}
+
+pub fn native_buff_to_uint_be(input: Value) -> Result<Value> {
+    Ok(Value::UInt(u128::from_be_bytes(get_padded_buffer(
+        input, false,
+    )?)))
+}
+
+pub fn native_int_to_buff_le(input: Value) -> Result<Value> {
+    match input {
+        Value::Int(int_val) => Value::buff_from(int_val.to_le_bytes().to_vec()),
+        Value::UInt(uint_val) => Value::buff_from(uint_val.to_le_bytes().to_vec()),
+        _ => Err(CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            input,
+        )
+        .into()),
+    }
+}
+
+pub fn native_int_to_buff_be(input: Value) -> Result<Value> {
+    match input {
+        Value::Int(int_val) => Value::buff_from(int_val.to_be_bytes().to_vec()),
+        Value::UInt(uint_val) => Value::buff_from(uint_val.to_be_bytes().to_vec()),
+        _ => Err(CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            input,
+        )
+        .into()),
+    }
+}
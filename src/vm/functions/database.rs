@@ -30,8 +30,8 @@ use vm::errors::{
 };
 use vm::representations::{SymbolicExpression, SymbolicExpressionType};
 use vm::types::{
-    BlockInfoProperty, BuffData, OptionalData, PrincipalData, SequenceData, TypeSignature, Value,
-    BUFF_32,
+    BlockInfoProperty, BuffData, BurnBlockInfoProperty, OptionalData, PrincipalData, SequenceData,
+    TupleData, TypeSignature, Value, BUFF_32,
 };
 use vm::{eval, Environment, LocalContext};
 
@@ -499,3 +499,68 @@ pub fn special_get_block_info(
 
     Ok(Value::some(result)?)
 }
+
+pub fn special_get_burn_block_info(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (get-burn-block-info? property-name burn-block-height-int)
+    runtime_cost(ClarityCostFunction::GetBurnBlockInfo, env, 0)?;
+
+    check_argument_count(2, args)?;
+
+    // Handle the block property name input arg.
+    let property_name = args[0]
+        .match_atom()
+        .ok_or(CheckErrors::GetBlockInfoExpectPropertyName)?;
+
+    let block_info_prop = BurnBlockInfoProperty::lookup_by_name(property_name)
+        .ok_or(CheckErrors::GetBlockInfoExpectPropertyName)?;
+
+    // Handle the burn-block-height input arg clause.
+    let height_eval = eval(&args[1], env, context)?;
+    let height_value = match height_eval {
+        Value::UInt(result) => Ok(result),
+        x => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, x)),
+    }?;
+
+    let height_value = match u32::try_from(height_value) {
+        Ok(result) => result,
+        _ => return Ok(Value::none()),
+    };
+
+    // heights past the burnchain tip of this block's parent are not found, so they are `none`
+    let result = match block_info_prop {
+        BurnBlockInfoProperty::HeaderHash => {
+            match env
+                .global_context
+                .database
+                .get_burnchain_block_header_hash_for_burnchain_height(height_value)
+            {
+                Some(burnchain_header_hash) => Value::Sequence(SequenceData::Buffer(BuffData {
+                    data: burnchain_header_hash.as_bytes().to_vec(),
+                })),
+                None => return Ok(Value::none()),
+            }
+        }
+        BurnBlockInfoProperty::PoxAddrs => {
+            match env
+                .global_context
+                .database
+                .get_pox_payout_addrs_for_burnchain_height(height_value)
+            {
+                Some((addrs, payout)) => Value::Tuple(TupleData::from_data(vec![
+                    (
+                        "addrs".into(),
+                        Value::list_from(addrs.into_iter().map(Value::Tuple).collect())?,
+                    ),
+                    ("payout".into(), Value::UInt(payout)),
+                ])?),
+                None => return Ok(Value::none()),
+            }
+        }
+    };
+
+    Ok(Value::some(result)?)
+}
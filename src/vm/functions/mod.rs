@@ -17,10 +17,12 @@
 mod arithmetic;
 mod assets;
 mod boolean;
+mod conversions;
 mod crypto;
 mod database;
 pub mod define;
 mod options;
+mod principals;
 mod sequences;
 mod special;
 pub mod tuples;
@@ -41,12 +43,13 @@ use vm::types::{
     BuffData, CharType, PrincipalData, ResponseData, SequenceData, TypeSignature, Value, BUFF_32,
     BUFF_33, BUFF_65,
 };
-use vm::{eval, Environment, LocalContext};
+use vm::{eval, ClarityVersion, Environment, LocalContext};
 
 use address::AddressHashMode;
 use chainstate::stacks::{StacksAddress, C32_ADDRESS_VERSION_TESTNET_SINGLESIG};
 use vm::costs::cost_functions::ClarityCostFunction;
 pub use vm::functions::assets::stx_transfer_consolidated;
+pub use vm::functions::conversions::MAX_INTEGER_STRING_LEN;
 pub use vm::functions::special::handle_contract_call_special_cases;
 
 define_named_enum!(NativeFunctions {
@@ -132,12 +135,59 @@ define_named_enum!(NativeFunctions {
     GetStxBalance("stx-get-balance"),
     StxTransfer("stx-transfer?"),
     StxBurn("stx-burn?"),
+    IntToAscii("int-to-ascii"),
+    IntToUtf8("int-to-utf8"),
+    StringToInt("string-to-int?"),
+    StringToUInt("string-to-uint?"),
+    BuffToIntLe("buff-to-int-le"),
+    BuffToIntBe("buff-to-int-be"),
+    BuffToUIntLe("buff-to-uint-le"),
+    BuffToUIntBe("buff-to-uint-be"),
+    IntToBuffLe("int-to-buff-le"),
+    IntToBuffBe("int-to-buff-be"),
+    Slice("slice?"),
+    ReplaceAt("replace-at?"),
+    BitwiseAnd("bit-and"),
+    BitwiseOr("bit-or"),
+    BitwiseNot("bit-not"),
+    BitwiseLShift("bit-shift-left"),
+    BitwiseRShift("bit-shift-right"),
+    PrincipalDestruct("principal-destruct?"),
+    PrincipalConstruct("principal-construct?"),
+    GetStxAccount("stx-account"),
+    GetBurnBlockInfo("get-burn-block-info?"),
+    StxTransferMemo("stx-transfer-memo?"),
 });
 
-pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
+impl NativeFunctions {
+    /// The Clarity version in which this native function was introduced.  Contracts published
+    ///  with an earlier version cannot call it, and may use its name for their own definitions.
+    pub fn get_version(&self) -> ClarityVersion {
+        use vm::functions::NativeFunctions::*;
+        match self {
+            IntToAscii | IntToUtf8 | StringToInt | StringToUInt | BuffToIntLe | BuffToIntBe
+            | BuffToUIntLe | BuffToUIntBe | IntToBuffLe | IntToBuffBe | Slice | ReplaceAt
+            | BitwiseAnd | BitwiseOr | BitwiseNot | BitwiseLShift | BitwiseRShift
+            | PrincipalDestruct | PrincipalConstruct | GetStxAccount | GetBurnBlockInfo
+            | StxTransferMemo => ClarityVersion::Clarity2,
+            _ => ClarityVersion::Clarity1,
+        }
+    }
+
+    /// Look up a native function by name, as seen by a contract of the given Clarity version
+    pub fn lookup_by_name_at_version(
+        name: &str,
+        version: &ClarityVersion,
+    ) -> Option<NativeFunctions> {
+        NativeFunctions::lookup_by_name(name)
+            .filter(|native_function| native_function.get_version() <= *version)
+    }
+}
+
+pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option<CallableType> {
     use vm::callables::CallableType::{NativeFunction, SpecialFunction};
     use vm::functions::NativeFunctions::*;
-    if let Some(native_function) = NativeFunctions::lookup_by_name(name) {
+    if let Some(native_function) = NativeFunctions::lookup_by_name_at_version(name, version) {
         let callable = match native_function {
             Add => NativeFunction(
                 "native_add",
@@ -397,6 +447,100 @@ pub fn lookup_reserved_functions(name: &str) -> Option<CallableType> {
             GetStxBalance => SpecialFunction("special_stx_balance", &assets::special_stx_balance),
             StxTransfer => SpecialFunction("special_stx_transfer", &assets::special_stx_transfer),
             StxBurn => SpecialFunction("special_stx_burn", &assets::special_stx_burn),
+            IntToAscii => NativeFunction(
+                "native_int_to_ascii",
+                NativeHandle::SingleArg(&conversions::native_int_to_ascii),
+                ClarityCostFunction::IntToAscii,
+            ),
+            IntToUtf8 => NativeFunction(
+                "native_int_to_utf8",
+                NativeHandle::SingleArg(&conversions::native_int_to_utf8),
+                ClarityCostFunction::IntToUtf8,
+            ),
+            StringToInt => NativeFunction(
+                "native_string_to_int",
+                NativeHandle::SingleArg(&conversions::native_string_to_int),
+                ClarityCostFunction::StringToInt,
+            ),
+            StringToUInt => NativeFunction(
+                "native_string_to_uint",
+                NativeHandle::SingleArg(&conversions::native_string_to_uint),
+                ClarityCostFunction::StringToUInt,
+            ),
+            BuffToIntLe => NativeFunction(
+                "native_buff_to_int_le",
+                NativeHandle::SingleArg(&conversions::native_buff_to_int_le),
+                ClarityCostFunction::BuffToIntLe,
+            ),
+            BuffToIntBe => NativeFunction(
+                "native_buff_to_int_be",
+                NativeHandle::SingleArg(&conversions::native_buff_to_int_be),
+                ClarityCostFunction::BuffToIntBe,
+            ),
+            BuffToUIntLe => NativeFunction(
+                "native_buff_to_uint_le",
+                NativeHandle::SingleArg(&conversions::native_buff_to_uint_le),
+                ClarityCostFunction::BuffToUIntLe,
+            ),
+            BuffToUIntBe => NativeFunction(
+                "native_buff_to_uint_be",
+                NativeHandle::SingleArg(&conversions::native_buff_to_uint_be),
+                ClarityCostFunction::BuffToUIntBe,
+            ),
+            IntToBuffLe => NativeFunction(
+                "native_int_to_buff_le",
+                NativeHandle::SingleArg(&conversions::native_int_to_buff_le),
+                ClarityCostFunction::IntToBuffLe,
+            ),
+            IntToBuffBe => NativeFunction(
+                "native_int_to_buff_be",
+                NativeHandle::SingleArg(&conversions::native_int_to_buff_be),
+                ClarityCostFunction::IntToBuffBe,
+            ),
+            Slice => SpecialFunction("special_slice", &sequences::special_slice),
+            ReplaceAt => SpecialFunction("special_replace_at", &sequences::special_replace_at),
+            BitwiseAnd => NativeFunction(
+                "native_bitwise_and",
+                NativeHandle::MoreArg(&arithmetic::native_bitwise_and),
+                ClarityCostFunction::BitwiseAnd,
+            ),
+            BitwiseOr => NativeFunction(
+                "native_bitwise_or",
+                NativeHandle::MoreArg(&arithmetic::native_bitwise_or),
+                ClarityCostFunction::BitwiseOr,
+            ),
+            BitwiseNot => NativeFunction(
+                "native_bitwise_not",
+                NativeHandle::SingleArg(&arithmetic::native_bitwise_not),
+                ClarityCostFunction::BitwiseNot,
+            ),
+            BitwiseLShift => NativeFunction(
+                "native_bitwise_left_shift",
+                NativeHandle::DoubleArg(&arithmetic::native_bitwise_left_shift),
+                ClarityCostFunction::BitwiseLShift,
+            ),
+            BitwiseRShift => NativeFunction(
+                "native_bitwise_right_shift",
+                NativeHandle::DoubleArg(&arithmetic::native_bitwise_right_shift),
+                ClarityCostFunction::BitwiseRShift,
+            ),
+            PrincipalDestruct => SpecialFunction(
+                "special_principal_destruct",
+                &principals::special_principal_destruct,
+            ),
+            PrincipalConstruct => SpecialFunction(
+                "special_principal_construct",
+                &principals::special_principal_construct,
+            ),
+            GetStxAccount => SpecialFunction("special_stx_account", &assets::special_stx_account),
+            GetBurnBlockInfo => SpecialFunction(
+                "special_get_burn_block_info",
+                &database::special_get_burn_block_info,
+            ),
+            StxTransferMemo => SpecialFunction(
+                "special_stx_transfer_memo",
+                &assets::special_stx_transfer_memo,
+            ),
         };
         Some(callable)
     } else {
@@ -556,7 +700,7 @@ fn special_let(
 
     finally_drop_memory!( env, memory_use; {
         handle_binding_list::<_, Error>(bindings, |binding_name, var_sexp| {
-            if is_reserved(binding_name) ||
+            if is_reserved(binding_name, env.contract_context.get_clarity_version()) ||
                 env.contract_context.lookup_function(binding_name).is_some() ||
                 inner_context.lookup_variable(binding_name).is_some() {
                     return Err(CheckErrors::NameAlreadyUsed(binding_name.clone().into()).into())
@@ -0,0 +1,183 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::TryFrom;
+
+use vm::costs::cost_functions::ClarityCostFunction;
+use vm::costs::runtime_cost;
+use vm::errors::{
+    check_argument_count, check_arguments_at_least, CheckErrors, InterpreterResult as Result,
+};
+use vm::representations::{ContractName, SymbolicExpression};
+use vm::types::{
+    ASCIIData, BuffData, CharType, PrincipalData, QualifiedContractIdentifier, SequenceData,
+    StandardPrincipalData, TupleData, TypeSignature, Value, BUFF_1, BUFF_20,
+};
+use vm::{eval, Environment, LocalContext};
+
+use chainstate::stacks::{
+    C32_ADDRESS_VERSION_MAINNET_MULTISIG, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+    C32_ADDRESS_VERSION_TESTNET_MULTISIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+
+/// `principal-construct?` error code: the principal is valid, but not for this network
+pub const PRINCIPAL_CONSTRUCT_WRONG_NETWORK: u128 = 0;
+/// `principal-construct?` error code: the version byte or hash bytes are malformed
+pub const PRINCIPAL_CONSTRUCT_BAD_BYTES: u128 = 1;
+/// `principal-construct?` error code: the contract name is malformed
+pub const PRINCIPAL_CONSTRUCT_BAD_CONTRACT_NAME: u128 = 2;
+
+/// Is `version` one of the address versions of the network this VM is running on?
+fn version_matches_network(version: u8, mainnet: bool) -> bool {
+    if mainnet {
+        version == C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+            || version == C32_ADDRESS_VERSION_MAINNET_MULTISIG
+    } else {
+        version == C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+            || version == C32_ADDRESS_VERSION_TESTNET_MULTISIG
+    }
+}
+
+fn make_principal_construct_error(
+    error_code: u128,
+    principal: Option<PrincipalData>,
+) -> Result<Value> {
+    let value = match principal {
+        Some(principal) => Value::some(Value::Principal(principal))?,
+        None => Value::none(),
+    };
+    Value::error(Value::Tuple(TupleData::from_data(vec![
+        ("error_code".into(), Value::UInt(error_code)),
+        ("value".into(), value),
+    ])?))
+}
+
+pub fn special_principal_destruct(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (principal-destruct? principal)
+    check_argument_count(1, args)?;
+
+    runtime_cost(ClarityCostFunction::PrincipalDestruct, env, 0)?;
+
+    let principal = eval(&args[0], env, context)?;
+    let (issuer, name) = match principal {
+        Value::Principal(PrincipalData::Standard(issuer)) => (issuer, None),
+        Value::Principal(PrincipalData::Contract(QualifiedContractIdentifier { issuer, name })) => {
+            (issuer, Some(name))
+        }
+        _ => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, principal).into())
+        }
+    };
+    let StandardPrincipalData(version, hash_bytes) = issuer;
+
+    let name = match name {
+        Some(name) => Value::some(Value::string_ascii_from_bytes(
+            name.as_str().as_bytes().to_vec(),
+        )?)?,
+        None => Value::none(),
+    };
+    let tuple = Value::Tuple(TupleData::from_data(vec![
+        ("version".into(), Value::buff_from_byte(version)),
+        ("hash-bytes".into(), Value::buff_from(hash_bytes.to_vec())?),
+        ("name".into(), name),
+    ])?);
+
+    if version_matches_network(version, env.global_context.mainnet) {
+        Value::okay(tuple)
+    } else {
+        Value::error(tuple)
+    }
+}
+
+pub fn special_principal_construct(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (principal-construct? version hash-bytes [contract-name])
+    check_arguments_at_least(2, args)?;
+    if args.len() > 3 {
+        return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+    }
+
+    runtime_cost(ClarityCostFunction::PrincipalConstruct, env, 0)?;
+
+    let version = eval(&args[0], env, context)?;
+    let hash_bytes = eval(&args[1], env, context)?;
+    let name = match args.get(2) {
+        Some(arg) => Some(eval(arg, env, context)?),
+        None => None,
+    };
+
+    // the type checker admits shorter buffers, so their lengths are checked here
+    let version = match version {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) if data.len() <= 1 => {
+            match data.first() {
+                Some(version) if *version < 32 => *version,
+                _ => return make_principal_construct_error(PRINCIPAL_CONSTRUCT_BAD_BYTES, None),
+            }
+        }
+        _ => return Err(CheckErrors::TypeValueError(BUFF_1, version).into()),
+    };
+    let hash_bytes = match hash_bytes {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) if data.len() <= 20 => {
+            if data.len() < 20 {
+                return make_principal_construct_error(PRINCIPAL_CONSTRUCT_BAD_BYTES, None);
+            }
+            let mut hash_bytes = [0u8; 20];
+            hash_bytes.copy_from_slice(&data);
+            hash_bytes
+        }
+        _ => return Err(CheckErrors::TypeValueError(BUFF_20, hash_bytes).into()),
+    };
+    let issuer = StandardPrincipalData(version, hash_bytes);
+
+    let principal = match name {
+        Some(Value::Sequence(SequenceData::String(CharType::ASCII(ASCIIData { data })))) => {
+            let name = match String::from_utf8(data)
+                .ok()
+                .and_then(|name| ContractName::try_from(name).ok())
+            {
+                Some(name) => name,
+                None => {
+                    return make_principal_construct_error(
+                        PRINCIPAL_CONSTRUCT_BAD_CONTRACT_NAME,
+                        None,
+                    )
+                }
+            };
+            PrincipalData::Contract(QualifiedContractIdentifier::new(issuer, name))
+        }
+        Some(name) => {
+            return Err(CheckErrors::TypeValueError(
+                TypeSignature::contract_name_string_ascii_type(),
+                name,
+            )
+            .into())
+        }
+        None => PrincipalData::Standard(issuer),
+    };
+
+    if version_matches_network(version, env.global_context.mainnet) {
+        Value::okay(Value::Principal(principal))
+    } else {
+        make_principal_construct_error(PRINCIPAL_CONSTRUCT_WRONG_NETWORK, Some(principal))
+    }
+}
@@ -311,3 +311,63 @@ pub fn native_element_at(sequence: Value, index: Value) -> Result<Value> {
         Ok(Value::none())
     }
 }
+
+/// Get the value of a sequence index argument, or `None` if it's too big to be an index
+fn get_sequence_index(index: Value) -> Result<Option<usize>> {
+    match index {
+        Value::UInt(index_u128) => Ok(usize::try_from(index_u128).ok()),
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, index).into()),
+    }
+}
+
+pub fn special_slice(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let left = eval(&args[1], env, context)?;
+    let right = eval(&args[2], env, context)?;
+
+    runtime_cost(ClarityCostFunction::Slice, env, sequence.size())?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+    match (get_sequence_index(left)?, get_sequence_index(right)?) {
+        (Some(left), Some(right)) => match sequence_data.slice(left, right) {
+            Some(result) => Value::some(result),
+            None => Ok(Value::none()),
+        },
+        _ => Ok(Value::none()),
+    }
+}
+
+pub fn special_replace_at(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let index = eval(&args[1], env, context)?;
+    let element = eval(&args[2], env, context)?;
+
+    runtime_cost(ClarityCostFunction::ReplaceAt, env, sequence.size())?;
+
+    let sequence_data = match sequence {
+        Value::Sequence(sequence_data) => sequence_data,
+        _ => return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into()),
+    };
+    match get_sequence_index(index)? {
+        Some(index) => match sequence_data.replace_at(index, element)? {
+            Some(result) => Value::some(result),
+            None => Ok(Value::none()),
+        },
+        None => Ok(Value::none()),
+    }
+}
@@ -29,8 +29,11 @@ pub mod contracts;
 pub mod ast;
 pub mod clarity;
 pub mod contexts;
+pub mod coverage;
 pub mod database;
+pub mod debug;
 pub mod representations;
+pub mod version;
 
 mod callables;
 mod functions;
@@ -50,6 +53,7 @@ use vm::costs::{
     MemoryConsumer,
 };
 use vm::database::MemoryBackingStore;
+use vm::debug::Debugger;
 use vm::errors::{
     CheckErrors, Error, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
@@ -64,7 +68,9 @@ pub use vm::representations::{
 use std::convert::{TryFrom, TryInto};
 pub use vm::contexts::MAX_CONTEXT_DEPTH;
 use vm::costs::cost_functions::ClarityCostFunction;
+pub use vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
 pub use vm::functions::stx_transfer_consolidated;
+pub use vm::version::ClarityVersion;
 
 const MAX_CALL_STACK_DEPTH: usize = 64;
 
@@ -105,7 +111,9 @@ fn lookup_variable(name: &str, context: &LocalContext, env: &mut Environment) ->
 pub fn lookup_function(name: &str, env: &mut Environment) -> Result<CallableType> {
     runtime_cost(ClarityCostFunction::LookupFunction, env, 0)?;
 
-    if let Some(result) = functions::lookup_reserved_functions(name) {
+    if let Some(result) =
+        functions::lookup_reserved_functions(name, env.contract_context.get_clarity_version())
+    {
         Ok(result)
     } else {
         let user_function = env
@@ -202,6 +210,25 @@ pub fn eval<'a>(
     exp: &SymbolicExpression,
     env: &'a mut Environment,
     context: &LocalContext,
+) -> Result<Value> {
+    if let Some(ref mut coverage) = env.global_context.coverage {
+        coverage.report_eval(exp);
+    }
+
+    if env.global_context.debugger.is_some() {
+        Debugger::begin_eval(env, context, exp);
+        let result = eval_expression(exp, env, context);
+        Debugger::finish_eval(env, &result);
+        result
+    } else {
+        eval_expression(exp, env, context)
+    }
+}
+
+fn eval_expression(
+    exp: &SymbolicExpression,
+    env: &mut Environment,
+    context: &LocalContext,
 ) -> Result<Value> {
     use vm::representations::SymbolicExpressionType::{
         Atom, AtomValue, Field, List, LiteralValue, TraitReference,
@@ -218,14 +245,24 @@ pub fn eval<'a>(
                 .match_atom()
                 .ok_or(CheckErrors::BadFunctionName)?;
             let f = lookup_function(&function_name, env)?;
-            apply(&f, &rest, env, context)
+            if let CallableType::UserFunction(_) = f {
+                // user-defined functions are profiled when they're applied
+                apply(&f, &rest, env, context)
+            } else {
+                env.global_context
+                    .cost_track
+                    .begin_profile_frame(|| function_name.to_string());
+                let result = apply(&f, &rest, env, context);
+                env.global_context.cost_track.end_profile_frame();
+                result
+            }
         }
         TraitReference(_, _) | Field(_) => unreachable!("can't be evaluated"),
     }
 }
 
-pub fn is_reserved(name: &str) -> bool {
-    if let Some(_result) = functions::lookup_reserved_functions(name) {
+pub fn is_reserved(name: &str, version: &ClarityVersion) -> bool {
+    if let Some(_result) = functions::lookup_reserved_functions(name, version) {
         true
     } else if variables::is_reserved_name(name) {
         true
@@ -256,7 +293,10 @@ fn eval_all(
                 let mut call_stack = CallStack::new();
                 let mut env = Environment::new(
                     context, contract_context, &mut call_stack, Some(publisher.clone()), Some(publisher.clone()));
-                functions::define::evaluate_define(exp, &mut env)
+                env.global_context.begin_coverage(&contract_context.contract_identifier);
+                let result = functions::define::evaluate_define(exp, &mut env);
+                env.global_context.end_coverage();
+                result
             })?;
             match try_define {
                 DefineResult::Variable(name, value) => {
@@ -357,7 +397,7 @@ fn eval_all(
  */
 pub fn execute(program: &str) -> Result<Option<Value>> {
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let mut contract_context = ContractContext::new(contract_id.clone(), ClarityVersion::latest());
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context = GlobalContext::new(false, conn, LimitedCostTracker::new_free());
@@ -378,8 +418,8 @@ mod test {
     use vm::execute;
     use vm::types::{QualifiedContractIdentifier, TypeSignature};
     use vm::{
-        CallStack, ContractContext, Environment, GlobalContext, LocalContext, SymbolicExpression,
-        Value,
+        CallStack, ClarityVersion, ContractContext, Environment, GlobalContext, LocalContext,
+        SymbolicExpression, Value,
     };
 
     #[test]
@@ -411,7 +451,10 @@ mod test {
         );
 
         let context = LocalContext::new();
-        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        let mut contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            ClarityVersion::latest(),
+        );
 
         let mut marf = MemoryBackingStore::new();
         let mut global_context =
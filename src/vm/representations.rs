@@ -190,6 +190,18 @@ impl PreSymbolicExpression {
     ) {
     }
 
+    /// Where the expression is in the source, if the parser recorded it (only with the
+    /// `developer-mode` feature)
+    #[cfg(feature = "developer-mode")]
+    pub fn span(&self) -> Option<&Span> {
+        Some(&self.span)
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn span(&self) -> Option<&Span> {
+        None
+    }
+
     pub fn sugared_contract_identifier(val: ContractName) -> PreSymbolicExpression {
         PreSymbolicExpression {
             pre_expr: PreSymbolicExpressionType::SugaredContractIdentifier(val),
@@ -377,6 +389,18 @@ impl SymbolicExpression {
     ) {
     }
 
+    /// Where the expression is in the source, if the parser recorded it (only with the
+    /// `developer-mode` feature)
+    #[cfg(feature = "developer-mode")]
+    pub fn span(&self) -> Option<&Span> {
+        Some(&self.span)
+    }
+
+    #[cfg(not(feature = "developer-mode"))]
+    pub fn span(&self) -> Option<&Span> {
+        None
+    }
+
     pub fn atom_value(val: Value) -> SymbolicExpression {
         SymbolicExpression {
             expr: SymbolicExpressionType::AtomValue(val),
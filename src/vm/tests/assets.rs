@@ -25,7 +25,9 @@ use vm::tests::{
     execute, is_committed, is_err_code, symbols_from_values, with_marfed_environment,
     with_memory_environment,
 };
-use vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, Value};
+use vm::types::{
+    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, TupleData, Value,
+};
 
 const FIRST_CLASS_TOKENS: &str = "(define-fungible-token stackaroos)
          (define-read-only (my-ft-get-balance (account principal))
@@ -136,6 +138,7 @@ fn test_native_stx_ops(owned_env: &mut OwnedEnvironment) {
     let contract = "(define-public (burn-stx (amount uint) (p principal)) (stx-burn? amount p))
                     (define-public (xfer-stx (amount uint) (p principal) (t principal)) (stx-transfer? amount p t))
                     (define-read-only (balance-stx (p principal)) (stx-get-balance p))
+                    (define-read-only (account-stx (p principal)) (stx-account p))
                     (define-public (to-contract (amount uint) (p principal))
                       (let ((contract-principal (as-contract tx-sender)))
                         (stx-transfer? amount p contract-principal)))
@@ -421,6 +424,29 @@ fn test_native_stx_ops(owned_env: &mut OwnedEnvironment) {
 
     assert_eq!(result, Value::UInt(10));
 
+    // ...and with stx-account
+
+    let (result, _asset_map, _events) = execute_transaction(
+        owned_env,
+        p2.clone(),
+        &token_contract_id,
+        "account-stx",
+        &symbols_from_values(vec![contract_principal.clone()]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        result,
+        Value::Tuple(
+            TupleData::from_data(vec![
+                ("locked".into(), Value::UInt(0)),
+                ("unlock-height".into(), Value::UInt(0)),
+                ("unlocked".into(), Value::UInt(10)),
+            ])
+            .unwrap()
+        )
+    );
+
     // now let's do a contract -> user transfer
 
     let (result, asset_map, _events) = execute_transaction(
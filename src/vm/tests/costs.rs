@@ -68,6 +68,13 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         Sqrti => "(sqrti 81)",
         Log2 => "(log2 8)",
         BitwiseXOR => "(xor 1 2)",
+        BitwiseAnd => "(bit-and 1 2)",
+        BitwiseOr => "(bit-or 1 2)",
+        BitwiseNot => "(bit-not 1)",
+        BitwiseLShift => "(bit-shift-left 1 u2)",
+        BitwiseRShift => "(bit-shift-right 4 u2)",
+        PrincipalDestruct => "(principal-destruct? 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        PrincipalConstruct => "(principal-construct? 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320)",
         And => "(and true false)",
         Or => "(or true false)",
         Not => "(not true)",
@@ -107,6 +114,7 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         PrincipalOf => "(principal-of? 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110)",
         AsContract => "(as-contract 1)",
         GetBlockInfo => "(get-block-info? time u1)",
+        GetBurnBlockInfo => "(get-burn-block-info? header-hash u1)",
         ConsOkay => "(ok 1)",
         ConsError => "(err 1)",
         ConsSome => "(some 1)",
@@ -133,8 +141,22 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         GetTokenSupply => "(ft-get-supply ft-foo)",
         AtBlock => "(at-block 0x55c9861be5cff984a20ce6d99d4aa65941412889bdc665094136429b84f8c2ee 1)",   // first stacksblockid
         GetStxBalance => "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        GetStxAccount => "(stx-account 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxTransfer => "(stx-transfer? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        StxTransferMemo => "(stx-transfer-memo? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 0x0102)",
         StxBurn => "(stx-burn? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        IntToAscii => "(int-to-ascii 1)",
+        IntToUtf8 => "(int-to-utf8 1)",
+        StringToInt => "(string-to-int? \"1\")",
+        StringToUInt => "(string-to-uint? \"1\")",
+        BuffToIntLe => "(buff-to-int-le 0x01)",
+        BuffToIntBe => "(buff-to-int-be 0x01)",
+        BuffToUIntLe => "(buff-to-uint-le 0x01)",
+        BuffToUIntBe => "(buff-to-uint-be 0x01)",
+        IntToBuffLe => "(int-to-buff-le 1)",
+        IntToBuffBe => "(int-to-buff-be 1)",
+        Slice => "(slice? list-bar u0 u1)",
+        ReplaceAt => "(replace-at? list-bar u0 2)",
     }
 }
 
@@ -615,6 +637,13 @@ fn test_cost_voting_integration() {
             cost_definer.clone().into(),
             "cost-definition",
         ),
+        // "boot cost" function is defined in costs-2, not costs
+        (
+            boot_code_id("costs", false).into(),
+            "cost_slice",
+            cost_definer.clone().into(),
+            "cost-definition",
+        ),
         // cost defining contract doesn't exist
         (
             intercepted.clone().into(),
@@ -709,7 +738,7 @@ fn test_cost_voting_integration() {
         for (target, referenced_function) in tracker.cost_function_references().into_iter() {
             assert_eq!(
                 &referenced_function.contract_id,
-                &boot_code_id("costs", false),
+                &boot_code_id(target.get_boot_contract_name(), false),
                 "All cost functions should still point to the boot costs"
             );
             assert_eq!(
@@ -736,6 +765,12 @@ fn test_cost_voting_integration() {
             cost_definer.clone(),
             "cost-definition-le",
         ),
+        (
+            boot_code_id("costs-2", false),
+            "cost_slice",
+            cost_definer.clone(),
+            "cost-definition-le",
+        ),
         (
             intercepted.clone(),
             "intercepted-function2",
@@ -818,13 +853,13 @@ fn test_cost_voting_integration() {
         assert_eq!(circuit2.unwrap().function_name, "cost-definition-multi-arg");
 
         for (target, referenced_function) in tracker.cost_function_references().into_iter() {
-            if target == &ClarityCostFunction::Le {
+            if target == &ClarityCostFunction::Le || target == &ClarityCostFunction::Slice {
                 assert_eq!(&referenced_function.contract_id, &cost_definer);
                 assert_eq!(&referenced_function.function_name, "cost-definition-le");
             } else {
                 assert_eq!(
                     &referenced_function.contract_id,
-                    &boot_code_id("costs", false),
+                    &boot_code_id(target.get_boot_contract_name(), false),
                     "Cost function should still point to the boot costs"
                 );
                 assert_eq!(
@@ -174,6 +174,108 @@ fn test_element_at() {
     }
 }
 
+#[test]
+fn test_slice() {
+    let good = [
+        "(slice? (list 1 2 3 4 5) u1 u3)",
+        "(slice? \"abcd\" u0 u4)",
+        "(slice? u\"abcd\" u2 u2)",
+        "(slice? 0xfedb u1 u2)",
+        "(slice? (list 1 2 3 4 5) u3 u2)",
+        "(slice? \"abcd\" u0 u5)",
+        "(slice? 0xfedb u340282366920938463463374607431768211455 u1)",
+        "(len (unwrap-panic (slice? (list 1 2 3 4 5) u0 u2)))",
+    ];
+
+    let expected = [
+        "(some (2 3))",
+        "(some \"abcd\")",
+        "(some u\"\")",
+        "(some 0xdb)",
+        "none",
+        "none",
+        "none",
+        "u2",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = ["(slice? 3 u1 u2)", "(slice? (list 1 2 3) 1 u2)"];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(1)),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        match execute(&bad_test).unwrap_err() {
+            Error::Unchecked(check_error) => {
+                assert_eq!(&check_error, expected);
+            }
+            _ => unreachable!("Should have raised unchecked errors"),
+        }
+    }
+}
+
+#[test]
+fn test_replace_at() {
+    let good = [
+        "(replace-at? (list 1 2 3) u0 4)",
+        "(replace-at? \"abcd\" u3 \"e\")",
+        "(replace-at? u\"abcd\" u1 u\"\\u{263A}\")",
+        "(replace-at? 0xfedb u1 0x01)",
+        "(replace-at? (list 1 2 3) u3 4)",
+        "(replace-at? \"\" u0 \"a\")",
+    ];
+
+    let expected = [
+        "(some (4 2 3))",
+        "(some \"abce\")",
+        "(some u\"a\\u{e298ba}cd\")",
+        "(some 0xfe01)",
+        "none",
+        "none",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = [
+        "(replace-at? 3 u1 4)",
+        "(replace-at? (list 1 2 3) u1 u4)",
+        "(replace-at? \"abcd\" u1 \"ef\")",
+        "(replace-at? 0xfedb u1 0x)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeValueError(TypeSignature::IntType, Value::UInt(4)),
+        CheckErrors::TypeValueError(
+            TypeSignature::min_string_ascii(),
+            execute("\"ef\"").unwrap().unwrap(),
+        ),
+        CheckErrors::TypeValueError(TypeSignature::min_buffer(), execute("0x").unwrap().unwrap()),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        match execute(&bad_test).unwrap_err() {
+            Error::Unchecked(check_error) => {
+                assert_eq!(&check_error, expected);
+            }
+            _ => unreachable!("Should have raised unchecked errors"),
+        }
+    }
+}
+
 #[test]
 fn test_string_ascii_admission() {
     let defines = "(define-private (set-name (x (string-ascii 11))) x)";
@@ -24,10 +24,12 @@ use vm::database::MemoryBackingStore;
 use vm::errors::{CheckErrors, Error, RuntimeErrorType, ShortReturnType};
 use vm::tests::execute;
 use vm::types::signatures::BufferLength;
-use vm::types::{BuffData, QualifiedContractIdentifier, TypeSignature};
+use vm::types::{BuffData, QualifiedContractIdentifier, TypeSignature, BUFF_1, BUFF_16};
 use vm::types::{PrincipalData, ResponseData, SequenceData, SequenceSubtype};
 use vm::{eval, execute as vm_execute};
-use vm::{CallStack, ContractContext, Environment, GlobalContext, LocalContext, Value};
+use vm::{
+    CallStack, ClarityVersion, ContractContext, Environment, GlobalContext, LocalContext, Value,
+};
 
 use address::c32;
 use address::AddressHashMode;
@@ -297,6 +299,89 @@ fn test_secp256k1_errors() {
     }
 }
 
+#[test]
+fn test_principal_destruct_and_construct() {
+    let testnet_tests = [
+        "(principal-destruct? 'ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)",
+        "(principal-destruct? 'SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ.foo)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x15 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"foo\")",
+        "(principal-construct? 0x16 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x1f 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x20 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a8)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"1foo\")",
+        "(let ((parts (unwrap-err-panic (principal-destruct? 'SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ.foo))))
+           (principal-construct? (get version parts) (get hash-bytes parts) (unwrap-panic (get name parts))))",
+    ];
+
+    let testnet_expectations = [
+        "(ok (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x1a)))",
+        "(err (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name (some \"foo\")) (version 0x16)))",
+        "(ok ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)",
+        "(ok SN1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VEB96V3.foo)",
+        "(err (tuple (error_code u0) (value (some SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ))))",
+        "(err (tuple (error_code u0) (value (some SZ1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582T5DJ3MS))))",
+        "(err (tuple (error_code u1) (value none)))",
+        "(err (tuple (error_code u1) (value none)))",
+        "(err (tuple (error_code u1) (value none)))",
+        "(err (tuple (error_code u2) (value none)))",
+        "(err (tuple (error_code u0) (value (some SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ.foo))))",
+    ];
+
+    for (program, expectation) in testnet_tests.iter().zip(testnet_expectations.iter()) {
+        assert_eq!(*expectation, format!("{}", execute(program)));
+    }
+
+    let mainnet_tests = [
+        "(principal-destruct? 'SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ)",
+        "(principal-destruct? 'SM1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RFYWE5B.foo)",
+        "(principal-destruct? 'ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP)",
+        "(principal-construct? 0x16 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"foo\")",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+    ];
+
+    let mainnet_expectations = [
+        "(ok (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x16)))",
+        "(ok (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name (some \"foo\")) (version 0x14)))",
+        "(err (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x1a)))",
+        "(ok SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ.foo)",
+        "(err (tuple (error_code u0) (value (some ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP))))",
+    ];
+
+    let mut marf = MemoryBackingStore::new();
+    let mut env = OwnedEnvironment::new_free(true, marf.as_clarity_db());
+    for (program, expectation) in mainnet_tests.iter().zip(mainnet_expectations.iter()) {
+        let (result, _, _) = env.eval_raw(program).unwrap();
+        assert_eq!(*expectation, format!("{}", result));
+    }
+
+    let bad_tests = [
+        "(principal-destruct? 0x01)",
+        "(principal-construct? 0x1a)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 \"foo\" \"bar\")",
+        "(principal-construct? 0x1a1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816)",
+        "(principal-construct? 0x1a 0x55c33a76868c1cdd2faedb909f13af348fd8a816 u\"foo\")",
+    ];
+
+    let bad_expectations: &[Error] = &[
+        CheckErrors::TypeValueError(TypeSignature::PrincipalType, Value::buff_from_byte(1)).into(),
+        CheckErrors::RequiresAtLeastArguments(2, 1).into(),
+        CheckErrors::IncorrectArgumentCount(3, 4).into(),
+        CheckErrors::TypeValueError(BUFF_1, Value::buff_from(vec![0x1a, 0x1a]).unwrap()).into(),
+        CheckErrors::TypeValueError(
+            TypeSignature::contract_name_string_ascii_type(),
+            Value::string_utf8_from_bytes(b"foo".to_vec()).unwrap(),
+        )
+        .into(),
+    ];
+
+    for (program, expectation) in bad_tests.iter().zip(bad_expectations.iter()) {
+        assert_eq!(*expectation, vm_execute(program).unwrap_err());
+    }
+}
+
 #[test]
 fn test_buffer_equality() {
     let tests = [
@@ -377,7 +462,10 @@ fn test_simple_if_functions() {
         );
 
         let context = LocalContext::new();
-        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        let mut contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            ClarityVersion::latest(),
+        );
         let mut marf = MemoryBackingStore::new();
         let mut global_context =
             GlobalContext::new(false, marf.as_clarity_db(), LimitedCostTracker::new_free());
@@ -597,6 +685,88 @@ fn test_simple_arithmetic_errors() {
     }
 }
 
+#[test]
+fn test_bitwise_functions() {
+    let tests = [
+        "(bit-and 24 16)",
+        "(bit-and u24 u16 u8)",
+        "(bit-and -1)",
+        "(bit-or 4 8 1)",
+        "(bit-or u4 u8)",
+        "(bit-or -64 -32)",
+        "(bit-not 3)",
+        "(bit-not u0)",
+        "(bit-shift-left 1 u4)",
+        "(bit-shift-left u1 u127)",
+        "(bit-shift-left 1 u127)",
+        "(bit-shift-left 3 u128)",
+        "(bit-shift-left u3 u130)",
+        "(bit-shift-right 16 u4)",
+        "(bit-shift-right -16 u2)",
+        "(bit-shift-right -1 u127)",
+        "(bit-shift-right u340282366920938463463374607431768211455 u127)",
+        "(bit-shift-right 16 u129)",
+    ];
+
+    let expectations = [
+        Value::Int(16),
+        Value::UInt(0),
+        Value::Int(-1),
+        Value::Int(13),
+        Value::UInt(12),
+        Value::Int(-32),
+        Value::Int(-4),
+        Value::UInt(u128::max_value()),
+        Value::Int(16),
+        Value::UInt(1 << 127),
+        Value::Int(i128::min_value()),
+        Value::Int(3),
+        Value::UInt(12),
+        Value::Int(1),
+        Value::Int(-4),
+        Value::Int(-1),
+        Value::UInt(1),
+        Value::Int(8),
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| assert_eq!(expectation.clone(), execute(program)));
+
+    let bad_tests = [
+        "(bit-and)",
+        "(bit-or 1 u2)",
+        "(bit-not)",
+        "(bit-not true)",
+        "(bit-shift-left 1)",
+        "(bit-shift-left 1 2)",
+        "(bit-shift-right true u1)",
+    ];
+
+    let bad_expectations: &[Error] = &[
+        CheckErrors::IncorrectArgumentCount(1, 0).into(),
+        CheckErrors::TypeValueError(TypeSignature::IntType, Value::UInt(2)).into(),
+        CheckErrors::IncorrectArgumentCount(1, 0).into(),
+        CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            Value::Bool(true),
+        )
+        .into(),
+        CheckErrors::IncorrectArgumentCount(2, 1).into(),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(2)).into(),
+        CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            Value::Bool(true),
+        )
+        .into(),
+    ];
+
+    for (program, expectation) in bad_tests.iter().zip(bad_expectations.iter()) {
+        assert_eq!(*expectation, vm_execute(program).unwrap_err());
+    }
+}
+
 #[test]
 fn test_unsigned_arithmetic() {
     let tests = [
@@ -626,6 +796,97 @@ fn test_unsigned_arithmetic() {
     }
 }
 
+#[test]
+fn test_integer_conversions() {
+    let tests = [
+        "(int-to-ascii -170141183460469231731687303715884105728)",
+        "(int-to-utf8 u340282366920938463463374607431768211455)",
+        "(string-to-int? \"-170141183460469231731687303715884105728\")",
+        "(string-to-int? \"170141183460469231731687303715884105728\")",
+        "(string-to-int? u\"-12\")",
+        "(string-to-int? \"+12\")",
+        "(string-to-int? \"1 2\")",
+        "(string-to-int? \"\")",
+        "(string-to-uint? \"340282366920938463463374607431768211455\")",
+        "(string-to-uint? \"-0\")",
+        "(string-to-uint? u\"12\\u{0663}\")",
+        "(buff-to-int-le 0x00000000000000000000000000000080)",
+        "(buff-to-int-be 0x80)",
+        "(buff-to-uint-le 0x0102)",
+        "(buff-to-uint-be 0x0102)",
+        "(int-to-buff-le -2)",
+        "(int-to-buff-be u258)",
+        "(buff-to-int-be (int-to-buff-be -170141183460469231731687303715884105728))",
+        "(buff-to-uint-le (int-to-buff-le u340282366920938463463374607431768211455))",
+    ];
+
+    let expectations = [
+        "\"-170141183460469231731687303715884105728\"",
+        "u\"340282366920938463463374607431768211455\"",
+        "(some -170141183460469231731687303715884105728)",
+        "none",
+        "(some -12)",
+        "none",
+        "none",
+        "none",
+        "(some u340282366920938463463374607431768211455)",
+        "none",
+        "none",
+        "-170141183460469231731687303715884105728",
+        "128",
+        "u513",
+        "u258",
+        "0xfeffffffffffffffffffffffffffffff",
+        "0x00000000000000000000000000000102",
+        "-170141183460469231731687303715884105728",
+        "u340282366920938463463374607431768211455",
+    ];
+
+    for (program, expectation) in tests.iter().zip(expectations.iter()) {
+        assert_eq!(
+            *expectation,
+            format!("{}", vm_execute(program).unwrap().unwrap())
+        );
+    }
+
+    let bad_tests = [
+        "(int-to-ascii true)",
+        "(string-to-int? 0x01)",
+        "(buff-to-int-le 0x0102030405060708090a0b0c0d0e0f1011)",
+        "(int-to-buff-be \"1\")",
+    ];
+
+    let bad_expectations: &[Error] = &[
+        CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            Value::Bool(true),
+        )
+        .into(),
+        CheckErrors::UnionTypeValueError(
+            vec![
+                TypeSignature::max_string_ascii(),
+                TypeSignature::max_string_utf8(),
+            ],
+            Value::buff_from(vec![1]).unwrap(),
+        )
+        .into(),
+        CheckErrors::TypeValueError(
+            BUFF_16.clone(),
+            Value::buff_from((1..18).collect()).unwrap(),
+        )
+        .into(),
+        CheckErrors::UnionTypeValueError(
+            vec![TypeSignature::IntType, TypeSignature::UIntType],
+            Value::string_ascii_from_bytes(b"1".to_vec()).unwrap(),
+        )
+        .into(),
+    ];
+
+    for (program, expectation) in bad_tests.iter().zip(bad_expectations.iter()) {
+        assert_eq!(*expectation, vm_execute(program).unwrap_err());
+    }
+}
+
 #[test]
 fn test_options_errors() {
     let tests = [
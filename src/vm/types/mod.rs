@@ -35,8 +35,8 @@ use vm::representations::{ClarityName, ContractName, SymbolicExpression, Symboli
 pub use vm::types::signatures::{
     parse_name_type_pairs, AssetIdentifier, BufferLength, FixedFunction, FunctionArg,
     FunctionSignature, FunctionType, ListTypeData, SequenceSubtype, StringSubtype,
-    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_20, BUFF_32, BUFF_33,
-    BUFF_64, BUFF_65,
+    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_16, BUFF_20, BUFF_32,
+    BUFF_33, BUFF_34, BUFF_64, BUFF_65,
 };
 
 pub const MAX_VALUE_SIZE: u32 = 1024 * 1024; // 1MB
@@ -263,6 +263,93 @@ impl SequenceData {
         Some(result)
     }
 
+    /// Get the elements from index `left` up to (but not including) index `right`, or `None` if
+    /// those indices aren't a range of this sequence.
+    pub fn slice(self, left: usize, right: usize) -> Option<Value> {
+        if left > right || right > self.len() {
+            return None;
+        }
+        macro_rules! slice {
+            ($data:expr) => {{
+                $data.data.truncate(right);
+                $data.data.drain(..left);
+            }};
+        }
+        let result = match self {
+            SequenceData::Buffer(mut data) => {
+                slice!(data);
+                SequenceData::Buffer(data)
+            }
+            SequenceData::List(mut data) => {
+                slice!(data);
+                SequenceData::List(data)
+            }
+            SequenceData::String(CharType::ASCII(mut data)) => {
+                slice!(data);
+                SequenceData::String(CharType::ASCII(data))
+            }
+            SequenceData::String(CharType::UTF8(mut data)) => {
+                slice!(data);
+                SequenceData::String(CharType::UTF8(data))
+            }
+        };
+        Some(Value::Sequence(result))
+    }
+
+    /// Replace the element at `index` with `element`, or return `None` if `index` is out of
+    /// range.  The elements of buffers and strings are 1-length buffers and strings.
+    pub fn replace_at(self, index: usize, element: Value) -> Result<Option<Value>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        let result = match (self, element) {
+            (SequenceData::Buffer(mut data), Value::Sequence(SequenceData::Buffer(element)))
+                if element.data.len() == 1 =>
+            {
+                data.data[index] = element.data[0];
+                SequenceData::Buffer(data)
+            }
+            (SequenceData::List(mut data), element) => {
+                let entry_type = data.type_signature.get_list_item_type();
+                if !entry_type.admits(&element) {
+                    return Err(CheckErrors::TypeValueError(entry_type.clone(), element).into());
+                }
+                data.data[index] = element;
+                SequenceData::List(data)
+            }
+            (
+                SequenceData::String(CharType::ASCII(mut data)),
+                Value::Sequence(SequenceData::String(CharType::ASCII(element))),
+            ) if element.data.len() == 1 => {
+                data.data[index] = element.data[0];
+                SequenceData::String(CharType::ASCII(data))
+            }
+            (
+                SequenceData::String(CharType::UTF8(mut data)),
+                Value::Sequence(SequenceData::String(CharType::UTF8(mut element))),
+            ) if element.data.len() == 1 => {
+                data.data[index] = element.data.remove(0);
+                SequenceData::String(CharType::UTF8(data))
+            }
+            (SequenceData::Buffer(_), element) => {
+                return Err(
+                    CheckErrors::TypeValueError(TypeSignature::min_buffer(), element).into(),
+                )
+            }
+            (SequenceData::String(CharType::ASCII(_)), element) => {
+                return Err(
+                    CheckErrors::TypeValueError(TypeSignature::min_string_ascii(), element).into(),
+                )
+            }
+            (SequenceData::String(CharType::UTF8(_)), element) => {
+                return Err(
+                    CheckErrors::TypeValueError(TypeSignature::min_string_utf8(), element).into(),
+                )
+            }
+        };
+        Ok(Some(Value::Sequence(result)))
+    }
+
     pub fn contains(&self, to_find: Value) -> Result<Option<usize>> {
         match self {
             SequenceData::Buffer(ref data) => {
@@ -566,6 +653,11 @@ define_named_enum!(BlockInfoProperty {
     MinerAddress("miner-address"),
 });
 
+define_named_enum!(BurnBlockInfoProperty {
+    HeaderHash("header-hash"),
+    PoxAddrs("pox-addrs"),
+});
+
 impl OptionalData {
     pub fn type_signature(&self) -> TypeSignature {
         let type_result = match self.data {
@@ -603,6 +695,34 @@ impl BlockInfoProperty {
     }
 }
 
+impl BurnBlockInfoProperty {
+    pub fn type_result(&self) -> TypeSignature {
+        use self::BurnBlockInfoProperty::*;
+        match self {
+            HeaderHash => BUFF_32.clone(),
+            PoxAddrs => TupleTypeSignature::try_from(vec![
+                (
+                    "addrs".into(),
+                    TypeSignature::list_of(
+                        TypeSignature::TupleType(
+                            TupleTypeSignature::try_from(vec![
+                                ("version".into(), BUFF_1.clone()),
+                                ("hashbytes".into(), BUFF_20.clone()),
+                            ])
+                            .expect("FATAL: bad type signature for pox addr"),
+                        ),
+                        2,
+                    )
+                    .expect("FATAL: bad list type signature"),
+                ),
+                ("payout".into(), TypeSignature::UIntType),
+            ])
+            .expect("FATAL: bad type signature for pox addr")
+            .into(),
+        }
+    }
+}
+
 impl PartialEq for ListData {
     fn eq(&self, other: &ListData) -> bool {
         self.data == other.data
@@ -959,6 +1079,10 @@ impl Value {
 }
 
 impl BuffData {
+    pub fn empty() -> BuffData {
+        BuffData { data: Vec::new() }
+    }
+
     pub fn len(&self) -> BufferLength {
         self.data.len().try_into().unwrap()
     }
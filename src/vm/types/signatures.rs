@@ -22,6 +22,7 @@ use std::{cmp, fmt};
 
 use address::c32;
 use util::hash;
+use vm::ast::parser::CONTRACT_MAX_NAME_LENGTH;
 use vm::costs::{cost_functions, runtime_cost, CostOverflowingMath};
 use vm::errors::{CheckErrors, Error as VMError, IncomparableError, RuntimeErrorType};
 use vm::representations::{
@@ -129,6 +130,8 @@ pub const BUFF_64: TypeSignature = SequenceType(SequenceSubtype::BufferType(Buff
 pub const BUFF_65: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(65)));
 pub const BUFF_32: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(32)));
 pub const BUFF_33: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(33)));
+pub const BUFF_34: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(34)));
+pub const BUFF_16: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(16)));
 pub const BUFF_20: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(20)));
 pub const BUFF_1: TypeSignature = SequenceType(SequenceSubtype::BufferType(BufferLength(1)));
 
@@ -160,6 +163,8 @@ pub enum FunctionType {
     ArithmeticUnary,
     ArithmeticBinary,
     ArithmeticComparison,
+    // Functions that take an `int` or `uint`, and a `uint`, and return the first argument's type
+    ArithmeticShift,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -637,6 +642,34 @@ impl TypeSignature {
         )))
     }
 
+    pub fn max_string_ascii() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+            BufferLength(
+                u32::try_from(MAX_VALUE_SIZE)
+                    .expect("FAIL: Max Clarity Value Size is no longer realizable in ASCII Type"),
+            ),
+        )))
+    }
+
+    pub fn max_string_utf8() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+            StringUTF8Length(
+                u32::try_from(MAX_VALUE_SIZE / 4)
+                    .expect("FAIL: Max Clarity Value Size is no longer realizable in UTF8 Type"),
+            ),
+        )))
+    }
+
+    /// The type of a contract name as a string: `(string-ascii 40)`
+    pub fn contract_name_string_ascii_type() -> TypeSignature {
+        SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+            BufferLength(
+                u32::try_from(CONTRACT_MAX_NAME_LENGTH)
+                    .expect("FAIL: Max contract name length is no longer realizable in ASCII Type"),
+            ),
+        )))
+    }
+
     pub fn max_buffer() -> TypeSignature {
         SequenceType(SequenceSubtype::BufferType(BufferLength(
             u32::try_from(MAX_VALUE_SIZE)
@@ -0,0 +1,69 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+/// The version of the Clarity language a contract was published with.
+///
+/// A contract keeps the version it was published with for its whole lifetime: native
+///  functions introduced in a later version are not visible to it, so names that it
+///  defines or binds can never be shadowed by a later upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ClarityVersion {
+    Clarity1,
+    Clarity2,
+}
+
+impl ClarityVersion {
+    /// The most recent version of the language.  Used for contracts that are not published
+    ///  on-chain, e.g. in the clarity-cli and in tests.
+    pub fn latest() -> ClarityVersion {
+        ClarityVersion::Clarity2
+    }
+
+    /// The version number stored in the Clarity database for this version
+    pub fn to_u128(&self) -> u128 {
+        match self {
+            ClarityVersion::Clarity1 => 1,
+            ClarityVersion::Clarity2 => 2,
+        }
+    }
+
+    pub fn from_u128(version: u128) -> Option<ClarityVersion> {
+        match version {
+            1 => Some(ClarityVersion::Clarity1),
+            2 => Some(ClarityVersion::Clarity2),
+            _ => None,
+        }
+    }
+}
+
+/// Contracts published before versioning was introduced, and whose serialized form therefore
+///  carries no version, are Clarity 1 contracts.
+impl Default for ClarityVersion {
+    fn default() -> ClarityVersion {
+        ClarityVersion::Clarity1
+    }
+}
+
+impl fmt::Display for ClarityVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClarityVersion::Clarity1 => write!(f, "Clarity 1"),
+            ClarityVersion::Clarity2 => write!(f, "Clarity 2"),
+        }
+    }
+}
@@ -6,6 +6,8 @@ use rand::RngCore;
 
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{MagicBytes, BLOCKSTACK_MAGIC_MAINNET};
+use stacks::chainstate::stacks::StacksBlockId;
+use stacks::core::mempool::{MemPoolSettings, MemPoolWalkOrder};
 use stacks::core::{
     BLOCK_LIMIT_MAINNET, CHAIN_ID_MAINNET, CHAIN_ID_TESTNET, PEER_VERSION_MAINNET,
     PEER_VERSION_TESTNET,
@@ -85,6 +87,49 @@ mod tests {
             "STRYYQQ9M8KAF4NS7WNZQYY59X93XEKR31JP64CP"
         );
     }
+
+    #[test]
+    fn should_load_replay_events_from_height() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            replay_events_from_height = 100
+            "#,
+        ));
+        assert_eq!(config.node.replay_events_from_height, Some(100));
+
+        let config = Config::from_config_file(ConfigFile::from_str("[node]"));
+        assert_eq!(config.node.replay_events_from_height, None);
+    }
+
+    #[test]
+    fn should_load_chainstate_snapshot() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            chainstate_snapshot = "/tmp/snapshot.bin"
+            chainstate_snapshot_block = "0101010101010101010101010101010101010101010101010101010101010101"
+            "#,
+        ));
+        assert_eq!(
+            config.node.chainstate_snapshot,
+            Some(("/tmp/snapshot.bin".to_string(), StacksBlockId([1u8; 32])))
+        );
+
+        let config = Config::from_config_file(ConfigFile::from_str("[node]"));
+        assert_eq!(config.node.chainstate_snapshot, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_reject_chainstate_snapshot_without_block() {
+        Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [node]
+            chainstate_snapshot = "/tmp/snapshot.bin"
+            "#,
+        ));
+    }
 }
 
 impl ConfigFile {
@@ -392,6 +437,7 @@ lazy_static! {
         soft_max_neighbors_per_org: 32, // soft limit on the number of neighbors per AS we permit (TODO: for now it must be greater than num_neighbors)
         soft_max_clients_per_host: 4,   // soft limit on how many inbound p2p connections per host we permit
         max_http_clients: 1000,         // maximum number of HTTP connections
+        max_subscribers: 100,           // maximum number of HTTP connections streaming events
         max_neighbors_of_neighbor: 10,  // maximum number of neighbors we'll handshake with when doing a neighbor walk (I/O for this can be expensive, so keep small-ish)
         walk_interval: 60,              // how often, in seconds, we do a neighbor walk
         inv_sync_interval: 45,          // how often, in seconds, we refresh block inventories
@@ -460,6 +506,46 @@ impl Config {
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
                     use_test_genesis_chainstate: node.use_test_genesis_chainstate,
+                    mempool_walk_order: match node.mempool_walk_order.as_deref() {
+                        Some("arrival") => MemPoolWalkOrder::Arrival,
+                        Some("fee_rate") => MemPoolWalkOrder::FeeRate,
+                        Some(order) => panic!(
+                            "Invalid mempool_walk_order '{}': expected 'arrival' or 'fee_rate'",
+                            order
+                        ),
+                        None => default_node_config.mempool_walk_order,
+                    },
+                    mempool_replace_by_fee_margin: node
+                        .mempool_replace_by_fee_margin
+                        .unwrap_or(default_node_config.mempool_replace_by_fee_margin),
+                    mempool_max_bytes: node
+                        .mempool_max_bytes
+                        .or(default_node_config.mempool_max_bytes),
+                    mempool_max_txs_per_origin: node
+                        .mempool_max_txs_per_origin
+                        .or(default_node_config.mempool_max_txs_per_origin),
+                    event_index: node.event_index.unwrap_or(default_node_config.event_index),
+                    marf_prune_depth: node
+                        .marf_prune_depth
+                        .or(default_node_config.marf_prune_depth),
+                    replay_events_from_height: node
+                        .replay_events_from_height
+                        .or(default_node_config.replay_events_from_height),
+                    chainstate_snapshot: match (
+                        node.chainstate_snapshot,
+                        node.chainstate_snapshot_block,
+                    ) {
+                        (Some(path), Some(block)) => Some((
+                            path,
+                            StacksBlockId::from_hex(&block).expect(
+                                "chainstate_snapshot_block should be a hex encoded index block hash",
+                            ),
+                        )),
+                        (None, None) => default_node_config.chainstate_snapshot,
+                        _ => panic!(
+                            "chainstate_snapshot and chainstate_snapshot_block must be set together"
+                        ),
+                    },
                 };
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
@@ -791,6 +877,9 @@ impl Config {
                     max_http_clients: opts.max_http_clients.unwrap_or_else(|| {
                         HELIUM_DEFAULT_CONNECTION_OPTIONS.max_http_clients.clone()
                     }),
+                    max_subscribers: opts.max_subscribers.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.max_subscribers.clone()
+                    }),
                     ..ConnectionOptions::default()
                 }
             }
@@ -863,6 +952,10 @@ impl Config {
         format!("{}/chainstate/atlas_db.sqlite", self.node.working_dir)
     }
 
+    pub fn get_event_observer_db_path(&self) -> String {
+        format!("{}/event_observers.sqlite", self.node.working_dir)
+    }
+
     pub fn add_initial_balance(&mut self, address: String, amount: u64) {
         let new_balance = InitialBalance {
             address: PrincipalData::parse_standard_principal(&address)
@@ -1046,6 +1139,15 @@ pub struct NodeConfig {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: u64,
     pub use_test_genesis_chainstate: Option<bool>,
+    pub mempool_walk_order: MemPoolWalkOrder,
+    pub mempool_replace_by_fee_margin: u64,
+    pub mempool_max_bytes: Option<u64>,
+    pub mempool_max_txs_per_origin: Option<u64>,
+    pub event_index: bool,
+    pub replay_events_from_height: Option<u64>,
+    pub marf_prune_depth: Option<u32>,
+    /// Snapshot to start a new chainstate from, and the index block hash it must be of
+    pub chainstate_snapshot: Option<(String, StacksBlockId)>,
 }
 
 impl NodeConfig {
@@ -1085,6 +1187,22 @@ impl NodeConfig {
             prometheus_bind: None,
             pox_sync_sample_secs: 30,
             use_test_genesis_chainstate: None,
+            mempool_walk_order: MemPoolWalkOrder::Arrival,
+            mempool_replace_by_fee_margin: 0,
+            mempool_max_bytes: None,
+            mempool_max_txs_per_origin: None,
+            event_index: false,
+            replay_events_from_height: None,
+            marf_prune_depth: None,
+            chainstate_snapshot: None,
+        }
+    }
+
+    pub fn get_mempool_settings(&self) -> MemPoolSettings {
+        MemPoolSettings {
+            replace_by_fee_margin: self.mempool_replace_by_fee_margin,
+            max_bytes: self.mempool_max_bytes,
+            max_txs_per_origin: self.mempool_max_txs_per_origin,
         }
     }
 
@@ -1183,6 +1301,7 @@ pub struct ConnectionOptionsFile {
     pub num_neighbors: Option<u64>,
     pub num_clients: Option<u64>,
     pub max_http_clients: Option<u64>,
+    pub max_subscribers: Option<u64>,
     pub soft_num_neighbors: Option<u64>,
     pub soft_num_clients: Option<u64>,
     pub max_neighbors_per_host: Option<u64>,
@@ -1237,6 +1356,15 @@ pub struct NodeConfigFile {
     pub prometheus_bind: Option<String>,
     pub pox_sync_sample_secs: Option<u64>,
     pub use_test_genesis_chainstate: Option<bool>,
+    pub mempool_walk_order: Option<String>,
+    pub mempool_replace_by_fee_margin: Option<u64>,
+    pub mempool_max_bytes: Option<u64>,
+    pub mempool_max_txs_per_origin: Option<u64>,
+    pub event_index: Option<bool>,
+    pub marf_prune_depth: Option<u32>,
+    pub replay_events_from_height: Option<u64>,
+    pub chainstate_snapshot: Option<String>,
+    pub chainstate_snapshot_block: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Default)]
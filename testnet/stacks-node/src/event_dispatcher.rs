@@ -2,7 +2,10 @@ use stacks::chainstate::coordinator::BlockEventDispatcher;
 use stacks::chainstate::stacks::db::StacksHeaderInfo;
 use stacks::chainstate::stacks::StacksBlock;
 use stacks::net::atlas::AttachmentInstance;
+use std::cmp;
 use std::collections::hash_map::Entry;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread;
 use std::thread::sleep;
 use std::time::Duration;
 use std::{
@@ -22,19 +25,25 @@ use stacks::chainstate::stacks::events::{
     TransactionOrigin,
 };
 use stacks::chainstate::stacks::{
-    db::accounts::MinerReward, db::MinerRewardInfo, StacksAddress, StacksBlockId, StacksTransaction,
+    db::accounts::MinerReward, db::MinerRewardInfo, StacksAddress, StacksBlockId, StacksMicroblock,
+    StacksTransaction,
 };
+use stacks::core::mempool::MemPoolDropReason;
+use stacks::net::subscriptions::{SubscriptionEvent, SubscriptionTopic};
 use stacks::net::StacksMessageCodec;
 use stacks::util::hash::bytes_to_hex;
 use stacks::vm::analysis::contract_interface_builder::build_contract_interface;
 use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
 
 use super::config::{EventKeyType, EventObserverConfig};
+use super::event_queue::{EventQueue, QueuedPayload};
 use super::node::ChainTip;
 
 #[derive(Debug, Clone)]
 struct EventObserver {
     endpoint: String,
+    queue: Arc<Mutex<EventQueue>>,
+    wakeup: SyncSender<()>,
 }
 
 const STATUS_RESP_TRUE: &str = "success";
@@ -42,24 +51,99 @@ const STATUS_RESP_NOT_COMMITTED: &str = "abort_by_response";
 const STATUS_RESP_POST_CONDITION: &str = "abort_by_post_condition";
 
 pub const PATH_MEMPOOL_TX_SUBMIT: &str = "new_mempool_tx";
+pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
 pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
 
+/// Header carrying a payload's sequence number in the observer's queue
+pub const HEADER_EVENT_SEQUENCE: &str = "X-Event-Sequence";
+
+/// How long to wait before retrying a failed delivery for the first time.  The wait doubles
+/// with each failure, up to the maximum.
+const DELIVERY_MIN_BACKOFF_MS: u64 = 1_000;
+const DELIVERY_MAX_BACKOFF_MS: u64 = 60_000;
+
 impl EventObserver {
-    fn send_payload(&self, payload: &serde_json::Value, path: &str) {
-        let body = match serde_json::to_vec(&payload) {
-            Ok(body) => body,
-            Err(err) => {
-                error!("Event dispatcher: serialization failed  - {:?}", err);
-                return;
+    /// Set up an observer whose payloads are queued in the database at `db_path`, and start the
+    /// thread that delivers them.
+    fn new(endpoint: &str, db_path: &str) -> EventObserver {
+        let queue = EventQueue::open(db_path, endpoint).expect(&format!(
+            "FATAL: failed to open event observer queue at {}",
+            db_path
+        ));
+        let delivery_queue = EventQueue::open(db_path, endpoint).expect(&format!(
+            "FATAL: failed to open event observer queue at {}",
+            db_path
+        ));
+
+        // only one wakeup needs to be outstanding at a time
+        let (wakeup, wakeup_rx) = sync_channel(1);
+        let delivery_endpoint = endpoint.to_string();
+        thread::Builder::new()
+            .name(format!("event-observer-{}", endpoint))
+            .spawn(move || {
+                EventObserver::deliver_payloads(&delivery_endpoint, delivery_queue, wakeup_rx)
+            })
+            .expect("FATAL: failed to start event observer delivery thread");
+
+        EventObserver {
+            endpoint: endpoint.to_string(),
+            queue: Arc::new(Mutex::new(queue)),
+            wakeup,
+        }
+    }
+
+    /// POST queued payloads to the observer in order, retrying each one until the observer accepts
+    /// it.  Runs until the event dispatcher goes away.
+    fn deliver_payloads(endpoint: &str, mut queue: EventQueue, wakeup: Receiver<()>) {
+        let mut backoff = DELIVERY_MIN_BACKOFF_MS;
+        loop {
+            let queued = match queue.next_pending() {
+                Ok(Some(queued)) => queued,
+                Ok(None) => match wakeup.recv() {
+                    Ok(_) => {
+                        continue;
+                    }
+                    Err(_) => {
+                        debug!("Event dispatcher: stop delivering to {}", endpoint);
+                        return;
+                    }
+                },
+                Err(e) => {
+                    error!(
+                        "Event dispatcher: failed to read queue for {} - {:?}",
+                        endpoint, e
+                    );
+                    sleep(Duration::from_millis(backoff));
+                    backoff = cmp::min(backoff * 2, DELIVERY_MAX_BACKOFF_MS);
+                    continue;
+                }
+            };
+
+            if !EventObserver::post_payload(endpoint, &queued) {
+                sleep(Duration::from_millis(backoff));
+                backoff = cmp::min(backoff * 2, DELIVERY_MAX_BACKOFF_MS);
+                continue;
             }
-        };
+            backoff = DELIVERY_MIN_BACKOFF_MS;
+
+            if let Err(e) = queue.mark_delivered(queued.seq) {
+                // it will be delivered again
+                error!(
+                    "Event dispatcher: failed to record delivery of {} to {} - {:?}",
+                    queued.seq, endpoint, e
+                );
+            }
+        }
+    }
 
+    /// Try once to POST a queued payload to the observer.  Returns true if it was accepted.
+    fn post_payload(endpoint: &str, queued: &QueuedPayload) -> bool {
         let url = {
-            let joined_components = match path.starts_with("/") {
-                true => format!("{}{}", &self.endpoint, path),
-                false => format!("{}/{}", &self.endpoint, path),
+            let joined_components = match queued.path.starts_with("/") {
+                true => format!("{}{}", endpoint, &queued.path),
+                false => format!("{}/{}", endpoint, &queued.path),
             };
             let url = format!("http://{}", joined_components);
             Url::parse(&url).expect(&format!(
@@ -68,45 +152,102 @@ impl EventObserver {
             ))
         };
 
-        let backoff = Duration::from_millis((1.0 * 1_000.0) as u64);
-
-        loop {
-            let body = body.clone();
-            let mut req = Request::new(Method::Post, url.clone());
-            req.append_header("Content-Type", "application/json")
-                .expect("Unable to set header");
-            req.set_body(body);
-
-            let response = async_std::task::block_on(async {
-                let stream = match TcpStream::connect(self.endpoint.clone()).await {
-                    Ok(stream) => stream,
-                    Err(err) => {
-                        println!("Event dispatcher: connection failed  - {:?}", err);
-                        return None;
-                    }
-                };
+        let mut req = Request::new(Method::Post, url);
+        req.append_header("Content-Type", "application/json")
+            .expect("Unable to set header");
+        req.append_header(HEADER_EVENT_SEQUENCE, queued.seq.to_string())
+            .expect("Unable to set header");
+        req.set_body(queued.payload.clone());
+
+        let response = async_std::task::block_on(async {
+            let stream = match TcpStream::connect(endpoint.to_string()).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    println!("Event dispatcher: connection failed  - {:?}", err);
+                    return None;
+                }
+            };
 
-                match client::connect(stream, req).await {
-                    Ok(response) => Some(response),
-                    Err(err) => {
-                        println!("Event dispatcher: rpc invokation failed  - {:?}", err);
-                        return None;
-                    }
+            match client::connect(stream, req).await {
+                Ok(response) => Some(response),
+                Err(err) => {
+                    println!("Event dispatcher: rpc invokation failed  - {:?}", err);
+                    return None;
                 }
-            });
+            }
+        });
 
-            if let Some(response) = response {
+        match response {
+            Some(response) => {
                 if response.status().is_success() {
-                    break;
+                    true
                 } else {
                     error!(
                         "Event dispatcher: POST {} failed with error {:?}",
-                        self.endpoint, response
+                        endpoint, response
                     );
+                    false
                 }
             }
-            sleep(backoff);
+            None => false,
+        }
+    }
+
+    /// Queue up a payload for delivery.  Payloads about a Stacks block carry its height, so they
+    /// can be replayed.
+    fn enqueue_payload(&self, payload: &serde_json::Value, path: &str, block_height: Option<u64>) {
+        let res = self
+            .queue
+            .lock()
+            .expect("FATAL: event observer queue lock poisoned")
+            .push(path, payload, block_height);
+        match res {
+            Ok(seq) => {
+                debug!(
+                    "Event dispatcher: queued payload {} for {}/{}",
+                    seq, &self.endpoint, path
+                );
+            }
+            Err(e) => {
+                error!(
+                    "Event dispatcher: failed to queue payload for {}/{} - {:?}",
+                    &self.endpoint, path, e
+                );
+                return;
+            }
+        }
+
+        // the delivery thread is already awake if the channel is full
+        let _ = self.wakeup.try_send(());
+    }
+
+    fn send_payload(&self, payload: &serde_json::Value, path: &str) {
+        self.enqueue_payload(payload, path, None)
+    }
+
+    /// Deliver the payloads about Stacks blocks at or above `block_height` again
+    fn replay_from_height(&self, block_height: u64) {
+        let res = self
+            .queue
+            .lock()
+            .expect("FATAL: event observer queue lock poisoned")
+            .replay_from_height(block_height);
+        match res {
+            Ok(num_replayed) => {
+                info!(
+                    "Event dispatcher: replaying {} payloads to {} from block height {}",
+                    num_replayed, &self.endpoint, block_height
+                );
+            }
+            Err(e) => {
+                error!(
+                    "Event dispatcher: failed to replay payloads to {} - {:?}",
+                    &self.endpoint, e
+                );
+                return;
+            }
         }
+        let _ = self.wakeup.try_send(());
     }
 
     fn make_new_mempool_txs_payload(transactions: Vec<StacksTransaction>) -> serde_json::Value {
@@ -120,6 +261,21 @@ impl EventObserver {
         serde_json::Value::Array(raw_txs)
     }
 
+    fn make_dropped_mempool_txs_payload(
+        txids: Vec<Txid>,
+        reason: MemPoolDropReason,
+    ) -> serde_json::Value {
+        let dropped_txids: Vec<_> = txids
+            .into_iter()
+            .map(|txid| serde_json::Value::String(format!("0x{}", &txid)))
+            .collect();
+
+        json!({
+            "dropped_txids": serde_json::Value::Array(dropped_txids),
+            "reason": reason.to_string(),
+        })
+    }
+
     fn make_new_burn_block_payload(
         burn_block: &BurnchainHeaderHash,
         burn_block_height: u64,
@@ -204,27 +360,55 @@ impl EventObserver {
         self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
     }
 
+    fn send_dropped_mempool_txs(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MEMPOOL_TX_DROP);
+    }
+
     fn send_new_burn_block(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
-    fn send(
-        &self,
-        filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+    fn make_new_microblocks_payload(
+        parent_index_block_hash: &StacksBlockId,
+        microblocks: &Vec<StacksMicroblock>,
+    ) -> serde_json::Value {
+        let serialized_microblocks: Vec<_> = microblocks
+            .iter()
+            .map(|microblock| {
+                let serialized_txs: Vec<_> = microblock
+                    .txs
+                    .iter()
+                    .map(|tx| {
+                        json!({
+                            "txid": format!("0x{}", tx.txid()),
+                            "raw_tx": format!("0x{}", bytes_to_hex(&tx.serialize_to_vec())),
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "microblock_hash": format!("0x{}", microblock.block_hash()),
+                    "sequence": microblock.header.sequence,
+                    "parent_microblock_hash": format!("0x{}", microblock.header.prev_block),
+                    "transactions": serialized_txs,
+                })
+            })
+            .collect();
+
+        json!({
+            "parent_index_block_hash": format!("0x{}", parent_index_block_hash),
+            "microblocks": serialized_microblocks,
+        })
+    }
+
+    fn make_new_block_payload(
+        serialized_events: Vec<serde_json::Value>,
         chain_tip: &ChainTip,
         parent_index_hash: &StacksBlockId,
         boot_receipts: &Vec<StacksTransactionReceipt>,
         winner_txid: &Txid,
         mature_rewards: &serde_json::Value,
-    ) {
-        // Serialize events to JSON
-        let serialized_events: Vec<serde_json::Value> = filtered_events
-            .iter()
-            .map(|(event_index, (committed, txid, event))| {
-                event.json_serialize(*event_index, txid, *committed)
-            })
-            .collect();
-
+    ) -> serde_json::Value {
         let mut tx_index: u32 = 0;
         let mut serialized_txs = vec![];
 
@@ -235,7 +419,7 @@ impl EventObserver {
         }
 
         // Wrap events
-        let payload = json!({
+        json!({
             "block_hash": format!("0x{}", chain_tip.block.block_hash()),
             "block_height": chain_tip.metadata.block_height,
             "burn_block_hash": format!("0x{}", chain_tip.metadata.burn_header_hash),
@@ -249,10 +433,41 @@ impl EventObserver {
             "matured_miner_rewards": mature_rewards.clone(),
             "events": serialized_events,
             "transactions": serialized_txs,
-        });
+        })
+    }
+
+    fn send(
+        &self,
+        filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+        chain_tip: &ChainTip,
+        parent_index_hash: &StacksBlockId,
+        boot_receipts: &Vec<StacksTransactionReceipt>,
+        winner_txid: &Txid,
+        mature_rewards: &serde_json::Value,
+    ) {
+        // Serialize events to JSON
+        let serialized_events: Vec<serde_json::Value> = filtered_events
+            .iter()
+            .map(|(event_index, (committed, txid, event))| {
+                event.json_serialize(*event_index, txid, *committed)
+            })
+            .collect();
+
+        let payload = EventObserver::make_new_block_payload(
+            serialized_events,
+            chain_tip,
+            parent_index_hash,
+            boot_receipts,
+            winner_txid,
+            mature_rewards,
+        );
 
         // Send payload
-        self.send_payload(&payload, PATH_BLOCK_PROCESSED);
+        self.enqueue_payload(
+            &payload,
+            PATH_BLOCK_PROCESSED,
+            Some(chain_tip.metadata.block_height),
+        );
     }
 }
 
@@ -266,6 +481,7 @@ pub struct EventDispatcher {
     stx_observers_lookup: HashSet<u16>,
     any_event_observers_lookup: HashSet<u16>,
     boot_receipts: Arc<Mutex<Option<Vec<StacksTransactionReceipt>>>>,
+    subscriptions: Option<SyncSender<SubscriptionEvent>>,
 }
 
 impl BlockEventDispatcher for EventDispatcher {
@@ -319,6 +535,26 @@ impl EventDispatcher {
             burn_block_observers_lookup: HashSet::new(),
             mempool_observers_lookup: HashSet::new(),
             boot_receipts: Arc::new(Mutex::new(None)),
+            subscriptions: None,
+        }
+    }
+
+    /// Publish events to the RPC server over this channel, to be streamed to subscribed clients
+    pub fn set_subscriptions(&mut self, subscriptions: SyncSender<SubscriptionEvent>) {
+        self.subscriptions = Some(subscriptions);
+    }
+
+    fn publish(&self, event: SubscriptionEvent) {
+        if let Some(ref subscriptions) = self.subscriptions {
+            match subscriptions.try_send(event) {
+                Ok(_) => {}
+                Err(TrySendError::Full(_)) => {
+                    warn!("Event dispatcher: subscription channel is full; dropping event");
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    debug!("Event dispatcher: subscription channel disconnected");
+                }
+            }
         }
     }
 
@@ -459,7 +695,7 @@ impl EventDispatcher {
             }
         }
 
-        if dispatch_matrix.len() > 0 {
+        if dispatch_matrix.len() > 0 || self.subscriptions.is_some() {
             let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
                 mature_rewards
                     .iter()
@@ -497,10 +733,82 @@ impl EventDispatcher {
                     &mature_rewards,
                 );
             }
+
+            if self.subscriptions.is_some() {
+                // subscribers get every event, and filter them on their own terms
+                let serialized_events: Vec<serde_json::Value> = events
+                    .iter()
+                    .enumerate()
+                    .map(|(event_index, (committed, txid, event))| {
+                        event.json_serialize(event_index, txid, *committed)
+                    })
+                    .collect();
+
+                let mut block_event = SubscriptionEvent::new(
+                    SubscriptionTopic::Blocks,
+                    EventObserver::make_new_block_payload(
+                        serialized_events.clone(),
+                        chain_tip,
+                        parent_index_hash,
+                        &boot_receipts,
+                        &winner_txid,
+                        &mature_rewards,
+                    ),
+                );
+                for receipt in chain_tip.receipts.iter().chain(boot_receipts.iter()) {
+                    if let TransactionOrigin::Stacks(ref tx) = receipt.transaction {
+                        block_event.add_transaction_keys(tx);
+                    }
+                }
+                for (_, _, event) in events.iter() {
+                    block_event.add_event_keys(event);
+                }
+                self.publish(block_event);
+
+                for ((_, _, event), payload) in events.iter().zip(serialized_events.into_iter()) {
+                    let mut tx_event = SubscriptionEvent::new(SubscriptionTopic::Events, payload);
+                    tx_event.add_event_keys(event);
+                    self.publish(tx_event);
+                }
+            }
+        }
+    }
+
+    pub fn process_new_microblocks(
+        &self,
+        parent_index_block_hash: &StacksBlockId,
+        microblocks: &Vec<StacksMicroblock>,
+    ) {
+        // microblocks are only streamed to subscribers
+        if self.subscriptions.is_none() {
+            return;
+        }
+
+        let mut microblocks_event = SubscriptionEvent::new(
+            SubscriptionTopic::Microblocks,
+            EventObserver::make_new_microblocks_payload(parent_index_block_hash, microblocks),
+        );
+        for microblock in microblocks.iter() {
+            for tx in microblock.txs.iter() {
+                microblocks_event.add_transaction_keys(tx);
+            }
         }
+        self.publish(microblocks_event);
     }
 
     pub fn process_new_mempool_txs(&self, txs: Vec<StacksTransaction>) {
+        // subscribers get one event per transaction, so they can filter them
+        if self.subscriptions.is_some() {
+            for tx in txs.iter() {
+                let mut tx_event = SubscriptionEvent::new(
+                    SubscriptionTopic::Mempool,
+                    EventObserver::make_new_mempool_txs_payload(vec![tx.clone()]),
+                );
+                tx_event.add_transaction_keys(tx);
+                self.publish(tx_event);
+            }
+        }
+
         // lazily assemble payload only if we have observers
         let interested_observers: Vec<_> = self
             .registered_observers
@@ -522,6 +830,38 @@ impl EventDispatcher {
         }
     }
 
+    pub fn process_dropped_mempool_txs(&self, dropped: Vec<(Txid, MemPoolDropReason)>) {
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.mempool_observers_lookup.contains(&(*obs_id as u16))
+                    || self.any_event_observers_lookup.contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        // one payload per drop reason
+        let mut dropped_by_reason: Vec<(MemPoolDropReason, Vec<Txid>)> = vec![];
+        for (txid, reason) in dropped.into_iter() {
+            match dropped_by_reason.iter_mut().find(|(r, _)| *r == reason) {
+                Some((_, txids)) => txids.push(txid),
+                None => dropped_by_reason.push((reason, vec![txid])),
+            }
+        }
+
+        for (reason, txids) in dropped_by_reason.into_iter() {
+            let payload = EventObserver::make_dropped_mempool_txs_payload(txids, reason);
+            for (_, observer) in interested_observers.iter() {
+                observer.send_dropped_mempool_txs(&payload);
+            }
+        }
+    }
+
     pub fn process_new_attachments(&self, attachments: &Vec<AttachmentInstance>) {
         let interested_observers: Vec<_> = self.registered_observers.iter().enumerate().collect();
         if interested_observers.len() < 1 {
@@ -539,6 +879,14 @@ impl EventDispatcher {
         }
     }
 
+    /// Deliver the `/new_block` payloads for Stacks blocks at or above `block_height` to every
+    /// observer again, as long as they're still queued.
+    pub fn replay_from_height(&self, block_height: u64) {
+        for observer in self.registered_observers.iter() {
+            observer.replay_from_height(block_height);
+        }
+    }
+
     pub fn process_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {
         self.boot_receipts = Arc::new(Mutex::new(Some(receipts)));
     }
@@ -556,12 +904,11 @@ impl EventDispatcher {
         }
     }
 
-    pub fn register_observer(&mut self, conf: &EventObserverConfig) {
-        // let event_observer = EventObserver::new(&conf.address, conf.port);
+    /// Register an event observer.  Its payloads are queued in the database at `db_path` until
+    /// it accepts them.
+    pub fn register_observer(&mut self, conf: &EventObserverConfig, db_path: &str) {
         info!("Registering event observer at: {}", conf.endpoint);
-        let event_observer = EventObserver {
-            endpoint: conf.endpoint.clone(),
-        };
+        let event_observer = EventObserver::new(&conf.endpoint, db_path);
 
         let observer_index = self.registered_observers.len() as u16;
 
@@ -0,0 +1,245 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, Row, NO_PARAMS};
+
+use stacks::util::db::Error as db_error;
+use stacks::util::db::{
+    query_row, sql_pragma, tx_begin_immediate, tx_busy_handler, u64_to_sql, FromColumn, FromRow,
+};
+
+/// How many Stacks blocks back the payloads delivered to an observer are kept, so that they can be
+/// replayed on startup.
+pub const EVENT_QUEUE_REPLAY_DEPTH: u64 = 1000;
+
+const EVENT_QUEUE_SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS observer_payloads(
+        endpoint TEXT NOT NULL,
+        seq INTEGER NOT NULL,
+        path TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        block_height INTEGER,
+        delivered INTEGER NOT NULL,
+        PRIMARY KEY(endpoint, seq)
+    );"#,
+    "CREATE INDEX IF NOT EXISTS observer_payloads_by_delivered ON observer_payloads(endpoint, delivered, seq);",
+    "CREATE INDEX IF NOT EXISTS observer_payloads_by_height ON observer_payloads(endpoint, block_height);",
+];
+
+/// A payload waiting to be POSTed to an event observer
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedPayload {
+    /// The payload's position in the observer's queue.  Sequence numbers start at 1 and
+    /// increase by 1 with each payload, so observers can tell when they've missed one.
+    pub seq: u64,
+    pub path: String,
+    pub payload: String,
+}
+
+impl FromRow<QueuedPayload> for QueuedPayload {
+    fn from_row<'a>(row: &'a Row) -> Result<QueuedPayload, db_error> {
+        let seq = u64::from_column(row, "seq")?;
+        let path: String = row.get_unwrap("path");
+        let payload: String = row.get_unwrap("payload");
+        Ok(QueuedPayload { seq, path, payload })
+    }
+}
+
+/// An event observer's outbound queue, stored on disk so that payloads survive a node restart.
+/// Every observer's queue lives in the same database, keyed by its endpoint.
+pub struct EventQueue {
+    conn: Connection,
+    endpoint: String,
+}
+
+impl fmt::Debug for EventQueue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EventQueue({})", &self.endpoint)
+    }
+}
+
+impl EventQueue {
+    /// Open the queue for the observer at `endpoint`, creating the database at `path` if need be.
+    pub fn open(path: &str, endpoint: &str) -> Result<EventQueue, db_error> {
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir).map_err(db_error::IOError)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.busy_handler(Some(tx_busy_handler))?;
+        sql_pragma(&conn, "PRAGMA journal_mode = WAL;")?;
+
+        let mut queue = EventQueue {
+            conn,
+            endpoint: endpoint.to_string(),
+        };
+        let tx = tx_begin_immediate(&mut queue.conn)?;
+        for row_text in EVENT_QUEUE_SCHEMA {
+            tx.execute(row_text, NO_PARAMS)?;
+        }
+        tx.commit()?;
+        Ok(queue)
+    }
+
+    /// Queue up a payload to POST to `path`, and return its sequence number.  Payloads about a
+    /// Stacks block carry its height, so they can be replayed later.
+    pub fn push(
+        &mut self,
+        path: &str,
+        payload: &serde_json::Value,
+        block_height: Option<u64>,
+    ) -> Result<u64, db_error> {
+        let payload = serde_json::to_string(payload).map_err(db_error::SerializationError)?;
+        let block_height = match block_height {
+            Some(height) => Some(u64_to_sql(height)?),
+            None => None,
+        };
+
+        let tx = tx_begin_immediate(&mut self.conn)?;
+        let last_seq: i64 = tx.query_row(
+            "SELECT IFNULL(MAX(seq), 0) FROM observer_payloads WHERE endpoint = ?1",
+            &[&self.endpoint],
+            |row| row.get(0),
+        )?;
+        let seq = last_seq + 1;
+
+        let args: &[&dyn ToSql] = &[&self.endpoint, &seq, &path, &payload, &block_height];
+        tx.execute(
+            "INSERT INTO observer_payloads (endpoint, seq, path, payload, block_height, delivered) VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+            args,
+        )?;
+
+        if let Some(height) = block_height {
+            // forget delivered payloads that are too old to replay
+            let args: &[&dyn ToSql] = &[
+                &self.endpoint,
+                &height.saturating_sub(EVENT_QUEUE_REPLAY_DEPTH as i64),
+            ];
+            tx.execute(
+                "DELETE FROM observer_payloads WHERE endpoint = ?1 AND delivered = 1 AND seq < \
+                 (SELECT IFNULL(MIN(seq), 0) FROM observer_payloads WHERE endpoint = ?1 AND block_height >= ?2)",
+                args,
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(seq as u64)
+    }
+
+    /// Get the oldest payload that hasn't been delivered yet
+    pub fn next_pending(&self) -> Result<Option<QueuedPayload>, db_error> {
+        query_row(
+            &self.conn,
+            "SELECT seq, path, payload FROM observer_payloads WHERE endpoint = ?1 AND delivered = 0 ORDER BY seq ASC LIMIT 1",
+            &[&self.endpoint],
+        )
+    }
+
+    /// Record that the observer accepted the payload with the given sequence number
+    pub fn mark_delivered(&mut self, seq: u64) -> Result<(), db_error> {
+        let args: &[&dyn ToSql] = &[&self.endpoint, &u64_to_sql(seq)?];
+        self.conn.execute(
+            "UPDATE observer_payloads SET delivered = 1 WHERE endpoint = ?1 AND seq = ?2",
+            args,
+        )?;
+        Ok(())
+    }
+
+    /// Deliver the payloads about Stacks blocks at or above `block_height` again, in their
+    /// original order and with their original sequence numbers.  Returns how many there are.
+    pub fn replay_from_height(&mut self, block_height: u64) -> Result<u64, db_error> {
+        let args: &[&dyn ToSql] = &[&self.endpoint, &u64_to_sql(block_height)?];
+        let num_replayed = self.conn.execute(
+            "UPDATE observer_payloads SET delivered = 0 WHERE endpoint = ?1 AND block_height >= ?2",
+            args,
+        )?;
+        Ok(num_replayed as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::env;
+
+    use rand::{thread_rng, Rng};
+    use serde_json::json;
+
+    #[test]
+    fn event_queue_delivery_and_replay() {
+        let dir = env::temp_dir().join(format!(
+            "event_queue_delivery_and_replay-{}",
+            thread_rng().gen::<u64>()
+        ));
+        let path_buf = dir.join("event_observers.sqlite");
+        let path = path_buf.to_str().unwrap();
+
+        let mut queue = EventQueue::open(path, "localhost:3700").unwrap();
+        let mut other_queue = EventQueue::open(path, "localhost:3701").unwrap();
+        assert_eq!(queue.next_pending().unwrap(), None);
+
+        assert_eq!(
+            queue
+                .push("new_block", &json!({ "block_height": 1 }), Some(1))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            queue
+                .push("new_mempool_tx", &json!(["0x00"]), None)
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            queue
+                .push("new_block", &json!({ "block_height": 2 }), Some(2))
+                .unwrap(),
+            3
+        );
+        // each observer has its own sequence numbers
+        assert_eq!(
+            other_queue
+                .push("new_block", &json!({ "block_height": 1 }), Some(1))
+                .unwrap(),
+            1
+        );
+
+        let pending = queue.next_pending().unwrap().unwrap();
+        assert_eq!(pending.seq, 1);
+        assert_eq!(pending.path, "new_block");
+        assert_eq!(pending.payload, "{\"block_height\":1}");
+
+        // payloads are only dequeued once delivered
+        assert_eq!(queue.next_pending().unwrap().unwrap().seq, 1);
+        for seq in 1..4 {
+            queue.mark_delivered(seq).unwrap();
+        }
+        assert_eq!(queue.next_pending().unwrap(), None);
+
+        // and survive reopening the queue
+        let mut queue = EventQueue::open(path, "localhost:3700").unwrap();
+        assert_eq!(queue.next_pending().unwrap(), None);
+        assert_eq!(queue.replay_from_height(2).unwrap(), 1);
+        assert_eq!(queue.next_pending().unwrap().unwrap().seq, 3);
+        queue.mark_delivered(3).unwrap();
+
+        // delivered payloads too old to replay are forgotten
+        queue
+            .push(
+                "new_block",
+                &json!({ "block_height": 1 + EVENT_QUEUE_REPLAY_DEPTH + 1 }),
+                Some(1 + EVENT_QUEUE_REPLAY_DEPTH + 1),
+            )
+            .unwrap();
+        assert_eq!(queue.replay_from_height(0).unwrap(), 2);
+        assert_eq!(queue.next_pending().unwrap().unwrap().seq, 3);
+        assert_eq!(other_queue.next_pending().unwrap().unwrap().seq, 1);
+
+        drop(queue);
+        drop(other_queue);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -22,6 +22,7 @@ pub mod monitoring;
 pub mod burnchains;
 pub mod config;
 pub mod event_dispatcher;
+pub mod event_queue;
 pub mod genesis_data;
 pub mod keychain;
 pub mod neon_node;
@@ -90,6 +91,10 @@ fn main() {
         );
     }
 
+    let replay_events_from_height: Option<u64> = args
+        .opt_value_from_str("--replay-events-from-height")
+        .expect("Failed to parse --replay-events-from-height argument");
+
     let config_file = match subcommand.as_str() {
         "mocknet" => {
             args.finish().unwrap();
@@ -135,7 +140,10 @@ fn main() {
         }
     };
 
-    let conf = Config::from_config_file(config_file);
+    let mut conf = Config::from_config_file(config_file);
+    if replay_events_from_height.is_some() {
+        conf.node.replay_events_from_height = replay_events_from_height;
+    }
     debug!("node configuration {:?}", &conf.node);
     debug!("burnchain configuration {:?}", &conf.burnchain);
     debug!("connection configuration {:?}", &conf.connection_options);
@@ -212,6 +220,8 @@ OPTIONAL ARGUMENTS:
 
 \t\t--mine-at-height=<height>: optional argument for a miner to not attempt mining until Stacks block has sync'ed to <height>
 
+\t\t--replay-events-from-height=<height>: optional argument to send event observers the blocks they were already sent from Stacks block <height> on, as long as they are still queued
+
 ", argv[0]);
 }
 
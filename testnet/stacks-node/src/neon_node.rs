@@ -38,6 +38,7 @@ use stacks::net::{
     p2p::PeerNetwork,
     relay::Relayer,
     rpc::RPCHandlerArgs,
+    subscriptions::SubscriptionEvent,
     Error as NetError, NetworkResult, PeerAddress, StacksMessageCodec,
 };
 use stacks::util::get_epoch_time_ms;
@@ -442,6 +443,7 @@ fn run_microblock_tenure(
     mem_pool: &MemPoolDB,
     relayer: &mut Relayer,
     miner_tip: Option<&(ConsensusHash, BlockHeaderHash, Secp256k1PrivateKey)>,
+    event_dispatcher: &EventDispatcher,
 ) {
     // TODO: this is sensitive to poll latency -- can we call this on a fixed
     // schedule, regardless of network activity?
@@ -472,6 +474,11 @@ fn run_microblock_tenure(
             // apply it
             Relayer::refresh_unconfirmed(chainstate, sortdb);
 
+            event_dispatcher.process_new_microblocks(
+                &StacksBlockHeader::make_index_block_hash(parent_consensus_hash, parent_block_hash),
+                &vec![next_microblock.clone()],
+            );
+
             // send it off
             let microblock_hash = next_microblock.block_hash();
             if let Err(e) = relayer.broadcast_microblock(
@@ -540,6 +547,7 @@ fn spawn_peer(
     relay_channel: SyncSender<RelayerDirective>,
     mut sync_comms: PoxSyncWatchdogComms,
     attachments_rx: Receiver<HashSet<AttachmentInstance>>,
+    subscriptions_rx: Receiver<SubscriptionEvent>,
     unconfirmed_txs: Arc<Mutex<UnconfirmedTxMap>>,
 ) -> Result<JoinHandle<()>, NetError> {
     let burn_db_path = config.get_burn_db_file_path();
@@ -548,6 +556,7 @@ fn spawn_peer(
     let exit_at_block_height = config.burnchain.process_exit_at_block_height;
 
     this.bind(p2p_sock, rpc_sock).unwrap();
+    this.http.set_subscriptions(subscriptions_rx);
     let (mut dns_resolver, mut dns_client) = DNSResolver::new(10);
     let sortdb = SortitionDB::open(&burn_db_path, false).map_err(NetError::DBError)?;
 
@@ -558,6 +567,8 @@ fn spawn_peer(
         block_limit,
     )
     .map_err(|e| NetError::ChainstateError(e.to_string()))?;
+    chainstate.event_index = config.node.event_index;
+    chainstate.marf_prune_depth = config.node.marf_prune_depth;
 
     let mut mem_pool = MemPoolDB::open(
         is_mainnet,
@@ -565,6 +576,7 @@ fn spawn_peer(
         &stacks_chainstate_path,
     )
     .map_err(NetError::DBError)?;
+    mem_pool.set_settings(config.node.get_mempool_settings());
 
     // buffer up blocks to store without stalling the p2p thread
     let mut results_with_data = VecDeque::new();
@@ -730,6 +742,7 @@ fn spawn_miner_relayer(
 
     let mut mem_pool = MemPoolDB::open(is_mainnet, chain_id, &stacks_chainstate_path)
         .map_err(NetError::DBError)?;
+    mem_pool.set_settings(config.node.get_mempool_settings());
 
     let mut last_mined_blocks: HashMap<
         BurnchainHeaderHash,
@@ -763,6 +776,18 @@ fn spawn_miner_relayer(
                         event_dispatcher.process_new_mempool_txs(net_receipts.mempool_txs_added);
                     }
 
+                    if net_receipts.mempool_txs_dropped.len() > 0 {
+                        event_dispatcher
+                            .process_dropped_mempool_txs(net_receipts.mempool_txs_dropped);
+                    }
+
+                    for mblocks in net_receipts.microblocks_added.iter() {
+                        event_dispatcher.process_new_microblocks(
+                            &mblocks.index_anchor_block,
+                            &mblocks.microblocks,
+                        );
+                    }
+
                     // Dispatch retrieved attachments, if any.
                     if net_result.has_attachments() {
                         event_dispatcher.process_new_attachments(&net_result.attachments);
@@ -943,6 +968,7 @@ fn spawn_miner_relayer(
                         &mem_pool,
                         &mut relayer,
                         miner_tip.as_ref(),
+                        &event_dispatcher,
                     );
 
                     // synchronize unconfirmed tx index to p2p thread
@@ -974,6 +1000,7 @@ impl InitializedNeonNode {
         sync_comms: PoxSyncWatchdogComms,
         burnchain: Burnchain,
         attachments_rx: Receiver<HashSet<AttachmentInstance>>,
+        subscriptions_rx: Receiver<SubscriptionEvent>,
         atlas_config: AtlasConfig,
     ) -> InitializedNeonNode {
         // we can call _open_ here rather than _connect_, since connect is first called in
@@ -1141,6 +1168,7 @@ impl InitializedNeonNode {
             relay_send.clone(),
             sync_comms,
             attachments_rx,
+            subscriptions_rx,
             shared_unconfirmed_txs,
         )
         .expect("Failed to initialize mine/relay thread");
@@ -1577,7 +1605,7 @@ impl InitializedNeonNode {
             }
         }
 
-        let (anchored_block, _, _) = match StacksBlockBuilder::build_anchored_block(
+        let (anchored_block, _, _) = match StacksBlockBuilder::build_anchored_block_with_order(
             chain_state,
             &burn_db.index_conn(),
             mem_pool,
@@ -1587,6 +1615,7 @@ impl InitializedNeonNode {
             mblock_pubkey_hash,
             &coinbase_tx,
             config.block_limit.clone(),
+            config.node.mempool_walk_order,
         ) {
             Ok(block) => block,
             Err(e) => {
@@ -1813,6 +1842,7 @@ impl NeonGenesisNode {
         coord_comms: CoordinatorChannels,
         sync_comms: PoxSyncWatchdogComms,
         attachments_rx: Receiver<HashSet<AttachmentInstance>>,
+        subscriptions_rx: Receiver<SubscriptionEvent>,
         atlas_config: AtlasConfig,
     ) -> InitializedNeonNode {
         let config = self.config;
@@ -1830,6 +1860,7 @@ impl NeonGenesisNode {
             sync_comms,
             self.burnchain,
             attachments_rx,
+            subscriptions_rx,
             atlas_config,
         )
     }
@@ -1841,6 +1872,7 @@ impl NeonGenesisNode {
         coord_comms: CoordinatorChannels,
         sync_comms: PoxSyncWatchdogComms,
         attachments_rx: Receiver<HashSet<AttachmentInstance>>,
+        subscriptions_rx: Receiver<SubscriptionEvent>,
         atlas_config: AtlasConfig,
     ) -> InitializedNeonNode {
         let config = self.config;
@@ -1858,6 +1890,7 @@ impl NeonGenesisNode {
             sync_comms,
             self.burnchain,
             attachments_rx,
+            subscriptions_rx,
             atlas_config,
         )
     }
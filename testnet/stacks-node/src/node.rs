@@ -22,7 +22,7 @@ use stacks::chainstate::stacks::{
     StacksTransaction, StacksTransactionSigner, TransactionAnchorMode, TransactionPayload,
     TransactionVersion,
 };
-use stacks::core::mempool::MemPoolDB;
+use stacks::core::mempool::{MemPoolDB, MemPoolSettings};
 use stacks::net::{
     atlas::{AtlasConfig, AtlasDB},
     db::PeerDB,
@@ -155,6 +155,7 @@ fn spawn_peer(
     exit_at_block_height: Option<u64>,
     genesis_chainstate_hash: Sha256Sum,
     poll_timeout: u64,
+    mempool_settings: MemPoolSettings,
 ) -> Result<JoinHandle<()>, NetError> {
     this.bind(p2p_sock, rpc_sock).unwrap();
     let server_thread = thread::spawn(move || {
@@ -192,6 +193,7 @@ fn spawn_peer(
                     continue;
                 }
             };
+            mem_pool.set_settings(mempool_settings.clone());
             let mut attachments = HashSet::new();
             let net_result = this
                 .run(
@@ -208,6 +210,9 @@ fn spawn_peer(
             if net_result.has_transactions() {
                 event_dispatcher.process_new_mempool_txs(net_result.transactions())
             }
+            if net_result.dropped_transactions.len() > 0 {
+                event_dispatcher.process_dropped_mempool_txs(net_result.dropped_transactions)
+            }
         }
     });
     Ok(server_thread)
@@ -286,7 +291,7 @@ impl Node {
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher.register_observer(observer, &config.get_event_observer_db_path());
         }
 
         event_dispatcher.process_boot_receipts(receipts);
@@ -316,7 +321,7 @@ impl Node {
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher.register_observer(observer, &config.get_event_observer_db_path());
         }
 
         let chainstate_path = config.get_chainstate_path();
@@ -478,6 +483,7 @@ impl Node {
             exit_at_block_height,
             Sha256Sum::from_hex(stx_genesis::GENESIS_CHAINSTATE_HASH).unwrap(),
             1000,
+            self.config.node.get_mempool_settings(),
         )
         .unwrap();
 
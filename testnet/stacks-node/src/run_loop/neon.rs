@@ -14,9 +14,14 @@ use stacks::chainstate::coordinator::{
 };
 use stacks::chainstate::stacks::boot;
 use stacks::chainstate::stacks::db::{ChainStateBootData, ClarityTx, StacksChainState};
+use stacks::chainstate::stacks::Error as ChainstateError;
 use stacks::net::atlas::{AtlasConfig, Attachment};
+use stacks::net::subscriptions::SUBSCRIPTION_CHANNEL_CAPACITY;
+use stacks::util::db::Error as db_error;
 use stacks::vm::types::{PrincipalData, Value};
 use std::cmp;
+use std::fs;
+use std::io;
 use std::sync::mpsc::sync_channel;
 use std::thread;
 use stx_genesis::GenesisData;
@@ -159,9 +164,17 @@ impl RunLoop {
 
         // setup dispatcher
         let mut event_dispatcher = EventDispatcher::new();
+        let event_observer_db_path = self.config.get_event_observer_db_path();
         for observer in self.config.events_observers.iter() {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher.register_observer(observer, &event_observer_db_path);
         }
+        if let Some(replay_height) = self.config.node.replay_events_from_height {
+            event_dispatcher.replay_from_height(replay_height);
+        }
+
+        // events streamed to RPC clients
+        let (subscriptions_tx, subscriptions_rx) = sync_channel(SUBSCRIPTION_CHANNEL_CAPACITY);
+        event_dispatcher.set_subscriptions(subscriptions_tx);
 
         let mut atlas_config = AtlasConfig::default(false);
         let genesis_attachments = GenesisData::new(USE_TEST_GENESIS_CHAINSTATE)
@@ -221,7 +234,35 @@ impl RunLoop {
             get_bulk_initial_names: Some(Box::new(|| get_names(USE_TEST_GENESIS_CHAINSTATE))),
         };
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        // a new chainstate starts from the configured snapshot instead of the boot block
+        if let Some((ref snapshot_path, ref snapshot_block)) = self.config.node.chainstate_snapshot
+        {
+            let mut file = io::BufReader::new(
+                fs::File::open(snapshot_path).expect("Failed to open chainstate snapshot"),
+            );
+            match StacksChainState::import_chainstate_snapshot(
+                mainnet,
+                chainid,
+                &chainstate_path,
+                snapshot_block,
+                &mut file,
+            ) {
+                Ok(header_info) => info!(
+                    "Started chainstate from the snapshot of {} at height {}",
+                    snapshot_block, header_info.block_height
+                ),
+                Err(ChainstateError::DBError(db_error::ExistsError)) => info!(
+                    "Chainstate already exists, so not importing snapshot {}",
+                    snapshot_path
+                ),
+                Err(e) => panic!(
+                    "Failed to import chainstate snapshot {}: {:?}",
+                    snapshot_path, e
+                ),
+            }
+        }
+
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             mainnet,
             chainid,
             &chainstate_path,
@@ -229,6 +270,8 @@ impl RunLoop {
             block_limit,
         )
         .unwrap();
+        chain_state_db.event_index = self.config.node.event_index;
+        chain_state_db.marf_prune_depth = self.config.node.marf_prune_depth;
         coordinator_dispatcher.dispatch_boot_receipts(receipts);
 
         let atlas_config = AtlasConfig::default(mainnet);
@@ -276,6 +319,7 @@ impl RunLoop {
                 coordinator_senders.clone(),
                 pox_watchdog.make_comms_handle(),
                 attachments_rx,
+                subscriptions_rx,
                 atlas_config,
             )
         } else {
@@ -285,6 +329,7 @@ impl RunLoop {
                 coordinator_senders.clone(),
                 pox_watchdog.make_comms_handle(),
                 attachments_rx,
+                subscriptions_rx,
                 atlas_config,
             )
         };
@@ -84,7 +84,7 @@ impl<'a> Tenure {
         )
         .unwrap();
 
-        let (anchored_block, _, _) = StacksBlockBuilder::build_anchored_block(
+        let (anchored_block, _, _) = StacksBlockBuilder::build_anchored_block_with_order(
             &mut chain_state,
             burn_dbconn,
             &mut self.mem_pool,
@@ -94,6 +94,7 @@ impl<'a> Tenure {
             self.microblock_pubkeyhash.clone(),
             &self.coinbase_tx,
             self.config.block_limit.clone(),
+            self.config.node.mempool_walk_order,
         )
         .unwrap();
 
@@ -86,10 +86,14 @@ mod test_observer {
     use std::convert::Infallible;
     use std::sync::Mutex;
     use std::thread;
+    use std::time::{Duration, Instant};
     use tokio;
     use warp;
     use warp::Filter;
 
+    use crate::event_queue::EventQueue;
+    use crate::Config;
+
     pub const EVENT_OBSERVER_PORT: u16 = 50303;
 
     lazy_static! {
@@ -153,6 +157,23 @@ mod test_observer {
         ATTACHMENTS.lock().unwrap().clone()
     }
 
+    /// Payloads are delivered to event observers in the background, so wait until the node
+    /// configured by `conf` has delivered every payload it has queued up for this observer.
+    pub fn wait_for_delivery(conf: &Config) {
+        let queue = EventQueue::open(
+            &conf.get_event_observer_db_path(),
+            &format!("localhost:{}", EVENT_OBSERVER_PORT),
+        )
+        .unwrap();
+        let start = Instant::now();
+        while queue.next_pending().unwrap().is_some() {
+            if start.elapsed() > Duration::from_secs(super::PANIC_TIMEOUT_SECS) {
+                panic!("Timed out waiting for the test observer to receive its payloads");
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
     async fn serve() {
         let new_blocks = warp::path!("new_block")
             .and(warp::post())
@@ -501,9 +522,11 @@ fn liquid_ustx_integration() {
 
     // clear and mine another burnchain block, so that the new winner is seen by the observer
     //   (the observer is logically "one block behind" the miner
+    test_observer::wait_for_delivery(&conf);
     test_observer::clear();
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
 
+    test_observer::wait_for_delivery(&conf);
     let mut blocks = test_observer::get_blocks();
     // should have produced 1 new block
     assert_eq!(blocks.len(), 1);
@@ -594,6 +617,7 @@ fn lockup_integration() {
     // block #1 should be unlocking STX
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
     assert_eq!(get_balance(&http_origin, &recipient), 13888888889);
+    test_observer::wait_for_delivery(&conf);
     let blocks = test_observer::get_blocks();
     let chain_tip = blocks.last().unwrap();
 
@@ -619,6 +643,7 @@ fn lockup_integration() {
     assert_eq!(get_balance(&http_origin, &recipient), 13888888889 * 3);
 
     // now let's ensure that the last block received by the event observer contains the lockup receipt
+    test_observer::wait_for_delivery(&conf);
     let blocks = test_observer::get_blocks();
     let chain_tip = blocks.last().unwrap();
 
@@ -1210,6 +1235,7 @@ fn microblock_integration_test() {
             .unwrap()
     );
 
+    test_observer::wait_for_delivery(&conf);
     let memtx_events = test_observer::get_memtxs();
     assert_eq!(memtx_events.len(), 1);
     assert_eq!(&memtx_events[0], &format!("0x{}", &bytes_to_hex(&tx)));
@@ -1664,9 +1690,11 @@ fn cost_voting_integration() {
 
     // clear and mine another burnchain block, so that the new winner is seen by the observer
     //   (the observer is logically "one block behind" the miner
+    test_observer::wait_for_delivery(&conf);
     test_observer::clear();
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
 
+    test_observer::wait_for_delivery(&conf);
     let mut blocks = test_observer::get_blocks();
     // should have produced 1 new block
     assert_eq!(blocks.len(), 1);
@@ -1714,9 +1742,11 @@ fn cost_voting_integration() {
 
     // clear and mine another burnchain block, so that the new winner is seen by the observer
     //   (the observer is logically "one block behind" the miner
+    test_observer::wait_for_delivery(&conf);
     test_observer::clear();
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
 
+    test_observer::wait_for_delivery(&conf);
     let mut blocks = test_observer::get_blocks();
     // should have produced 1 new block
     assert_eq!(blocks.len(), 1);
@@ -1763,9 +1793,11 @@ fn cost_voting_integration() {
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
     // clear and mine another burnchain block, so that the new winner is seen by the observer
     //   (the observer is logically "one block behind" the miner
+    test_observer::wait_for_delivery(&conf);
     test_observer::clear();
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
 
+    test_observer::wait_for_delivery(&conf);
     let mut blocks = test_observer::get_blocks();
     // should have produced 1 new block
     assert_eq!(blocks.len(), 1);
@@ -1807,9 +1839,11 @@ fn cost_voting_integration() {
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
     // clear and mine another burnchain block, so that the new winner is seen by the observer
     //   (the observer is logically "one block behind" the miner
+    test_observer::wait_for_delivery(&conf);
     test_observer::clear();
     next_block_and_wait(&mut btc_regtest_controller, &blocks_processed);
 
+    test_observer::wait_for_delivery(&conf);
     let mut blocks = test_observer::get_blocks();
     // should have produced 1 new block
     assert_eq!(blocks.len(), 1);
@@ -2094,6 +2128,7 @@ fn pox_integration_test() {
 
     let mut sort_height = channel.get_sortitions_processed();
     eprintln!("Sort height: {}", sort_height);
+    test_observer::wait_for_delivery(&conf);
     test_observer::clear();
 
     // now let's mine until the next reward cycle starts ...
@@ -2103,6 +2138,7 @@ fn pox_integration_test() {
         eprintln!("Sort height: {}", sort_height);
     }
 
+    test_observer::wait_for_delivery(&conf);
     let blocks_observed = test_observer::get_blocks();
     assert!(
         blocks_observed.len() >= 2,
@@ -2838,6 +2874,7 @@ fn atlas_integration_test() {
     }
 
     // Ensure that we the attached sidecar was able to receive a total of 10 attachments
+    test_observer::wait_for_delivery(&conf_follower_node);
     assert_eq!(test_observer::get_attachments().len(), 10);
     test_observer::clear();
     channel.stop_chains_coordinator();